@@ -0,0 +1,84 @@
+//! Minimal command line parsing: positionals, `--flag value` and `--switch`
+
+pub struct Args {
+    args: Vec<String>,
+}
+
+impl Args {
+    /// Arguments after the subcommand name
+    pub fn new(args: Vec<String>) -> Self {
+        Self { args }
+    }
+
+    /// Value following `flag`
+    pub fn value(&self, flag: &str) -> Option<&str> {
+        let i = self.args.iter().position(|a| a == flag)?;
+        self.args.get(i + 1).map(String::as_str)
+    }
+
+    /// Value following `flag`, parsed
+    pub fn parsed<T: std::str::FromStr>(&self, flag: &str) -> Result<Option<T>, String> {
+        match self.value(flag) {
+            Some(v) => v.parse().map(Some).map_err(|_| format!("Invalid value for {}: {}", flag, v)),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether a switch without value is present
+    pub fn has(&self, flag: &str) -> bool {
+        self.args.iter().any(|a| a == flag)
+    }
+
+    /// The n-th argument that is neither a flag nor a flag's value
+    pub fn positional(&self, n: usize) -> Option<&str> {
+        let mut skip_next = false;
+        self.args
+            .iter()
+            .filter(|a| {
+                if skip_next {
+                    skip_next = false;
+                    return false;
+                }
+                if a.starts_with("--") {
+                    // Switches are listed here; everything else takes a value
+                    skip_next = !SWITCHES.contains(&a.as_str());
+                    return false;
+                }
+                true
+            })
+            .nth(n)
+            .map(String::as_str)
+    }
+}
+
+/// Flags that take no value
+const SWITCHES: &[&str] = &[
+    "--remove-frames",
+    "--remove-sheet",
+    "--optimize",
+    "--lossy",
+    "--binarize",
+    "--feather",
+    "--dry-run",
+    "--clean",
+    "--pack",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Args {
+        Args::new(list.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_positional_skips_flag_values() {
+        let a = args(&["--state", "idle", "anim.gif", "--fps", "12", "out"]);
+        assert_eq!(a.positional(0), Some("anim.gif"));
+        assert_eq!(a.positional(1), Some("out"));
+        assert_eq!(a.value("--state"), Some("idle"));
+        assert_eq!(a.parsed::<f32>("--fps"), Ok(Some(12.0)));
+        assert_eq!(a.positional(2), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! `clean` - strip semi-transparent pixels out of frame PNGs
+//!
+//! The default hard cut clears everything that is not fully opaque.
+//! `--binarize` snaps alpha to 0/255 around the threshold instead, and
+//! `--feather` keeps antialiasing, only clearing faint noise and softening
+//! the outline by a pixel.
+
+use glob::glob;
+use image::{Rgba, RgbaImage};
+
+use crate::args::Args;
+
+const USAGE: &str =
+    "Usage: gassetsmaker clean <folder> [--threshold <0-255>] [--binarize | --feather] [--dry-run]";
+
+const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// Clear pixels with alpha below the threshold
+    HardCut,
+    /// Clear pixels below the threshold, make the rest fully opaque
+    Binarize,
+    /// Clear pixels below the threshold, then soften the outline
+    Feather,
+}
+
+impl Mode {
+    /// Alpha below which pixels are cleared, when `--threshold` is not given
+    fn default_threshold(self) -> u8 {
+        match self {
+            Mode::HardCut => 255,
+            Mode::Binarize => 128,
+            Mode::Feather => 16,
+        }
+    }
+}
+
+/// Pixels touched in one image
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Changes {
+    cleared: usize,
+    adjusted: usize,
+}
+
+impl Changes {
+    fn any(&self) -> bool {
+        self.cleared + self.adjusted > 0
+    }
+}
+
+/// How `clean` treats semi-transparent pixels
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+    mode: Mode,
+    threshold: u8,
+    dry_run: bool,
+}
+
+impl Settings {
+    /// Read `--threshold`, `--binarize`, `--feather` and `--dry-run`
+    pub fn from_args(args: &Args) -> Result<Self, String> {
+        let mode = match (args.has("--binarize"), args.has("--feather")) {
+            (false, false) => Mode::HardCut,
+            (true, false) => Mode::Binarize,
+            (false, true) => Mode::Feather,
+            (true, true) => return Err(USAGE.to_string()),
+        };
+        Ok(Self {
+            mode,
+            threshold: args.parsed::<u8>("--threshold")?.unwrap_or(mode.default_threshold()),
+            dry_run: args.has("--dry-run"),
+        })
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let folder = args.value("--path").or_else(|| args.positional(0)).ok_or(USAGE)?;
+    clean_folder(folder, &Settings::from_args(args)?)
+}
+
+/// Clean the PNGs directly inside `folder`
+pub fn clean_folder(folder: &str, settings: &Settings) -> Result<(), String> {
+    let Settings {
+        mode,
+        threshold,
+        dry_run,
+    } = *settings;
+
+    // Clean up path and add the glob pattern
+    let clean_path = folder.trim_end_matches('/');
+    let pattern = format!("{}/*.png", clean_path);
+
+    println!("Cleaning frames in: {} ({:?}, threshold {})", pattern, mode, threshold);
+
+    let mut total = Changes::default();
+    let mut files = 0;
+    for path in glob(&pattern).map_err(|e| e.to_string())?.flatten() {
+        let mut img = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?.into_rgba8();
+        let changes = clean_image(&mut img, mode, threshold);
+        if !changes.any() {
+            continue;
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if dry_run {
+            println!("Would fix {}: {} cleared, {} adjusted", name, changes.cleared, changes.adjusted);
+        } else {
+            img.save(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            println!("Fixed {}: {} cleared, {} adjusted", name, changes.cleared, changes.adjusted);
+        }
+        files += 1;
+        total.cleared += changes.cleared;
+        total.adjusted += changes.adjusted;
+    }
+
+    println!(
+        "{} {} files: {} pixels cleared, {} adjusted",
+        if dry_run { "Would change" } else { "Changed" },
+        files,
+        total.cleared,
+        total.adjusted
+    );
+    Ok(())
+}
+
+fn clean_image(img: &mut RgbaImage, mode: Mode, threshold: u8) -> Changes {
+    let mut changes = Changes::default();
+
+    for pixel in img.pixels_mut() {
+        if pixel[3] < threshold || pixel[3] == 0 {
+            // Fully transparent pixels are normalized too, so their color
+            // doesn't bleed in when scaled
+            if *pixel != CLEAR {
+                *pixel = CLEAR;
+                changes.cleared += 1;
+            }
+        } else if mode == Mode::Binarize && pixel[3] < 255 {
+            pixel[3] = 255;
+            changes.adjusted += 1;
+        }
+    }
+
+    if mode == Mode::Feather {
+        changes.adjusted += feather(img);
+    }
+    changes
+}
+
+/// Lower each visible pixel's alpha to the average over its 3x3
+/// neighbourhood, fading the outline without growing it
+fn feather(img: &mut RgbaImage) -> usize {
+    let source = img.clone();
+    let (width, height) = source.dimensions();
+    let mut adjusted = 0;
+
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let mut sum = 0u32;
+        for ny in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+            for nx in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                sum += source.get_pixel(nx, ny)[3] as u32;
+            }
+        }
+        // Pixels outside the image count as transparent
+        let average = (sum / 9) as u8;
+        if average < pixel[3] {
+            pixel[3] = average.max(1);
+            adjusted += 1;
+        }
+    }
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(alphas: &[u8]) -> RgbaImage {
+        let mut img = RgbaImage::new(alphas.len() as u32, 1);
+        for (pixel, &alpha) in img.pixels_mut().zip(alphas) {
+            *pixel = Rgba([200, 100, 50, alpha]);
+        }
+        img
+    }
+
+    fn alphas(img: &RgbaImage) -> Vec<u8> {
+        img.pixels().map(|p| p[3]).collect()
+    }
+
+    #[test]
+    fn test_hard_cut() {
+        let mut img = row(&[0, 40, 200, 255]);
+        let changes = clean_image(&mut img, Mode::HardCut, 255);
+        assert_eq!(alphas(&img), vec![0, 0, 0, 255]);
+        assert_eq!(changes, Changes { cleared: 3, adjusted: 0 });
+    }
+
+    #[test]
+    fn test_binarize() {
+        let mut img = row(&[40, 127, 128, 255]);
+        let changes = clean_image(&mut img, Mode::Binarize, 128);
+        assert_eq!(alphas(&img), vec![0, 0, 255, 255]);
+        assert_eq!(changes, Changes { cleared: 2, adjusted: 1 });
+    }
+
+    #[test]
+    fn test_feather_softens_edges_only() {
+        let mut img = RgbaImage::from_pixel(5, 5, Rgba([10, 10, 10, 255]));
+        img.put_pixel(0, 0, Rgba([10, 10, 10, 5]));
+        clean_image(&mut img, Mode::Feather, 16);
+        // Noise is cleared, the outline fades, the inside stays opaque
+        assert_eq!(img.get_pixel(0, 0)[3], 0);
+        assert!(img.get_pixel(4, 2)[3] < 255);
+        assert_eq!(img.get_pixel(2, 2)[3], 255);
+    }
+}
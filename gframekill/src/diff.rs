@@ -0,0 +1,166 @@
+//! `diff` - compare two personas (zips or directories) before publishing
+//!
+//! Reports manifest changes, added/removed/changed files with changed pixel
+//! counts for images, and the size difference.
+
+use image::RgbaImage;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::args::Args;
+use crate::manifest::Manifest;
+use crate::persona::PersonaFiles;
+
+const USAGE: &str = "Usage: gassetsmaker diff <old.persona.zip|dir> <new.persona.zip|dir>";
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let (old_path, new_path) = match (args.positional(0), args.positional(1)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Err(USAGE.to_string()),
+    };
+    let old = PersonaFiles::open(Path::new(old_path))?;
+    let new = PersonaFiles::open(Path::new(new_path))?;
+
+    println!("--- {}\n+++ {}", old_path, new_path);
+
+    println!("\nManifest:");
+    let changes = manifest_changes(&load_manifest(&old, old_path)?, &load_manifest(&new, new_path)?);
+    if changes.is_empty() {
+        println!("  unchanged");
+    }
+    for change in &changes {
+        println!("  {}", change);
+    }
+
+    println!("\nFiles:");
+    let paths: BTreeSet<&str> = old.files().chain(new.files()).map(|(p, _)| p).collect();
+    let mut unchanged = 0;
+    for path in paths {
+        match (old.get(path), new.get(path)) {
+            (Some(a), Some(b)) if a == b => unchanged += 1,
+            (Some(a), Some(b)) => println!("  ~ {} ({}){}", path, size_delta(a.len(), b.len()), image_change(path, a, b)),
+            (Some(a), None) => println!("  - {} ({} bytes)", path, a.len()),
+            (None, Some(b)) => println!("  + {} ({} bytes)", path, b.len()),
+            (None, None) => {}
+        }
+    }
+    println!("  {} unchanged", unchanged);
+
+    let total = |files: &PersonaFiles| files.files().map(|(_, d)| d.len()).sum::<usize>();
+    println!("\nSize: {}", size_delta(total(&old), total(&new)));
+    for (label, path) in [("old", old_path), ("new", new_path)] {
+        if let Ok(meta) = std::fs::metadata(path) {
+            if meta.is_file() {
+                println!("  {} archive: {} bytes", label, meta.len());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn load_manifest(files: &PersonaFiles, path: &str) -> Result<Manifest, String> {
+    match files.manifest() {
+        Some(manifest) => manifest.map_err(|e| format!("{}: {}", path, e)),
+        None => Ok(Manifest::default()),
+    }
+}
+
+/// Readable list of manifest differences
+fn manifest_changes(old: &Manifest, new: &Manifest) -> Vec<String> {
+    let mut changes = Vec::new();
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+
+    if old.name != new.name {
+        changes.push(format!("name: {} -> {}", show(&old.name), show(&new.name)));
+    }
+    if old.still != new.still {
+        changes.push(format!("still: {} -> {}", show(&old.still), show(&new.still)));
+    }
+    if old.size != new.size {
+        let size = |s: Option<[u32; 2]>| s.map_or("(none)".to_string(), |[w, h]| format!("{}x{}", w, h));
+        changes.push(format!("size: {} -> {}", size(old.size), size(new.size)));
+    }
+
+    let states: BTreeSet<&String> = old.states.keys().chain(new.states.keys()).collect();
+    for state in states {
+        match (old.states.get(state), new.states.get(state)) {
+            (Some(a), Some(b)) if a != b => changes.push(format!(
+                "state {}: {} frames @ {}fps -> {} frames @ {}fps",
+                state, a.frames, a.fps, b.frames, b.fps
+            )),
+            (Some(_), None) => changes.push(format!("state {} removed", state)),
+            (None, Some(b)) => changes.push(format!("state {} added ({} frames @ {}fps)", state, b.frames, b.fps)),
+            _ => {}
+        }
+    }
+    changes
+}
+
+/// `, N pixels differ` for changed images, empty for other files
+fn image_change(path: &str, old: &[u8], new: &[u8]) -> String {
+    if !path.ends_with(".png") {
+        return String::new();
+    }
+    let (old, new) = match (image::load_from_memory(old), image::load_from_memory(new)) {
+        (Ok(old), Ok(new)) => (old.into_rgba8(), new.into_rgba8()),
+        _ => return ", unreadable image".to_string(),
+    };
+    match pixel_diff(&old, &new) {
+        Some(0) => ", same pixels".to_string(),
+        Some(count) => format!(", {} pixels differ", count),
+        None => format!(", {}x{} -> {}x{}", old.width(), old.height(), new.width(), new.height()),
+    }
+}
+
+/// Number of differing pixels; None if the sizes differ
+fn pixel_diff(old: &RgbaImage, new: &RgbaImage) -> Option<usize> {
+    if old.dimensions() != new.dimensions() {
+        return None;
+    }
+    Some(old.pixels().zip(new.pixels()).filter(|(a, b)| a != b).count())
+}
+
+/// `old -> new bytes (+delta)`
+fn size_delta(old: usize, new: usize) -> String {
+    format!("{} -> {} bytes ({:+})", old, new, new as i64 - old as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::StateInfo;
+    use image::Rgba;
+
+    #[test]
+    fn test_pixel_diff() {
+        let a = RgbaImage::new(4, 4);
+        let mut b = a.clone();
+        b.put_pixel(1, 2, Rgba([1, 0, 0, 255]));
+        assert_eq!(pixel_diff(&a, &a), Some(0));
+        assert_eq!(pixel_diff(&a, &b), Some(1));
+        assert_eq!(pixel_diff(&a, &RgbaImage::new(4, 5)), None);
+    }
+
+    #[test]
+    fn test_manifest_changes() {
+        let mut old = Manifest::default();
+        old.states.insert("idle".to_string(), StateInfo::new(12.0, 10));
+        old.states.insert("sad".to_string(), StateInfo::new(12.0, 4));
+        let mut new = old.clone();
+        new.name = Some("rin".to_string());
+        new.states.get_mut("idle").unwrap().frames = 12;
+        new.states.remove("sad");
+        new.states.insert("happy".to_string(), StateInfo::new(10.0, 6));
+
+        assert_eq!(
+            manifest_changes(&old, &new),
+            vec![
+                "name: (none) -> rin",
+                "state happy added (6 frames @ 10fps)",
+                "state idle: 10 frames @ 12fps -> 12 frames @ 12fps",
+                "state sad removed",
+            ]
+        );
+        assert!(manifest_changes(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,60 @@
+//! Frame sequence folders - `<persona>/<state>/frame_%04d.png`
+
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+
+/// File name of the n-th frame (1-based, like ffmpeg's `frame_%04d.png`)
+pub fn frame_name(n: usize) -> String {
+    format!("frame_{:04}.png", n)
+}
+
+/// Prepare an empty state folder, removing frames left from an earlier import
+pub fn prepare_state_dir(persona_dir: &Path, state: &str) -> Result<PathBuf, String> {
+    let dir = persona_dir.join(state);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    remove_frames(&dir)?;
+    Ok(dir)
+}
+
+/// Delete the frames in a state folder
+pub fn remove_frames(dir: &Path) -> Result<usize, String> {
+    let frames = list_frames(dir)?;
+    for path in &frames {
+        std::fs::remove_file(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(frames.len())
+}
+
+/// `frame_*.png` files in a state folder, sorted by name
+pub fn list_frames(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let pattern = format!("{}/frame_*.png", dir.display());
+    let mut frames: Vec<PathBuf> = glob::glob(&pattern).map_err(|e| e.to_string())?.flatten().collect();
+    frames.sort();
+    Ok(frames)
+}
+
+/// State folders of a persona, sorted by name
+pub fn state_dirs(persona_dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = std::fs::read_dir(persona_dir).map_err(|e| format!("{}: {}", persona_dir.display(), e))?;
+    let mut dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Still images of a persona: the manifest's `still`, otherwise the PNGs
+/// next to the state folders
+pub fn still_images(persona_dir: &Path, still: Option<&str>) -> Result<Vec<PathBuf>, String> {
+    if let Some(still) = still {
+        return Ok(vec![persona_dir.join(still)]);
+    }
+    let pattern = format!("{}/*.png", persona_dir.display());
+    let mut stills: Vec<PathBuf> = glob::glob(&pattern).map_err(|e| e.to_string())?.flatten().collect();
+    stills.sort();
+    Ok(stills)
+}
+
+/// Write the n-th frame into a state folder
+pub fn save_frame(dir: &Path, n: usize, frame: &RgbaImage) -> Result<(), String> {
+    let path = dir.join(frame_name(n));
+    frame.save(&path).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,82 @@
+//! `from-gif` - decode an animated GIF into a state folder
+
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::{Manifest, StateInfo};
+
+/// Browsers play GIF frames without a delay at this speed
+const DEFAULT_DELAY_MS: f32 = 100.0;
+
+const USAGE: &str = "Usage: gassetsmaker from-gif <file.gif> --state <name> [--persona <dir>] [--fps <n>]";
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let input = args.positional(0).ok_or(USAGE)?;
+    let state = args.value("--state").ok_or(USAGE)?;
+    let persona_dir = Path::new(args.value("--persona").unwrap_or("."));
+
+    let file = File::open(input).map_err(|e| format!("{}: {}", input, e))?;
+    let decoder = GifDecoder::new(BufReader::new(file)).map_err(|e| format!("{}: {}", input, e))?;
+    let gif_frames = decoder
+        .into_frames()
+        .collect_frames()
+        .map_err(|e| format!("{}: {}", input, e))?;
+    if gif_frames.is_empty() {
+        return Err(format!("{}: no frames", input));
+    }
+
+    let dir = frames::prepare_state_dir(persona_dir, state)?;
+    let mut delays = Vec::with_capacity(gif_frames.len());
+    for (i, frame) in gif_frames.iter().enumerate() {
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        delays.push(numer as f32 / denom.max(1) as f32);
+        // Frames come out composited onto the full canvas
+        frames::save_frame(&dir, i + 1, frame.buffer())?;
+    }
+
+    let fps = match args.parsed::<f32>("--fps")? {
+        Some(fps) => fps,
+        None => suggested_fps(&delays),
+    };
+    println!("Wrote {} frames to {} (suggested fps: {})", gif_frames.len(), dir.display(), fps);
+
+    let mut manifest = Manifest::load(persona_dir)?;
+    manifest.set_state(
+        persona_dir,
+        state,
+        StateInfo::new(fps, gif_frames.len() as u32),
+    );
+    manifest.save(persona_dir)
+}
+
+/// Playback speed matching the average frame delay (in ms), rounded
+fn suggested_fps(delays: &[f32]) -> f32 {
+    if delays.is_empty() {
+        return 1000.0 / DEFAULT_DELAY_MS;
+    }
+    let total: f32 = delays
+        .iter()
+        .map(|&d| if d > 0.0 { d } else { DEFAULT_DELAY_MS })
+        .sum();
+    let average = total / delays.len() as f32;
+    (1000.0 / average).round().clamp(1.0, 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggested_fps() {
+        assert_eq!(suggested_fps(&[100.0, 100.0]), 10.0);
+        assert_eq!(suggested_fps(&[40.0, 40.0, 45.0]), 24.0);
+        // Missing delays play at the browser default
+        assert_eq!(suggested_fps(&[0.0, 0.0]), 10.0);
+        assert_eq!(suggested_fps(&[1.0]), 60.0);
+    }
+}
@@ -0,0 +1,125 @@
+//! `from-video` - extract a video's frames into a state folder with ffmpeg
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::{Manifest, StateInfo};
+
+const USAGE: &str = "Usage: gassetsmaker from-video <file> --state <name> [--persona <dir>] [--fps <n>] \
+                     [--key <rrggbb>] [--similarity <0-1>] [--blend <0-1>] [--max <px>]";
+
+const DEFAULT_FPS: f32 = 24.0;
+
+/// Largest frame side by default (the GPU limit is 2048)
+const DEFAULT_MAX_SIZE: u32 = 1024;
+
+/// Options for the ffmpeg filter chain
+struct Filter {
+    fps: f32,
+    /// Background color keyed to alpha (hex without `#`)
+    key: Option<String>,
+    similarity: f32,
+    blend: f32,
+    max_size: u32,
+}
+
+impl Filter {
+    /// ffmpeg `-vf` argument
+    fn to_ffmpeg(&self) -> String {
+        let mut filters = vec![format!("fps={}", self.fps)];
+        if let Some(ref key) = self.key {
+            filters.push(format!("chromakey=0x{}:{}:{}", key, self.similarity, self.blend));
+        }
+        filters.push("format=rgba".to_string());
+        filters.push(format!(
+            "scale=w='min({max},iw)':h='min({max},ih)':force_original_aspect_ratio=decrease",
+            max = self.max_size
+        ));
+        filters.join(",")
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let input = args.positional(0).ok_or(USAGE)?;
+    let state = args.value("--state").ok_or(USAGE)?;
+    let persona_dir = Path::new(args.value("--persona").unwrap_or("."));
+
+    let key = args.value("--key").map(parse_color).transpose()?;
+    let filter = Filter {
+        fps: args.parsed("--fps")?.unwrap_or(DEFAULT_FPS),
+        key,
+        similarity: args.parsed("--similarity")?.unwrap_or(0.1),
+        blend: args.parsed("--blend")?.unwrap_or(0.2),
+        max_size: args.parsed("--max")?.unwrap_or(DEFAULT_MAX_SIZE),
+    };
+
+    if !Path::new(input).is_file() {
+        return Err(format!("{}: file not found", input));
+    }
+
+    let dir = frames::prepare_state_dir(persona_dir, state)?;
+    let output = dir.join("frame_%04d.png");
+    let status = Command::new("ffmpeg")
+        .args(["-hide_banner", "-loglevel", "error", "-y", "-i", input, "-an"])
+        .args(["-vf", &filter.to_ffmpeg(), "-pix_fmt", "rgba"])
+        .arg(&output)
+        .status()
+        .map_err(|e| format!("Failed to run ffmpeg (is it installed?): {}", e))?;
+    if !status.success() {
+        return Err(format!("ffmpeg failed ({})", status));
+    }
+
+    let count = frames::list_frames(&dir)?.len();
+    if count == 0 {
+        return Err(format!("{}: no frames extracted", input));
+    }
+    println!("Wrote {} frames to {} at {} fps", count, dir.display(), filter.fps);
+
+    let mut manifest = Manifest::load(persona_dir)?;
+    manifest.set_state(
+        persona_dir,
+        state,
+        StateInfo::new(filter.fps, count as u32),
+    );
+    manifest.save(persona_dir)
+}
+
+/// Validate a `rrggbb` color (a leading `#` or `0x` is allowed)
+fn parse_color(value: &str) -> Result<String, String> {
+    let hex = value.trim_start_matches('#').trim_start_matches("0x");
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(hex.to_lowercase())
+    } else {
+        Err(format!("Invalid color: {} (expected rrggbb)", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#00FF00"), Ok("00ff00".to_string()));
+        assert_eq!(parse_color("0x274535"), Ok("274535".to_string()));
+        assert!(parse_color("green").is_err());
+    }
+
+    #[test]
+    fn test_filter_chain() {
+        let filter = Filter {
+            fps: 12.0,
+            key: Some("00ff00".to_string()),
+            similarity: 0.1,
+            blend: 0.2,
+            max_size: 512,
+        };
+        assert_eq!(
+            filter.to_ffmpeg(),
+            "fps=12,chromakey=0x00ff00:0.1:0.2,format=rgba,\
+             scale=w='min(512,iw)':h='min(512,ih)':force_original_aspect_ratio=decrease"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! `import` - convert Shimeji image sets and Ukagaka shells into personas
+//!
+//! Shimeji sets are `shime<N>.png` frames in the standard shimeji-ee
+//! numbering; their actions map to animation states. Ukagaka shells (a
+//! folder or `.nar` archive) have one `surface<N>.png` per expression,
+//! which become single-frame states. Both end up as a persona directory
+//! plus its `.persona.zip`.
+
+use image::{Rgba, RgbaImage};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::{Manifest, StateInfo};
+use crate::pack;
+use crate::persona::PersonaFiles;
+
+const USAGE: &str = "Usage: gassetsmaker import <shimeji_dir|shell_dir|shell.nar> [--format shimeji|ukagaka] [--persona <dir>] [--out <file.persona.zip>]";
+
+/// Shimeji actions as (state, frame numbers, fps)
+const SHIMEJI_STATES: &[(&str, &[u32], f32)] = &[
+    ("idle", &[1], 1.0),
+    ("walk", &[1, 2, 1, 3], 6.0),
+    ("fall", &[4], 1.0),
+    ("drag", &[5, 6, 7, 8, 9, 10], 8.0),
+    ("sit", &[11], 1.0),
+    ("thinking", &[26], 1.0),
+    ("sad", &[21], 1.0),
+];
+
+/// Ukagaka surface numbers of the main character as (state, surface)
+const UKAGAKA_STATES: &[(&str, u32)] = &[
+    ("idle", 0),
+    ("shy", 1),
+    ("surprised", 2),
+    ("worried", 3),
+    ("dejected", 4),
+    ("happy", 5),
+    ("thinking", 6),
+    ("angry", 7),
+    ("smug", 8),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Shimeji,
+    Ukagaka,
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let input = Path::new(args.positional(0).ok_or(USAGE)?);
+    let source = PersonaFiles::open(input)?;
+    let format = match args.value("--format") {
+        Some("shimeji") => Format::Shimeji,
+        Some("ukagaka") => Format::Ukagaka,
+        Some(_) => return Err(USAGE.to_string()),
+        None => detect(&source).ok_or("No shime1.png or surface0.png found; not a Shimeji set or Ukagaka shell")?,
+    };
+    let persona_dir = match args.value("--persona") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(input.file_stem().ok_or(USAGE)?),
+    };
+
+    // State name -> frames and fps
+    let states: Vec<(&str, Vec<RgbaImage>, f32)> = match format {
+        Format::Shimeji => {
+            let images = numbered_images(&source, "shime")?;
+            SHIMEJI_STATES
+                .iter()
+                .map(|(state, numbers, fps)| {
+                    let frames = numbers.iter().filter_map(|n| images.get(n).cloned()).collect();
+                    (*state, frames, *fps)
+                })
+                .collect()
+        }
+        Format::Ukagaka => {
+            let images = ukagaka_surfaces(&source)?;
+            UKAGAKA_STATES
+                .iter()
+                .map(|(state, n)| (*state, images.get(n).cloned().into_iter().collect(), 1.0))
+                .collect()
+        }
+    };
+
+    std::fs::create_dir_all(&persona_dir).map_err(|e| format!("{}: {}", persona_dir.display(), e))?;
+    let mut manifest = Manifest::load(&persona_dir)?;
+    let mut imported = Vec::new();
+    for (state, state_frames, fps) in states.iter().filter(|(_, f, _)| !f.is_empty()) {
+        let dir = frames::prepare_state_dir(&persona_dir, state)?;
+        for (i, frame) in state_frames.iter().enumerate() {
+            frames::save_frame(&dir, i + 1, frame)?;
+        }
+        manifest.set_state(
+            &persona_dir,
+            state,
+            StateInfo::new(*fps, state_frames.len() as u32),
+        );
+        imported.push(*state);
+    }
+    if imported.is_empty() {
+        return Err(format!("{}: no frames matched any state", input.display()));
+    }
+
+    // The idle pose doubles as the still image
+    if let Some((_, idle, _)) = states.iter().find(|(s, f, _)| *s == "idle" && !f.is_empty()) {
+        let still = persona_dir.join("still.png");
+        idle[0].save(&still).map_err(|e| format!("{}: {}", still.display(), e))?;
+        manifest.still = Some("still.png".to_string());
+    }
+    manifest.save(&persona_dir)?;
+
+    println!(
+        "Imported {:?} {} into {} (states: {})",
+        format,
+        input.display(),
+        persona_dir.display(),
+        imported.join(", ")
+    );
+    pack::write(&persona_dir, &pack::output_path(&persona_dir, args)?, None)
+}
+
+fn detect(source: &PersonaFiles) -> Option<Format> {
+    let names: Vec<&str> = source.files().map(|(p, _)| file_name(p)).collect();
+    if names.iter().any(|n| n.eq_ignore_ascii_case("shime1.png")) {
+        Some(Format::Shimeji)
+    } else if names.iter().any(|n| n.eq_ignore_ascii_case("surface0.png")) {
+        Some(Format::Ukagaka)
+    } else {
+        None
+    }
+}
+
+/// `<prefix><N>.png` images, from the folder holding `<prefix>1.png` or
+/// `<prefix>0.png`
+fn numbered_images(source: &PersonaFiles, prefix: &str) -> Result<BTreeMap<u32, RgbaImage>, String> {
+    let paths = numbered_paths(source, prefix, "png");
+    let mut images = BTreeMap::new();
+    for (n, path) in paths {
+        let data = source.get(&path).unwrap_or_default();
+        let image = image::load_from_memory(data).map_err(|e| format!("{}: {}", path, e))?;
+        images.insert(n, image.into_rgba8());
+    }
+    Ok(images)
+}
+
+fn numbered_paths(source: &PersonaFiles, prefix: &str, extension: &str) -> BTreeMap<u32, String> {
+    let all: Vec<(u32, &str)> = source
+        .files()
+        .filter_map(|(p, _)| number_in(file_name(p), prefix, extension).map(|n| (n, p)))
+        .collect();
+    // Only one folder: shells may carry several characters or variants
+    let folder = match all.iter().min_by_key(|(n, _)| *n) {
+        Some((_, path)) => parent(path),
+        None => return BTreeMap::new(),
+    };
+    all.into_iter()
+        .filter(|(_, p)| parent(p) == folder)
+        .map(|(n, p)| (n, p.to_string()))
+        .collect()
+}
+
+/// Ukagaka surfaces with transparency applied: `surface<N>.pna` alpha masks
+/// when present, otherwise the top-left pixel's color is keyed out
+fn ukagaka_surfaces(source: &PersonaFiles) -> Result<BTreeMap<u32, RgbaImage>, String> {
+    let masks = numbered_paths(source, "surface", "pna");
+    let mut surfaces = numbered_images(source, "surface")?;
+    for (n, surface) in surfaces.iter_mut() {
+        let mask = masks
+            .get(n)
+            .and_then(|p| source.get(p))
+            .and_then(|data| image::load_from_memory_with_format(data, image::ImageFormat::Png).ok());
+        match mask {
+            Some(mask) => apply_mask(surface, &mask.into_luma8()),
+            None => key_out_corner(surface),
+        }
+    }
+    Ok(surfaces)
+}
+
+fn apply_mask(image: &mut RgbaImage, mask: &image::GrayImage) {
+    if mask.dimensions() != image.dimensions() {
+        return;
+    }
+    for (pixel, alpha) in image.pixels_mut().zip(mask.pixels()) {
+        pixel[3] = alpha[0];
+    }
+}
+
+/// Make every pixel with the top-left pixel's color transparent
+fn key_out_corner(image: &mut RgbaImage) {
+    let key = *image.get_pixel(0, 0);
+    if key[3] < 255 {
+        // Already has transparency
+        return;
+    }
+    for pixel in image.pixels_mut() {
+        if *pixel == key {
+            *pixel = Rgba([0, 0, 0, 0]);
+        }
+    }
+}
+
+/// N in `<prefix><N>.<extension>` (case-insensitive)
+fn number_in(name: &str, prefix: &str, extension: &str) -> Option<u32> {
+    let name = name.to_ascii_lowercase();
+    let stem = name.strip_suffix(&format!(".{}", extension))?;
+    stem.strip_prefix(prefix)?.parse().ok()
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_in() {
+        assert_eq!(number_in("shime12.png", "shime", "png"), Some(12));
+        assert_eq!(number_in("Surface0.PNG", "surface", "png"), Some(0));
+        assert_eq!(number_in("surface0.pna", "surface", "png"), None);
+        assert_eq!(number_in("shime.png", "shime", "png"), None);
+    }
+
+    #[test]
+    fn test_numbered_paths_single_folder() {
+        let mut source = PersonaFiles::default();
+        for path in ["img/A/shime1.png", "img/A/shime2.png", "img/B/shime1.png", "img/B/shime3.png"] {
+            source.insert(path, Vec::new());
+        }
+        let paths = numbered_paths(&source, "shime", "png");
+        assert_eq!(paths.values().collect::<Vec<_>>(), vec!["img/A/shime1.png", "img/A/shime2.png"]);
+    }
+
+    #[test]
+    fn test_key_out_corner() {
+        let mut image = RgbaImage::from_pixel(3, 1, Rgba([0, 255, 0, 255]));
+        image.put_pixel(1, 0, Rgba([200, 10, 10, 255]));
+        key_out_corner(&mut image);
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(image.get_pixel(1, 0)[3], 255);
+        assert_eq!(image.get_pixel(2, 0)[3], 0);
+    }
+}
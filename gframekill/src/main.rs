@@ -1,50 +1,129 @@
-use image::{GenericImage, Rgba};
-use glob::glob;
+//! gassetsmaker - tools for preparing persona frame sequences
+//!
+//! ```text
+//! gassetsmaker new <name>                              scaffold an empty persona
+//! gassetsmaker clean <folder>                          hard-cut semi-transparent pixels
+//! gassetsmaker from-gif <file.gif> --state <name>      import an animated GIF
+//! gassetsmaker from-video <file> --state <name>        extract video frames with ffmpeg
+//! gassetsmaker import <shimeji_dir|shell.nar>          convert Shimeji / Ukagaka characters
+//! gassetsmaker spritesheet pack|slice <state_dir>      convert frames <-> sprite sheet
+//! gassetsmaker slice <sheet.png> --rows --cols|--atlas  cut an artist's sheet into states
+//! gassetsmaker resize <persona_dir> --scale <f>        scale still image and frames
+//! gassetsmaker trim <persona_dir>                      crop transparent margins off all frames
+//! gassetsmaker preview <persona_dir|zip>               animated GIF preview, thumbnail, contact sheet
+//! gassetsmaker validate <persona_dir|zip>              check a persona before publishing
+//! gassetsmaker optimize <persona_dir>                  resize and re-encode PNGs smaller in place
+//! gassetsmaker pack <persona_dir>                      bundle into a .persona.zip
+//! gassetsmaker diff <old> <new>                        compare two personas before publishing
+//! gassetsmaker sign <persona> --key <keyfile>          sign a persona zip or folder
+//! gassetsmaker verify <persona>                        check a persona's signature
+//! gassetsmaker watch <persona_dir>                     re-run clean/validate/pack on changes
+//! ```
+
+mod args;
+mod clean;
+mod diff;
+mod frames;
+mod from_gif;
+mod from_video;
+mod import;
+mod manifest;
+mod new;
+mod optimize;
+mod pack;
+mod persona;
+mod preview;
+mod resize;
+mod sign;
+mod slice;
+mod spritesheet;
+mod trim;
+mod validate;
+mod watch;
+
+use args::Args;
 use std::env;
 
+const USAGE: &str = "Usage: gassetsmaker <command> [args]
+
+Commands:
+  new <name>                         Create a persona skeleton: config.toml, state
+           [--size <w>x<h>] [--fps <n>]   folders, placeholder still image, README
+  clean <folder>                     Remove semi-transparent pixels from frame PNGs
+           [--threshold <0-255>] [--binarize | --feather] [--dry-run]
+  from-gif <file.gif> --state <name> Decode a GIF into <persona>/<state>/frame_%04d.png
+           [--persona <dir>] [--fps <n>]
+  from-video <file> --state <name>   Extract video frames with ffmpeg (--key keys a
+           [--persona <dir>] [--fps <n>] background color to alpha)
+           [--key <rrggbb>] [--similarity <0-1>] [--blend <0-1>] [--max <px>]
+  import <shimeji_dir|shell.nar>     Convert a Shimeji image set or Ukagaka shell into a
+           [--format shimeji|ukagaka]   persona folder and .persona.zip
+           [--persona <dir>] [--out <file.persona.zip>]
+  spritesheet pack <state_dir>       Pack frames into sheet.png + sheet.json
+           [--columns <n>] [--fps <n>] [--remove-frames]
+  spritesheet slice <state_dir>      Slice sheet.png back into frame_%04d.png
+           [--remove-sheet]
+  slice <sheet.png>                  Cut a sprite sheet into <persona>/<state>/ frame folders:
+           --rows <n> --cols <n> (--state <name> | --states <a,b,...>)   a grid, one
+                                     state per row or the whole sheet as one state
+           --atlas <file.json>       or a TexturePacker/Aseprite atlas (states from tags
+                                     or frame names)
+           [--persona <dir>] [--fps <n>]
+  resize <persona_dir>               Scale the still image and all frames alike
+           (--scale <factor> | --max <px>)
+  trim <persona_dir>                 Crop the transparent margin shared by all frames
+                                     (records the new size in config.toml)
+  preview <persona_dir|zip>          Write preview.gif, thumbnail.png and contact.png of a state
+           [--state <name>] [--size <px>] [--fps <n>] [--out <dir>]
+  validate <persona_dir|zip>         Check manifest keys, frames, sizes, still image and
+                                     total size; prints a JSON report, exits 1 on errors
+  optimize <persona_dir>             Re-encode PNGs in place (palette when <= 256 colors),
+           [--level <0-9>] [--lossy] [--colors <n>]   reporting zip size before/after;
+           [--max <px> | --scale <f>]   --max/--scale resize all images first
+  pack <persona_dir>                 Write <name>.persona.zip next to the folder
+           [--out <file>] [--optimize] [--lossy] [--colors <n>] [--level <0-9>]
+  diff <old> <new>                   Compare two persona zips/folders: manifest, added,
+                                     removed and changed frames, size
+  sign <persona.zip|dir> --key <f>   Embed an ed25519 signature (signature.json); a new
+                                     key is generated (with <f>.pub) if <f> doesn't exist
+  verify <persona.zip|dir>           Check the signature, optionally against the signer's
+           [--fingerprint <hex>]     key fingerprint; exits 1 if invalid
+  watch <persona_dir>                Validate on every change; --clean cleans changed
+           [--clean [clean options]] [--pack [pack options]]   folders, --pack repacks the zip";
+
 fn main() {
-    // Collect command line arguments: [executable, --path, folder/path/]
-    let args: Vec<String> = env::args().collect();
-    
-    // Find the index of "--path" and get the value after it
-    let path_arg = args.iter().position(|r| r == "--path")
-        .and_then(|i| args.get(i + 1));
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_default();
+    let rest: Vec<String> = args.collect();
 
-    let folder = match path_arg {
-        Some(p) => p,
-        None => {
-            println!("Usage: cargo run --path ../ghost/assets/your/folder/");
+    let result = match command.as_str() {
+        "new" => new::run(&Args::new(rest)),
+        "clean" => clean::run(&Args::new(rest)),
+        "from-gif" => from_gif::run(&Args::new(rest)),
+        "from-video" => from_video::run(&Args::new(rest)),
+        "import" => import::run(&Args::new(rest)),
+        "spritesheet" => spritesheet::run(&Args::new(rest)),
+        "slice" => slice::run(&Args::new(rest)),
+        "resize" => resize::run(&Args::new(rest)),
+        "trim" => trim::run(&Args::new(rest)),
+        "preview" => preview::run(&Args::new(rest)),
+        "validate" => validate::run(&Args::new(rest)),
+        "optimize" => optimize::run(&Args::new(rest)),
+        "pack" => pack::run(&Args::new(rest)),
+        "diff" => diff::run(&Args::new(rest)),
+        "sign" => sign::run_sign(&Args::new(rest)),
+        "verify" => sign::run_verify(&Args::new(rest)),
+        "watch" => watch::run(&Args::new(rest)),
+        // Old invocation: `pixelfix --path <folder>`
+        "--path" => clean::run(&Args::new([vec![command], rest].concat())),
+        _ => {
+            println!("{}", USAGE);
             return;
         }
     };
 
-    // Clean up path and add the glob pattern
-    let clean_path = folder.trim_end_matches('/');
-    let pattern = format!("{}/*.png", clean_path);
-
-    println!("Cleaning frames in: {}", pattern);
-
-    for entry in glob(&pattern).expect("Failed to read glob pattern") {
-        if let Ok(path) = entry {
-            let mut img = image::open(&path).expect("Failed to load").into_rgba8();
-            let (width, height) = img.dimensions();
-            let mut changed = false;
-
-            for x in 0..width {
-                for y in 0..height {
-                    let pixel = img.get_pixel(x, y);
-                    // The "Hard Cut" logic you liked
-                    if pixel.0[3] < 255 {
-                        img.put_pixel(x, y, Rgba([0, 0, 0, 0]));
-                        changed = true;
-                    }
-                }
-            }
-
-            if changed {
-                img.save(&path).unwrap();
-                println!("Fixed: {:?}", path.file_name().unwrap());
-            }
-        }
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,154 @@
+//! Persona `config.toml` - the still image and per-state frame info
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the persona manifest
+pub const MANIFEST_FILE: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Still image shown when animation is off (relative to the persona dir)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub still: Option<String>,
+    /// Frame size in pixels [width, height], recorded by `trim`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<[u32; 2]>,
+    /// Animation states by folder name
+    #[serde(default)]
+    pub states: BTreeMap<String, StateInfo>,
+    /// Sections read by the ghost itself (e.g. `[state_machine]`), kept as is
+    #[serde(flatten)]
+    pub other: BTreeMap<String, toml::Value>,
+}
+
+/// Play modes the ghost understands for a state's `mode`
+pub const PLAY_MODES: [&str; 4] = ["loop", "once", "once-and-hide", "ping-pong"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateInfo {
+    /// Suggested playback speed
+    pub fps: f32,
+    /// Number of frames in the state folder
+    pub frames: u32,
+    /// How the state plays, one of `PLAY_MODES` (the ghost loops by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Sound file played when the state starts (relative to the persona dir)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    /// State to switch to when a one-shot state finishes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    /// Keys this tool doesn't know (e.g. newer ghost settings), kept as is
+    #[serde(flatten)]
+    pub other: BTreeMap<String, toml::Value>,
+}
+
+impl StateInfo {
+    pub fn new(fps: f32, frames: u32) -> Self {
+        Self {
+            fps,
+            frames,
+            mode: None,
+            sound: None,
+            next: None,
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+impl Manifest {
+    /// Load the manifest from a persona directory (empty if missing)
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Write the manifest into a persona directory
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(MANIFEST_FILE);
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Record a state's frames, naming the persona after its folder if unnamed;
+    /// a re-exported state keeps its mode, sound, next state and other keys
+    pub fn set_state(&mut self, dir: &Path, state: &str, mut info: StateInfo) {
+        if self.name.is_none() {
+            self.name = dir
+                .canonicalize()
+                .ok()
+                .and_then(|d| d.file_name().map(|n| n.to_string_lossy().into_owned()));
+        }
+        if let Some(old) = self.states.remove(state) {
+            info.mode = info.mode.or(old.mode);
+            info.sound = info.sound.or(old.sound);
+            info.next = info.next.or(old.next);
+            for (key, value) in old.other {
+                info.other.entry(key).or_insert(value);
+            }
+        }
+        self.states.insert(state.to_string(), info);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_other_sections() {
+        let text = r#"
+name = "rin"
+
+[states.idle]
+fps = 12.0
+frames = 4
+
+[states.wave]
+fps = 8.0
+frames = 6
+mode = "once"
+sound = "sounds/wave.ogg"
+next = "idle"
+loop_start = 2
+
+[state_machine]
+transitions = [{ from = "idle", to = "wave", on = "click" }]
+"#;
+        let manifest: Manifest = toml::from_str(text).unwrap();
+        assert!(manifest.other.contains_key("state_machine"));
+
+        let saved: Manifest = toml::from_str(&toml::to_string_pretty(&manifest).unwrap()).unwrap();
+        assert_eq!(saved.other, manifest.other);
+        assert_eq!(saved.states, manifest.states);
+        assert_eq!(saved.states["wave"].next.as_deref(), Some("idle"));
+        assert_eq!(saved.states["idle"].mode, None);
+        assert_eq!(saved.states["wave"].other["loop_start"].as_integer(), Some(2));
+    }
+
+    #[test]
+    fn test_set_state_keeps_hints() {
+        let mut manifest = Manifest::default();
+        let mut wave = StateInfo::new(8.0, 6);
+        wave.mode = Some("once".to_string());
+        wave.next = Some("idle".to_string());
+        wave.other.insert("loop_start".to_string(), toml::Value::Integer(2));
+        manifest.set_state(Path::new("."), "wave", wave);
+
+        manifest.set_state(Path::new("."), "wave", StateInfo::new(12.0, 10));
+        let wave = &manifest.states["wave"];
+        assert_eq!((wave.fps, wave.frames), (12.0, 10));
+        assert_eq!(wave.mode.as_deref(), Some("once"));
+        assert_eq!(wave.next.as_deref(), Some("idle"));
+        assert!(wave.other.contains_key("loop_start"));
+    }
+}
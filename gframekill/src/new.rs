@@ -0,0 +1,131 @@
+//! `new` - scaffold an empty persona directory
+//!
+//! Creates `config.toml` listing the states ghost-ui knows, one empty folder
+//! per state, a placeholder still image and a short README for the author.
+
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+use crate::args::Args;
+use crate::manifest::{Manifest, StateInfo};
+
+const USAGE: &str = "Usage: gassetsmaker new <name> [--size <width>x<height>] [--fps <n>]";
+
+/// States ghost-ui maps to its built-in animation states
+const STANDARD_STATES: &[&str] = &["idle", "talk", "thinking", "happy", "sad"];
+
+const STILL_IMAGE: &str = "still.png";
+
+const DEFAULT_SIZE: (u32, u32) = (288, 512);
+
+const README: &str = "# {name}
+
+A ghost persona. Each folder is an animation state holding
+`frame_0001.png`, `frame_0002.png`, ... (all frames the same size,
+transparent background). `{still}` is shown when animation is off.
+
+Fill a state from existing art:
+
+    gassetsmaker from-gif wave.gif --state idle --persona {name}
+    gassetsmaker from-video talk.mp4 --state talk --persona {name} --key 00ff00
+
+or export frames into the folder yourself and set `frames` in config.toml.
+Remove states you don't need, or add folders for custom states.
+
+When done:
+
+    gassetsmaker validate {name}
+    gassetsmaker pack {name}
+";
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()));
+    }
+    let (width, height) = match args.value("--size") {
+        Some(size) => parse_size(size).ok_or_else(|| format!("Invalid value for --size: {}", size))?,
+        None => DEFAULT_SIZE,
+    };
+    let fps = args.parsed::<f32>("--fps")?.unwrap_or(12.0);
+    let name = dir.file_name().ok_or(USAGE)?.to_string_lossy().into_owned();
+
+    for state in STANDARD_STATES {
+        let state_dir = dir.join(state);
+        std::fs::create_dir_all(&state_dir).map_err(|e| format!("{}: {}", state_dir.display(), e))?;
+    }
+
+    let manifest = Manifest {
+        name: Some(name.clone()),
+        still: Some(STILL_IMAGE.to_string()),
+        size: None,
+        states: STANDARD_STATES
+            .iter()
+            .map(|state| (state.to_string(), StateInfo::new(fps, 0)))
+            .collect(),
+        ..Default::default()
+    };
+    manifest.save(dir)?;
+
+    let still = dir.join(STILL_IMAGE);
+    placeholder(width, height)
+        .save(&still)
+        .map_err(|e| format!("{}: {}", still.display(), e))?;
+
+    let readme = dir.join("README.md");
+    let text = README.replace("{name}", &name).replace("{still}", STILL_IMAGE);
+    std::fs::write(&readme, text).map_err(|e| format!("{}: {}", readme.display(), e))?;
+
+    println!(
+        "Created {} with states {} ({}x{} placeholder still)",
+        dir.display(),
+        STANDARD_STATES.join(", "),
+        width,
+        height
+    );
+    Ok(())
+}
+
+/// `WIDTHxHEIGHT`
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    let size = (width.parse().ok()?, height.parse().ok()?);
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+/// Grey head-and-shoulders silhouette on a transparent background
+fn placeholder(width: u32, height: u32) -> RgbaImage {
+    let (w, h) = (width as f32, height as f32);
+    let head = (w / 2.0, h * 0.3, w.min(h) * 0.2);
+    let body = (w / 2.0, h * 0.85, w * 0.4, h * 0.35);
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+        let in_head = (x - head.0).powi(2) + (y - head.1).powi(2) <= head.2.powi(2);
+        let in_body = ((x - body.0) / body.2).powi(2) + ((y - body.1) / body.3).powi(2) <= 1.0;
+        if in_head || in_body {
+            Rgba([160, 160, 170, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("288x512"), Some((288, 512)));
+        assert_eq!(parse_size("0x512"), None);
+        assert_eq!(parse_size("288"), None);
+    }
+
+    #[test]
+    fn test_placeholder() {
+        let img = placeholder(100, 200);
+        assert_eq!(img.get_pixel(50, 60)[3], 255);
+        assert_eq!(img.get_pixel(0, 0)[3], 0);
+    }
+}
@@ -0,0 +1,242 @@
+//! `optimize` - re-encode a persona's PNGs smaller
+//!
+//! With `--max` or `--scale` all images are first resized alike (see
+//! `resize`), for art exported far larger than the ghost shows it.
+//! Images with at most 256 colors become palette PNGs (alpha kept in tRNS);
+//! `--lossy` quantizes the rest down to a palette with NeuQuant. Files are
+//! only replaced when the result is smaller.
+
+use color_quant::NeuQuant;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::args::Args;
+use crate::pack;
+use crate::resize;
+use crate::persona::PersonaFiles;
+
+const USAGE: &str =
+    "Usage: gassetsmaker optimize <persona_dir> [--max <px> | --scale <factor>] [--level <0-9>] [--lossy] [--colors <2-256>]";
+
+/// NeuQuant sampling factor (1 is best quality, 30 fastest)
+const QUANT_SAMPLE_FACTOR: i32 = 10;
+
+/// PNG encoding options
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// zlib effort, 0-9
+    pub level: u8,
+    /// Quantize images with more than 256 colors
+    pub lossy: bool,
+    /// Palette size for lossy quantization
+    pub colors: usize,
+}
+
+impl Options {
+    /// Read `--level`, `--lossy` and `--colors`
+    pub fn from_args(args: &Args) -> Result<Self, String> {
+        let level = args.parsed::<u8>("--level")?.unwrap_or(9);
+        let colors = args.parsed::<usize>("--colors")?.unwrap_or(256);
+        if level > 9 || !(2..=256).contains(&colors) {
+            return Err(USAGE.to_string());
+        }
+        Ok(Self {
+            level,
+            lossy: args.has("--lossy"),
+            colors,
+        })
+    }
+
+    fn compression(&self) -> png::Compression {
+        match self.level {
+            0..=3 => png::Compression::Fast,
+            4..=6 => png::Compression::Default,
+            _ => png::Compression::Best,
+        }
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let persona_dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    if !persona_dir.is_dir() {
+        return Err(USAGE.to_string());
+    }
+    let options = Options::from_args(args)?;
+
+    let before = pack::zip_persona(&PersonaFiles::open(persona_dir)?, None)?.len();
+    if args.value("--max").is_some() || args.value("--scale").is_some() {
+        resize::run(args)?;
+    }
+
+    let files = PersonaFiles::open(persona_dir)?;
+    let mut optimized = 0;
+    for (path, data) in files.files().filter(|(p, _)| p.ends_with(".png")) {
+        if let Some(smaller) = optimize_png(data, &options)? {
+            let file = persona_dir.join(path);
+            std::fs::write(&file, smaller).map_err(|e| format!("{}: {}", file.display(), e))?;
+            optimized += 1;
+        }
+    }
+    let after = pack::zip_persona(&PersonaFiles::open(persona_dir)?, None)?.len();
+
+    println!("Optimized {} images", optimized);
+    print_sizes(before, after);
+    Ok(())
+}
+
+/// Print before/after sizes with the saving
+pub fn print_sizes(before: usize, after: usize) {
+    let saved = before.saturating_sub(after);
+    println!(
+        "Zip size: {} KB -> {} KB ({:.1}% smaller)",
+        before / 1024,
+        after / 1024,
+        saved as f64 * 100.0 / before.max(1) as f64
+    );
+}
+
+/// Re-encode a PNG; None if that doesn't make it smaller
+pub fn optimize_png(data: &[u8], options: &Options) -> Result<Option<Vec<u8>>, String> {
+    let image = image::load_from_memory(data).map_err(|e| e.to_string())?.into_rgba8();
+    let (width, height) = image.dimensions();
+    let pixels = image.as_raw();
+
+    let encoded = match exact_palette(pixels) {
+        Some((palette, indices)) => encode_indexed(width, height, &palette, &indices, options)?,
+        None if options.lossy => {
+            let (palette, indices) = quantize(pixels, options.colors);
+            encode_indexed(width, height, &palette, &indices, options)?
+        }
+        None if pixels.chunks_exact(4).all(|p| p[3] == 255) => {
+            let rgb: Vec<u8> = pixels.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect();
+            encode(width, height, png::ColorType::Rgb, &rgb, options, None)?
+        }
+        None => encode(width, height, png::ColorType::Rgba, pixels, options, None)?,
+    };
+
+    Ok((encoded.len() < data.len()).then_some(encoded))
+}
+
+/// Palette (RGBA) and indices if the image has at most 256 colors
+fn exact_palette(pixels: &[u8]) -> Option<(Vec<[u8; 4]>, Vec<u8>)> {
+    let mut palette: Vec<[u8; 4]> = Vec::new();
+    let mut lookup: HashMap<[u8; 4], u8> = HashMap::new();
+    let mut indices = Vec::with_capacity(pixels.len() / 4);
+    for p in pixels.chunks_exact(4) {
+        // All fully transparent pixels share one entry
+        let color = if p[3] == 0 { [0, 0, 0, 0] } else { [p[0], p[1], p[2], p[3]] };
+        let index = match lookup.get(&color) {
+            Some(&index) => index,
+            None => {
+                if palette.len() == 256 {
+                    return None;
+                }
+                palette.push(color);
+                let index = (palette.len() - 1) as u8;
+                lookup.insert(color, index);
+                index
+            }
+        };
+        indices.push(index);
+    }
+    Some((palette, indices))
+}
+
+/// Reduce to `colors` palette entries with NeuQuant
+fn quantize(pixels: &[u8], colors: usize) -> (Vec<[u8; 4]>, Vec<u8>) {
+    let quant = NeuQuant::new(QUANT_SAMPLE_FACTOR, colors, pixels);
+    let palette = quant
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|c| [c[0], c[1], c[2], c[3]])
+        .collect();
+    let indices = pixels.chunks_exact(4).map(|p| quant.index_of(p) as u8).collect();
+    (palette, indices)
+}
+
+fn encode_indexed(
+    width: u32,
+    height: u32,
+    palette: &[[u8; 4]],
+    indices: &[u8],
+    options: &Options,
+) -> Result<Vec<u8>, String> {
+    let rgb: Vec<u8> = palette.iter().flat_map(|c| [c[0], c[1], c[2]]).collect();
+    let mut trns: Vec<u8> = palette.iter().map(|c| c[3]).collect();
+    // Trailing opaque entries can be left out of tRNS
+    while trns.last() == Some(&255) {
+        trns.pop();
+    }
+    encode(width, height, png::ColorType::Indexed, indices, options, Some((rgb, trns)))
+}
+
+fn encode(
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    data: &[u8],
+    options: &Options,
+    palette: Option<(Vec<u8>, Vec<u8>)>,
+) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_compression(options.compression());
+        encoder.set_adaptive_filter(png::AdaptiveFilterType::Adaptive);
+        if let Some((rgb, trns)) = palette {
+            encoder.set_palette(rgb);
+            if !trns.is_empty() {
+                encoder.set_trns(trns);
+            }
+        }
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(data).map_err(|e| e.to_string())?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_palette_merges_transparent_pixels() {
+        let pixels = [
+            10, 20, 30, 255, //
+            1, 2, 3, 0, //
+            10, 20, 30, 255, //
+            9, 9, 9, 0,
+        ];
+        let (palette, indices) = exact_palette(&pixels).unwrap();
+        assert_eq!(palette, vec![[10, 20, 30, 255], [0, 0, 0, 0]]);
+        assert_eq!(indices, vec![0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_exact_palette_too_many_colors() {
+        let pixels: Vec<u8> = (0..300u32).flat_map(|i| [(i % 256) as u8, (i / 256) as u8, 0, 255]).collect();
+        assert!(exact_palette(&pixels).is_none());
+    }
+
+    #[test]
+    fn test_indexed_round_trip() {
+        let image = image::RgbaImage::from_fn(4, 4, |x, y| image::Rgba([x as u8 * 60, y as u8 * 60, 0, (x * 60) as u8]));
+        let options = Options {
+            level: 9,
+            lossy: false,
+            colors: 256,
+        };
+        let (palette, indices) = exact_palette(image.as_raw()).unwrap();
+        let encoded = encode_indexed(4, 4, &palette, &indices, &options).unwrap();
+        let decoded = image::load_from_memory(&encoded).unwrap().into_rgba8();
+        // Only fully transparent pixels may change color
+        for (a, b) in image.pixels().zip(decoded.pixels()) {
+            assert_eq!(a[3], b[3]);
+            if a[3] > 0 {
+                assert_eq!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! `pack` - bundle a persona directory into a `.persona.zip`
+//!
+//! PNGs are stored as-is (they are already compressed); everything else is
+//! deflated. `--optimize` re-encodes the PNGs in the archive only, leaving
+//! the source directory untouched.
+
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::args::Args;
+use crate::optimize::{self, Options};
+use crate::persona::PersonaFiles;
+
+const USAGE: &str =
+    "Usage: gassetsmaker pack <persona_dir> [--out <file.persona.zip>] [--optimize] [--lossy] [--colors <n>] [--level <0-9>]";
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let persona_dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    if !persona_dir.is_dir() {
+        return Err(USAGE.to_string());
+    }
+    let out = output_path(persona_dir, args)?;
+    let optimize = if args.has("--optimize") {
+        Some(Options::from_args(args)?)
+    } else {
+        None
+    };
+    write(persona_dir, &out, optimize.as_ref())
+}
+
+/// `--out`, or `<name>.persona.zip` next to the persona directory
+pub fn output_path(persona_dir: &Path, args: &Args) -> Result<PathBuf, String> {
+    if let Some(out) = args.value("--out") {
+        return Ok(PathBuf::from(out));
+    }
+    let dir = persona_dir.canonicalize().map_err(|e| format!("{}: {}", persona_dir.display(), e))?;
+    let name = dir.file_name().ok_or(USAGE)?.to_string_lossy();
+    Ok(dir.with_file_name(format!("{}.persona.zip", name)))
+}
+
+/// Zip a persona directory into `out`
+pub fn write(persona_dir: &Path, out: &Path, optimize: Option<&Options>) -> Result<(), String> {
+    let files = PersonaFiles::open(persona_dir)?;
+    let zip = match optimize {
+        Some(options) => {
+            let before = zip_persona(&files, None)?.len();
+            let zip = zip_persona(&files, Some(options))?;
+            optimize::print_sizes(before, zip.len());
+            zip
+        }
+        None => zip_persona(&files, None)?,
+    };
+
+    std::fs::write(out, &zip).map_err(|e| format!("{}: {}", out.display(), e))?;
+    println!("Wrote {} ({} KB)", out.display(), zip.len() / 1024);
+    Ok(())
+}
+
+/// Zip a persona in memory, optionally optimizing its PNGs on the way
+pub fn zip_persona(files: &PersonaFiles, optimize: Option<&Options>) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (path, data) in files.files() {
+        let is_png = path.ends_with(".png");
+        let method = if is_png {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let optimized = match optimize {
+            Some(options) if is_png => optimize::optimize_png(data, options).map_err(|e| format!("{}: {}", path, e))?,
+            _ => None,
+        };
+
+        zip.start_file(path, FileOptions::default().compression_method(method))
+            .map_err(|e| e.to_string())?;
+        zip.write_all(optimized.as_deref().unwrap_or(data))
+            .map_err(|e| e.to_string())?;
+    }
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(cursor.into_inner())
+}
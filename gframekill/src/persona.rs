@@ -0,0 +1,172 @@
+//! Persona contents read from a directory or a `.persona.zip`, as paths
+//! relative to the persona root
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+use crate::manifest::{Manifest, MANIFEST_FILE};
+
+/// Folder of the parts of a composite persona (eyes, mouth, ...), each laid
+/// out like a persona of its own
+pub const LAYERS_DIR: &str = "layers";
+
+#[derive(Default)]
+pub struct PersonaFiles {
+    /// File contents by `/`-separated relative path
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl PersonaFiles {
+    /// Read a persona directory or zip archive
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let files = if path.is_dir() {
+            let mut files = BTreeMap::new();
+            read_dir(path, "", &mut files)?;
+            files
+        } else {
+            read_zip(path)?
+        };
+        Ok(Self { files })
+    }
+
+    pub fn get(&self, path: &str) -> Option<&[u8]> {
+        self.files.get(path).map(Vec::as_slice)
+    }
+
+    /// Add or replace a file
+    pub fn insert(&mut self, path: &str, data: Vec<u8>) {
+        self.files.insert(path.to_string(), data);
+    }
+
+    /// All files with their contents, sorted by path
+    pub fn files(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.files.iter().map(|(p, d)| (p.as_str(), d.as_slice()))
+    }
+
+    /// The parsed manifest (None if there is no `config.toml`)
+    pub fn manifest(&self) -> Option<Result<Manifest, String>> {
+        let bytes = self.get(MANIFEST_FILE)?;
+        let text = String::from_utf8_lossy(bytes);
+        Some(toml::from_str(&text).map_err(|e| e.to_string()))
+    }
+
+    /// Names of the top-level folders (animation states), leaving out
+    /// folders holding only sounds and the composite parts in `layers/`
+    pub fn state_names(&self) -> Vec<String> {
+        let mut states: Vec<String> = self
+            .files
+            .keys()
+            .filter(|p| !is_sound(p))
+            .filter_map(|p| p.split_once('/').map(|(dir, _)| dir.to_string()))
+            .filter(|dir| dir != LAYERS_DIR)
+            .collect();
+        states.dedup();
+        states
+    }
+
+    /// Total size of all files in bytes (uncompressed)
+    pub fn total_size(&self) -> usize {
+        self.files.values().map(Vec::len).sum()
+    }
+
+    /// Frame file names in a state folder, sorted
+    pub fn frames(&self, state: &str) -> Vec<&str> {
+        let prefix = format!("{}/", state);
+        self.files
+            .keys()
+            .filter_map(|p| p.strip_prefix(&prefix))
+            .filter(|name| name.starts_with("frame_") && name.ends_with(".png"))
+            .collect()
+    }
+
+    /// Top-level PNG files (still image candidates)
+    pub fn top_level_images(&self) -> Vec<&str> {
+        self.files
+            .keys()
+            .map(String::as_str)
+            .filter(|p| !p.contains('/') && p.ends_with(".png"))
+            .collect()
+    }
+}
+
+/// Width and height from an image's header
+pub fn image_size(bytes: &[u8]) -> Result<(u32, u32), String> {
+    image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_dimensions()
+        .map_err(|e| e.to_string())
+}
+
+fn read_dir(dir: &Path, prefix: &str, files: &mut BTreeMap<String, Vec<u8>>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            read_dir(&path, &format!("{}/", name), files)?;
+        } else {
+            let data = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            files.insert(name, data);
+        }
+    }
+    Ok(())
+}
+
+fn read_zip(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut files = BTreeMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        files.insert(entry.name().to_string(), data);
+    }
+    Ok(strip_root(files))
+}
+
+/// Archives often wrap everything in one folder; use it as the root
+fn strip_root(files: BTreeMap<String, Vec<u8>>) -> BTreeMap<String, Vec<u8>> {
+    if files.contains_key(MANIFEST_FILE) {
+        return files;
+    }
+    let root = match files.keys().next().and_then(|p| p.split_once('/')) {
+        Some((root, _)) => format!("{}/", root),
+        None => return files,
+    };
+    if !files.keys().all(|p| p.starts_with(&root)) {
+        return files;
+    }
+    files
+        .into_iter()
+        .map(|(p, d)| (p[root.len()..].to_string(), d))
+        .collect()
+}
+
+/// Whether a file is audio (persona sounds live next to the states)
+fn is_sound(path: &str) -> bool {
+    let ext = path.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    matches!(ext.as_str(), "ogg" | "wav" | "mp3" | "flac")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_root() {
+        let files: BTreeMap<String, Vec<u8>> = ["rin/config.toml", "rin/idle/frame_0001.png"]
+            .iter()
+            .map(|p| (p.to_string(), Vec::new()))
+            .collect();
+        let stripped = strip_root(files);
+        assert!(stripped.contains_key("config.toml"));
+        assert!(stripped.contains_key("idle/frame_0001.png"));
+    }
+}
@@ -0,0 +1,178 @@
+//! `preview` - small animated GIF, static thumbnail and contact sheet of a
+//! persona
+//!
+//! Renders one state (idle by default) for galleries, READMEs and the
+//! persona picker, and lays all its frames out side by side for review.
+//! Works on persona directories and zips.
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, GenericImage, GenericImageView, RgbaImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::args::Args;
+use crate::persona::PersonaFiles;
+use crate::spritesheet::{self, SpriteSheet, SHEET_IMAGE, SHEET_META};
+
+const USAGE: &str =
+    "Usage: gassetsmaker preview <persona_dir|zip> [--state <name>] [--size <px>] [--fps <n>] [--out <dir>]";
+
+/// GIF preview, written next to the thumbnail
+pub const PREVIEW_FILE: &str = "preview.gif";
+/// Static thumbnail (first frame)
+pub const THUMBNAIL_FILE: &str = "thumbnail.png";
+/// Every frame of the state in a grid
+pub const CONTACT_SHEET_FILE: &str = "contact.png";
+
+const DEFAULT_SIZE: u32 = 128;
+const DEFAULT_FPS: f32 = 12.0;
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let path = Path::new(args.positional(0).ok_or(USAGE)?);
+    let state = args.value("--state").unwrap_or("idle");
+    let size = args.parsed::<u32>("--size")?.unwrap_or(DEFAULT_SIZE).max(1);
+    let out_dir = match args.value("--out") {
+        Some(out) => PathBuf::from(out),
+        None if path.is_dir() => path.to_path_buf(),
+        None => path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+
+    let persona = PersonaFiles::open(path)?;
+    let (frames, sheet_fps) = load_frames(&persona, state)?;
+    let manifest_fps = match persona.manifest() {
+        Some(Ok(manifest)) => manifest.states.get(state).map(|s| s.fps),
+        _ => None,
+    };
+    let fps = match args.parsed::<f32>("--fps")? {
+        Some(fps) => fps,
+        None => sheet_fps.or(manifest_fps).unwrap_or(DEFAULT_FPS),
+    };
+    if fps <= 0.0 {
+        return Err(USAGE.to_string());
+    }
+
+    let (width, height) = fit_within(frames[0].dimensions(), size);
+    let frames: Vec<RgbaImage> = frames
+        .iter()
+        .map(|f| image::imageops::resize(f, width, height, FilterType::Triangle))
+        .collect();
+
+    let thumbnail = out_dir.join(THUMBNAIL_FILE);
+    frames[0]
+        .save(&thumbnail)
+        .map_err(|e| format!("{}: {}", thumbnail.display(), e))?;
+
+    let preview = out_dir.join(PREVIEW_FILE);
+    let file = File::create(&preview).map_err(|e| format!("{}: {}", preview.display(), e))?;
+    let mut encoder = GifEncoder::new(BufWriter::new(file));
+    encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+    let delay = Delay::from_saturating_duration(Duration::from_secs_f32(1.0 / fps));
+    encoder
+        .encode_frames(frames.iter().map(|f| Frame::from_parts(f.clone(), 0, 0, delay)))
+        .map_err(|e| format!("{}: {}", preview.display(), e))?;
+
+    let contact = out_dir.join(CONTACT_SHEET_FILE);
+    contact_sheet(&frames)?
+        .save(&contact)
+        .map_err(|e| format!("{}: {}", contact.display(), e))?;
+
+    println!(
+        "Wrote {} ({} frames, {}x{} @ {}fps), {} and {}",
+        preview.display(),
+        frames.len(),
+        width,
+        height,
+        fps,
+        thumbnail.display(),
+        contact.display()
+    );
+    Ok(())
+}
+
+/// Equally sized frames in a roughly square grid, row by row
+fn contact_sheet(frames: &[RgbaImage]) -> Result<RgbaImage, String> {
+    let (frame_width, frame_height) = frames[0].dimensions();
+    let layout = SpriteSheet {
+        frame_width,
+        frame_height,
+        columns: spritesheet::square_columns(frames.len() as u32),
+        frames: frames.len() as u32,
+        fps: None,
+    };
+    let (width, height) = layout.image_size();
+    let mut sheet = RgbaImage::new(width, height);
+    for (i, frame) in frames.iter().enumerate() {
+        let (x, y) = layout.frame_origin(i as u32);
+        sheet.copy_from(frame, x, y).map_err(|e| e.to_string())?;
+    }
+    Ok(sheet)
+}
+
+/// A state's frames, from `frame_*.png` files or its sprite sheet, and the
+/// sheet's fps if it has one
+fn load_frames(persona: &PersonaFiles, state: &str) -> Result<(Vec<RgbaImage>, Option<f32>), String> {
+    let decode = |path: &str| -> Result<RgbaImage, String> {
+        let data = persona.get(path).ok_or_else(|| format!("{}: not found", path))?;
+        Ok(image::load_from_memory(data).map_err(|e| format!("{}: {}", path, e))?.into_rgba8())
+    };
+
+    let names = persona.frames(state);
+    if !names.is_empty() {
+        let frames = names
+            .iter()
+            .map(|name| decode(&format!("{}/{}", state, name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok((frames, None));
+    }
+
+    let meta_path = format!("{}/{}", state, SHEET_META);
+    let meta = persona
+        .get(&meta_path)
+        .ok_or_else(|| format!("State '{}' has no frames", state))?;
+    let sheet: SpriteSheet = serde_json::from_slice(meta).map_err(|e| format!("{}: {}", meta_path, e))?;
+    let image = decode(&format!("{}/{}", state, SHEET_IMAGE))?;
+    let frames = (0..sheet.frames)
+        .map(|i| {
+            let (x, y) = sheet.frame_origin(i);
+            image.view(x, y, sheet.frame_width, sheet.frame_height).to_image()
+        })
+        .collect();
+    Ok((frames, sheet.fps))
+}
+
+/// Size scaled down (never up) so the longer side is at most `max`
+fn fit_within((width, height): (u32, u32), max: u32) -> (u32, u32) {
+    let largest = width.max(height);
+    if largest <= max {
+        return (width, height);
+    }
+    let scale = max as f32 / largest as f32;
+    (
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_within() {
+        assert_eq!(fit_within((288, 512), 128), (72, 128));
+        assert_eq!(fit_within((512, 100), 128), (128, 25));
+        assert_eq!(fit_within((64, 32), 128), (64, 32));
+    }
+
+    #[test]
+    fn test_contact_sheet() {
+        let frames: Vec<RgbaImage> = (0..5).map(|i| RgbaImage::from_pixel(4, 2, image::Rgba([i, 0, 0, 255]))).collect();
+        let sheet = contact_sheet(&frames).unwrap();
+        assert_eq!(sheet.dimensions(), (12, 4));
+        assert_eq!(sheet.get_pixel(4, 2)[0], 4);
+        assert_eq!(sheet.get_pixel(8, 2)[3], 0);
+    }
+}
@@ -0,0 +1,139 @@
+//! `resize` - scale a persona's still image and every frame by one factor
+//!
+//! All images share the factor so frames stay aligned with each other and
+//! with the still image.
+
+use image::imageops::FilterType;
+use image::{GenericImage, GenericImageView, RgbaImage};
+use std::path::{Path, PathBuf};
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::Manifest;
+use crate::spritesheet::{SpriteSheet, SHEET_IMAGE};
+
+const USAGE: &str = "Usage: gassetsmaker resize <persona_dir> (--scale <factor> | --max <px>)";
+
+/// Images found in a persona directory
+struct PersonaImages {
+    /// Still images and frames, resized whole
+    images: Vec<PathBuf>,
+    /// State folders holding a sprite sheet, resized cell by cell
+    sheets: Vec<(PathBuf, SpriteSheet)>,
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let persona_dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    let found = collect(persona_dir)?;
+
+    let scale = match (args.parsed::<f32>("--scale")?, args.parsed::<u32>("--max")?) {
+        (Some(scale), None) if scale > 0.0 => scale,
+        (None, Some(max)) if max > 0 => {
+            let largest = largest_side(&found)?;
+            if largest <= max {
+                println!("Largest image is {}px, nothing to do", largest);
+                return Ok(());
+            }
+            max as f32 / largest as f32
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+
+    for path in &found.images {
+        let img = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let (width, height) = scaled_size(img.dimensions(), scale);
+        img.resize_exact(width, height, FilterType::Lanczos3)
+            .save(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    for (dir, sheet) in &found.sheets {
+        resize_sheet(dir, sheet, scale)?;
+    }
+
+    // Keep the frame size recorded by `trim` in step
+    let mut manifest = Manifest::load(persona_dir)?;
+    if let Some([width, height]) = manifest.size {
+        let (width, height) = scaled_size((width, height), scale);
+        manifest.size = Some([width, height]);
+        manifest.save(persona_dir)?;
+    }
+
+    println!(
+        "Resized {} images and {} sprite sheets by {:.3}",
+        found.images.len(),
+        found.sheets.len(),
+        scale
+    );
+    Ok(())
+}
+
+fn collect(persona_dir: &Path) -> Result<PersonaImages, String> {
+    let manifest = Manifest::load(persona_dir)?;
+    let mut images = frames::still_images(persona_dir, manifest.still.as_deref())?;
+    let mut sheets = Vec::new();
+    for dir in frames::state_dirs(persona_dir)? {
+        images.extend(frames::list_frames(&dir)?);
+        if let Some(sheet) = SpriteSheet::load(&dir)? {
+            sheets.push((dir, sheet));
+        }
+    }
+    Ok(PersonaImages { images, sheets })
+}
+
+/// Longest side over all stills and frames
+fn largest_side(found: &PersonaImages) -> Result<u32, String> {
+    let mut largest = 0;
+    for path in &found.images {
+        let (w, h) = image::image_dimensions(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        largest = largest.max(w).max(h);
+    }
+    for (_, sheet) in &found.sheets {
+        largest = largest.max(sheet.frame_width).max(sheet.frame_height);
+    }
+    Ok(largest)
+}
+
+/// Resize each cell of a sprite sheet and rebuild it
+fn resize_sheet(dir: &Path, sheet: &SpriteSheet, scale: f32) -> Result<(), String> {
+    let path = dir.join(SHEET_IMAGE);
+    let img = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (frame_width, frame_height) = scaled_size((sheet.frame_width, sheet.frame_height), scale);
+    let resized = SpriteSheet {
+        frame_width,
+        frame_height,
+        ..sheet.clone()
+    };
+
+    let (width, height) = resized.image_size();
+    let mut out = RgbaImage::new(width, height);
+    for i in 0..sheet.frames {
+        let (x, y) = sheet.frame_origin(i);
+        let cell = img.crop_imm(x, y, sheet.frame_width, sheet.frame_height);
+        let cell = cell.resize_exact(frame_width, frame_height, FilterType::Lanczos3);
+        let (x, y) = resized.frame_origin(i);
+        out.copy_from(&cell.to_rgba8(), x, y).map_err(|e| e.to_string())?;
+    }
+
+    out.save(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    resized.save(dir)
+}
+
+/// Size scaled by `scale`, at least 1x1
+fn scaled_size((width, height): (u32, u32), scale: f32) -> (u32, u32) {
+    (
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaled_size() {
+        assert_eq!(scaled_size((288, 512), 0.5), (144, 256));
+        assert_eq!(scaled_size((3, 1), 0.1), (1, 1));
+        assert_eq!(scaled_size((100, 50), 1.5), (150, 75));
+    }
+}
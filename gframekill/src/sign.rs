@@ -0,0 +1,203 @@
+//! `sign` / `verify` - persona signatures
+//!
+//! The signed message lists every file except `signature.json`, sorted by
+//! path, one `<sha256 hex>  <path>\n` line each (the `sha256sum` format).
+//! `signature.json` holds the ed25519 signature of that message, the public
+//! key and its fingerprint (first 8 bytes of the key's SHA-256, in hex).
+//! Works the same on a `.persona.zip` and on a persona directory.
+//!
+//! The ghost checks the same listing when it loads a persona (ghost-ui's
+//! `PersonaTrust`): a signed persona whose files changed doesn't load, and
+//! with `skin.trusted_keys` set only personas signed by one of them do.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use crate::args::Args;
+use crate::pack;
+use crate::persona::PersonaFiles;
+
+const SIGN_USAGE: &str = "Usage: gassetsmaker sign <persona.zip|dir> --key <keyfile>";
+const VERIFY_USAGE: &str = "Usage: gassetsmaker verify <persona.zip|dir> [--fingerprint <hex>]";
+
+/// File holding the signature, at the persona root
+pub const SIGNATURE_FILE: &str = "signature.json";
+
+/// Contents of `signature.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct SignatureFile {
+    algorithm: String,
+    /// Public key, hex
+    public_key: String,
+    fingerprint: String,
+    /// Signature of the file listing, hex
+    signature: String,
+}
+
+pub fn run_sign(args: &Args) -> Result<(), String> {
+    let path = Path::new(args.positional(0).ok_or(SIGN_USAGE)?);
+    let key_path = Path::new(args.value("--key").ok_or(SIGN_USAGE)?);
+    let key = load_or_create_key(key_path)?;
+
+    let files = PersonaFiles::open(path)?;
+    let signature = sign(&files, &key);
+    let json = serde_json::to_string_pretty(&signature).map_err(|e| e.to_string())?;
+
+    if path.is_dir() {
+        let file = path.join(SIGNATURE_FILE);
+        std::fs::write(&file, json).map_err(|e| format!("{}: {}", file.display(), e))?;
+    } else {
+        // Repack with the signature added (replacing an old one)
+        let mut files = files;
+        files.insert(SIGNATURE_FILE, json.into_bytes());
+        let zip = pack::zip_persona(&files, None)?;
+        std::fs::write(path, zip).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    println!("Signed {} with key {}", path.display(), signature.fingerprint);
+    Ok(())
+}
+
+pub fn run_verify(args: &Args) -> Result<(), String> {
+    let path = Path::new(args.positional(0).ok_or(VERIFY_USAGE)?);
+    let files = PersonaFiles::open(path)?;
+    let fingerprint = verify(&files)?;
+
+    if let Some(expected) = args.value("--fingerprint") {
+        if !expected.eq_ignore_ascii_case(&fingerprint) {
+            return Err(format!("Signed by {}, expected {}", fingerprint, expected));
+        }
+    }
+    println!("{}: valid signature by {}", path.display(), fingerprint);
+    Ok(())
+}
+
+fn sign(files: &PersonaFiles, key: &SigningKey) -> SignatureFile {
+    let public_key = key.verifying_key();
+    SignatureFile {
+        algorithm: "ed25519".to_string(),
+        public_key: to_hex(public_key.as_bytes()),
+        fingerprint: fingerprint(&public_key),
+        signature: to_hex(&key.sign(&file_listing(files)).to_bytes()),
+    }
+}
+
+/// Check the embedded signature; the signer's fingerprint if valid
+fn verify(files: &PersonaFiles) -> Result<String, String> {
+    let json = files.get(SIGNATURE_FILE).ok_or("Not signed (no signature.json)")?;
+    let file: SignatureFile = serde_json::from_slice(json).map_err(|e| format!("{}: {}", SIGNATURE_FILE, e))?;
+    if file.algorithm != "ed25519" {
+        return Err(format!("Unsupported signature algorithm: {}", file.algorithm));
+    }
+
+    let key_bytes: [u8; 32] = from_hex(&file.public_key)
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Invalid public key")?;
+    let public_key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| e.to_string())?;
+    let signature_bytes: [u8; 64] = from_hex(&file.signature)
+        .and_then(|b| b.try_into().ok())
+        .ok_or("Invalid signature")?;
+
+    let fingerprint = fingerprint(&public_key);
+    if fingerprint != file.fingerprint {
+        return Err("Fingerprint does not match the public key".to_string());
+    }
+    public_key
+        .verify(&file_listing(files), &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| "Signature does not match the contents (modified after signing?)".to_string())?;
+    Ok(fingerprint)
+}
+
+/// The signed message: `<sha256>  <path>` per file, sorted by path
+fn file_listing(files: &PersonaFiles) -> Vec<u8> {
+    let mut listing = String::new();
+    for (path, data) in files.files().filter(|(p, _)| *p != SIGNATURE_FILE) {
+        listing.push_str(&format!("{}  {}\n", to_hex(&Sha256::digest(data)), path));
+    }
+    listing.into_bytes()
+}
+
+fn fingerprint(key: &VerifyingKey) -> String {
+    to_hex(&Sha256::digest(key.as_bytes())[..8])
+}
+
+/// Read a hex secret key, generating one (and `<keyfile>.pub`) if missing
+fn load_or_create_key(path: &Path) -> Result<SigningKey, String> {
+    if path.exists() {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let bytes: [u8; 32] = from_hex(text.trim())
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| format!("{}: not a hex ed25519 secret key", path.display()))?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+
+    let key = SigningKey::generate(&mut OsRng);
+    std::fs::write(path, to_hex(key.as_bytes()) + "\n").map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut public = PathBuf::from(path).into_os_string();
+    public.push(".pub");
+    let public = PathBuf::from(public);
+    std::fs::write(&public, to_hex(key.verifying_key().as_bytes()) + "\n")
+        .map_err(|e| format!("{}: {}", public.display(), e))?;
+    println!(
+        "Generated key {} ({}); keep it private",
+        path.display(),
+        fingerprint(&key.verifying_key())
+    );
+    Ok(key)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn persona(files: &[(&str, &[u8])]) -> PersonaFiles {
+        let mut persona = PersonaFiles::default();
+        for (path, data) in files {
+            persona.insert(path, data.to_vec());
+        }
+        persona
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(to_hex(&[0, 171, 255]), "00abff");
+        assert_eq!(from_hex("00abff"), Some(vec![0, 171, 255]));
+        assert_eq!(from_hex("0g"), None);
+        assert_eq!(from_hex("abc"), None);
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut files = persona(&[("config.toml", b"name = \"rin\""), ("idle/frame_0001.png", b"png")]);
+        let signature = sign(&files, &key);
+        files.insert(SIGNATURE_FILE, serde_json::to_vec(&signature).unwrap());
+        assert_eq!(verify(&files), Ok(signature.fingerprint.clone()));
+
+        // Any change to the contents breaks the signature
+        files.insert("idle/frame_0001.png", b"other".to_vec());
+        assert!(verify(&files).is_err());
+    }
+
+    #[test]
+    fn test_unsigned() {
+        assert!(verify(&persona(&[("config.toml", b"")])).is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! `slice` - cut an artist's sprite sheet into per-state frame folders
+//!
+//! Two layouts are understood: a plain grid (`--rows`/`--cols`, one state
+//! per row or the whole sheet as one state) and a JSON atlas as exported by
+//! TexturePacker or Aseprite (frames as a hash or array; Aseprite frame tags
+//! name the states, otherwise the frame names do: `walk_01.png` -> `walk`).
+
+use image::{GenericImage, GenericImageView, RgbaImage};
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::{Manifest, StateInfo};
+
+const USAGE: &str = "Usage: gassetsmaker slice <sheet.png> --rows <n> --cols <n> (--state <name> | --states <a,b,...>)
+                          [--persona <dir>] [--fps <n>]
+       gassetsmaker slice <sheet.png> --atlas <file.json> [--persona <dir>] [--fps <n>]";
+
+/// Playback speed recorded when neither `--fps` nor the atlas gives one
+const DEFAULT_FPS: f32 = 12.0;
+
+/// Frame area in the sheet
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+struct Rect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+struct Size {
+    w: u32,
+    h: u32,
+}
+
+/// One frame of a JSON atlas
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlasFrame {
+    #[serde(default)]
+    filename: String,
+    frame: Rect,
+    #[serde(default)]
+    rotated: bool,
+    /// Where a trimmed frame sits in its original canvas
+    #[serde(default)]
+    sprite_source_size: Option<Rect>,
+    /// Original canvas of a trimmed frame
+    #[serde(default)]
+    source_size: Option<Size>,
+    /// Display time in milliseconds (Aseprite)
+    #[serde(default)]
+    duration: Option<f32>,
+}
+
+/// Aseprite frame tag: frames `from..=to` form the state `name`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct FrameTag {
+    name: String,
+    from: usize,
+    to: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtlasMeta {
+    #[serde(default)]
+    frame_tags: Vec<FrameTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Atlas {
+    #[serde(deserialize_with = "atlas_frames")]
+    frames: Vec<AtlasFrame>,
+    #[serde(default)]
+    meta: AtlasMeta,
+}
+
+/// A state and the frames cut for it
+struct SlicedState {
+    name: String,
+    frames: Vec<RgbaImage>,
+    /// Suggested playback speed, if the source has timing
+    fps: Option<f32>,
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let input = args.positional(0).ok_or(USAGE)?;
+    let persona_dir = Path::new(args.value("--persona").unwrap_or("."));
+    let image = image::open(input).map_err(|e| format!("{}: {}", input, e))?.into_rgba8();
+
+    let states = match args.value("--atlas") {
+        Some(atlas) => slice_atlas(&image, Path::new(atlas))?,
+        None => slice_grid(&image, args)?,
+    };
+
+    let mut manifest = Manifest::load(persona_dir)?;
+    for state in &states {
+        let dir = frames::prepare_state_dir(persona_dir, &state.name)?;
+        for (i, frame) in state.frames.iter().enumerate() {
+            frames::save_frame(&dir, i + 1, frame)?;
+        }
+        let fps = match args.parsed::<f32>("--fps")? {
+            Some(fps) => fps,
+            None => state.fps.unwrap_or(DEFAULT_FPS),
+        };
+        println!("Wrote {} frames to {} ({} fps)", state.frames.len(), dir.display(), fps);
+        manifest.set_state(persona_dir, &state.name, StateInfo::new(fps, state.frames.len() as u32));
+    }
+    manifest.save(persona_dir)
+}
+
+fn slice_grid(image: &RgbaImage, args: &Args) -> Result<Vec<SlicedState>, String> {
+    let rows = args.parsed::<u32>("--rows")?.ok_or(USAGE)?;
+    let columns = args.parsed::<u32>("--cols")?.ok_or(USAGE)?;
+    if rows == 0 || columns == 0 {
+        return Err("--rows and --cols must be at least 1".to_string());
+    }
+    let (width, height) = image.dimensions();
+    if width % columns != 0 || height % rows != 0 {
+        println!(
+            "Warning: {}x{} does not divide into {} columns and {} rows; the remainder is ignored",
+            width, height, columns, rows
+        );
+    }
+    let (cell_width, cell_height) = (width / columns, height / rows);
+    if cell_width == 0 || cell_height == 0 {
+        return Err(format!("{}x{} is too small for {} columns and {} rows", width, height, columns, rows));
+    }
+
+    // Cells of each state, row by row
+    let names: Vec<String> = match (args.value("--states"), args.value("--state")) {
+        (Some(list), _) => list.split(',').map(|s| s.trim().to_string()).collect(),
+        (None, Some(state)) => vec![state.to_string()],
+        (None, None) => return Err(USAGE.to_string()),
+    };
+    let cells = grid_cells(rows, columns, cell_width, cell_height);
+    let groups: Vec<(String, &[Rect])> = if names.len() == 1 {
+        vec![(names[0].clone(), &cells[..])]
+    } else if names.len() == rows as usize {
+        names.into_iter().zip(cells.chunks(columns as usize)).collect()
+    } else {
+        return Err(format!("--states lists {} states for {} rows", names.len(), rows));
+    };
+
+    let mut states = Vec::with_capacity(groups.len());
+    for (name, rects) in groups {
+        if name.is_empty() {
+            return Err("Empty state name in --states".to_string());
+        }
+        let mut frames: Vec<RgbaImage> = rects
+            .iter()
+            .map(|r| image.view(r.x, r.y, r.w, r.h).to_image())
+            .collect();
+        // Rows often end in unused cells
+        while frames.last().is_some_and(is_blank) {
+            frames.pop();
+        }
+        if frames.is_empty() {
+            println!("Skipping state '{}': all cells are empty", name);
+            continue;
+        }
+        states.push(SlicedState { name, frames, fps: None });
+    }
+    if states.is_empty() {
+        return Err("The sheet has no frames".to_string());
+    }
+    Ok(states)
+}
+
+fn slice_atlas(image: &RgbaImage, atlas_path: &Path) -> Result<Vec<SlicedState>, String> {
+    let text = std::fs::read_to_string(atlas_path).map_err(|e| format!("{}: {}", atlas_path.display(), e))?;
+    let atlas: Atlas = serde_json::from_str(&text).map_err(|e| format!("{}: {}", atlas_path.display(), e))?;
+
+    let mut states = Vec::new();
+    for (name, indices) in atlas_groups(&atlas)? {
+        let mut frames = Vec::with_capacity(indices.len());
+        let mut durations = Vec::new();
+        for i in indices {
+            let frame = &atlas.frames[i];
+            frames.push(cut_frame(image, frame).map_err(|e| format!("{}: {}", atlas_path.display(), e))?);
+            durations.extend(frame.duration.filter(|d| *d > 0.0));
+        }
+        let fps = (!durations.is_empty()).then(|| {
+            let average = durations.iter().sum::<f32>() / durations.len() as f32;
+            (1000.0 / average).round().clamp(1.0, 60.0)
+        });
+        states.push(SlicedState { name, frames, fps });
+    }
+    if states.is_empty() {
+        return Err(format!("{}: no frames", atlas_path.display()));
+    }
+    Ok(states)
+}
+
+/// Frame indices of each state: by Aseprite tag if there are tags, else
+/// by frame name, in order of first appearance
+fn atlas_groups(atlas: &Atlas) -> Result<Vec<(String, Vec<usize>)>, String> {
+    if !atlas.meta.frame_tags.is_empty() {
+        return atlas
+            .meta
+            .frame_tags
+            .iter()
+            .map(|tag| {
+                if tag.from > tag.to || tag.to >= atlas.frames.len() {
+                    return Err(format!("Tag '{}' covers frames {}-{} of {}", tag.name, tag.from, tag.to, atlas.frames.len()));
+                }
+                Ok((tag.name.to_lowercase(), (tag.from..=tag.to).collect()))
+            })
+            .collect();
+    }
+
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, frame) in atlas.frames.iter().enumerate() {
+        let name = state_name(&frame.filename);
+        match groups.iter_mut().find(|(state, _)| *state == name) {
+            Some((_, indices)) => indices.push(i),
+            None => groups.push((name, vec![i])),
+        }
+    }
+    Ok(groups)
+}
+
+/// Cut one atlas frame, restoring the canvas of trimmed frames so all
+/// frames of a state line up
+fn cut_frame(image: &RgbaImage, frame: &AtlasFrame) -> Result<RgbaImage, String> {
+    if frame.rotated {
+        return Err(format!("Frame '{}' is rotated; export the atlas without rotation", frame.filename));
+    }
+    let Rect { x, y, w, h } = frame.frame;
+    if x + w > image.width() || y + h > image.height() {
+        return Err(format!("Frame '{}' lies outside the sheet", frame.filename));
+    }
+    let cut = image.view(x, y, w, h).to_image();
+    match (frame.source_size, frame.sprite_source_size) {
+        (Some(size), Some(offset)) if (size.w, size.h) != (w, h) => {
+            let mut canvas = RgbaImage::new(size.w, size.h);
+            canvas
+                .copy_from(&cut, offset.x, offset.y)
+                .map_err(|_| format!("Frame '{}' does not fit its source size", frame.filename))?;
+            Ok(canvas)
+        }
+        _ => Ok(cut),
+    }
+}
+
+/// Cells of a grid, row by row
+fn grid_cells(rows: u32, columns: u32, width: u32, height: u32) -> Vec<Rect> {
+    (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| Rect { x: column * width, y: row * height, w: width, h: height }))
+        .collect()
+}
+
+/// State a frame name belongs to: `Walk_01.png` -> `walk`, `idle 3` -> `idle`
+fn state_name(filename: &str) -> String {
+    let stem = match filename.rsplit_once('.') {
+        Some((stem, ext)) if !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphabetic()) => stem,
+        _ => filename,
+    };
+    let name = stem
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches(['_', '-', ' ', '.'])
+        .to_lowercase();
+    if name.is_empty() {
+        "idle".to_string()
+    } else {
+        name
+    }
+}
+
+fn is_blank(frame: &RgbaImage) -> bool {
+    frame.pixels().all(|p| p[3] == 0)
+}
+
+/// Atlas frames as a hash (`{"name": {...}}`, in file order) or an array
+fn atlas_frames<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<AtlasFrame>, D::Error> {
+    struct FramesVisitor;
+
+    impl<'de> Visitor<'de> for FramesVisitor {
+        type Value = Vec<AtlasFrame>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a map or list of frames")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut frames = Vec::new();
+            while let Some((filename, mut frame)) = map.next_entry::<String, AtlasFrame>()? {
+                frame.filename = filename;
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut frames = Vec::new();
+            while let Some(frame) = seq.next_element()? {
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+    }
+
+    deserializer.deserialize_any(FramesVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_name() {
+        assert_eq!(state_name("Walk_01.png"), "walk");
+        assert_eq!(state_name("idle 3.aseprite"), "idle");
+        assert_eq!(state_name("talk-12"), "talk");
+        assert_eq!(state_name("0.png"), "idle");
+    }
+
+    #[test]
+    fn test_grid_cells() {
+        let cells = grid_cells(2, 3, 10, 20);
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells[4], Rect { x: 10, y: 20, w: 10, h: 20 });
+    }
+
+    #[test]
+    fn test_atlas_groups() {
+        // Hash frames keep their file order; names decide the states
+        let text = r#"{"frames": {
+            "wave_2.png": {"frame": {"x": 10, "y": 0, "w": 10, "h": 10}},
+            "wave_1.png": {"frame": {"x": 0, "y": 0, "w": 10, "h": 10}},
+            "idle_1.png": {"frame": {"x": 20, "y": 0, "w": 10, "h": 10}}
+        }}"#;
+        let atlas: Atlas = serde_json::from_str(text).unwrap();
+        assert_eq!(atlas.frames[0].filename, "wave_2.png");
+        let groups = atlas_groups(&atlas).unwrap();
+        assert_eq!(groups, vec![("wave".to_string(), vec![0, 1]), ("idle".to_string(), vec![2])]);
+
+        // Aseprite tags take precedence
+        let text = r#"{"frames": [
+            {"filename": "a 0", "frame": {"x": 0, "y": 0, "w": 8, "h": 8}, "duration": 100},
+            {"filename": "a 1", "frame": {"x": 8, "y": 0, "w": 8, "h": 8}, "duration": 100}
+        ], "meta": {"frameTags": [{"name": "Blink", "from": 1, "to": 1}]}}"#;
+        let atlas: Atlas = serde_json::from_str(text).unwrap();
+        assert_eq!(atlas_groups(&atlas).unwrap(), vec![("blink".to_string(), vec![1])]);
+    }
+
+    #[test]
+    fn test_cut_trimmed_frame() {
+        let mut image = RgbaImage::new(4, 4);
+        image.put_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+        let frame = AtlasFrame {
+            filename: "idle".to_string(),
+            frame: Rect { x: 1, y: 1, w: 1, h: 1 },
+            rotated: false,
+            sprite_source_size: Some(Rect { x: 2, y: 3, w: 1, h: 1 }),
+            source_size: Some(Size { w: 5, h: 5 }),
+            duration: None,
+        };
+        let cut = cut_frame(&image, &frame).unwrap();
+        assert_eq!(cut.dimensions(), (5, 5));
+        assert_eq!(cut.get_pixel(2, 3)[3], 255);
+        assert!(!is_blank(&cut));
+    }
+}
@@ -0,0 +1,183 @@
+//! `spritesheet` - pack a state's frames into `sheet.png` + `sheet.json`, or
+//! slice a sheet back into frames
+//!
+//! The layout matches what ghost-ui's `AnimatedSkin` loads: equally sized
+//! frames row by row, `columns` per row.
+
+use image::{GenericImage, GenericImageView, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::args::Args;
+use crate::frames;
+
+const USAGE: &str = "Usage: gassetsmaker spritesheet pack <state_dir> [--columns <n>] [--fps <n>] [--remove-frames]
+       gassetsmaker spritesheet slice <state_dir> [--remove-sheet]";
+
+pub const SHEET_IMAGE: &str = "sheet.png";
+pub const SHEET_META: &str = "sheet.json";
+
+/// Contents of `sheet.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheet {
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub frames: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f32>,
+}
+
+impl SpriteSheet {
+    /// Read `sheet.json` from a state folder (None if there is no sheet)
+    pub fn load(dir: &Path) -> Result<Option<Self>, String> {
+        let path = dir.join(SHEET_META);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map(Some).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Write `sheet.json` into a state folder
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let meta = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(SHEET_META), meta).map_err(|e| e.to_string())
+    }
+
+    /// Top-left corner of frame `i` in the sheet
+    pub fn frame_origin(&self, i: u32) -> (u32, u32) {
+        let columns = self.columns.max(1);
+        ((i % columns) * self.frame_width, (i / columns) * self.frame_height)
+    }
+
+    fn rows(&self) -> u32 {
+        self.frames.div_ceil(self.columns.max(1))
+    }
+
+    /// Size of the sheet image
+    pub fn image_size(&self) -> (u32, u32) {
+        (self.columns.min(self.frames) * self.frame_width, self.rows() * self.frame_height)
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let dir = Path::new(args.positional(1).ok_or(USAGE)?);
+    match args.positional(0) {
+        Some("pack") => pack(dir, args),
+        Some("slice") => slice(dir, args),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn pack(dir: &Path, args: &Args) -> Result<(), String> {
+    let paths = frames::list_frames(dir)?;
+    if paths.is_empty() {
+        return Err(format!("No frames in {}", dir.display()));
+    }
+
+    let mut images = Vec::with_capacity(paths.len());
+    for path in &paths {
+        images.push(image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?.into_rgba8());
+    }
+    let (frame_width, frame_height) = images[0].dimensions();
+    if let Some(i) = images.iter().position(|img| img.dimensions() != (frame_width, frame_height)) {
+        return Err(format!(
+            "{} is {:?}, expected {}x{} like the first frame",
+            paths[i].display(),
+            images[i].dimensions(),
+            frame_width,
+            frame_height
+        ));
+    }
+
+    let count = images.len() as u32;
+    let columns = match args.parsed::<u32>("--columns")? {
+        Some(c) if c > 0 => c,
+        Some(_) => return Err("--columns must be at least 1".to_string()),
+        None => square_columns(count),
+    };
+    let sheet = SpriteSheet {
+        frame_width,
+        frame_height,
+        columns,
+        frames: count,
+        fps: args.parsed("--fps")?,
+    };
+
+    let (width, height) = sheet.image_size();
+    let mut image = RgbaImage::new(width, height);
+    for (i, frame) in images.iter().enumerate() {
+        let (x, y) = sheet.frame_origin(i as u32);
+        image.copy_from(frame, x, y).map_err(|e| e.to_string())?;
+    }
+
+    let image_path = dir.join(SHEET_IMAGE);
+    image.save(&image_path).map_err(|e| format!("{}: {}", image_path.display(), e))?;
+    sheet.save(dir)?;
+    println!(
+        "Packed {} frames into {} ({}x{})",
+        count,
+        image_path.display(),
+        image.width(),
+        image.height()
+    );
+
+    if args.has("--remove-frames") {
+        let removed = frames::remove_frames(dir)?;
+        println!("Removed {} frame files", removed);
+    }
+    Ok(())
+}
+
+fn slice(dir: &Path, args: &Args) -> Result<(), String> {
+    let sheet = SpriteSheet::load(dir)?.ok_or_else(|| format!("No {} in {}", SHEET_META, dir.display()))?;
+    let image_path = dir.join(SHEET_IMAGE);
+    let image = image::open(&image_path).map_err(|e| format!("{}: {}", image_path.display(), e))?;
+
+    frames::remove_frames(dir)?;
+    for i in 0..sheet.frames {
+        let (x, y) = sheet.frame_origin(i);
+        if x + sheet.frame_width > image.width() || y + sheet.frame_height > image.height() {
+            return Err(format!("Frame {} lies outside {}", i + 1, image_path.display()));
+        }
+        let frame = image.view(x, y, sheet.frame_width, sheet.frame_height).to_image();
+        frames::save_frame(dir, i as usize + 1, &frame)?;
+    }
+    println!("Sliced {} frames into {}", sheet.frames, dir.display());
+
+    if args.has("--remove-sheet") {
+        std::fs::remove_file(&image_path).map_err(|e| e.to_string())?;
+        std::fs::remove_file(dir.join(SHEET_META)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Columns for a roughly square sheet
+pub fn square_columns(frames: u32) -> u32 {
+    (frames as f64).sqrt().ceil().max(1.0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(square_columns(1), 1);
+        assert_eq!(square_columns(10), 4);
+        assert_eq!(square_columns(16), 4);
+
+        let sheet = SpriteSheet {
+            frame_width: 10,
+            frame_height: 20,
+            columns: 4,
+            frames: 10,
+            fps: None,
+        };
+        assert_eq!(sheet.rows(), 3);
+        assert_eq!(sheet.frame_origin(0), (0, 0));
+        assert_eq!(sheet.frame_origin(5), (10, 20));
+        assert_eq!(sheet.frame_origin(9), (10, 40));
+    }
+}
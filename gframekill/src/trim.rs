@@ -0,0 +1,239 @@
+//! `trim` - crop the transparent margin shared by every frame
+//!
+//! The crop box is the union of the visible pixels over all frames of all
+//! states, so every frame is cut identically and stays aligned with the rest.
+
+use image::{GenericImage, GenericImageView, RgbaImage};
+use std::path::{Path, PathBuf};
+
+use crate::args::Args;
+use crate::frames;
+use crate::manifest::Manifest;
+use crate::spritesheet::{SpriteSheet, SHEET_IMAGE};
+
+const USAGE: &str = "Usage: gassetsmaker trim <persona_dir>";
+
+/// Pixel rectangle, `right` and `bottom` exclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+impl Bounds {
+    fn union(self, other: Bounds) -> Bounds {
+        Bounds {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.right - self.left
+    }
+
+    fn height(&self) -> u32 {
+        self.bottom - self.top
+    }
+}
+
+/// Frames of a persona, as files or sprite sheet cells
+struct PersonaFrames {
+    images: Vec<PathBuf>,
+    sheets: Vec<(PathBuf, SpriteSheet)>,
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let persona_dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    let mut manifest = Manifest::load(persona_dir)?;
+    let found = collect(persona_dir)?;
+
+    let (size, bounds) = union_bounds(&found)?;
+    let bounds = match bounds {
+        Some(bounds) => bounds,
+        None => return Err("Every frame is fully transparent".to_string()),
+    };
+    if (bounds.width(), bounds.height()) == size {
+        println!("No transparent margin to trim ({}x{})", size.0, size.1);
+        return Ok(());
+    }
+
+    for path in &found.images {
+        let img = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        img.crop_imm(bounds.left, bounds.top, bounds.width(), bounds.height())
+            .save(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    for (dir, sheet) in &found.sheets {
+        trim_sheet(dir, sheet, bounds)?;
+    }
+
+    manifest.size = Some([bounds.width(), bounds.height()]);
+    manifest.save(persona_dir)?;
+
+    println!(
+        "Trimmed {} frames and {} sprite sheets from {}x{} to {}x{} (offset {},{})",
+        found.images.len(),
+        found.sheets.len(),
+        size.0,
+        size.1,
+        bounds.width(),
+        bounds.height(),
+        bounds.left,
+        bounds.top
+    );
+    Ok(())
+}
+
+fn collect(persona_dir: &Path) -> Result<PersonaFrames, String> {
+    let mut images = Vec::new();
+    let mut sheets = Vec::new();
+    for dir in frames::state_dirs(persona_dir)? {
+        images.extend(frames::list_frames(&dir)?);
+        if let Some(sheet) = SpriteSheet::load(&dir)? {
+            sheets.push((dir, sheet));
+        }
+    }
+    if images.is_empty() && sheets.is_empty() {
+        return Err(format!("{}: no frames found", persona_dir.display()));
+    }
+    Ok(PersonaFrames { images, sheets })
+}
+
+/// Common frame size and the union of visible pixels over every frame
+fn union_bounds(found: &PersonaFrames) -> Result<((u32, u32), Option<Bounds>), String> {
+    let mut size = None;
+    let mut union: Option<Bounds> = None;
+    let mut add = |name: &dyn Fn() -> String, frame: &RgbaImage| -> Result<(), String> {
+        let dimensions = frame.dimensions();
+        match size {
+            None => size = Some(dimensions),
+            Some(expected) if expected != dimensions => {
+                return Err(format!(
+                    "{}: {}x{} differs from {}x{}; frames must share one size to be trimmed together",
+                    name(),
+                    dimensions.0,
+                    dimensions.1,
+                    expected.0,
+                    expected.1
+                ));
+            }
+            Some(_) => {}
+        }
+        if let Some(bounds) = visible_bounds(frame) {
+            union = Some(union.map_or(bounds, |u| u.union(bounds)));
+        }
+        Ok(())
+    };
+
+    for path in &found.images {
+        let frame = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?.to_rgba8();
+        add(&|| path.display().to_string(), &frame)?;
+    }
+    for (dir, sheet) in &found.sheets {
+        let path = dir.join(SHEET_IMAGE);
+        let img = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?.to_rgba8();
+        for i in 0..sheet.frames {
+            let (x, y) = sheet.frame_origin(i);
+            let cell = img.view(x, y, sheet.frame_width, sheet.frame_height).to_image();
+            add(&|| format!("{} cell {}", path.display(), i + 1), &cell)?;
+        }
+    }
+    Ok((size.unwrap_or_default(), union))
+}
+
+/// Crop each cell of a sprite sheet and rebuild it
+fn trim_sheet(dir: &Path, sheet: &SpriteSheet, bounds: Bounds) -> Result<(), String> {
+    let path = dir.join(SHEET_IMAGE);
+    let img = image::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let trimmed = SpriteSheet {
+        frame_width: bounds.width(),
+        frame_height: bounds.height(),
+        ..sheet.clone()
+    };
+
+    let (width, height) = trimmed.image_size();
+    let mut out = RgbaImage::new(width, height);
+    for i in 0..sheet.frames {
+        let (x, y) = sheet.frame_origin(i);
+        let cell = img.crop_imm(x + bounds.left, y + bounds.top, bounds.width(), bounds.height());
+        let (x, y) = trimmed.frame_origin(i);
+        out.copy_from(&cell.to_rgba8(), x, y).map_err(|e| e.to_string())?;
+    }
+
+    out.save(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    trimmed.save(dir)
+}
+
+/// Smallest rectangle holding every pixel with alpha > 0
+fn visible_bounds(frame: &RgbaImage) -> Option<Bounds> {
+    let mut bounds: Option<Bounds> = None;
+    for (x, y, pixel) in frame.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let point = Bounds {
+            left: x,
+            top: y,
+            right: x + 1,
+            bottom: y + 1,
+        };
+        bounds = Some(bounds.map_or(point, |b| b.union(point)));
+    }
+    bounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_visible_bounds() {
+        let mut frame = RgbaImage::new(10, 8);
+        assert_eq!(visible_bounds(&frame), None);
+
+        frame.put_pixel(2, 3, Rgba([255, 0, 0, 255]));
+        frame.put_pixel(6, 5, Rgba([0, 0, 0, 1]));
+        let bounds = visible_bounds(&frame).unwrap();
+        assert_eq!(
+            bounds,
+            Bounds {
+                left: 2,
+                top: 3,
+                right: 7,
+                bottom: 6
+            }
+        );
+        assert_eq!((bounds.width(), bounds.height()), (5, 3));
+    }
+
+    #[test]
+    fn test_union() {
+        let a = Bounds {
+            left: 2,
+            top: 0,
+            right: 5,
+            bottom: 4,
+        };
+        let b = Bounds {
+            left: 0,
+            top: 1,
+            right: 3,
+            bottom: 6,
+        };
+        assert_eq!(
+            a.union(b),
+            Bounds {
+                left: 0,
+                top: 0,
+                right: 5,
+                bottom: 6
+            }
+        );
+    }
+}
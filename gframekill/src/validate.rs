@@ -0,0 +1,415 @@
+//! `validate` - check a persona directory or zip before publishing
+//!
+//! Prints a JSON report on stdout (for CI) and a readable summary on stderr;
+//! exits nonzero when there are errors.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::args::Args;
+use crate::manifest::{Manifest, MANIFEST_FILE, PLAY_MODES};
+use crate::persona::{image_size, PersonaFiles};
+use crate::spritesheet::{SpriteSheet, SHEET_META};
+
+const USAGE: &str = "Usage: gassetsmaker validate <persona_dir|persona.zip>";
+
+/// Largest texture the ghost can upload (matches ghost-ui)
+const MAX_TEXTURE_SIZE: u32 = 2048;
+
+/// Fastest playback the ghost animates at
+const MAX_FPS: f32 = 60.0;
+
+/// Uncompressed size above which a persona is worth optimizing
+const MAX_PACKAGE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Top-level manifest keys read by gassetsmaker or the ghost
+const MANIFEST_KEYS: [&str; 11] = [
+    "name", "still", "size", "states", "state_machine", "sounds", "script", "layers", "auto_life", "lip_sync", "gaze",
+];
+
+/// Keys of a `[states.<name>]` table
+const STATE_KEYS: [&str; 5] = ["fps", "frames", "mode", "sound", "next"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize)]
+struct Issue {
+    severity: Severity,
+    /// Stable identifier, e.g. `frame_gap`
+    code: &'static str,
+    /// File or folder the issue is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Report {
+    path: String,
+    ok: bool,
+    errors: usize,
+    warnings: usize,
+    issues: Vec<Issue>,
+}
+
+impl Report {
+    fn error(&mut self, code: &'static str, path: Option<&str>, message: String) {
+        self.push(Severity::Error, code, path, message);
+    }
+
+    fn warning(&mut self, code: &'static str, path: Option<&str>, message: String) {
+        self.push(Severity::Warning, code, path, message);
+    }
+
+    fn push(&mut self, severity: Severity, code: &'static str, path: Option<&str>, message: String) {
+        self.issues.push(Issue {
+            severity,
+            code,
+            path: path.map(str::to_string),
+            message,
+        });
+    }
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let path = args.positional(0).ok_or(USAGE)?;
+    let report = report(path)?;
+    println!("{}", serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?);
+
+    if !report.ok {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Validate and print the readable summary only; true if there are no errors
+pub fn check(path: &str) -> Result<bool, String> {
+    Ok(report(path)?.ok)
+}
+
+/// Validate a persona, printing the summary on stderr
+fn report(path: &str) -> Result<Report, String> {
+    let persona = PersonaFiles::open(Path::new(path))?;
+    let mut report = validate(&persona);
+    report.path = path.to_string();
+
+    for issue in &report.issues {
+        let location = issue.path.as_deref().map(|p| format!(" ({})", p)).unwrap_or_default();
+        eprintln!("{:?}: {}{}", issue.severity, issue.message, location);
+    }
+    eprintln!("{} errors, {} warnings", report.errors, report.warnings);
+    Ok(report)
+}
+
+fn validate(persona: &PersonaFiles) -> Report {
+    let mut report = Report::default();
+
+    let manifest = match persona.manifest() {
+        Some(Ok(manifest)) => {
+            check_manifest(persona, &manifest, &mut report);
+            if let Some(text) = persona.get(MANIFEST_FILE) {
+                let text = String::from_utf8_lossy(text);
+                check_keys(&text, &mut report);
+                check_layers(persona, &text, &mut report);
+            }
+            manifest
+        }
+        Some(Err(e)) => {
+            report.error("invalid_manifest", Some(MANIFEST_FILE), e);
+            Manifest::default()
+        }
+        None => {
+            report.error("missing_manifest", Some(MANIFEST_FILE), "No config.toml".to_string());
+            Manifest::default()
+        }
+    };
+    check_still(persona, &manifest, &mut report);
+    check_frames(persona, &manifest, &mut report);
+    check_total_size(persona.total_size(), &mut report);
+
+    report.errors = report.issues.iter().filter(|i| i.severity == Severity::Error).count();
+    report.warnings = report.issues.len() - report.errors;
+    report.ok = report.errors == 0;
+    report
+}
+
+fn check_manifest(persona: &PersonaFiles, manifest: &Manifest, report: &mut Report) {
+    if manifest.name.as_deref().is_none_or(|n| n.trim().is_empty()) {
+        report.error("missing_name", Some(MANIFEST_FILE), "Manifest has no name".to_string());
+    }
+    if manifest.states.is_empty() {
+        report.error("no_states", Some(MANIFEST_FILE), "Manifest lists no states".to_string());
+    } else if !manifest.states.contains_key("idle") {
+        report.warning("missing_idle", Some(MANIFEST_FILE), "No idle state".to_string());
+    }
+
+    let folders = persona.state_names();
+    for (state, info) in &manifest.states {
+        if !(info.fps > 0.0 && info.fps <= MAX_FPS) {
+            report.error(
+                "invalid_fps",
+                Some(MANIFEST_FILE),
+                format!("State '{}' has fps {} (expected 0-{})", state, info.fps, MAX_FPS),
+            );
+        }
+        if !folders.contains(state) {
+            report.error("missing_state", Some(state), format!("State '{}' has no folder", state));
+        }
+        if let Some(ref mode) = info.mode {
+            if !PLAY_MODES.contains(&mode.as_str()) {
+                report.error(
+                    "invalid_mode",
+                    Some(MANIFEST_FILE),
+                    format!("State '{}' has mode '{}' (expected {})", state, mode, PLAY_MODES.join(", ")),
+                );
+            }
+        }
+        if let Some(ref sound) = info.sound {
+            if persona.get(sound).is_none() {
+                report.error("missing_sound", Some(sound), format!("Sound '{}' of state '{}' not found", sound, state));
+            }
+        }
+        if let Some(ref next) = info.next {
+            if !manifest.states.contains_key(next) {
+                report.error("unknown_next", Some(MANIFEST_FILE), format!("State '{}' moves on to unknown state '{}'", state, next));
+            }
+        }
+    }
+    for folder in &folders {
+        if !manifest.states.contains_key(folder) {
+            report.warning("unlisted_state", Some(folder), format!("Folder '{}' is not in the manifest", folder));
+        }
+    }
+}
+
+/// Warn about manifest keys nothing reads, usually typos
+fn check_keys(text: &str, report: &mut Report) {
+    let Ok(table) = text.parse::<toml::Table>() else { return };
+    for key in table.keys().filter(|key| !MANIFEST_KEYS.contains(&key.as_str())) {
+        report.warning(
+            "unknown_key",
+            Some(MANIFEST_FILE),
+            format!("Unknown key '{}' (expected one of {})", key, MANIFEST_KEYS.join(", ")),
+        );
+    }
+    let Some(states) = table.get("states").and_then(toml::Value::as_table) else { return };
+    for (state, info) in states {
+        let Some(info) = info.as_table() else { continue };
+        for key in info.keys().filter(|key| !STATE_KEYS.contains(&key.as_str())) {
+            report.warning(
+                "unknown_key",
+                Some(MANIFEST_FILE),
+                format!("Unknown key '{}' in state '{}' (expected one of {})", key, state, STATE_KEYS.join(", ")),
+            );
+        }
+    }
+}
+
+/// Every `[[layers]]` part needs a folder in the persona
+fn check_layers(persona: &PersonaFiles, text: &str, report: &mut Report) {
+    let Ok(table) = text.parse::<toml::Table>() else { return };
+    let Some(layers) = table.get("layers").and_then(toml::Value::as_array) else { return };
+    for layer in layers {
+        let Some(path) = layer.get("path").and_then(toml::Value::as_str) else {
+            report.error("invalid_layer", Some(MANIFEST_FILE), "Layer has no path".to_string());
+            continue;
+        };
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        if !persona.files().any(|(file, _)| file.starts_with(&prefix)) {
+            report.error("missing_layer", Some(path), format!("Layer folder '{}' not found", path));
+        }
+    }
+}
+
+fn check_total_size(size: usize, report: &mut Report) {
+    if size > MAX_PACKAGE_SIZE {
+        report.warning(
+            "large_package",
+            None,
+            format!(
+                "Persona is {} MB (over {} MB); shrink it with `gassetsmaker optimize --max <px>`",
+                size / (1024 * 1024),
+                MAX_PACKAGE_SIZE / (1024 * 1024)
+            ),
+        );
+    }
+}
+
+fn check_still(persona: &PersonaFiles, manifest: &Manifest, report: &mut Report) {
+    let still = match manifest.still {
+        Some(ref still) => still.clone(),
+        None => match persona.top_level_images().first() {
+            Some(still) => still.to_string(),
+            None => {
+                report.error("missing_still", None, "No still image".to_string());
+                return;
+            }
+        },
+    };
+    match persona.get(&still).map(image_size) {
+        Some(Ok(size)) => check_size(&still, size, report),
+        Some(Err(e)) => report.error("invalid_image", Some(&still), e),
+        None => report.error("missing_still", Some(&still), format!("Still image '{}' not found", still)),
+    }
+}
+
+fn check_frames(persona: &PersonaFiles, manifest: &Manifest, report: &mut Report) {
+    // Frame size -> first state seen with it
+    let mut sizes: BTreeMap<(u32, u32), String> = BTreeMap::new();
+
+    for state in persona.state_names() {
+        let names = persona.frames(&state);
+        let sheet_path = format!("{}/{}", state, SHEET_META);
+        let count = if names.is_empty() {
+            match persona.get(&sheet_path).map(serde_json::from_slice::<SpriteSheet>) {
+                Some(Ok(sheet)) => {
+                    let size = (sheet.frame_width, sheet.frame_height);
+                    check_size(&sheet_path, size, report);
+                    sizes.entry(size).or_insert_with(|| state.clone());
+                    sheet.frames as usize
+                }
+                Some(Err(e)) => {
+                    report.error("invalid_sheet", Some(&sheet_path), e.to_string());
+                    continue;
+                }
+                None => {
+                    report.error("no_frames", Some(&state), format!("State '{}' has no frames", state));
+                    continue;
+                }
+            }
+        } else {
+            if let Some(gap) = first_gap(&names) {
+                report.error(
+                    "frame_gap",
+                    Some(&state),
+                    format!("Frame {} is missing; later frames will not load", gap),
+                );
+            }
+            for name in &names {
+                let path = format!("{}/{}", state, name);
+                match persona.get(&path).map(image_size) {
+                    Some(Ok(size)) => {
+                        check_size(&path, size, report);
+                        sizes.entry(size).or_insert_with(|| state.clone());
+                    }
+                    Some(Err(e)) => report.error("invalid_image", Some(&path), e),
+                    None => {}
+                }
+            }
+            names.len()
+        };
+
+        if let Some(info) = manifest.states.get(&state) {
+            if info.frames as usize != count {
+                report.warning(
+                    "frame_count_mismatch",
+                    Some(&state),
+                    format!("Manifest says {} frames, found {}", info.frames, count),
+                );
+            }
+        }
+    }
+
+    if sizes.len() > 1 {
+        let list: Vec<String> = sizes
+            .iter()
+            .map(|((w, h), state)| format!("{}x{} ({})", w, h, state))
+            .collect();
+        report.error(
+            "inconsistent_dimensions",
+            None,
+            format!("Frames differ in size: {}", list.join(", ")),
+        );
+    }
+}
+
+fn check_size(path: &str, (width, height): (u32, u32), report: &mut Report) {
+    if width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
+        report.error(
+            "oversized_texture",
+            Some(path),
+            format!("{}x{} exceeds the {}px texture limit", width, height, MAX_TEXTURE_SIZE),
+        );
+    }
+}
+
+/// First missing frame number, given sorted `frame_NNNN.png` names
+fn first_gap(names: &[&str]) -> Option<u32> {
+    for (expected, name) in (1..).zip(names) {
+        let number = name
+            .strip_prefix("frame_")
+            .and_then(|n| n.strip_suffix(".png"))
+            .and_then(|n| n.parse::<u32>().ok())?;
+        if number != expected {
+            return Some(expected);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_gap() {
+        assert_eq!(first_gap(&["frame_0001.png", "frame_0002.png"]), None);
+        assert_eq!(first_gap(&["frame_0001.png", "frame_0003.png"]), Some(2));
+        assert_eq!(first_gap(&["frame_0002.png"]), Some(1));
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let mut report = Report::default();
+        check_keys(
+            r#"
+name = "rin"
+nmae = "typo"
+
+[states.idle]
+fps = 12.0
+frames = 4
+loop = true
+
+[state_machine]
+idle_interval = [10.0, 30.0]
+"#,
+            &mut report,
+        );
+        let messages: Vec<&str> = report.issues.iter().map(|i| i.message.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("Unknown key 'nmae'"));
+        assert!(messages[1].starts_with("Unknown key 'loop' in state 'idle'"));
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut persona = PersonaFiles::default();
+        persona.insert("layers/mouth/talk/frame_0001.png", Vec::new());
+        assert!(persona.state_names().is_empty());
+
+        let mut report = Report::default();
+        check_layers(
+            &persona,
+            r#"
+[[layers]]
+path = "layers/mouth"
+
+[[layers]]
+path = "layers/eyes"
+
+[[layers]]
+anchor = "center"
+"#,
+            &mut report,
+        );
+        let codes: Vec<&str> = report.issues.iter().map(|i| i.code).collect();
+        assert_eq!(codes, ["missing_layer", "invalid_layer"]);
+    }
+}
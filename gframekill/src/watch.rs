@@ -0,0 +1,138 @@
+//! `watch` - re-run clean/validate/pack whenever a persona directory changes
+//!
+//! Point the ghost at the packed `.persona.zip` (or the directory itself) and
+//! edits show up on the live ghost once it reloads the skin.
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::args::Args;
+use crate::clean;
+use crate::optimize::Options;
+use crate::pack;
+use crate::validate;
+
+const USAGE: &str = "Usage: gassetsmaker watch <persona_dir> [--clean [clean options]] [--pack [--out <file>] [--optimize]]";
+
+/// Quiet time after the last change before running, so a save touching
+/// many files triggers one run
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// What to do on each change
+struct Steps {
+    clean: Option<clean::Settings>,
+    /// Zip destination and PNG options
+    pack: Option<(PathBuf, Option<Options>)>,
+}
+
+pub fn run(args: &Args) -> Result<(), String> {
+    let persona_dir = Path::new(args.positional(0).ok_or(USAGE)?);
+    if !persona_dir.is_dir() {
+        return Err(USAGE.to_string());
+    }
+    let persona_dir = persona_dir.canonicalize().map_err(|e| format!("{}: {}", persona_dir.display(), e))?;
+
+    let steps = Steps {
+        clean: args.has("--clean").then(|| clean::Settings::from_args(args)).transpose()?,
+        pack: if args.has("--pack") {
+            let optimize = args.has("--optimize").then(|| Options::from_args(args)).transpose()?;
+            Some((pack::output_path(&persona_dir, args)?, optimize))
+        } else {
+            None
+        },
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    watcher
+        .watch(&persona_dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("{}: {}", persona_dir.display(), e))?;
+
+    println!("Watching {} (Ctrl+C to stop)", persona_dir.display());
+    run_steps(&persona_dir, &steps, &BTreeSet::new());
+
+    loop {
+        let mut changed = BTreeSet::new();
+        // Block for the first change, then gather the rest of the burst
+        let first = rx.recv().map_err(|e| e.to_string())?;
+        collect(first, &mut changed);
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            collect(event, &mut changed);
+        }
+        if let Some((out, _)) = &steps.pack {
+            changed.remove(out);
+        }
+        if changed.is_empty() {
+            continue;
+        }
+
+        println!("\n{} files changed", changed.len());
+        run_steps(&persona_dir, &steps, &changed);
+
+        // Drop the events caused by our own writes
+        std::thread::sleep(DEBOUNCE);
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+fn collect(event: notify::Result<Event>, changed: &mut BTreeSet<PathBuf>) {
+    match event {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => changed.extend(event.paths),
+        Ok(_) => {}
+        Err(e) => eprintln!("Watch error: {}", e),
+    }
+}
+
+/// Run the configured steps; errors are reported and watching goes on
+fn run_steps(persona_dir: &Path, steps: &Steps, changed: &BTreeSet<PathBuf>) {
+    if let Some(settings) = &steps.clean {
+        for folder in changed_folders(changed) {
+            if let Err(e) = clean::clean_folder(&folder.to_string_lossy(), settings) {
+                eprintln!("Clean failed: {}", e);
+            }
+        }
+    }
+
+    let ok = match validate::check(&persona_dir.to_string_lossy()) {
+        Ok(ok) => ok,
+        Err(e) => {
+            eprintln!("Validate failed: {}", e);
+            false
+        }
+    };
+
+    if let Some((out, optimize)) = &steps.pack {
+        if !ok {
+            eprintln!("Not packing until the errors above are fixed");
+        } else if let Err(e) = pack::write(persona_dir, out, optimize.as_ref()) {
+            eprintln!("Pack failed: {}", e);
+        }
+    }
+}
+
+/// Folders holding changed PNGs
+fn changed_folders(changed: &BTreeSet<PathBuf>) -> BTreeSet<PathBuf> {
+    changed
+        .iter()
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")))
+        .filter_map(|p| p.parent().map(Path::to_path_buf))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_folders() {
+        let changed: BTreeSet<PathBuf> = ["rin/idle/frame_0001.png", "rin/idle/frame_0002.png", "rin/talk/a.PNG", "rin/config.toml"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let folders: Vec<PathBuf> = changed_folders(&changed).into_iter().collect();
+        assert_eq!(folders, vec![PathBuf::from("rin/idle"), PathBuf::from("rin/talk")]);
+    }
+}
@@ -1,21 +1,39 @@
 //! Main callout implementation
 
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
 use std::time::Duration;
 
+use ghost_ui::{NineSliceRenderer, NineSliceSkin};
 use wgpu::{Device, Queue, RenderPass, TextureFormat};
 
-use crate::shape::{CalloutShape, ShapeRenderer};
+use crate::markup::{parse_markup, StyledSpan};
+use crate::sdf::{SdfPrimitive, SdfShapeRenderer};
+use crate::shape::{progress_bar, CalloutShape};
 use crate::text::{TextAnimator, TextRenderer};
-use crate::types::{ArrowPosition, CalloutStyle, CalloutTiming, CalloutType, TextAnimation};
+use crate::types::{ArrowTarget, CalloutStyle, CalloutTiming, CalloutType, TextAnimation};
+
+/// How fast an auto-sized bubble follows its text (higher is snappier)
+const SIZE_EASE_RATE: f32 = 14.0;
+
+/// Seconds a paced callout stays up after its text is fully revealed
+const READ_AFTER_REVEAL: f32 = 1.5;
+
+/// Height of the progress bar and its gap to the text, in logical pixels
+const PROGRESS_BAR_HEIGHT: f32 = 6.0;
+const PROGRESS_BAR_GAP: f32 = 6.0;
+
+/// Sending half of a streamed callout: each message is appended to the
+/// text. Dropping every sender ends the stream.
+pub type TextStream = Sender<String>;
 
 /// A callout bubble with text and animation
 pub struct Callout {
-    /// Callout type (Talk, Think, Scream)
+    /// Callout type (Talk, Think, Scream, Whisper)
     callout_type: CalloutType,
     /// Position relative to parent [x, y]
     position: [f32; 2],
-    /// Arrow position and direction
-    arrow: ArrowPosition,
+    /// Where the arrow points
+    arrow: ArrowTarget,
     /// Maximum width before text wraps
     max_width: f32,
     /// Visual style
@@ -24,14 +42,28 @@ pub struct Callout {
     timing: CalloutTiming,
     /// Text animation style
     text_animation: TextAnimation,
+    /// Show text and bubble instantly (no typing or fading)
+    reduced_motion: bool,
 
     // Runtime state
     /// Text animator
     text_animator: Option<TextAnimator>,
+    /// Text arriving for the current callout (see `stream`)
+    stream: Option<Receiver<String>>,
+    /// Seconds the current callout stays up at least (see `say_paced`)
+    min_visible: f32,
+    /// Image drawn as the bubble instead of the shape
+    skin: Option<NineSliceSkin>,
     /// Shape renderer
-    shape_renderer: Option<ShapeRenderer>,
+    shape_renderer: Option<SdfShapeRenderer>,
+    /// Skin renderer
+    skin_renderer: Option<NineSliceRenderer>,
+    /// Whether the skin still has to be uploaded
+    skin_changed: bool,
     /// Text renderer
     text_renderer: Option<TextRenderer>,
+    /// Emphasis parsed from the callout's markup
+    spans: Vec<StyledSpan>,
     /// Current shape
     shape: Option<CalloutShape>,
     /// Current visibility state
@@ -44,6 +76,18 @@ pub struct Callout {
     scale_factor: f32,
     /// Whether shape needs regeneration (when scale factor changes)
     needs_shape_regen: bool,
+    /// Fit the bubble to the visible text and animate size changes
+    auto_size: bool,
+    /// Size measured from the visible text, in physical pixels (auto-size)
+    target_size: Option<[f32; 2]>,
+    /// Current, animated bubble size in physical pixels (auto-size)
+    bubble_size: Option<[f32; 2]>,
+    /// Size the current shape was built with
+    shape_size: Option<[f32; 2]>,
+    /// Progress (0-1) shown as a bar under the text
+    progress: Option<f32>,
+    /// The progress bar in shape coordinates, drawn over the bubble
+    progress_bar: Vec<SdfPrimitive>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -60,20 +104,33 @@ impl Callout {
         Self {
             callout_type: CalloutType::default(),
             position: [0.0, 0.0],
-            arrow: ArrowPosition::default(),
+            arrow: ArrowTarget::default(),
             max_width: 200.0,
             style: CalloutStyle::default(),
             timing: CalloutTiming::default(),
             text_animation: TextAnimation::default(),
+            reduced_motion: false,
             text_animator: None,
+            stream: None,
+            min_visible: 0.0,
+            skin: None,
             shape_renderer: None,
+            skin_renderer: None,
+            skin_changed: false,
             text_renderer: None,
+            spans: Vec::new(),
             shape: None,
             visibility: VisibilityState::Hidden,
             elapsed: 0.0,
             is_visible: false,
             scale_factor: 1.0,
             needs_shape_regen: true,
+            auto_size: false,
+            target_size: None,
+            bubble_size: None,
+            shape_size: None,
+            progress: None,
+            progress_bar: Vec::new(),
         }
     }
 
@@ -89,9 +146,9 @@ impl Callout {
         self
     }
 
-    /// Set the arrow position
-    pub fn with_arrow(mut self, arrow: ArrowPosition) -> Self {
-        self.arrow = arrow;
+    /// Set the arrow position, or a point for it to aim at
+    pub fn with_arrow(mut self, arrow: impl Into<ArrowTarget>) -> Self {
+        self.arrow = arrow.into();
         self
     }
 
@@ -131,9 +188,30 @@ impl Callout {
         self
     }
 
+    /// Fit the bubble to the visible text (up to the maximum width) and let
+    /// it grow and shrink smoothly as the text is revealed or appended
+    pub fn with_auto_size(mut self, auto_size: bool) -> Self {
+        self.auto_size = auto_size;
+        self
+    }
+
+    /// Show text and bubble instantly, ignoring the text animation and fades
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    /// Draw the bubble as a stretched image instead of a shape (no tail)
+    pub fn with_skin(mut self, skin: NineSliceSkin) -> Self {
+        self.set_skin(Some(skin));
+        self
+    }
+
     /// Initialize GPU resources
     pub fn init(&mut self, device: &Device, queue: &Queue, format: TextureFormat) {
-        self.shape_renderer = Some(ShapeRenderer::new(device, format));
+        self.shape_renderer = Some(SdfShapeRenderer::new(device, format));
+        self.skin_renderer = Some(NineSliceRenderer::new(device, format));
+        self.skin_changed = true;
         self.text_renderer = Some(TextRenderer::new(device, queue, format));
     }
 
@@ -143,6 +221,18 @@ impl Callout {
         self.show_text(text);
     }
 
+    /// Say something, revealing the text over `reveal` (e.g. while it is
+    /// spoken) and staying up at least until shortly after
+    pub fn say_paced(&mut self, text: impl Into<String>, reveal: Duration) {
+        let text = text.into();
+        self.callout_type = CalloutType::Talk;
+        let paced = self.text_animation.paced(&text, reveal.as_secs_f32());
+        let animation = std::mem::replace(&mut self.text_animation, paced);
+        self.show_text(text);
+        self.text_animation = animation;
+        self.min_visible = reveal.as_secs_f32() + READ_AFTER_REVEAL;
+    }
+
     /// Think something (thought bubble)
     pub fn think(&mut self, text: impl Into<String>) {
         self.callout_type = CalloutType::Think;
@@ -155,14 +245,85 @@ impl Callout {
         self.show_text(text);
     }
 
+    /// Whisper something (faint dashed bubble, smaller text)
+    pub fn whisper(&mut self, text: impl Into<String>) {
+        self.callout_type = CalloutType::Whisper;
+        self.show_text(text);
+    }
+
+    /// Show an empty bubble of the given type and return a sender for its
+    /// text, e.g. tokens from an LLM as they arrive. The callout doesn't
+    /// time out while text keeps coming.
+    pub fn stream(&mut self, callout_type: CalloutType) -> TextStream {
+        let (sender, receiver) = mpsc::channel();
+        self.callout_type = callout_type;
+        self.show_text(String::new());
+        self.stream = Some(receiver);
+        sender
+    }
+
+    /// Add text to the visible callout (shows it if hidden); the bubble is
+    /// re-measured and the new text revealed at the animation's pace
+    pub fn append_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.text_animator {
+            Some(ref mut animator) if self.is_visible => animator.append(text),
+            _ => {
+                // Appended text is shown as it is, without markup
+                self.show_styled(text.to_string(), Vec::new());
+                return;
+            }
+        }
+        // Keep the callout up while text keeps coming
+        self.elapsed = self.elapsed.min(self.timing.delay.as_secs_f32());
+        self.needs_shape_regen = true;
+    }
+
+    /// Whether a stream is still delivering text
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Append text received on the stream
+    fn poll_stream(&mut self) {
+        let Some(ref receiver) = self.stream else { return };
+        let mut received = String::new();
+        let finished = loop {
+            match receiver.try_recv() {
+                Ok(text) => received.push_str(&text),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        self.append_text(&received);
+        if finished {
+            // The duration counts from the end of the stream
+            self.stream = None;
+            self.elapsed = self.elapsed.min(self.timing.delay.as_secs_f32());
+        }
+    }
+
     /// Show text with current settings
     fn show_text(&mut self, text: impl Into<String>) {
-        let text = text.into();
-        self.text_animator = Some(TextAnimator::new(text, self.text_animation));
+        let (text, spans) = parse_markup(&text.into());
+        self.show_styled(text, spans);
+    }
+
+    /// Show plain text with emphasis spans
+    fn show_styled(&mut self, text: String, spans: Vec<StyledSpan>) {
+        self.spans = spans;
+        self.stream = None;
+        self.progress = None;
+        self.progress_bar.clear();
+        self.min_visible = 0.0;
+        let animation = if self.reduced_motion { TextAnimation::Instant } else { self.text_animation };
+        self.text_animator = Some(TextAnimator::new(text, animation));
         self.elapsed = 0.0;
         self.is_visible = true;
         self.visibility = if self.timing.delay.is_zero() {
-            if self.timing.fade_in.is_zero() {
+            if self.fade_in().is_zero() {
                 VisibilityState::Visible
             } else {
                 VisibilityState::FadingIn { progress: 0.0 }
@@ -173,18 +334,21 @@ impl Callout {
 
         // Mark shape for regeneration (will happen in prepare() with correct scale_factor)
         self.needs_shape_regen = true;
+        self.bubble_size = None;
     }
 
     /// Regenerate the callout shape based on current text and scale factor
     fn regenerate_shape(&mut self, scale_factor: f32) {
         // Calculate content size based on text with scale factor
+        let style = self.text_style();
         let text_height = if let (Some(ref mut text_renderer), Some(ref animator)) =
             (&mut self.text_renderer, &self.text_animator)
         {
             // Set text to get bounds - use scaled font size for accurate bounds
-            text_renderer.set_text_scaled(
+            text_renderer.set_rich_text_scaled(
                 animator.full_text(),
-                &self.style,
+                &self.spans,
+                &style,
                 self.max_width - 2.0 * self.style.padding * scale_factor,
                 scale_factor,
             );
@@ -196,30 +360,103 @@ impl Callout {
 
         // Scale width and padding for the shape
         let width = self.max_width * scale_factor;
-        let height = text_height + 2.0 * self.style.padding * scale_factor;
+        let height = text_height + 2.0 * self.style.padding * scale_factor + self.progress_height(scale_factor);
+        self.build_shape([width, height], scale_factor);
+    }
+
+    /// Measure the visible text for an auto-sized bubble, in physical pixels
+    fn measure_visible_text(&mut self, scale_factor: f32) -> [f32; 2] {
+        let padding = self.style.padding * scale_factor;
+        let max_width = self.max_width * scale_factor;
+        let style = self.text_style();
+        let (text_width, text_height) = match (&mut self.text_renderer, &self.text_animator) {
+            (Some(text_renderer), Some(animator)) => {
+                text_renderer.set_rich_text_scaled(
+                    animator.visible_text(),
+                    &self.spans,
+                    &style,
+                    max_width - 2.0 * padding,
+                    scale_factor,
+                );
+                text_renderer.bounds()
+            }
+            _ => (max_width - 2.0 * padding, 50.0 * scale_factor),
+        };
+        // Keep room for the arrow and a line of text while still empty
+        [
+            (text_width + 2.0 * padding).max(40.0 * scale_factor).min(max_width),
+            text_height.max(20.0 * scale_factor) + 2.0 * padding + self.progress_height(scale_factor),
+        ]
+    }
+
+    /// Room the progress bar takes below the text, in physical pixels
+    fn progress_height(&self, scale_factor: f32) -> f32 {
+        match self.progress {
+            Some(_) => (PROGRESS_BAR_HEIGHT + PROGRESS_BAR_GAP) * scale_factor,
+            None => 0.0,
+        }
+    }
+
+    /// Lay the progress bar out along the bottom of the current shape
+    fn build_progress_bar(&mut self) {
+        self.progress_bar = match (self.progress, self.shape_size) {
+            (Some(fraction), Some([width, height])) => {
+                let padding = self.style.padding * self.scale_factor;
+                let bar_height = PROGRESS_BAR_HEIGHT * self.scale_factor;
+                let rect = [padding, height - padding - bar_height, width - 2.0 * padding, bar_height];
+                progress_bar(rect, fraction, self.style.text_color)
+            }
+            _ => Vec::new(),
+        };
+    }
+
+    /// Style for the text, with the font sized for the callout type
+    fn text_style(&self) -> CalloutStyle {
+        CalloutStyle {
+            font_size: self.style.font_size * self.callout_type.font_scale(),
+            ..self.style.clone()
+        }
+    }
 
+    /// Build the bubble shape with the given size in physical pixels
+    fn build_shape(&mut self, [width, height]: [f32; 2], scale_factor: f32) {
         // Create scaled style for shape
         let mut scaled_style = self.style.clone();
         scaled_style.padding *= scale_factor;
         scaled_style.border_radius *= scale_factor;
         scaled_style.border_width *= scale_factor;
+        scaled_style.shadow_blur *= scale_factor;
+        scaled_style.shadow_offset = self.style.shadow_offset.map(|v| v * scale_factor);
+
+        // An aimed tail works in the shape's own physical pixels
+        let arrow = match self.arrow {
+            ArrowTarget::Point([x, y]) => ArrowTarget::Point([
+                (x - self.position[0]) * scale_factor,
+                (y - self.position[1]) * scale_factor,
+            ]),
+            arrow => arrow,
+        };
 
         self.shape = Some(CalloutShape::new(
             self.callout_type,
             width,
             height,
-            self.arrow,
+            arrow,
             &scaled_style,
         ));
 
+        self.shape_size = Some([width, height]);
         self.scale_factor = scale_factor;
         self.needs_shape_regen = false;
+        self.build_progress_bar();
     }
 
     /// Hide the callout
     pub fn hide(&mut self) {
+        // Text still streaming in is dropped
+        self.stream = None;
         if self.is_visible {
-            self.visibility = if self.timing.fade_out.is_zero() {
+            self.visibility = if self.fade_out().is_zero() {
                 self.is_visible = false;
                 VisibilityState::Hidden
             } else {
@@ -230,6 +467,7 @@ impl Callout {
 
     /// Update the callout (call every frame)
     pub fn update(&mut self, delta_seconds: f32) {
+        self.poll_stream();
         if !self.is_visible && self.visibility == VisibilityState::Hidden {
             return;
         }
@@ -245,14 +483,14 @@ impl Callout {
         match self.visibility {
             VisibilityState::Hidden => {
                 // Start fading in after delay
-                self.visibility = if self.timing.fade_in.is_zero() {
+                self.visibility = if self.fade_in().is_zero() {
                     VisibilityState::Visible
                 } else {
                     VisibilityState::FadingIn { progress: 0.0 }
                 };
             }
             VisibilityState::FadingIn { progress } => {
-                let fade_duration = self.timing.fade_in.as_secs_f32();
+                let fade_duration = self.fade_in().as_secs_f32();
                 let new_progress = progress + delta_seconds / fade_duration;
                 if new_progress >= 1.0 {
                     self.visibility = VisibilityState::Visible;
@@ -263,11 +501,12 @@ impl Callout {
                 }
             }
             VisibilityState::Visible => {
-                // Check if we should start fading out
-                if let Some(duration) = self.timing.duration {
+                // Check if we should start fading out (not while text is
+                // streaming in or progress is shown)
+                if let (Some(duration), None, None) = (self.timing.duration, &self.stream, self.progress) {
                     let visible_time = self.elapsed - self.timing.delay.as_secs_f32();
-                    if visible_time >= duration.as_secs_f32() {
-                        self.visibility = if self.timing.fade_out.is_zero() {
+                    if visible_time >= duration.as_secs_f32().max(self.min_visible) {
+                        self.visibility = if self.fade_out().is_zero() {
                             self.is_visible = false;
                             VisibilityState::Hidden
                         } else {
@@ -277,7 +516,7 @@ impl Callout {
                 }
             }
             VisibilityState::FadingOut { progress } => {
-                let fade_duration = self.timing.fade_out.as_secs_f32();
+                let fade_duration = self.fade_out().as_secs_f32();
                 let new_progress = progress + delta_seconds / fade_duration;
                 if new_progress >= 1.0 {
                     self.visibility = VisibilityState::Hidden;
@@ -294,6 +533,33 @@ impl Callout {
         if let Some(ref mut animator) = self.text_animator {
             animator.update(delta_seconds);
         }
+
+        // Let an auto-sized bubble follow its text
+        if let (Some(size), Some(target)) = (self.bubble_size, self.target_size) {
+            self.bubble_size = Some(if self.reduced_motion {
+                target
+            } else {
+                ease_size(size, target, delta_seconds)
+            });
+        }
+    }
+
+    /// Fade in duration (none with reduced motion)
+    fn fade_in(&self) -> Duration {
+        if self.reduced_motion {
+            Duration::ZERO
+        } else {
+            self.timing.fade_in
+        }
+    }
+
+    /// Fade out duration (none with reduced motion)
+    fn fade_out(&self) -> Duration {
+        if self.reduced_motion {
+            Duration::ZERO
+        } else {
+            self.timing.fade_out
+        }
     }
 
     /// Get current opacity based on visibility state
@@ -319,13 +585,19 @@ impl Callout {
             .map(|a| !a.is_complete())
             .unwrap_or(false);
 
+        // Waiting for streamed text
+        let streaming = self.stream.is_some();
+
         // Visibility fade in progress
         let visibility_animating = matches!(
             self.visibility,
             VisibilityState::FadingIn { .. } | VisibilityState::FadingOut { .. }
         );
 
-        text_animating || visibility_animating
+        // Auto-sized bubble still growing or shrinking
+        let resizing = self.auto_size && self.bubble_size != self.target_size;
+
+        text_animating || streaming || visibility_animating || resizing
     }
 
     /// Check if text animation is complete
@@ -358,23 +630,56 @@ impl Callout {
             return;
         }
 
-        // Regenerate shape if scale factor changed or shape needs regeneration
-        if self.needs_shape_regen || (self.scale_factor - scale_factor).abs() > 0.01 {
+        let scale_changed = (self.scale_factor - scale_factor).abs() > 0.01;
+        if self.auto_size {
+            // Start at the measured size, then follow it smoothly (see update)
+            let target = self.measure_visible_text(scale_factor);
+            if scale_changed {
+                self.bubble_size = None;
+            }
+            let size = *self.bubble_size.get_or_insert(target);
+            self.target_size = Some(target);
+            if self.needs_shape_regen || scale_changed || self.shape_size != Some(size) {
+                self.build_shape(size, scale_factor);
+            }
+        } else if self.needs_shape_regen || scale_changed {
+            // Regenerate shape if scale factor changed or shape needs regeneration
             self.regenerate_shape(scale_factor);
         }
 
-        // Prepare shape with scaled position
-        if let (Some(ref mut shape_renderer), Some(ref shape)) =
+        let scaled_position = [
+            self.position[0] * scale_factor,
+            self.position[1] * scale_factor,
+        ];
+        if let (Some(ref mut skin_renderer), Some(ref skin)) = (&mut self.skin_renderer, &self.skin) {
+            // Stretch the skin over the bubble
+            if std::mem::take(&mut self.skin_changed) {
+                if let Err(e) = skin_renderer.set_skin(device, queue, skin) {
+                    log::warn!("Failed to load callout skin: {}", e);
+                }
+            }
+            if let Some([width, height]) = self.shape_size {
+                let rect = [scaled_position[0], scaled_position[1], width, height];
+                skin_renderer.prepare(device, queue, rect, scale_factor, 1.0, viewport);
+            }
+            // The progress bar goes over the skin
+            if let Some(ref mut shape_renderer) = self.shape_renderer {
+                shape_renderer.prepare_primitives(device, queue, &self.progress_bar, scaled_position, viewport);
+            }
+        } else if let (Some(ref mut shape_renderer), Some(ref shape)) =
             (&mut self.shape_renderer, &self.shape)
         {
-            let scaled_position = [
-                self.position[0] * scale_factor,
-                self.position[1] * scale_factor,
-            ];
-            shape_renderer.prepare(device, queue, shape, scaled_position, viewport);
+            // Prepare shape with scaled position
+            if self.progress_bar.is_empty() {
+                shape_renderer.prepare(device, queue, shape, scaled_position, viewport);
+            } else {
+                let primitives = [shape.primitives(), &self.progress_bar].concat();
+                shape_renderer.prepare_primitives(device, queue, &primitives, scaled_position, viewport);
+            }
         }
 
         // Prepare text with scale factor for proper DPI rendering
+        let style = self.text_style();
         if let (Some(ref mut text_renderer), Some(ref animator)) =
             (&mut self.text_renderer, &self.text_animator)
         {
@@ -385,9 +690,10 @@ impl Callout {
             ];
             // Text is already scaled in set_text_scaled during regenerate_shape,
             // now just update visible text and prepare for rendering
-            text_renderer.set_text_scaled(
+            text_renderer.set_rich_text_scaled(
                 animator.visible_text(),
-                &self.style,
+                &self.spans,
+                &style,
                 self.max_width * scale_factor - 2.0 * scaled_padding,
                 scale_factor,
             );
@@ -395,7 +701,7 @@ impl Callout {
                 device,
                 queue,
                 text_position,
-                &self.style,
+                &style,
                 [viewport[0] as u32, viewport[1] as u32],
                 1.0, // Scale is already applied to font metrics
             );
@@ -408,7 +714,13 @@ impl Callout {
             return;
         }
 
-        // Render shape first
+        // Render the bubble first (the shape renderer then only holds the
+        // progress bar, if any)
+        if self.skin.is_some() {
+            if let Some(ref skin_renderer) = self.skin_renderer {
+                skin_renderer.render(render_pass);
+            }
+        }
         if let Some(ref shape_renderer) = self.shape_renderer {
             shape_renderer.render(render_pass);
         }
@@ -426,7 +738,94 @@ impl Callout {
 
     /// Set position
     pub fn set_position(&mut self, x: f32, y: f32) {
-        self.position = [x, y];
+        if self.position != [x, y] {
+            self.position = [x, y];
+            // An aimed tail has to turn toward its point again
+            if matches!(self.arrow, ArrowTarget::Point(_)) {
+                self.needs_shape_regen = true;
+            }
+        }
+    }
+
+    /// Where the arrow points
+    pub fn arrow(&self) -> ArrowTarget {
+        self.arrow
+    }
+
+    /// Change where the arrow points, e.g. after the callout window moved
+    /// relative to the mascot
+    pub fn set_arrow(&mut self, arrow: impl Into<ArrowTarget>) {
+        let arrow = arrow.into();
+        if self.arrow != arrow {
+            self.arrow = arrow;
+            self.needs_shape_regen = true;
+        }
+    }
+
+    /// Show a progress bar (0-1) under the current text, or remove it (None).
+    /// The bubble stays up while it shows progress; new text removes the bar.
+    pub fn set_progress(&mut self, progress: Option<f32>) {
+        let progress = progress.map(|p| p.clamp(0.0, 1.0));
+        if progress.is_some() != self.progress.is_some() {
+            // The bubble grows or shrinks by the bar
+            self.needs_shape_regen = true;
+        }
+        self.progress = progress;
+        self.build_progress_bar();
+    }
+
+    /// Progress shown in the bar, if any
+    pub fn progress(&self) -> Option<f32> {
+        self.progress
+    }
+
+    /// Draw the bubble as a stretched image, or as its shape again (None)
+    pub fn set_skin(&mut self, skin: Option<NineSliceSkin>) {
+        self.skin = skin;
+        self.skin_changed = true;
+    }
+
+    /// Whether a point in physical pixels lies on the visible bubble
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        let Some(ref shape) = self.shape else { return false };
+        if !self.is_visible() {
+            return false;
+        }
+        let [bx, by, width, height] = shape.bounds();
+        let left = self.position[0] * self.scale_factor + bx;
+        let top = self.position[1] * self.scale_factor + by;
+        x >= left && x <= left + width && y >= top && y <= top + height
+    }
+
+    /// Where the tail ends, in logical pixels (None without a tail or
+    /// before the bubble is laid out)
+    pub fn arrow_tip(&self) -> Option<[f32; 2]> {
+        let [x, y] = self.shape.as_ref()?.tip()?;
+        Some([
+            self.position[0] + x / self.scale_factor,
+            self.position[1] + y / self.scale_factor,
+        ])
+    }
+
+    /// Area the bubble covers with its tail and shadow in logical pixels
+    /// [x, y, width, height], e.g. to fit a window around it
+    pub fn extent(&self) -> Option<[f32; 4]> {
+        let [x, y, width, height] = self.shape.as_ref()?.bounds().map(|v| v / self.scale_factor);
+        let [mut left, mut top, mut right, mut bottom] = [x, y, x + width, y + height];
+        if self.style.shadow_color[3] > 0.0 {
+            let [dx, dy] = self.style.shadow_offset;
+            let blur = self.style.shadow_blur;
+            left = left.min(x + dx - blur);
+            top = top.min(y + dy - blur);
+            right = right.max(x + width + dx + blur);
+            bottom = bottom.max(y + height + dy + blur);
+        }
+        Some([
+            self.position[0] + left,
+            self.position[1] + top,
+            right - left,
+            bottom - top,
+        ])
     }
 
     /// Get the bounding box of the callout
@@ -443,6 +842,20 @@ impl Callout {
     }
 }
 
+/// Move a size toward its target, snapping once within half a pixel
+fn ease_size(size: [f32; 2], target: [f32; 2], delta_seconds: f32) -> [f32; 2] {
+    let t = 1.0 - (-SIZE_EASE_RATE * delta_seconds).exp();
+    let step = |from: f32, to: f32| {
+        let next = from + (to - from) * t;
+        if (to - next).abs() < 0.5 {
+            to
+        } else {
+            next
+        }
+    };
+    [step(size[0], target[0]), step(size[1], target[1])]
+}
+
 impl Default for Callout {
     fn default() -> Self {
         Self::new()
@@ -474,9 +887,15 @@ impl CalloutBuilder {
         self
     }
 
-    /// Set the arrow position
-    pub fn arrow(mut self, arrow: ArrowPosition) -> Self {
-        self.callout.arrow = arrow;
+    /// Set the arrow position, or a point for it to aim at
+    pub fn arrow(mut self, arrow: impl Into<ArrowTarget>) -> Self {
+        self.callout.arrow = arrow.into();
+        self
+    }
+
+    /// Draw the bubble as a stretched image
+    pub fn skin(mut self, skin: NineSliceSkin) -> Self {
+        self.callout.set_skin(Some(skin));
         self
     }
 
@@ -516,6 +935,18 @@ impl CalloutBuilder {
         self
     }
 
+    /// Fit the bubble to the visible text and animate size changes
+    pub fn auto_size(mut self, auto_size: bool) -> Self {
+        self.callout.auto_size = auto_size;
+        self
+    }
+
+    /// Show text and bubble instantly
+    pub fn reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.callout.reduced_motion = reduced_motion;
+        self
+    }
+
     /// Build the callout
     pub fn build(self) -> Callout {
         self.callout
@@ -533,3 +964,75 @@ impl Default for CalloutBuilder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_appends_and_holds() {
+        let mut callout = Callout::new()
+            .with_text_animation(TextAnimation::Instant)
+            .with_duration(Duration::from_secs(1));
+        let stream = callout.stream(CalloutType::Talk);
+        stream.send("Hello".to_string()).unwrap();
+        stream.send(", world".to_string()).unwrap();
+        callout.update(0.5);
+        assert_eq!(callout.visible_text(), "Hello, world");
+
+        // No timeout while the stream is open
+        callout.update(5.0);
+        assert!(callout.is_visible());
+        assert!(callout.is_streaming());
+
+        // The duration counts from the end of the stream
+        drop(stream);
+        callout.update(0.5);
+        assert!(!callout.is_streaming());
+        assert_eq!(callout.opacity(), 1.0);
+        callout.update(1.0);
+        callout.update(1.0);
+        assert!(!callout.is_visible());
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut callout = Callout::new()
+            .with_position(10.0, 10.0)
+            .with_text_animation(TextAnimation::Instant)
+            .with_delay(Duration::ZERO);
+        callout.say("Boo");
+        callout.build_shape([100.0, 50.0], 2.0);
+        assert!(callout.contains(60.0, 40.0));
+        assert!(!callout.contains(5.0, 5.0));
+        assert!(!callout.contains(400.0, 40.0));
+
+        callout.hide();
+        callout.update(10.0);
+        assert!(!callout.contains(60.0, 40.0));
+    }
+
+    #[test]
+    fn test_extent_and_tip() {
+        let mut callout = Callout::new()
+            .with_position(10.0, 10.0)
+            .with_text_animation(TextAnimation::Instant);
+        assert_eq!(callout.extent(), None);
+        callout.say("Boo");
+        callout.build_shape([100.0, 50.0], 2.0);
+
+        // Tail hangs off the bottom middle; the shadow reaches past the bubble
+        assert_eq!(callout.arrow_tip(), Some([35.0, 42.5]));
+        assert_eq!(callout.extent(), Some([8.0, 8.0, 58.0, 40.5]));
+    }
+
+    #[test]
+    fn test_ease_size() {
+        let size = ease_size([100.0, 40.0], [200.0, 40.0], 0.05);
+        assert!(size[0] > 100.0 && size[0] < 200.0);
+        assert_eq!(size[1], 40.0);
+
+        // Close enough snaps to the target
+        assert_eq!(ease_size([199.8, 40.0], [200.0, 40.0], 0.01), [200.0, 40.0]);
+    }
+}
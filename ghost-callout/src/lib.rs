@@ -3,9 +3,14 @@
 //! A crate for rendering callout bubbles with text animation for ghost-ui.
 //!
 //! ## Features
-//! - Different callout types (Talk, Think, Scream)
-//! - Configurable arrow/tail position
+//! - Different callout types (Talk, Think, Scream, Whisper)
+//! - Smooth, anti-aliased bubble edges and outlines at any DPI
+//! - Configurable arrow/tail position, or a tail aimed at a point
 //! - Text animation (typewriter, word-by-word, streaming)
+//! - Live text appended while a callout is showing (`Callout::stream`)
+//! - Inline emphasis: `*bold*`, `_italic_`, `{color:#ff0000}red{/color}`
+//! - Image bubbles stretched from a nine-slice skin (`Callout::with_skin`)
+//! - A progress bar under the text (`Callout::set_progress`)
 //! - Timing and duration control
 //!
 //! ## Example
@@ -24,11 +29,15 @@
 //! ```
 
 mod callout;
+mod markup;
+mod sdf;
 mod shape;
 mod text;
 mod types;
 
-pub use callout::{Callout, CalloutBuilder};
-pub use shape::{CalloutShape, ShapeRenderer};
+pub use callout::{Callout, CalloutBuilder, TextStream};
+pub use markup::{parse_markup, StyledSpan};
+pub use sdf::{SdfOutline, SdfPrimitive, SdfShapeRenderer};
+pub use shape::CalloutShape;
 pub use text::{TextAnimator, TextRenderer};
-pub use types::{ArrowPosition, CalloutStyle, CalloutType, TextAnimation};
+pub use types::{ArrowPosition, ArrowTarget, CalloutStyle, CalloutType, TextAnimation};
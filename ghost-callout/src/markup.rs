@@ -0,0 +1,183 @@
+//! Inline emphasis in callout text
+//!
+//! `*bold*`, `_italic_` and `{color:#ff0000}red{/color}` are turned into
+//! styled spans over the plain text; a backslash keeps a marker literal
+//! (`\*`). Markers inside words (`snake_case`, `2*3*4`) stay plain text, as
+//! do markers without a partner.
+
+use std::ops::Range;
+
+/// Style of a run of the plain text
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyledSpan {
+    /// Byte range in the plain text
+    pub range: Range<usize>,
+    pub bold: bool,
+    pub italic: bool,
+    /// Text color overriding `CalloutStyle::text_color`
+    pub color: Option<[f32; 4]>,
+}
+
+/// Split marked-up text into plain text and the styled spans within it
+pub fn parse_markup(text: &str) -> (String, Vec<StyledSpan>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut plain = String::with_capacity(text.len());
+    let mut spans: Vec<StyledSpan> = Vec::new();
+    let (mut bold, mut italic, mut color) = (false, false, None);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\\' if chars.get(i + 1).is_some_and(|&n| is_marker(n)) => {
+                i += 1;
+                push_char(&mut plain, &mut spans, chars[i], bold, italic, color);
+            }
+            '*' | '_' => {
+                let open = if c == '*' { bold } else { italic };
+                let toggles = if open { closes(&chars, i) } else { opens(&chars, i) };
+                if toggles {
+                    if c == '*' {
+                        bold = !bold;
+                    } else {
+                        italic = !italic;
+                    }
+                } else {
+                    push_char(&mut plain, &mut spans, c, bold, italic, color);
+                }
+            }
+            '{' => match parse_tag(&chars[i..]) {
+                Some((tag, len)) => {
+                    color = tag;
+                    i += len - 1;
+                }
+                None => push_char(&mut plain, &mut spans, c, bold, italic, color),
+            },
+            _ => push_char(&mut plain, &mut spans, c, bold, italic, color),
+        }
+        i += 1;
+    }
+    (plain, spans)
+}
+
+fn is_marker(c: char) -> bool {
+    matches!(c, '*' | '_' | '{' | '\\')
+}
+
+/// A marker starting emphasis: not inside a word, followed by text, and
+/// closed later on
+fn opens(chars: &[char], i: usize) -> bool {
+    let after_word = i > 0 && chars[i - 1].is_alphanumeric();
+    let before_text = chars.get(i + 1).is_some_and(|c| !c.is_whitespace() && *c != chars[i]);
+    !after_word && before_text && (i + 1..chars.len()).any(|j| chars[j] == chars[i] && closes(chars, j))
+}
+
+/// A marker ending emphasis: right after text and not inside a word
+fn closes(chars: &[char], i: usize) -> bool {
+    let after_text = i > 0 && !chars[i - 1].is_whitespace();
+    let before_word = chars.get(i + 1).is_some_and(|c| c.is_alphanumeric());
+    after_text && !before_word
+}
+
+/// `{color:#rrggbb}` (Some color) or `{/color}` (None), with the tag length
+fn parse_tag(chars: &[char]) -> Option<(Option<[f32; 4]>, usize)> {
+    let end = chars.iter().position(|&c| c == '}')?;
+    let tag: String = chars[1..end].iter().collect();
+    let tag = tag.trim();
+    if tag == "/color" || tag == "/" {
+        return Some((None, end + 1));
+    }
+    let color = parse_hex_color(tag.strip_prefix("color:")?.trim())?;
+    Some((Some(color), end + 1))
+}
+
+/// "#rgb", "#rrggbb" or "#rrggbbaa"
+fn parse_hex_color(hex: &str) -> Option<[f32; 4]> {
+    let hex = hex.strip_prefix('#')?;
+    let digits: Vec<u32> = hex.chars().map(|c| c.to_digit(16)).collect::<Option<_>>()?;
+    let channels: Vec<u32> = match digits.len() {
+        3 => digits.iter().map(|d| d * 17).chain([255]).collect(),
+        6 | 8 => digits.chunks(2).map(|p| p[0] * 16 + p[1]).chain([255]).take(4).collect(),
+        _ => return None,
+    };
+    Some([
+        channels[0] as f32 / 255.0,
+        channels[1] as f32 / 255.0,
+        channels[2] as f32 / 255.0,
+        channels[3] as f32 / 255.0,
+    ])
+}
+
+/// Add a character, extending the last span if the style continues
+fn push_char(plain: &mut String, spans: &mut Vec<StyledSpan>, c: char, bold: bool, italic: bool, color: Option<[f32; 4]>) {
+    let start = plain.len();
+    plain.push(c);
+    if !bold && !italic && color.is_none() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if last.range.end == start && last.bold == bold && last.italic == italic && last.color == color => {
+            last.range.end = plain.len();
+        }
+        _ => spans.push(StyledSpan {
+            range: start..plain.len(),
+            bold,
+            italic,
+            color,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(range: Range<usize>, bold: bool, italic: bool) -> StyledSpan {
+        StyledSpan {
+            range,
+            bold,
+            italic,
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_emphasis() {
+        let (plain, spans) = parse_markup("I *really* like _you_");
+        assert_eq!(plain, "I really like you");
+        assert_eq!(spans, vec![span(2..8, true, false), span(14..17, false, true)]);
+
+        let (plain, spans) = parse_markup("*_both_*");
+        assert_eq!(plain, "both");
+        assert_eq!(spans, vec![span(0..4, true, true)]);
+    }
+
+    #[test]
+    fn test_literal_markers() {
+        for text in ["snake_case_name", "2*3*4", "a * b", "*unclosed", "_"] {
+            let (plain, spans) = parse_markup(text);
+            assert_eq!(plain, text);
+            assert!(spans.is_empty(), "{}", text);
+        }
+        let (plain, spans) = parse_markup(r"\*not bold\*");
+        assert_eq!(plain, "*not bold*");
+        assert!(spans.is_empty());
+    }
+
+    #[test]
+    fn test_color() {
+        let (plain, spans) = parse_markup("so {color:#ff0000}hot{/color}!");
+        assert_eq!(plain, "so hot!");
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].range, 3..6);
+        assert_eq!(spans[0].color, Some([1.0, 0.0, 0.0, 1.0]));
+
+        // Unknown tags (e.g. unexpanded variables) stay as they are
+        let (plain, spans) = parse_markup("{weather} {color:red}");
+        assert_eq!(plain, "{weather} {color:red}");
+        assert!(spans.is_empty());
+
+        assert_eq!(parse_hex_color("#0f08"), None);
+        assert_eq!(parse_hex_color("#00000080"), Some([0.0, 0.0, 0.0, 128.0 / 255.0]));
+    }
+}
@@ -0,0 +1,527 @@
+//! Anti-aliased callout rendering with signed distance fields
+//!
+//! Instead of filling triangles, each part of a bubble is drawn as one quad
+//! whose fragment shader measures the distance to the part's outline. Edges
+//! get exactly one pixel of smoothing at any DPI, and the tail merges into
+//! the bubble so the border runs around both without a seam.
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, TextureFormat,
+};
+
+use ghost_ui::DynamicBuffer;
+
+use crate::shape::CalloutShape;
+
+/// Outline of a bubble part, built on its `SdfPrimitive::rect`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SdfOutline {
+    /// Rounded rectangle; a radius of half the size makes a circle
+    RoundedRect { radius: f32 },
+    /// Rectangle with `count` round bumps up to `depth` deep around it
+    Cloud { count: f32, depth: f32 },
+    /// Rectangle with `count` spikes `depth` deep around it
+    Spikes { count: f32, depth: f32 },
+}
+
+impl SdfOutline {
+    /// How far the outline reaches past its rectangle
+    fn reach(&self) -> f32 {
+        match self {
+            Self::RoundedRect { .. } => 0.0,
+            Self::Cloud { depth, .. } | Self::Spikes { depth, .. } => *depth,
+        }
+    }
+}
+
+/// One part of a bubble: an outline (plus an optional tail merged into
+/// it), filled and bordered
+#[derive(Debug, Clone, PartialEq)]
+pub struct SdfPrimitive {
+    pub outline: SdfOutline,
+    /// Rectangle [x, y, width, height] the outline is built on
+    pub rect: [f32; 4],
+    /// Triangle merged into the outline
+    pub tail: Option<[[f32; 2]; 3]>,
+    pub fill: [f32; 4],
+    pub border_color: [f32; 4],
+    /// Border width in pixels (0 = none)
+    pub border_width: f32,
+    /// Dash and gap length of a dashed border ([0, 0] = solid)
+    pub dash: [f32; 2],
+    /// How far the edge fades out, in pixels (0 = crisp; shadows)
+    pub blur: f32,
+}
+
+impl SdfPrimitive {
+    /// A filled shape without border
+    pub fn filled(outline: SdfOutline, rect: [f32; 4], fill: [f32; 4]) -> Self {
+        Self {
+            outline,
+            rect,
+            tail: None,
+            fill,
+            border_color: [0.0; 4],
+            border_width: 0.0,
+            dash: [0.0, 0.0],
+            blur: 0.0,
+        }
+    }
+
+    /// A filled circle
+    pub fn circle(center: [f32; 2], radius: f32, fill: [f32; 4]) -> Self {
+        Self::filled(
+            SdfOutline::RoundedRect { radius },
+            [center[0] - radius, center[1] - radius, 2.0 * radius, 2.0 * radius],
+            fill,
+        )
+    }
+
+    /// Merge a triangle into the outline
+    pub fn with_tail(mut self, tail: [[f32; 2]; 3]) -> Self {
+        self.tail = Some(tail);
+        self
+    }
+
+    /// Add a border
+    pub fn with_border(mut self, color: [f32; 4], width: f32) -> Self {
+        self.border_color = color;
+        self.border_width = width;
+        self
+    }
+
+    /// Break the border into dashes
+    pub fn with_dash(mut self, dash: f32, gap: f32) -> Self {
+        self.dash = [dash, gap];
+        self
+    }
+
+    /// A soft shadow of the primitive, moved by `offset`
+    pub fn shadow(&self, offset: [f32; 2], blur: f32, color: [f32; 4]) -> Self {
+        let shift = |[x, y]: [f32; 2]| [x + offset[0], y + offset[1]];
+        let [x, y, w, h] = self.rect;
+        let [x, y] = shift([x, y]);
+        // A see-through shape casts a fainter shadow
+        let mut color = color;
+        color[3] *= self.fill[3].max(self.border_color[3]);
+        Self {
+            outline: self.outline,
+            rect: [x, y, w, h],
+            tail: self.tail.map(|tail| tail.map(shift)),
+            fill: color,
+            border_color: [0.0; 4],
+            border_width: 0.0,
+            dash: [0.0, 0.0],
+            blur,
+        }
+    }
+
+    /// Area [x, y, width, height] the primitive may draw to, including the
+    /// border and a pixel for smoothing
+    pub fn quad_bounds(&self) -> [f32; 4] {
+        let margin = self.outline.reach() + self.border_width / 2.0 + self.blur + 2.0;
+        let [x, y, w, h] = self.rect;
+        let (mut left, mut top, mut right, mut bottom) = (x - margin, y - margin, x + w + margin, y + h + margin);
+        for [px, py] in self.tail.into_iter().flatten() {
+            let margin = self.border_width / 2.0 + self.blur + 2.0;
+            left = left.min(px - margin);
+            top = top.min(py - margin);
+            right = right.max(px + margin);
+            bottom = bottom.max(py + margin);
+        }
+        [left, top, right - left, bottom - top]
+    }
+
+    fn vertices(&self) -> [SdfVertex; 4] {
+        let (kind, radius, count, depth) = match self.outline {
+            SdfOutline::RoundedRect { radius } => (0.0, radius, 0.0, 0.0),
+            SdfOutline::Cloud { count, depth } => (1.0, 0.0, count, depth),
+            SdfOutline::Spikes { count, depth } => (2.0, 0.0, count, depth),
+        };
+        let [a, b, c] = self.tail.unwrap_or_default();
+        let [x, y, w, h] = self.quad_bounds();
+        [[x, y], [x + w, y], [x + w, y + h], [x, y + h]].map(|position| SdfVertex {
+            position,
+            rect: self.rect,
+            shape: [kind, radius, count, depth],
+            tail_ab: [a[0], a[1], b[0], b[1]],
+            tail_c: c,
+            fill: self.fill,
+            border: self.border_color,
+            line: [self.border_width, self.dash[0], self.dash[1], self.blur],
+        })
+    }
+}
+
+/// Quad corner carrying its primitive's parameters
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct SdfVertex {
+    /// Position [x, y] in pixels
+    position: [f32; 2],
+    /// Outline rectangle [x, y, width, height]
+    rect: [f32; 4],
+    /// [kind, corner radius, wave count, wave depth]
+    shape: [f32; 4],
+    /// First two tail corners (all corners zero for no tail)
+    tail_ab: [f32; 4],
+    /// Third tail corner
+    tail_c: [f32; 2],
+    fill: [f32; 4],
+    border: [f32; 4],
+    /// [border width, dash length, gap length, blur]
+    line: [f32; 4],
+}
+
+impl SdfVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 8] = wgpu::vertex_attr_array![
+        0 => Float32x2, 1 => Float32x4, 2 => Float32x4, 3 => Float32x4,
+        4 => Float32x2, 5 => Float32x4, 6 => Float32x4, 7 => Float32x4
+    ];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SdfVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Uniforms for SDF rendering
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct SdfUniforms {
+    /// Transform: [scale_x, scale_y, offset_x, offset_y]
+    transform: [f32; 4],
+    /// Viewport size [width, height, 0, 0]
+    viewport: [f32; 4],
+}
+
+/// GPU renderer drawing callout shapes with smooth edges
+pub struct SdfShapeRenderer {
+    pipeline: RenderPipeline,
+    uniform_buffer: Buffer,
+    bind_group: BindGroup,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    index_count: u32,
+}
+
+impl SdfShapeRenderer {
+    /// Create a new SDF shape renderer
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("SDF Shape Shader"),
+            source: wgpu::ShaderSource::Wgsl(SDF_SHADER.into()),
+        });
+
+        let bind_group_layout: BindGroupLayout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("SDF Shape Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("SDF Shape Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("SDF Shape Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[SdfVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("SDF Shape Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[SdfUniforms {
+                transform: [1.0, 1.0, 0.0, 0.0],
+                viewport: [800.0, 600.0, 0.0, 0.0],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("SDF Shape Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            vertex_buffer: DynamicBuffer::new("SDF Shape Vertex Buffer", wgpu::BufferUsages::VERTEX),
+            index_buffer: DynamicBuffer::new("SDF Shape Index Buffer", wgpu::BufferUsages::INDEX),
+            index_count: 0,
+        }
+    }
+
+    /// Prepare the renderer with a shape
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        shape: &CalloutShape,
+        position: [f32; 2],
+        viewport: [f32; 2],
+    ) {
+        self.prepare_primitives(device, queue, shape.primitives(), position, viewport);
+    }
+
+    /// Prepare the renderer with loose primitives, in order back to front
+    pub fn prepare_primitives(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        primitives: &[SdfPrimitive],
+        position: [f32; 2],
+        viewport: [f32; 2],
+    ) {
+        let uniforms = SdfUniforms {
+            transform: [1.0, 1.0, position[0], position[1]],
+            viewport: [viewport[0], viewport[1], 0.0, 0.0],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let mut vertices = Vec::with_capacity(primitives.len() * 4);
+        let mut indices: Vec<u16> = Vec::with_capacity(primitives.len() * 6);
+        for primitive in primitives {
+            let start = vertices.len() as u16;
+            vertices.extend(primitive.vertices());
+            indices.extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+        }
+
+        self.index_count = indices.len() as u32;
+        if indices.is_empty() {
+            return;
+        }
+
+        self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&vertices));
+        self.index_buffer.write(device, queue, bytemuck::cast_slice(&indices));
+    }
+
+    /// Render the prepared shape
+    pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.index_count == 0 {
+            return;
+        }
+        if let (Some(vertex_buffer), Some(index_buffer)) = (self.vertex_buffer.buffer(), self.index_buffer.buffer()) {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+        }
+    }
+}
+
+// Branch-free on purpose: dpdx/dpdy need uniform control flow
+const SDF_SHADER: &str = r#"
+struct Uniforms {
+    transform: vec4<f32>,  // scale_x, scale_y, offset_x, offset_y
+    viewport: vec4<f32>,   // width, height, 0, 0
+}
+
+@group(0) @binding(0)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) rect: vec4<f32>,
+    @location(2) shape: vec4<f32>,
+    @location(3) tail_ab: vec4<f32>,
+    @location(4) tail_c: vec2<f32>,
+    @location(5) fill: vec4<f32>,
+    @location(6) border: vec4<f32>,
+    @location(7) line: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) @interpolate(flat) rect: vec4<f32>,
+    @location(2) @interpolate(flat) shape: vec4<f32>,
+    @location(3) @interpolate(flat) tail_ab: vec4<f32>,
+    @location(4) @interpolate(flat) tail_c: vec2<f32>,
+    @location(5) @interpolate(flat) fill: vec4<f32>,
+    @location(6) @interpolate(flat) border: vec4<f32>,
+    @location(7) @interpolate(flat) line: vec4<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let world_pos = in.position * uniforms.transform.xy + uniforms.transform.zw;
+    let clip_x = (world_pos.x / uniforms.viewport.x) * 2.0 - 1.0;
+    let clip_y = 1.0 - (world_pos.y / uniforms.viewport.y) * 2.0;
+
+    out.clip_position = vec4<f32>(clip_x, clip_y, 0.0, 1.0);
+    out.local = in.position;
+    out.rect = in.rect;
+    out.shape = in.shape;
+    out.tail_ab = in.tail_ab;
+    out.tail_c = in.tail_c;
+    out.fill = in.fill;
+    out.border = in.border;
+    out.line = in.line;
+    return out;
+}
+
+fn sd_box(p: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let d = abs(p) - half_size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+fn sd_triangle(p: vec2<f32>, p0: vec2<f32>, p1: vec2<f32>, p2: vec2<f32>) -> f32 {
+    let e0 = p1 - p0;
+    let e1 = p2 - p1;
+    let e2 = p0 - p2;
+    let v0 = p - p0;
+    let v1 = p - p1;
+    let v2 = p - p2;
+    let pq0 = v0 - e0 * clamp(dot(v0, e0) / dot(e0, e0), 0.0, 1.0);
+    let pq1 = v1 - e1 * clamp(dot(v1, e1) / dot(e1, e1), 0.0, 1.0);
+    let pq2 = v2 - e2 * clamp(dot(v2, e2) / dot(e2, e2), 0.0, 1.0);
+    let s = sign(e0.x * e2.y - e0.y * e2.x);
+    let d = min(min(vec2<f32>(dot(pq0, pq0), s * (v0.x * e0.y - v0.y * e0.x)),
+                    vec2<f32>(dot(pq1, pq1), s * (v1.x * e1.y - v1.y * e1.x))),
+                    vec2<f32>(dot(pq2, pq2), s * (v2.x * e2.y - v2.y * e2.x)));
+    return -sqrt(d.x) * sign(d.y);
+}
+
+// Distance walked clockwise around the rectangle from its top-left corner
+// to the edge point nearest to p, where bumps and spikes are laid out
+fn perimeter_pos(p: vec2<f32>, size: vec2<f32>) -> f32 {
+    let q = clamp(p, vec2<f32>(0.0), size);
+    let top = q.y;
+    let right = size.x - q.x;
+    let bottom = size.y - q.y;
+    let left = q.x;
+    let nearest = min(min(top, right), min(bottom, left));
+    var s = 2.0 * (size.x + size.y) - q.y;
+    s = select(s, 2.0 * size.x + size.y - q.x, nearest == bottom);
+    s = select(s, size.x + q.y, nearest == right);
+    s = select(s, q.x, nearest == top);
+    return s;
+}
+
+fn body_distance(p: vec2<f32>, rect: vec4<f32>, shape: vec4<f32>) -> f32 {
+    let size = rect.zw;
+    let local = p - rect.xy;
+    let centered = local - size * 0.5;
+
+    let radius = min(shape.y, min(size.x, size.y) * 0.5);
+    let rounded = sd_box(centered, size * 0.5 - vec2<f32>(radius)) - radius;
+
+    let phase = perimeter_pos(local, size) / (2.0 * (size.x + size.y)) * shape.z;
+    let bumps = shape.w * (sin(phase * 6.2831853) * 0.5 + 0.5);
+    let spikes = shape.w * abs(2.0 * fract(phase) - 1.0);
+    let wavy = sd_box(centered, size * 0.5) - select(spikes, bumps, shape.x < 1.5);
+
+    return select(wavy, rounded, shape.x < 0.5);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let body = body_distance(in.local, in.rect, in.shape);
+    let tail = sd_triangle(in.local, in.tail_ab.xy, in.tail_ab.zw, in.tail_c);
+    let tail_edge = in.tail_ab.zw - in.tail_ab.xy;
+    let tail_side = in.tail_c - in.tail_ab.xy;
+    let has_tail = abs(tail_edge.x * tail_side.y - tail_edge.y * tail_side.x) > 0.0001;
+    let d = select(body, min(body, tail), has_tail);
+
+    // Distance in pixels, also where bumps and spikes stretch the field
+    let gradient = length(vec2<f32>(dpdx(d), dpdy(d)));
+    let dist = d / max(gradient, 0.0001);
+
+    // Dashes run along the rectangle's perimeter
+    let period = in.line.y + in.line.z;
+    let along = perimeter_pos(in.local - in.rect.xy, in.rect.zw);
+    let gap = period > 0.0 && fract(along / max(period, 0.0001)) * period > in.line.y;
+
+    // Shadows fade over their blur, everything else over a pixel
+    let softness = max(in.line.w, 0.5);
+    let fill_cover = 1.0 - smoothstep(-softness, softness, dist);
+    let border_cover = clamp(0.5 - (abs(dist) - in.line.x * 0.5), 0.0, 1.0)
+        * select(1.0, 0.0, gap) * step(0.0001, in.line.x);
+
+    // Border over fill, premultiplied
+    let fill = vec4<f32>(in.fill.rgb * in.fill.a, in.fill.a) * fill_cover;
+    let border = vec4<f32>(in.border.rgb * in.border.a, in.border.a) * border_cover;
+    let color = border + fill * (1.0 - border.a);
+    return vec4<f32>(color.rgb / max(color.a, 0.0001), color.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quad_bounds() {
+        let bubble = SdfPrimitive::filled(SdfOutline::RoundedRect { radius: 10.0 }, [0.0, 0.0, 100.0, 50.0], [1.0; 4])
+            .with_border([0.0, 0.0, 0.0, 1.0], 4.0);
+        assert_eq!(bubble.quad_bounds(), [-4.0, -4.0, 108.0, 58.0]);
+
+        // The tail reaches below the bubble
+        let talk = bubble.with_tail([[40.0, 50.0], [50.0, 70.0], [60.0, 50.0]]);
+        assert_eq!(talk.quad_bounds(), [-4.0, -4.0, 108.0, 78.0]);
+
+        let cloud = SdfPrimitive::filled(SdfOutline::Cloud { count: 5.0, depth: 4.5 }, [0.0, 0.0, 100.0, 50.0], [1.0; 4]);
+        assert_eq!(cloud.quad_bounds(), [-6.5, -6.5, 113.0, 63.0]);
+    }
+
+    #[test]
+    fn test_shadow() {
+        let bubble = SdfPrimitive::filled(SdfOutline::RoundedRect { radius: 10.0 }, [0.0, 0.0, 100.0, 50.0], [1.0, 1.0, 1.0, 0.5])
+            .with_border([0.0, 0.0, 0.0, 1.0], 4.0)
+            .with_tail([[40.0, 50.0], [50.0, 70.0], [60.0, 50.0]]);
+        let shadow = bubble.shadow([2.0, 3.0], 4.0, [0.0, 0.0, 0.0, 0.2]);
+        assert_eq!(shadow.rect, [2.0, 3.0, 100.0, 50.0]);
+        assert_eq!(shadow.tail, Some([[42.0, 53.0], [52.0, 73.0], [62.0, 53.0]]));
+        assert_eq!(shadow.border_width, 0.0);
+        assert_eq!(shadow.quad_bounds(), [-4.0, -3.0, 112.0, 82.0]);
+    }
+}
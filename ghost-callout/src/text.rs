@@ -1,11 +1,13 @@
 //! Text rendering and animation for callouts
 
 use glyphon::{
-    Attrs, Buffer, Color, Family, FontSystem, Metrics, Resolution, Shaping,
-    SwashCache, TextArea, TextAtlas, TextBounds, TextRenderer as GlyphonTextRenderer,
+    Attrs, Buffer, Color, Family, FontSystem, Metrics, Resolution, Shaping, Style,
+    SwashCache, TextArea, TextAtlas, TextBounds, TextRenderer as GlyphonTextRenderer, Weight,
 };
+use ghost_ui::fonts;
 use wgpu::{Device, MultisampleState, Queue, RenderPass, TextureFormat};
 
+use crate::markup::StyledSpan;
 use crate::types::{CalloutStyle, TextAnimation};
 
 /// Text animator that handles progressive text reveal
@@ -111,15 +113,7 @@ impl TextAnimator {
                 let mut time_consumed = 0.0;
 
                 for (i, c) in self.full_text.chars().enumerate() {
-                    let char_duration = if c.is_whitespace() {
-                        0.5 / cps // Faster for spaces
-                    } else if c == '.' || c == ',' || c == '!' || c == '?' {
-                        2.0 / cps // Slower for punctuation
-                    } else {
-                        1.0 / cps
-                    };
-
-                    time_consumed += char_duration;
+                    time_consumed += stream_char_duration(c, cps);
                     if time_consumed <= self.elapsed {
                         actual_chars = i + 1;
                     } else {
@@ -174,6 +168,43 @@ impl TextAnimator {
         self.reset();
     }
 
+    /// Add text to the end, e.g. tokens streamed from an LLM. Already
+    /// visible text stays; the new text is revealed at the animation's pace.
+    pub fn append(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if self.is_complete {
+            // Continue from what is visible instead of from where time ran out
+            self.elapsed = self.reveal_time(self.visible_chars);
+        }
+        self.full_text.push_str(text);
+        self.word_boundaries = Self::compute_word_boundaries(&self.full_text);
+        if matches!(self.animation, TextAnimation::Instant) {
+            self.visible_chars = self.full_text.chars().count();
+        } else {
+            self.is_complete = false;
+        }
+    }
+
+    /// Seconds the animation takes to reveal the first `chars` characters
+    fn reveal_time(&self, chars: usize) -> f32 {
+        match self.animation {
+            TextAnimation::Instant => 0.0,
+            TextAnimation::Typewriter { cps } => chars as f32 / cps,
+            TextAnimation::WordByWord { wps } => {
+                let words = self.word_boundaries.iter().filter(|&&b| b <= chars).count();
+                words as f32 / wps
+            }
+            TextAnimation::Stream { cps } => self
+                .full_text
+                .chars()
+                .take(chars)
+                .map(|c| stream_char_duration(c, cps))
+                .sum(),
+        }
+    }
+
     /// Skip to end of animation
     pub fn skip(&mut self) {
         self.visible_chars = self.full_text.chars().count();
@@ -191,20 +222,60 @@ impl TextAnimator {
     }
 }
 
+/// Seconds a character takes in `TextAnimation::Stream`: faster on spaces,
+/// slower on punctuation
+fn stream_char_duration(c: char, cps: f32) -> f32 {
+    if c.is_whitespace() {
+        0.5 / cps
+    } else if c == '.' || c == ',' || c == '!' || c == '?' {
+        2.0 / cps
+    } else {
+        1.0 / cps
+    }
+}
+
+/// Font attributes of a styled span
+fn span_attrs<'a>(span: &StyledSpan, attrs: Attrs<'a>) -> Attrs<'a> {
+    let mut attrs = attrs;
+    if span.bold {
+        attrs = attrs.weight(Weight::BOLD);
+    }
+    if span.italic {
+        attrs = attrs.style(Style::Italic);
+    }
+    if let Some([r, g, b, a]) = span.color {
+        attrs = attrs.color(Color::rgba((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, (a * 255.0) as u8));
+    }
+    attrs
+}
+
 /// Text renderer using glyphon
 pub struct TextRenderer {
     font_system: FontSystem,
+    /// Family of the color emoji font
+    emoji_family: Option<String>,
     swash_cache: SwashCache,
     atlas: TextAtlas,
     renderer: GlyphonTextRenderer,
     buffer: Buffer,
     line_height: f32,
+    /// What `buffer` was last shaped from, to skip reshaping unchanged text
+    shaped: Option<ShapedKey>,
+}
+
+/// Inputs that change how callout text is shaped (colors are applied later)
+#[derive(PartialEq)]
+struct ShapedKey {
+    text: String,
+    spans: Vec<StyledSpan>,
+    font_size: f32,
+    max_width: f32,
 }
 
 impl TextRenderer {
     /// Create a new text renderer
     pub fn new(device: &Device, queue: &Queue, format: TextureFormat) -> Self {
-        let mut font_system = FontSystem::new();
+        let (mut font_system, emoji_family) = fonts::load_fonts();
         let swash_cache = SwashCache::new();
         let mut atlas = TextAtlas::new(device, queue, format);
         let renderer = GlyphonTextRenderer::new(&mut atlas, device, MultisampleState::default(), None);
@@ -213,11 +284,13 @@ impl TextRenderer {
 
         Self {
             font_system,
+            emoji_family,
             swash_cache,
             atlas,
             renderer,
             buffer,
             line_height: 20.0,
+            shaped: None,
         }
     }
 
@@ -228,7 +301,31 @@ impl TextRenderer {
 
     /// Set the text content and style with scale factor applied to font metrics
     pub fn set_text_scaled(&mut self, text: &str, style: &CalloutStyle, max_width: f32, scale_factor: f32) {
+        self.set_rich_text_scaled(text, &[], style, max_width, scale_factor);
+    }
+
+    /// Set text with styled spans (see `parse_markup`); spans reaching past
+    /// the end of `text` are cut off, so a partly revealed text keeps its styles
+    pub fn set_rich_text_scaled(
+        &mut self,
+        text: &str,
+        spans: &[StyledSpan],
+        style: &CalloutStyle,
+        max_width: f32,
+        scale_factor: f32,
+    ) {
         let scaled_font_size = style.font_size * scale_factor;
+        let key = ShapedKey {
+            text: text.to_string(),
+            spans: spans.to_vec(),
+            font_size: scaled_font_size,
+            max_width,
+        };
+        if self.shaped.as_ref() == Some(&key) {
+            return;
+        }
+        self.shaped = Some(key);
+
         let line_height = scaled_font_size * 1.2;
         self.line_height = line_height;
         let metrics = Metrics::new(scaled_font_size, line_height);
@@ -236,7 +333,26 @@ impl TextRenderer {
         self.buffer.set_size(&mut self.font_system, max_width, f32::MAX);
 
         let attrs = Attrs::new().family(Family::SansSerif);
-        self.buffer.set_text(&mut self.font_system, text, attrs, Shaping::Advanced);
+        let emoji = self.emoji_family.as_deref();
+
+        // Plain runs between the styled spans, emoji set in the emoji font
+        let mut runs = Vec::with_capacity(spans.len() * 2 + 1);
+        let mut end = 0;
+        for span in spans {
+            let start = span.range.start.min(text.len());
+            let span_end = span.range.end.min(text.len());
+            if start > end {
+                runs.extend(fonts::with_emoji(&text[end..start], attrs, emoji));
+            }
+            if span_end > start {
+                runs.extend(fonts::with_emoji(&text[start..span_end], span_attrs(span, attrs), emoji));
+            }
+            end = end.max(span_end);
+        }
+        if end < text.len() {
+            runs.extend(fonts::with_emoji(&text[end..], attrs, emoji));
+        }
+        self.buffer.set_rich_text(&mut self.font_system, runs, Shaping::Advanced);
     }
 
     /// Get the computed text bounds
@@ -354,4 +470,26 @@ mod tests {
         assert_eq!(animator.visible_text(), "Hello World");
         assert!(animator.is_complete());
     }
+
+    #[test]
+    fn test_text_animator_append() {
+        let mut animator = TextAnimator::new("Hi", TextAnimation::Typewriter { cps: 10.0 });
+        animator.update(1.0);
+        assert!(animator.is_complete());
+
+        // Waiting for tokens doesn't make the new text appear at once
+        animator.append(" there");
+        assert!(!animator.is_complete());
+        assert_eq!(animator.visible_text(), "Hi");
+        animator.update(0.1);
+        assert_eq!(animator.visible_text(), "Hi ");
+        animator.update(1.0);
+        assert_eq!(animator.visible_text(), "Hi there");
+        assert!(animator.is_complete());
+
+        let mut instant = TextAnimator::new("Hi", TextAnimation::Instant);
+        instant.append("!");
+        assert_eq!(instant.visible_text(), "Hi!");
+        assert!(instant.is_complete());
+    }
 }
@@ -27,6 +27,15 @@ pub enum CalloutType {
     /// \/\/\/\/\/
     /// ```
     Scream,
+
+    /// Quiet aside: faint bubble with a dashed outline and smaller text
+    /// ```text
+    /// ╭╌╌╌╌╌╌╌╌╌╮
+    /// ┆  psst…  ┆
+    /// ╰╌╌╌╌╌╌╌╌╌╯
+    ///   ·
+    /// ```
+    Whisper,
 }
 
 impl Default for CalloutType {
@@ -35,6 +44,16 @@ impl Default for CalloutType {
     }
 }
 
+impl CalloutType {
+    /// Font size relative to `CalloutStyle::font_size`
+    pub fn font_scale(&self) -> f32 {
+        match self {
+            Self::Whisper => 0.8,
+            Self::Talk | Self::Think | Self::Scream => 1.0,
+        }
+    }
+}
+
 /// Position of the arrow/tail on the callout bubble
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ArrowPosition {
@@ -74,6 +93,100 @@ impl ArrowPosition {
     pub fn is_vertical(&self) -> bool {
         matches!(self, Self::Left(_) | Self::Right(_))
     }
+
+    /// The arrow of a bubble mirrored left to right (`horizontal`) and/or
+    /// top to bottom (`vertical`)
+    pub fn mirrored(self, horizontal: bool, vertical: bool) -> Self {
+        let flip = |p: f32, mirror: bool| if mirror { 1.0 - p } else { p };
+        match self {
+            Self::Bottom(p) if vertical => Self::Top(flip(p, horizontal)),
+            Self::Top(p) if vertical => Self::Bottom(flip(p, horizontal)),
+            Self::Bottom(p) => Self::Bottom(flip(p, horizontal)),
+            Self::Top(p) => Self::Top(flip(p, horizontal)),
+            Self::Left(p) if horizontal => Self::Right(flip(p, vertical)),
+            Self::Right(p) if horizontal => Self::Left(flip(p, vertical)),
+            Self::Left(p) => Self::Left(flip(p, vertical)),
+            Self::Right(p) => Self::Right(flip(p, vertical)),
+            Self::None => Self::None,
+        }
+    }
+}
+
+/// Where the arrow/tail of the callout points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArrowTarget {
+    /// A fixed spot on one edge
+    Edge(ArrowPosition),
+    /// Aim at a point [x, y], e.g. the mascot's mouth. The tail leaves the
+    /// edge facing the point and follows it as the bubble moves or resizes.
+    /// A `Callout` takes the point in its parent's coordinates (like its
+    /// position), a `CalloutShape` relative to the bubble's top-left corner.
+    Point([f32; 2]),
+}
+
+impl Default for ArrowTarget {
+    fn default() -> Self {
+        Self::Edge(ArrowPosition::default())
+    }
+}
+
+impl From<ArrowPosition> for ArrowTarget {
+    fn from(arrow: ArrowPosition) -> Self {
+        Self::Edge(arrow)
+    }
+}
+
+impl ArrowTarget {
+    /// The point aimed at, if any
+    pub fn point(&self) -> Option<[f32; 2]> {
+        match self {
+            Self::Point(point) => Some(*point),
+            Self::Edge(_) => None,
+        }
+    }
+
+    /// The target mirrored like `ArrowPosition::mirrored`; an aimed point
+    /// stays, as the tail turns toward it anyway
+    pub fn mirrored(self, horizontal: bool, vertical: bool) -> Self {
+        match self {
+            Self::Edge(arrow) => Self::Edge(arrow.mirrored(horizontal, vertical)),
+            point => point,
+        }
+    }
+
+    /// The edge and spot the tail leaves a `width` x `height` bubble from,
+    /// keeping it `margin` pixels clear of the corners. A point over the
+    /// bubble itself gets no tail.
+    pub fn resolve(&self, width: f32, height: f32, margin: f32) -> ArrowPosition {
+        let [x, y] = match self {
+            Self::Edge(arrow) => return *arrow,
+            Self::Point(point) => *point,
+        };
+        // How far the point lies beyond each pair of edges
+        let beyond_x = (-x).max(x - width).max(0.0);
+        let beyond_y = (-y).max(y - height).max(0.0);
+        if beyond_x == 0.0 && beyond_y == 0.0 {
+            return ArrowPosition::None;
+        }
+        let along = |value: f32, length: f32| {
+            if length <= 2.0 * margin {
+                0.5
+            } else {
+                value.clamp(margin, length - margin) / length
+            }
+        };
+        if beyond_y >= beyond_x {
+            if y > height {
+                ArrowPosition::Bottom(along(x, width))
+            } else {
+                ArrowPosition::Top(along(x, width))
+            }
+        } else if x > width {
+            ArrowPosition::Right(along(y, height))
+        } else {
+            ArrowPosition::Left(along(y, height))
+        }
+    }
 }
 
 /// Text animation style
@@ -122,6 +235,26 @@ impl TextAnimation {
     pub fn stream(cps: f32) -> Self {
         Self::Stream { cps }
     }
+
+    /// The same kind of animation, sped up or slowed down to reveal `text`
+    /// in `seconds` (e.g. the length of the spoken text). Streaming becomes
+    /// an even typewriter, as speech has no pauses to follow.
+    pub fn paced(self, text: &str, seconds: f32) -> Self {
+        if seconds <= 0.0 {
+            return self;
+        }
+        match self {
+            Self::Instant => Self::Instant,
+            Self::Typewriter { .. } | Self::Stream { .. } => {
+                let chars = text.chars().count().max(1);
+                Self::Typewriter { cps: chars as f32 / seconds }
+            }
+            Self::WordByWord { .. } => {
+                let words = text.split_whitespace().count().max(1);
+                Self::WordByWord { wps: words as f32 / seconds }
+            }
+        }
+    }
 }
 
 /// Visual style for the callout
@@ -141,11 +274,11 @@ pub struct CalloutStyle {
     pub padding: f32,
     /// Corner radius for rounded rectangles
     pub border_radius: f32,
-    /// Shadow blur radius (0 for no shadow)
+    /// Shadow blur radius (0 for a hard-edged shadow)
     pub shadow_blur: f32,
     /// Shadow offset [x, y]
     pub shadow_offset: [f32; 2],
-    /// Shadow color [r, g, b, a]
+    /// Shadow color [r, g, b, a] (transparent for no shadow)
     pub shadow_color: [f32; 4],
 }
 
@@ -185,6 +318,39 @@ impl CalloutStyle {
             ..Default::default()
         }
     }
+
+    /// Switch the text to black or white if it contrasts less than `ratio`
+    /// (WCAG contrast ratio, 1-21) with the background
+    pub fn with_min_contrast(mut self, ratio: f32) -> Self {
+        if contrast_ratio(self.text_color, self.background) < ratio {
+            let black = [0.0, 0.0, 0.0, 1.0];
+            let white = [1.0, 1.0, 1.0, 1.0];
+            self.text_color = if contrast_ratio(black, self.background) >= contrast_ratio(white, self.background) {
+                black
+            } else {
+                white
+            };
+        }
+        self
+    }
+}
+
+/// WCAG contrast ratio between two colors (alpha ignored)
+fn contrast_ratio(a: [f32; 4], b: [f32; 4]) -> f32 {
+    let (la, lb) = (relative_luminance(a), relative_luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+/// WCAG relative luminance of an sRGB color
+fn relative_luminance(color: [f32; 4]) -> f32 {
+    let linear = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color[0]) + 0.7152 * linear(color[1]) + 0.0722 * linear(color[2])
 }
 
 /// Configuration for callout timing
@@ -228,3 +394,61 @@ impl CalloutTiming {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paced() {
+        assert_eq!(TextAnimation::stream(30.0).paced("Hello there", 2.0), TextAnimation::typewriter(5.5));
+        assert_eq!(TextAnimation::word_by_word(3.0).paced("one two three four", 2.0), TextAnimation::word_by_word(2.0));
+        assert_eq!(TextAnimation::Instant.paced("Hello", 2.0), TextAnimation::Instant);
+        assert_eq!(TextAnimation::typewriter(30.0).paced("Hello", 0.0), TextAnimation::typewriter(30.0));
+    }
+
+    #[test]
+    fn test_arrow_target_resolve() {
+        let edge = ArrowTarget::from(ArrowPosition::Left(0.3));
+        assert_eq!(edge.resolve(200.0, 100.0, 20.0), ArrowPosition::Left(0.3));
+
+        // Below the bubble, then far off to the left
+        assert_eq!(ArrowTarget::Point([50.0, 180.0]).resolve(200.0, 100.0, 20.0), ArrowPosition::Bottom(0.25));
+        assert_eq!(ArrowTarget::Point([-300.0, 180.0]).resolve(200.0, 100.0, 20.0), ArrowPosition::Left(0.8));
+        // Clamped clear of the corners
+        assert_eq!(ArrowTarget::Point([-10.0, -60.0]).resolve(200.0, 100.0, 20.0), ArrowPosition::Top(0.1));
+        assert_eq!(ArrowTarget::Point([100.0, 50.0]).resolve(200.0, 100.0, 20.0), ArrowPosition::None);
+    }
+
+    #[test]
+    fn test_arrow_mirrored() {
+        assert_eq!(ArrowPosition::Bottom(0.25).mirrored(false, true), ArrowPosition::Top(0.25));
+        assert_eq!(ArrowPosition::Bottom(0.25).mirrored(true, false), ArrowPosition::Bottom(0.75));
+        assert_eq!(ArrowPosition::Left(0.2).mirrored(true, true), ArrowPosition::Right(0.8));
+        assert_eq!(ArrowPosition::None.mirrored(true, true), ArrowPosition::None);
+        assert_eq!(ArrowTarget::Point([5.0, 6.0]).mirrored(true, true), ArrowTarget::Point([5.0, 6.0]));
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = [0.0, 0.0, 0.0, 1.0];
+        let white = [1.0, 1.0, 1.0, 1.0];
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(white, white) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_with_min_contrast() {
+        // Grey on white is raised to black
+        let style = CalloutStyle::with_colors([1.0, 1.0, 1.0, 1.0], [0.6, 0.6, 0.6, 1.0]).with_min_contrast(7.0);
+        assert_eq!(style.text_color, [0.0, 0.0, 0.0, 1.0]);
+
+        // Light grey on dark is raised to white
+        let style = CalloutStyle::with_colors([0.1, 0.1, 0.1, 1.0], [0.5, 0.5, 0.5, 1.0]).with_min_contrast(7.0);
+        assert_eq!(style.text_color, [1.0, 1.0, 1.0, 1.0]);
+
+        // Enough contrast already: unchanged
+        let style = CalloutStyle::dark().with_min_contrast(7.0);
+        assert_eq!(style.text_color, CalloutStyle::dark().text_color);
+    }
+}
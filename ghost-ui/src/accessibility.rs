@@ -0,0 +1,427 @@
+//! Screen reader support through AccessKit
+//!
+//! The ghost window's widgets, and text the app wants read out (e.g. what
+//! the callout bubble says), are exposed as an accessibility tree, so
+//! VoiceOver, NVDA and Orca can read them and press buttons.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use accesskit::{
+    Action, ActionData, ActionHandler, ActionRequest, Checked, DefaultActionVerb, Live, Node, NodeBuilder,
+    NodeClassSet, NodeId, Rect, Role, Tree, TreeUpdate,
+};
+use tao::window::Window;
+
+use crate::renderer::Widgets;
+use crate::widget::{ButtonId, SliderId, TextInputId, ToggleId, ToggleKind, Widget};
+use crate::window::ScreenRect;
+
+const ROOT_ID: NodeId = NodeId(0);
+
+/// Kinds of nodes, in the high bits of their ids (the low bits hold the
+/// widget's id, or its index for widgets without one)
+const BUTTON: u64 = 1 << 32;
+const TOGGLE: u64 = 2 << 32;
+const SLIDER: u64 = 3 << 32;
+const TEXT_INPUT: u64 = 4 << 32;
+const PROGRESS_BAR: u64 = 5 << 32;
+const TEXT: u64 = 6 << 32;
+
+/// Share of a slider's range an increment or decrement moves it, when it
+/// has no step
+const SLIDER_NUDGE: f32 = 0.1;
+
+/// What an assistive technology asked the window to do
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AccessAction {
+    Click(ButtonId),
+    Focus(ButtonId),
+    Toggle(ToggleId),
+    SetSlider(SliderId, f32),
+    /// Move a slider one step up (true) or down
+    NudgeSlider(SliderId, bool),
+    FocusInput(TextInputId),
+    SetText(TextInputId, String),
+}
+
+/// Sends requests to the event loop, as adapters may call it on any thread
+struct Forwarder(Sender<ActionRequest>);
+
+impl ActionHandler for Forwarder {
+    fn do_action(&mut self, request: ActionRequest) {
+        let _ = self.0.send(request);
+    }
+}
+
+/// A window's accessibility tree and the platform adapter it is sent to
+pub(crate) struct Accessibility {
+    adapter: platform::Adapter,
+    requests: Receiver<ActionRequest>,
+    title: String,
+    classes: NodeClassSet,
+    /// Nodes and focus last sent, to skip unchanged updates
+    sent: Option<(Vec<(NodeId, Node)>, NodeId)>,
+    focused: bool,
+    bounds: Option<ScreenRect>,
+}
+
+impl Accessibility {
+    /// Attach to `window`; must be done before it is first shown
+    pub(crate) fn new(window: &Window, title: &str) -> Self {
+        let (sender, requests) = mpsc::channel();
+        let root_title = title.to_string();
+        let source = move || TreeUpdate {
+            nodes: vec![(ROOT_ID, root_node(&root_title, &mut NodeClassSet::new(), Vec::new()))],
+            tree: Some(tree()),
+            focus: ROOT_ID,
+        };
+        Self {
+            adapter: platform::Adapter::new(window, source, Box::new(Forwarder(sender))),
+            requests,
+            title: title.to_string(),
+            classes: NodeClassSet::new(),
+            sent: None,
+            focused: false,
+            bounds: None,
+        }
+    }
+
+    /// Send the widgets and `texts` to the screen reader, if one is
+    /// listening and anything changed
+    pub(crate) fn update(&mut self, widgets: &Widgets, texts: &[String], window_height: f32) {
+        let (nodes, focus) = self.nodes(widgets, texts, window_height);
+        if self.sent.as_ref().is_some_and(|(sent, sent_focus)| *sent == nodes && *sent_focus == focus) {
+            return;
+        }
+        let update = || TreeUpdate {
+            nodes: nodes.clone(),
+            tree: Some(tree()),
+            focus,
+        };
+        if self.adapter.update_if_active(update) {
+            self.sent = Some((nodes, focus));
+        }
+    }
+
+    /// Tell the screen reader where the window is and whether it has focus
+    pub(crate) fn set_window_state(&mut self, focused: bool, bounds: Option<ScreenRect>) {
+        if focused != self.focused {
+            self.focused = focused;
+            self.adapter.set_focused(focused);
+        }
+        if bounds != self.bounds {
+            self.bounds = bounds;
+            if let Some(bounds) = bounds {
+                self.adapter.set_bounds(to_rect(
+                    bounds.x as f32,
+                    bounds.y as f32,
+                    bounds.width as f32,
+                    bounds.height as f32,
+                ));
+            }
+        }
+    }
+
+    /// Next request from the screen reader that the window can carry out
+    pub(crate) fn poll_action(&mut self) -> Option<AccessAction> {
+        while let Ok(request) = self.requests.try_recv() {
+            if let Some(action) = decode(request) {
+                return Some(action);
+            }
+        }
+        None
+    }
+
+    /// The tree's nodes and the focused one
+    fn nodes(&mut self, widgets: &Widgets, texts: &[String], window_height: f32) -> (Vec<(NodeId, Node)>, NodeId) {
+        let classes = &mut self.classes;
+        let mut nodes = Vec::new();
+        let mut focus = ROOT_ID;
+
+        for (i, text) in texts.iter().enumerate() {
+            let mut node = NodeBuilder::new(Role::StaticText);
+            node.set_name(text.as_str());
+            // Read out new text (e.g. a new callout) as it appears
+            node.set_live(Live::Polite);
+            nodes.push((NodeId(TEXT | i as u64), node.build(classes)));
+        }
+
+        for button in widgets.buttons.iter().filter(|button| button.is_visible()) {
+            let id = NodeId(BUTTON | button.id().0 as u64);
+            let mut node = NodeBuilder::new(Role::Button);
+            // Icon-only buttons go by their tooltip
+            let name = match button.label() {
+                "" => button.tooltip().unwrap_or_default(),
+                label => label,
+            };
+            node.set_name(name);
+            node.set_bounds(bounds(button.screen_bounds(window_height)));
+            node.set_default_action_verb(DefaultActionVerb::Click);
+            node.add_action(Action::Default);
+            node.add_action(Action::Focus);
+            nodes.push((id, node.build(classes)));
+            if widgets.focused == Some(button.id()) {
+                focus = id;
+            }
+        }
+
+        for toggle in widgets.toggles.iter().filter(|toggle| toggle.is_visible()) {
+            let role = match toggle.kind() {
+                ToggleKind::Switch => Role::Switch,
+                ToggleKind::Checkbox => Role::CheckBox,
+            };
+            let mut node = NodeBuilder::new(role);
+            node.set_bounds(bounds(toggle.screen_bounds(window_height)));
+            node.set_checked(if toggle.is_on() { Checked::True } else { Checked::False });
+            node.set_default_action_verb(if toggle.is_on() {
+                DefaultActionVerb::Uncheck
+            } else {
+                DefaultActionVerb::Check
+            });
+            node.add_action(Action::Default);
+            nodes.push((NodeId(TOGGLE | toggle.id().0 as u64), node.build(classes)));
+        }
+
+        for slider in widgets.sliders.iter().filter(|slider| slider.is_visible()) {
+            let mut node = NodeBuilder::new(Role::Slider);
+            node.set_bounds(bounds(slider.screen_bounds(window_height)));
+            node.set_numeric_value(slider.value() as f64);
+            node.set_min_numeric_value(slider.min() as f64);
+            node.set_max_numeric_value(slider.max() as f64);
+            if slider.step() > 0.0 {
+                node.set_numeric_value_step(slider.step() as f64);
+            }
+            node.set_value(slider.value_label());
+            node.add_action(Action::SetValue);
+            node.add_action(Action::Increment);
+            node.add_action(Action::Decrement);
+            nodes.push((NodeId(SLIDER | slider.id().0 as u64), node.build(classes)));
+        }
+
+        for input in widgets.text_inputs.iter().filter(|input| input.is_visible()) {
+            let id = NodeId(TEXT_INPUT | input.id().0 as u64);
+            let mut node = NodeBuilder::new(Role::TextInput);
+            node.set_bounds(bounds(input.screen_bounds(window_height)));
+            if !input.placeholder().is_empty() {
+                node.set_placeholder(input.placeholder());
+            }
+            node.set_value(input.text());
+            node.add_action(Action::Focus);
+            node.add_action(Action::SetValue);
+            nodes.push((id, node.build(classes)));
+            if input.is_focused() {
+                focus = id;
+            }
+        }
+
+        for (i, bar) in widgets.progress_bars.iter().enumerate().filter(|(_, bar)| bar.is_visible()) {
+            let mut node = NodeBuilder::new(Role::ProgressIndicator);
+            node.set_bounds(bounds(bar.screen_bounds(window_height)));
+            if let Some(progress) = bar.progress() {
+                node.set_numeric_value(progress as f64 * 100.0);
+                node.set_min_numeric_value(0.0);
+                node.set_max_numeric_value(100.0);
+            }
+            nodes.push((NodeId(PROGRESS_BAR | i as u64), node.build(classes)));
+        }
+
+        let children = nodes.iter().map(|(id, _)| *id).collect();
+        nodes.insert(0, (ROOT_ID, root_node(&self.title, classes, children)));
+        (nodes, focus)
+    }
+}
+
+fn tree() -> Tree {
+    Tree {
+        toolkit_name: Some("ghost-ui".to_string()),
+        toolkit_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        ..Tree::new(ROOT_ID)
+    }
+}
+
+fn root_node(title: &str, classes: &mut NodeClassSet, children: Vec<NodeId>) -> Node {
+    let mut node = NodeBuilder::new(Role::Window);
+    node.set_name(title);
+    node.set_children(children);
+    node.build(classes)
+}
+
+fn bounds([x, y, width, height]: [f32; 4]) -> Rect {
+    to_rect(x, y, width, height)
+}
+
+fn to_rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+    Rect::new(x as f64, y as f64, (x + width) as f64, (y + height) as f64)
+}
+
+/// Turn a request into something the window can do; None for requests on
+/// nodes or with data it doesn't support
+fn decode(request: ActionRequest) -> Option<AccessAction> {
+    let kind = request.target.0 & !0xffff_ffff;
+    let id = (request.target.0 & 0xffff_ffff) as u32;
+    match (kind, request.action, request.data) {
+        (BUTTON, Action::Default, _) => Some(AccessAction::Click(ButtonId(id))),
+        (BUTTON, Action::Focus, _) => Some(AccessAction::Focus(ButtonId(id))),
+        (TOGGLE, Action::Default, _) => Some(AccessAction::Toggle(ToggleId(id))),
+        (SLIDER, Action::SetValue, Some(ActionData::NumericValue(value))) => {
+            Some(AccessAction::SetSlider(SliderId(id), value as f32))
+        }
+        (SLIDER, Action::Increment, _) => Some(AccessAction::NudgeSlider(SliderId(id), true)),
+        (SLIDER, Action::Decrement, _) => Some(AccessAction::NudgeSlider(SliderId(id), false)),
+        (TEXT_INPUT, Action::Focus, _) => Some(AccessAction::FocusInput(TextInputId(id))),
+        (TEXT_INPUT, Action::SetValue, Some(ActionData::Value(text))) => {
+            Some(AccessAction::SetText(TextInputId(id), text.into()))
+        }
+        _ => None,
+    }
+}
+
+/// How far an increment or decrement moves a slider with range
+/// `min..=max` and `step` (0 = no snapping)
+pub(crate) fn slider_nudge(min: f32, max: f32, step: f32) -> f32 {
+    if step > 0.0 {
+        step
+    } else {
+        (max - min) * SLIDER_NUDGE
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use accesskit::{ActionHandler, Rect, TreeUpdate};
+    use tao::window::Window;
+
+    /// Talks to AT-SPI; None when the accessibility bus isn't running
+    pub(super) struct Adapter(Option<accesskit_unix::Adapter>);
+
+    impl Adapter {
+        pub(super) fn new(
+            _window: &Window,
+            source: impl 'static + FnOnce() -> TreeUpdate,
+            handler: Box<dyn ActionHandler + Send>,
+        ) -> Self {
+            Self(accesskit_unix::Adapter::new(source, false, handler))
+        }
+
+        pub(super) fn update_if_active(&self, update: impl FnOnce() -> TreeUpdate) -> bool {
+            let Some(ref adapter) = self.0 else { return false };
+            adapter.update(update());
+            true
+        }
+
+        pub(super) fn set_focused(&self, focused: bool) {
+            if let Some(ref adapter) = self.0 {
+                adapter.update_window_focus_state(focused);
+            }
+        }
+
+        pub(super) fn set_bounds(&self, bounds: Rect) {
+            // Ghost windows have no decorations, so outer and inner agree
+            if let Some(ref adapter) = self.0 {
+                adapter.set_root_window_bounds(bounds, bounds);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use accesskit::{ActionHandler, Rect, TreeUpdate};
+    use accesskit_windows::{SubclassingAdapter, HWND};
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use tao::window::Window;
+
+    /// Subclasses the window to answer UI Automation
+    pub(super) struct Adapter(Option<SubclassingAdapter>);
+
+    impl Adapter {
+        pub(super) fn new(
+            window: &Window,
+            source: impl 'static + FnOnce() -> TreeUpdate,
+            handler: Box<dyn ActionHandler + Send>,
+        ) -> Self {
+            let RawWindowHandle::Win32(handle) = window.raw_window_handle() else { return Self(None) };
+            Self(Some(SubclassingAdapter::new(HWND(handle.hwnd as isize), source, handler)))
+        }
+
+        pub(super) fn update_if_active(&self, update: impl FnOnce() -> TreeUpdate) -> bool {
+            let Some(events) = self.0.as_ref().and_then(|adapter| adapter.update_if_active(update)) else {
+                return false;
+            };
+            events.raise();
+            true
+        }
+
+        pub(super) fn set_focused(&self, _focused: bool) {}
+
+        pub(super) fn set_bounds(&self, _bounds: Rect) {}
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use accesskit::{ActionHandler, Rect, TreeUpdate};
+    use accesskit_macos::SubclassingAdapter;
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use tao::window::Window;
+
+    /// Subclasses the window's content view to answer the macOS
+    /// accessibility API
+    pub(super) struct Adapter(Option<SubclassingAdapter>);
+
+    impl Adapter {
+        pub(super) fn new(
+            window: &Window,
+            source: impl 'static + FnOnce() -> TreeUpdate,
+            handler: Box<dyn ActionHandler + Send>,
+        ) -> Self {
+            let RawWindowHandle::AppKit(handle) = window.raw_window_handle() else { return Self(None) };
+            // SAFETY: the view belongs to the window, which outlives the
+            // adapter (both are owned by the same `WindowData`)
+            Self(Some(unsafe { SubclassingAdapter::new(handle.ns_view, source, handler) }))
+        }
+
+        pub(super) fn update_if_active(&self, update: impl FnOnce() -> TreeUpdate) -> bool {
+            let Some(events) = self.0.as_ref().and_then(|adapter| adapter.update_if_active(update)) else {
+                return false;
+            };
+            events.raise();
+            true
+        }
+
+        pub(super) fn set_focused(&self, focused: bool) {
+            if let Some(events) = self.0.as_ref().and_then(|adapter| adapter.update_view_focus_state(focused)) {
+                events.raise();
+            }
+        }
+
+        pub(super) fn set_bounds(&self, _bounds: Rect) {}
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows, target_os = "macos")))]
+mod platform {
+    use accesskit::{ActionHandler, Rect, TreeUpdate};
+    use tao::window::Window;
+
+    /// No accessibility API to talk to
+    pub(super) struct Adapter;
+
+    impl Adapter {
+        pub(super) fn new(
+            _window: &Window,
+            _source: impl 'static + FnOnce() -> TreeUpdate,
+            _handler: Box<dyn ActionHandler + Send>,
+        ) -> Self {
+            Self
+        }
+
+        pub(super) fn update_if_active(&self, _update: impl FnOnce() -> TreeUpdate) -> bool {
+            false
+        }
+
+        pub(super) fn set_focused(&self, _focused: bool) {}
+
+        pub(super) fn set_bounds(&self, _bounds: Rect) {}
+    }
+}
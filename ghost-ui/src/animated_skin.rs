@@ -1,13 +1,22 @@
 //! Animated skin support with frame sequences and state management
 
-use std::collections::HashMap;
-use std::path::Path;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 use wgpu::{Device, Queue};
 
+use crate::signature::{PersonaTrust, SIGNATURE_FILE};
 use crate::skin::{Skin, SkinData, SkinError};
 
 /// Animation playback mode
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum PlayMode {
     /// Loop the animation forever
     Loop,
@@ -19,14 +28,95 @@ pub enum PlayMode {
     PingPong,
 }
 
+/// Shortest frame delay in seconds for GIF/APNG/WebP; shorter ones (often
+/// 0) are shown at 10fps, as browsers do
+const MIN_FRAME_DELAY: f32 = 0.02;
+const DEFAULT_FRAME_DELAY: f32 = 0.1;
+
+/// Frames of the current state kept uploaded ahead of playback when a GPU
+/// memory budget is set
+const PRELOAD_FRAMES: usize = 8;
+
+/// Extensions of single-file animations, in the order they are looked for
+const ANIMATION_EXTENSIONS: [&str; 4] = ["gif", "webp", "apng", "png"];
+
+/// File names of a state's sprite sheet and its metadata
+pub const SHEET_IMAGE: &str = "sheet.png";
+pub const SHEET_META: &str = "sheet.json";
+
+/// Persona manifest holding per-state settings under `[states.<name>]`
+pub const PERSONA_MANIFEST: &str = "config.toml";
+
+/// Folder of a persona holding the parts of a composite skin, which is not
+/// a state of the body
+pub const PERSONA_LAYERS_DIR: &str = "layers";
+
+/// Playback settings of one state from the persona manifest
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StateSettings {
+    /// Frames per second (overrides the skin and sprite sheet fps)
+    pub fps: Option<f32>,
+    /// How the state plays: "loop", "once", "once-and-hide" or "ping-pong"
+    pub mode: Option<PlayMode>,
+    /// Sound file played when the state starts, relative to the persona
+    pub sound: Option<String>,
+    /// State to switch to when a one-shot finishes (instead of the default)
+    pub next: Option<String>,
+}
+
+impl StateSettings {
+    /// Read the `[states]` table of a persona manifest; other keys are ignored
+    pub fn parse_manifest(text: &str) -> Result<HashMap<String, StateSettings>, toml::de::Error> {
+        #[derive(Deserialize)]
+        struct Manifest {
+            #[serde(default)]
+            states: HashMap<String, StateSettings>,
+        }
+        Ok(toml::from_str::<Manifest>(text)?.states)
+    }
+}
+
+/// Sprite sheet metadata (`sheet.json`): equally sized frames laid out
+/// row by row, `columns` per row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheet {
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub frames: u32,
+    /// Playback speed (overrides the skin fps when set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<f32>,
+}
+
+impl SpriteSheet {
+    /// Top-left corner of frame `i` in the sheet
+    pub fn frame_origin(&self, i: u32) -> (u32, u32) {
+        let columns = self.columns.max(1);
+        ((i % columns) * self.frame_width, (i / columns) * self.frame_height)
+    }
+
+    /// Read `sheet.json` from a state directory
+    pub fn load(dir: &Path) -> Result<Self, SkinError> {
+        let text = std::fs::read_to_string(dir.join(SHEET_META))?;
+        serde_json::from_str(&text)
+            .map_err(|e| SkinError::NotFound(format!("Invalid {}: {}", dir.join(SHEET_META).display(), e)))
+    }
+}
+
 /// A single animation (sequence of frames)
 pub struct Animation {
     /// Frame data (loaded from disk)
     frames: Vec<SkinData>,
     /// GPU textures for each frame
     textures: Vec<Option<Skin>>,
+    /// Tick each texture was last needed on, for LRU eviction
+    last_used: Vec<u64>,
     /// Frames per second
     fps: f32,
+    /// Per-frame durations in seconds (GIF/APNG/WebP), overriding `fps`
+    delays: Option<Vec<f32>>,
     /// Playback mode
     pub play_mode: PlayMode,
     /// Current frame index
@@ -42,8 +132,13 @@ pub struct Animation {
 impl Animation {
     /// Load an animation from a directory of PNG frames
     /// Frames should be named frame_0001.png, frame_0002.png, etc.
+    /// Without frames, a sprite sheet (`sheet.png` + `sheet.json`) is used.
     pub fn from_directory(dir: impl AsRef<Path>, fps: f32) -> Result<Self, SkinError> {
         let dir = dir.as_ref();
+        if !dir.join("frame_0001.png").exists() && dir.join(SHEET_META).exists() {
+            return Self::from_sheet(dir, fps);
+        }
+
         let mut frames = Vec::new();
         let mut frame_num = 1;
 
@@ -72,32 +167,263 @@ impl Animation {
             dir.display()
         );
 
-        Ok(Self {
+        Ok(Self::from_frames(frames, fps))
+    }
+
+    /// Load an animation from a state directory's sprite sheet
+    pub fn from_sheet(dir: impl AsRef<Path>, fps: f32) -> Result<Self, SkinError> {
+        let dir = dir.as_ref();
+        let sheet = SpriteSheet::load(dir)?;
+        let image = image::open(dir.join(SHEET_IMAGE))?;
+        Self::from_sheet_image(&sheet, &image, fps, dir)
+    }
+
+    /// Cut a loaded sprite sheet into frames; `dir` is only used in messages
+    fn from_sheet_image(sheet: &SpriteSheet, image: &DynamicImage, fps: f32, dir: &Path) -> Result<Self, SkinError> {
+        let (width, height) = image.dimensions();
+
+        let mut frames = Vec::with_capacity(sheet.frames as usize);
+        for i in 0..sheet.frames {
+            let (x, y) = sheet.frame_origin(i);
+            if x + sheet.frame_width > width || y + sheet.frame_height > height {
+                return Err(SkinError::NotFound(format!(
+                    "Frame {} lies outside the sprite sheet in {}",
+                    i + 1,
+                    dir.display()
+                )));
+            }
+            let frame = image.crop_imm(x, y, sheet.frame_width, sheet.frame_height);
+            frames.push(SkinData::from_image(&frame)?);
+        }
+
+        if frames.is_empty() {
+            return Err(SkinError::NotFound(format!(
+                "Empty sprite sheet in directory: {}",
+                dir.display()
+            )));
+        }
+
+        let fps = sheet.fps.unwrap_or(fps);
+        log::info!(
+            "Loaded sprite sheet animation: {} frames at {}fps from {}",
+            frames.len(),
+            fps,
+            dir.display()
+        );
+        Ok(Self::from_frames(frames, fps))
+    }
+
+    /// Load a state folder of an unpacked archive: frames named
+    /// frame_0001.png, ... or a sprite sheet. Returns None if it has neither.
+    fn from_archive_folder(files: &HashMap<&str, &[u8]>, fps: f32, dir: &Path) -> Result<Option<Self>, SkinError> {
+        let frames = (1..)
+            .map(|i| format!("frame_{:04}.png", i))
+            .map_while(|name| files.get(name.as_str()).copied())
+            .map(SkinData::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+        if !frames.is_empty() {
+            log::info!(
+                "Loaded animation: {} frames at {}fps from {}",
+                frames.len(),
+                fps,
+                dir.display()
+            );
+            return Ok(Some(Self::from_frames(frames, fps)));
+        }
+
+        let (Some(meta), Some(image)) = (files.get(SHEET_META), files.get(SHEET_IMAGE)) else {
+            return Ok(None);
+        };
+        let sheet: SpriteSheet = serde_json::from_slice(meta)
+            .map_err(|e| SkinError::NotFound(format!("Invalid {}: {}", dir.join(SHEET_META).display(), e)))?;
+        let image = image::load_from_memory(image)?;
+        Self::from_sheet_image(&sheet, &image, fps, dir).map(Some)
+    }
+
+    /// Load an animation from an animated GIF, keeping its frame delays
+    pub fn from_gif(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let path = path.as_ref();
+        let file = BufReader::new(File::open(path)?);
+        let decoded = GifDecoder::new(file)?.into_frames().collect_frames()?;
+        Self::from_decoded(decoded, path)
+    }
+
+    /// Load an animation from an animated PNG, keeping its frame delays.
+    /// A plain PNG becomes a single frame.
+    pub fn from_apng(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let path = path.as_ref();
+        let decoder = PngDecoder::new(BufReader::new(File::open(path)?))?;
+        if !decoder.is_apng() {
+            return Self::from_still(path);
+        }
+        let decoded = decoder.apng().into_frames().collect_frames()?;
+        Self::from_decoded(decoded, path)
+    }
+
+    /// Load an animation from an animated WebP, keeping its frame delays.
+    /// A still WebP becomes a single frame.
+    pub fn from_webp(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let path = path.as_ref();
+        let decoder = WebPDecoder::new(BufReader::new(File::open(path)?))?;
+        if !decoder.has_animation() {
+            return Self::from_still(path);
+        }
+        let decoded = decoder.into_frames().collect_frames()?;
+        Self::from_decoded(decoded, path)
+    }
+
+    /// Load an animation from a GIF, APNG or WebP file, by extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        match extension.to_lowercase().as_str() {
+            "gif" => Self::from_gif(path),
+            "png" | "apng" => Self::from_apng(path),
+            "webp" => Self::from_webp(path),
+            _ => Err(SkinError::NotFound(format!(
+                "Unsupported animation format: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Is `path` a file `from_file` can load
+    pub fn is_animation_file(path: impl AsRef<Path>) -> bool {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        path.is_file() && ANIMATION_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+    }
+
+    /// Single-frame animation from a still image
+    fn from_still(path: &Path) -> Result<Self, SkinError> {
+        let frame = SkinData::from_image(&image::open(path)?)?;
+        let mut animation = Self::from_frames(vec![frame], 1.0 / DEFAULT_FRAME_DELAY);
+        animation.delays = Some(vec![DEFAULT_FRAME_DELAY]);
+        Ok(animation)
+    }
+
+    /// Animation from decoded frames with their own delays
+    fn from_decoded(decoded: Vec<image::Frame>, path: &Path) -> Result<Self, SkinError> {
+        let mut frames = Vec::with_capacity(decoded.len());
+        let mut delays = Vec::with_capacity(decoded.len());
+        for frame in decoded {
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            delays.push(frame_delay(numer, denom));
+            frames.push(SkinData::from_image(&DynamicImage::ImageRgba8(frame.into_buffer()))?);
+        }
+
+        if frames.is_empty() {
+            return Err(SkinError::NotFound(format!("No frames in: {}", path.display())));
+        }
+
+        let total: f32 = delays.iter().sum();
+        let fps = frames.len() as f32 / total;
+        log::info!(
+            "Loaded animation: {} frames ({:.1}s, ~{:.1}fps) from {}",
+            frames.len(),
+            total,
+            fps,
+            path.display()
+        );
+        let mut animation = Self::from_frames(frames, fps);
+        animation.delays = Some(delays);
+        Ok(animation)
+    }
+
+    fn from_frames(frames: Vec<SkinData>, fps: f32) -> Self {
+        Self {
             textures: (0..frames.len()).map(|_| None).collect(),
+            last_used: vec![0; frames.len()],
             frames,
             fps,
+            delays: None,
             play_mode: PlayMode::Loop,
             current_frame: 0,
             time_accumulator: 0.0,
             direction: 1,
             finished: false,
-        })
+        }
     }
 
     /// Initialize GPU resources for all frames
     pub fn init_gpu(&mut self, device: &Device, queue: &Queue) {
-        for (i, frame_data) in self.frames.iter().enumerate() {
-            if self.textures[i].is_none() {
-                match Skin::from_skin_data(frame_data, device, queue) {
-                    Ok(skin) => {
-                        self.textures[i] = Some(skin);
+        for i in 0..self.frames.len() {
+            self.upload_frame(i, device, queue);
+        }
+    }
+
+    /// Upload one frame if it isn't on the GPU yet, returning the bytes
+    /// newly allocated
+    fn upload_frame(&mut self, index: usize, device: &Device, queue: &Queue) -> usize {
+        if self.textures[index].is_some() {
+            return 0;
+        }
+        match Skin::from_skin_data(&self.frames[index], device, queue) {
+            Ok(skin) => {
+                self.textures[index] = Some(skin);
+                self.frame_bytes(index)
+            }
+            Err(e) => {
+                log::error!("Failed to create texture for frame {}: {}", index, e);
+                0
+            }
+        }
+    }
+
+    /// Drop one frame's texture, returning the bytes freed
+    fn evict_frame(&mut self, index: usize) -> usize {
+        match self.textures[index].take() {
+            Some(_) => self.frame_bytes(index),
+            None => 0,
+        }
+    }
+
+    /// Video memory a frame's texture takes (RGBA8)
+    fn frame_bytes(&self, index: usize) -> usize {
+        let (width, height) = self.frames[index].dimensions();
+        width as usize * height as usize * 4
+    }
+
+    /// Video memory taken by the frames currently on the GPU
+    fn gpu_bytes(&self) -> usize {
+        (0..self.frames.len())
+            .filter(|&i| self.textures[i].is_some())
+            .map(|i| self.frame_bytes(i))
+            .sum()
+    }
+
+    /// The current frame and up to `count - 1` frames that play after it
+    fn upcoming_frames(&self, count: usize) -> Vec<usize> {
+        let frame_count = self.frames.len();
+        let mut upcoming = Vec::new();
+        if frame_count == 0 {
+            return upcoming;
+        }
+        let mut frame = self.current_frame.min(frame_count - 1);
+        let mut direction = self.direction;
+        for _ in 0..count.min(frame_count) {
+            upcoming.push(frame);
+            if frame_count == 1 {
+                break;
+            }
+            frame = match self.play_mode {
+                PlayMode::Loop => (frame + 1) % frame_count,
+                PlayMode::Once | PlayMode::OnceAndHide => {
+                    if frame + 1 >= frame_count {
+                        break;
                     }
-                    Err(e) => {
-                        log::error!("Failed to create texture for frame {}: {}", i, e);
+                    frame + 1
+                }
+                PlayMode::PingPong => {
+                    let next = frame as i32 + direction;
+                    if next < 0 || next >= frame_count as i32 {
+                        direction = -direction;
                     }
+                    (frame as i32 + direction) as usize
                 }
-            }
+            };
         }
+        upcoming
     }
 
     /// Update animation timing
@@ -107,14 +433,24 @@ impl Animation {
         }
 
         self.time_accumulator += delta;
-        let frame_duration = 1.0 / self.fps;
-
-        while self.time_accumulator >= frame_duration {
+        loop {
+            let frame_duration = self.frame_duration();
+            if self.time_accumulator < frame_duration {
+                break;
+            }
             self.time_accumulator -= frame_duration;
             self.advance_frame();
         }
     }
 
+    /// How long the current frame is shown, in seconds
+    fn frame_duration(&self) -> f32 {
+        match self.delays {
+            Some(ref delays) => delays.get(self.current_frame).copied().unwrap_or(DEFAULT_FRAME_DELAY),
+            None => 1.0 / self.fps,
+        }
+    }
+
     /// Advance to the next frame based on play mode
     fn advance_frame(&mut self) {
         let frame_count = self.frames.len();
@@ -156,6 +492,21 @@ impl Animation {
         self.textures.get(self.current_frame)?.as_ref()
     }
 
+    /// Get the first frame's skin (hidden once a hiding one-shot finished)
+    pub fn first_skin(&self) -> Option<&Skin> {
+        if self.finished && self.play_mode == PlayMode::OnceAndHide {
+            return None;
+        }
+        self.textures.first()?.as_ref()
+    }
+
+    /// Show the frame matching a level from 0 (first frame) to 1 (last)
+    pub fn show_level(&mut self, level: f32) {
+        let last = self.frames.len().saturating_sub(1);
+        self.current_frame = (level.clamp(0.0, 1.0) * last as f32).round() as usize;
+        self.time_accumulator = 0.0;
+    }
+
     /// Reset animation to the beginning
     pub fn reset(&mut self) {
         self.current_frame = 0;
@@ -188,6 +539,12 @@ impl Animation {
     pub fn set_play_mode(&mut self, mode: PlayMode) {
         self.play_mode = mode;
     }
+
+    /// Play at a fixed frame rate, replacing any per-frame delays
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps;
+        self.delays = None;
+    }
 }
 
 /// Animation state identifier
@@ -202,7 +559,7 @@ pub enum AnimationState {
 }
 
 impl AnimationState {
-    /// Parse state from string
+    /// Parse state from string (unknown names become custom states)
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "idle" => Self::Idle,
@@ -210,9 +567,48 @@ impl AnimationState {
             "thinking" | "think" => Self::Thinking,
             "happy" => Self::Happy,
             "sad" => Self::Sad,
-            _ => Self::Idle,
+            other => Self::custom(other),
+        }
+    }
+
+    /// Custom state identified by name (e.g. "worried", "sweating")
+    pub fn custom(name: &str) -> Self {
+        // FNV-1a hash of the lowercase name
+        let hash = name
+            .to_lowercase()
+            .bytes()
+            .fold(0x811c_9dc5u32, |acc, b| (acc ^ b as u32).wrapping_mul(0x0100_0193));
+        Self::Custom(hash)
+    }
+}
+
+/// Progress of loading a skin, reported after each state
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadProgress {
+    /// State just loaded
+    pub state: String,
+    /// Frames of that state (0 if it failed to load)
+    pub frames: usize,
+    /// States done so far
+    pub loaded: usize,
+    /// States to load in total
+    pub total: usize,
+}
+
+impl LoadProgress {
+    fn new(state: &str, frames: usize, loaded: usize, total: usize) -> Self {
+        Self {
+            state: state.to_string(),
+            frames,
+            loaded,
+            total,
         }
     }
+
+    /// Share of states done, 0-1
+    pub fn fraction(&self) -> f32 {
+        self.loaded as f32 / self.total.max(1) as f32
+    }
 }
 
 /// Animated skin with multiple animation states
@@ -223,8 +619,22 @@ pub struct AnimatedSkin {
     current_state: AnimationState,
     /// Default/fallback state
     default_state: AnimationState,
+    /// State each one-shot switches to when done, instead of the default
+    next_states: HashMap<AnimationState, AnimationState>,
     /// Whether GPU resources are initialized
     gpu_initialized: bool,
+    /// Video memory frames may take; unset keeps every frame uploaded
+    gpu_budget: Option<usize>,
+    /// Video memory taken by uploaded frames (tracked with a budget)
+    gpu_bytes: usize,
+    /// Counts `prepare_gpu` calls, to find least-recently-used frames
+    gpu_tick: u64,
+    /// Hold each state's first frame instead of playing it
+    reduced_motion: bool,
+    /// State, frame and hidden flag last reported by `take_changed`
+    shown_frame: Option<(AnimationState, usize, bool)>,
+    /// Level picking the current state's frame instead of time
+    level: Option<f32>,
 }
 
 impl AnimatedSkin {
@@ -234,7 +644,14 @@ impl AnimatedSkin {
             animations: HashMap::new(),
             current_state: AnimationState::Idle,
             default_state: AnimationState::Idle,
+            next_states: HashMap::new(),
             gpu_initialized: false,
+            gpu_budget: None,
+            gpu_bytes: 0,
+            gpu_tick: 0,
+            reduced_motion: false,
+            shown_frame: None,
+            level: None,
         }
     }
 
@@ -244,49 +661,164 @@ impl AnimatedSkin {
     ///   idle/frame_0001.png, frame_0002.png, ...
     ///   talking/frame_0001.png, ...
     ///   etc.
+    /// A built-in state can also be a single animated file instead of a
+    /// folder: `idle.gif`, `talk.webp`, `happy.png` (APNG).
+    /// Other subdirectories are loaded as `AnimationState::custom(dir_name)`.
+    /// Per-state settings are read from `base_dir/config.toml` if present.
+    /// A persona changed since it was signed (see `PersonaTrust`) fails to
+    /// load.
     pub fn from_directory(base_dir: impl AsRef<Path>, fps: f32) -> Result<Self, SkinError> {
+        Self::from_directory_with_progress(base_dir, fps, &PersonaTrust::default(), &mut |_| {})
+    }
+
+    /// `from_directory` accepting the signers `trust` does, reporting each
+    /// state as it is loaded
+    pub fn from_directory_with_progress(
+        base_dir: impl AsRef<Path>,
+        fps: f32,
+        trust: &PersonaTrust,
+        progress: &mut dyn FnMut(LoadProgress),
+    ) -> Result<Self, SkinError> {
         let base_dir = base_dir.as_ref();
+        // Reading every file is only worth it when there is something to check
+        if trust.requires_signature() || base_dir.join(SIGNATURE_FILE).is_file() {
+            let mut files = BTreeMap::new();
+            read_dir_files(base_dir, "", &mut files)?;
+            trust.check(&files, base_dir)?;
+        }
+        let sources = state_sources(base_dir)?;
         let mut skin = Self::new();
 
-        // Try to load common animation states
-        let states = [
-            ("idle", AnimationState::Idle),
-            ("talking", AnimationState::Talking),
-            ("talk", AnimationState::Talking),
-            ("thinking", AnimationState::Thinking),
-            ("think", AnimationState::Thinking),
-            ("happy", AnimationState::Happy),
-            ("sad", AnimationState::Sad),
-        ];
-
-        for (dir_name, state) in states {
-            let state_dir = base_dir.join(dir_name);
-            if state_dir.exists() && state_dir.is_dir() {
-                match Animation::from_directory(&state_dir, fps) {
-                    Ok(anim) => {
-                        skin.add_animation(state, anim);
-                    }
-                    Err(e) => {
-                        log::warn!("Could not load animation '{}': {}", dir_name, e);
-                    }
+        for (i, (name, state, path)) in sources.iter().enumerate() {
+            let loaded = if path.is_dir() {
+                Animation::from_directory(path, fps)
+            } else {
+                Animation::from_file(path)
+            };
+            let frames = match loaded {
+                Ok(anim) => {
+                    let frames = anim.frame_count();
+                    skin.add_animation(*state, anim);
+                    frames
+                }
+                Err(e) => {
+                    log::warn!("Could not load animation '{}': {}", path.display(), e);
+                    0
+                }
+            };
+            progress(LoadProgress::new(name, frames, i + 1, sources.len()));
+        }
+
+        let manifest = base_dir.join(PERSONA_MANIFEST);
+        if manifest.is_file() {
+            let text = std::fs::read_to_string(&manifest)?;
+            skin.apply_manifest(&text, &manifest);
+        }
+
+        skin.finish(base_dir)
+    }
+
+    /// Load a packed persona (`.zip`): state folders of frames or sprite
+    /// sheets, with the per-state settings of its `config.toml`. The files
+    /// may sit in a single top-level folder. A persona changed since it was
+    /// signed (see `PersonaTrust`) fails to load.
+    pub fn from_zip(path: impl AsRef<Path>, fps: f32) -> Result<Self, SkinError> {
+        Self::from_zip_with_progress(path, fps, &PersonaTrust::default(), &mut |_| {})
+    }
+
+    /// `from_zip` accepting the signers `trust` does, reporting each state
+    /// as it is loaded
+    pub fn from_zip_with_progress(
+        path: impl AsRef<Path>,
+        fps: f32,
+        trust: &PersonaTrust,
+        progress: &mut dyn FnMut(LoadProgress),
+    ) -> Result<Self, SkinError> {
+        let path = path.as_ref();
+        let files = read_zip(path)?;
+        trust.check(&files, path)?;
+        let mut skin = Self::new();
+
+        // Files directly inside each top-level folder
+        let mut folders: BTreeMap<&str, HashMap<&str, &[u8]>> = BTreeMap::new();
+        for (name, data) in &files {
+            if let Some((folder, file)) = name.split_once('/') {
+                if !file.is_empty() && !file.contains('/') {
+                    folders.entry(folder).or_default().insert(file, data);
                 }
             }
         }
 
-        if skin.animations.is_empty() {
+        for (i, (folder, folder_files)) in folders.iter().enumerate() {
+            let frames = match Animation::from_archive_folder(folder_files, fps, &path.join(folder)) {
+                Ok(Some(anim)) => {
+                    let frames = anim.frame_count();
+                    skin.add_animation(AnimationState::from_str(folder), anim);
+                    frames
+                }
+                Ok(None) => 0,
+                Err(e) => {
+                    log::warn!("Could not load animation '{}': {}", folder, e);
+                    0
+                }
+            };
+            progress(LoadProgress::new(folder, frames, i + 1, folders.len()));
+        }
+
+        if let Some(text) = files.get(PERSONA_MANIFEST) {
+            skin.apply_manifest(&String::from_utf8_lossy(text), &path.join(PERSONA_MANIFEST));
+        }
+
+        skin.finish(path)
+    }
+
+    /// Apply the `[states]` of a persona manifest, logging a broken one
+    fn apply_manifest(&mut self, text: &str, path: &Path) {
+        match StateSettings::parse_manifest(text) {
+            Ok(states) => self.apply_state_settings(&states),
+            Err(e) => log::warn!("Ignoring state settings in {}: {}", path.display(), e),
+        }
+    }
+
+    /// Apply per-state fps, play mode and follow-up state, keyed by state
+    /// name; settings of states the skin lacks are ignored
+    pub fn apply_state_settings(&mut self, states: &HashMap<String, StateSettings>) {
+        for (name, settings) in states {
+            let state = AnimationState::from_str(name);
+            let Some(anim) = self.animations.get_mut(&state) else { continue };
+            if let Some(fps) = settings.fps.filter(|fps| *fps > 0.0) {
+                anim.set_fps(fps);
+            }
+            if let Some(mode) = settings.mode {
+                anim.set_play_mode(mode);
+            }
+            if let Some(next) = &settings.next {
+                self.next_states.insert(state, AnimationState::from_str(next));
+            }
+        }
+    }
+
+    /// Check a loaded skin has animations and pick its default state
+    fn finish(mut self, source: &Path) -> Result<Self, SkinError> {
+        if self.animations.is_empty() {
             return Err(SkinError::NotFound(format!(
                 "No animations found in: {}",
-                base_dir.display()
+                source.display()
             )));
         }
 
-        // Set the first available state as default
-        if let Some(state) = skin.animations.keys().next().copied() {
-            skin.current_state = state;
-            skin.default_state = state;
+        // Prefer idle as default, else the first available state
+        let state = if self.animations.contains_key(&AnimationState::Idle) {
+            Some(AnimationState::Idle)
+        } else {
+            self.animations.keys().next().copied()
+        };
+        if let Some(state) = state {
+            self.current_state = state;
+            self.default_state = state;
         }
 
-        Ok(skin)
+        Ok(self)
     }
 
     /// Load a single animation as the idle state (for simple use cases)
@@ -297,13 +829,34 @@ impl AnimatedSkin {
         Ok(skin)
     }
 
+    /// Load an animated GIF as the idle state
+    pub fn from_gif(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let anim = Animation::from_gif(path)?;
+        let mut skin = Self::new();
+        skin.add_animation(AnimationState::Idle, anim);
+        Ok(skin)
+    }
+
+    /// Load an animated GIF, APNG or WebP as the idle state
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SkinError> {
+        let anim = Animation::from_file(path)?;
+        let mut skin = Self::new();
+        skin.add_animation(AnimationState::Idle, anim);
+        Ok(skin)
+    }
+
     /// Add an animation for a state
     pub fn add_animation(&mut self, state: AnimationState, animation: Animation) {
         self.animations.insert(state, animation);
     }
 
-    /// Initialize GPU resources for all animations
+    /// Initialize GPU resources for all animations (with a budget, only
+    /// the frames about to play)
     pub fn init_gpu(&mut self, device: &Device, queue: &Queue) {
+        if self.gpu_budget.is_some() {
+            self.prepare_gpu(device, queue);
+            return;
+        }
         if self.gpu_initialized {
             return;
         }
@@ -316,23 +869,135 @@ impl AnimatedSkin {
         log::info!("AnimatedSkin GPU initialized with {} states", self.animations.len());
     }
 
+    /// Cap the video memory animation frames take, in bytes. Frames stay
+    /// decoded in RAM; only the next few of the current state are uploaded
+    /// and the least recently used textures are dropped to stay under it.
+    pub fn set_gpu_budget(&mut self, budget: Option<usize>) {
+        self.gpu_budget = budget;
+        self.gpu_bytes = self.animations.values().map(Animation::gpu_bytes).sum();
+        // Without a budget every frame gets uploaded again
+        self.gpu_initialized = false;
+    }
+
+    /// Upload the frames about to play and evict the least recently used
+    /// ones past the budget; call once per frame before rendering. Without
+    /// a budget this is `init_gpu`.
+    pub fn prepare_gpu(&mut self, device: &Device, queue: &Queue) {
+        let Some(budget) = self.gpu_budget else {
+            self.init_gpu(device, queue);
+            return;
+        };
+        self.gpu_tick += 1;
+        let tick = self.gpu_tick;
+
+        if let Some(anim) = self.animations.get_mut(&self.current_state) {
+            // Reduced motion only ever shows the first frame
+            let upcoming: Vec<usize> = if self.reduced_motion {
+                (0..anim.frames.len().min(1)).collect()
+            } else if self.level.is_some() {
+                // Any frame may come next
+                (0..anim.frames.len()).collect()
+            } else {
+                anim.upcoming_frames(PRELOAD_FRAMES)
+            };
+            for index in upcoming {
+                self.gpu_bytes += anim.upload_frame(index, device, queue);
+                anim.last_used[index] = tick;
+            }
+        }
+
+        if self.gpu_bytes <= budget {
+            return;
+        }
+        // Oldest first; frames needed this tick are never evicted
+        let mut uploaded: Vec<(u64, AnimationState, usize)> = self
+            .animations
+            .iter()
+            .flat_map(|(state, anim)| {
+                (0..anim.textures.len())
+                    .filter(|&i| anim.textures[i].is_some() && anim.last_used[i] < tick)
+                    .map(move |i| (anim.last_used[i], *state, i))
+            })
+            .collect();
+        uploaded.sort_by_key(|&(used, _, _)| used);
+        for (_, state, index) in uploaded {
+            if self.gpu_bytes <= budget {
+                break;
+            }
+            if let Some(anim) = self.animations.get_mut(&state) {
+                self.gpu_bytes -= anim.evict_frame(index);
+            }
+        }
+    }
+
     /// Update the current animation
     pub fn update(&mut self, delta: f32) {
+        if let Some(level) = self.level {
+            if let Some(anim) = self.animations.get_mut(&self.current_state) {
+                anim.show_level(level);
+            }
+            return;
+        }
         if let Some(anim) = self.animations.get_mut(&self.current_state) {
             anim.update(delta);
 
-            // If animation finished and it's a one-shot, return to default
+            // If animation finished and it's a one-shot, move on to its
+            // follow-up state or return to default
             if anim.is_finished() && anim.play_mode != PlayMode::Loop {
-                self.set_state(self.default_state);
+                let next = self.next_states.get(&self.current_state).copied();
+                self.set_state(next.unwrap_or(self.default_state));
             }
         }
     }
 
     /// Get the current frame's skin for rendering
     pub fn current_skin(&self) -> Option<&Skin> {
-        self.animations
-            .get(&self.current_state)
-            .and_then(|a| a.current_skin())
+        let anim = self.animations.get(&self.current_state)?;
+        if self.reduced_motion {
+            anim.first_skin()
+        } else {
+            anim.current_skin()
+        }
+    }
+
+    /// Whether the frame on screen differs from the last call, i.e. the
+    /// window needs redrawing
+    pub fn take_changed(&mut self) -> bool {
+        let shown = self.animations.get(&self.current_state).map(|anim| {
+            let frame = if self.reduced_motion { 0 } else { anim.current_frame };
+            let hidden = anim.finished && anim.play_mode == PlayMode::OnceAndHide;
+            (self.current_state, frame, hidden)
+        });
+        let changed = shown != self.shown_frame;
+        self.shown_frame = shown;
+        changed
+    }
+
+    /// Whether the current state is still moving through its frames
+    pub fn is_animating(&self) -> bool {
+        !self.reduced_motion
+            && self
+                .animations
+                .get(&self.current_state)
+                .is_some_and(|anim| anim.frame_count() > 1 && !anim.is_finished())
+    }
+
+    /// Drive the current state's frames by a level from 0 to 1 (e.g. the
+    /// loudness of speech, with mouth frames from closed to open) instead
+    /// of time; None plays the frames again
+    pub fn set_level(&mut self, level: Option<f32>) {
+        self.level = level;
+    }
+
+    /// Show a still pose per state instead of playing frames. One-shot
+    /// states still last as long as their animation would.
+    pub fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.reduced_motion = reduced_motion;
+    }
+
+    /// Whether states are shown as still poses
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
     }
 
     /// Set the current animation state
@@ -378,12 +1043,127 @@ impl AnimatedSkin {
 
     /// Play a one-shot animation and return to default when done
     pub fn play_once(&mut self, state: AnimationState) {
+        self.play(state, PlayMode::Once);
+    }
+
+    /// Switch to a state from its first frame with the given play mode
+    pub fn play(&mut self, state: AnimationState, mode: PlayMode) {
         if let Some(anim) = self.animations.get_mut(&state) {
             anim.reset();
-            anim.set_play_mode(PlayMode::Once);
+            anim.set_play_mode(mode);
         }
         self.set_state(state);
     }
+
+    /// Whether a state's animation has played to its end
+    pub fn is_state_finished(&self, state: AnimationState) -> bool {
+        self.animations.get(&state).is_some_and(Animation::is_finished)
+    }
+}
+
+/// States of a skin folder with the folder or animated file each is
+/// loaded from: built-in states first, then custom folders by name
+fn state_sources(base_dir: &Path) -> Result<Vec<(String, AnimationState, PathBuf)>, SkinError> {
+    let states = [
+        ("idle", AnimationState::Idle),
+        ("talking", AnimationState::Talking),
+        ("talk", AnimationState::Talking),
+        ("thinking", AnimationState::Thinking),
+        ("think", AnimationState::Thinking),
+        ("happy", AnimationState::Happy),
+        ("sad", AnimationState::Sad),
+    ];
+
+    let mut sources = Vec::new();
+    for (dir_name, state) in states {
+        let state_dir = base_dir.join(dir_name);
+        if state_dir.is_dir() {
+            sources.push((dir_name.to_string(), state, state_dir));
+            continue;
+        }
+        // Or a single animated file named after the state (idle.gif, talk.webp, ...)
+        if let Some(file) = ANIMATION_EXTENSIONS
+            .iter()
+            .map(|ext| base_dir.join(format!("{}.{}", dir_name, ext)))
+            .find(|path| path.is_file())
+        {
+            sources.push((dir_name.to_string(), state, file));
+        }
+    }
+
+    // Any other subdirectory becomes a custom state named after it
+    let mut custom_dirs: Vec<_> = std::fs::read_dir(base_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    custom_dirs.sort();
+    for state_dir in custom_dirs {
+        let Some(dir_name) = state_dir.file_name().and_then(|n| n.to_str()) else { continue };
+        if dir_name == PERSONA_LAYERS_DIR || states.iter().any(|(name, _)| *name == dir_name.to_lowercase()) {
+            continue;
+        }
+        sources.push((dir_name.to_string(), AnimationState::custom(dir_name), state_dir));
+    }
+    Ok(sources)
+}
+
+/// Read every file of a zip archive by path. If all files share one
+/// top-level folder and there is no manifest at the top, that folder is
+/// stripped so `rin/idle/...` and `idle/...` load the same.
+fn read_zip(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, SkinError> {
+    let invalid = |e: zip::result::ZipError| SkinError::NotFound(format!("Invalid archive {}: {}", path.display(), e));
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(invalid)?;
+    let mut files = BTreeMap::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(invalid)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(name) = entry.enclosed_name().map(|p| p.to_string_lossy().replace('\\', "/")) else {
+            continue;
+        };
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+
+    if files.contains_key(PERSONA_MANIFEST) {
+        return Ok(files);
+    }
+    let root = files.keys().next().and_then(|name| name.split_once('/')).map(|(root, _)| format!("{}/", root));
+    match root {
+        Some(root) if files.keys().all(|name| name.starts_with(&root)) => Ok(files
+            .into_iter()
+            .map(|(name, data)| (name[root.len()..].to_string(), data))
+            .collect()),
+        _ => Ok(files),
+    }
+}
+
+/// Read every file below `dir` into `files`, keyed by `/`-separated path
+/// relative to the persona root
+fn read_dir_files(dir: &Path, prefix: &str, files: &mut BTreeMap<String, Vec<u8>>) -> Result<(), SkinError> {
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            read_dir_files(&path, &format!("{}/", name), files)?;
+        } else {
+            files.insert(name, std::fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+/// Frame delay in seconds from a decoder's delay in milliseconds
+fn frame_delay(numer: u32, denom: u32) -> f32 {
+    let seconds = numer as f32 / denom.max(1) as f32 / 1000.0;
+    if seconds < MIN_FRAME_DELAY {
+        DEFAULT_FRAME_DELAY
+    } else {
+        seconds
+    }
 }
 
 impl Default for AnimatedSkin {
@@ -0,0 +1,168 @@
+//! Native context menu opened by right-clicking the ghost window
+
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, OnceLock};
+
+use tray_icon::menu::{CheckMenuItem, IsMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu};
+
+use crate::icon::TRAY_ID_PREFIX;
+
+/// Prefix of the native ids of context menu items, telling them apart from
+/// tray menu items
+const ID_PREFIX: &str = "ghost-context:";
+
+/// An entry of a `ContextMenu`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContextMenuItem {
+    /// Plain item, reported as `GhostEvent::ContextMenuSelected(id)`
+    Action { id: String, label: String, enabled: bool },
+    /// Item with a checkmark, reported like an action
+    Check { id: String, label: String, checked: bool },
+    /// Line between groups of items
+    Separator,
+    /// Nested menu
+    Submenu { label: String, menu: ContextMenu },
+}
+
+/// Items of the menu shown when the mascot is right-clicked
+/// (see `GhostApp::context_menu`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContextMenu {
+    items: Vec<ContextMenuItem>,
+}
+
+impl ContextMenu {
+    /// Create an empty menu
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an item chosen by `id`
+    pub fn item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.items.push(ContextMenuItem::Action { id: id.into(), label: label.into(), enabled: true });
+        self
+    }
+
+    /// Add a greyed-out item (e.g. a heading)
+    pub fn disabled_item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.items.push(ContextMenuItem::Action { id: id.into(), label: label.into(), enabled: false });
+        self
+    }
+
+    /// Add an item with a checkmark
+    pub fn check_item(mut self, id: impl Into<String>, label: impl Into<String>, checked: bool) -> Self {
+        self.items.push(ContextMenuItem::Check { id: id.into(), label: label.into(), checked });
+        self
+    }
+
+    /// Add a separator line
+    pub fn separator(mut self) -> Self {
+        self.items.push(ContextMenuItem::Separator);
+        self
+    }
+
+    /// Add a nested menu
+    pub fn submenu(mut self, label: impl Into<String>, menu: ContextMenu) -> Self {
+        self.items.push(ContextMenuItem::Submenu { label: label.into(), menu });
+        self
+    }
+
+    /// The menu's entries
+    pub fn items(&self) -> &[ContextMenuItem] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Build the native menu
+    pub(crate) fn to_native(&self) -> Menu {
+        let menu = Menu::new();
+        for item in native_items(&self.items) {
+            if let Err(e) = menu.append(item.as_ref()) {
+                log::warn!("Failed to add context menu item: {}", e);
+            }
+        }
+        menu
+    }
+}
+
+fn native_items(items: &[ContextMenuItem]) -> Vec<Box<dyn IsMenuItem>> {
+    items
+        .iter()
+        .map(|item| -> Box<dyn IsMenuItem> {
+            match item {
+                ContextMenuItem::Action { id, label, enabled } => {
+                    Box::new(MenuItem::with_id(native_id(id), label, *enabled, None))
+                }
+                ContextMenuItem::Check { id, label, checked } => {
+                    Box::new(CheckMenuItem::with_id(native_id(id), label, true, *checked, None))
+                }
+                ContextMenuItem::Separator => Box::new(PredefinedMenuItem::separator()),
+                ContextMenuItem::Submenu { label, menu } => {
+                    let submenu = Submenu::new(label, true);
+                    for child in native_items(&menu.items) {
+                        if let Err(e) = submenu.append(child.as_ref()) {
+                            log::warn!("Failed to add context menu item: {}", e);
+                        }
+                    }
+                    Box::new(submenu)
+                }
+            }
+        })
+        .collect()
+}
+
+fn native_id(id: &str) -> String {
+    format!("{}{}", ID_PREFIX, id)
+}
+
+/// Menu events split into context menu selections, `TrayMenuBuilder` menu
+/// selections and everything else
+struct MenuRouter {
+    selections: Mutex<Receiver<String>>,
+    tray_selections: Mutex<Receiver<String>>,
+    others: Mutex<Receiver<MenuEvent>>,
+}
+
+/// Route menu events through ghost-ui; set up on first use, before any
+/// menu event is sent
+fn router() -> &'static MenuRouter {
+    static ROUTER: OnceLock<MenuRouter> = OnceLock::new();
+    ROUTER.get_or_init(|| {
+        let (selection_sender, selections) = mpsc::channel();
+        let (tray_sender, tray_selections) = mpsc::channel();
+        let (other_sender, others) = mpsc::channel();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some(id) = event.id.0.strip_prefix(ID_PREFIX) {
+                let _ = selection_sender.send(id.to_string());
+            } else if let Some(id) = event.id.0.strip_prefix(TRAY_ID_PREFIX) {
+                let _ = tray_sender.send(id.to_string());
+            } else {
+                let _ = other_sender.send(event);
+            }
+        }));
+        MenuRouter {
+            selections: Mutex::new(selections),
+            tray_selections: Mutex::new(tray_selections),
+            others: Mutex::new(others),
+        }
+    })
+}
+
+/// Id of the next chosen context menu item
+pub(crate) fn poll_selection() -> Option<String> {
+    router().selections.lock().ok()?.try_recv().ok()
+}
+
+/// Id of the next chosen `TrayMenuBuilder` item
+pub(crate) fn poll_tray_selection() -> Option<String> {
+    router().tray_selections.lock().ok()?.try_recv().ok()
+}
+
+/// Next event of the other menus (e.g. a tray menu made by hand). The ghost window
+/// routes all menu events, so use this instead of `MenuEvent::receiver`.
+pub fn poll_menu_event() -> Option<MenuEvent> {
+    router().others.lock().ok()?.try_recv().ok()
+}
@@ -0,0 +1,89 @@
+//! Easing curves for animated moves and transitions
+
+use std::f32::consts::PI;
+
+/// How an animation speeds up and slows down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Start slow, end fast
+    EaseIn,
+    /// Start fast, end slow
+    EaseOut,
+    /// Start and end slow
+    EaseInOut,
+    /// Overshoot the end a little and settle back
+    Back,
+    /// Bounce against the end like a dropped ball
+    Bounce,
+    /// Overshoot and spring back and forth into place
+    Elastic,
+}
+
+impl Easing {
+    /// Parse an easing name ("linear", "ease-in", "ease-out", "ease-in-out",
+    /// "back", "bounce", "elastic")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace('_', "-").as_str() {
+            "linear" => Some(Self::Linear),
+            "ease-in" | "in" => Some(Self::EaseIn),
+            "ease-out" | "out" => Some(Self::EaseOut),
+            "ease-in-out" | "ease" | "in-out" => Some(Self::EaseInOut),
+            "back" => Some(Self::Back),
+            "bounce" => Some(Self::Bounce),
+            "elastic" => Some(Self::Elastic),
+            _ => None,
+        }
+    }
+
+    /// Eased progress for `t` (0.0 - 1.0); starts at 0.0 and ends at 1.0,
+    /// but may overshoot in between
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Self::Back => {
+                const OVERSHOOT: f32 = 1.70158;
+                let u = t - 1.0;
+                1.0 + (OVERSHOOT + 1.0) * u * u * u + OVERSHOOT * u * u
+            }
+            Self::Bounce => bounce_out(t),
+            Self::Elastic => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+        }
+    }
+}
+
+/// Ball dropped on the end: three shrinking bounces
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
@@ -0,0 +1,111 @@
+//! Font setup shared by the text renderers - system fonts plus a color
+//! emoji font
+//!
+//! cosmic-text only falls back to a few emoji fonts by name, so emoji
+//! rendered as tofu whenever another one was installed. Text is therefore
+//! split into emoji and non-emoji runs and the emoji runs are set in the
+//! emoji font found here.
+
+use std::ops::Range;
+
+use glyphon::{Attrs, Family, FontSystem};
+
+/// Emoji fonts in order of preference
+const EMOJI_FAMILIES: &[&str] = &[
+    "Apple Color Emoji",
+    "Segoe UI Emoji",
+    "Noto Color Emoji",
+    "Twemoji",
+    "Twitter Color Emoji",
+    "JoyPixels",
+    "EmojiOne Color",
+    "OpenMoji Color",
+    "Noto Emoji",
+];
+
+/// Emoji font files not always indexed with the system fonts
+const EMOJI_FILES: &[&str] = &[
+    "/System/Library/Fonts/Apple Color Emoji.ttc",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+    "/usr/share/fonts/truetype/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/google-noto-emoji/NotoColorEmoji.ttf",
+    "/usr/share/fonts/noto-emoji/NotoColorEmoji.ttf",
+];
+
+/// Load the system fonts, plus an emoji font if none was found among them;
+/// returns the fonts and the family name of the emoji font
+pub fn load_fonts() -> (FontSystem, Option<String>) {
+    let mut font_system = FontSystem::new();
+    let mut emoji_family = find_emoji_family(&font_system);
+    if emoji_family.is_none() {
+        for path in EMOJI_FILES {
+            if std::path::Path::new(path).is_file() {
+                font_system.db_mut().load_font_file(path).ok();
+            }
+        }
+        emoji_family = find_emoji_family(&font_system);
+    }
+    match emoji_family {
+        Some(ref family) => log::debug!("Emoji font: {}", family),
+        None => log::warn!("No color emoji font found; emoji may not display"),
+    }
+    (font_system, emoji_family)
+}
+
+/// Split `text` into runs with `attrs`, setting emoji in `emoji_family`
+pub fn with_emoji<'t, 'a>(text: &'t str, attrs: Attrs<'a>, emoji_family: Option<&'a str>) -> Vec<(&'t str, Attrs<'a>)> {
+    let Some(family) = emoji_family else {
+        return vec![(text, attrs)];
+    };
+    let emoji_attrs = attrs.family(Family::Name(family));
+    emoji_runs(text)
+        .into_iter()
+        .map(|(range, emoji)| (&text[range], if emoji { emoji_attrs } else { attrs }))
+        .collect()
+}
+
+fn find_emoji_family(font_system: &FontSystem) -> Option<String> {
+    let installed: Vec<String> = font_system
+        .db()
+        .faces()
+        .flat_map(|face| face.families.iter().map(|(name, _)| name.clone()))
+        .collect();
+    EMOJI_FAMILIES
+        .iter()
+        .find(|family| installed.iter().any(|name| name == *family))
+        .map(|family| family.to_string())
+        .or_else(|| installed.into_iter().find(|name| name.to_lowercase().contains("emoji")))
+}
+
+/// Byte ranges of `text` alternating between non-emoji and emoji (true)
+pub fn emoji_runs(text: &str) -> Vec<(Range<usize>, bool)> {
+    let mut runs: Vec<(Range<usize>, bool)> = Vec::new();
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        let emoji = match runs.last() {
+            // Joiners, variation selectors and skin tones belong to the emoji before
+            Some(&(_, last)) if is_emoji_modifier(c) => last,
+            _ => is_emoji(c),
+        };
+        match runs.last_mut() {
+            Some((range, last)) if *last == emoji => range.end = end,
+            _ => runs.push((i..end, emoji)),
+        }
+    }
+    runs
+}
+
+/// Characters drawn as emoji by default
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // Emoticons, pictographs, flags, ...
+        | 0x2600..=0x27BF // Misc symbols and dingbats
+        | 0x2B50 | 0x2B55 | 0x2B1B | 0x2B1C // Stars and squares
+        | 0x231A | 0x231B | 0x23E9..=0x23FA // Watches and media symbols
+    )
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(c as u32, 0x200D | 0xFE0F | 0x20E3 | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F)
+}
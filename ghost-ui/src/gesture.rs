@@ -0,0 +1,164 @@
+//! Pointer gestures recognized over the skin
+
+use std::time::{Duration, Instant};
+
+/// Thresholds for the rub gesture (rapid back-and-forth movement, e.g. petting)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RubGesture {
+    /// Direction changes needed to count as a rub
+    pub min_reversals: u32,
+    /// Minimum horizontal travel of each stroke in pixels
+    pub min_stroke: f32,
+    /// Time window the direction changes must fall into, in seconds
+    pub window: f32,
+}
+
+impl Default for RubGesture {
+    fn default() -> Self {
+        Self {
+            min_reversals: 4,
+            min_stroke: 12.0,
+            window: 1.2,
+        }
+    }
+}
+
+/// Recognizes the rub gesture from cursor positions
+pub(crate) struct RubDetector {
+    gesture: RubGesture,
+    last_x: Option<f32>,
+    /// Current stroke direction (-1, 0 or 1)
+    direction: i8,
+    /// Where the current stroke started
+    stroke_start: f32,
+    /// Times of recent direction changes
+    reversals: Vec<Instant>,
+}
+
+impl RubDetector {
+    pub(crate) fn new(gesture: RubGesture) -> Self {
+        Self {
+            gesture,
+            last_x: None,
+            direction: 0,
+            stroke_start: 0.0,
+            reversals: Vec::new(),
+        }
+    }
+
+    /// Forget the current strokes (e.g. when the cursor leaves the skin)
+    pub(crate) fn reset(&mut self) {
+        self.last_x = None;
+        self.direction = 0;
+        self.reversals.clear();
+    }
+
+    /// Feed a cursor x position; returns true when a rub is recognized
+    pub(crate) fn moved(&mut self, x: f32, now: Instant) -> bool {
+        let Some(last_x) = self.last_x.replace(x) else {
+            self.stroke_start = x;
+            return false;
+        };
+
+        let direction = match x.partial_cmp(&last_x) {
+            Some(std::cmp::Ordering::Greater) => 1,
+            Some(std::cmp::Ordering::Less) => -1,
+            _ => return false,
+        };
+        if direction == self.direction {
+            return false;
+        }
+
+        // Only strokes long enough count, small jitter just turns around
+        if self.direction != 0 && (last_x - self.stroke_start).abs() >= self.gesture.min_stroke {
+            self.reversals.push(now);
+        }
+        self.direction = direction;
+        self.stroke_start = last_x;
+
+        let window = Duration::from_secs_f32(self.gesture.window.max(0.0));
+        self.reversals.retain(|t| now.duration_since(*t) <= window);
+
+        if self.reversals.len() as u32 >= self.gesture.min_reversals {
+            self.reversals.clear();
+            return true;
+        }
+        false
+    }
+}
+
+/// Longest pause between the two clicks of a double-click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// How far apart the two clicks of a double-click may land, in pixels
+const DOUBLE_CLICK_DISTANCE: f32 = 6.0;
+
+/// Recognizes double-clicks from left-button presses
+#[derive(Default)]
+pub(crate) struct ClickDetector {
+    /// Time and position of a press that may start a double-click
+    last: Option<(Instant, [f32; 2])>,
+}
+
+impl ClickDetector {
+    /// Feed a press position; returns true when it completes a double-click
+    /// (a third press starts over)
+    pub(crate) fn pressed(&mut self, position: [f32; 2], now: Instant) -> bool {
+        if let Some((time, [x, y])) = self.last.take() {
+            let distance = (position[0] - x).hypot(position[1] - y);
+            if now.duration_since(time) <= DOUBLE_CLICK_TIME && distance <= DOUBLE_CLICK_DISTANCE {
+                return true;
+            }
+        }
+        self.last = Some((now, position));
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rub back and forth between 0 and `stroke`, a stroke every 50 ms;
+    /// returns after which stroke the rub was recognized
+    fn rub(detector: &mut RubDetector, stroke: f32, strokes: usize, start: Instant) -> Option<usize> {
+        (0..=strokes).find(|&i| {
+            let x = if i % 2 == 0 { 0.0 } else { stroke };
+            detector.moved(x, start + Duration::from_millis(50 * i as u64))
+        })
+    }
+
+    #[test]
+    fn test_rub() {
+        let mut detector = RubDetector::new(RubGesture::default());
+        // The first stroke only sets a direction, each later one reverses it
+        assert_eq!(rub(&mut detector, 30.0, 10, Instant::now()), Some(5));
+    }
+
+    #[test]
+    fn test_short_strokes_are_jitter() {
+        let mut detector = RubDetector::new(RubGesture::default());
+        assert_eq!(rub(&mut detector, 5.0, 10, Instant::now()), None);
+    }
+
+    #[test]
+    fn test_slow_rubs_expire() {
+        let gesture = RubGesture::default();
+        let mut detector = RubDetector::new(gesture);
+        let start = Instant::now();
+        for i in 0..10u64 {
+            let x = if i % 2 == 0 { 0.0 } else { 30.0 };
+            assert!(!detector.moved(x, start + Duration::from_secs(i)));
+        }
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut clicks = ClickDetector::default();
+        let start = Instant::now();
+        assert!(!clicks.pressed([10.0, 10.0], start));
+        assert!(clicks.pressed([12.0, 11.0], start + Duration::from_millis(200)));
+        assert!(!clicks.pressed([12.0, 11.0], start + Duration::from_millis(300)));
+        // Too far away
+        assert!(!clicks.pressed([40.0, 11.0], start + Duration::from_millis(400)));
+    }
+}
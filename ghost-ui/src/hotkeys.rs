@@ -0,0 +1,300 @@
+//! System-wide keyboard shortcuts, reported while another app has focus
+
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum HotkeyError {
+    #[error("Empty shortcut")]
+    Empty,
+    #[error("Unknown key '{0}'")]
+    UnknownKey(String),
+    #[error("Shortcut '{0}' needs a key besides the modifiers")]
+    MissingKey(String),
+    #[error("Shortcut '{0}' needs a modifier such as Ctrl or Alt")]
+    MissingModifier(String),
+    #[error("Hotkeys are already registered")]
+    AlreadyRegistered,
+    #[error("Global hotkeys are not supported on this platform")]
+    Unsupported,
+}
+
+/// The non-modifier key of a shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyKey {
+    /// `A` to `Z`, stored uppercase
+    Letter(char),
+    /// `0` to `9`
+    Digit(u8),
+    /// `F1` to `F24`
+    Function(u8),
+    Space,
+    Enter,
+    Tab,
+    Escape,
+}
+
+/// A key combination such as `Ctrl+Shift+G`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    /// The Windows, Command or Super key
+    pub meta: bool,
+    pub key: HotkeyKey,
+}
+
+impl FromStr for Hotkey {
+    type Err = HotkeyError;
+
+    /// Parse `+`-separated modifiers and a key, ignoring case
+    /// (e.g. "ctrl+alt+F5")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(HotkeyError::Empty);
+        }
+        let (mut ctrl, mut alt, mut shift, mut meta) = (false, false, false, false);
+        let mut key = None;
+        for part in s.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => ctrl = true,
+                "alt" | "option" => alt = true,
+                "shift" => shift = true,
+                "super" | "win" | "cmd" | "command" | "meta" => meta = true,
+                _ if key.is_some() => return Err(HotkeyError::UnknownKey(part.to_string())),
+                _ => key = Some(parse_key(part)?),
+            }
+        }
+        let key = key.ok_or_else(|| HotkeyError::MissingKey(s.to_string()))?;
+        // A bare letter would swallow that key in every app
+        if !(ctrl || alt || meta || matches!(key, HotkeyKey::Function(_))) {
+            return Err(HotkeyError::MissingModifier(s.to_string()));
+        }
+        Ok(Self { ctrl, alt, shift, meta, key })
+    }
+}
+
+fn parse_key(name: &str) -> Result<HotkeyKey, HotkeyError> {
+    let lower = name.to_ascii_lowercase();
+    let mut chars = lower.chars();
+    let key = match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii_lowercase() => HotkeyKey::Letter(c.to_ascii_uppercase()),
+        (Some(c), None) if c.is_ascii_digit() => HotkeyKey::Digit(c as u8 - b'0'),
+        _ => match lower.as_str() {
+            "space" => HotkeyKey::Space,
+            "enter" | "return" => HotkeyKey::Enter,
+            "tab" => HotkeyKey::Tab,
+            "esc" | "escape" => HotkeyKey::Escape,
+            _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=24) => HotkeyKey::Function(n),
+                _ => return Err(HotkeyError::UnknownKey(name.to_string())),
+            },
+        },
+    };
+    Ok(key)
+}
+
+/// Ids of pressed hotkeys, sent from the listener thread
+static PRESSED: OnceLock<Mutex<Receiver<String>>> = OnceLock::new();
+
+/// Start listening for `bindings`, each reported as
+/// `GhostEvent::HotkeyPressed(id)`. Can only be called once; a shortcut
+/// another app already grabbed is skipped with a warning.
+pub fn register_hotkeys(bindings: Vec<(String, Hotkey)>) -> Result<(), HotkeyError> {
+    if !cfg!(any(target_os = "linux", target_os = "windows")) {
+        return Err(HotkeyError::Unsupported);
+    }
+    let (sender, receiver) = mpsc::channel();
+    PRESSED
+        .set(Mutex::new(receiver))
+        .map_err(|_| HotkeyError::AlreadyRegistered)?;
+    std::thread::spawn(move || listen(bindings, sender));
+    Ok(())
+}
+
+/// Id of the next pressed hotkey
+pub(crate) fn poll_pressed() -> Option<String> {
+    PRESSED.get()?.lock().ok()?.try_recv().ok()
+}
+
+#[cfg(target_os = "linux")]
+fn listen(bindings: Vec<(String, Hotkey)>, sender: Sender<String>) {
+    use std::os::raw::c_int;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use x11_dl::xlib;
+
+    static GRAB_FAILED: AtomicBool = AtomicBool::new(false);
+
+    unsafe extern "C" fn on_error(_: *mut xlib::Display, _: *mut xlib::XErrorEvent) -> c_int {
+        GRAB_FAILED.store(true, Ordering::SeqCst);
+        0
+    }
+
+    let xlib = match xlib::Xlib::open() {
+        Ok(xlib) => xlib,
+        Err(e) => {
+            log::warn!("Global hotkeys unavailable: {}", e);
+            return;
+        }
+    };
+    unsafe {
+        let display = (xlib.XOpenDisplay)(std::ptr::null());
+        if display.is_null() {
+            log::warn!("Global hotkeys need an X11 display");
+            return;
+        }
+        let root = (xlib.XDefaultRootWindow)(display);
+
+        // Grab each combination also with Caps Lock and Num Lock on
+        let ignored = [0, xlib::LockMask, xlib::Mod2Mask, xlib::LockMask | xlib::Mod2Mask];
+        let mut grabbed = Vec::new();
+        let previous_handler = (xlib.XSetErrorHandler)(Some(on_error));
+        for (id, hotkey) in bindings {
+            let keycode = (xlib.XKeysymToKeycode)(display, x11_keysym(hotkey.key));
+            if keycode == 0 {
+                log::warn!("Hotkey '{}' has no key on this keyboard", id);
+                continue;
+            }
+            let mask = x11_modifiers(&hotkey);
+            GRAB_FAILED.store(false, Ordering::SeqCst);
+            for extra in ignored {
+                (xlib.XGrabKey)(display, keycode as c_int, mask | extra, root, xlib::False, xlib::GrabModeAsync, xlib::GrabModeAsync);
+            }
+            (xlib.XSync)(display, xlib::False);
+            if GRAB_FAILED.load(Ordering::SeqCst) {
+                log::warn!("Hotkey '{}' is taken by another app", id);
+                continue;
+            }
+            grabbed.push((keycode as u32, mask, id));
+        }
+        (xlib.XSetErrorHandler)(previous_handler);
+        if grabbed.is_empty() {
+            return;
+        }
+        log::info!("Registered {} global hotkey(s)", grabbed.len());
+
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        loop {
+            (xlib.XNextEvent)(display, &mut event);
+            if event.get_type() != xlib::KeyPress {
+                continue;
+            }
+            let state = event.key.state & !(xlib::LockMask | xlib::Mod2Mask);
+            let pressed = grabbed
+                .iter()
+                .find(|(keycode, mask, _)| *keycode == event.key.keycode && *mask == state);
+            if let Some((_, _, id)) = pressed {
+                if sender.send(id.clone()).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn x11_modifiers(hotkey: &Hotkey) -> std::os::raw::c_uint {
+    use x11_dl::xlib;
+
+    let mut mask = 0;
+    if hotkey.ctrl {
+        mask |= xlib::ControlMask;
+    }
+    if hotkey.alt {
+        mask |= xlib::Mod1Mask;
+    }
+    if hotkey.shift {
+        mask |= xlib::ShiftMask;
+    }
+    if hotkey.meta {
+        mask |= xlib::Mod4Mask;
+    }
+    mask
+}
+
+#[cfg(target_os = "linux")]
+fn x11_keysym(key: HotkeyKey) -> std::os::raw::c_ulong {
+    use x11_dl::keysym;
+
+    let sym = match key {
+        HotkeyKey::Letter(c) => keysym::XK_a + (c as u32 - 'A' as u32),
+        HotkeyKey::Digit(n) => keysym::XK_0 + n as u32,
+        HotkeyKey::Function(n) => keysym::XK_F1 + (n as u32 - 1),
+        HotkeyKey::Space => keysym::XK_space,
+        HotkeyKey::Enter => keysym::XK_Return,
+        HotkeyKey::Tab => keysym::XK_Tab,
+        HotkeyKey::Escape => keysym::XK_Escape,
+    };
+    sym as std::os::raw::c_ulong
+}
+
+#[cfg(target_os = "windows")]
+fn listen(bindings: Vec<(String, Hotkey)>, sender: Sender<String>) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT, MOD_WIN,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetMessageW, MSG, WM_HOTKEY};
+
+    // Hotkeys without a window are posted to this thread's message queue
+    let mut ids = Vec::new();
+    for (id, hotkey) in bindings {
+        let mut modifiers = MOD_NOREPEAT.0;
+        if hotkey.ctrl {
+            modifiers |= MOD_CONTROL.0;
+        }
+        if hotkey.alt {
+            modifiers |= MOD_ALT.0;
+        }
+        if hotkey.shift {
+            modifiers |= MOD_SHIFT.0;
+        }
+        if hotkey.meta {
+            modifiers |= MOD_WIN.0;
+        }
+        let registered = unsafe {
+            RegisterHotKey(HWND(0), ids.len() as i32, HOT_KEY_MODIFIERS(modifiers), virtual_key(hotkey.key))
+        };
+        if registered.as_bool() {
+            ids.push(id);
+        } else {
+            log::warn!("Hotkey '{}' is taken by another app", id);
+        }
+    }
+    if ids.is_empty() {
+        return;
+    }
+    log::info!("Registered {} global hotkey(s)", ids.len());
+
+    let mut message = MSG::default();
+    while unsafe { GetMessageW(&mut message, HWND(0), 0, 0) }.as_bool() {
+        if message.message != WM_HOTKEY {
+            continue;
+        }
+        if let Some(id) = ids.get(message.wParam.0) {
+            if sender.send(id.clone()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn virtual_key(key: HotkeyKey) -> u32 {
+    match key {
+        HotkeyKey::Letter(c) => c as u32,
+        HotkeyKey::Digit(n) => b'0' as u32 + n as u32,
+        HotkeyKey::Function(n) => 0x70 + (n as u32 - 1),
+        HotkeyKey::Space => 0x20,
+        HotkeyKey::Enter => 0x0D,
+        HotkeyKey::Tab => 0x09,
+        HotkeyKey::Escape => 0x1B,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn listen(_bindings: Vec<(String, Hotkey)>, _sender: Sender<String>) {}
@@ -1,8 +1,15 @@
 //! Icon and tray helpers
 
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
 use std::path::Path;
 use thiserror::Error;
-use tray_icon::{menu::Menu, Icon as TrayIconImage, TrayIcon, TrayIconBuilder};
+use tray_icon::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{Icon as TrayIconImage, TrayIcon, TrayIconBuilder};
+
+/// Prefix of the native ids of `TrayMenuBuilder` items, telling them apart
+/// from other menus' items
+pub(crate) const TRAY_ID_PREFIX: &str = "ghost-tray:";
 
 #[derive(Error, Debug)]
 pub enum IconError {
@@ -25,6 +32,11 @@ pub fn load_icon(bytes: &[u8]) -> Result<TrayIconImage, IconError> {
     Ok(icon)
 }
 
+/// Convert an RGBA image into a tray icon.
+fn icon_from_image(image: &RgbaImage) -> Result<TrayIconImage, IconError> {
+    Ok(TrayIconImage::from_rgba(image.as_raw().clone(), image.width(), image.height())?)
+}
+
 /// Load an icon from a file path.
 pub fn load_icon_from_path(path: impl AsRef<Path>) -> Result<TrayIconImage, IconError> {
     let bytes = std::fs::read(path)?;
@@ -91,10 +103,41 @@ pub fn set_dock_icon(_bytes: &[u8]) -> Result<(), IconError> {
     Ok(())
 }
 
+/// Frame-based tray icon animation.
+struct IconAnimation {
+    frames: Vec<RgbaImage>,
+    frame_time: f32,
+    elapsed: f32,
+    current: usize,
+}
+
+/// Badge drawn in the top-right corner of the tray and dock icons
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    /// A red circle with the count ("+" above 9)
+    Count(u32),
+    /// A plain dot of this RGB color
+    Dot([u8; 3]),
+}
+
 /// Helper struct to manage application icons.
+///
+/// The tray icon can play a frame animation (e.g. pulsing while busy), and
+/// the tray and dock icons can show a badge; call [`AppIcon::update`] every
+/// frame to apply changes.
 pub struct AppIcon {
     icon_bytes: Vec<u8>,
     tray_icon: Option<TrayIcon>,
+    /// Decoded icon, used when no animation is playing
+    base: Option<RgbaImage>,
+    animation: Option<IconAnimation>,
+    badge: Option<Badge>,
+    /// The tray icon image needs to be redrawn
+    dirty: bool,
+    /// The dock shows this icon (set up with `setup_dock`)
+    dock: bool,
+    /// The dock icon image needs to be redrawn
+    dock_dirty: bool,
 }
 
 impl AppIcon {
@@ -103,6 +146,12 @@ impl AppIcon {
         Self {
             icon_bytes: bytes.to_vec(),
             tray_icon: None,
+            base: image::load_from_memory(bytes).ok().map(|img| img.to_rgba8()),
+            animation: None,
+            badge: None,
+            dirty: false,
+            dock: false,
+            dock_dirty: false,
         }
     }
 
@@ -120,6 +169,11 @@ impl AppIcon {
         Ok(self.tray_icon.as_ref().unwrap())
     }
 
+    /// Use an existing tray icon (e.g. one built with a tooltip) for updates.
+    pub fn set_tray(&mut self, tray: TrayIcon) {
+        self.tray_icon = Some(tray);
+    }
+
     /// Set up the system tray icon with a custom menu.
     pub fn setup_tray_with_menu(&mut self, menu: Menu) -> Result<&TrayIcon, IconError> {
         let icon = load_icon(&self.icon_bytes)?;
@@ -131,8 +185,12 @@ impl AppIcon {
     /// Set the dock icon (macOS only).
     ///
     /// Note: Only works when running as a bundled .app, not via `cargo run`.
-    pub fn setup_dock(&self) -> Result<(), IconError> {
-        set_dock_icon(&self.icon_bytes)
+    pub fn setup_dock(&mut self) -> Result<(), IconError> {
+        set_dock_icon(&self.icon_bytes)?;
+        self.dock = true;
+        // Badge set before the dock
+        self.dock_dirty = self.badge.is_some();
+        Ok(())
     }
 
     /// Set up both tray and dock icons.
@@ -148,6 +206,409 @@ impl AppIcon {
     pub fn tray(&self) -> Option<&TrayIcon> {
         self.tray_icon.as_ref()
     }
+
+    /// Show `menu` when the tray icon is clicked, replacing the current one.
+    pub fn set_menu(&self, menu: &TrayMenuHandle) {
+        if let Some(ref tray) = self.tray_icon {
+            tray.set_menu(Some(Box::new(menu.menu().clone())));
+        }
+    }
+
+    /// Play an animation from PNG frames, looping at `fps`.
+    pub fn set_animation(&mut self, frames: &[&[u8]], fps: f32) -> Result<(), IconError> {
+        let frames = frames
+            .iter()
+            .map(|bytes| Ok(image::load_from_memory(bytes)?.to_rgba8()))
+            .collect::<Result<Vec<_>, IconError>>()?;
+        self.play(frames, fps);
+        Ok(())
+    }
+
+    /// Pulse the icon by fading it in and out, `fps` frames per second.
+    pub fn start_pulse(&mut self, fps: f32) {
+        if self.is_animating() {
+            return;
+        }
+        let Some(ref base) = self.base else { return };
+
+        const STEPS: usize = 8;
+        let frames = (0..STEPS)
+            .map(|i| {
+                // Triangle wave between full and 40% opacity
+                let t = 1.0 - (i as f32 / (STEPS / 2) as f32 - 1.0).abs();
+                let alpha = 1.0 - 0.6 * t;
+                let mut frame = base.clone();
+                for pixel in frame.pixels_mut() {
+                    pixel[3] = (pixel[3] as f32 * alpha) as u8;
+                }
+                frame
+            })
+            .collect();
+        self.play(frames, fps);
+    }
+
+    /// Stop the animation and show the static icon.
+    pub fn stop_animation(&mut self) {
+        if self.animation.take().is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Whether an animation is playing.
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+    /// Show a badge with `count` in the corner (0 hides it).
+    pub fn set_badge(&mut self, count: u32) {
+        self.show_badge((count > 0).then_some(Badge::Count(count)));
+    }
+
+    /// Show a badge in the corner of the tray and dock icons (None hides it).
+    pub fn show_badge(&mut self, badge: Option<Badge>) {
+        if self.badge != badge {
+            self.badge = badge;
+            self.dirty = true;
+            self.dock_dirty = self.dock;
+        }
+    }
+
+    /// The badge shown, if any.
+    pub fn badge(&self) -> Option<Badge> {
+        self.badge
+    }
+
+    /// Advance the animation and push changes to the tray and dock icons.
+    pub fn update(&mut self, delta: f32) {
+        if self.dock_dirty {
+            self.dock_dirty = false;
+            self.update_dock();
+        }
+
+        if let Some(ref mut animation) = self.animation {
+            animation.elapsed += delta;
+            while animation.elapsed >= animation.frame_time {
+                animation.elapsed -= animation.frame_time;
+                animation.current = (animation.current + 1) % animation.frames.len();
+                self.dirty = true;
+            }
+        }
+
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let Some(ref tray) = self.tray_icon else { return };
+        let frame = match (&self.animation, &self.base) {
+            (Some(animation), _) => &animation.frames[animation.current],
+            (None, Some(base)) => base,
+            (None, None) => return,
+        };
+
+        let mut image = frame.clone();
+        if let Some(badge) = self.badge {
+            draw_badge(&mut image, badge);
+        }
+        match icon_from_image(&image) {
+            Ok(icon) => {
+                if let Err(e) = tray.set_icon(Some(icon)) {
+                    log::error!("Failed to update tray icon: {}", e);
+                }
+            }
+            Err(e) => log::error!("Failed to create tray icon: {}", e),
+        }
+    }
+
+    /// Redraw the dock icon with the badge (macOS only)
+    fn update_dock(&self) {
+        if !cfg!(target_os = "macos") {
+            return;
+        }
+        let Some(ref base) = self.base else { return };
+        let mut image = base.clone();
+        if let Some(badge) = self.badge {
+            draw_badge(&mut image, badge);
+        }
+        let mut png = std::io::Cursor::new(Vec::new());
+        let encoded = image.write_to(&mut png, image::ImageOutputFormat::Png).map_err(IconError::from);
+        if let Err(e) = encoded.and_then(|_| set_dock_icon(png.get_ref())) {
+            log::error!("Failed to update dock icon: {}", e);
+        }
+    }
+
+    fn play(&mut self, frames: Vec<RgbaImage>, fps: f32) {
+        if frames.is_empty() {
+            return;
+        }
+        self.animation = Some(IconAnimation {
+            frames,
+            frame_time: 1.0 / fps.max(1.0),
+            elapsed: 0.0,
+            current: 0,
+        });
+        self.dirty = true;
+    }
+}
+
+/// An entry of a `TrayMenuBuilder`
+#[derive(Debug, Clone, PartialEq)]
+enum TrayMenuEntry {
+    Item { id: String, label: String, enabled: bool },
+    Check { id: String, label: String, checked: bool, enabled: bool },
+    Separator,
+    Submenu { id: String, label: String, enabled: bool, menu: TrayMenuBuilder },
+}
+
+/// Items of a tray menu, built into a `TrayMenuHandle` whose items can be
+/// relabeled, checked and enabled while it is shown. Choosing an item is
+/// reported as `GhostEvent::TrayMenuSelected(id)`.
+///
+/// # Example
+/// ```no_run
+/// let menu = ghost_ui::TrayMenuBuilder::new()
+///     .item("chat", "Open Chat")
+///     .check_item("mute", "Mute", false)
+///     .separator()
+///     .submenu("size", "Size", ghost_ui::TrayMenuBuilder::new().item("small", "Small").item("large", "Large"))
+///     .item("quit", "Quit")
+///     .build();
+/// menu.set_label("chat", "Close Chat");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayMenuBuilder {
+    entries: Vec<TrayMenuEntry>,
+}
+
+impl TrayMenuBuilder {
+    /// Create an empty menu
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an item chosen by `id`
+    pub fn item(mut self, id: impl Into<String>, label: impl Into<String>) -> Self {
+        self.entries.push(TrayMenuEntry::Item { id: id.into(), label: label.into(), enabled: true });
+        self
+    }
+
+    /// Add an item with a checkmark; choosing it flips the checkmark
+    pub fn check_item(mut self, id: impl Into<String>, label: impl Into<String>, checked: bool) -> Self {
+        self.entries.push(TrayMenuEntry::Check { id: id.into(), label: label.into(), checked, enabled: true });
+        self
+    }
+
+    /// Add a separator line
+    pub fn separator(mut self) -> Self {
+        self.entries.push(TrayMenuEntry::Separator);
+        self
+    }
+
+    /// Add a nested menu, itself addressed by `id`
+    pub fn submenu(mut self, id: impl Into<String>, label: impl Into<String>, menu: TrayMenuBuilder) -> Self {
+        self.entries.push(TrayMenuEntry::Submenu { id: id.into(), label: label.into(), enabled: true, menu });
+        self
+    }
+
+    /// Grey out the entry added last (or not)
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        match self.entries.last_mut() {
+            Some(TrayMenuEntry::Item { enabled: e, .. })
+            | Some(TrayMenuEntry::Check { enabled: e, .. })
+            | Some(TrayMenuEntry::Submenu { enabled: e, .. }) => *e = enabled,
+            Some(TrayMenuEntry::Separator) | None => {}
+        }
+        self
+    }
+
+    /// Whether nothing was added yet
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Build the native menu
+    pub fn build(self) -> TrayMenuHandle {
+        let menu = Menu::new();
+        let mut items = HashMap::new();
+        for item in native_tray_items(self.entries, &mut items) {
+            if let Err(e) = menu.append(item.as_ref()) {
+                log::warn!("Failed to add tray menu item: {}", e);
+            }
+        }
+        TrayMenuHandle { menu, items }
+    }
+}
+
+fn native_tray_items(entries: Vec<TrayMenuEntry>, items: &mut HashMap<String, TrayMenuItem>) -> Vec<Box<dyn IsMenuItem>> {
+    entries
+        .into_iter()
+        .map(|entry| -> Box<dyn IsMenuItem> {
+            match entry {
+                TrayMenuEntry::Item { id, label, enabled } => {
+                    let item = MenuItem::with_id(tray_native_id(&id), label, enabled, None);
+                    items.insert(id, TrayMenuItem::Item(item.clone()));
+                    Box::new(item)
+                }
+                TrayMenuEntry::Check { id, label, checked, enabled } => {
+                    let item = CheckMenuItem::with_id(tray_native_id(&id), label, enabled, checked, None);
+                    items.insert(id, TrayMenuItem::Check(item.clone()));
+                    Box::new(item)
+                }
+                TrayMenuEntry::Separator => Box::new(PredefinedMenuItem::separator()),
+                TrayMenuEntry::Submenu { id, label, enabled, menu } => {
+                    let submenu = Submenu::with_id(tray_native_id(&id), label, enabled);
+                    for child in native_tray_items(menu.entries, items) {
+                        if let Err(e) = submenu.append(child.as_ref()) {
+                            log::warn!("Failed to add tray menu item: {}", e);
+                        }
+                    }
+                    items.insert(id, TrayMenuItem::Submenu(submenu.clone()));
+                    Box::new(submenu)
+                }
+            }
+        })
+        .collect()
+}
+
+fn tray_native_id(id: &str) -> String {
+    format!("{}{}", TRAY_ID_PREFIX, id)
+}
+
+/// A native item of a built tray menu
+enum TrayMenuItem {
+    Item(MenuItem),
+    Check(CheckMenuItem),
+    Submenu(Submenu),
+}
+
+/// A tray menu built by `TrayMenuBuilder`; items are changed in place by id
+/// (unknown ids are ignored)
+pub struct TrayMenuHandle {
+    menu: Menu,
+    items: HashMap<String, TrayMenuItem>,
+}
+
+impl TrayMenuHandle {
+    /// The native menu, e.g. for `TrayIconBuilder::with_menu`
+    pub fn menu(&self) -> &Menu {
+        &self.menu
+    }
+
+    /// Whether the menu has an item (or submenu) with `id`
+    pub fn contains(&self, id: &str) -> bool {
+        self.items.contains_key(id)
+    }
+
+    /// Change the label of an item or submenu
+    pub fn set_label(&self, id: &str, label: &str) {
+        match self.items.get(id) {
+            Some(TrayMenuItem::Item(item)) => item.set_text(label),
+            Some(TrayMenuItem::Check(item)) => item.set_text(label),
+            Some(TrayMenuItem::Submenu(submenu)) => submenu.set_text(label),
+            None => {}
+        }
+    }
+
+    /// Enable or grey out an item or submenu
+    pub fn set_enabled(&self, id: &str, enabled: bool) {
+        match self.items.get(id) {
+            Some(TrayMenuItem::Item(item)) => item.set_enabled(enabled),
+            Some(TrayMenuItem::Check(item)) => item.set_enabled(enabled),
+            Some(TrayMenuItem::Submenu(submenu)) => submenu.set_enabled(enabled),
+            None => {}
+        }
+    }
+
+    /// Set or clear the checkmark of a checkable item
+    pub fn set_checked(&self, id: &str, checked: bool) {
+        if let Some(TrayMenuItem::Check(item)) = self.items.get(id) {
+            item.set_checked(checked);
+        }
+    }
+
+    /// The checkmark of a checkable item (None for other items)
+    pub fn is_checked(&self, id: &str) -> Option<bool> {
+        match self.items.get(id) {
+            Some(TrayMenuItem::Check(item)) => Some(item.is_checked()),
+            _ => None,
+        }
+    }
+}
+
+/// 3x5 pixel glyphs for badge digits and '+', one row per entry (bit 2 = left)
+const BADGE_GLYPHS: [[u8; 5]; 11] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b010, 0b010, 0b010], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+];
+
+/// Draw a badge in the top-right corner.
+fn draw_badge(image: &mut RgbaImage, badge: Badge) {
+    let size = image.width().min(image.height()) as f32;
+    match badge {
+        Badge::Count(count) => draw_count(image, count, size * 0.3),
+        Badge::Dot(color) => {
+            draw_circle(image, size * 0.2, color);
+        }
+    }
+}
+
+/// Draw a circle of `radius` touching the top-right corner; returns its center.
+fn draw_circle(image: &mut RgbaImage, radius: f32, color: [u8; 3]) -> (f32, f32) {
+    let center = (image.width() as f32 - radius, radius);
+    for y in 0..(radius * 2.0).ceil() as u32 {
+        for x in image.width().saturating_sub((radius * 2.0) as u32)..image.width() {
+            let dx = x as f32 + 0.5 - center.0;
+            let dy = y as f32 + 0.5 - center.1;
+            // One pixel of anti-aliasing at the edge
+            let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage > 0.0 && y < image.height() {
+                blend(image.get_pixel_mut(x, y), color, coverage);
+            }
+        }
+    }
+    center
+}
+
+/// Draw a red badge with `count` (or "+" above 9).
+fn draw_count(image: &mut RgbaImage, count: u32, radius: f32) {
+    let center = draw_circle(image, radius, [220, 40, 40]);
+    let glyph = &BADGE_GLYPHS[if count > 9 { 10 } else { count as usize }];
+    let scale = ((radius * 1.2) / 5.0).floor().max(1.0) as u32;
+    let left = (center.0 - 1.5 * scale as f32).round() as u32;
+    let top = (center.1 - 2.5 * scale as f32).round() as u32;
+    for (row, bits) in glyph.iter().enumerate() {
+        for col in 0..3 {
+            if bits & (0b100 >> col) == 0 {
+                continue;
+            }
+            for sy in 0..scale {
+                for sx in 0..scale {
+                    let x = left + col * scale + sx;
+                    let y = top + row as u32 * scale + sy;
+                    if x < image.width() && y < image.height() {
+                        image.put_pixel(x, y, Rgba([255, 255, 255, 255]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Blend an opaque color over a pixel with the given coverage.
+fn blend(pixel: &mut Rgba<u8>, color: [u8; 3], coverage: f32) {
+    for i in 0..3 {
+        pixel[i] = (pixel[i] as f32 * (1.0 - coverage) + color[i] as f32 * coverage) as u8;
+    }
+    pixel[3] = pixel[3].max((coverage * 255.0) as u8);
 }
 
 /// Convenience function to create an AppIcon from a file path.
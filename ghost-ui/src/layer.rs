@@ -6,7 +6,8 @@ use glyphon::{
 };
 use wgpu::{BindGroup, Device, MultisampleState, Queue, RenderPass, TextureFormat};
 
-use crate::{Skin, SkinData, SkinError, SpritePipeline};
+use crate::fonts;
+use crate::{AnimatedSkin, AnimationState, Skin, SkinData, SkinError, SpritePipeline};
 
 /// Text alignment options
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -150,16 +151,29 @@ impl Default for LayerConfig {
     }
 }
 
+/// What a layer shows
+enum LayerImage {
+    /// A single image
+    Still {
+        /// Image data (loaded but not yet on GPU)
+        data: SkinData,
+        /// GPU skin (created after GPU init)
+        skin: Option<Skin>,
+    },
+    /// A part of a composite skin (eyes, mouth, ...) with its own state
+    /// animations
+    Animated(AnimatedSkin),
+}
+
 /// A layer that can be rendered on top of the skin
 pub struct Layer {
-    /// Layer image data (loaded but not yet on GPU)
-    skin_data: SkinData,
-    /// GPU skin (created after GPU init)
-    skin: Option<Skin>,
+    image: LayerImage,
     /// Layer configuration
     pub config: LayerConfig,
     /// Computed position [x, y] in pixels relative to window origin
     position: [f32; 2],
+    /// Extra offset on top of the computed position (e.g. eyes following the cursor)
+    dynamic_offset: [f32; 2],
     /// Prepared bind group for rendering
     bind_group: Option<BindGroup>,
 }
@@ -167,11 +181,21 @@ pub struct Layer {
 impl Layer {
     /// Create a new layer from image data
     pub fn new(skin_data: SkinData, config: LayerConfig) -> Self {
+        Self::with_image(LayerImage::Still { data: skin_data, skin: None }, config)
+    }
+
+    /// Create a layer playing its own animations, e.g. a mouth with a
+    /// `talk` state drawn over the body
+    pub fn animated(skin: AnimatedSkin, config: LayerConfig) -> Self {
+        Self::with_image(LayerImage::Animated(skin), config)
+    }
+
+    fn with_image(image: LayerImage, config: LayerConfig) -> Self {
         Self {
-            skin_data,
-            skin: None,
+            image,
             config,
             position: [0.0, 0.0],
+            dynamic_offset: [0.0, 0.0],
             bind_group: None,
         }
     }
@@ -184,19 +208,53 @@ impl Layer {
 
     /// Initialize GPU resources for this layer
     pub fn init_gpu(&mut self, device: &Device, queue: &Queue) {
-        if self.skin.is_none() {
-            match Skin::from_skin_data(&self.skin_data, device, queue) {
-                Ok(skin) => {
-                    self.skin = Some(skin);
-                    log::info!("Layer GPU initialized: {}x{}", self.skin_data.width(), self.skin_data.height());
+        match &mut self.image {
+            LayerImage::Still { data, skin } if skin.is_none() => match Skin::from_skin_data(data, device, queue) {
+                Ok(gpu_skin) => {
+                    *skin = Some(gpu_skin);
+                    log::info!("Layer GPU initialized: {}x{}", data.width(), data.height());
                 }
                 Err(e) => {
                     log::error!("Failed to create layer skin: {}", e);
                 }
+            },
+            LayerImage::Still { .. } => {}
+            LayerImage::Animated(skin) => skin.init_gpu(device, queue),
+        }
+    }
+
+    /// Advance the layer's animation; true when it shows a new frame
+    pub fn update(&mut self, delta: f32) -> bool {
+        match &mut self.image {
+            LayerImage::Still { .. } => false,
+            LayerImage::Animated(skin) => {
+                skin.update(delta);
+                skin.take_changed()
             }
         }
     }
 
+    /// Play the layer's animation for the body's `state`, or its default
+    /// one when it has none for that state
+    pub fn follow_state(&mut self, state: AnimationState) {
+        let LayerImage::Animated(skin) = &mut self.image else { return };
+        let state = if skin.has_state(state) { state } else { AnimationState::Idle };
+        skin.set_state(state);
+    }
+
+    /// Whether the layer is moving through animation frames
+    pub fn is_animating(&self) -> bool {
+        matches!(&self.image, LayerImage::Animated(skin) if skin.is_animating())
+    }
+
+    /// Pick the animation frame by a level from 0 to 1 instead of time
+    /// (see `AnimatedSkin::set_level`)
+    pub fn set_level(&mut self, level: Option<f32>) {
+        if let LayerImage::Animated(skin) = &mut self.image {
+            skin.set_level(level);
+        }
+    }
+
     /// Calculate the layer position based on parent skin dimensions
     pub fn calculate_position(&mut self, parent_width: u32, parent_height: u32) {
         let (anchor_x, anchor_y) = self.config.anchor.as_fraction();
@@ -206,8 +264,8 @@ impl Layer {
         let anchor_py = parent_height as f32 * anchor_y;
 
         // Center the layer on the anchor point
-        let layer_width = self.skin_data.width() as f32;
-        let layer_height = self.skin_data.height() as f32;
+        let (layer_width, layer_height) = self.dimensions();
+        let (layer_width, layer_height) = (layer_width as f32, layer_height as f32);
 
         // Position so layer is centered on anchor
         let x = anchor_px - (layer_width * anchor_x) + self.config.offset[0];
@@ -218,17 +276,31 @@ impl Layer {
 
     /// Get the layer's skin for rendering
     pub fn skin(&self) -> Option<&Skin> {
-        self.skin.as_ref()
+        match &self.image {
+            LayerImage::Still { skin, .. } => skin.as_ref(),
+            LayerImage::Animated(skin) => skin.current_skin(),
+        }
     }
 
-    /// Get the computed position
+    /// Get the computed position, including the dynamic offset
     pub fn position(&self) -> [f32; 2] {
-        self.position
+        [
+            self.position[0] + self.dynamic_offset[0],
+            self.position[1] + self.dynamic_offset[1],
+        ]
+    }
+
+    /// Shift the layer from its anchored position without recalculating it
+    pub fn set_dynamic_offset(&mut self, offset: [f32; 2]) {
+        self.dynamic_offset = offset;
     }
 
     /// Get layer dimensions
     pub fn dimensions(&self) -> (u32, u32) {
-        self.skin_data.dimensions()
+        match &self.image {
+            LayerImage::Still { data, .. } => data.dimensions(),
+            LayerImage::Animated(skin) => skin.dimensions().unwrap_or((0, 0)),
+        }
     }
 
     /// Get the text to render (if any)
@@ -236,6 +308,11 @@ impl Layer {
         self.config.text.as_deref()
     }
 
+    /// Replace the text rendered on the layer
+    pub fn set_text(&mut self, text: Option<String>) {
+        self.config.text = text;
+    }
+
     /// Prepare the layer for rendering - creates bind group with current position
     pub fn prepare(
         &mut self,
@@ -258,12 +335,13 @@ impl Layer {
         scale_factor: f32,
         opacity: f32,
     ) {
-        let Some(skin) = &self.skin else { return };
+        let Some(skin) = self.skin() else { return };
 
         // Scale the position by the display scale factor
+        let position = self.position();
         let scaled_position = [
-            self.position[0] * scale_factor,
-            self.position[1] * scale_factor,
+            position[0] * scale_factor,
+            position[1] * scale_factor,
         ];
 
         log::debug!(
@@ -271,7 +349,7 @@ impl Layer {
             self.position, scaled_position, viewport, scale_factor, opacity, self.config.size
         );
 
-        self.bind_group = Some(pipeline.create_bind_group_at_position_with_size(
+        let bind_group = pipeline.create_bind_group_at_position_with_size(
             device,
             queue,
             skin,
@@ -280,7 +358,8 @@ impl Layer {
             viewport,
             scale_factor,
             self.config.size,
-        ));
+        );
+        self.bind_group = Some(bind_group);
     }
 
     /// Get the bind group for rendering
@@ -293,25 +372,63 @@ impl Layer {
 pub struct LayerRenderer {
     // Text rendering resources
     font_system: FontSystem,
+    /// Family of the color emoji font
+    emoji_family: Option<String>,
     swash_cache: SwashCache,
     text_atlas: Option<TextAtlas>,
     text_renderer: Option<GlyphonTextRenderer>,
-    text_buffer: Buffer,
+    /// Shaped text per layer, reshaped only when its text or size changes
+    shaped: Vec<Option<ShapedText>>,
     initialized: bool,
 }
 
+/// What a layer's shaped text depends on
+#[derive(PartialEq)]
+struct TextKey {
+    text: String,
+    font_size: f32,
+    /// Text area [width, height] in physical pixels
+    area: [f32; 2],
+}
+
+/// A layer's text laid out by glyphon
+struct ShapedText {
+    key: TextKey,
+    buffer: Buffer,
+    /// Width of the widest line
+    width: f32,
+}
+
+impl ShapedText {
+    fn new(font_system: &mut FontSystem, emoji_family: Option<&str>, key: TextKey) -> Self {
+        let line_height = key.font_size * 1.2;
+        let mut buffer = Buffer::new(font_system, Metrics::new(key.font_size, line_height));
+        buffer.set_size(font_system, key.area[0].max(1.0), key.area[1].max(line_height));
+        let attrs = Attrs::new().family(Family::SansSerif);
+        buffer.set_rich_text(font_system, fonts::with_emoji(&key.text, attrs, emoji_family), Shaping::Advanced);
+        buffer.shape_until_scroll(font_system);
+
+        let width = buffer
+            .layout_runs()
+            .map(|run| run.line_w)
+            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or(0.0);
+        Self { key, buffer, width }
+    }
+}
+
 impl LayerRenderer {
     pub fn new() -> Self {
-        let mut font_system = FontSystem::new();
+        let (font_system, emoji_family) = fonts::load_fonts();
         let swash_cache = SwashCache::new();
-        let text_buffer = Buffer::new(&mut font_system, Metrics::new(16.0, 20.0));
 
         Self {
             font_system,
+            emoji_family,
             swash_cache,
             text_atlas: None,
             text_renderer: None,
-            text_buffer,
+            shaped: Vec::new(),
             initialized: false,
         }
     }
@@ -337,137 +454,133 @@ impl LayerRenderer {
         log::info!("LayerRenderer GPU initialized");
     }
 
-    /// Prepare text for a layer
+    /// Prepare the text of all layers (layers without text are skipped)
     ///
     /// Text positioning is relative to the layer:
     /// - text_offset [x, y] is relative to the layer's top-left corner
     /// - text_align determines horizontal alignment within the layer
     /// - text_valign determines vertical alignment within the layer
     /// - text_padding provides spacing from edges [left, right, top, bottom]
+    ///
+    /// Text is only shaped again when a layer's text, font size or area
+    /// changed since the last call.
     pub fn prepare_text(
         &mut self,
         device: &Device,
         queue: &Queue,
-        layer: &Layer,
+        layers: &[Layer],
         viewport: [f32; 2],
         scale_factor: f32,
     ) {
-        let Some(text) = layer.text() else { return };
         let Some(atlas) = &mut self.text_atlas else { return };
         let Some(renderer) = &mut self.text_renderer else { return };
 
-        let font_size = layer.config.font_size * scale_factor;
-        let line_height = font_size * 1.2;
-
-        // Set up text buffer
-        self.text_buffer.set_metrics(
-            &mut self.font_system,
-            Metrics::new(font_size, line_height),
-        );
-
-        // Get layer dimensions (use configured size if available, else actual image size)
-        let (img_width, img_height) = layer.dimensions();
-        let (layer_width, layer_height) = layer.config.size
-            .map(|s| (s[0] as u32, s[1] as u32))
-            .unwrap_or((img_width, img_height));
-
-        let layer_width_scaled = layer_width as f32 * scale_factor;
-        let layer_height_scaled = layer_height as f32 * scale_factor;
-
-        // Scale padding
-        let [pad_left, pad_right, pad_top, pad_bottom] = layer.config.text_padding;
-        let pad_left = pad_left * scale_factor;
-        let pad_right = pad_right * scale_factor;
-        let pad_top = pad_top * scale_factor;
-        let pad_bottom = pad_bottom * scale_factor;
-
-        // Available text area within padding
-        let text_area_width = layer_width_scaled - pad_left - pad_right;
-        let text_area_height = layer_height_scaled - pad_top - pad_bottom;
-
-        self.text_buffer.set_size(
-            &mut self.font_system,
-            text_area_width.max(1.0),
-            text_area_height.max(line_height),
-        );
-
-        // Set text
-        let attrs = Attrs::new().family(Family::SansSerif);
-        self.text_buffer.set_text(
-            &mut self.font_system,
-            text,
-            attrs,
-            Shaping::Advanced,
-        );
-
-        // Shape the text
-        self.text_buffer.shape_until_scroll(&mut self.font_system);
-
-        // Get text dimensions
-        let text_width: f32 = self.text_buffer
-            .layout_runs()
-            .map(|run| run.line_w)
-            .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
-            .unwrap_or(0.0);
-        let text_height = line_height; // Single line for now
-
-        // Calculate layer position in screen coordinates
-        let pos = layer.position();
-        let layer_x = pos[0] * scale_factor;
-        let layer_y = pos[1] * scale_factor;
-
-        // Apply text offset (scaled)
-        let offset_x = layer.config.text_offset[0] * scale_factor;
-        let offset_y = layer.config.text_offset[1] * scale_factor;
-
-        // Calculate text X position based on alignment
-        let text_x = match layer.config.text_align {
-            TextAlign::Left => {
-                layer_x + pad_left + offset_x
-            }
-            TextAlign::Center => {
-                layer_x + pad_left + (text_area_width - text_width) / 2.0 + offset_x
-            }
-            TextAlign::Right => {
-                layer_x + layer_width_scaled - pad_right - text_width + offset_x
-            }
-        };
-
-        // Calculate text Y position based on vertical alignment
-        let text_y = match layer.config.text_valign {
-            TextVAlign::Top => {
-                layer_y + pad_top + offset_y
-            }
-            TextVAlign::Center => {
-                layer_y + pad_top + (text_area_height - text_height) / 2.0 + offset_y
-            }
-            TextVAlign::Bottom => {
-                layer_y + layer_height_scaled - pad_bottom - text_height + offset_y
-            }
-        };
+        self.shaped.resize_with(layers.len(), || None);
+        let mut placed = Vec::new();
+        for (i, layer) in layers.iter().enumerate() {
+            let Some(text) = layer.text() else {
+                self.shaped[i] = None;
+                continue;
+            };
+
+            let font_size = layer.config.font_size * scale_factor;
+            let line_height = font_size * 1.2;
+
+            // Get layer dimensions (use configured size if available, else actual image size)
+            let (img_width, img_height) = layer.dimensions();
+            let (layer_width, layer_height) = layer.config.size
+                .map(|s| (s[0] as u32, s[1] as u32))
+                .unwrap_or((img_width, img_height));
+
+            let layer_width_scaled = layer_width as f32 * scale_factor;
+            let layer_height_scaled = layer_height as f32 * scale_factor;
+
+            // Scale padding
+            let [pad_left, pad_right, pad_top, pad_bottom] = layer.config.text_padding;
+            let pad_left = pad_left * scale_factor;
+            let pad_right = pad_right * scale_factor;
+            let pad_top = pad_top * scale_factor;
+            let pad_bottom = pad_bottom * scale_factor;
+
+            // Available text area within padding
+            let text_area_width = layer_width_scaled - pad_left - pad_right;
+            let text_area_height = layer_height_scaled - pad_top - pad_bottom;
+
+            // Shape the text unless it's unchanged
+            let key = TextKey {
+                text: text.to_string(),
+                font_size,
+                area: [text_area_width, text_area_height],
+            };
+            let shaped = match self.shaped[i] {
+                Some(ref shaped) if shaped.key == key => shaped,
+                _ => self.shaped[i].insert(ShapedText::new(&mut self.font_system, self.emoji_family.as_deref(), key)),
+            };
+            let text_width = shaped.width;
+            let text_height = line_height; // Single line for now
+
+            // Calculate layer position in screen coordinates
+            let pos = layer.position();
+            let layer_x = pos[0] * scale_factor;
+            let layer_y = pos[1] * scale_factor;
+
+            // Apply text offset (scaled)
+            let offset_x = layer.config.text_offset[0] * scale_factor;
+            let offset_y = layer.config.text_offset[1] * scale_factor;
+
+            // Calculate text X position based on alignment
+            let text_x = match layer.config.text_align {
+                TextAlign::Left => {
+                    layer_x + pad_left + offset_x
+                }
+                TextAlign::Center => {
+                    layer_x + pad_left + (text_area_width - text_width) / 2.0 + offset_x
+                }
+                TextAlign::Right => {
+                    layer_x + layer_width_scaled - pad_right - text_width + offset_x
+                }
+            };
 
-        // Convert color to glyphon format
-        let [r, g, b, a] = layer.config.text_color;
-        let color = Color::rgba(
-            (r * 255.0) as u8,
-            (g * 255.0) as u8,
-            (b * 255.0) as u8,
-            (a * 255.0) as u8,
-        );
+            // Calculate text Y position based on vertical alignment
+            let text_y = match layer.config.text_valign {
+                TextVAlign::Top => {
+                    layer_y + pad_top + offset_y
+                }
+                TextVAlign::Center => {
+                    layer_y + pad_top + (text_area_height - text_height) / 2.0 + offset_y
+                }
+                TextVAlign::Bottom => {
+                    layer_y + layer_height_scaled - pad_bottom - text_height + offset_y
+                }
+            };
+
+            // Convert color to glyphon format
+            let [r, g, b, a] = layer.config.text_color;
+            let color = Color::rgba(
+                (r * 255.0) as u8,
+                (g * 255.0) as u8,
+                (b * 255.0) as u8,
+                (a * 255.0) as u8,
+            );
+            placed.push((i, text_x, text_y, color));
+        }
 
-        let text_area = TextArea {
-            buffer: &self.text_buffer,
-            left: text_x,
-            top: text_y,
-            scale: 1.0,
-            bounds: TextBounds {
-                left: 0,
-                top: 0,
-                right: viewport[0] as i32,
-                bottom: viewport[1] as i32,
-            },
-            default_color: color,
-        };
+        let text_areas = placed.into_iter().filter_map(|(i, left, top, color)| {
+            let shaped = self.shaped[i].as_ref()?;
+            Some(TextArea {
+                buffer: &shaped.buffer,
+                left,
+                top,
+                scale: 1.0,
+                bounds: TextBounds {
+                    left: 0,
+                    top: 0,
+                    right: viewport[0] as i32,
+                    bottom: viewport[1] as i32,
+                },
+                default_color: color,
+            })
+        });
 
         let resolution = Resolution {
             width: viewport[0] as u32,
@@ -480,7 +593,7 @@ impl LayerRenderer {
             &mut self.font_system,
             atlas,
             resolution,
-            [text_area],
+            text_areas,
             &mut self.swash_cache,
         ) {
             log::error!("Failed to prepare layer text: {:?}", e);
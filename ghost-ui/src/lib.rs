@@ -5,12 +5,17 @@
 //! ## Features
 //! - Transparent, borderless windows
 //! - PNG skin support for custom window shapes
+//! - Nine-slice skins that stretch to any panel size
+//! - Hot reload of skins and the app config edited while running
 //! - Cross-platform (macOS, Windows, Linux)
 //! - Always-on-top and click-through options
 //! - Draggable windows
 //! - Alpha-based hit testing (clicks on transparent areas pass through)
 //! - Focus-based opacity (opaque when focused, transparent when not)
-//! - System tray and dock icon helpers
+//! - Animated show and hide (fade, pop, slide, dissolve)
+//! - Work-area awareness (staying clear of the taskbar, dock or menu bar)
+//! - System tray and dock icon helpers, with tray menus updated in place
+//! - Global hotkeys
 //!
 //! ## Example
 //!
@@ -42,26 +47,89 @@
 //! }
 //! ```
 
+mod accessibility;
 pub mod animated_skin;
+mod context_menu;
+mod easing;
+pub mod fonts;
+mod gesture;
+mod hotkeys;
 pub mod icon;
 pub mod layer;
+mod monitor;
+mod motion;
+mod nine_slice;
+pub mod particles;
+mod placement;
 mod platform;
+mod random;
 mod renderer;
+pub mod signature;
 mod skin;
+mod skin_loader;
+mod skin_watch;
+mod snap;
+pub mod state_machine;
+mod transition;
 pub mod widget;
 mod window;
 
 // Icon helpers
-pub use icon::{icon, icon_bytes, AppIcon, IconError};
+pub use icon::{icon, icon_bytes, AppIcon, Badge, IconError, TrayMenuBuilder, TrayMenuHandle};
 
 // Skin helpers
 pub use skin::{skin, skin_bytes, Skin, SkinData, SkinError};
+pub use skin_loader::{SkinLoadEvent, SkinLoader};
+pub use skin_watch::SkinWatcher;
+pub use signature::PersonaTrust;
+
+// Nine-slice skins for resizable panels
+pub use nine_slice::{NineSlicePatch, NineSliceSkin};
 
 // Animated skin
-pub use animated_skin::{AnimatedSkin, Animation, AnimationState, PlayMode};
+pub use animated_skin::{
+    AnimatedSkin, Animation, AnimationState, LoadProgress, PlayMode, SpriteSheet, StateSettings,
+};
+pub use state_machine::{AnimationStateMachine, StateMachineConfig};
+
+// Gestures
+pub use gesture::RubGesture;
+
+// Right-click menu
+pub use context_menu::{poll_menu_event, ContextMenu, ContextMenuItem};
+
+// Global shortcuts
+pub use hotkeys::{register_hotkeys, Hotkey, HotkeyError, HotkeyKey};
+
+// Monitor selection
+pub use monitor::MonitorSelection;
+
+// Edge snapping
+pub use snap::Edge;
+
+// Callout placement
+pub use placement::CalloutFlip;
+
+// Animated moves
+pub use easing::Easing;
+
+// Show and hide animations
+pub use transition::Transition;
+
+// Physics movement
+pub use motion::{MotionConfig, MotionState};
+
+// Random timing for idle behavior
+pub use random::Rng;
+
+// Particles
+pub use particles::ParticleSystem;
 
 // Renderer
-pub use renderer::{ButtonRenderer, Renderer, RendererError, SpritePipeline};
+pub use renderer::{
+    ButtonRenderer, DynamicBuffer, Widgets, Effect, EffectParams, EffectStack, NineSliceRenderer, Recolor, Renderer, RendererError,
+    SpritePipeline, MAX_PALETTE_COLORS,
+};
 
 // Layer system
 pub use layer::{Layer, LayerAnchor, LayerConfig, LayerRenderer, TextAlign, TextVAlign};
@@ -69,12 +137,18 @@ pub use layer::{Layer, LayerAnchor, LayerConfig, LayerRenderer, TextAlign, TextV
 // Window
 pub use window::{
     run, run_with_app, run_with_app_and_callout, run_with_app_callout_and_extra,
-    CalloutApp, CalloutWindowConfig, ExtraWindow,
-    GhostApp, GhostEvent, GhostWindow, GhostWindowBuilder, GpuResources, WindowConfig, WindowError,
+    run_with_app_callout_and_extras, CalloutApp, CalloutLayout, CalloutWindowConfig, ExtraWindow, LinkedWindow,
+    GhostApp, GhostEvent, GhostWindow, GhostWindowBuilder, GpuResources, MoveRequest, ScreenRect, WindowConfig,
+    WindowError, WindowLayer,
 };
 
 // Widget system
-pub use widget::{Button, ButtonId, ButtonState, ButtonStyle, Origin, Widget};
+pub use widget::{
+    Align, Anchor, Axis, Button, ButtonId, ButtonImage, ButtonState, ButtonStyle, Checkbox, FocusAction, FocusManager,
+    LayoutItem, MarqueeLabel, Origin, ProgressBar, ProgressBarStyle, Slider, SliderId, SliderStyle, Stack, TextInput,
+    TextInputAction, TextInputId, TextInputStyle, Toggle, ToggleId, ToggleKind, ToggleStyle, ToggleSwitch, Tooltip,
+    TooltipStyle, Widget, WidgetAnimation,
+};
 
 // Re-export commonly used types
 pub use tao::event_loop::EventLoop;
@@ -0,0 +1,93 @@
+//! Choosing the display a window starts on
+
+use tao::dpi::{LogicalSize, PhysicalPosition};
+use tao::event_loop::EventLoopWindowTarget;
+use tao::monitor::MonitorHandle;
+
+/// Which monitor a window spawns on
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorSelection {
+    /// The OS primary monitor
+    Primary,
+    /// The monitor under the mouse cursor
+    Cursor,
+    /// A monitor by its position in the OS monitor list (0-based)
+    Index(usize),
+    /// A monitor whose name contains this text (case-insensitive)
+    Name(String),
+}
+
+impl MonitorSelection {
+    /// Parse `"primary"`, `"cursor"`, an index like `"1"`, or a monitor name
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("primary") {
+            Self::Primary
+        } else if value.eq_ignore_ascii_case("cursor") {
+            Self::Cursor
+        } else if let Ok(index) = value.parse() {
+            Self::Index(index)
+        } else {
+            Self::Name(value.to_string())
+        }
+    }
+
+    /// Find the selected monitor, falling back to the primary one
+    pub(crate) fn resolve<T>(&self, target: &EventLoopWindowTarget<T>) -> Option<MonitorHandle> {
+        let found = match self {
+            Self::Primary => None,
+            Self::Cursor => target
+                .cursor_position()
+                .ok()
+                .and_then(|p| target.monitor_from_point(p.x, p.y)),
+            Self::Index(index) => target.available_monitors().nth(*index),
+            Self::Name(name) => {
+                let name = name.to_lowercase();
+                target
+                    .available_monitors()
+                    .find(|m| m.name().is_some_and(|n| n.to_lowercase().contains(&name)))
+            }
+        };
+        if found.is_none() && *self != Self::Primary {
+            log::warn!("Monitor {:?} not found, using the primary monitor", self);
+        }
+        found.or_else(|| target.primary_monitor()).or_else(|| target.available_monitors().next())
+    }
+}
+
+/// Outer position of a window of `size` on `monitor`: `offset` (logical
+/// pixels from the monitor's top-left corner) or centered when None.
+/// Uses the monitor's own scale factor, so it is right on mixed-DPI setups.
+pub(crate) fn position_on(
+    monitor: &MonitorHandle,
+    size: LogicalSize<u32>,
+    offset: Option<[f32; 2]>,
+) -> PhysicalPosition<i32> {
+    let scale = monitor.scale_factor();
+    let origin = monitor.position();
+    let (x, y) = match offset {
+        Some([x, y]) => (x as f64 * scale, y as f64 * scale),
+        None => {
+            let area = monitor.size();
+            (
+                (area.width as f64 - size.width as f64 * scale) / 2.0,
+                (area.height as f64 - size.height as f64 * scale) / 2.0,
+            )
+        }
+    };
+    PhysicalPosition::new(origin.x + x.round() as i32, origin.y + y.round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(MonitorSelection::parse("primary"), MonitorSelection::Primary);
+        assert_eq!(MonitorSelection::parse(" Cursor "), MonitorSelection::Cursor);
+        assert_eq!(MonitorSelection::parse("1"), MonitorSelection::Index(1));
+        assert_eq!(MonitorSelection::parse("DELL U2720Q"), MonitorSelection::Name("DELL U2720Q".to_string()));
+        assert_eq!(MonitorSelection::parse("-1"), MonitorSelection::Name("-1".to_string()));
+    }
+}
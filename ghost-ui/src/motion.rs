@@ -0,0 +1,268 @@
+//! Physics-based idle movement - falling to the bottom of the screen,
+//! walking along it and being thrown, like classic Shimeji pets
+//!
+//! The engine lives in the window (see `GhostWindowBuilder::with_motion`)
+//! and runs while `GhostApp::motion_enabled` returns true. State changes are
+//! reported as `GhostEvent::MotionChanged` so the app can play the matching
+//! animation.
+
+use crate::random::Rng;
+use crate::window::ScreenRect;
+
+/// Falls slower than this (logical px/s) land instead of bouncing
+const LAND_SPEED: f32 = 200.0;
+
+/// Fastest throw in logical px/s
+const MAX_THROW_SPEED: f32 = 3000.0;
+
+/// Horizontal speed kept on each bounce off the floor
+const FLOOR_FRICTION: f32 = 0.7;
+
+/// Tuning of the movement engine (distances in logical pixels)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotionConfig {
+    /// Downward acceleration in px/s²
+    pub gravity: f32,
+    /// Walking speed in px/s
+    pub walk_speed: f32,
+    /// Longest single walk in px
+    pub max_walk_distance: f32,
+    /// Minimum seconds standing before the next walk
+    pub min_walk_interval: f32,
+    /// Maximum seconds standing before the next walk
+    pub max_walk_interval: f32,
+    /// Velocity kept when bouncing off the floor and screen edges (0-1)
+    pub bounce: f32,
+    /// Distance kept from the bottom edge (e.g. for the taskbar)
+    pub bottom_margin: f32,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            gravity: 2000.0,
+            walk_speed: 60.0,
+            max_walk_distance: 300.0,
+            min_walk_interval: 5.0,
+            max_walk_interval: 20.0,
+            bounce: 0.3,
+            bottom_margin: 48.0,
+        }
+    }
+}
+
+/// What the mascot is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionState {
+    /// Standing on the bottom of the screen
+    Standing,
+    /// Walking along the bottom of the screen
+    Walking { left: bool },
+    /// Falling or flying after a throw
+    Falling,
+    /// Held by the user
+    Dragged,
+}
+
+/// The movement engine
+pub(crate) struct Motion {
+    config: MotionConfig,
+    state: MotionState,
+    /// Last state reported to the app
+    reported: MotionState,
+    /// Precise window position in physical pixels
+    position: (f32, f32),
+    /// Velocity in physical px/s
+    velocity: (f32, f32),
+    /// Pixels left in the current walk
+    walk_remaining: f32,
+    /// Seconds until the next walk
+    timer: f32,
+    rng: Rng,
+}
+
+impl Motion {
+    pub(crate) fn new(config: MotionConfig) -> Self {
+        let mut motion = Self {
+            config,
+            state: MotionState::Standing,
+            reported: MotionState::Standing,
+            position: (0.0, 0.0),
+            velocity: (0.0, 0.0),
+            walk_remaining: 0.0,
+            timer: 0.0,
+            rng: Rng::new(),
+        };
+        motion.timer = motion.next_interval();
+        motion
+    }
+
+    /// The user picked the mascot up
+    pub(crate) fn grab(&mut self) {
+        self.state = MotionState::Dragged;
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// The user let go, with the drag velocity in physical px/s
+    pub(crate) fn throw(&mut self, (vx, vy): (f32, f32), scale: f32) {
+        let max = MAX_THROW_SPEED * scale;
+        self.state = MotionState::Falling;
+        self.velocity = (vx.clamp(-max, max), vy.clamp(-max, max));
+    }
+
+    /// Stop moving (the engine was disabled)
+    pub(crate) fn stop(&mut self) {
+        self.state = MotionState::Standing;
+        self.velocity = (0.0, 0.0);
+    }
+
+    /// The state, if it changed since the last call
+    pub(crate) fn take_change(&mut self) -> Option<MotionState> {
+        if self.state == self.reported {
+            return None;
+        }
+        self.reported = self.state;
+        Some(self.state)
+    }
+
+    /// Advance the simulation; returns the new window position if it moved
+    pub(crate) fn step(&mut self, window: ScreenRect, screen: ScreenRect, scale: f32, delta: f32) -> Option<(i32, i32)> {
+        // Follow moves made by someone else (drags, the app, the OS)
+        let rounded = (self.position.0.round() as i32, self.position.1.round() as i32);
+        if rounded != (window.x, window.y) {
+            self.position = (window.x as f32, window.y as f32);
+        }
+
+        let min_x = screen.x as f32;
+        let max_x = (screen.x + screen.width as i32 - window.width as i32) as f32;
+        let top = screen.y as f32;
+        let floor = (screen.y + screen.height as i32 - window.height as i32) as f32 - self.config.bottom_margin * scale;
+        let on_floor = (self.position.1 - floor).abs() <= 1.0;
+
+        match self.state {
+            MotionState::Dragged => return None,
+            MotionState::Standing | MotionState::Walking { .. } if !on_floor && self.position.1 < floor => {
+                self.state = MotionState::Falling;
+                self.velocity = (0.0, 0.0);
+            }
+            MotionState::Standing => {
+                self.position.1 = floor;
+                self.timer -= delta;
+                if self.timer <= 0.0 {
+                    self.timer = self.next_interval();
+                    let left = self.rng.next_u64().is_multiple_of(2);
+                    let max = self.config.max_walk_distance * scale;
+                    self.walk_remaining = self.rng.range(max / 4.0, max);
+                    self.state = MotionState::Walking { left };
+                }
+            }
+            MotionState::Walking { left } => {
+                self.position.1 = floor;
+                let step = (self.config.walk_speed * scale * delta).min(self.walk_remaining);
+                self.position.0 += if left { -step } else { step };
+                self.walk_remaining -= step;
+                let at_edge = self.position.0 <= min_x || self.position.0 >= max_x;
+                if self.walk_remaining <= 0.0 || at_edge {
+                    self.state = MotionState::Standing;
+                }
+            }
+            MotionState::Falling => self.fall(min_x, max_x, top, floor, scale, delta),
+        }
+
+        self.position.0 = self.position.0.clamp(min_x, max_x.max(min_x));
+        let position = (self.position.0.round() as i32, self.position.1.round() as i32);
+        (position != (window.x, window.y)).then_some(position)
+    }
+
+    /// Ballistic flight with bounces off the screen edges and floor
+    fn fall(&mut self, min_x: f32, max_x: f32, top: f32, floor: f32, scale: f32, delta: f32) {
+        let bounce = self.config.bounce.clamp(0.0, 1.0);
+        self.velocity.1 += self.config.gravity * scale * delta;
+        self.position.0 += self.velocity.0 * delta;
+        self.position.1 += self.velocity.1 * delta;
+
+        if self.position.0 < min_x || self.position.0 > max_x {
+            self.position.0 = self.position.0.clamp(min_x, max_x.max(min_x));
+            self.velocity.0 = -self.velocity.0 * bounce;
+        }
+        if self.position.1 < top {
+            self.position.1 = top;
+            self.velocity.1 = 0.0;
+        }
+        if self.position.1 >= floor {
+            self.position.1 = floor;
+            if self.velocity.1 * bounce > LAND_SPEED * scale {
+                self.velocity = (self.velocity.0 * FLOOR_FRICTION, -self.velocity.1 * bounce);
+            } else {
+                self.state = MotionState::Standing;
+                self.velocity = (0.0, 0.0);
+                self.timer = self.next_interval();
+            }
+        }
+    }
+
+    fn next_interval(&mut self) -> f32 {
+        let min = self.config.min_walk_interval.max(0.5);
+        let max = self.config.max_walk_interval.max(min);
+        self.rng.range(min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: ScreenRect = ScreenRect { x: 0, y: 0, width: 1920, height: 1080 };
+
+    fn window(x: i32, y: i32) -> ScreenRect {
+        ScreenRect { x, y, width: 200, height: 200 }
+    }
+
+    /// Step until the window stops moving, returns where it ended up
+    fn settle(motion: &mut Motion, mut position: (i32, i32)) -> (i32, i32) {
+        for _ in 0..1000 {
+            match motion.step(window(position.0, position.1), SCREEN, 1.0, 1.0 / 60.0) {
+                Some(moved) => position = moved,
+                None if motion.state == MotionState::Standing => break,
+                None => {}
+            }
+        }
+        position
+    }
+
+    #[test]
+    fn test_falls_to_the_floor() {
+        let mut motion = Motion::new(MotionConfig::default());
+        let floor = 1080 - 200 - 48;
+        assert_eq!(settle(&mut motion, (500, 0)), (500, floor));
+        assert_eq!(motion.take_change(), None);
+    }
+
+    #[test]
+    fn test_throw_bounces_off_the_walls() {
+        let mut motion = Motion::new(MotionConfig::default());
+        motion.grab();
+        assert_eq!(motion.take_change(), Some(MotionState::Dragged));
+        assert_eq!(motion.step(window(500, 100), SCREEN, 1.0, 0.1), None);
+
+        motion.throw((-100_000.0, -500.0), 1.0);
+        assert_eq!(motion.velocity.0, -MAX_THROW_SPEED);
+        let (x, y) = settle(&mut motion, (500, 100));
+        assert!((0..=1720).contains(&x));
+        assert_eq!(y, 1080 - 200 - 48);
+    }
+
+    #[test]
+    fn test_walks_after_standing() {
+        let config = MotionConfig {
+            min_walk_interval: 0.5,
+            max_walk_interval: 0.5,
+            ..Default::default()
+        };
+        let mut motion = Motion::new(config);
+        let floor = 1080 - 200 - 48;
+        motion.step(window(500, floor), SCREEN, 1.0, 0.6);
+        assert!(matches!(motion.take_change(), Some(MotionState::Walking { .. })));
+        assert!(motion.step(window(500, floor), SCREEN, 1.0, 0.5).is_some());
+    }
+}
@@ -0,0 +1,142 @@
+//! Nine-slice skins - PNG backgrounds that stretch to any size
+//!
+//! The image is cut into a 3x3 grid by four insets. Corners keep their size,
+//! edges stretch along one axis and the center along both, so a small frame
+//! image can back a panel, bubble or window of any size.
+
+use std::path::Path;
+
+use crate::{SkinData, SkinError};
+
+/// A skin image with the insets [left, top, right, bottom] (in image pixels)
+/// that mark its fixed corners
+#[derive(Clone)]
+pub struct NineSliceSkin {
+    data: SkinData,
+    insets: [f32; 4],
+}
+
+/// One of the nine pieces of a stretched skin
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSlicePatch {
+    /// Target area [x, y, width, height]
+    pub rect: [f32; 4],
+    /// Texture area [u0, v0, u1, v1]
+    pub uv: [f32; 4],
+}
+
+impl NineSliceSkin {
+    /// Load a skin image from a file
+    pub fn from_path(path: impl AsRef<Path>, insets: [f32; 4]) -> Result<Self, SkinError> {
+        Ok(Self::new(SkinData::from_path(path)?, insets))
+    }
+
+    /// Use loaded skin data; insets are clamped to the image
+    pub fn new(data: SkinData, insets: [f32; 4]) -> Self {
+        let [width, height] = [data.width() as f32, data.height() as f32];
+        let [left, top, right, bottom] = insets.map(|inset| inset.max(0.0));
+        let left = left.min(width);
+        let top = top.min(height);
+        Self {
+            data,
+            insets: [left, top, right.min(width - left), bottom.min(height - top)],
+        }
+    }
+
+    pub fn data(&self) -> &SkinData {
+        &self.data
+    }
+
+    /// Insets [left, top, right, bottom] in image pixels
+    pub fn insets(&self) -> [f32; 4] {
+        self.insets
+    }
+
+    /// The pieces covering `rect` [x, y, width, height]; corners are drawn
+    /// `scale` times their image size, shrunk if `rect` is too small for them
+    pub fn patches(&self, rect: [f32; 4], scale: f32) -> Vec<NineSlicePatch> {
+        let [x, y, width, height] = rect;
+        let [left, top, right, bottom] = self.insets;
+        let [image_width, image_height] = [self.data.width() as f32, self.data.height() as f32];
+        if width <= 0.0 || height <= 0.0 || image_width <= 0.0 || image_height <= 0.0 {
+            return Vec::new();
+        }
+
+        // Target and texture positions of the grid lines along one axis
+        let lines = |start: f32, size: f32, near: f32, far: f32, image_size: f32| {
+            let fit = (size / ((near + far) * scale)).min(1.0);
+            let [near_px, far_px] = [near * scale * fit, far * scale * fit];
+            (
+                [start, start + near_px, start + size - far_px, start + size],
+                [0.0, near / image_size, 1.0 - far / image_size, 1.0],
+            )
+        };
+        let (xs, us) = lines(x, width, left, right, image_width);
+        let (ys, vs) = lines(y, height, top, bottom, image_height);
+
+        let mut patches = Vec::with_capacity(9);
+        for row in 0..3 {
+            for column in 0..3 {
+                let patch = NineSlicePatch {
+                    rect: [xs[column], ys[row], xs[column + 1] - xs[column], ys[row + 1] - ys[row]],
+                    uv: [us[column], vs[row], us[column + 1], vs[row + 1]],
+                };
+                // Skip pieces that are empty on screen or in the image
+                if patch.rect[2] > 0.0 && patch.rect[3] > 0.0 && patch.uv[2] > patch.uv[0] && patch.uv[3] > patch.uv[1] {
+                    patches.push(patch);
+                }
+            }
+        }
+        patches
+    }
+}
+
+impl std::fmt::Debug for NineSliceSkin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NineSliceSkin")
+            .field("size", &self.data.dimensions())
+            .field("insets", &self.insets)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skin(width: u32, height: u32, insets: [f32; 4]) -> NineSliceSkin {
+        let image = image::DynamicImage::new_rgba8(width, height);
+        NineSliceSkin::new(SkinData::from_image(&image).unwrap(), insets)
+    }
+
+    #[test]
+    fn test_patches() {
+        let skin = skin(30, 30, [10.0; 4]);
+        let patches = skin.patches([0.0, 0.0, 100.0, 50.0], 1.0);
+        assert_eq!(patches.len(), 9);
+        // Corners keep their size, the center stretches
+        assert_eq!(patches[0].rect, [0.0, 0.0, 10.0, 10.0]);
+        assert_eq!(patches[4].rect, [10.0, 10.0, 80.0, 30.0]);
+        assert_eq!(patches[8].rect, [90.0, 40.0, 10.0, 10.0]);
+        let third = 10.0 / 30.0;
+        assert_eq!(patches[4].uv, [third, third, 1.0 - third, 1.0 - third]);
+
+        // Corners scale with the display
+        assert_eq!(skin.patches([0.0, 0.0, 100.0, 50.0], 2.0)[0].rect, [0.0, 0.0, 20.0, 20.0]);
+    }
+
+    #[test]
+    fn test_small_rect_shrinks_corners() {
+        let patches = skin(30, 30, [10.0; 4]).patches([0.0, 0.0, 10.0, 10.0], 1.0);
+        // No room for edges or center, only the corners at half size
+        assert_eq!(patches.len(), 4);
+        assert_eq!(patches[0].rect, [0.0, 0.0, 5.0, 5.0]);
+        assert!(skin(30, 30, [10.0; 4]).patches([0.0, 0.0, 0.0, 10.0], 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_insets_clamped() {
+        let skin = skin(20, 20, [15.0, -5.0, 15.0, 40.0]);
+        assert_eq!(skin.insets(), [15.0, 0.0, 5.0, 20.0]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Particle bursts (e.g. floating hearts) drawn on top of the skin
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use wgpu::{BindGroup, Device, Queue, RenderPass};
+
+use crate::renderer::SpritePipeline;
+use crate::{Skin, SkinError};
+
+/// Upward speed of new particles in pixels per second
+const RISE_SPEED: f32 = 60.0;
+
+/// A single particle
+struct Particle {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    age: f32,
+    lifetime: f32,
+}
+
+impl Particle {
+    /// Fully visible, then fading out over the last 40% of its life
+    fn opacity(&self) -> f32 {
+        let t = self.age / self.lifetime;
+        ((1.0 - t) / 0.4).clamp(0.0, 1.0)
+    }
+}
+
+/// A set of short-lived sprites that rise and fade out
+pub struct ParticleSystem {
+    image: DynamicImage,
+    skin: Option<Skin>,
+    particles: Vec<Particle>,
+    bind_groups: Vec<BindGroup>,
+    /// Drawn size in pixels
+    size: f32,
+    /// Seconds each particle lives
+    lifetime: f32,
+}
+
+impl ParticleSystem {
+    /// Create a particle system from an image
+    pub fn new(image: DynamicImage, size: f32) -> Self {
+        Self {
+            image,
+            skin: None,
+            particles: Vec::new(),
+            bind_groups: Vec::new(),
+            size,
+            lifetime: 1.4,
+        }
+    }
+
+    /// Load the particle image from a file
+    pub fn from_path(path: &str, size: f32) -> Result<Self, SkinError> {
+        Ok(Self::new(image::open(path)?, size))
+    }
+
+    /// Built-in pink heart particles
+    pub fn hearts(size: f32) -> Self {
+        Self::new(DynamicImage::ImageRgba8(heart_image(32)), size)
+    }
+
+    /// Set how long each particle lives in seconds
+    pub fn with_lifetime(mut self, lifetime: f32) -> Self {
+        self.lifetime = lifetime.max(0.1);
+        self
+    }
+
+    /// Initialize GPU resources
+    pub fn init_gpu(&mut self, device: &Device, queue: &Queue) {
+        if self.skin.is_none() {
+            match Skin::from_image(self.image.clone(), device, queue) {
+                Ok(skin) => self.skin = Some(skin),
+                Err(e) => log::error!("Failed to create particle skin: {}", e),
+            }
+        }
+    }
+
+    /// Emit `count` particles around `origin` (window pixels)
+    pub fn burst(&mut self, origin: [f32; 2], count: usize) {
+        for i in 0..count {
+            // Fan out evenly with a little variation in speed
+            let spread = if count > 1 { i as f32 / (count - 1) as f32 - 0.5 } else { 0.0 };
+            let speed = RISE_SPEED * (0.8 + 0.1 * (i % 4) as f32);
+            self.particles.push(Particle {
+                position: [origin[0] - self.size / 2.0, origin[1] - self.size / 2.0],
+                velocity: [spread * RISE_SPEED, -speed],
+                age: 0.0,
+                lifetime: self.lifetime * (0.85 + 0.05 * (i % 4) as f32),
+            });
+        }
+    }
+
+    /// Move and age particles
+    pub fn update(&mut self, delta: f32) {
+        for particle in &mut self.particles {
+            particle.age += delta;
+            particle.position[0] += particle.velocity[0] * delta;
+            particle.position[1] += particle.velocity[1] * delta;
+            // Slow the sideways drift
+            particle.velocity[0] *= 1.0 - (delta * 1.5).min(1.0);
+        }
+        self.particles.retain(|p| p.age < p.lifetime);
+    }
+
+    /// Whether any particle is alive
+    pub fn is_active(&self) -> bool {
+        !self.particles.is_empty()
+    }
+
+    /// Prepare bind groups for the live particles
+    pub fn prepare(
+        &mut self,
+        pipeline: &SpritePipeline,
+        device: &Device,
+        queue: &Queue,
+        viewport: [f32; 2],
+        scale_factor: f32,
+        opacity: f32,
+    ) {
+        self.bind_groups.clear();
+        let Some(ref skin) = self.skin else { return };
+
+        for particle in &self.particles {
+            let position = [particle.position[0] * scale_factor, particle.position[1] * scale_factor];
+            self.bind_groups.push(pipeline.create_bind_group_at_position_with_size(
+                device,
+                queue,
+                skin,
+                opacity * particle.opacity(),
+                position,
+                viewport,
+                scale_factor,
+                Some([self.size, self.size]),
+            ));
+        }
+    }
+
+    /// Render the prepared particles
+    pub fn render<'a>(&'a self, pipeline: &'a SpritePipeline, render_pass: &mut RenderPass<'a>) {
+        for bind_group in &self.bind_groups {
+            pipeline.render_bind_group(render_pass, bind_group);
+        }
+    }
+}
+
+/// Draw a heart shape, anti-aliased with 4x4 supersampling
+fn heart_image(size: u32) -> RgbaImage {
+    const SAMPLES: u32 = 4;
+    let inside = |x: f32, y: f32| {
+        let a = x * x + y * y - 1.0;
+        a * a * a - x * x * y * y * y <= 0.0
+    };
+
+    RgbaImage::from_fn(size, size, |px, py| {
+        let mut hits = 0;
+        for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+                // Map the pixel into [-1.3, 1.3], y pointing up
+                let fx = (px as f32 + (sx as f32 + 0.5) / SAMPLES as f32) / size as f32;
+                let fy = (py as f32 + (sy as f32 + 0.5) / SAMPLES as f32) / size as f32;
+                if inside(fx * 2.6 - 1.3, 1.3 - fy * 2.6) {
+                    hits += 1;
+                }
+            }
+        }
+        let alpha = (hits * 255 / (SAMPLES * SAMPLES)) as u8;
+        Rgba([255, 105, 150, alpha])
+    })
+}
@@ -0,0 +1,106 @@
+//! Keeping the callout and linked windows on screen next to the mascot
+
+use crate::window::ScreenRect;
+
+/// Sides of the mascot the callout moved to, to stay on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CalloutFlip {
+    /// Mirrored to the mascot's other side (left <-> right)
+    pub horizontal: bool,
+    /// Mirrored to the mascot's other side (above <-> below)
+    pub vertical: bool,
+}
+
+/// Position of a `size` callout window at `offset` from the `main` window,
+/// mirrored across the main window on each axis where that leaves less of
+/// it off `screen`
+pub(crate) fn place_callout(
+    main: ScreenRect,
+    size: (u32, u32),
+    offset: [i32; 2],
+    screen: ScreenRect,
+) -> ((i32, i32), CalloutFlip) {
+    let (x, horizontal) = place_axis(
+        (main.x, main.width as i32),
+        offset[0],
+        size.0 as i32,
+        (screen.x, screen.width as i32),
+    );
+    let (y, vertical) = place_axis(
+        (main.y, main.height as i32),
+        offset[1],
+        size.1 as i32,
+        (screen.y, screen.height as i32),
+    );
+    ((x, y), CalloutFlip { horizontal, vertical })
+}
+
+/// Start of a `length` long window at `offset` from `main` (start,
+/// length) on one axis, and whether it was mirrored to stay on `screen`
+fn place_axis(main: (i32, i32), offset: i32, length: i32, screen: (i32, i32)) -> (i32, bool) {
+    let placed = main.0 + offset;
+    let overflow = |start: i32| (screen.0 - start).max(0) + (start + length - (screen.0 + screen.1)).max(0);
+    if overflow(placed) == 0 {
+        return (placed, false);
+    }
+    // The same gap from the main window's far edge
+    let mirrored = main.0 + main.1 - offset - length;
+    if overflow(mirrored) < overflow(placed) {
+        (mirrored, true)
+    } else {
+        (placed, false)
+    }
+}
+
+/// Move a window of `size` at `position` the least needed to lie inside
+/// `area` (to its top-left corner if it is larger)
+pub(crate) fn keep_inside(position: (i32, i32), size: (u32, u32), area: ScreenRect) -> (i32, i32) {
+    let clamp = |start: i32, length: u32, area_start: i32, area_length: u32| {
+        start.min(area_start + area_length as i32 - length as i32).max(area_start)
+    };
+    (
+        clamp(position.0, size.0, area.x, area.width),
+        clamp(position.1, size.1, area.y, area.height),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> ScreenRect {
+        ScreenRect { x, y, width, height }
+    }
+
+    #[test]
+    fn test_place_callout() {
+        let screen = rect(0, 0, 1920, 1080);
+        let main = rect(800, 500, 200, 200);
+        // Up and to the right of the mascot, fits as is
+        assert_eq!(place_callout(main, (300, 100), [150, -110], screen), ((950, 390), CalloutFlip::default()));
+
+        // Against the top-right corner: mirrored on both axes
+        let main = rect(1700, 0, 200, 200);
+        let ((x, y), flip) = place_callout(main, (300, 100), [150, -110], screen);
+        assert_eq!((x, y), (1450, 210));
+        assert!(flip.horizontal && flip.vertical);
+    }
+
+    #[test]
+    fn test_callout_stays_when_mirroring_is_no_better() {
+        // Too wide for either side
+        let screen = rect(0, 0, 400, 1080);
+        let main = rect(100, 500, 200, 200);
+        let ((x, _), flip) = place_callout(main, (600, 100), [150, -110], screen);
+        assert_eq!(x, 250);
+        assert!(!flip.horizontal);
+    }
+
+    #[test]
+    fn test_keep_inside() {
+        let area = rect(0, 0, 1920, 1080);
+        assert_eq!(keep_inside((100, 100), (200, 200), area), (100, 100));
+        assert_eq!(keep_inside((1800, -50), (200, 200), area), (1720, 0));
+        assert_eq!(keep_inside((50, 50), (3000, 200), area), (0, 50));
+    }
+}
@@ -65,3 +65,124 @@ fn configure_linux(_window: &Window, _click_through: bool) {
 fn configure_window(_window: &Window, _click_through: bool) {
     // Fallback for other platforms
 }
+
+/// Keep a window stacked below normal windows off the taskbar and window
+/// switchers, so it sits on the desktop like a widget (or undo that).
+pub fn pin_to_desktop(window: &Window, pinned: bool) {
+    #[cfg(target_os = "macos")]
+    pin_to_desktop_macos(window, pinned);
+
+    #[cfg(target_os = "linux")]
+    pin_to_desktop_linux(window, pinned);
+
+    // Windows: tao's always-on-bottom keeps the window at the bottom of
+    // the z-order, and borderless windows have no taskbar button anyway
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let _ = (window, pinned);
+}
+
+#[cfg(target_os = "macos")]
+fn pin_to_desktop_macos(window: &Window, pinned: bool) {
+    use cocoa::appkit::{NSWindow, NSWindowCollectionBehavior};
+    use tao::platform::macos::WindowExtMacOS;
+
+    // Stay put on every space and out of Mission Control and Cmd-`
+    let desktop = NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces
+        | NSWindowCollectionBehavior::NSWindowCollectionBehaviorStationary
+        | NSWindowCollectionBehavior::NSWindowCollectionBehaviorIgnoresCycle;
+    unsafe {
+        let ns_window = window.ns_window() as cocoa::base::id;
+        let behavior = ns_window.collectionBehavior();
+        ns_window.setCollectionBehavior_(if pinned { behavior | desktop } else { behavior - desktop });
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn pin_to_desktop_linux(window: &Window, pinned: bool) {
+    use gtk::prelude::GtkWindowExt;
+    use tao::platform::unix::WindowExtUnix;
+
+    let gtk_window = window.gtk_window();
+    gtk_window.set_skip_taskbar_hint(pinned);
+    gtk_window.set_skip_pager_hint(pinned);
+}
+
+/// How far the taskbar, dock or menu bar reach into the monitor the window
+/// is on, in physical pixels [left, top, right, bottom]. Uses the screen's
+/// visible frame (menu bar, Dock) on macOS.
+#[cfg(target_os = "macos")]
+pub fn work_area_insets(window: &Window) -> Option<[i32; 4]> {
+    use cocoa::appkit::{NSScreen, NSWindow};
+    use cocoa::base::{id, nil};
+    use tao::platform::macos::WindowExtMacOS;
+
+    // Cocoa measures in points from the bottom-left corner
+    let (frame, visible) = unsafe {
+        let screen = (window.ns_window() as id).screen();
+        if screen == nil {
+            return None;
+        }
+        (NSScreen::frame(screen), NSScreen::visibleFrame(screen))
+    };
+    let scale = window.scale_factor();
+    let inset = |points: f64| (points * scale).round() as i32;
+    Some([
+        inset(visible.origin.x - frame.origin.x),
+        inset((frame.origin.y + frame.size.height) - (visible.origin.y + visible.size.height)),
+        inset((frame.origin.x + frame.size.width) - (visible.origin.x + visible.size.width)),
+        inset(visible.origin.y - frame.origin.y),
+    ])
+}
+
+/// Work area insets from the monitor info (taskbar)
+#[cfg(target_os = "windows")]
+pub fn work_area_insets(window: &Window) -> Option<[i32; 4]> {
+    use tao::platform::windows::WindowExtWindows;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        let monitor = MonitorFromWindow(HWND(window.hwnd() as _), MONITOR_DEFAULTTONEAREST);
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return None;
+        }
+    }
+    let (frame, work) = (info.rcMonitor, info.rcWork);
+    Some([
+        work.left - frame.left,
+        work.top - frame.top,
+        frame.right - work.right,
+        frame.bottom - work.bottom,
+    ])
+}
+
+/// Work area insets from the GDK monitor (panels, docks)
+#[cfg(target_os = "linux")]
+pub fn work_area_insets(window: &Window) -> Option<[i32; 4]> {
+    use gtk::gdk::prelude::MonitorExt;
+    use gtk::prelude::WidgetExt;
+    use tao::platform::unix::WindowExtUnix;
+
+    // GDK measures in logical pixels
+    let gdk_window = window.gtk_window().window()?;
+    let monitor = gdk_window.display().monitor_at_window(&gdk_window)?;
+    let (frame, work) = (monitor.geometry(), monitor.workarea());
+    let scale = window.scale_factor();
+    let inset = |pixels: i32| (pixels as f64 * scale).round() as i32;
+    Some([
+        inset(work.x() - frame.x()),
+        inset(work.y() - frame.y()),
+        inset((frame.x() + frame.width()) - (work.x() + work.width())),
+        inset((frame.y() + frame.height()) - (work.y() + work.height())),
+    ])
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn work_area_insets(_window: &Window) -> Option<[i32; 4]> {
+    // Unknown here: the whole monitor is usable
+    None
+}
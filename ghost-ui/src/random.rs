@@ -0,0 +1,60 @@
+//! Small random number generator for animation timing and idle behavior
+
+/// xorshift64 generator; fast and good enough for picking intervals and
+/// directions, not for anything that needs real randomness
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Seeded from the clock
+    pub fn new() -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::with_seed(seed)
+    }
+
+    /// The same sequence for the same seed (for tests)
+    pub fn with_seed(seed: u64) -> Self {
+        // An all-zero state would only ever give zeros
+        Self { state: seed | 1 }
+    }
+
+    /// Next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Random value in `min..max`
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        let t = (self.next_u64() % 10_000) as f32 / 10_000.0;
+        min + (max - min) * t
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let mut rng = Rng::with_seed(0);
+        for _ in 0..1000 {
+            let value = rng.range(2.0, 5.0);
+            assert!((2.0..5.0).contains(&value));
+        }
+        assert_eq!(Rng::with_seed(7).next_u64(), Rng::with_seed(7).next_u64());
+    }
+}
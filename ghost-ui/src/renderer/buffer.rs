@@ -0,0 +1,68 @@
+//! GPU buffers reused across frames
+
+use wgpu::{Buffer, BufferUsages, Device, Queue};
+
+/// Smallest allocation, so tiny meshes don't regrow every few frames
+const MIN_CAPACITY: u64 = 1024;
+
+/// A vertex or index buffer rewritten each frame with `Queue::write_buffer`,
+/// reallocated only when the data outgrows it
+pub struct DynamicBuffer {
+    label: &'static str,
+    usage: BufferUsages,
+    buffer: Option<Buffer>,
+    capacity: u64,
+}
+
+impl DynamicBuffer {
+    /// Create an empty buffer; nothing is allocated until the first write
+    pub fn new(label: &'static str, usage: BufferUsages) -> Self {
+        Self {
+            label,
+            usage: usage | BufferUsages::COPY_DST,
+            buffer: None,
+            capacity: 0,
+        }
+    }
+
+    /// Upload `data` to the start of the buffer, growing it if needed
+    pub fn write(&mut self, device: &Device, queue: &Queue, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        // Copies must be a multiple of four bytes (e.g. an odd u16 index count)
+        let size = padded_size(data.len() as u64);
+        if self.buffer.is_none() || size > self.capacity {
+            self.capacity = grown_capacity(size);
+            self.buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(self.label),
+                size: self.capacity,
+                usage: self.usage,
+                mapped_at_creation: false,
+            }));
+        }
+        let Some(ref buffer) = self.buffer else { return };
+        if size == data.len() as u64 {
+            queue.write_buffer(buffer, 0, data);
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(size as usize, 0);
+            queue.write_buffer(buffer, 0, &padded);
+        }
+    }
+
+    /// The underlying buffer, once something was written
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+}
+
+/// `len` rounded up to the copy alignment
+fn padded_size(len: u64) -> u64 {
+    len.div_ceil(wgpu::COPY_BUFFER_ALIGNMENT) * wgpu::COPY_BUFFER_ALIGNMENT
+}
+
+/// Capacity for `size` bytes, doubling so growth is rare
+fn grown_capacity(size: u64) -> u64 {
+    size.next_power_of_two().max(MIN_CAPACITY)
+}
@@ -1,12 +1,19 @@
 //! Button rendering pipeline
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
 use bytemuck::{Pod, Zeroable};
 use wgpu::util::DeviceExt;
 use wgpu::{
-    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, TextureFormat,
+    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, Sampler, TextureFormat,
 };
 
-use crate::widget::{Button, Widget};
+use super::widget_text::WidgetText;
+use super::DynamicBuffer;
+use crate::widget::{Button, ButtonId, ProgressBar, Slider, TextInput, Toggle, ToggleKind, Tooltip};
+use crate::{Skin, SkinData};
 
 /// Vertex for button rendering
 #[repr(C)]
@@ -14,11 +21,16 @@ use crate::widget::{Button, Widget};
 struct ButtonVertex {
     position: [f32; 2],
     color: [f32; 4],
+    /// Rounded rectangle [x, y, width, height] drawn by the quad
+    rect: [f32; 4],
+    /// [corner radius or checkmark half-width, edge softness, shape, 0];
+    /// shape 0 is a rounded rectangle, 1 a checkmark
+    params: [f32; 4],
 }
 
 impl ButtonVertex {
-    const ATTRIBS: [wgpu::VertexAttribute; 2] =
-        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+    const ATTRIBS: [wgpu::VertexAttribute; 4] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4, 2 => Float32x4, 3 => Float32x4];
 
     fn desc() -> wgpu::VertexBufferLayout<'static> {
         wgpu::VertexBufferLayout {
@@ -29,6 +41,28 @@ impl ButtonVertex {
     }
 }
 
+/// Vertex for button images
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ImageVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+    opacity: f32,
+}
+
+impl ImageVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ImageVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
 /// Uniforms for button rendering
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -36,15 +70,38 @@ struct ButtonUniforms {
     viewport: [f32; 4], // width, height, 0, 0
 }
 
-/// Renderer for button widgets
+/// Widgets drawn by `ButtonRenderer`
+#[derive(Default)]
+pub struct Widgets<'a> {
+    pub buttons: Vec<&'a Button>,
+    pub toggles: Vec<&'a dyn Toggle>,
+    pub sliders: Vec<&'a Slider>,
+    pub progress_bars: Vec<&'a ProgressBar>,
+    /// In the order given to `ButtonRenderer::layout_text_inputs`
+    pub text_inputs: Vec<&'a TextInput>,
+    /// Drawn over the other widgets while it shows
+    pub tooltip: Option<&'a Tooltip>,
+    /// Button with keyboard focus, drawn with a focus ring
+    pub focused: Option<ButtonId>,
+}
+
+/// Renderer for buttons, switches, checkboxes, sliders, progress bars,
+/// text inputs and tooltips
 pub struct ButtonRenderer {
     pipeline: RenderPipeline,
     bind_group_layout: BindGroupLayout,
     uniform_buffer: Buffer,
     bind_group: BindGroup,
-    vertex_buffer: Option<Buffer>,
-    index_buffer: Option<Buffer>,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
     index_count: u32,
+    /// Indices from here on draw the tooltip box
+    tooltip_start: u32,
+    format: TextureFormat,
+    /// Text of text inputs and tooltips (loading fonts once there are any)
+    text: Option<WidgetText>,
+    /// Buttons drawn with an image
+    images: ImagePass,
 }
 
 impl ButtonRenderer {
@@ -128,20 +185,27 @@ impl ButtonRenderer {
             bind_group_layout,
             uniform_buffer,
             bind_group,
-            vertex_buffer: None,
-            index_buffer: None,
+            vertex_buffer: DynamicBuffer::new("Button Vertex Buffer", wgpu::BufferUsages::VERTEX),
+            index_buffer: DynamicBuffer::new("Button Index Buffer", wgpu::BufferUsages::INDEX),
             index_count: 0,
+            tooltip_start: 0,
+            format,
+            text: None,
+            images: ImagePass::new(device, format),
         }
     }
 
-    /// Prepare buttons for rendering
-    pub fn prepare(
-        &mut self,
-        device: &Device,
-        queue: &Queue,
-        buttons: &[&Button],
-        viewport: [f32; 2],
-    ) {
+    /// Shape the text of text inputs before `prepare`, telling each where
+    /// its characters are (for the caret, selection and clicks)
+    pub fn layout_text_inputs(&mut self, inputs: Vec<&mut TextInput>) {
+        if inputs.is_empty() && self.text.is_none() {
+            return;
+        }
+        self.text.get_or_insert_with(WidgetText::new).layout(inputs);
+    }
+
+    /// Prepare widgets for rendering
+    pub fn prepare(&mut self, device: &Device, queue: &Queue, widgets: &Widgets, viewport: [f32; 2]) {
         // Update uniforms
         queue.write_buffer(
             &self.uniform_buffer,
@@ -154,45 +218,74 @@ impl ButtonRenderer {
         // Generate vertices for all visible buttons
         let mut vertices = Vec::new();
         let mut indices = Vec::new();
+        let mut images = Vec::new();
 
-        for button in buttons {
-            if !button.is_visible() {
+        for button in &widgets.buttons {
+            if !button.is_drawn() {
                 continue;
             }
 
-            let bounds = button.screen_bounds(viewport[1]);
-            let color = button.current_background();
-            let base_idx = vertices.len() as u16;
+            let bounds = button.drawn_bounds(viewport[1]);
+            let style = button.style();
+            let radius = style.border_radius.min(bounds[2] / 2.0).min(bounds[3] / 2.0);
+            let faded = |color| fade(color, button.opacity());
+
+            // Shadow first, so the button covers it; an image draws its own
+            if style.has_shadow() && button.image().is_none() {
+                let [dx, dy] = style.shadow_offset;
+                let shadow = [bounds[0] + dx, bounds[1] + dy, bounds[2], bounds[3]];
+                let color = faded(style.shadow_color);
+                push_rounded_rect(&mut vertices, &mut indices, shadow, radius, style.shadow_blur, color);
+            }
+            // The button covers all but the ring's outer edge
+            if widgets.focused == Some(button.id()) && button.is_visible() {
+                let width = style.focus_width;
+                let ring = [bounds[0] - width, bounds[1] - width, bounds[2] + width * 2.0, bounds[3] + width * 2.0];
+                push_rounded_rect(&mut vertices, &mut indices, ring, radius + width, 0.0, faded(style.focus_color));
+            }
+            if button.image().is_some() {
+                images.push((*button, bounds));
+            } else {
+                push_rounded_rect(&mut vertices, &mut indices, bounds, radius, 0.0, faded(button.current_background()));
+            }
+        }
+        self.images.prepare(device, queue, &self.uniform_buffer, &widgets.buttons, &images);
+
+        for toggle in &widgets.toggles {
+            if toggle.is_drawn() {
+                push_toggle(&mut vertices, &mut indices, *toggle, viewport[1]);
+            }
+        }
+
+        for slider in &widgets.sliders {
+            if slider.is_drawn() {
+                push_slider(&mut vertices, &mut indices, slider, viewport[1]);
+            }
+        }
+
+        for bar in &widgets.progress_bars {
+            if bar.is_drawn() {
+                push_progress_bar(&mut vertices, &mut indices, bar, viewport[1]);
+            }
+        }
 
-            // Simple rectangle (no rounded corners for now)
-            let x = bounds[0];
-            let y = bounds[1];
-            let w = bounds[2];
-            let h = bounds[3];
-
-            vertices.push(ButtonVertex {
-                position: [x, y],
-                color,
-            });
-            vertices.push(ButtonVertex {
-                position: [x + w, y],
-                color,
-            });
-            vertices.push(ButtonVertex {
-                position: [x + w, y + h],
-                color,
-            });
-            vertices.push(ButtonVertex {
-                position: [x, y + h],
-                color,
-            });
-
-            indices.push(base_idx);
-            indices.push(base_idx + 1);
-            indices.push(base_idx + 2);
-            indices.push(base_idx);
-            indices.push(base_idx + 2);
-            indices.push(base_idx + 3);
+        for input in &widgets.text_inputs {
+            if input.is_drawn() {
+                push_text_input(&mut vertices, &mut indices, input, viewport[1]);
+            }
+        }
+
+        // The tooltip box goes last, over everything else
+        self.tooltip_start = indices.len() as u32;
+        let tooltip = widgets.tooltip.filter(|tooltip| tooltip.text().is_some());
+        if tooltip.is_some() || self.text.is_some() {
+            let text = self.text.get_or_insert_with(WidgetText::new);
+            if let (Some(rect), Some(tooltip)) = (text.layout_tooltip(tooltip, viewport), tooltip) {
+                let style = tooltip.style();
+                let radius = style.border_radius.min(rect[2] / 2.0).min(rect[3] / 2.0);
+                push_rounded_rect(&mut vertices, &mut indices, rect, radius, 0.0, style.background);
+            }
+            text.prepare(device, queue, self.format, &widgets.text_inputs, viewport);
         }
 
         if vertices.is_empty() {
@@ -200,39 +293,446 @@ impl ButtonRenderer {
             return;
         }
 
-        self.vertex_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Button Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        }));
-
-        self.index_buffer = Some(device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Button Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        }));
-
+        self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&vertices));
+        self.index_buffer.write(device, queue, bytemuck::cast_slice(&indices));
         self.index_count = indices.len() as u32;
     }
 
-    /// Render the prepared buttons
+    /// Render the prepared widgets
     pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
-        if self.index_count == 0 {
-            return;
+        self.draw(render_pass, 0..self.tooltip_start);
+        self.images.render(render_pass);
+
+        // Text goes over the input boxes, and the tooltip over both
+        let Some(ref text) = self.text else { return };
+        text.render_inputs(render_pass);
+        if self.tooltip_start < self.index_count {
+            self.draw(render_pass, self.tooltip_start..self.index_count);
+            text.render_tooltip(render_pass);
         }
+    }
 
+    /// Draw a range of the prepared quads
+    fn draw<'a>(&'a self, render_pass: &mut RenderPass<'a>, indices: Range<u32>) {
+        if indices.is_empty() || self.index_count == 0 {
+            return;
+        }
         if let (Some(vertex_buffer), Some(index_buffer)) =
-            (&self.vertex_buffer, &self.index_buffer)
+            (self.vertex_buffer.buffer(), self.index_buffer.buffer())
         {
             render_pass.set_pipeline(&self.pipeline);
             render_pass.set_bind_group(0, &self.bind_group, &[]);
             render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
             render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+            render_pass.draw_indexed(indices, 0, 0..1);
         }
     }
 }
 
+/// A button's uploaded sheet
+struct ButtonTexture {
+    /// The sheet it was made from
+    sheet: Arc<SkinData>,
+    /// The texture and its bind group, None if the upload failed
+    uploaded: Option<(Skin, BindGroup)>,
+}
+
+/// Textured quads drawing buttons' images, with a texture per button
+struct ImagePass {
+    pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    sampler: Sampler,
+    textures: HashMap<ButtonId, ButtonTexture>,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    /// Buttons to draw, six indices each
+    drawn: Vec<ButtonId>,
+}
+
+impl ImagePass {
+    fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Button Image Shader"),
+            source: wgpu::ShaderSource::Wgsl(BUTTON_IMAGE_SHADER.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Button Image Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Button Image Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Button Image Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[ImageVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            textures: HashMap::new(),
+            vertex_buffer: DynamicBuffer::new("Button Image Vertex Buffer", wgpu::BufferUsages::VERTEX),
+            index_buffer: DynamicBuffer::new("Button Image Index Buffer", wgpu::BufferUsages::INDEX),
+            drawn: Vec::new(),
+        }
+    }
+
+    /// Upload new or changed sheets of `buttons` and lay out the `drawn`
+    /// buttons at their bounds, each showing the frame for its state
+    fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        uniform_buffer: &Buffer,
+        buttons: &[&Button],
+        drawn: &[(&Button, [f32; 4])],
+    ) {
+        self.textures.retain(|id, _| buttons.iter().any(|button| button.id() == *id && button.image().is_some()));
+        for button in buttons {
+            let Some(image) = button.image() else { continue };
+            let texture = self.textures.get(&button.id());
+            if !texture.is_some_and(|texture| Arc::ptr_eq(&texture.sheet, image.sheet())) {
+                let uploaded = self.upload(device, queue, uniform_buffer, image.sheet());
+                let sheet = image.sheet().clone();
+                self.textures.insert(button.id(), ButtonTexture { sheet, uploaded });
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut indices: Vec<u16> = Vec::new();
+        self.drawn.clear();
+        for (button, [x, y, w, h]) in drawn {
+            let Some(image) = button.image() else { continue };
+            if self.textures.get(&button.id()).is_none_or(|texture| texture.uploaded.is_none()) {
+                continue;
+            }
+            let (_, opacity) = image.frame(button.state());
+            let opacity = opacity * button.opacity();
+            // Half a texel in, so filtering doesn't bleed in the next frame
+            let inset = 0.5 / image.sheet().width() as f32;
+            let [u0, v0, u1, v1] = image.frame_uv(button.state());
+            let (u0, u1) = (u0 + inset, u1 - inset);
+
+            let base_idx = vertices.len() as u16;
+            vertices.extend_from_slice(&[
+                ImageVertex { position: [*x, *y], tex_coords: [u0, v0], opacity },
+                ImageVertex { position: [x + w, *y], tex_coords: [u1, v0], opacity },
+                ImageVertex { position: [x + w, y + h], tex_coords: [u1, v1], opacity },
+                ImageVertex { position: [*x, y + h], tex_coords: [u0, v1], opacity },
+            ]);
+            indices.extend_from_slice(&[base_idx, base_idx + 1, base_idx + 2, base_idx, base_idx + 2, base_idx + 3]);
+            self.drawn.push(button.id());
+        }
+
+        if !vertices.is_empty() {
+            self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&vertices));
+            self.index_buffer.write(device, queue, bytemuck::cast_slice(&indices));
+        }
+    }
+
+    /// Upload a button's sheet, None (logged) if it can't be
+    fn upload(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        uniform_buffer: &Buffer,
+        sheet: &SkinData,
+    ) -> Option<(Skin, BindGroup)> {
+        let texture = Skin::from_skin_data(sheet, device, queue)
+            .map_err(|e| log::error!("Failed to upload a button image: {}", e))
+            .ok()?;
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Button Image Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture.texture_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        Some((texture, bind_group))
+    }
+
+    /// Draw the prepared images
+    fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        let (Some(vertex_buffer), Some(index_buffer)) = (self.vertex_buffer.buffer(), self.index_buffer.buffer())
+        else {
+            return;
+        };
+        if self.drawn.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for (index, id) in self.drawn.iter().enumerate() {
+            let Some((_, bind_group)) = self.textures.get(id).and_then(|texture| texture.uploaded.as_ref()) else {
+                continue;
+            };
+            let start = index as u32 * 6;
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw_indexed(start..start + 6, 0, 0..1);
+        }
+    }
+}
+
+/// Add the quads drawing a switch (track and knob) or checkbox (box and
+/// checkmark)
+fn push_toggle(vertices: &mut Vec<ButtonVertex>, indices: &mut Vec<u16>, toggle: &dyn Toggle, window_height: f32) {
+    let bounds = toggle.drawn_bounds(window_height);
+    let [x, y, w, h] = bounds;
+    let style = toggle.style();
+    let faded = |color| fade(color, toggle.opacity());
+    let fill = faded(style.fill(toggle.is_on(), toggle.state()));
+
+    match toggle.kind() {
+        ToggleKind::Switch => {
+            push_rounded_rect(vertices, indices, bounds, h / 2.0, 0.0, fill);
+            let inset = (h * 0.12).max(1.0);
+            let knob = h - inset * 2.0;
+            let knob_x = if toggle.is_on() { x + w - inset - knob } else { x + inset };
+            let knob_rect = [knob_x, y + inset, knob, knob];
+            push_rounded_rect(vertices, indices, knob_rect, knob / 2.0, 0.0, faded(style.mark_color));
+        }
+        ToggleKind::Checkbox => {
+            let radius = style.border_radius.min(w / 2.0).min(h / 2.0);
+            if toggle.is_on() {
+                push_rounded_rect(vertices, indices, bounds, radius, 0.0, fill);
+                let half_width = (w.min(h) * 0.06).max(0.75);
+                push_shape(vertices, indices, bounds, [half_width, 0.0, 1.0, 0.0], faded(style.mark_color));
+            } else {
+                let border = style.border_width;
+                push_rounded_rect(vertices, indices, bounds, radius, 0.0, faded(style.border_color));
+                let inner = [x + border, y + border, w - border * 2.0, h - border * 2.0];
+                push_rounded_rect(vertices, indices, inner, (radius - border).max(0.0), 0.0, fill);
+            }
+        }
+    }
+}
+
+/// Add the quads drawing a slider: the track, filled up to the knob, and
+/// the knob
+fn push_slider(vertices: &mut Vec<ButtonVertex>, indices: &mut Vec<u16>, slider: &Slider, window_height: f32) {
+    let [x, y, w, h] = slider.drawn_bounds(window_height);
+    let style = slider.style();
+    let faded = |color| fade(color, slider.opacity());
+    let track_height = style.track_height.min(h);
+    let track_y = y + (h - track_height) / 2.0;
+    let knob_x = x + slider.fraction() * (w - h).max(0.0);
+    let radius = track_height / 2.0;
+
+    push_rounded_rect(vertices, indices, [x, track_y, w, track_height], radius, 0.0, faded(style.track_color));
+    let filled = [x, track_y, knob_x + h / 2.0 - x, track_height];
+    push_rounded_rect(vertices, indices, filled, radius, 0.0, faded(style.fill(slider.state())));
+    push_rounded_rect(vertices, indices, [knob_x, y, h, h], h / 2.0, 0.0, faded(style.knob_color));
+}
+
+/// Add the quads drawing a text input: the box, the selection and the caret
+/// (those two only once the input is done animating in)
+fn push_text_input(vertices: &mut Vec<ButtonVertex>, indices: &mut Vec<u16>, input: &TextInput, window_height: f32) {
+    let bounds = input.drawn_bounds(window_height);
+    let [x, y, w, h] = bounds;
+    let style = input.style();
+    let faded = |color| fade(color, input.opacity());
+    let radius = style.border_radius.min(w / 2.0).min(h / 2.0);
+    let border = style.border_width;
+    let border_color = if input.is_focused() { style.focused_border_color } else { style.border_color };
+
+    push_rounded_rect(vertices, indices, bounds, radius, 0.0, faded(border_color));
+    let inner = [x + border, y + border, w - border * 2.0, h - border * 2.0];
+    push_rounded_rect(vertices, indices, inner, (radius - border).max(0.0), 0.0, faded(style.background));
+    if !input.is_fully_shown() {
+        return;
+    }
+    if let Some(selection) = input.selection_rect(window_height) {
+        push_rounded_rect(vertices, indices, selection, 0.0, 0.0, style.selection_color);
+    }
+    if let Some(caret) = input.caret_rect(window_height) {
+        push_rounded_rect(vertices, indices, caret, 0.0, 0.0, style.caret_color);
+    }
+}
+
+/// Add the quads drawing a progress bar: the track and the filled part
+fn push_progress_bar(vertices: &mut Vec<ButtonVertex>, indices: &mut Vec<u16>, bar: &ProgressBar, window_height: f32) {
+    let bounds = bar.drawn_bounds(window_height);
+    let [x, y, w, h] = bounds;
+    let style = bar.style();
+    push_rounded_rect(vertices, indices, bounds, style.border_radius, 0.0, fade(style.track_color, bar.opacity()));
+
+    let ([start, end], opacity) = bar.fill();
+    if end > start {
+        let fill = [x + start * w, y, (end - start) * w, h];
+        let color = fade(style.fill_color, opacity * bar.opacity());
+        push_rounded_rect(vertices, indices, fill, style.border_radius, 0.0, color);
+    }
+}
+
+/// `color` with its alpha multiplied by `opacity`
+fn fade([r, g, b, a]: [f32; 4], opacity: f32) -> [f32; 4] {
+    [r, g, b, a * opacity]
+}
+
+/// Add a quad drawing a rounded rectangle whose edge fades over `softness`
+/// pixels (at least one pixel, for smooth edges)
+fn push_rounded_rect(
+    vertices: &mut Vec<ButtonVertex>,
+    indices: &mut Vec<u16>,
+    rect: [f32; 4],
+    radius: f32,
+    softness: f32,
+    color: [f32; 4],
+) {
+    push_shape(vertices, indices, rect, [radius, softness, 0.0, 0.0], color);
+}
+
+/// Add a quad drawing the shape in `params` within `rect`
+fn push_shape(vertices: &mut Vec<ButtonVertex>, indices: &mut Vec<u16>, rect: [f32; 4], params: [f32; 4], color: [f32; 4]) {
+    let softness = params[1];
+    let base_idx = vertices.len() as u16;
+    let margin = softness + 1.0;
+    let [x, y, w, h] = rect;
+    for position in [
+        [x - margin, y - margin],
+        [x + w + margin, y - margin],
+        [x + w + margin, y + h + margin],
+        [x - margin, y + h + margin],
+    ] {
+        vertices.push(ButtonVertex {
+            position,
+            color,
+            rect,
+            params,
+        });
+    }
+    indices.extend_from_slice(&[base_idx, base_idx + 1, base_idx + 2, base_idx, base_idx + 2, base_idx + 3]);
+}
+
+const BUTTON_IMAGE_SHADER: &str = r#"
+struct Uniforms {
+    viewport: vec4<f32>, // width, height, 0, 0
+}
+
+@group(0) @binding(2)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) opacity: f32,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) opacity: f32,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Convert to clip space (-1 to 1)
+    let clip_x = (in.position.x / uniforms.viewport.x) * 2.0 - 1.0;
+    let clip_y = 1.0 - (in.position.y / uniforms.viewport.y) * 2.0;
+
+    out.clip_position = vec4<f32>(clip_x, clip_y, 0.0, 1.0);
+    out.tex_coords = in.tex_coords;
+    out.opacity = in.opacity;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_image: texture_2d<f32>;
+@group(0) @binding(1)
+var s_image: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_image, s_image, in.tex_coords);
+    // Pre-multiply alpha for proper transparency blending
+    let alpha = color.a * in.opacity;
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
+"#;
+
 const BUTTON_SHADER: &str = r#"
 struct Uniforms {
     viewport: vec4<f32>, // width, height, 0, 0
@@ -244,11 +744,16 @@ var<uniform> uniforms: Uniforms;
 struct VertexInput {
     @location(0) position: vec2<f32>,
     @location(1) color: vec4<f32>,
+    @location(2) rect: vec4<f32>,
+    @location(3) params: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+    @location(2) @interpolate(flat) rect: vec4<f32>,
+    @location(3) @interpolate(flat) params: vec4<f32>,
 }
 
 @vertex
@@ -261,12 +766,41 @@ fn vs_main(in: VertexInput) -> VertexOutput {
 
     out.clip_position = vec4<f32>(clip_x, clip_y, 0.0, 1.0);
     out.color = in.color;
+    out.local = in.position;
+    out.rect = in.rect;
+    out.params = in.params;
 
     return out;
 }
 
+// Distance from p to the segment from a to b
+fn segment_distance(p: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = clamp(dot(pa, ba) / dot(ba, ba), 0.0, 1.0);
+    return length(pa - ba * h);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return in.color;
+    var d: f32;
+    if (in.params.z > 0.5) {
+        // Signed distance to a checkmark stroke across the rectangle
+        let a = in.rect.xy + in.rect.zw * vec2<f32>(0.22, 0.52);
+        let b = in.rect.xy + in.rect.zw * vec2<f32>(0.42, 0.72);
+        let c = in.rect.xy + in.rect.zw * vec2<f32>(0.78, 0.3);
+        d = min(segment_distance(in.local, a, b), segment_distance(in.local, b, c)) - in.params.x;
+    } else {
+        // Signed distance to the rounded rectangle
+        let half_size = in.rect.zw * 0.5;
+        let radius = min(in.params.x, min(half_size.x, half_size.y));
+        let q = abs(in.local - in.rect.xy - half_size) - (half_size - vec2<f32>(radius));
+        d = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+    }
+
+    // Shadows fade over their blur, everything else over a pixel
+    let softness = max(in.params.y, 0.5);
+    let cover = 1.0 - smoothstep(-softness, softness, d);
+    return vec4<f32>(in.color.rgb, in.color.a * cover);
 }
 "#;
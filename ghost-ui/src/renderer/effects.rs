@@ -0,0 +1,197 @@
+//! Skin effects - glow, dissolve, pixelation and grayscale drawn by the
+//! sprite shader, each faded in and out over time
+
+/// A post effect of the skin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// Soft halo around the skin's opaque pixels
+    Glow,
+    /// Pixels vanish in a random pattern (e.g. while hiding)
+    Dissolve,
+    /// Large square pixels
+    Pixelate,
+    /// Colors drained to gray (e.g. while sleeping)
+    Grayscale,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [Effect::Glow, Effect::Dissolve, Effect::Pixelate, Effect::Grayscale];
+
+    /// Parse an effect name ("glow", "dissolve", "pixelate", "grayscale")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "glow" => Some(Self::Glow),
+            "dissolve" => Some(Self::Dissolve),
+            "pixelate" | "pixelated" => Some(Self::Pixelate),
+            "grayscale" | "greyscale" | "gray" | "grey" => Some(Self::Grayscale),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Glow => "glow",
+            Self::Dissolve => "dissolve",
+            Self::Pixelate => "pixelate",
+            Self::Grayscale => "grayscale",
+        }
+    }
+}
+
+/// Effect strengths as the sprite shader takes them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EffectParams {
+    /// Glow color, its alpha scaled by the glow's strength
+    pub glow_color: [f32; 4],
+    /// Glow reach in texture pixels
+    pub glow_radius: f32,
+    /// Share of pixels dissolved (0.0 - 1.0)
+    pub dissolve: f32,
+    /// Pixel block size in texture pixels (1.0 = sharp)
+    pub pixel_size: f32,
+    /// Share of color drained (0.0 - 1.0)
+    pub grayscale: f32,
+}
+
+impl Default for EffectParams {
+    fn default() -> Self {
+        Self {
+            glow_color: [0.0; 4],
+            glow_radius: 0.0,
+            dissolve: 0.0,
+            pixel_size: 1.0,
+            grayscale: 0.0,
+        }
+    }
+}
+
+impl EffectParams {
+    /// The same effects without the glow (e.g. for parts drawn on top of
+    /// a glowing body)
+    pub fn without_glow(self) -> Self {
+        Self { glow_color: [0.0; 4], ..self }
+    }
+}
+
+/// An effect of the stack fading toward on or off
+#[derive(Debug, Clone)]
+struct ActiveEffect {
+    effect: Effect,
+    /// Current strength (0.0 - 1.0)
+    amount: f32,
+    /// Strength faded toward
+    target: f32,
+    /// Strength change per second (0 = instant)
+    speed: f32,
+}
+
+/// The effects switched on, fading in and out over time
+#[derive(Debug, Clone)]
+pub struct EffectStack {
+    effects: Vec<ActiveEffect>,
+    glow_color: [f32; 4],
+    glow_radius: f32,
+    pixel_size: f32,
+}
+
+impl Default for EffectStack {
+    fn default() -> Self {
+        Self {
+            effects: Vec::new(),
+            glow_color: [0.7, 0.85, 1.0, 0.8],
+            glow_radius: 6.0,
+            pixel_size: 6.0,
+        }
+    }
+}
+
+impl EffectStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Glow color (RGBA 0.0 - 1.0) and reach in pixels
+    pub fn with_glow(mut self, color: [f32; 4], radius: f32) -> Self {
+        self.glow_color = color;
+        self.glow_radius = radius.max(0.0);
+        self
+    }
+
+    /// Pixel block size of `Effect::Pixelate`
+    pub fn with_pixel_size(mut self, size: f32) -> Self {
+        self.pixel_size = size.max(1.0);
+        self
+    }
+
+    /// Switch an effect on or off, fading over `fade` seconds
+    pub fn set(&mut self, effect: Effect, on: bool, fade: f32) {
+        let target = if on { 1.0 } else { 0.0 };
+        let speed = if fade > 0.0 { 1.0 / fade } else { 0.0 };
+        match self.effects.iter_mut().find(|e| e.effect == effect) {
+            Some(active) => {
+                active.target = target;
+                active.speed = speed;
+            }
+            None if on => self.effects.push(ActiveEffect { effect, amount: 0.0, target, speed }),
+            None => {}
+        }
+        // Instant changes take effect right away
+        if speed == 0.0 {
+            self.update(0.0);
+        }
+    }
+
+    /// Switch an effect on if it's off and off if it's on
+    pub fn toggle(&mut self, effect: Effect, fade: f32) {
+        let on = self.is_on(effect);
+        self.set(effect, !on, fade);
+    }
+
+    /// Whether an effect is on (or fading in)
+    pub fn is_on(&self, effect: Effect) -> bool {
+        self.effects.iter().any(|e| e.effect == effect && e.target > 0.0)
+    }
+
+    /// Current strength of an effect (0.0 - 1.0)
+    pub fn amount(&self, effect: Effect) -> f32 {
+        self.effects.iter().find(|e| e.effect == effect).map_or(0.0, |e| e.amount)
+    }
+
+    /// Fade the effects by `delta` seconds; true if any strength changed
+    pub fn update(&mut self, delta: f32) -> bool {
+        let mut changed = false;
+        for active in &mut self.effects {
+            if active.amount == active.target {
+                continue;
+            }
+            let step = if active.speed > 0.0 { active.speed * delta } else { 1.0 };
+            active.amount = if active.amount < active.target {
+                (active.amount + step).min(active.target)
+            } else {
+                (active.amount - step).max(active.target)
+            };
+            changed = true;
+        }
+        // Effects faded out are gone
+        self.effects.retain(|e| e.amount > 0.0 || e.target > 0.0);
+        changed
+    }
+
+    /// Whether any effect is still fading
+    pub fn is_animating(&self) -> bool {
+        self.effects.iter().any(|e| e.amount != e.target)
+    }
+
+    /// Strengths for the shader
+    pub fn params(&self) -> EffectParams {
+        let glow = self.amount(Effect::Glow);
+        let [r, g, b, a] = self.glow_color;
+        EffectParams {
+            glow_color: [r, g, b, a * glow],
+            glow_radius: self.glow_radius,
+            dissolve: self.amount(Effect::Dissolve),
+            pixel_size: 1.0 + (self.pixel_size - 1.0) * self.amount(Effect::Pixelate),
+            grayscale: self.amount(Effect::Grayscale),
+        }
+    }
+}
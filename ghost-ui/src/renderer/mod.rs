@@ -1,10 +1,17 @@
 //! wgpu-based renderer for ghost windows
 
+mod buffer;
 mod button;
+mod effects;
+mod nine_slice;
 mod sprite;
+mod widget_text;
 
-pub use button::ButtonRenderer;
-pub use sprite::SpritePipeline;
+pub use buffer::DynamicBuffer;
+pub use button::{ButtonRenderer, Widgets};
+pub use effects::{Effect, EffectParams, EffectStack};
+pub use nine_slice::NineSliceRenderer;
+pub use sprite::{Recolor, SpritePipeline, MAX_PALETTE_COLORS};
 
 use tao::window::Window;
 use thiserror::Error;
@@ -30,6 +37,8 @@ pub struct Renderer<'window> {
     surface: Surface<'window>,
     config: SurfaceConfiguration,
     sprite_pipeline: SpritePipeline,
+    /// Scale of everything drawn, anchored at the bottom center
+    zoom: f32,
 }
 
 impl<'window> Renderer<'window> {
@@ -123,6 +132,7 @@ impl<'window> Renderer<'window> {
             surface,
             config,
             sprite_pipeline,
+            zoom: 1.0,
         })
     }
 
@@ -145,6 +155,44 @@ impl<'window> Renderer<'window> {
         }
     }
 
+    /// Mirror the skin horizontally.
+    pub fn set_flip_x(&mut self, flip_x: bool) {
+        self.sprite_pipeline.set_flip_x(flip_x);
+    }
+
+    /// Scale the skin vertically, anchored at its bottom edge.
+    pub fn set_skin_scale(&mut self, scale: f32) {
+        self.sprite_pipeline.set_scale_y(scale);
+    }
+
+    /// Recolor the skin (hue shift, tint, palette swap).
+    pub fn set_recolor(&mut self, recolor: Recolor) {
+        self.sprite_pipeline.set_recolor(recolor);
+    }
+
+    /// Apply post effects (glow, dissolve, pixelation, grayscale) to the skin.
+    pub fn set_effects(&mut self, effects: EffectParams) {
+        self.sprite_pipeline.set_effects(effects);
+    }
+
+    /// Shrink everything drawn (skin, layers, buttons) toward the bottom
+    /// center of the window; 1.0 draws it as is.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(0.0, 1.0);
+    }
+
+    /// Viewport [x, y, width, height] drawing at the current zoom, None
+    /// when drawing the whole surface
+    fn zoom_viewport(&self) -> Option<[f32; 4]> {
+        if self.zoom >= 1.0 {
+            return None;
+        }
+        let (width, height) = (self.config.width as f32, self.config.height as f32);
+        // Viewports can't be empty
+        let (zoomed_width, zoomed_height) = ((width * self.zoom).max(1.0), (height * self.zoom).max(1.0));
+        Some([(width - zoomed_width) / 2.0, height - zoomed_height, zoomed_width, zoomed_height])
+    }
+
     /// Render a skin to the window.
     pub fn render(&mut self, skin: Option<&Skin>, opacity: f32) -> Result<(), wgpu::SurfaceError> {
         self.render_with_extra(skin, opacity, [0.0, 0.0], |_| {})
@@ -175,6 +223,7 @@ impl<'window> Renderer<'window> {
             );
         }
 
+        let zoom = self.zoom_viewport();
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -201,6 +250,9 @@ impl<'window> Renderer<'window> {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            if let Some([x, y, width, height]) = zoom {
+                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            }
 
             if skin.is_some() {
                 self.sprite_pipeline.render(&mut render_pass);
@@ -238,6 +290,7 @@ impl<'window> Renderer<'window> {
             );
         }
 
+        let zoom = self.zoom_viewport();
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -264,6 +317,9 @@ impl<'window> Renderer<'window> {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            if let Some([x, y, width, height]) = zoom {
+                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            }
 
             if skin.is_some() {
                 self.sprite_pipeline.render(&mut render_pass);
@@ -309,6 +365,7 @@ impl<'window> Renderer<'window> {
             );
         }
 
+        let zoom = self.zoom_viewport();
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -335,6 +392,9 @@ impl<'window> Renderer<'window> {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            if let Some([x, y, width, height]) = zoom {
+                render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+            }
 
             if skin.is_some() {
                 self.sprite_pipeline.render(&mut render_pass);
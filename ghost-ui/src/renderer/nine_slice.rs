@@ -0,0 +1,304 @@
+//! Nine-slice skin rendering pipeline
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+use wgpu::{
+    BindGroup, BindGroupLayout, Buffer, Device, Queue, RenderPass, RenderPipeline, Sampler,
+    TextureFormat,
+};
+
+use super::DynamicBuffer;
+use crate::{NineSliceSkin, Skin, SkinError};
+
+/// Vertex for nine-slice rendering
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct NineSliceVertex {
+    /// Position [x, y] in pixels
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+
+impl NineSliceVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<NineSliceVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Uniforms for nine-slice rendering
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct NineSliceUniforms {
+    viewport: [f32; 4], // width, height, opacity, 0
+}
+
+/// Renderer stretching a nine-slice skin over a rectangle
+pub struct NineSliceRenderer {
+    pipeline: RenderPipeline,
+    bind_group_layout: BindGroupLayout,
+    uniform_buffer: Buffer,
+    sampler: Sampler,
+    /// The skin and its texture, once set
+    skin: Option<(NineSliceSkin, Skin)>,
+    bind_group: Option<BindGroup>,
+    vertex_buffer: DynamicBuffer,
+    index_buffer: DynamicBuffer,
+    index_count: u32,
+}
+
+impl NineSliceRenderer {
+    /// Create a new nine-slice renderer
+    pub fn new(device: &Device, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Nine-Slice Shader"),
+            source: wgpu::ShaderSource::Wgsl(NINE_SLICE_SHADER.into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Nine-Slice Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Nine-Slice Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Nine-Slice Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[NineSliceVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Nine-Slice Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[NineSliceUniforms {
+                viewport: [800.0, 600.0, 1.0, 0.0],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            uniform_buffer,
+            sampler,
+            skin: None,
+            bind_group: None,
+            vertex_buffer: DynamicBuffer::new("Nine-Slice Vertex Buffer", wgpu::BufferUsages::VERTEX),
+            index_buffer: DynamicBuffer::new("Nine-Slice Index Buffer", wgpu::BufferUsages::INDEX),
+            index_count: 0,
+        }
+    }
+
+    /// Upload the skin to draw
+    pub fn set_skin(&mut self, device: &Device, queue: &Queue, skin: &NineSliceSkin) -> Result<(), SkinError> {
+        let texture = Skin::from_skin_data(skin.data(), device, queue)?;
+        self.bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Nine-Slice Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture.texture_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        }));
+        self.skin = Some((skin.clone(), texture));
+        Ok(())
+    }
+
+    /// Stretch the skin over `rect` [x, y, width, height] in physical pixels;
+    /// corners are drawn `scale_factor` times their image size
+    pub fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        rect: [f32; 4],
+        scale_factor: f32,
+        opacity: f32,
+        viewport: [f32; 2],
+    ) {
+        let Some((ref skin, _)) = self.skin else {
+            self.index_count = 0;
+            return;
+        };
+
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[NineSliceUniforms {
+                viewport: [viewport[0], viewport[1], opacity, 0.0],
+            }]),
+        );
+
+        let mut vertices = Vec::new();
+        let mut indices: Vec<u16> = Vec::new();
+        for patch in skin.patches(rect, scale_factor) {
+            let base_idx = vertices.len() as u16;
+            let [x, y, w, h] = patch.rect;
+            let [u0, v0, u1, v1] = patch.uv;
+            vertices.extend_from_slice(&[
+                NineSliceVertex { position: [x, y], tex_coords: [u0, v0] },
+                NineSliceVertex { position: [x + w, y], tex_coords: [u1, v0] },
+                NineSliceVertex { position: [x + w, y + h], tex_coords: [u1, v1] },
+                NineSliceVertex { position: [x, y + h], tex_coords: [u0, v1] },
+            ]);
+            indices.extend_from_slice(&[base_idx, base_idx + 1, base_idx + 2, base_idx, base_idx + 2, base_idx + 3]);
+        }
+
+        if vertices.is_empty() {
+            self.index_count = 0;
+            return;
+        }
+
+        self.vertex_buffer.write(device, queue, bytemuck::cast_slice(&vertices));
+        self.index_buffer.write(device, queue, bytemuck::cast_slice(&indices));
+
+        self.index_count = indices.len() as u32;
+    }
+
+    /// Render the prepared skin
+    pub fn render<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        if self.index_count == 0 {
+            return;
+        }
+
+        if let (Some(bind_group), Some(vertex_buffer), Some(index_buffer)) =
+            (&self.bind_group, self.vertex_buffer.buffer(), self.index_buffer.buffer())
+        {
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+        }
+    }
+}
+
+const NINE_SLICE_SHADER: &str = r#"
+struct Uniforms {
+    viewport: vec4<f32>, // width, height, opacity, 0
+}
+
+@group(0) @binding(2)
+var<uniform> uniforms: Uniforms;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // Convert to clip space (-1 to 1)
+    let clip_x = (in.position.x / uniforms.viewport.x) * 2.0 - 1.0;
+    let clip_y = 1.0 - (in.position.y / uniforms.viewport.y) * 2.0;
+
+    out.clip_position = vec4<f32>(clip_x, clip_y, 0.0, 1.0);
+    out.tex_coords = in.tex_coords;
+    return out;
+}
+
+@group(0) @binding(0)
+var t_skin: texture_2d<f32>;
+@group(0) @binding(1)
+var s_skin: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_skin, s_skin, in.tex_coords);
+    // Pre-multiply alpha for proper transparency blending
+    let alpha = color.a * uniforms.viewport.z;
+    return vec4<f32>(color.rgb * alpha, alpha);
+}
+"#;
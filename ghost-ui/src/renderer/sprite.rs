@@ -7,6 +7,7 @@ use wgpu::{
     TextureFormat,
 };
 
+use super::effects::EffectParams;
 use crate::Skin;
 
 #[repr(C)]
@@ -51,6 +52,44 @@ const VERTICES: &[Vertex] = &[
 
 const INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
 
+/// Most colors a palette swap can replace
+pub const MAX_PALETTE_COLORS: usize = 8;
+
+/// Color changes applied to a sprite while drawing it, e.g. a color
+/// variant of a persona. Colors are RGB from 0.0 to 1.0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recolor {
+    /// Hue rotation in degrees
+    pub hue_shift: f32,
+    /// Saturation multiplier (0.0 = grayscale, 1.0 = unchanged)
+    pub saturation: f32,
+    /// Brightness multiplier (1.0 = unchanged)
+    pub brightness: f32,
+    /// Color the sprite is tinted with
+    pub tint: [f32; 3],
+    /// How much of the tint shows (0.0 = none, 1.0 = only the tint's hue)
+    pub tint_strength: f32,
+    /// Colors replaced by others (`from`, `to`), keeping the shading;
+    /// at most `MAX_PALETTE_COLORS` are used
+    pub palette: Vec<([f32; 3], [f32; 3])>,
+    /// How far a pixel may be from a palette color and still be replaced
+    pub tolerance: f32,
+}
+
+impl Default for Recolor {
+    fn default() -> Self {
+        Self {
+            hue_shift: 0.0,
+            saturation: 1.0,
+            brightness: 1.0,
+            tint: [1.0, 1.0, 1.0],
+            tint_strength: 0.0,
+            palette: Vec::new(),
+            tolerance: 0.1,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct Uniforms {
@@ -61,6 +100,46 @@ struct Uniforms {
     /// Skin size as fraction of viewport (0.0 to 1.0)
     size: [f32; 2],
     _padding2: [f32; 2],
+    /// Hue shift (radians), saturation, brightness, palette tolerance
+    color: [f32; 4],
+    /// Tint RGB and strength
+    tint: [f32; 4],
+    /// Palette colors, `w` = 1.0 for the ones in use
+    palette_from: [[f32; 4]; MAX_PALETTE_COLORS],
+    palette_to: [[f32; 4]; MAX_PALETTE_COLORS],
+    /// Glow radius, dissolve, pixel size, grayscale
+    effects: [f32; 4],
+    /// Glow color, alpha scaled by its strength
+    glow_color: [f32; 4],
+}
+
+impl Uniforms {
+    fn new(opacity: f32, offset: [f32; 2], size: [f32; 2], recolor: &Recolor, effects: &EffectParams) -> Self {
+        let mut palette_from = [[0.0; 4]; MAX_PALETTE_COLORS];
+        let mut palette_to = [[0.0; 4]; MAX_PALETTE_COLORS];
+        for (i, (from, to)) in recolor.palette.iter().take(MAX_PALETTE_COLORS).enumerate() {
+            palette_from[i] = [from[0], from[1], from[2], 1.0];
+            palette_to[i] = [to[0], to[1], to[2], 1.0];
+        }
+        Self {
+            opacity,
+            _padding: 0.0,
+            offset,
+            size,
+            _padding2: [0.0, 0.0],
+            color: [
+                recolor.hue_shift.to_radians(),
+                recolor.saturation,
+                recolor.brightness,
+                recolor.tolerance,
+            ],
+            tint: [recolor.tint[0], recolor.tint[1], recolor.tint[2], recolor.tint_strength],
+            palette_from,
+            palette_to,
+            effects: [effects.glow_radius, effects.dissolve, effects.pixel_size, effects.grayscale],
+            glow_color: effects.glow_color,
+        }
+    }
 }
 
 pub struct SpritePipeline {
@@ -71,6 +150,14 @@ pub struct SpritePipeline {
     bind_group_layout: BindGroupLayout,
     sampler: Sampler,
     current_bind_group: Option<BindGroup>,
+    /// Mirror the skin horizontally
+    flip_x: bool,
+    /// Vertical scale of the skin, anchored at its bottom edge
+    scale_y: f32,
+    /// Color changes of everything drawn
+    recolor: Recolor,
+    /// Post effects of everything drawn
+    effects: EffectParams,
 }
 
 impl SpritePipeline {
@@ -177,13 +264,13 @@ impl SpritePipeline {
         // Create uniform buffer
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[Uniforms {
-                opacity: 1.0,
-                _padding: 0.0,
-                offset: [0.0, 0.0],
-                size: [1.0, 1.0],
-                _padding2: [0.0, 0.0],
-            }]),
+            contents: bytemuck::cast_slice(&[Uniforms::new(
+                1.0,
+                [0.0, 0.0],
+                [1.0, 1.0],
+                &Recolor::default(),
+                &EffectParams::default(),
+            )]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -206,9 +293,36 @@ impl SpritePipeline {
             bind_group_layout,
             sampler,
             current_bind_group: None,
+            flip_x: false,
+            scale_y: 1.0,
+            recolor: Recolor::default(),
+            effects: EffectParams::default(),
         }
     }
 
+    /// Mirror the skin horizontally on the next `prepare`.
+    pub fn set_flip_x(&mut self, flip_x: bool) {
+        self.flip_x = flip_x;
+    }
+
+    /// Scale the skin vertically on the next `prepare`, keeping its bottom
+    /// edge in place.
+    pub fn set_scale_y(&mut self, scale_y: f32) {
+        self.scale_y = scale_y;
+    }
+
+    /// Recolor sprites prepared from now on (the skin on the next
+    /// `prepare`, layers as their bind groups are created).
+    pub fn set_recolor(&mut self, recolor: Recolor) {
+        self.recolor = recolor;
+    }
+
+    /// Apply post effects (glow, dissolve, pixelation, grayscale) to
+    /// sprites prepared from now on, like `set_recolor`.
+    pub fn set_effects(&mut self, effects: EffectParams) {
+        self.effects = effects;
+    }
+
     /// Prepare the pipeline for rendering with a specific skin.
     ///
     /// * `skin_offset` - Offset of skin within viewport [x, y] in pixels
@@ -237,14 +351,14 @@ impl SpritePipeline {
             (size_x, size_y, offset_x, offset_y)
         };
 
+        // A negative width mirrors the quad (culling is disabled)
+        let size_x = if self.flip_x { -size_x } else { size_x };
+        // Scale around the bottom edge (at offset - size in NDC)
+        let offset_y = offset_y - size_y + size_y * self.scale_y;
+        let size_y = size_y * self.scale_y;
+
         // Update uniforms
-        let uniforms = Uniforms {
-            opacity,
-            _padding: 0.0,
-            offset: [offset_x, offset_y],
-            size: [size_x, size_y],
-            _padding2: [0.0, 0.0],
-        };
+        let uniforms = Uniforms::new(opacity, [offset_x, offset_y], [size_x, size_y], &self.recolor, &self.effects);
         queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
 
         // Create bind group for this skin
@@ -354,13 +468,13 @@ impl SpritePipeline {
         // Create a uniform buffer for this specific layer
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Layer Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[Uniforms {
+            contents: bytemuck::cast_slice(&[Uniforms::new(
                 opacity,
-                _padding: 0.0,
-                offset: [center_x, center_y],
-                size: [size_x, size_y],
-                _padding2: [0.0, 0.0],
-            }]),
+                [center_x, center_y],
+                [size_x, size_y],
+                &self.recolor,
+                &self.effects,
+            )]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
@@ -15,6 +15,17 @@ struct Uniforms {
     _padding: f32,
     offset: vec2<f32>,
     size: vec2<f32>,
+    // Hue shift (radians), saturation, brightness, palette tolerance
+    color: vec4<f32>,
+    // Tint RGB and strength
+    tint: vec4<f32>,
+    // Palette colors, w = 1.0 for the ones in use
+    palette_from: array<vec4<f32>, 8>,
+    palette_to: array<vec4<f32>, 8>,
+    // Glow radius, dissolve, pixel size, grayscale
+    effects: vec4<f32>,
+    // Glow color, alpha scaled by its strength
+    glow_color: vec4<f32>,
 }
 @group(0) @binding(2)
 var<uniform> uniforms: Uniforms;
@@ -37,10 +48,90 @@ var t_diffuse: texture_2d<f32>;
 @group(0) @binding(1)
 var s_diffuse: sampler;
 
+fn luma(rgb: vec3<f32>) -> f32 {
+    return dot(rgb, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+// Palette swap, hue rotation, saturation, brightness and tint
+fn recolor(rgb: vec3<f32>) -> vec3<f32> {
+    var c = rgb;
+
+    // Replace the first palette color close enough, keeping the shading
+    for (var i = 0; i < 8; i++) {
+        let swap = uniforms.palette_from[i];
+        if swap.w > 0.5 && distance(c, swap.rgb) <= uniforms.color.w {
+            c = uniforms.palette_to[i].rgb + (c - swap.rgb);
+            break;
+        }
+    }
+
+    // Rotate the hue around the gray axis
+    let angle = uniforms.color.x;
+    if angle != 0.0 {
+        let k = vec3<f32>(0.57735, 0.57735, 0.57735);
+        let cos_a = cos(angle);
+        c = c * cos_a + cross(k, c) * sin(angle) + k * dot(k, c) * (1.0 - cos_a);
+    }
+
+    c = mix(vec3<f32>(luma(c)), c, uniforms.color.y) * uniforms.color.z;
+    c = mix(c, luma(c) * uniforms.tint.rgb, uniforms.tint.w);
+    return clamp(c, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Random value (0-1) per texture pixel
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+// Highest alpha within `radius` pixels, fading with distance
+fn glow_alpha(uv: vec2<f32>, dims: vec2<f32>, radius: f32) -> f32 {
+    var around = 0.0;
+    for (var ring = 1; ring <= 3; ring++) {
+        let reach = radius * f32(ring) / 3.0;
+        let falloff = 1.0 - f32(ring - 1) / 3.0;
+        for (var i = 0; i < 12; i++) {
+            let angle = f32(i) * 0.5235988;
+            let offset = vec2<f32>(cos(angle), sin(angle)) * reach / dims;
+            around = max(around, textureSampleLevel(t_diffuse, s_diffuse, uv + offset, 0.0).a * falloff);
+        }
+    }
+    return around;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let dims = vec2<f32>(textureDimensions(t_diffuse));
+
+    // Pixelate: sample the center of each block
+    var uv = in.tex_coords;
+    let pixel_size = uniforms.effects.z;
+    if pixel_size > 1.0 {
+        uv = (floor(uv * dims / pixel_size) + 0.5) * pixel_size / dims;
+    }
+
+    let color = textureSample(t_diffuse, s_diffuse, uv);
+    var rgb = recolor(color.rgb);
+    rgb = mix(rgb, vec3<f32>(luma(rgb)), uniforms.effects.w);
+
     // Pre-multiply alpha for proper transparency blending
-    let alpha = color.a * uniforms.opacity;
-    return vec4<f32>(color.rgb * alpha, alpha);
+    var alpha = color.a;
+    rgb = rgb * alpha;
+
+    // Glow behind the skin's opaque pixels
+    let radius = uniforms.effects.x;
+    if uniforms.glow_color.a > 0.0 && radius > 0.0 {
+        let glow = glow_alpha(uv, dims, radius) * uniforms.glow_color.a * (1.0 - alpha);
+        rgb = rgb + uniforms.glow_color.rgb * glow;
+        alpha = alpha + glow;
+    }
+
+    // Dissolve: pixels vanish once their random value falls below it
+    let dissolve = uniforms.effects.y;
+    if dissolve > 0.0 {
+        let keep = smoothstep(dissolve, dissolve + 0.08, hash(floor(uv * dims)));
+        rgb = rgb * keep;
+        alpha = alpha * keep;
+    }
+
+    return vec4<f32>(rgb, alpha) * uniforms.opacity;
 }
@@ -0,0 +1,260 @@
+//! Text of text input widgets and tooltips, shaped and drawn with glyphon
+
+use glyphon::{
+    Attrs, Buffer, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache, TextArea, TextAtlas,
+    TextBounds, TextRenderer as GlyphonTextRenderer,
+};
+use wgpu::{Device, MultisampleState, Queue, RenderPass, TextureFormat};
+
+use crate::fonts;
+use crate::widget::{TextInput, Tooltip};
+
+/// What shaped text depends on
+#[derive(PartialEq)]
+struct TextKey {
+    text: String,
+    font_size: f32,
+}
+
+/// Shaped text of one input or tooltip
+struct ShapedText {
+    key: TextKey,
+    buffer: Buffer,
+}
+
+/// The showing tooltip's text and box
+struct PlacedTooltip {
+    shaped: ShapedText,
+    /// Box [x, y, width, height] in screen coordinates
+    rect: [f32; 4],
+    padding: [f32; 2],
+    color: [f32; 4],
+}
+
+/// Text renderers sharing one atlas; tooltip text is drawn separately so
+/// it can go over everything else
+struct TextGpu {
+    atlas: TextAtlas,
+    inputs: GlyphonTextRenderer,
+    tooltip: GlyphonTextRenderer,
+}
+
+/// Shapes and draws the text (or placeholder) of text inputs and the
+/// tooltip
+pub(super) struct WidgetText {
+    font_system: FontSystem,
+    /// Family of the color emoji font
+    emoji_family: Option<String>,
+    swash_cache: SwashCache,
+    /// Created on the first prepare
+    gpu: Option<TextGpu>,
+    /// Shaped text per input, reshaped only when it changes
+    shaped: Vec<ShapedText>,
+    /// None while no tooltip shows
+    tooltip: Option<PlacedTooltip>,
+}
+
+impl WidgetText {
+    pub(super) fn new() -> Self {
+        let (font_system, emoji_family) = fonts::load_fonts();
+        Self {
+            font_system,
+            emoji_family,
+            swash_cache: SwashCache::new(),
+            gpu: None,
+            shaped: Vec::new(),
+            tooltip: None,
+        }
+    }
+
+    /// Shape the text of each input, telling it where its characters are
+    pub(super) fn layout(&mut self, inputs: Vec<&mut TextInput>) {
+        self.shaped.truncate(inputs.len());
+        for (i, input) in inputs.into_iter().enumerate() {
+            let shown = if input.text().is_empty() { input.placeholder() } else { input.text() };
+            let key = TextKey {
+                text: shown.to_string(),
+                font_size: input.style().font_size,
+            };
+            if self.shaped.get(i).is_none_or(|shaped| shaped.key != key) {
+                let shaped = self.shape(key);
+                if i < self.shaped.len() {
+                    self.shaped[i] = shaped;
+                } else {
+                    self.shaped.push(shaped);
+                }
+            }
+
+            let stops = if input.text().is_empty() {
+                vec![(0, 0.0)]
+            } else {
+                stops(&self.shaped[i].buffer)
+            };
+            input.set_stops(stops);
+        }
+    }
+
+    /// Shape the tooltip's text and place its box next to where it showed,
+    /// inside the viewport; returns the box
+    pub(super) fn layout_tooltip(&mut self, tooltip: Option<&Tooltip>, viewport: [f32; 2]) -> Option<[f32; 4]> {
+        let (Some(tooltip), Some(text), Some([cursor_x, cursor_y])) =
+            (tooltip, tooltip.and_then(Tooltip::text), tooltip.and_then(Tooltip::position))
+        else {
+            self.tooltip = None;
+            return None;
+        };
+        let style = tooltip.style();
+        let key = TextKey {
+            text: text.to_string(),
+            font_size: style.font_size,
+        };
+        let shaped = match self.tooltip.take() {
+            Some(placed) if placed.shaped.key == key => placed.shaped,
+            _ => self.shape(key),
+        };
+
+        let text_width = shaped.buffer.layout_runs().map(|run| run.line_w).fold(0.0, f32::max);
+        let width = text_width + style.padding[0] * 2.0;
+        let height = shaped.buffer.metrics().line_height + style.padding[1] * 2.0;
+        let x = (cursor_x + style.offset[0]).min(viewport[0] - width).max(0.0);
+        let mut y = cursor_y + style.offset[1];
+        if y + height > viewport[1] {
+            y = (cursor_y - style.offset[1] - height).max(0.0);
+        }
+
+        let rect = [x, y, width, height];
+        self.tooltip = Some(PlacedTooltip {
+            shaped,
+            rect,
+            padding: style.padding,
+            color: style.text_color,
+        });
+        Some(rect)
+    }
+
+    /// Prepare the text of the inputs and the tooltip laid out last
+    pub(super) fn prepare(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        format: TextureFormat,
+        inputs: &[&TextInput],
+        viewport: [f32; 2],
+    ) {
+        let gpu = self.gpu.get_or_insert_with(|| {
+            let mut atlas = TextAtlas::new(device, queue, format);
+            let inputs = GlyphonTextRenderer::new(&mut atlas, device, MultisampleState::default(), None);
+            let tooltip = GlyphonTextRenderer::new(&mut atlas, device, MultisampleState::default(), None);
+            TextGpu { atlas, inputs, tooltip }
+        });
+        let resolution = Resolution {
+            width: viewport[0] as u32,
+            height: viewport[1] as u32,
+        };
+
+        let input_areas = inputs.iter().zip(&self.shaped).filter(|(input, _)| input.is_fully_shown()).map(|(input, shaped)| {
+            let style = input.style();
+            let color = if input.text().is_empty() { style.placeholder_color } else { style.text_color };
+            let [x, y, width, height] = input.inner_bounds(viewport[1]);
+            TextArea {
+                buffer: &shaped.buffer,
+                left: input.text_left(viewport[1]),
+                top: input.text_top(viewport[1]),
+                scale: 1.0,
+                bounds: text_bounds([x, y, width, height]),
+                default_color: to_color(color),
+            }
+        });
+        if let Err(e) = gpu.inputs.prepare(
+            device,
+            queue,
+            &mut self.font_system,
+            &mut gpu.atlas,
+            resolution,
+            input_areas,
+            &mut self.swash_cache,
+        ) {
+            log::error!("Failed to prepare text input text: {:?}", e);
+        }
+
+        let tooltip_areas = self.tooltip.iter().map(|placed| TextArea {
+            buffer: &placed.shaped.buffer,
+            left: placed.rect[0] + placed.padding[0],
+            top: placed.rect[1] + placed.padding[1],
+            scale: 1.0,
+            bounds: text_bounds(placed.rect),
+            default_color: to_color(placed.color),
+        });
+        if let Err(e) = gpu.tooltip.prepare(
+            device,
+            queue,
+            &mut self.font_system,
+            &mut gpu.atlas,
+            resolution,
+            tooltip_areas,
+            &mut self.swash_cache,
+        ) {
+            log::error!("Failed to prepare tooltip text: {:?}", e);
+        }
+    }
+
+    pub(super) fn render_inputs<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        let Some(gpu) = &self.gpu else { return };
+        if let Err(e) = gpu.inputs.render(&gpu.atlas, render_pass) {
+            log::error!("Failed to render text input text: {:?}", e);
+        }
+    }
+
+    pub(super) fn render_tooltip<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
+        let Some(gpu) = &self.gpu else { return };
+        if self.tooltip.is_none() {
+            return;
+        }
+        if let Err(e) = gpu.tooltip.render(&gpu.atlas, render_pass) {
+            log::error!("Failed to render tooltip text: {:?}", e);
+        }
+    }
+
+    /// Shape `key` on a single line that never wraps
+    fn shape(&mut self, key: TextKey) -> ShapedText {
+        let font_system = &mut self.font_system;
+        let line_height = key.font_size * 1.2;
+        let mut buffer = Buffer::new(font_system, Metrics::new(key.font_size, line_height));
+        buffer.set_size(font_system, f32::MAX, line_height);
+        let attrs = Attrs::new().family(Family::SansSerif);
+        buffer.set_rich_text(
+            font_system,
+            fonts::with_emoji(&key.text, attrs, self.emoji_family.as_deref()),
+            Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(font_system);
+        ShapedText { key, buffer }
+    }
+}
+
+fn text_bounds([x, y, width, height]: [f32; 4]) -> TextBounds {
+    TextBounds {
+        left: x as i32,
+        top: y as i32,
+        right: (x + width) as i32,
+        bottom: (y + height) as i32,
+    }
+}
+
+fn to_color([r, g, b, a]: [f32; 4]) -> Color {
+    Color::rgba((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, (a * 255.0) as u8)
+}
+
+/// Start and end of each glyph as (byte index, x), sorted by index
+fn stops(buffer: &Buffer) -> Vec<(usize, f32)> {
+    let mut stops = vec![(0, 0.0)];
+    for run in buffer.layout_runs() {
+        for glyph in run.glyphs {
+            stops.push((glyph.start, glyph.x));
+            stops.push((glyph.end, glyph.x + glyph.w));
+        }
+    }
+    stops.sort_by_key(|(index, _)| *index);
+    stops.dedup_by_key(|(index, _)| *index);
+    stops
+}
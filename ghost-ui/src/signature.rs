@@ -0,0 +1,182 @@
+//! Persona signatures - checking the `signature.json` embedded by
+//! `gassetsmaker sign` when a persona is loaded
+//!
+//! The signed message lists every other file of the persona, sorted by
+//! path, one `<sha256 hex>  <path>\n` line each. A persona whose files were
+//! changed after signing never loads; with trusted keys set, only personas
+//! signed by one of them do.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::skin::SkinError;
+
+/// File holding the signature, at the persona root
+pub const SIGNATURE_FILE: &str = "signature.json";
+
+/// Contents of `signature.json`
+#[derive(Debug, Deserialize)]
+struct SignatureFile {
+    algorithm: String,
+    /// Public key, hex
+    public_key: String,
+    /// First 8 bytes of the public key's SHA-256, hex
+    fingerprint: String,
+    /// Signature of the file listing, hex
+    signature: String,
+}
+
+/// Which persona signers to accept
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersonaTrust {
+    /// Fingerprints or public keys (hex) of trusted signers; empty also
+    /// accepts unsigned personas
+    keys: Vec<String>,
+}
+
+impl PersonaTrust {
+    /// Trust the signers with these fingerprints or public keys (hex)
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// Whether only personas signed by a trusted key load
+    pub fn requires_signature(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Check a persona's files (by `/`-separated path relative to its
+    /// root); returns the signer's fingerprint, None if it is unsigned and
+    /// that is allowed
+    pub fn check(&self, files: &BTreeMap<String, Vec<u8>>, source: &Path) -> Result<Option<String>, SkinError> {
+        let Some(json) = files.get(SIGNATURE_FILE) else {
+            if self.requires_signature() {
+                return Err(untrusted(source, "not signed"));
+            }
+            return Ok(None);
+        };
+        let (fingerprint, public_key) = verify(json, files).map_err(|e| untrusted(source, &e))?;
+        let trusted = self.keys.is_empty()
+            || self
+                .keys
+                .iter()
+                .any(|key| key.eq_ignore_ascii_case(&fingerprint) || key.eq_ignore_ascii_case(&public_key));
+        if !trusted {
+            return Err(untrusted(source, &format!("signed by untrusted key {}", fingerprint)));
+        }
+        log::info!("{} is signed by {}", source.display(), fingerprint);
+        Ok(Some(fingerprint))
+    }
+}
+
+fn untrusted(source: &Path, reason: &str) -> SkinError {
+    SkinError::Untrusted(format!("{}: {}", source.display(), reason))
+}
+
+/// Check the signature in `json` against `files`; the signer's fingerprint
+/// and public key (hex) if it is valid
+fn verify(json: &[u8], files: &BTreeMap<String, Vec<u8>>) -> Result<(String, String), String> {
+    let file: SignatureFile = serde_json::from_slice(json).map_err(|e| format!("{}: {}", SIGNATURE_FILE, e))?;
+    if file.algorithm != "ed25519" {
+        return Err(format!("unsupported signature algorithm {}", file.algorithm));
+    }
+
+    let key_bytes: [u8; 32] = from_hex(&file.public_key)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("invalid public key")?;
+    let public_key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| e.to_string())?;
+    let signature_bytes: [u8; 64] = from_hex(&file.signature)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("invalid signature")?;
+
+    let fingerprint = to_hex(&Sha256::digest(public_key.as_bytes())[..8]);
+    if !fingerprint.eq_ignore_ascii_case(&file.fingerprint) {
+        return Err("fingerprint does not match the public key".to_string());
+    }
+    public_key
+        .verify(&file_listing(files), &Signature::from_bytes(&signature_bytes))
+        .map_err(|_| "files were changed after signing".to_string())?;
+    Ok((fingerprint, to_hex(public_key.as_bytes())))
+}
+
+/// The signed message: `<sha256>  <path>` per file, sorted by path
+fn file_listing(files: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+    let mut listing = String::new();
+    for (path, data) in files.iter().filter(|(path, _)| *path != SIGNATURE_FILE) {
+        listing.push_str(&format!("{}  {}\n", to_hex(&Sha256::digest(data)), path));
+    }
+    listing.into_bytes()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// A persona with `signature.json` as `gassetsmaker sign` writes it
+    fn signed_persona(key: &SigningKey) -> BTreeMap<String, Vec<u8>> {
+        let mut files = BTreeMap::new();
+        files.insert("config.toml".to_string(), b"name = \"rin\"".to_vec());
+        files.insert("idle/frame_0001.png".to_string(), b"png".to_vec());
+        let public_key = key.verifying_key();
+        let json = format!(
+            r#"{{"algorithm": "ed25519", "public_key": "{}", "fingerprint": "{}", "signature": "{}"}}"#,
+            to_hex(public_key.as_bytes()),
+            to_hex(&Sha256::digest(public_key.as_bytes())[..8]),
+            to_hex(&key.sign(&file_listing(&files)).to_bytes()),
+        );
+        files.insert(SIGNATURE_FILE.to_string(), json.into_bytes());
+        files
+    }
+
+    #[test]
+    fn test_signed_persona() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let files = signed_persona(&key);
+        let fingerprint = to_hex(&Sha256::digest(key.verifying_key().as_bytes())[..8]);
+        let source = Path::new("rin");
+
+        assert_eq!(PersonaTrust::default().check(&files, source).unwrap(), Some(fingerprint.clone()));
+        let trusted = PersonaTrust::new(vec![fingerprint.to_uppercase()]);
+        assert_eq!(trusted.check(&files, source).unwrap(), Some(fingerprint));
+        let public_key = PersonaTrust::new(vec![to_hex(key.verifying_key().as_bytes())]);
+        assert!(public_key.check(&files, source).is_ok());
+
+        let other = PersonaTrust::new(vec!["0011223344556677".to_string()]);
+        assert!(matches!(other.check(&files, source), Err(SkinError::Untrusted(_))));
+    }
+
+    #[test]
+    fn test_changed_after_signing() {
+        let mut files = signed_persona(&SigningKey::from_bytes(&[7; 32]));
+        files.insert("idle/frame_0001.png".to_string(), b"other".to_vec());
+        assert!(PersonaTrust::default().check(&files, Path::new("rin")).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_persona() {
+        let mut files = BTreeMap::new();
+        files.insert("config.toml".to_string(), Vec::new());
+        assert_eq!(PersonaTrust::default().check(&files, Path::new("rin")).unwrap(), None);
+        let trusted = PersonaTrust::new(vec!["0011223344556677".to_string()]);
+        assert!(trusted.check(&files, Path::new("rin")).is_err());
+    }
+}
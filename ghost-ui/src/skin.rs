@@ -14,6 +14,8 @@ pub enum SkinError {
     IoError(#[from] std::io::Error),
     #[error("{0}")]
     NotFound(String),
+    #[error("Refusing persona {0}")]
+    Untrusted(String),
 }
 
 /// Skin data that can be loaded before GPU initialization.
@@ -43,6 +45,18 @@ impl SkinData {
         })
     }
 
+    /// Create skin data from a decoded image (stored PNG-encoded).
+    pub fn from_image(img: &image::DynamicImage) -> Result<Self, SkinError> {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut bytes, image::ImageOutputFormat::Png)?;
+        let (width, height) = img.dimensions();
+        Ok(Self {
+            bytes: bytes.into_inner(),
+            width,
+            height,
+        })
+    }
+
     /// Get the raw bytes.
     pub fn bytes(&self) -> &[u8] {
         &self.bytes
@@ -0,0 +1,67 @@
+//! Loading an animated skin off the UI thread
+
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crate::animated_skin::{AnimatedSkin, LoadProgress};
+use crate::skin::SkinError;
+
+/// What a `SkinLoader` reports
+pub enum SkinLoadEvent {
+    /// A state finished loading
+    Progress(LoadProgress),
+    /// The whole skin is loaded (or failed); this is the last event
+    Loaded(Result<AnimatedSkin, SkinError>),
+}
+
+/// Loads an animated skin on a background thread, state by state
+pub struct SkinLoader {
+    receiver: Receiver<SkinLoadEvent>,
+    done: bool,
+}
+
+impl SkinLoader {
+    /// Run `load` on a new thread; it reports each state through the
+    /// callback it is given, e.g. `AnimatedSkin::from_directory_with_progress`
+    pub fn spawn<F>(load: F) -> Self
+    where
+        F: FnOnce(&mut dyn FnMut(LoadProgress)) -> Result<AnimatedSkin, SkinError> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let progress_sender = sender.clone();
+            let result = load(&mut |progress| {
+                let _ = progress_sender.send(SkinLoadEvent::Progress(progress));
+            });
+            let _ = sender.send(SkinLoadEvent::Loaded(result));
+        });
+        Self { receiver, done: false }
+    }
+
+    /// Events that arrived since the last poll
+    pub fn poll(&mut self) -> Vec<SkinLoadEvent> {
+        let mut events = Vec::new();
+        while !self.done {
+            match self.receiver.try_recv() {
+                Ok(event) => {
+                    self.done = matches!(event, SkinLoadEvent::Loaded(_));
+                    events.push(event);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // The loading thread panicked
+                    self.done = true;
+                    events.push(SkinLoadEvent::Loaded(Err(SkinError::NotFound(
+                        "Skin loading stopped unexpectedly".to_string(),
+                    ))));
+                }
+            }
+        }
+        events
+    }
+
+    /// Whether the final `Loaded` event has been delivered
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
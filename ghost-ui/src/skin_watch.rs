@@ -0,0 +1,86 @@
+//! Watching the skin on disk so edits show up without a restart
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Quiet time after the last change before reloading, so an export
+/// writing many frames reloads once
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Watches a skin image, animation file or persona folder for changes (or
+/// any other file, e.g. the app's config)
+pub struct SkinWatcher {
+    path: PathBuf,
+    /// None if the path could not be watched
+    watcher: Option<(RecommendedWatcher, Receiver<notify::Result<Event>>)>,
+    /// When the last change of a pending reload arrived
+    last_change: Option<Instant>,
+}
+
+impl SkinWatcher {
+    /// Start watching `path`; failures are logged and leave the watcher idle
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let watcher = watch(&path)
+            .map_err(|e| log::warn!("Cannot watch '{}': {}", path.display(), e))
+            .ok();
+        if watcher.is_some() {
+            log::info!("Watching '{}' for changes", path.display());
+        }
+        Self {
+            path,
+            watcher,
+            last_change: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True once when the skin changed and has been quiet for a moment
+    pub fn poll(&mut self) -> bool {
+        let Some((_, ref receiver)) = self.watcher else { return false };
+        for event in receiver.try_iter() {
+            match event {
+                Ok(event) if self.affects(&event) => self.last_change = Some(Instant::now()),
+                Ok(_) => {}
+                Err(e) => log::warn!("File watch error: {}", e),
+            }
+        }
+        match self.last_change {
+            Some(at) if at.elapsed() >= DEBOUNCE => {
+                self.last_change = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether an event changes the skin; a single file is watched through
+    /// its folder since editors often replace files instead of writing them
+    fn affects(&self, event: &Event) -> bool {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return false;
+        }
+        if self.path.is_dir() {
+            return true;
+        }
+        event.paths.iter().any(|p| p.file_name() == self.path.file_name())
+    }
+}
+
+fn watch(path: &Path) -> notify::Result<(RecommendedWatcher, Receiver<notify::Result<Event>>)> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    if path.is_dir() {
+        watcher.watch(path, RecursiveMode::Recursive)?;
+    } else {
+        let folder = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        watcher.watch(folder, RecursiveMode::NonRecursive)?;
+    }
+    Ok((watcher, rx))
+}
@@ -0,0 +1,159 @@
+//! Docking the window against screen edges after a drag
+
+use crate::window::ScreenRect;
+
+/// Seconds the window has to stay put before a drag counts as finished
+pub(crate) const DRAG_SETTLE: f32 = 0.3;
+
+/// Seconds the window takes to slide against the edge
+pub(crate) const SNAP_DURATION: f32 = 0.15;
+
+/// Screen edge or corner the window docked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Tracks a drag by the user until the button is released or the window
+/// stops moving
+pub(crate) struct DragTracker {
+    last_position: Option<(i32, i32)>,
+    /// Seconds since the window last moved
+    still: f32,
+    /// Smoothed drag velocity in px/s
+    velocity: (f32, f32),
+    released: bool,
+}
+
+impl DragTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_position: None,
+            still: 0.0,
+            velocity: (0.0, 0.0),
+            released: false,
+        }
+    }
+
+    /// The mouse button was released (not reported on every platform)
+    pub(crate) fn release(&mut self) {
+        self.released = true;
+    }
+
+    /// Velocity of the drag in px/s
+    pub(crate) fn velocity(&self) -> (f32, f32) {
+        self.velocity
+    }
+
+    /// Feed the current position, returns true once the drag has ended
+    pub(crate) fn update(&mut self, position: (i32, i32), delta: f32) -> bool {
+        let last = *self.last_position.get_or_insert(position);
+        if last == position {
+            self.still += delta;
+        } else {
+            self.last_position = Some(position);
+            self.still = 0.0;
+        }
+        if delta > 0.0 {
+            let current = ((position.0 - last.0) as f32 / delta, (position.1 - last.1) as f32 / delta);
+            self.velocity = (
+                (self.velocity.0 + current.0) / 2.0,
+                (self.velocity.1 + current.1) / 2.0,
+            );
+        }
+        self.released || self.still >= DRAG_SETTLE
+    }
+}
+
+/// Position that docks `window` against the edges of `screen` lying within
+/// `threshold` pixels, or None when no edge is close enough
+pub(crate) fn snap_target(window: ScreenRect, screen: ScreenRect, threshold: i32) -> Option<((i32, i32), Edge)> {
+    let left = window.x - screen.x;
+    let top = window.y - screen.y;
+    let right = (screen.x + screen.width as i32) - (window.x + window.width as i32);
+    let bottom = (screen.y + screen.height as i32) - (window.y + window.height as i32);
+
+    // Closest edge on each axis, if within reach (overshooting counts too)
+    let horizontal = match (left.abs() <= threshold, right.abs() <= threshold) {
+        (true, true) if right.abs() < left.abs() => Some(false),
+        (true, _) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    };
+    let vertical = match (top.abs() <= threshold, bottom.abs() <= threshold) {
+        (true, true) if bottom.abs() < top.abs() => Some(false),
+        (true, _) => Some(true),
+        (false, true) => Some(false),
+        (false, false) => None,
+    };
+
+    let x = match horizontal {
+        Some(true) => screen.x,
+        Some(false) => screen.x + screen.width as i32 - window.width as i32,
+        None => window.x,
+    };
+    let y = match vertical {
+        Some(true) => screen.y,
+        Some(false) => screen.y + screen.height as i32 - window.height as i32,
+        None => window.y,
+    };
+    let edge = match (horizontal, vertical) {
+        (Some(true), Some(true)) => Edge::TopLeft,
+        (Some(false), Some(true)) => Edge::TopRight,
+        (Some(true), Some(false)) => Edge::BottomLeft,
+        (Some(false), Some(false)) => Edge::BottomRight,
+        (Some(true), None) => Edge::Left,
+        (Some(false), None) => Edge::Right,
+        (None, Some(true)) => Edge::Top,
+        (None, Some(false)) => Edge::Bottom,
+        (None, None) => return None,
+    };
+    Some(((x, y), edge))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> ScreenRect {
+        ScreenRect { x, y, width, height }
+    }
+
+    #[test]
+    fn test_snap_to_edges() {
+        let screen = rect(0, 0, 1920, 1080);
+        assert_eq!(snap_target(rect(10, 500, 200, 200), screen, 20), Some(((0, 500), Edge::Left)));
+        assert_eq!(snap_target(rect(1715, 500, 200, 200), screen, 20), Some(((1720, 500), Edge::Right)));
+        assert_eq!(snap_target(rect(800, 890, 200, 200), screen, 20), Some(((800, 880), Edge::Bottom)));
+        assert_eq!(snap_target(rect(5, -8, 200, 200), screen, 20), Some(((0, 0), Edge::TopLeft)));
+        assert_eq!(snap_target(rect(800, 500, 200, 200), screen, 20), None);
+    }
+
+    #[test]
+    fn test_snap_on_second_monitor() {
+        // Closer to the right edge than the left one of a narrow screen
+        let screen = rect(1920, 0, 230, 1080);
+        assert_eq!(snap_target(rect(1938, 500, 200, 200), screen, 20), Some(((1950, 500), Edge::Right)));
+    }
+
+    #[test]
+    fn test_drag_tracker() {
+        let mut drag = DragTracker::new();
+        assert!(!drag.update((0, 0), 0.1));
+        assert!(!drag.update((10, 0), 0.1));
+        assert!(drag.velocity().0 > 0.0);
+        assert!(!drag.update((10, 0), 0.1));
+        assert!(drag.update((10, 0), DRAG_SETTLE));
+
+        let mut drag = DragTracker::new();
+        drag.release();
+        assert!(drag.update((0, 0), 0.0));
+    }
+}
@@ -0,0 +1,233 @@
+//! Animation state machine - declarative transitions between the states of
+//! an `AnimatedSkin`
+//!
+//! States declare what happens on events ("idle -> wave on click"), when a
+//! one-shot animation finishes ("wave -> idle when finished"), optional
+//! transition animations played in between, and weighted random variations
+//! shown now and then while idle. The app fires events with `trigger` and
+//! calls `update` after updating the skin.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::animated_skin::{AnimatedSkin, AnimationState, PlayMode};
+use crate::random::Rng;
+
+/// Event fired when a one-shot animation reaches its last frame
+pub const FINISHED: &str = "finished";
+
+/// Matches any state in `TransitionConfig::from`
+pub const ANY_STATE: &str = "*";
+
+/// A transition, e.g. `{ from = "idle", to = "wave", on = "click" }`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TransitionConfig {
+    /// State the transition leaves ("*" for any)
+    pub from: String,
+    /// State the transition enters
+    pub to: String,
+    /// Event name, or "finished" when `from` plays to its end
+    pub on: String,
+    /// Animation played once before entering `to`
+    #[serde(default)]
+    pub via: Option<String>,
+}
+
+/// A state occasionally played once instead of plain idling
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IdleVariation {
+    pub state: String,
+    /// Relative chance of being picked
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// The `[state_machine]` section of a persona's `config.toml`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct StateMachineConfig {
+    /// State that idle variations play from (default: "idle")
+    #[serde(default = "default_idle")]
+    pub idle: String,
+    #[serde(default)]
+    pub transitions: Vec<TransitionConfig>,
+    #[serde(default)]
+    pub idle_variations: Vec<IdleVariation>,
+    /// Seconds between idle variations, [min, max]
+    #[serde(default = "default_idle_interval")]
+    pub idle_interval: [f32; 2],
+}
+
+fn default_idle() -> String {
+    "idle".to_string()
+}
+
+fn default_idle_interval() -> [f32; 2] {
+    [10.0, 30.0]
+}
+
+impl Default for StateMachineConfig {
+    fn default() -> Self {
+        Self {
+            idle: default_idle(),
+            transitions: Vec::new(),
+            idle_variations: Vec::new(),
+            idle_interval: default_idle_interval(),
+        }
+    }
+}
+
+/// Drives an `AnimatedSkin` through the transitions of a `StateMachineConfig`
+pub struct AnimationStateMachine {
+    config: StateMachineConfig,
+    /// Names of the states mentioned in the config
+    names: HashMap<AnimationState, String>,
+    /// State last entered by the machine
+    current: Option<AnimationState>,
+    /// State to enter once the transition animation finishes
+    pending: Option<String>,
+    /// Seconds until the next idle variation
+    idle_timer: f32,
+    rng: Rng,
+}
+
+impl AnimationStateMachine {
+    pub fn new(config: StateMachineConfig) -> Self {
+        let mut names = HashMap::new();
+        let mentioned = config
+            .transitions
+            .iter()
+            .flat_map(|t| [Some(&t.from), Some(&t.to), t.via.as_ref()])
+            .flatten()
+            .chain(config.idle_variations.iter().map(|v| &v.state))
+            .chain(std::iter::once(&config.idle));
+        for name in mentioned.filter(|n| *n != ANY_STATE) {
+            names.insert(AnimationState::from_str(name), name.to_lowercase());
+        }
+
+        let mut machine = Self {
+            config,
+            names,
+            current: None,
+            pending: None,
+            idle_timer: 0.0,
+            rng: Rng::new(),
+        };
+        machine.idle_timer = machine.next_interval();
+        machine
+    }
+
+    /// Fire an event (e.g. "click"); returns true if a transition was taken
+    pub fn trigger(&mut self, skin: &mut AnimatedSkin, event: &str) -> bool {
+        let Some(from) = self.names.get(&skin.current_state()).cloned() else {
+            return self.fire(skin, ANY_STATE, event);
+        };
+        self.fire(skin, &from, event)
+    }
+
+    /// Follow finished animations and play idle variations; call after
+    /// `AnimatedSkin::update`
+    pub fn update(&mut self, skin: &mut AnimatedSkin, delta: f32) {
+        let state = skin.current_state();
+        if let Some(current) = self.current.filter(|c| *c != state) {
+            self.current = None;
+            if skin.is_state_finished(current) {
+                // A one-shot played to its end and the skin fell back to default
+                match self.pending.take() {
+                    Some(target) => {
+                        self.enter(skin, &target);
+                    }
+                    None => {
+                        if let Some(from) = self.names.get(&current).cloned() {
+                            self.fire(skin, &from, FINISHED);
+                        }
+                    }
+                }
+            } else {
+                // Someone else switched the state
+                self.pending = None;
+            }
+            return;
+        }
+
+        if self.config.idle_variations.is_empty() || state != AnimationState::from_str(&self.config.idle) {
+            return;
+        }
+        self.idle_timer -= delta;
+        if self.idle_timer <= 0.0 {
+            self.idle_timer = self.next_interval();
+            if let Some(variation) = self.pick_variation() {
+                log::debug!("Idle variation: {}", variation);
+                if skin.has_state(AnimationState::from_str(&variation)) {
+                    skin.play(AnimationState::from_str(&variation), PlayMode::Once);
+                    self.current = Some(skin.current_state());
+                }
+            }
+        }
+    }
+
+    /// Take the first transition leaving `from` on `event`
+    fn fire(&mut self, skin: &mut AnimatedSkin, from: &str, event: &str) -> bool {
+        let transition = self
+            .config
+            .transitions
+            .iter()
+            .find(|t| (t.from.eq_ignore_ascii_case(from) || t.from == ANY_STATE) && t.on.eq_ignore_ascii_case(event))
+            .cloned();
+        let Some(transition) = transition else { return false };
+        log::debug!("Transition {} -> {} on {}", from, transition.to, event);
+
+        if let Some(via) = transition.via.filter(|v| skin.has_state(AnimationState::from_str(v))) {
+            skin.play(AnimationState::from_str(&via), PlayMode::Once);
+            self.current = Some(skin.current_state());
+            self.pending = Some(transition.to);
+            return true;
+        }
+        self.enter(skin, &transition.to)
+    }
+
+    /// Switch to a state: once if something follows when it finishes,
+    /// otherwise looping
+    fn enter(&mut self, skin: &mut AnimatedSkin, name: &str) -> bool {
+        let state = AnimationState::from_str(name);
+        if !skin.has_state(state) {
+            log::warn!("State machine: animation state not available: {}", name);
+            return false;
+        }
+        let plays_once = self
+            .config
+            .transitions
+            .iter()
+            .any(|t| t.from.eq_ignore_ascii_case(name) && t.on.eq_ignore_ascii_case(FINISHED));
+        skin.play(state, if plays_once { PlayMode::Once } else { PlayMode::Loop });
+        self.current = Some(state);
+        self.pending = None;
+        true
+    }
+
+    /// Weighted random idle variation
+    fn pick_variation(&mut self) -> Option<String> {
+        let total: f32 = self.config.idle_variations.iter().map(|v| v.weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut roll = self.rng.range(0.0, total);
+        for variation in &self.config.idle_variations {
+            roll -= variation.weight.max(0.0);
+            if roll <= 0.0 {
+                return Some(variation.state.clone());
+            }
+        }
+        self.config.idle_variations.last().map(|v| v.state.clone())
+    }
+
+    fn next_interval(&mut self) -> f32 {
+        let [min, max] = self.config.idle_interval;
+        let min = min.max(1.0);
+        self.rng.range(min, max.max(min))
+    }
+}
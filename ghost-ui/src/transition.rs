@@ -0,0 +1,101 @@
+//! Animated showing and hiding of the ghost window
+
+use std::time::Duration;
+
+use crate::easing::Easing;
+use crate::window::ScreenRect;
+
+/// How the window appears and disappears
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// Fade in and out
+    Fade,
+    /// Grow from and shrink to the skin's bottom edge
+    Pop,
+    /// Slide in from and out past the nearest screen edge
+    Slide,
+    /// Dissolve pixel by pixel (see `Effect::Dissolve`)
+    Dissolve,
+}
+
+impl Transition {
+    /// Parse a transition name ("fade", "pop", "slide", "dissolve")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "fade" => Some(Self::Fade),
+            "pop" | "scale" => Some(Self::Pop),
+            "slide" => Some(Self::Slide),
+            "dissolve" => Some(Self::Dissolve),
+            _ => None,
+        }
+    }
+}
+
+/// A show or hide in progress
+pub(crate) struct VisibilityTransition {
+    pub(crate) transition: Transition,
+    pub(crate) showing: bool,
+    /// Visibility when it started (0.0 hidden - 1.0 shown)
+    from: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl VisibilityTransition {
+    /// Start showing or hiding from `from`, e.g. the visibility of an
+    /// interrupted transition
+    pub(crate) fn new(transition: Transition, showing: bool, from: f32, duration: Duration) -> Self {
+        Self {
+            transition,
+            showing,
+            from: from.clamp(0.0, 1.0),
+            duration: duration.as_secs_f32(),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advance by `delta` seconds, returns true once finished
+    pub(crate) fn advance(&mut self, delta: f32) -> bool {
+        self.elapsed += delta;
+        self.progress() >= 1.0
+    }
+
+    fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// How far the window is shown (0.0 hidden - 1.0 shown), eased out
+    /// when showing and in when hiding
+    pub(crate) fn visibility(&self) -> f32 {
+        let t = self.progress();
+        let (to, eased) = if self.showing {
+            (1.0, Easing::EaseOut.apply(t))
+        } else {
+            (0.0, Easing::EaseIn.apply(t))
+        };
+        self.from + (to - self.from) * eased
+    }
+}
+
+/// Where the window sits when slid out past the screen edge nearest to it
+pub(crate) fn slide_away(window: ScreenRect, screen: ScreenRect) -> (i32, i32) {
+    let left = window.x - screen.x;
+    let right = (screen.x + screen.width as i32) - (window.x + window.width as i32);
+    let top = window.y - screen.y;
+    let bottom = (screen.y + screen.height as i32) - (window.y + window.height as i32);
+
+    let nearest = left.min(right).min(top).min(bottom);
+    if nearest == left {
+        (screen.x - window.width as i32, window.y)
+    } else if nearest == right {
+        (screen.x + screen.width as i32, window.y)
+    } else if nearest == bottom {
+        (window.x, screen.y + screen.height as i32)
+    } else {
+        (window.x, screen.y - window.height as i32)
+    }
+}
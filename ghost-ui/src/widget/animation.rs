@@ -0,0 +1,209 @@
+//! Animated showing and hiding of widgets
+
+use std::time::Duration;
+
+use crate::easing::Easing;
+
+/// How a widget appears and disappears; fading, sliding and scaling
+/// combine, e.g. `WidgetAnimation::fade().with_slide(0.0, 8.0)`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WidgetAnimation {
+    /// Fade the widget in and out
+    pub fade: bool,
+    /// Offset [x, y] in pixels (y down) the widget slides in from and out to
+    pub slide: [f32; 2],
+    /// Scale the widget grows from and shrinks to, around its center
+    /// (1.0 = no scaling)
+    pub scale: f32,
+    pub duration: Duration,
+    /// Easing when showing; hiding plays it in reverse
+    pub easing: Easing,
+    /// Show and hide at once, for people who asked for less motion
+    pub reduced_motion: bool,
+}
+
+impl Default for WidgetAnimation {
+    fn default() -> Self {
+        Self::fade()
+    }
+}
+
+impl WidgetAnimation {
+    /// Fade in and out
+    pub fn fade() -> Self {
+        Self {
+            fade: true,
+            slide: [0.0, 0.0],
+            scale: 1.0,
+            duration: Duration::from_millis(200),
+            easing: Easing::EaseOut,
+            reduced_motion: false,
+        }
+    }
+
+    /// Slide in from and out to an offset, without fading
+    pub fn slide(dx: f32, dy: f32) -> Self {
+        Self {
+            fade: false,
+            ..Self::fade()
+        }
+        .with_slide(dx, dy)
+    }
+
+    /// Grow from and shrink to `scale`, without fading
+    pub fn scale(scale: f32) -> Self {
+        Self {
+            fade: false,
+            ..Self::fade()
+        }
+        .with_scale(scale)
+    }
+
+    /// Also slide in from and out to an offset
+    pub fn with_slide(mut self, dx: f32, dy: f32) -> Self {
+        self.slide = [dx, dy];
+        self
+    }
+
+    /// Also grow from and shrink to `scale`
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(0.0);
+        self
+    }
+
+    /// Set how long showing or hiding takes
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Set the easing curve
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Skip the animation when reduced motion is on
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+}
+
+/// A widget's animated visibility: where it is between hidden (0.0) and
+/// shown (1.0), heading towards one of them
+#[derive(Debug, Clone)]
+pub(crate) struct Appearance {
+    animation: WidgetAnimation,
+    shown: bool,
+    /// Progress (0.0 hidden - 1.0 shown), before easing
+    progress: f32,
+}
+
+impl Appearance {
+    pub(crate) fn new(animation: WidgetAnimation, shown: bool) -> Self {
+        let progress = if shown { 1.0 } else { 0.0 };
+        Self {
+            animation,
+            shown,
+            progress,
+        }
+    }
+
+    /// Head towards shown or hidden, from wherever an interrupted animation
+    /// left off
+    pub(crate) fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// Whether anything of the widget is drawn
+    pub(crate) fn is_drawn(&self) -> bool {
+        self.progress > 0.0
+    }
+
+    /// Whether the widget is shown and done animating in
+    pub(crate) fn is_fully_shown(&self) -> bool {
+        self.progress >= 1.0
+    }
+
+    /// Advance by `delta` seconds, returns true while animating
+    pub(crate) fn update(&mut self, delta: f32) -> bool {
+        let to = if self.shown { 1.0 } else { 0.0 };
+        if self.progress == to {
+            return false;
+        }
+        let duration = self.animation.duration.as_secs_f32();
+        let step = if duration > 0.0 && !self.animation.reduced_motion { delta / duration } else { 1.0 };
+        self.progress = if self.shown {
+            (self.progress + step).min(1.0)
+        } else {
+            (self.progress - step).max(0.0)
+        };
+        true
+    }
+
+    /// Eased visibility; may overshoot 1.0 with springy easings
+    fn visibility(&self) -> f32 {
+        self.animation.easing.apply(self.progress)
+    }
+
+    /// Opacity multiplier
+    pub(crate) fn opacity(&self) -> f32 {
+        if self.animation.fade {
+            self.visibility().clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// `bounds` [x, y, width, height] moved and scaled for the visibility
+    pub(crate) fn transform(&self, bounds: [f32; 4]) -> [f32; 4] {
+        let hidden = 1.0 - self.visibility();
+        let [x, y, width, height] = bounds;
+        let scale = 1.0 + (self.animation.scale - 1.0) * hidden;
+        let (scaled_width, scaled_height) = (width * scale.max(0.0), height * scale.max(0.0));
+        [
+            x + (width - scaled_width) / 2.0 + self.animation.slide[0] * hidden,
+            y + (height - scaled_height) / 2.0 + self.animation.slide[1] * hidden,
+            scaled_width,
+            scaled_height,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_show_and_hide() {
+        let mut appearance = Appearance::new(WidgetAnimation::fade(), false);
+        assert!(!appearance.is_drawn());
+        appearance.set_shown(true);
+        assert!(appearance.update(0.1));
+        assert!(appearance.is_drawn() && !appearance.is_fully_shown());
+        assert!(appearance.opacity() > 0.0 && appearance.opacity() < 1.0);
+        assert!(appearance.update(0.1));
+        assert!(appearance.is_fully_shown());
+        assert!(!appearance.update(0.1));
+
+        appearance.set_shown(false);
+        appearance.update(0.1);
+        assert!(appearance.is_drawn());
+        appearance.update(0.1);
+        assert!(!appearance.is_drawn());
+    }
+
+    #[test]
+    fn test_reduced_motion() {
+        let animation = WidgetAnimation::slide(0.0, 8.0).with_reduced_motion(true);
+        let mut appearance = Appearance::new(animation, false);
+        appearance.set_shown(true);
+        assert!(appearance.update(0.001));
+        assert!(appearance.is_fully_shown());
+        assert_eq!(appearance.transform([10.0, 20.0, 30.0, 40.0]), [10.0, 20.0, 30.0, 40.0]);
+        appearance.set_shown(false);
+        appearance.update(0.001);
+        assert!(!appearance.is_drawn());
+    }
+}
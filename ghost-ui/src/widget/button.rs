@@ -1,6 +1,12 @@
 //! Button widget for ghost-ui
 
-use super::{to_screen_coords, Origin, Widget};
+use std::sync::Arc;
+
+use image::{DynamicImage, GenericImage, RgbaImage};
+
+use super::animation::{Appearance, WidgetAnimation};
+use super::{to_screen_coords, LayoutItem, Origin, Widget};
+use crate::{SkinData, SkinError};
 
 /// Unique identifier for a button
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -19,6 +25,103 @@ pub enum ButtonState {
     Normal,
     Hover,
     Pressed,
+    /// Not taking clicks or focus
+    Disabled,
+}
+
+/// Opacity of a disabled button without a disabled frame or color of its own
+const DISABLED_OPACITY: f32 = 0.5;
+
+/// Hand-drawn look for a button: a sprite sheet with one frame per state,
+/// side by side in the order normal, hover, pressed, disabled
+///
+/// Sheets may stop early; a missing hover frame shows the normal one, a
+/// missing pressed frame the hover one, and a missing disabled frame the
+/// normal one faded.
+#[derive(Clone)]
+pub struct ButtonImage {
+    /// Shared, so the renderer can tell when a button's image changed
+    sheet: Arc<SkinData>,
+    frames: u32,
+}
+
+impl ButtonImage {
+    /// Most frames a sheet can have
+    pub const MAX_FRAMES: u32 = 4;
+
+    /// Use `sheet`, `frames` equally wide frames side by side
+    pub fn from_sheet(sheet: SkinData, frames: u32) -> Result<Self, SkinError> {
+        if !(1..=Self::MAX_FRAMES).contains(&frames) {
+            return Err(SkinError::NotFound(format!("a button sheet has 1 to 4 frames, not {}", frames)));
+        }
+        if !sheet.width().is_multiple_of(frames) {
+            return Err(SkinError::NotFound(format!(
+                "a {} pixel wide button sheet does not split into {} frames",
+                sheet.width(),
+                frames
+            )));
+        }
+        Ok(Self {
+            sheet: Arc::new(sheet),
+            frames,
+        })
+    }
+
+    /// Put separate images of the same size, in state order, into a sheet
+    pub fn from_frames(frames: &[SkinData]) -> Result<Self, SkinError> {
+        let Some(first) = frames.first() else {
+            return Err(SkinError::NotFound("a button needs at least one image".to_string()));
+        };
+        let (width, height) = first.dimensions();
+        if frames.iter().any(|frame| frame.dimensions() != (width, height)) {
+            return Err(SkinError::NotFound("button images differ in size".to_string()));
+        }
+        let count = frames.len().min(Self::MAX_FRAMES as usize) as u32;
+        let mut sheet = RgbaImage::new(width * count, height);
+        for (index, frame) in frames.iter().take(count as usize).enumerate() {
+            let image = image::load_from_memory(frame.bytes())?;
+            sheet.copy_from(&image.to_rgba8(), width * index as u32, 0)?;
+        }
+        Self::from_sheet(SkinData::from_image(&DynamicImage::ImageRgba8(sheet))?, count)
+    }
+
+    /// The whole sheet
+    pub fn sheet(&self) -> &Arc<SkinData> {
+        &self.sheet
+    }
+
+    /// Number of frames in the sheet
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Frame to draw for `state` and the opacity to draw it with
+    pub fn frame(&self, state: ButtonState) -> (u32, f32) {
+        let hover = if self.frames > 1 { 1 } else { 0 };
+        match state {
+            ButtonState::Normal => (0, 1.0),
+            ButtonState::Hover => (hover, 1.0),
+            ButtonState::Pressed => (if self.frames > 2 { 2 } else { hover }, 1.0),
+            ButtonState::Disabled if self.frames > 3 => (3, 1.0),
+            ButtonState::Disabled => (0, DISABLED_OPACITY),
+        }
+    }
+
+    /// Texture coordinates [u0, v0, u1, v1] of the frame to draw for `state`
+    pub fn frame_uv(&self, state: ButtonState) -> [f32; 4] {
+        let (frame, _) = self.frame(state);
+        let width = 1.0 / self.frames as f32;
+        [frame as f32 * width, 0.0, (frame + 1) as f32 * width, 1.0]
+    }
+}
+
+impl std::fmt::Debug for ButtonImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ButtonImage")
+            .field("size", &self.sheet.dimensions())
+            .field("frames", &self.frames)
+            .finish()
+    }
 }
 
 /// Visual style for buttons
@@ -38,6 +141,16 @@ pub struct ButtonStyle {
     pub font_size: f32,
     /// Padding
     pub padding: f32,
+    /// Shadow color (transparent for no shadow)
+    pub shadow_color: [f32; 4],
+    /// Shadow offset [x, y] in pixels, positive is right and down
+    pub shadow_offset: [f32; 2],
+    /// How far the shadow's edge fades out, in pixels
+    pub shadow_blur: f32,
+    /// Color of the ring around the button while it has keyboard focus
+    pub focus_color: [f32; 4],
+    /// Width of the focus ring in pixels
+    pub focus_width: f32,
 }
 
 impl Default for ButtonStyle {
@@ -50,6 +163,11 @@ impl Default for ButtonStyle {
             border_radius: 4.0,
             font_size: 14.0,
             padding: 8.0,
+            shadow_color: [0.0, 0.0, 0.0, 0.3],
+            shadow_offset: [0.0, 2.0],
+            shadow_blur: 3.0,
+            focus_color: [0.4, 0.7, 1.0, 1.0],
+            focus_width: 2.0,
         }
     }
 }
@@ -76,6 +194,19 @@ impl ButtonStyle {
             ..Default::default()
         }
     }
+
+    /// The same style without a shadow
+    pub fn without_shadow(self) -> Self {
+        Self {
+            shadow_color: [0.0; 4],
+            ..self
+        }
+    }
+
+    /// Whether the style casts a shadow
+    pub fn has_shadow(&self) -> bool {
+        self.shadow_color[3] > 0.0
+    }
 }
 
 /// A clickable button widget
@@ -97,6 +228,12 @@ pub struct Button {
     origin: Origin,
     /// Whether button is visible
     visible: bool,
+    /// Shown when the cursor rests on the button
+    tooltip: Option<String>,
+    /// Animated showing and hiding (None = instant)
+    appearance: Option<Appearance>,
+    /// Drawn instead of the style's colors
+    image: Option<ButtonImage>,
 }
 
 impl Button {
@@ -111,9 +248,24 @@ impl Button {
             style: ButtonStyle::default(),
             origin: Origin::BottomLeft,
             visible: true,
+            tooltip: None,
+            appearance: None,
+            image: None,
         }
     }
 
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.appearance = Some(Appearance::new(animation, self.visible));
+        self
+    }
+
+    /// Show `tooltip` when the cursor rests on the button
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
     /// Set the button position (relative to origin)
     pub fn with_position(mut self, x: f32, y: f32) -> Self {
         self.position = [x, y];
@@ -132,6 +284,12 @@ impl Button {
         self
     }
 
+    /// Draw `image` instead of the style's background and shadow
+    pub fn with_image(mut self, image: ButtonImage) -> Self {
+        self.image = Some(image);
+        self
+    }
+
     /// Set the coordinate origin
     pub fn with_origin(mut self, origin: Origin) -> Self {
         self.origin = origin;
@@ -153,6 +311,16 @@ impl Button {
         self.label = label.into();
     }
 
+    /// Get the tooltip
+    pub fn tooltip(&self) -> Option<&str> {
+        self.tooltip.as_deref()
+    }
+
+    /// Set or remove the tooltip
+    pub fn set_tooltip(&mut self, tooltip: Option<String>) {
+        self.tooltip = tooltip;
+    }
+
     /// Get the current state
     pub fn state(&self) -> ButtonState {
         self.state
@@ -163,14 +331,64 @@ impl Button {
         &self.style
     }
 
+    /// Get the image, if the button has one
+    pub fn image(&self) -> Option<&ButtonImage> {
+        self.image.as_ref()
+    }
+
+    /// Check if the button takes clicks and focus
+    pub fn is_enabled(&self) -> bool {
+        self.state != ButtonState::Disabled
+    }
+
+    /// Enable or disable the button
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled != self.is_enabled() {
+            self.state = if enabled { ButtonState::Normal } else { ButtonState::Disabled };
+        }
+    }
+
     /// Check if visible
     pub fn is_visible(&self) -> bool {
         self.visible
     }
 
-    /// Set visibility
+    /// Set visibility, animated if the button has an animation; a hiding
+    /// button no longer takes clicks
     pub fn set_visible(&mut self, visible: bool) {
         self.visible = visible;
+        if let Some(ref mut appearance) = self.appearance {
+            appearance.set_shown(visible);
+        }
+    }
+
+    /// Advance the show or hide animation, returns true while it runs (the
+    /// ghost window calls this every frame)
+    pub fn update(&mut self, delta: f32) -> bool {
+        self.appearance.as_mut().is_some_and(|appearance| appearance.update(delta))
+    }
+
+    /// Whether any of the button is drawn, including while it fades out
+    pub fn is_drawn(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => appearance.is_drawn(),
+            None => self.visible,
+        }
+    }
+
+    /// Opacity multiplier of the show or hide animation
+    pub fn opacity(&self) -> f32 {
+        self.appearance.as_ref().map_or(1.0, Appearance::opacity)
+    }
+
+    /// Bounds to draw at [x, y, width, height], moved and scaled by the
+    /// show or hide animation
+    pub fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        let bounds = self.screen_bounds(window_height);
+        match self.appearance {
+            Some(ref appearance) => appearance.transform(bounds),
+            None => bounds,
+        }
     }
 
     /// Get position in local coordinates
@@ -194,12 +412,16 @@ impl Button {
             ButtonState::Normal => self.style.background,
             ButtonState::Hover => self.style.background_hover,
             ButtonState::Pressed => self.style.background_pressed,
+            ButtonState::Disabled => {
+                let [r, g, b, a] = self.style.background;
+                [r, g, b, a * DISABLED_OPACITY]
+            }
         }
     }
 
     /// Check if a point (in screen coordinates) is inside the button
     fn contains_point(&self, screen_x: f32, screen_y: f32, window_height: f32) -> bool {
-        if !self.visible {
+        if !self.visible || !self.is_enabled() {
             return false;
         }
 
@@ -228,6 +450,7 @@ impl Widget for Button {
         let is_inside = self.contains_point(cursor_x, cursor_y, window_height);
 
         self.state = match self.state {
+            ButtonState::Disabled => ButtonState::Disabled,
             ButtonState::Pressed => ButtonState::Pressed, // Keep pressed until release
             _ if is_inside => ButtonState::Hover,
             _ => ButtonState::Normal,
@@ -248,7 +471,7 @@ impl Widget for Button {
     }
 
     fn handle_release(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
-        if !self.visible {
+        if !self.visible || !self.is_enabled() {
             return false;
         }
 
@@ -277,3 +500,92 @@ impl Widget for Button {
         [x, y, self.size[0], self.size[1]]
     }
 }
+
+impl LayoutItem for Button {
+    fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> SkinData {
+        SkinData::from_image(&DynamicImage::new_rgba8(width, height)).unwrap()
+    }
+
+    fn frames(image: &ButtonImage) -> Vec<(u32, f32)> {
+        [ButtonState::Normal, ButtonState::Hover, ButtonState::Pressed, ButtonState::Disabled]
+            .into_iter()
+            .map(|state| image.frame(state))
+            .collect()
+    }
+
+    #[test]
+    fn test_frame_per_state() {
+        let four = ButtonImage::from_sheet(image(80, 20), 4).unwrap();
+        assert_eq!(frames(&four), [(0, 1.0), (1, 1.0), (2, 1.0), (3, 1.0)]);
+        assert_eq!(four.frame_uv(ButtonState::Pressed), [0.5, 0.0, 0.75, 1.0]);
+
+        // Without a disabled frame the normal one is faded
+        let three = ButtonImage::from_sheet(image(60, 20), 3).unwrap();
+        assert_eq!(frames(&three), [(0, 1.0), (1, 1.0), (2, 1.0), (0, DISABLED_OPACITY)]);
+
+        let two = ButtonImage::from_sheet(image(40, 20), 2).unwrap();
+        assert_eq!(frames(&two), [(0, 1.0), (1, 1.0), (1, 1.0), (0, DISABLED_OPACITY)]);
+
+        let one = ButtonImage::from_sheet(image(20, 20), 1).unwrap();
+        assert_eq!(frames(&one), [(0, 1.0), (0, 1.0), (0, 1.0), (0, DISABLED_OPACITY)]);
+        assert_eq!(one.frame_uv(ButtonState::Hover), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_invalid_sheet() {
+        assert!(ButtonImage::from_sheet(image(60, 20), 0).is_err());
+        assert!(ButtonImage::from_sheet(image(100, 20), 5).is_err());
+        assert!(ButtonImage::from_sheet(image(50, 20), 3).is_err());
+        assert!(ButtonImage::from_frames(&[]).is_err());
+        assert!(ButtonImage::from_frames(&[image(20, 20), image(20, 10)]).is_err());
+    }
+
+    #[test]
+    fn test_from_frames() {
+        let red = image::RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let blue = image::RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255]));
+        let frames = [red, blue.clone(), blue].map(|frame| SkinData::from_image(&frame.into()).unwrap());
+
+        let image = ButtonImage::from_frames(&frames).unwrap();
+        assert_eq!(image.frames(), 3);
+        let sheet = image::load_from_memory(image.sheet().bytes()).unwrap().to_rgba8();
+        assert_eq!(sheet.dimensions(), (6, 2));
+        assert_eq!(sheet.get_pixel(1, 1), &image::Rgba([255, 0, 0, 255]));
+        assert_eq!(sheet.get_pixel(2, 0), &image::Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_disabled_button() {
+        let mut button = Button::new(ButtonId::new(1), "Greet").with_position(0.0, 0.0).with_size(50.0, 20.0);
+        button.set_enabled(false);
+        button.update_hover(10.0, 90.0, 100.0);
+        assert!(!button.handle_press(10.0, 90.0, 100.0));
+        assert_eq!(button.state(), ButtonState::Disabled);
+
+        button.set_enabled(true);
+        assert!(button.handle_press(10.0, 90.0, 100.0));
+        assert!(button.handle_release(10.0, 90.0, 100.0));
+        assert_eq!(button.state(), ButtonState::Hover);
+    }
+}
@@ -0,0 +1,135 @@
+//! Keyboard focus for ghost-ui buttons
+
+use tao::keyboard::{Key, ModifiersState};
+
+use super::{Button, ButtonId};
+
+/// What a key did to the keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusAction {
+    /// Focus moved to another button, or left the buttons
+    Moved,
+    /// Enter or Space was pressed on the focused button
+    Activated(ButtonId),
+}
+
+/// Tracks which button has keyboard focus and moves it with the keyboard:
+/// Tab and Shift+Tab or the arrow keys go through the visible, enabled
+/// buttons in order, Enter or Space activates the focused one and Escape drops focus.
+///
+/// The ghost window keeps one and draws a focus ring around the focused
+/// button; clicking with the mouse drops focus again.
+#[derive(Debug, Clone, Default)]
+pub struct FocusManager {
+    focused: Option<ButtonId>,
+}
+
+impl FocusManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The focused button
+    pub fn focused(&self) -> Option<ButtonId> {
+        self.focused
+    }
+
+    /// Focus a button, or none
+    pub fn set_focused(&mut self, focused: Option<ButtonId>) {
+        self.focused = focused;
+    }
+
+    /// Handle a pressed key for `buttons` in focus order; returns None if
+    /// the key is not for focus
+    pub fn handle_key(&mut self, key: &Key, modifiers: ModifiersState, buttons: &[&Button]) -> Option<FocusAction> {
+        let focusable: Vec<ButtonId> = buttons
+            .iter()
+            .filter(|button| button.is_visible() && button.is_enabled())
+            .map(|button| button.id())
+            .collect();
+        let current = self.focused.and_then(|focused| focusable.iter().position(|id| *id == focused));
+
+        let backwards = match key {
+            Key::Tab => modifiers.shift_key(),
+            Key::ArrowLeft | Key::ArrowUp => true,
+            Key::ArrowRight | Key::ArrowDown => false,
+            Key::Enter | Key::Space => {
+                return current.map(|index| FocusAction::Activated(focusable[index]));
+            }
+            Key::Escape if self.focused.is_some() => {
+                self.focused = None;
+                return Some(FocusAction::Moved);
+            }
+            _ => return None,
+        };
+        // Arrows only move focus once a button has it, so they stay free
+        // for the app until Tab is pressed
+        if current.is_none() && !matches!(key, Key::Tab) {
+            return None;
+        }
+        if focusable.is_empty() {
+            return None;
+        }
+
+        let count = focusable.len();
+        let next = match (current, backwards) {
+            (Some(index), false) => (index + 1) % count,
+            (Some(index), true) => (index + count - 1) % count,
+            (None, false) => 0,
+            (None, true) => count - 1,
+        };
+        self.focused = Some(focusable[next]);
+        Some(FocusAction::Moved)
+    }
+
+    /// Drop focus, returns true if a button had it
+    pub fn clear(&mut self) -> bool {
+        self.focused.take().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buttons() -> Vec<Button> {
+        let mut hidden = Button::new(ButtonId::new(2), "hidden");
+        hidden.set_visible(false);
+        vec![Button::new(ButtonId::new(1), "one"), hidden, Button::new(ButtonId::new(3), "three")]
+    }
+
+    #[test]
+    fn test_tab_cycles_visible_buttons() {
+        let buttons = buttons();
+        let buttons: Vec<&Button> = buttons.iter().collect();
+        let mut focus = FocusManager::new();
+        let none = ModifiersState::empty();
+
+        // Arrows are left to the app until Tab is pressed
+        assert_eq!(focus.handle_key(&Key::ArrowRight, none, &buttons), None);
+        assert_eq!(focus.handle_key(&Key::Tab, none, &buttons), Some(FocusAction::Moved));
+        assert_eq!(focus.focused(), Some(ButtonId::new(1)));
+        focus.handle_key(&Key::Tab, none, &buttons);
+        assert_eq!(focus.focused(), Some(ButtonId::new(3)));
+        focus.handle_key(&Key::ArrowRight, none, &buttons);
+        assert_eq!(focus.focused(), Some(ButtonId::new(1)));
+        focus.handle_key(&Key::Tab, ModifiersState::SHIFT, &buttons);
+        assert_eq!(focus.focused(), Some(ButtonId::new(3)));
+    }
+
+    #[test]
+    fn test_activate_and_escape() {
+        let buttons = buttons();
+        let buttons: Vec<&Button> = buttons.iter().collect();
+        let mut focus = FocusManager::new();
+        let none = ModifiersState::empty();
+
+        assert_eq!(focus.handle_key(&Key::Enter, none, &buttons), None);
+        focus.handle_key(&Key::Tab, ModifiersState::SHIFT, &buttons);
+        assert_eq!(focus.handle_key(&Key::Space, none, &buttons), Some(FocusAction::Activated(ButtonId::new(3))));
+        assert_eq!(focus.handle_key(&Key::Escape, none, &buttons), Some(FocusAction::Moved));
+        assert_eq!(focus.focused(), None);
+        assert_eq!(focus.handle_key(&Key::Escape, none, &buttons), None);
+        assert!(!focus.clear());
+    }
+}
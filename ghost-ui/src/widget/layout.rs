@@ -0,0 +1,214 @@
+//! Layout containers for ghost-ui widgets
+//!
+//! Widgets are placed by absolute coordinates; a `Stack` computes those
+//! coordinates from the window size, so a row of buttons can stay at the
+//! bottom center of the window however it is resized.
+
+use super::{to_screen_coords, Origin};
+
+/// A widget a layout can move
+pub trait LayoutItem {
+    /// Size [width, height]
+    fn size(&self) -> [f32; 2];
+
+    /// Coordinate origin the position is given in
+    fn origin(&self) -> Origin;
+
+    /// Hidden items take no space in a stack
+    fn is_visible(&self) -> bool;
+
+    /// Set position (relative to origin)
+    fn set_position(&mut self, x: f32, y: f32);
+}
+
+/// Point of the window a stack is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    #[default]
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Horizontal and vertical placement as fractions of the free space
+    /// (0.0 = left/top, 1.0 = right/bottom)
+    fn fractions(self) -> [f32; 2] {
+        match self {
+            Anchor::TopLeft => [0.0, 0.0],
+            Anchor::TopCenter => [0.5, 0.0],
+            Anchor::TopRight => [1.0, 0.0],
+            Anchor::CenterLeft => [0.0, 0.5],
+            Anchor::Center => [0.5, 0.5],
+            Anchor::CenterRight => [1.0, 0.5],
+            Anchor::BottomLeft => [0.0, 1.0],
+            Anchor::BottomCenter => [0.5, 1.0],
+            Anchor::BottomRight => [1.0, 1.0],
+        }
+    }
+}
+
+/// Direction a stack lines up its items in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Left to right
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+}
+
+/// Where items smaller than the stack sit across its axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    /// Top of a row, left of a column
+    Start,
+    #[default]
+    Center,
+    /// Bottom of a row, right of a column
+    End,
+}
+
+impl Align {
+    fn fraction(self) -> f32 {
+        match self {
+            Align::Start => 0.0,
+            Align::Center => 0.5,
+            Align::End => 1.0,
+        }
+    }
+}
+
+/// Lines up widgets in a row or column and anchors the group in the window.
+///
+/// Call `arrange` when the window size changes (see `GhostApp::layout`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stack {
+    axis: Axis,
+    /// Gap between items
+    spacing: f32,
+    anchor: Anchor,
+    align: Align,
+    /// Gap between the stack and the window edges it is anchored to
+    margin: [f32; 2],
+}
+
+impl Stack {
+    /// Items left to right (an HStack)
+    pub fn row() -> Self {
+        Self::new(Axis::Horizontal)
+    }
+
+    /// Items top to bottom (a VStack)
+    pub fn column() -> Self {
+        Self::new(Axis::Vertical)
+    }
+
+    /// Create a stack along `axis`, anchored at the bottom center
+    pub fn new(axis: Axis) -> Self {
+        Self {
+            axis,
+            spacing: 8.0,
+            anchor: Anchor::default(),
+            align: Align::default(),
+            margin: [0.0, 0.0],
+        }
+    }
+
+    /// Set the gap between items
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Set the point of the window the stack is attached to
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set where smaller items sit across the stack
+    pub fn with_align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Keep the stack `x` pixels from the left or right edge and `y` from
+    /// the top or bottom edge it is anchored to
+    pub fn with_margin(mut self, x: f32, y: f32) -> Self {
+        self.margin = [x, y];
+        self
+    }
+
+    /// Get the axis
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    /// Get the anchor
+    pub fn anchor(&self) -> Anchor {
+        self.anchor
+    }
+
+    /// Size [width, height] the stack takes for items of `sizes`
+    pub fn size(&self, sizes: &[[f32; 2]]) -> [f32; 2] {
+        let (main, cross) = self.axes();
+        let mut size = [0.0, 0.0];
+        size[main] = sizes.iter().map(|s| s[main]).sum::<f32>()
+            + self.spacing * sizes.len().saturating_sub(1) as f32;
+        size[cross] = sizes.iter().map(|s| s[cross]).fold(0.0, f32::max);
+        size
+    }
+
+    /// Top-left corners (screen coordinates) of items of `sizes` in a
+    /// window of `window_size`
+    pub fn positions(&self, sizes: &[[f32; 2]], window_size: [f32; 2]) -> Vec<[f32; 2]> {
+        let (main, cross) = self.axes();
+        let size = self.size(sizes);
+
+        // Corner of the whole stack
+        let fractions = self.anchor.fractions();
+        let mut corner = [0.0, 0.0];
+        for axis in 0..2 {
+            let free = window_size[axis] - self.margin[axis] * 2.0 - size[axis];
+            corner[axis] = self.margin[axis] + free * fractions[axis];
+        }
+
+        let mut along = corner[main];
+        sizes
+            .iter()
+            .map(|item| {
+                let mut position = [0.0, 0.0];
+                position[main] = along;
+                position[cross] = corner[cross] + (size[cross] - item[cross]) * self.align.fraction();
+                along += item[main] + self.spacing;
+                position
+            })
+            .collect()
+    }
+
+    /// Move the visible `items` into place for a window of `window_size`
+    pub fn arrange(&self, items: Vec<&mut dyn LayoutItem>, window_size: [f32; 2]) {
+        let mut items: Vec<_> = items.into_iter().filter(|item| item.is_visible()).collect();
+        let sizes: Vec<_> = items.iter().map(|item| item.size()).collect();
+        for (item, [x, y]) in items.iter_mut().zip(self.positions(&sizes, window_size)) {
+            let [width, height] = item.size();
+            // Screen to local coordinates is the same flip as the reverse
+            let (x, y) = to_screen_coords(x, y, width, height, window_size[1], item.origin());
+            item.set_position(x, y);
+        }
+    }
+
+    /// Indices of the main and cross axis in [x, y]
+    fn axes(&self) -> (usize, usize) {
+        match self.axis {
+            Axis::Horizontal => (0, 1),
+            Axis::Vertical => (1, 0),
+        }
+    }
+}
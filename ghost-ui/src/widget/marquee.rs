@@ -0,0 +1,112 @@
+//! Marquee label - scrolls text that is too long for its width
+
+/// Horizontally scrolling text, measured in characters.
+///
+/// The label only computes what is visible; render `visible_text()` with a
+/// text renderer (e.g. a layer's text).
+#[derive(Debug, Clone)]
+pub struct MarqueeLabel {
+    text: String,
+    /// Visible width in characters
+    width: usize,
+    /// Scroll speed in characters per second
+    speed: f32,
+    /// Separator shown between repetitions
+    gap: String,
+    /// Current scroll offset in characters
+    offset: f32,
+    /// Cut long text off instead of scrolling it
+    reduced_motion: bool,
+}
+
+impl MarqueeLabel {
+    /// Create a new marquee label
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            width: 24,
+            speed: 6.0,
+            gap: "   ".to_string(),
+            offset: 0.0,
+            reduced_motion: false,
+        }
+    }
+
+    /// Set the visible width in characters
+    pub fn with_width(mut self, width: usize) -> Self {
+        self.width = width.max(1);
+        self
+    }
+
+    /// Set the scroll speed in characters per second
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    /// Set the separator shown between repetitions
+    pub fn with_gap(mut self, gap: impl Into<String>) -> Self {
+        self.gap = gap.into();
+        self
+    }
+
+    /// Cut long text off with an ellipsis instead of scrolling it
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    /// Get the full text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, restarting the scroll if it changed
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if text != self.text {
+            self.text = text;
+            self.offset = 0.0;
+        }
+    }
+
+    /// Whether the text is too long and scrolls
+    pub fn is_scrolling(&self) -> bool {
+        !self.reduced_motion && self.is_too_long()
+    }
+
+    fn is_too_long(&self) -> bool {
+        self.text.chars().count() > self.width
+    }
+
+    /// Advance the scroll, returns true if the visible text changed
+    pub fn update(&mut self, delta: f32) -> bool {
+        if !self.is_scrolling() {
+            return false;
+        }
+
+        let cycle = (self.text.chars().count() + self.gap.chars().count()) as f32;
+        let before = self.offset as usize;
+        self.offset = (self.offset + self.speed * delta) % cycle;
+        self.offset as usize != before
+    }
+
+    /// The currently visible window of text
+    pub fn visible_text(&self) -> String {
+        if !self.is_too_long() {
+            return self.text.clone();
+        }
+        if self.reduced_motion {
+            let cut: String = self.text.chars().take(self.width - 1).collect();
+            return format!("{}…", cut);
+        }
+
+        self.text
+            .chars()
+            .chain(self.gap.chars())
+            .cycle()
+            .skip(self.offset as usize)
+            .take(self.width)
+            .collect()
+    }
+}
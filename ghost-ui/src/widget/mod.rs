@@ -1,11 +1,34 @@
 //! Widget system for ghost-ui
 //!
-//! Provides interactive UI elements like buttons that can be placed
-//! relative to the ghost window using a bottom-left origin coordinate system.
+//! Provides UI elements like buttons (with tooltips), switches, checkboxes,
+//! sliders, progress bars and text inputs that can be placed relative to
+//! the ghost window using a bottom-left origin coordinate system, either
+//! directly or through layout containers anchored to the window. Widgets
+//! can fade, slide or scale in and out instead of appearing at once (or
+//! not, under reduced motion), and buttons can be focused and pressed with
+//! the keyboard.
 
+mod animation;
 mod button;
+mod focus;
+mod layout;
+mod marquee;
+mod progress;
+mod slider;
+mod text_input;
+mod toggle;
+mod tooltip;
 
-pub use button::{Button, ButtonId, ButtonState, ButtonStyle};
+pub use animation::WidgetAnimation;
+pub use button::{Button, ButtonId, ButtonImage, ButtonState, ButtonStyle};
+pub use focus::{FocusAction, FocusManager};
+pub use layout::{Align, Anchor, Axis, LayoutItem, Stack};
+pub use marquee::MarqueeLabel;
+pub use progress::{ProgressBar, ProgressBarStyle};
+pub use slider::{Slider, SliderId, SliderStyle};
+pub use text_input::{TextInput, TextInputAction, TextInputId, TextInputStyle};
+pub use toggle::{Checkbox, Toggle, ToggleId, ToggleKind, ToggleStyle, ToggleSwitch};
+pub use tooltip::{Tooltip, TooltipStyle};
 
 /// Coordinate origin for widget positioning
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
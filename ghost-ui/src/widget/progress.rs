@@ -0,0 +1,247 @@
+//! Progress bar widget for ghost-ui
+
+use super::animation::{Appearance, WidgetAnimation};
+use super::{to_screen_coords, LayoutItem, Origin};
+
+/// Seconds for the indeterminate segment to sweep across and back
+const SWEEP_SECONDS: f32 = 1.6;
+
+/// Share of the bar the indeterminate segment covers
+const SEGMENT_WIDTH: f32 = 0.3;
+
+/// Visual style for progress bars
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressBarStyle {
+    /// Color of the empty bar
+    pub track_color: [f32; 4],
+    /// Color of the filled part
+    pub fill_color: [f32; 4],
+    /// Corner radius
+    pub border_radius: f32,
+}
+
+impl Default for ProgressBarStyle {
+    fn default() -> Self {
+        Self {
+            track_color: [0.2, 0.2, 0.2, 0.9],
+            fill_color: [0.2, 0.5, 0.9, 0.95],
+            border_radius: 3.0,
+        }
+    }
+}
+
+/// A bar showing how far along a task is, or a segment sweeping back and
+/// forth while the progress is unknown (e.g. while thinking).
+///
+/// Call `update` every frame to animate the indeterminate mode.
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    /// Position in local coordinates (bottom-left origin by default)
+    position: [f32; 2],
+    /// Size [width, height]
+    size: [f32; 2],
+    /// Progress (0.0 - 1.0), None while unknown
+    progress: Option<f32>,
+    /// Indeterminate animation time in seconds
+    phase: f32,
+    style: ProgressBarStyle,
+    origin: Origin,
+    visible: bool,
+    appearance: Option<Appearance>,
+    /// Show a dimmed full bar instead of the sweeping segment
+    reduced_motion: bool,
+}
+
+impl ProgressBar {
+    /// Create an empty progress bar
+    pub fn new() -> Self {
+        Self {
+            position: [0.0, 0.0],
+            size: [120.0, 6.0],
+            progress: Some(0.0),
+            phase: 0.0,
+            style: ProgressBarStyle::default(),
+            origin: Origin::BottomLeft,
+            visible: true,
+            appearance: None,
+            reduced_motion: false,
+        }
+    }
+
+    /// Set the bar position (relative to origin)
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.position = [x, y];
+        self
+    }
+
+    /// Set the bar size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    /// Set the bar style
+    pub fn with_style(mut self, style: ProgressBarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.appearance = Some(Appearance::new(animation, self.visible));
+        self
+    }
+
+    /// Set the coordinate origin
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Show a dimmed full bar instead of sweeping while the progress is unknown
+    pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+        self.reduced_motion = reduced_motion;
+        self
+    }
+
+    /// Get the progress (0.0 - 1.0), None while unknown
+    pub fn progress(&self) -> Option<f32> {
+        self.progress
+    }
+
+    /// Set the progress (clamped to 0.0 - 1.0)
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = Some(progress.clamp(0.0, 1.0));
+    }
+
+    /// Show that work is going on without knowing how far along it is
+    pub fn set_indeterminate(&mut self) {
+        if self.progress.is_some() {
+            self.progress = None;
+            self.phase = 0.0;
+        }
+    }
+
+    /// Whether the progress is unknown
+    pub fn is_indeterminate(&self) -> bool {
+        self.progress.is_none()
+    }
+
+    /// Get the style
+    pub fn style(&self) -> &ProgressBarStyle {
+        &self.style
+    }
+
+    /// Check if visible
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Set visibility, animated if the bar has an animation
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        if let Some(ref mut appearance) = self.appearance {
+            appearance.set_shown(visible);
+        }
+    }
+
+    /// Get position in local coordinates
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// Set position
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+
+    /// Get size
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    /// Advance the indeterminate and show or hide animations, returns true
+    /// if either moved
+    pub fn update(&mut self, delta: f32) -> bool {
+        let appearing = self.appearance.as_mut().is_some_and(|appearance| appearance.update(delta));
+        if self.progress.is_some() || self.reduced_motion || !self.visible {
+            return appearing;
+        }
+        self.phase = (self.phase + delta) % SWEEP_SECONDS;
+        true
+    }
+
+    /// Whether any of the bar is drawn, including while it fades out
+    pub fn is_drawn(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => appearance.is_drawn(),
+            None => self.visible,
+        }
+    }
+
+    /// Opacity multiplier of the show or hide animation
+    pub fn opacity(&self) -> f32 {
+        self.appearance.as_ref().map_or(1.0, Appearance::opacity)
+    }
+
+    /// Bounds to draw at [x, y, width, height], moved and scaled by the
+    /// show or hide animation
+    pub fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        let bounds = self.screen_bounds(window_height);
+        match self.appearance {
+            Some(ref appearance) => appearance.transform(bounds),
+            None => bounds,
+        }
+    }
+
+    /// The filled part as [start, end] fractions of the bar, and its opacity
+    pub fn fill(&self) -> ([f32; 2], f32) {
+        match self.progress {
+            Some(progress) => ([0.0, progress], 1.0),
+            None if self.reduced_motion => ([0.0, 1.0], 0.5),
+            None => {
+                // Ease back and forth across the bar
+                let t = self.phase / SWEEP_SECONDS * std::f32::consts::TAU;
+                let start = (1.0 - SEGMENT_WIDTH) * (0.5 - 0.5 * t.cos());
+                ([start, start + SEGMENT_WIDTH], 1.0)
+            }
+        }
+    }
+
+    /// Get the bar bounds in screen coordinates [x, y, width, height]
+    pub fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        let (x, y) = to_screen_coords(
+            self.position[0],
+            self.position[1],
+            self.size[0],
+            self.size[1],
+            window_height,
+            self.origin,
+        );
+        [x, y, self.size[0], self.size[1]]
+    }
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayoutItem for ProgressBar {
+    fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+}
@@ -0,0 +1,343 @@
+//! Slider widget for ghost-ui
+
+use super::animation::{Appearance, WidgetAnimation};
+use super::{to_screen_coords, ButtonState, LayoutItem, Origin, Widget};
+
+/// Unique identifier for a slider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SliderId(pub u32);
+
+impl SliderId {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+/// Visual style for sliders
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliderStyle {
+    /// Track color right of the knob
+    pub track_color: [f32; 4],
+    /// Track color left of the knob
+    pub fill_color: [f32; 4],
+    /// Knob color
+    pub knob_color: [f32; 4],
+    /// Track thickness in pixels (the knob is as tall as the slider)
+    pub track_height: f32,
+    /// How much lighter the filled track gets when hovered, and darker
+    /// when dragged (0.0 - 1.0)
+    pub highlight: f32,
+}
+
+impl Default for SliderStyle {
+    fn default() -> Self {
+        Self {
+            track_color: [0.2, 0.2, 0.2, 0.9],
+            fill_color: [0.2, 0.5, 0.9, 0.95],
+            knob_color: [1.0, 1.0, 1.0, 1.0],
+            track_height: 4.0,
+            highlight: 0.12,
+        }
+    }
+}
+
+impl SliderStyle {
+    /// Filled track color for the visual state
+    pub fn fill(&self, state: ButtonState) -> [f32; 4] {
+        let [r, g, b, a] = self.fill_color;
+        let shade = |c: f32| match state {
+            ButtonState::Normal | ButtonState::Disabled => c,
+            ButtonState::Hover => c + (1.0 - c) * self.highlight,
+            ButtonState::Pressed => c * (1.0 - self.highlight),
+        };
+        [shade(r), shade(g), shade(b), a]
+    }
+}
+
+/// A horizontal slider picking a value between `min` and `max` by dragging
+/// its knob; changes are reported as `GhostEvent::SliderChanged`
+#[derive(Debug, Clone)]
+pub struct Slider {
+    id: SliderId,
+    /// Position in local coordinates (bottom-left origin by default)
+    position: [f32; 2],
+    /// Size [width, height]
+    size: [f32; 2],
+    min: f32,
+    max: f32,
+    /// Values snap to multiples of this above `min` (0 = no snapping)
+    step: f32,
+    value: f32,
+    state: ButtonState,
+    style: SliderStyle,
+    origin: Origin,
+    visible: bool,
+    appearance: Option<Appearance>,
+    /// Text shown for a value
+    label_format: fn(f32) -> String,
+}
+
+impl Slider {
+    /// Create a new slider over `min..=max`
+    pub fn new(id: SliderId, min: f32, max: f32, value: f32) -> Self {
+        let mut slider = Self {
+            id,
+            position: [0.0, 0.0],
+            size: [120.0, 18.0],
+            min,
+            max: max.max(min),
+            step: 0.0,
+            value: min,
+            state: ButtonState::Normal,
+            style: SliderStyle::default(),
+            origin: Origin::BottomLeft,
+            visible: true,
+            appearance: None,
+            label_format: |value| format!("{}", (value * 100.0).round() / 100.0),
+        };
+        slider.set_value(value);
+        slider
+    }
+
+    /// Snap values to multiples of `step` above the minimum
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step.max(0.0);
+        self.set_value(self.value);
+        self
+    }
+
+    /// Set the slider position (relative to origin)
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.position = [x, y];
+        self
+    }
+
+    /// Set the slider size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    /// Set the slider style
+    pub fn with_style(mut self, style: SliderStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.appearance = Some(Appearance::new(animation, self.visible));
+        self
+    }
+
+    /// Set the coordinate origin
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set how `value_label` shows a value (e.g. as a percentage)
+    pub fn with_label_format(mut self, format: fn(f32) -> String) -> Self {
+        self.label_format = format;
+        self
+    }
+
+    /// Get the slider ID
+    pub fn id(&self) -> SliderId {
+        self.id
+    }
+
+    /// Get the value
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Get the smallest value
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// Get the largest value
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// Get the step values snap to (0 = no snapping)
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Set the value (clamped and snapped) without reporting a change
+    pub fn set_value(&mut self, value: f32) {
+        let mut value = value.clamp(self.min, self.max);
+        if self.step > 0.0 {
+            value = (self.min + ((value - self.min) / self.step).round() * self.step).min(self.max);
+        }
+        self.value = value;
+    }
+
+    /// Where the value lies between min (0.0) and max (1.0)
+    pub fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+
+    /// The value as text, for a label next to the slider
+    pub fn value_label(&self) -> String {
+        (self.label_format)(self.value)
+    }
+
+    /// Get the current state
+    pub fn state(&self) -> ButtonState {
+        self.state
+    }
+
+    /// Get the style
+    pub fn style(&self) -> &SliderStyle {
+        &self.style
+    }
+
+    /// Check if visible
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Set visibility, animated if the slider has an animation
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        if let Some(ref mut appearance) = self.appearance {
+            appearance.set_shown(visible);
+        }
+    }
+
+    /// Advance the show or hide animation, returns true while it runs
+    pub fn update(&mut self, delta: f32) -> bool {
+        self.appearance.as_mut().is_some_and(|appearance| appearance.update(delta))
+    }
+
+    /// Whether any of the slider is drawn, including while it fades out
+    pub fn is_drawn(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => appearance.is_drawn(),
+            None => self.visible,
+        }
+    }
+
+    /// Opacity multiplier of the show or hide animation
+    pub fn opacity(&self) -> f32 {
+        self.appearance.as_ref().map_or(1.0, Appearance::opacity)
+    }
+
+    /// Bounds to draw at [x, y, width, height], moved and scaled by the
+    /// show or hide animation
+    pub fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        let bounds = self.screen_bounds(window_height);
+        match self.appearance {
+            Some(ref appearance) => appearance.transform(bounds),
+            None => bounds,
+        }
+    }
+
+    /// Get position in local coordinates
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// Set position
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+
+    /// Get size
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    /// Move the value to the cursor while the knob is dragged; returns
+    /// whether it changed
+    pub fn drag(&mut self, cursor_x: f32, window_height: f32) -> bool {
+        if self.state != ButtonState::Pressed {
+            return false;
+        }
+        let [x, _, width, height] = self.screen_bounds(window_height);
+        // The knob center stays within the track
+        let radius = height / 2.0;
+        let travel = (width - height).max(1.0);
+        let fraction = ((cursor_x - x - radius) / travel).clamp(0.0, 1.0);
+        let before = self.value;
+        self.set_value(self.min + fraction * (self.max - self.min));
+        self.value != before
+    }
+
+    /// Check if a point (in screen coordinates) is inside the slider
+    fn contains_point(&self, screen_x: f32, screen_y: f32, window_height: f32) -> bool {
+        let [x, y, width, height] = self.screen_bounds(window_height);
+        self.visible && screen_x >= x && screen_x <= x + width && screen_y >= y && screen_y <= y + height
+    }
+}
+
+impl Widget for Slider {
+    fn update_hover(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) {
+        if !self.visible {
+            return;
+        }
+        self.state = match self.state {
+            ButtonState::Pressed => ButtonState::Pressed, // Keep dragging until release
+            _ if self.contains_point(cursor_x, cursor_y, window_height) => ButtonState::Hover,
+            _ => ButtonState::Normal,
+        };
+    }
+
+    /// Start dragging, moving the knob to the cursor
+    fn handle_press(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        if !self.contains_point(cursor_x, cursor_y, window_height) {
+            return false;
+        }
+        self.state = ButtonState::Pressed;
+        self.drag(cursor_x, window_height);
+        true
+    }
+
+    fn handle_release(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let was_pressed = self.state == ButtonState::Pressed;
+        let is_inside = self.contains_point(cursor_x, cursor_y, window_height);
+        self.state = if is_inside { ButtonState::Hover } else { ButtonState::Normal };
+        was_pressed
+    }
+
+    fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        let (x, y) = to_screen_coords(
+            self.position[0],
+            self.position[1],
+            self.size[0],
+            self.size[1],
+            window_height,
+            self.origin,
+        );
+        [x, y, self.size[0], self.size[1]]
+    }
+}
+
+impl LayoutItem for Slider {
+    fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+}
@@ -0,0 +1,655 @@
+//! Single-line text input widget for ghost-ui
+
+use std::ops::Range;
+
+use tao::event::KeyEvent;
+use tao::keyboard::{Key, KeyCode, ModifiersState};
+
+use super::animation::{Appearance, WidgetAnimation};
+use super::{to_screen_coords, ButtonState, LayoutItem, Origin, Widget};
+
+/// Seconds the caret stays shown, then hidden
+const CARET_BLINK: f32 = 0.53;
+
+/// Caret width in pixels
+const CARET_WIDTH: f32 = 1.5;
+
+/// Unique identifier for a text input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextInputId(pub u32);
+
+impl TextInputId {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+/// Visual style for text inputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextInputStyle {
+    pub background: [f32; 4],
+    pub border_color: [f32; 4],
+    /// Border color while typing into the input
+    pub focused_border_color: [f32; 4],
+    pub border_width: f32,
+    pub border_radius: f32,
+    pub text_color: [f32; 4],
+    /// Color of the placeholder shown while empty
+    pub placeholder_color: [f32; 4],
+    /// Background of selected text
+    pub selection_color: [f32; 4],
+    pub caret_color: [f32; 4],
+    pub font_size: f32,
+    /// Space between the border and the text
+    pub padding: f32,
+}
+
+impl Default for TextInputStyle {
+    fn default() -> Self {
+        Self {
+            background: [0.12, 0.12, 0.12, 0.92],
+            border_color: [0.4, 0.4, 0.4, 0.95],
+            focused_border_color: [0.3, 0.6, 1.0, 1.0],
+            border_width: 1.5,
+            border_radius: 6.0,
+            text_color: [1.0, 1.0, 1.0, 1.0],
+            placeholder_color: [0.6, 0.6, 0.6, 1.0],
+            selection_color: [0.3, 0.5, 0.9, 0.6],
+            caret_color: [1.0, 1.0, 1.0, 1.0],
+            font_size: 14.0,
+            padding: 6.0,
+        }
+    }
+}
+
+/// What a key press did to a text input
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextInputAction {
+    /// The text was edited
+    Changed,
+    /// Enter was pressed; the input was cleared and this is what it held
+    Submitted(String),
+}
+
+/// A single-line text field with a caret and selection.
+///
+/// Clicking focuses it; while focused it takes the keyboard, reporting edits
+/// as `GhostEvent::TextChanged` and Enter as `GhostEvent::TextSubmitted`.
+/// Escape or clicking elsewhere gives the keyboard back.
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    id: TextInputId,
+    /// Position in local coordinates (bottom-left origin by default)
+    position: [f32; 2],
+    /// Size [width, height]
+    size: [f32; 2],
+    origin: Origin,
+    visible: bool,
+    appearance: Option<Appearance>,
+    style: TextInputStyle,
+    text: String,
+    /// Text shown while empty
+    placeholder: String,
+    /// Longest text in characters
+    max_chars: Option<usize>,
+    /// Caret position (byte index)
+    caret: usize,
+    /// Other end of the selection (byte index, equal to `caret` when
+    /// nothing is selected)
+    anchor: usize,
+    focused: bool,
+    /// The mouse is selecting text
+    selecting: bool,
+    state: ButtonState,
+    /// Caret blink time in seconds
+    blink: f32,
+    /// How far the text is scrolled left to keep the caret in view
+    scroll: f32,
+    /// Left edge of each character boundary (byte index, x), measured by
+    /// the renderer
+    stops: Vec<(usize, f32)>,
+}
+
+impl TextInput {
+    /// Create a new, empty text input
+    pub fn new(id: TextInputId) -> Self {
+        Self {
+            id,
+            position: [0.0, 0.0],
+            size: [200.0, 28.0],
+            origin: Origin::BottomLeft,
+            visible: true,
+            appearance: None,
+            style: TextInputStyle::default(),
+            text: String::new(),
+            placeholder: String::new(),
+            max_chars: None,
+            caret: 0,
+            anchor: 0,
+            focused: false,
+            selecting: false,
+            state: ButtonState::Normal,
+            blink: 0.0,
+            scroll: 0.0,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Set the input position (relative to origin)
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.position = [x, y];
+        self
+    }
+
+    /// Set the input size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    /// Set the input style
+    pub fn with_style(mut self, style: TextInputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.appearance = Some(Appearance::new(animation, self.visible));
+        self
+    }
+
+    /// Set the coordinate origin
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set the text shown while empty
+    pub fn with_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    /// Limit the text to `max_chars` characters
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    /// Get the input ID
+    pub fn id(&self) -> TextInputId {
+        self.id
+    }
+
+    /// Get the text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, with the caret at its end
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into().chars().filter(|c| !c.is_control()).collect();
+        self.caret = self.text.len();
+        self.anchor = self.caret;
+    }
+
+    /// Clear the input, returning its text
+    pub fn take_text(&mut self) -> String {
+        self.caret = 0;
+        self.anchor = 0;
+        self.scroll = 0.0;
+        std::mem::take(&mut self.text)
+    }
+
+    /// Get the placeholder
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+
+    /// Whether the input takes the keyboard
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Give the input the keyboard (or take it away)
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.selecting &= focused;
+        self.blink = 0.0;
+    }
+
+    /// Caret position (byte index into the text)
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    /// Selected byte range of the text (empty when nothing is selected)
+    pub fn selection(&self) -> Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    /// Get the selected text
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.caret = self.text.len();
+    }
+
+    /// Get the current state
+    pub fn state(&self) -> ButtonState {
+        self.state
+    }
+
+    /// Get the style
+    pub fn style(&self) -> &TextInputStyle {
+        &self.style
+    }
+
+    /// Check if visible
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Set visibility, animated if the input has an animation (a hidden
+    /// input gives up the keyboard)
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        if let Some(ref mut appearance) = self.appearance {
+            appearance.set_shown(visible);
+        }
+        if !visible {
+            self.set_focused(false);
+        }
+    }
+
+    /// Whether any of the input is drawn, including while it fades out
+    pub fn is_drawn(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => appearance.is_drawn(),
+            None => self.visible,
+        }
+    }
+
+    /// Opacity multiplier of the show or hide animation
+    pub fn opacity(&self) -> f32 {
+        self.appearance.as_ref().map_or(1.0, Appearance::opacity)
+    }
+
+    /// Bounds to draw at [x, y, width, height], moved and scaled by the
+    /// show or hide animation
+    pub fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        let bounds = self.screen_bounds(window_height);
+        match self.appearance {
+            Some(ref appearance) => appearance.transform(bounds),
+            None => bounds,
+        }
+    }
+
+    /// Whether the input is done animating in; its text, caret and
+    /// selection only show then
+    pub fn is_fully_shown(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => self.visible && appearance.is_fully_shown(),
+            None => self.visible,
+        }
+    }
+
+    /// Get position in local coordinates
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// Set position
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+
+    /// Get size
+    pub fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    /// Type `text` over the selection (control characters are dropped);
+    /// returns whether the text changed
+    pub fn insert(&mut self, text: &str) -> bool {
+        let range = self.selection();
+        let mut text: String = text.chars().filter(|c| !c.is_control()).collect();
+        if let Some(max_chars) = self.max_chars {
+            let kept = self.text.chars().count() - self.text[range.clone()].chars().count();
+            text = text.chars().take(max_chars.saturating_sub(kept)).collect();
+        }
+        if text.is_empty() && range.is_empty() {
+            return false;
+        }
+        self.text.replace_range(range.clone(), &text);
+        self.caret = range.start + text.len();
+        self.anchor = self.caret;
+        self.blink = 0.0;
+        true
+    }
+
+    /// Delete the selection or the character before the caret
+    pub fn backspace(&mut self) -> bool {
+        if self.selection().is_empty() {
+            self.anchor = self.prev_boundary(self.caret);
+        }
+        self.insert("")
+    }
+
+    /// Delete the selection or the character after the caret
+    pub fn delete(&mut self) -> bool {
+        if self.selection().is_empty() {
+            self.anchor = self.next_boundary(self.caret);
+        }
+        self.insert("")
+    }
+
+    /// Move the caret to the byte index `to`, selecting from where it was
+    /// when `extend` is set
+    pub fn move_caret(&mut self, to: usize, extend: bool) {
+        let mut to = to.min(self.text.len());
+        while !self.text.is_char_boundary(to) {
+            to -= 1;
+        }
+        self.caret = to;
+        if !extend {
+            self.anchor = to;
+        }
+        self.blink = 0.0;
+    }
+
+    /// Handle a key press while focused
+    pub fn handle_key(&mut self, event: &KeyEvent, modifiers: ModifiersState) -> Option<TextInputAction> {
+        let command = modifiers.control_key() || modifiers.super_key();
+        let shift = modifiers.shift_key();
+        let changed = match event.logical_key {
+            Key::Enter => {
+                if self.text.is_empty() {
+                    return None;
+                }
+                return Some(TextInputAction::Submitted(self.take_text()));
+            }
+            Key::Escape => {
+                self.set_focused(false);
+                return None;
+            }
+            Key::Backspace => self.backspace(),
+            Key::Delete => self.delete(),
+            Key::ArrowLeft => {
+                let selection = self.selection();
+                let to = if selection.is_empty() || shift { self.prev_boundary(self.caret) } else { selection.start };
+                self.move_caret(to, shift);
+                false
+            }
+            Key::ArrowRight => {
+                let selection = self.selection();
+                let to = if selection.is_empty() || shift { self.next_boundary(self.caret) } else { selection.end };
+                self.move_caret(to, shift);
+                false
+            }
+            Key::Home => {
+                self.move_caret(0, shift);
+                false
+            }
+            Key::End => {
+                self.move_caret(self.text.len(), shift);
+                false
+            }
+            _ if command && event.physical_key == KeyCode::KeyA => {
+                self.select_all();
+                false
+            }
+            _ if command => false,
+            _ => event.text.is_some_and(|text| self.insert(text)),
+        };
+        changed.then_some(TextInputAction::Changed)
+    }
+
+    /// Blink the caret and advance the show or hide animation, returns
+    /// true if the caret appeared or disappeared or the input moved
+    pub fn update(&mut self, delta: f32) -> bool {
+        let appearing = self.appearance.as_mut().is_some_and(|appearance| appearance.update(delta));
+        if !self.focused {
+            return appearing;
+        }
+        let shown = self.caret_shown();
+        self.blink = (self.blink + delta) % (CARET_BLINK * 2.0);
+        appearing || shown != self.caret_shown()
+    }
+
+    fn caret_shown(&self) -> bool {
+        self.blink < CARET_BLINK
+    }
+
+    fn prev_boundary(&self, index: usize) -> usize {
+        self.text[..index].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self, index: usize) -> usize {
+        self.text[index..].chars().next().map_or(index, |c| index + c.len_utf8())
+    }
+
+    /// Take the measured character boundaries of the text and scroll the
+    /// caret into view
+    pub(crate) fn set_stops(&mut self, stops: Vec<(usize, f32)>) {
+        self.stops = stops;
+        let inner_width = self.inner_size()[0];
+        let caret_x = self.x_of(self.caret);
+        if caret_x - self.scroll > inner_width {
+            self.scroll = caret_x - inner_width;
+        }
+        self.scroll = self.scroll.min(caret_x);
+        let text_width = self.stops.last().map_or(0.0, |(_, x)| *x);
+        self.scroll = self.scroll.min(text_width - inner_width).max(0.0);
+    }
+
+    /// Offset of a byte index from the start of the text
+    fn x_of(&self, index: usize) -> f32 {
+        self.stops.iter().rev().find(|(i, _)| *i <= index).map_or(0.0, |(_, x)| *x)
+    }
+
+    /// Byte index of the character boundary closest to `x` (from the start
+    /// of the text)
+    fn index_at(&self, x: f32) -> usize {
+        self.stops
+            .iter()
+            .min_by(|a, b| (a.1 - x).abs().total_cmp(&(b.1 - x).abs()))
+            .map_or(0, |(i, _)| *i)
+    }
+
+    fn inner_size(&self) -> [f32; 2] {
+        let padding = self.style.padding + self.style.border_width;
+        [(self.size[0] - padding * 2.0).max(0.0), (self.size[1] - padding * 2.0).max(0.0)]
+    }
+
+    /// Area the text is drawn and clipped to, in screen coordinates
+    pub(crate) fn inner_bounds(&self, window_height: f32) -> [f32; 4] {
+        let [x, y, ..] = self.screen_bounds(window_height);
+        let padding = self.style.padding + self.style.border_width;
+        let [width, height] = self.inner_size();
+        [x + padding, y + padding, width, height]
+    }
+
+    /// Left edge of the text in screen coordinates (scrolled)
+    pub(crate) fn text_left(&self, window_height: f32) -> f32 {
+        self.inner_bounds(window_height)[0] - self.scroll
+    }
+
+    fn line_height(&self) -> f32 {
+        self.style.font_size * 1.2
+    }
+
+    /// Top of the text line in screen coordinates
+    pub(crate) fn text_top(&self, window_height: f32) -> f32 {
+        let [_, y, _, height] = self.inner_bounds(window_height);
+        y + (height - self.line_height()) / 2.0
+    }
+
+    /// Caret rectangle in screen coordinates, while it blinks on
+    pub(crate) fn caret_rect(&self, window_height: f32) -> Option<[f32; 4]> {
+        if !self.focused || !self.caret_shown() {
+            return None;
+        }
+        let x = self.text_left(window_height) + self.x_of(self.caret);
+        Some([x, self.text_top(window_height), CARET_WIDTH, self.line_height()])
+    }
+
+    /// Selection rectangle in screen coordinates, clipped to the text area
+    pub(crate) fn selection_rect(&self, window_height: f32) -> Option<[f32; 4]> {
+        let selection = self.selection();
+        if selection.is_empty() {
+            return None;
+        }
+        let [inner_x, _, inner_width, _] = self.inner_bounds(window_height);
+        let left = (self.text_left(window_height) + self.x_of(selection.start)).max(inner_x);
+        let right = (self.text_left(window_height) + self.x_of(selection.end)).min(inner_x + inner_width);
+        (right > left).then(|| [left, self.text_top(window_height), right - left, self.line_height()])
+    }
+
+    /// Where the input method's candidate window goes (below the caret),
+    /// while focused
+    pub fn ime_position(&self, window_height: f32) -> Option<(f32, f32)> {
+        if !self.focused || !self.visible {
+            return None;
+        }
+        let x = self.text_left(window_height) + self.x_of(self.caret);
+        Some((x, self.text_top(window_height) + self.line_height()))
+    }
+
+    /// Check if a point (in screen coordinates) is inside the input
+    fn contains_point(&self, screen_x: f32, screen_y: f32, window_height: f32) -> bool {
+        let [x, y, width, height] = self.screen_bounds(window_height);
+        self.visible && screen_x >= x && screen_x <= x + width && screen_y >= y && screen_y <= y + height
+    }
+}
+
+impl Widget for TextInput {
+    /// Also extends the selection while the mouse button is held
+    fn update_hover(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) {
+        if !self.visible {
+            return;
+        }
+        if self.selecting {
+            let index = self.index_at(cursor_x - self.text_left(window_height));
+            self.move_caret(index, true);
+        }
+        self.state = if self.contains_point(cursor_x, cursor_y, window_height) {
+            ButtonState::Hover
+        } else {
+            ButtonState::Normal
+        };
+    }
+
+    /// Focus the input and put the caret at the cursor if it is inside,
+    /// otherwise give up the keyboard
+    fn handle_press(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        if !self.contains_point(cursor_x, cursor_y, window_height) {
+            self.set_focused(false);
+            return false;
+        }
+        self.set_focused(true);
+        self.selecting = true;
+        let index = self.index_at(cursor_x - self.text_left(window_height));
+        self.move_caret(index, false);
+        true
+    }
+
+    fn handle_release(&mut self, _cursor_x: f32, _cursor_y: f32, _window_height: f32) -> bool {
+        std::mem::take(&mut self.selecting)
+    }
+
+    fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        let (x, y) = to_screen_coords(
+            self.position[0],
+            self.position[1],
+            self.size[0],
+            self.size[1],
+            window_height,
+            self.origin,
+        );
+        [x, y, self.size[0], self.size[1]]
+    }
+}
+
+impl LayoutItem for TextInput {
+    fn size(&self) -> [f32; 2] {
+        self.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.position = [x, y];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editing() {
+        let mut input = TextInput::new(TextInputId::new(1));
+        assert!(input.insert("héllo"));
+        assert_eq!(input.caret(), input.text().len());
+
+        // Backspace removes whole characters
+        input.move_caret(3, false);
+        assert!(input.backspace());
+        assert_eq!(input.text(), "hllo");
+        assert!(input.delete());
+        assert_eq!(input.text(), "hlo");
+        input.move_caret(0, false);
+        assert!(!input.backspace());
+        assert!(!input.insert("\n"));
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut input = TextInput::new(TextInputId::new(1));
+        input.set_text("hello world");
+        input.move_caret(5, true);
+        assert_eq!(input.selected_text(), " world");
+        assert!(input.insert("!"));
+        assert_eq!(input.text(), "hello!");
+
+        input.select_all();
+        assert!(input.backspace());
+        assert_eq!(input.text(), "");
+    }
+
+    #[test]
+    fn test_max_chars() {
+        let mut input = TextInput::new(TextInputId::new(1)).with_max_chars(5);
+        input.insert("abc");
+        input.insert("défgh");
+        assert_eq!(input.text(), "abcdé");
+        assert!(!input.insert("x"));
+
+        // Typing over a selection frees its characters
+        input.move_caret(3, true);
+        assert!(input.insert("xyz"));
+        assert_eq!(input.text(), "abcxy");
+    }
+
+    #[test]
+    fn test_move_caret_stays_on_char_boundary() {
+        let mut input = TextInput::new(TextInputId::new(1));
+        input.set_text("é");
+        input.move_caret(1, false);
+        assert_eq!(input.caret(), 0);
+        assert_eq!(input.take_text(), "é");
+        assert_eq!(input.caret(), 0);
+    }
+}
@@ -0,0 +1,504 @@
+//! Toggle switch and checkbox widgets for ghost-ui
+
+use super::animation::{Appearance, WidgetAnimation};
+use super::{to_screen_coords, ButtonState, LayoutItem, Origin, Widget};
+
+/// Unique identifier for a toggle switch or checkbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ToggleId(pub u32);
+
+impl ToggleId {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+/// Shape a toggle is drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToggleKind {
+    /// A pill-shaped track with a knob that slides to the right when on
+    Switch,
+    /// A box with a checkmark when on
+    Checkbox,
+}
+
+/// Visual style for toggle switches and checkboxes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToggleStyle {
+    /// Track or box color when on
+    pub on_color: [f32; 4],
+    /// Track or box color when off
+    pub off_color: [f32; 4],
+    /// Knob and checkmark color
+    pub mark_color: [f32; 4],
+    /// Border color of an unchecked checkbox
+    pub border_color: [f32; 4],
+    /// Border width of an unchecked checkbox
+    pub border_width: f32,
+    /// Checkbox corner radius (switches are always round)
+    pub border_radius: f32,
+    /// How much lighter the widget gets when hovered, and darker when
+    /// pressed (0.0 - 1.0)
+    pub highlight: f32,
+}
+
+impl Default for ToggleStyle {
+    fn default() -> Self {
+        Self {
+            on_color: [0.2, 0.5, 0.9, 0.95],
+            off_color: [0.2, 0.2, 0.2, 0.9],
+            mark_color: [1.0, 1.0, 1.0, 1.0],
+            border_color: [0.6, 0.6, 0.6, 0.95],
+            border_width: 1.5,
+            border_radius: 3.0,
+            highlight: 0.12,
+        }
+    }
+}
+
+impl ToggleStyle {
+    /// Track or box color for the value and visual state
+    pub fn fill(&self, on: bool, state: ButtonState) -> [f32; 4] {
+        let [r, g, b, a] = if on { self.on_color } else { self.off_color };
+        let shade = |c: f32| match state {
+            ButtonState::Normal | ButtonState::Disabled => c,
+            ButtonState::Hover => c + (1.0 - c) * self.highlight,
+            ButtonState::Pressed => c * (1.0 - self.highlight),
+        };
+        [shade(r), shade(g), shade(b), a]
+    }
+}
+
+/// A widget with an on/off value that flips when clicked, reported as
+/// `GhostEvent::ToggleChanged`
+pub trait Toggle: Widget {
+    fn id(&self) -> ToggleId;
+
+    fn kind(&self) -> ToggleKind;
+
+    fn is_on(&self) -> bool;
+
+    /// Set the value without reporting a change
+    fn set_on(&mut self, on: bool);
+
+    fn state(&self) -> ButtonState;
+
+    fn style(&self) -> &ToggleStyle;
+
+    fn is_visible(&self) -> bool;
+
+    /// Advance the show or hide animation, returns true while it runs
+    fn update(&mut self, delta: f32) -> bool;
+
+    /// Whether any of the toggle is drawn, including while it fades out
+    fn is_drawn(&self) -> bool;
+
+    /// Opacity multiplier of the show or hide animation
+    fn opacity(&self) -> f32;
+
+    /// Bounds to draw at, moved and scaled by the show or hide animation
+    fn drawn_bounds(&self, window_height: f32) -> [f32; 4];
+}
+
+/// State and hit testing shared by the toggle widgets
+#[derive(Debug, Clone)]
+struct ToggleCore {
+    id: ToggleId,
+    /// Position in local coordinates (bottom-left origin by default)
+    position: [f32; 2],
+    /// Size [width, height]
+    size: [f32; 2],
+    on: bool,
+    state: ButtonState,
+    style: ToggleStyle,
+    origin: Origin,
+    visible: bool,
+    appearance: Option<Appearance>,
+}
+
+impl ToggleCore {
+    fn new(id: ToggleId, on: bool, size: [f32; 2]) -> Self {
+        Self {
+            id,
+            position: [0.0, 0.0],
+            size,
+            on,
+            state: ButtonState::Normal,
+            style: ToggleStyle::default(),
+            origin: Origin::BottomLeft,
+            visible: true,
+            appearance: None,
+        }
+    }
+
+    fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+        if let Some(ref mut appearance) = self.appearance {
+            appearance.set_shown(visible);
+        }
+    }
+
+    fn update(&mut self, delta: f32) -> bool {
+        self.appearance.as_mut().is_some_and(|appearance| appearance.update(delta))
+    }
+
+    fn is_drawn(&self) -> bool {
+        match self.appearance {
+            Some(ref appearance) => appearance.is_drawn(),
+            None => self.visible,
+        }
+    }
+
+    fn opacity(&self) -> f32 {
+        self.appearance.as_ref().map_or(1.0, Appearance::opacity)
+    }
+
+    fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        let bounds = self.screen_bounds(window_height);
+        match self.appearance {
+            Some(ref appearance) => appearance.transform(bounds),
+            None => bounds,
+        }
+    }
+
+    fn contains_point(&self, screen_x: f32, screen_y: f32, window_height: f32) -> bool {
+        let [x, y, width, height] = self.screen_bounds(window_height);
+        self.visible && screen_x >= x && screen_x <= x + width && screen_y >= y && screen_y <= y + height
+    }
+
+    fn update_hover(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) {
+        if !self.visible {
+            return;
+        }
+        self.state = match self.state {
+            ButtonState::Pressed => ButtonState::Pressed, // Keep pressed until release
+            _ if self.contains_point(cursor_x, cursor_y, window_height) => ButtonState::Hover,
+            _ => ButtonState::Normal,
+        };
+    }
+
+    fn handle_press(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        if self.contains_point(cursor_x, cursor_y, window_height) {
+            self.state = ButtonState::Pressed;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Flip the value if the click completed inside
+    fn handle_release(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let was_pressed = self.state == ButtonState::Pressed;
+        let is_inside = self.contains_point(cursor_x, cursor_y, window_height);
+        self.state = if is_inside { ButtonState::Hover } else { ButtonState::Normal };
+        if was_pressed && is_inside {
+            self.on = !self.on;
+        }
+        was_pressed && is_inside
+    }
+
+    fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        let (x, y) = to_screen_coords(
+            self.position[0],
+            self.position[1],
+            self.size[0],
+            self.size[1],
+            window_height,
+            self.origin,
+        );
+        [x, y, self.size[0], self.size[1]]
+    }
+}
+
+/// An on/off switch: a pill-shaped track with a sliding knob
+#[derive(Debug, Clone)]
+pub struct ToggleSwitch {
+    core: ToggleCore,
+}
+
+impl ToggleSwitch {
+    /// Create a new switch
+    pub fn new(id: ToggleId, on: bool) -> Self {
+        Self { core: ToggleCore::new(id, on, [40.0, 22.0]) }
+    }
+
+    /// Set the switch position (relative to origin)
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.core.position = [x, y];
+        self
+    }
+
+    /// Set the switch size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.core.size = [width, height];
+        self
+    }
+
+    /// Set the switch style
+    pub fn with_style(mut self, style: ToggleStyle) -> Self {
+        self.core.style = style;
+        self
+    }
+
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.core.appearance = Some(Appearance::new(animation, self.core.visible));
+        self
+    }
+
+    /// Set the coordinate origin
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.core.origin = origin;
+        self
+    }
+
+    /// Set visibility, animated if the toggle has an animation
+    pub fn set_visible(&mut self, visible: bool) {
+        self.core.set_visible(visible);
+    }
+
+    /// Get position in local coordinates
+    pub fn position(&self) -> [f32; 2] {
+        self.core.position
+    }
+
+    /// Set position
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.core.position = [x, y];
+    }
+
+    /// Get size
+    pub fn size(&self) -> [f32; 2] {
+        self.core.size
+    }
+}
+
+impl Widget for ToggleSwitch {
+    fn update_hover(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) {
+        self.core.update_hover(cursor_x, cursor_y, window_height);
+    }
+
+    fn handle_press(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        self.core.handle_press(cursor_x, cursor_y, window_height)
+    }
+
+    fn handle_release(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        self.core.handle_release(cursor_x, cursor_y, window_height)
+    }
+
+    fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        self.core.screen_bounds(window_height)
+    }
+}
+
+impl Toggle for ToggleSwitch {
+    fn id(&self) -> ToggleId {
+        self.core.id
+    }
+
+    fn kind(&self) -> ToggleKind {
+        ToggleKind::Switch
+    }
+
+    fn is_on(&self) -> bool {
+        self.core.on
+    }
+
+    fn set_on(&mut self, on: bool) {
+        self.core.on = on;
+    }
+
+    fn state(&self) -> ButtonState {
+        self.core.state
+    }
+
+    fn style(&self) -> &ToggleStyle {
+        &self.core.style
+    }
+
+    fn is_visible(&self) -> bool {
+        self.core.visible
+    }
+
+    fn update(&mut self, delta: f32) -> bool {
+        self.core.update(delta)
+    }
+
+    fn is_drawn(&self) -> bool {
+        self.core.is_drawn()
+    }
+
+    fn opacity(&self) -> f32 {
+        self.core.opacity()
+    }
+
+    fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        self.core.drawn_bounds(window_height)
+    }
+}
+
+/// A checkbox: a box with a checkmark when checked
+#[derive(Debug, Clone)]
+pub struct Checkbox {
+    core: ToggleCore,
+}
+
+impl Checkbox {
+    /// Create a new checkbox
+    pub fn new(id: ToggleId, checked: bool) -> Self {
+        Self { core: ToggleCore::new(id, checked, [18.0, 18.0]) }
+    }
+
+    /// Set the checkbox position (relative to origin)
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.core.position = [x, y];
+        self
+    }
+
+    /// Set the checkbox size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.core.size = [width, height];
+        self
+    }
+
+    /// Set the checkbox style
+    pub fn with_style(mut self, style: ToggleStyle) -> Self {
+        self.core.style = style;
+        self
+    }
+
+    /// Animate `set_visible` instead of showing and hiding at once
+    pub fn with_animation(mut self, animation: WidgetAnimation) -> Self {
+        self.core.appearance = Some(Appearance::new(animation, self.core.visible));
+        self
+    }
+
+    /// Set the coordinate origin
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.core.origin = origin;
+        self
+    }
+
+    /// Set visibility, animated if the toggle has an animation
+    pub fn set_visible(&mut self, visible: bool) {
+        self.core.set_visible(visible);
+    }
+
+    /// Get position in local coordinates
+    pub fn position(&self) -> [f32; 2] {
+        self.core.position
+    }
+
+    /// Set position
+    pub fn set_position(&mut self, x: f32, y: f32) {
+        self.core.position = [x, y];
+    }
+
+    /// Get size
+    pub fn size(&self) -> [f32; 2] {
+        self.core.size
+    }
+}
+
+impl Widget for Checkbox {
+    fn update_hover(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) {
+        self.core.update_hover(cursor_x, cursor_y, window_height);
+    }
+
+    fn handle_press(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        self.core.handle_press(cursor_x, cursor_y, window_height)
+    }
+
+    fn handle_release(&mut self, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+        self.core.handle_release(cursor_x, cursor_y, window_height)
+    }
+
+    fn screen_bounds(&self, window_height: f32) -> [f32; 4] {
+        self.core.screen_bounds(window_height)
+    }
+}
+
+impl Toggle for Checkbox {
+    fn id(&self) -> ToggleId {
+        self.core.id
+    }
+
+    fn kind(&self) -> ToggleKind {
+        ToggleKind::Checkbox
+    }
+
+    fn is_on(&self) -> bool {
+        self.core.on
+    }
+
+    fn set_on(&mut self, on: bool) {
+        self.core.on = on;
+    }
+
+    fn state(&self) -> ButtonState {
+        self.core.state
+    }
+
+    fn style(&self) -> &ToggleStyle {
+        &self.core.style
+    }
+
+    fn is_visible(&self) -> bool {
+        self.core.visible
+    }
+
+    fn update(&mut self, delta: f32) -> bool {
+        self.core.update(delta)
+    }
+
+    fn is_drawn(&self) -> bool {
+        self.core.is_drawn()
+    }
+
+    fn opacity(&self) -> f32 {
+        self.core.opacity()
+    }
+
+    fn drawn_bounds(&self, window_height: f32) -> [f32; 4] {
+        self.core.drawn_bounds(window_height)
+    }
+}
+
+impl LayoutItem for ToggleSwitch {
+    fn size(&self) -> [f32; 2] {
+        self.core.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.core.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.core.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.core.position = [x, y];
+    }
+}
+
+impl LayoutItem for Checkbox {
+    fn size(&self) -> [f32; 2] {
+        self.core.size
+    }
+
+    fn origin(&self) -> Origin {
+        self.core.origin
+    }
+
+    fn is_visible(&self) -> bool {
+        self.core.visible
+    }
+
+    fn set_position(&mut self, x: f32, y: f32) {
+        self.core.position = [x, y];
+    }
+}
@@ -0,0 +1,99 @@
+//! Tooltips for ghost-ui widgets
+
+/// Look of tooltips, and how long the cursor rests before one shows
+#[derive(Debug, Clone, PartialEq)]
+pub struct TooltipStyle {
+    /// Box color
+    pub background: [f32; 4],
+    /// Text color
+    pub text_color: [f32; 4],
+    /// Font size in pixels
+    pub font_size: f32,
+    /// Space around the text [horizontal, vertical]
+    pub padding: [f32; 2],
+    /// Corner radius
+    pub border_radius: f32,
+    /// Distance from the cursor to the box, flipped above the cursor when
+    /// there is no room below
+    pub offset: [f32; 2],
+    /// Seconds the cursor rests on a widget before its tooltip shows
+    pub delay: f32,
+}
+
+impl Default for TooltipStyle {
+    fn default() -> Self {
+        Self {
+            background: [0.1, 0.1, 0.1, 0.92],
+            text_color: [1.0, 1.0, 1.0, 1.0],
+            font_size: 13.0,
+            padding: [8.0, 4.0],
+            border_radius: 4.0,
+            offset: [12.0, 18.0],
+            delay: 0.6,
+        }
+    }
+}
+
+/// The tooltip of the hovered widget, shown once the cursor has rested on
+/// it for `TooltipStyle::delay` and hidden as soon as it moves away.
+///
+/// The ghost window keeps one for its buttons (see `Button::with_tooltip`).
+#[derive(Debug, Clone, Default)]
+pub struct Tooltip {
+    style: TooltipStyle,
+    /// Tooltip of the hovered widget
+    hovered: Option<String>,
+    /// Seconds the cursor has rested on the hovered widget
+    resting: f32,
+    /// Cursor position when the tooltip showed, None while hidden
+    shown_at: Option<[f32; 2]>,
+}
+
+impl Tooltip {
+    /// Create a hidden tooltip
+    pub fn new(style: TooltipStyle) -> Self {
+        Self {
+            style,
+            ..Self::default()
+        }
+    }
+
+    /// Get the style
+    pub fn style(&self) -> &TooltipStyle {
+        &self.style
+    }
+
+    /// Set the style
+    pub fn set_style(&mut self, style: TooltipStyle) {
+        self.style = style;
+    }
+
+    /// Follow the tooltip of the hovered widget (None if there is none) with
+    /// the cursor at `cursor`; returns true if the tooltip showed or hid
+    pub fn update(&mut self, hovered: Option<&str>, cursor: [f32; 2], delta: f32) -> bool {
+        if hovered != self.hovered.as_deref() {
+            self.hovered = hovered.map(str::to_string);
+            self.resting = 0.0;
+            return self.shown_at.take().is_some();
+        }
+        if self.hovered.is_none() || self.shown_at.is_some() {
+            return false;
+        }
+        self.resting += delta;
+        if self.resting < self.style.delay {
+            return false;
+        }
+        self.shown_at = Some(cursor);
+        true
+    }
+
+    /// Text of the tooltip, None while hidden
+    pub fn text(&self) -> Option<&str> {
+        self.shown_at.and(self.hovered.as_deref())
+    }
+
+    /// Cursor position (screen coordinates) when the tooltip showed
+    pub fn position(&self) -> Option<[f32; 2]> {
+        self.shown_at
+    }
+}
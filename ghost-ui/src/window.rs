@@ -1,20 +1,33 @@
 //! Ghost window creation and event handling
 
 use std::path::Path;
+use std::time::Duration;
 
-use crate::widget::Widget;
+use crate::widget::{ButtonState, FocusAction, FocusManager, TextInputAction, Tooltip, TooltipStyle, Widget};
 
 use tao::{
     dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
-    event::{ElementState, Event, MouseButton, WindowEvent},
+    event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::ModifiersState,
     event_loop::{ControlFlow, EventLoop},
     window::{Window, WindowBuilder},
 };
 use thiserror::Error;
 
-use crate::platform::configure_window;
-use crate::renderer::{Renderer, RendererError};
+use crate::accessibility::{self, AccessAction, Accessibility};
+use crate::context_menu::{self, ContextMenu};
+use crate::easing::Easing;
+use crate::gesture::{ClickDetector, RubDetector, RubGesture};
+use crate::hotkeys;
+use crate::motion::{Motion, MotionConfig, MotionState};
+use crate::monitor::{self, MonitorSelection};
+use crate::placement::{keep_inside, place_callout, CalloutFlip};
+use crate::platform::{configure_window, pin_to_desktop, work_area_insets};
+use crate::renderer::{EffectParams, Recolor, Renderer, RendererError};
 use crate::skin::SkinData;
+use crate::skin_watch::SkinWatcher;
+use crate::snap::{self, DragTracker, Edge};
+use crate::transition::{self, Transition, VisibilityTransition};
 use crate::Skin;
 
 /// Maximum texture size supported by most GPUs.
@@ -25,6 +38,9 @@ const MAX_TEXTURE_SIZE: u32 = 2048;
 /// Pixels with alpha <= this value are considered transparent.
 const DEFAULT_ALPHA_THRESHOLD: u8 = 10;
 
+/// Logical pixels of touchpad scrolling reported as one wheel line
+const SCROLL_LINE_PIXELS: f64 = 24.0;
+
 #[derive(Error, Debug)]
 pub enum WindowError {
     #[error("Failed to create window: {0}")]
@@ -44,6 +60,9 @@ pub struct WindowConfig {
     pub height: u32,
     /// Whether the window should always stay on top.
     pub always_on_top: bool,
+    /// Whether the window stays below normal windows, on the desktop like a
+    /// widget (wins over `always_on_top`).
+    pub always_on_bottom: bool,
     /// Whether mouse clicks should pass through the window entirely.
     pub click_through: bool,
     /// Whether the window can be dragged.
@@ -62,6 +81,24 @@ pub struct WindowConfig {
     pub alpha_threshold: u8,
     /// Whether to change opacity based on focus state.
     pub focus_opacity_enabled: bool,
+    /// Rub gesture to recognize over the skin (None = disabled).
+    pub rub_gesture: Option<RubGesture>,
+    /// Monitor to start on (None = wherever the OS puts the window).
+    pub monitor: Option<MonitorSelection>,
+    /// Start position in logical pixels from the monitor's top-left corner
+    /// (None = centered on the monitor). Only used with `monitor` set.
+    pub position: Option<[f32; 2]>,
+    /// Dock against screen edges closer than this many logical pixels when
+    /// a drag ends (None = disabled).
+    pub edge_snap: Option<f32>,
+    /// Physics movement engine, run while `GhostApp::motion_enabled` is true
+    /// (None = not available).
+    pub motion: Option<MotionConfig>,
+    /// Keep the window and its linked windows inside the monitor's work
+    /// area, clear of the taskbar, dock or menu bar.
+    pub keep_in_work_area: bool,
+    /// Look and delay of button tooltips.
+    pub tooltip: TooltipStyle,
 }
 
 impl Default for WindowConfig {
@@ -70,6 +107,7 @@ impl Default for WindowConfig {
             width: 200,
             height: 200,
             always_on_top: true,
+            always_on_bottom: false,
             click_through: false,
             draggable: true,
             title: "Ghost".to_string(),
@@ -79,6 +117,13 @@ impl Default for WindowConfig {
             alpha_hit_test: true,
             alpha_threshold: DEFAULT_ALPHA_THRESHOLD,
             focus_opacity_enabled: true,
+            rub_gesture: None,
+            monitor: None,
+            position: None,
+            edge_snap: None,
+            motion: None,
+            keep_in_work_area: false,
+            tooltip: TooltipStyle::default(),
         }
     }
 }
@@ -117,10 +162,61 @@ struct WindowData {
     original_skin_size: Option<(u32, u32)>,
     /// Whether the window is currently focused
     is_focused: bool,
+    /// Modifier keys held down, for text input shortcuts
+    modifiers: ModifiersState,
+    /// Where the IME candidate window was last placed
+    ime_position: Option<(f32, f32)>,
+    /// Window size the app last laid out its widgets for
+    laid_out_size: Option<(u32, u32)>,
+    /// Tooltip of the hovered button
+    tooltip: Tooltip,
+    /// Button with keyboard focus
+    focus: FocusManager,
+    /// Widgets and text exposed to screen readers
+    accessibility: Option<Accessibility>,
     /// Current opacity (computed from focus state)
     current_opacity: f32,
+    /// Opacity forced by the app, ignoring focus state
+    opacity_override: Option<f32>,
+    /// Skin is mirrored horizontally
+    flipped: bool,
+    /// Vertical scale of the skin, anchored at its bottom edge
+    skin_scale: f32,
+    /// Color changes of the skin
+    recolor: Recolor,
+    /// Post effects of the skin
+    skin_effects: EffectParams,
+    /// Show or hide animation in progress
+    transition: Option<VisibilityTransition>,
+    /// Opacity of a fade transition
+    transition_opacity: f32,
+    /// Dissolve of a dissolve transition
+    transition_dissolve: f32,
+    /// Where a slide transition brings the window back to and slides it
+    /// out to
+    slide: Option<((i32, i32), (i32, i32))>,
+    /// Animated move in progress
+    window_move: Option<WindowMove>,
     /// Skin offset within the window [x, y] in pixels
     skin_offset: [f32; 2],
+    /// Rub gesture recognizer (when enabled in the config)
+    rub: Option<RubDetector>,
+    /// Double-click recognizer
+    clicks: ClickDetector,
+    /// Native context menu last shown (kept alive while it is open)
+    context_menu: Option<tray_icon::menu::Menu>,
+    /// User drag in progress (tracked only with edge snapping)
+    drag: Option<DragTracker>,
+    /// Edge the running animated move docks against
+    docking: Option<Edge>,
+    /// Physics movement engine (when enabled in the config)
+    motion: Option<Motion>,
+    /// Click-through last set by `set_ignore_cursor_events`
+    ignoring_cursor: Option<bool>,
+    /// Watches the skin named by `GhostApp::skin_path`
+    skin_watcher: Option<SkinWatcher>,
+    /// Watches the file named by `GhostApp::config_path`
+    config_watcher: Option<SkinWatcher>,
 }
 
 /// A transparent, shaped window for ghost UI elements.
@@ -153,20 +249,55 @@ impl GhostWindow {
             config.opacity_focused
         };
 
-        let window = WindowBuilder::new()
-            .with_inner_size(LogicalSize::new(clamped_width, clamped_height))
+        let size = LogicalSize::new(clamped_width, clamped_height);
+        let start = config
+            .monitor
+            .as_ref()
+            .and_then(|selection| selection.resolve(event_loop))
+            .map(|m| (monitor::position_on(&m, size, config.position), m));
+
+        let mut builder = WindowBuilder::new()
+            .with_inner_size(size)
             .with_transparent(true)
             .with_decorations(false)
-            .with_always_on_top(config.always_on_top)
+            .with_always_on_top(config.always_on_top && !config.always_on_bottom)
+            .with_always_on_bottom(config.always_on_bottom)
             .with_title(&config.title)
-            .build(event_loop)?;
+            // Shown once the accessibility adapter is attached
+            .with_visible(false);
+        if let Some((position, _)) = start {
+            builder = builder.with_position(position);
+        }
+        let window = builder.build(event_loop)?;
+
+        if let Some((position, monitor)) = start {
+            log::info!(
+                "Starting on monitor {} at {:?} (scale {})",
+                monitor.name().unwrap_or_default(),
+                position,
+                monitor.scale_factor()
+            );
+            // Some window managers ignore the initial position, and the size
+            // may have been scaled for the monitor the window was created on
+            window.set_outer_position(position);
+            window.set_inner_size(size);
+        }
+
+        let accessibility = Accessibility::new(&window, &config.title);
+        window.set_visible(true);
 
         // Apply platform-specific configuration
         configure_window(&window, config.click_through);
+        if config.always_on_bottom {
+            pin_to_desktop(&window, true);
+        }
 
         let window_size = window.inner_size();
 
         // Store window data in a box
+        let rub = config.rub_gesture.map(RubDetector::new);
+        let motion = config.motion.map(Motion::new);
+        let tooltip = Tooltip::new(config.tooltip.clone());
         let data = Box::new(WindowData {
             window,
             skin: None,
@@ -176,8 +307,33 @@ impl GhostWindow {
             cursor_position: None,
             original_skin_size: None,
             is_focused: false,
+            modifiers: ModifiersState::empty(),
+            ime_position: None,
+            laid_out_size: None,
+            tooltip,
+            focus: FocusManager::new(),
+            accessibility: Some(accessibility),
             current_opacity: initial_opacity,
+            opacity_override: None,
+            flipped: false,
+            skin_scale: 1.0,
+            recolor: Recolor::default(),
+            skin_effects: EffectParams::default(),
+            transition: None,
+            transition_opacity: 1.0,
+            transition_dissolve: 0.0,
+            slide: None,
+            window_move: None,
             skin_offset: [0.0, 0.0],
+            rub,
+            clicks: ClickDetector::default(),
+            context_menu: None,
+            drag: None,
+            docking: None,
+            motion,
+            ignoring_cursor: None,
+            skin_watcher: None,
+            config_watcher: None,
         });
 
         // Create renderer with a reference to the boxed window
@@ -221,6 +377,50 @@ impl GhostWindow {
         self.load_skin_from_bytes(data.bytes())
     }
 
+    /// Watch the skin at `path` (None = stop watching) and report whether
+    /// it changed since the last call; a static skin image is reloaded.
+    fn poll_skin_changes(&mut self, path: Option<&Path>) -> bool {
+        let Some(path) = path else {
+            self.data.skin_watcher = None;
+            return false;
+        };
+        let watcher = match self.data.skin_watcher {
+            Some(ref mut watcher) if watcher.path() == path => watcher,
+            // New or switched skin: start over
+            _ => {
+                self.data.skin_watcher = Some(SkinWatcher::new(path));
+                return false;
+            }
+        };
+        if !watcher.poll() {
+            return false;
+        }
+        if self.data.skin.is_some() && path.is_file() {
+            if let Err(e) = self.load_skin_from_path(path) {
+                log::error!("Failed to reload skin '{}': {}", path.display(), e);
+                return false;
+            }
+        }
+        log::info!("Reloaded skin '{}'", path.display());
+        true
+    }
+
+    /// Watch the config file at `path` (None = stop watching) and report
+    /// whether it changed since the last call
+    fn poll_config_changes(&mut self, path: Option<&Path>) -> bool {
+        let Some(path) = path else {
+            self.data.config_watcher = None;
+            return false;
+        };
+        match self.data.config_watcher {
+            Some(ref mut watcher) if watcher.path() == path => watcher.poll(),
+            _ => {
+                self.data.config_watcher = Some(SkinWatcher::new(path));
+                false
+            }
+        }
+    }
+
     /// Set the window position (in physical pixels).
     pub fn set_position(&self, x: i32, y: i32) {
         self.data
@@ -257,11 +457,136 @@ impl GhostWindow {
         }
     }
 
+    /// Force an opacity regardless of focus, or None to return to focus-based opacity.
+    pub fn set_opacity_override(&mut self, opacity: Option<f32>) {
+        self.data.opacity_override = opacity.map(|o| o.clamp(0.0, 1.0));
+        self.update_opacity_for_focus();
+    }
+
+    /// Get the forced opacity, if any.
+    pub fn opacity_override(&self) -> Option<f32> {
+        self.data.opacity_override
+    }
+
     /// Get the current opacity value.
     pub fn opacity(&self) -> f32 {
         self.data.current_opacity
     }
 
+    /// Opacity the window is drawn with, faded by a fade transition
+    fn render_opacity(&self) -> f32 {
+        self.data.current_opacity * self.data.transition_opacity
+    }
+
+    /// Show the window, animated over `duration`.
+    ///
+    /// The animation is advanced by the event loop; a show or hide started
+    /// meanwhile takes over from where it got to.
+    pub fn show_animated(&mut self, transition: Transition, duration: Duration) {
+        let from = if self.data.window.is_visible() { self.visibility() } else { 0.0 };
+        self.start_transition(transition, true, from, duration);
+        self.data.window.set_visible(true);
+    }
+
+    /// Hide the window, animated over `duration`; it's hidden once the
+    /// animation ends.
+    pub fn hide_animated(&mut self, transition: Transition, duration: Duration) {
+        if !self.data.window.is_visible() {
+            return;
+        }
+        let from = self.visibility();
+        self.start_transition(transition, false, from, duration);
+    }
+
+    /// Show or hide the window right away, ending a running transition.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.end_transition();
+        self.data.window.set_visible(visible);
+    }
+
+    /// Whether a show or hide animation is running.
+    pub fn is_transitioning(&self) -> bool {
+        self.data.transition.is_some()
+    }
+
+    /// How far the running transition has shown the window (1.0 without one)
+    fn visibility(&self) -> f32 {
+        self.data.transition.as_ref().map_or(1.0, VisibilityTransition::visibility)
+    }
+
+    fn start_transition(&mut self, transition: Transition, showing: bool, from: f32, duration: Duration) {
+        // A slide interrupted by another slide keeps its way home
+        let slide = self.data.slide.filter(|_| transition == Transition::Slide);
+        self.end_transition();
+        if transition == Transition::Slide {
+            let home = slide.map(|(home, _)| home).or_else(|| self.outer_position());
+            if let (Some(home), Some(window), Some(screen)) = (home, self.outer_rect(), self.monitor_rect()) {
+                let window = ScreenRect { x: home.0, y: home.1, ..window };
+                self.data.slide = Some((home, transition::slide_away(window, screen)));
+            }
+        }
+        self.data.transition = Some(VisibilityTransition::new(transition, showing, from, duration));
+        self.apply_transition();
+    }
+
+    /// Draw the running transition, returns the window position when it
+    /// slid
+    fn apply_transition(&mut self) -> Option<(i32, i32)> {
+        let transition = self.data.transition.as_ref()?;
+        let visibility = transition.visibility();
+        match transition.transition {
+            Transition::Fade => self.data.transition_opacity = visibility,
+            Transition::Pop => {
+                if let Some(ref mut renderer) = self.renderer {
+                    renderer.set_zoom(visibility);
+                }
+            }
+            Transition::Dissolve => {
+                self.data.transition_dissolve = 1.0 - visibility;
+                self.apply_skin_effects();
+            }
+            Transition::Slide => {
+                let (home, away) = self.data.slide?;
+                let lerp = |a: i32, b: i32| a + ((b - a) as f32 * visibility).round() as i32;
+                let position = (lerp(away.0, home.0), lerp(away.1, home.1));
+                self.set_position(position.0, position.1);
+                return Some(position);
+            }
+        }
+        None
+    }
+
+    /// Stop the running transition and draw the window as is, returns the
+    /// window position when it slid back home
+    fn end_transition(&mut self) -> Option<(i32, i32)> {
+        self.data.transition = None;
+        self.data.transition_opacity = 1.0;
+        self.data.transition_dissolve = 0.0;
+        self.apply_skin_effects();
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_zoom(1.0);
+        }
+        let (home, _) = self.data.slide.take()?;
+        self.set_position(home.0, home.1);
+        Some(home)
+    }
+
+    /// Advance the show or hide animation, returns the window position
+    /// when it slid.
+    fn advance_transition(&mut self, delta: f32) -> Option<(i32, i32)> {
+        let transition = self.data.transition.as_mut()?;
+        let done = transition.advance(delta);
+        let showing = transition.showing;
+        let position = self.apply_transition();
+        if !done {
+            return position;
+        }
+        if !showing {
+            self.data.window.set_visible(false);
+        }
+        self.end_transition().or(position)
+    }
+
     /// Get a reference to the underlying tao window.
     pub fn window(&self) -> &Window {
         &self.data.window
@@ -282,10 +607,86 @@ impl GhostWindow {
         self.data.skin_offset
     }
 
+    /// Mirror the skin horizontally (e.g. to face the other way).
+    pub fn set_flipped(&mut self, flipped: bool) {
+        self.data.flipped = flipped;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_flip_x(flipped);
+        }
+    }
+
+    /// Whether the skin is mirrored horizontally.
+    pub fn is_flipped(&self) -> bool {
+        self.data.flipped
+    }
+
+    /// Scale the skin vertically, keeping its bottom edge in place (e.g.
+    /// breathing).
+    pub fn set_skin_scale(&mut self, scale: f32) {
+        self.data.skin_scale = scale;
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_skin_scale(scale);
+        }
+    }
+
+    /// Vertical scale of the skin.
+    pub fn skin_scale(&self) -> f32 {
+        self.data.skin_scale
+    }
+
+    /// Recolor the skin (hue shift, tint, palette swap) without editing
+    /// its images.
+    pub fn set_recolor(&mut self, recolor: Recolor) {
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_recolor(recolor.clone());
+        }
+        self.data.recolor = recolor;
+    }
+
+    /// Color changes of the skin.
+    pub fn recolor(&self) -> &Recolor {
+        &self.data.recolor
+    }
+
+    /// Apply post effects (glow, dissolve, pixelation, grayscale) to the
+    /// skin.
+    pub fn set_skin_effects(&mut self, effects: EffectParams) {
+        self.data.skin_effects = effects;
+        self.apply_skin_effects();
+    }
+
+    /// Draw the skin effects, dissolved further by a dissolve transition
+    fn apply_skin_effects(&mut self) {
+        let mut effects = self.data.skin_effects;
+        effects.dissolve = effects.dissolve.max(self.data.transition_dissolve);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.set_effects(effects);
+        }
+    }
+
+    /// Post effects of the skin.
+    pub fn skin_effects(&self) -> EffectParams {
+        self.data.skin_effects
+    }
+
+    /// Cursor position relative to the window's top-left corner in logical
+    /// pixels, also when the cursor is outside the window.
+    pub fn global_cursor_position(&self) -> Option<(f32, f32)> {
+        let window = &self.data.window;
+        let cursor = window.cursor_position().ok()?;
+        let origin = window.inner_position().ok()?;
+        let scale = window.scale_factor();
+        Some((
+            ((cursor.x - origin.x as f64) / scale) as f32,
+            ((cursor.y - origin.y as f64) / scale) as f32,
+        ))
+    }
+
     /// Render the current frame.
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        let opacity = self.render_opacity();
         if let Some(ref mut renderer) = self.renderer {
-            renderer.render(self.data.skin.as_ref(), self.data.current_opacity)
+            renderer.render(self.data.skin.as_ref(), opacity)
         } else {
             Ok(())
         }
@@ -296,10 +697,11 @@ impl GhostWindow {
     where
         F: FnOnce(&mut wgpu::RenderPass<'_>),
     {
+        let opacity = self.render_opacity();
         if let Some(ref mut renderer) = self.renderer {
             renderer.render_with_extra(
                 self.data.skin.as_ref(),
-                self.data.current_opacity,
+                opacity,
                 self.data.skin_offset,
                 extra,
             )
@@ -313,10 +715,11 @@ impl GhostWindow {
         &mut self,
         button_renderer: Option<&crate::renderer::ButtonRenderer>,
     ) -> Result<(), wgpu::SurfaceError> {
+        let opacity = self.render_opacity();
         if let Some(ref mut renderer) = self.renderer {
             renderer.render_with_buttons(
                 self.data.skin.as_ref(),
-                self.data.current_opacity,
+                opacity,
                 self.data.skin_offset,
                 button_renderer,
             )
@@ -331,6 +734,7 @@ impl GhostWindow {
         button_renderer: Option<&crate::renderer::ButtonRenderer>,
         app: &mut A,
     ) -> Result<(), wgpu::SurfaceError> {
+        let opacity = self.render_opacity();
         if let Some(ref mut renderer) = self.renderer {
             // Get app's skin first (if any) to avoid borrow conflicts
             // We use a raw pointer to work around the borrow checker
@@ -341,7 +745,7 @@ impl GhostWindow {
             };
             renderer.render_with_buttons_and_app(
                 skin,
-                self.data.current_opacity,
+                opacity,
                 self.data.skin_offset,
                 button_renderer,
                 app,
@@ -388,7 +792,9 @@ impl GhostWindow {
 
     /// Update opacity based on current focus state.
     fn update_opacity_for_focus(&mut self) {
-        self.data.current_opacity = if self.data.is_focused {
+        self.data.current_opacity = if let Some(opacity) = self.data.opacity_override {
+            opacity
+        } else if self.data.is_focused || !self.data.config.focus_opacity_enabled {
             self.data.config.opacity_focused
         } else {
             self.data.config.opacity_unfocused
@@ -410,12 +816,64 @@ impl GhostWindow {
     /// Handle cursor leaving the window.
     pub fn handle_cursor_left(&mut self) {
         self.data.cursor_position = None;
+        if let Some(ref mut rub) = self.data.rub {
+            rub.reset();
+        }
         // Re-enable click handling when cursor leaves (only matters when focused)
         if self.data.config.alpha_hit_test && !self.data.config.click_through && self.data.is_focused {
             self.update_click_through(false);
         }
     }
 
+    /// Feed the cursor position to the rub recognizer; returns true when
+    /// a rub over the skin was recognized.
+    fn detect_rub(&mut self) -> bool {
+        let over_skin = self.hit_test_at_cursor();
+        let (Some(cursor), Some(rub)) = (self.data.cursor_position, self.data.rub.as_mut()) else {
+            return false;
+        };
+        if !over_skin {
+            rub.reset();
+            return false;
+        }
+        rub.moved(cursor.x as f32, std::time::Instant::now())
+    }
+
+    /// Open a native menu at `position` (logical pixels from the window's
+    /// top-left corner, None = at the cursor); the chosen item is reported
+    /// as `GhostEvent::ContextMenuSelected`
+    pub fn show_context_menu(&mut self, menu: &ContextMenu, position: Option<(f32, f32)>) {
+        use tray_icon::menu::dpi::{LogicalPosition, Position};
+        use tray_icon::menu::ContextMenu as _;
+
+        let native = menu.to_native();
+        let position = position.map(|(x, y)| Position::Logical(LogicalPosition::new(x as f64, y as f64)));
+        #[cfg(target_os = "windows")]
+        {
+            use tao::platform::windows::WindowExtWindows;
+            native.show_context_menu_for_hwnd(self.data.window.hwnd(), position);
+        }
+        #[cfg(target_os = "linux")]
+        {
+            use gtk::prelude::Cast;
+            use tao::platform::unix::WindowExtUnix;
+            native.show_context_menu_for_gtk_window(self.data.window.gtk_window().upcast_ref(), position);
+        }
+        #[cfg(target_os = "macos")]
+        {
+            use tao::platform::macos::WindowExtMacOS;
+            native.show_context_menu_for_nsview(self.data.window.ns_view() as _, position);
+        }
+        self.data.context_menu = Some(native);
+    }
+
+    /// Cursor position in logical pixels from the window's top-left corner
+    fn logical_cursor_position(&self) -> Option<(f32, f32)> {
+        let cursor = self.data.cursor_position?;
+        let scale = self.data.window.scale_factor();
+        Some(((cursor.x / scale) as f32, (cursor.y / scale) as f32))
+    }
+
     /// Update platform click-through state.
     #[allow(unused_variables)]
     fn update_click_through(&self, transparent: bool) {
@@ -430,6 +888,59 @@ impl GhostWindow {
         // so we handle it in the event loop instead
     }
 
+    /// Move the window above other windows, among them, or below them on
+    /// the desktop.
+    pub fn set_layer(&mut self, layer: WindowLayer) {
+        if layer == self.layer() {
+            return;
+        }
+        let window = &self.data.window;
+        match layer {
+            WindowLayer::Top => {
+                window.set_always_on_bottom(false);
+                window.set_always_on_top(true);
+            }
+            WindowLayer::Normal => {
+                window.set_always_on_top(false);
+                window.set_always_on_bottom(false);
+            }
+            WindowLayer::Desktop => {
+                window.set_always_on_top(false);
+                window.set_always_on_bottom(true);
+            }
+        }
+        pin_to_desktop(window, layer == WindowLayer::Desktop);
+        self.data.config.always_on_top = layer == WindowLayer::Top;
+        self.data.config.always_on_bottom = layer == WindowLayer::Desktop;
+    }
+
+    /// Where the window stacks among the other windows.
+    pub fn layer(&self) -> WindowLayer {
+        if self.data.config.always_on_bottom {
+            WindowLayer::Desktop
+        } else if self.data.config.always_on_top {
+            WindowLayer::Top
+        } else {
+            WindowLayer::Normal
+        }
+    }
+
+    /// Let clicks pass through the whole window (or not), on every platform
+    pub fn set_ignore_cursor_events(&mut self, ignore: bool) {
+        if self.data.ignoring_cursor != Some(ignore) {
+            self.data.ignoring_cursor = Some(ignore);
+            let _ = self.data.window.set_ignore_cursor_events(ignore);
+        }
+    }
+
+    /// Cursor position relative to the window's client area, even when the
+    /// window ignores cursor events
+    fn cursor_in_window(&self) -> Option<[f32; 2]> {
+        let cursor = self.data.window.cursor_position().ok()?;
+        let origin = self.data.window.inner_position().ok()?;
+        Some([(cursor.x - origin.x as f64) as f32, (cursor.y - origin.y as f64) as f32])
+    }
+
     /// Test if the cursor is over a non-transparent pixel.
     fn hit_test_at_cursor(&self) -> bool {
         let Some(cursor_pos) = self.data.cursor_position else {
@@ -454,8 +965,11 @@ impl GhostWindow {
         let scale_x = orig_w as f64 / win_w as f64;
         let scale_y = orig_h as f64 / win_h as f64;
 
-        let skin_x = (cursor_pos.x * scale_x) as f32;
+        let mut skin_x = (cursor_pos.x * scale_x) as f32;
         let skin_y = (cursor_pos.y * scale_y) as f32;
+        if self.data.flipped {
+            skin_x = orig_w as f32 - skin_x;
+        }
 
         skin.hit_test(skin_x, skin_y, self.data.config.alpha_threshold)
     }
@@ -516,127 +1030,985 @@ impl GhostWindow {
 
         self.data.last_size = (final_width, final_height);
 
-        if let Some(ref mut renderer) = self.renderer {
-            renderer.resize(final_width, final_height);
+        if let Some(ref mut renderer) = self.renderer {
+            renderer.resize(final_width, final_height);
+        }
+    }
+
+    /// Check if the window is draggable.
+    pub fn is_draggable(&self) -> bool {
+        self.data.config.draggable
+    }
+
+    /// Check if the window is focused.
+    pub fn is_focused(&self) -> bool {
+        self.data.is_focused
+    }
+
+    /// Start dragging the window.
+    pub fn drag(&mut self) {
+        if self.data.config.edge_snap.is_some() || self.data.motion.is_some() {
+            self.data.drag = Some(DragTracker::new());
+        }
+        if let Some(ref mut motion) = self.data.motion {
+            motion.grab();
+        }
+        let _ = self.data.window.drag_window();
+    }
+
+    /// The left mouse button was released.
+    pub fn handle_drag_released(&mut self) {
+        if let Some(ref mut drag) = self.data.drag {
+            drag.release();
+        }
+    }
+
+    /// Get the window's outer position (screen coordinates).
+    pub fn outer_position(&self) -> Option<(i32, i32)> {
+        self.data.window.outer_position().ok().map(|p| (p.x, p.y))
+    }
+
+    /// Get the window's outer rectangle (screen coordinates).
+    pub fn outer_rect(&self) -> Option<ScreenRect> {
+        let (x, y) = self.outer_position()?;
+        let size = self.data.window.outer_size();
+        Some(ScreenRect { x, y, width: size.width, height: size.height })
+    }
+
+    /// Get the bounds of the monitor the window is on.
+    pub fn monitor_rect(&self) -> Option<ScreenRect> {
+        let monitor = self.data.window.current_monitor()?;
+        let position = monitor.position();
+        let size = monitor.size();
+        Some(ScreenRect {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+
+    /// Get the usable part of the monitor the window is on: without the
+    /// taskbar, dock or menu bar (the whole monitor where the platform
+    /// can't tell).
+    pub fn work_area(&self) -> Option<ScreenRect> {
+        let monitor = self.monitor_rect()?;
+        let Some([left, top, right, bottom]) = work_area_insets(&self.data.window) else {
+            return Some(monitor);
+        };
+        Some(ScreenRect {
+            x: monitor.x + left,
+            y: monitor.y + top,
+            width: (monitor.width as i32 - left - right).max(1) as u32,
+            height: (monitor.height as i32 - top - bottom).max(1) as u32,
+        })
+    }
+
+    /// Whether the window keeps inside the monitor's work area.
+    pub fn keeps_in_work_area(&self) -> bool {
+        self.data.config.keep_in_work_area
+    }
+
+    /// The work area linked windows keep inside (None when not kept in it)
+    fn linked_area(&self) -> Option<ScreenRect> {
+        self.work_area().filter(|_| self.data.config.keep_in_work_area)
+    }
+
+    /// The area the window moves in: the work area when kept inside it,
+    /// else the whole monitor.
+    pub fn screen_rect(&self) -> Option<ScreenRect> {
+        if self.data.config.keep_in_work_area {
+            self.work_area()
+        } else {
+            self.monitor_rect()
+        }
+    }
+
+    /// Glide the window to (x, y) in screen coordinates over `duration` seconds,
+    /// speeding up and slowing down along `easing`.
+    ///
+    /// The move is advanced by the event loop, which keeps linked windows
+    /// (callout, chat) following; a drag by the user cancels it. A window
+    /// kept in the work area stops at its edge.
+    pub fn move_to_animated(&mut self, x: i32, y: i32, duration: f32, easing: Easing) {
+        let from = self.outer_position().unwrap_or((x, y));
+        let (x, y) = match (self.data.config.keep_in_work_area, self.outer_rect(), self.work_area()) {
+            (true, Some(window), Some(area)) => keep_inside((x, y), (window.width, window.height), area),
+            _ => (x, y),
+        };
+        self.data.docking = None;
+        self.data.window_move = Some(WindowMove {
+            from,
+            to: (x, y),
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+            easing,
+        });
+    }
+
+    /// Bring the window to the front and focus it.
+    pub fn focus(&self) {
+        self.data.window.set_visible(true);
+        self.data.window.set_focus();
+    }
+
+    /// Stop an animated move, returns false if none was running.
+    pub fn stop_move(&mut self) -> bool {
+        self.data.docking = None;
+        self.data.window_move.take().is_some()
+    }
+
+    /// Whether an animated move is running.
+    pub fn is_moving(&self) -> bool {
+        self.data.window_move.is_some()
+    }
+
+    /// Follow a drag until it ends, returns the release velocity in px/s.
+    fn advance_drag(&mut self, delta: f32) -> Option<(f32, f32)> {
+        let position = self.outer_position()?;
+        let drag = self.data.drag.as_mut()?;
+        if !drag.update(position, delta) {
+            return None;
+        }
+        let velocity = drag.velocity();
+        self.data.drag = None;
+        Some(velocity)
+    }
+
+    /// Slide the window against nearby screen edges. Returns the edge when
+    /// already docked without moving.
+    fn snap_to_edge(&mut self) -> Option<Edge> {
+        let position = self.outer_position()?;
+        let threshold = self.data.config.edge_snap? * self.data.window.scale_factor() as f32;
+        let (window, screen) = (self.outer_rect()?, self.screen_rect()?);
+        let ((x, y), edge) = snap::snap_target(window, screen, threshold.round() as i32)?;
+        if (x, y) == position {
+            return Some(edge);
+        }
+        self.move_to_animated(x, y, snap::SNAP_DURATION, Easing::EaseOut);
+        self.data.docking = Some(edge);
+        None
+    }
+
+    /// Run the movement engine for a frame; returns its state when it changed.
+    fn advance_motion(&mut self, delta: f32, enabled: bool) -> Option<MotionState> {
+        let scale = self.data.window.scale_factor() as f32;
+        let (window, screen) = (self.outer_rect(), self.screen_rect());
+        let moving = self.data.window_move.is_some();
+        let motion = self.data.motion.as_mut()?;
+        if !enabled {
+            motion.stop();
+        } else if let (Some(window), Some(screen), false) = (window, screen, moving) {
+            // Animated moves requested by the app take precedence
+            if let Some((x, y)) = motion.step(window, screen, scale, delta) {
+                self.set_position(x, y);
+            }
+        }
+        self.data.motion.as_mut()?.take_change()
+    }
+
+    /// Advance the animated move, returns the new position and whether it arrived.
+    fn advance_move(&mut self, delta: f32) -> Option<((i32, i32), bool)> {
+        let movement = self.data.window_move.as_mut()?;
+        movement.elapsed += delta;
+
+        let t = if movement.duration > 0.0 {
+            (movement.elapsed / movement.duration).min(1.0)
+        } else {
+            1.0
+        };
+        let eased = movement.easing.apply(t);
+        let lerp = |a: i32, b: i32| a + ((b - a) as f32 * eased).round() as i32;
+        let position = (
+            lerp(movement.from.0, movement.to.0),
+            lerp(movement.from.1, movement.to.1),
+        );
+
+        let arrived = t >= 1.0;
+        if arrived {
+            self.data.window_move = None;
+        }
+        self.set_position(position.0, position.1);
+        Some((position, arrived))
+    }
+
+    /// Get the current aspect ratio.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.data.aspect_ratio
+    }
+
+    /// Set whether to maintain aspect ratio during resize.
+    pub fn set_maintain_aspect_ratio(&mut self, maintain: bool) {
+        self.data.config.maintain_aspect_ratio = maintain;
+    }
+
+    /// Set the alpha threshold for hit testing.
+    pub fn set_alpha_threshold(&mut self, threshold: u8) {
+        self.data.config.alpha_threshold = threshold;
+    }
+}
+
+/// Where a window stacks among the other windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowLayer {
+    /// Above normal windows
+    #[default]
+    Top,
+    /// Like any other window
+    Normal,
+    /// Below normal windows, above the wallpaper, like a desktop widget
+    Desktop,
+}
+
+impl WindowLayer {
+    /// Parse a layer name ("top", "normal", "desktop")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "top" | "always-on-top" => Some(Self::Top),
+            "normal" => Some(Self::Normal),
+            "desktop" | "bottom" | "always-on-bottom" => Some(Self::Desktop),
+            _ => None,
+        }
+    }
+}
+
+/// A rectangle in physical screen pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Window movement requested by the app
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveRequest {
+    /// Glide the window's outer position to (x, y) over `duration` seconds
+    To { x: i32, y: i32, duration: f32, easing: Easing },
+    /// Stop an animated move where it is
+    Stop,
+}
+
+/// An animated move in progress
+struct WindowMove {
+    from: (i32, i32),
+    to: (i32, i32),
+    duration: f32,
+    elapsed: f32,
+    easing: Easing,
+}
+
+/// Events that can be emitted by the ghost window
+#[derive(Debug, Clone)]
+pub enum GhostEvent {
+    /// A button was clicked
+    ButtonClicked(crate::widget::ButtonId),
+    /// A switch or checkbox was clicked, with its new value
+    ToggleChanged(crate::widget::ToggleId, bool),
+    /// A slider was dragged to a new value
+    SliderChanged(crate::widget::SliderId, f32),
+    /// The text of a text input was edited, with its new text
+    TextChanged(crate::widget::TextInputId, String),
+    /// Enter was pressed in a text input, with the text it held (the input
+    /// is cleared)
+    TextSubmitted(crate::widget::TextInputId, String),
+    /// Window was focused or unfocused
+    FocusChanged(bool),
+    /// Window was resized
+    Resized(u32, u32),
+    /// Window was moved (x, y in screen coordinates)
+    Moved(i32, i32),
+    /// Frame update (for animations)
+    Update(f32), // delta time in seconds
+    /// Global cursor position relative to the window's top-left corner in
+    /// logical pixels (only sent while `GhostApp::tracks_cursor` is true)
+    CursorTracked(f32, f32),
+    /// The window is on a new monitor (also sent once at startup); the
+    /// screen is the work area when the window keeps inside it
+    ScreenChanged { screen: ScreenRect, window: ScreenRect },
+    /// An animated move reached its target
+    MoveFinished,
+    /// An animated move was interrupted by the user dragging the window
+    MoveInterrupted,
+    /// The cursor rubbed back and forth over the skin (see `RubGesture`)
+    Rubbed,
+    /// A drag ended near a screen edge and the window docked against it
+    /// (see `GhostWindowBuilder::with_edge_snap`)
+    Docked(Edge),
+    /// The movement engine changed state (see `GhostWindowBuilder::with_motion`)
+    MotionChanged(MotionState),
+    /// The skin named by `GhostApp::skin_path` changed on disk; a static
+    /// skin has already been reloaded, animated skins are up to the app
+    SkinReloaded,
+    /// The file named by `GhostApp::config_path` changed on disk; reading
+    /// and applying it is up to the app
+    ConfigReloaded,
+    /// The right mouse button was pressed at (x, y), in logical pixels from
+    /// the window's top-left corner (e.g. to open a context menu)
+    RightClicked(f32, f32),
+    /// The left mouse button was pressed twice in quick succession at (x, y),
+    /// in logical pixels; the first press was reported as usual
+    DoubleClicked(f32, f32),
+    /// The mouse wheel or touchpad scrolled over the window by (x, y) lines;
+    /// positive y scrolls up
+    Scrolled(f32, f32),
+    /// An item of the menu from `GhostApp::context_menu` was chosen, with
+    /// the id it was added with
+    ContextMenuSelected(String),
+    /// An item of a tray menu built by `TrayMenuBuilder` was chosen, with
+    /// the id it was added with
+    TrayMenuSelected(String),
+    /// A shortcut from `register_hotkeys` was pressed, with its id
+    HotkeyPressed(String),
+    /// A file was dropped onto the window
+    FileDropped(std::path::PathBuf),
+}
+
+/// GPU resources for app initialization
+pub struct GpuResources<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub format: wgpu::TextureFormat,
+}
+
+/// Application trait for handling ghost window events
+pub trait GhostApp {
+    /// Called once when GPU resources are available (for initializing renderers)
+    fn init_gpu(&mut self, _gpu: GpuResources<'_>) {}
+
+    /// Called each frame to update state (e.g., animations)
+    /// delta is the time since the last frame in seconds
+    fn update(&mut self, _delta: f32) {}
+
+    /// Return true if the app wants to quit
+    fn should_quit(&self) -> bool {
+        false
+    }
+
+    /// Called once when the event loop ends, before the windows close
+    /// (e.g. to save state for the next launch)
+    fn on_exit(&mut self) {}
+
+    /// Called when an event occurs
+    fn on_event(&mut self, event: GhostEvent);
+
+    /// Called before rendering, return buttons to render
+    fn buttons(&self) -> Vec<&crate::widget::Button> {
+        Vec::new()
+    }
+
+    /// Called to update button states (for hover effects, etc.)
+    fn buttons_mut(&mut self) -> Vec<&mut crate::widget::Button> {
+        Vec::new()
+    }
+
+    /// Called before rendering, return switches and checkboxes to render
+    fn toggles(&self) -> Vec<&dyn crate::widget::Toggle> {
+        Vec::new()
+    }
+
+    /// Called to update switch and checkbox states
+    fn toggles_mut(&mut self) -> Vec<&mut dyn crate::widget::Toggle> {
+        Vec::new()
+    }
+
+    /// Called before rendering, return sliders to render
+    fn sliders(&self) -> Vec<&crate::widget::Slider> {
+        Vec::new()
+    }
+
+    /// Called to update slider states and values while dragged
+    fn sliders_mut(&mut self) -> Vec<&mut crate::widget::Slider> {
+        Vec::new()
+    }
+
+    /// Called before rendering, return progress bars to render (animate
+    /// them with `ProgressBar::update` in `update`)
+    fn progress_bars(&self) -> Vec<&crate::widget::ProgressBar> {
+        Vec::new()
+    }
+
+    /// Called before the first frame and whenever the window size changes
+    /// (in physical pixels), to position widgets (e.g. with a `Stack`)
+    fn layout(&mut self, _width: f32, _height: f32) {}
+
+    /// Text for screen readers to read out besides the widgets, e.g. what
+    /// the callout bubble says; changes are announced as they happen
+    fn accessible_text(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Called before rendering, return text inputs to render
+    fn text_inputs(&self) -> Vec<&crate::widget::TextInput> {
+        Vec::new()
+    }
+
+    /// Called to edit text inputs and lay out their text; must return the
+    /// same inputs in the same order as `text_inputs`
+    fn text_inputs_mut(&mut self) -> Vec<&mut crate::widget::TextInput> {
+        Vec::new()
+    }
+
+    /// Return the current skin to render (for animated skins)
+    /// If None, the window's static skin will be used
+    fn current_skin(&self) -> Option<&crate::Skin> {
+        None
+    }
+
+    /// Return true if the app needs continuous frame updates (for animations)
+    /// When true, the event loop will use Poll instead of Wait
+    fn needs_continuous_update(&self) -> bool {
+        self.current_skin().is_some()
+    }
+
+    /// Return true if the main window shows something new since the last
+    /// call (a skin frame advanced, a layer changed); polled once per frame.
+    /// Input and window state changes redraw on their own.
+    fn take_redraw_request(&mut self) -> bool {
+        self.needs_continuous_update()
+    }
+
+    /// Return an opacity that replaces the focus-based window opacity
+    /// (e.g. dimming at night), or None to keep the default behavior
+    fn opacity_override(&self) -> Option<f32> {
+        None
+    }
+
+    /// Return the (focused, unfocused) opacity, replacing the one the window
+    /// was built with (polled once per frame; None = keep it)
+    fn focus_opacity(&self) -> Option<(f32, f32)> {
+        None
+    }
+
+    /// Return true to mirror the skin horizontally
+    fn skin_flipped(&self) -> bool {
+        false
+    }
+
+    /// Return the vertical scale of the skin, anchored at its bottom edge
+    /// (e.g. breathing); 1.0 draws it as is
+    fn skin_scale(&self) -> f32 {
+        1.0
+    }
+
+    /// Return the color changes of the skin (None = its own colors)
+    fn recolor(&self) -> Option<&Recolor> {
+        None
+    }
+
+    /// Return the post effects of the skin, e.g. `EffectStack::params`
+    /// (polled once per frame)
+    fn skin_effects(&self) -> EffectParams {
+        EffectParams::default()
+    }
+
+    /// Return the skin file or persona folder to watch for changes, which
+    /// are reported as `GhostEvent::SkinReloaded` (None = no hot reload)
+    fn skin_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Return the config file to watch for changes, which are reported as
+    /// `GhostEvent::ConfigReloaded` (None = no hot reload)
+    fn config_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Return the menu to open when the window is right-clicked (None = no
+    /// menu; `GhostEvent::RightClicked` is sent either way)
+    fn context_menu(&self) -> Option<ContextMenu> {
+        None
+    }
+
+    /// Return a pending window move (polled once per frame)
+    fn take_move_request(&mut self) -> Option<MoveRequest> {
+        None
+    }
+
+    /// Return Some(false) to hide the window or Some(true) to show it again
+    /// (polled once per frame)
+    fn take_visibility_request(&mut self) -> Option<bool> {
+        None
+    }
+
+    /// Return the animation and its length for showing and hiding the
+    /// window (None = show and hide right away)
+    fn visibility_transition(&self) -> Option<(Transition, Duration)> {
+        None
+    }
+
+    /// Return true to bring the window to the front (polled once per frame)
+    fn take_focus_request(&mut self) -> bool {
+        false
+    }
+
+    /// Return true to run the movement engine (see
+    /// `GhostWindowBuilder::with_motion`), polled once per frame
+    fn motion_enabled(&self) -> bool {
+        false
+    }
+
+    /// Return true to receive `GhostEvent::CursorTracked` every frame,
+    /// even when the cursor is outside the window
+    fn tracks_cursor(&self) -> bool {
+        false
+    }
+
+    /// Return the target frames per second for animations (default: 30)
+    fn target_fps(&self) -> f32 {
+        30.0
+    }
+
+    /// Called before rendering to prepare GPU resources (callouts, etc.)
+    /// scale_factor is the display's DPI scale (1.0 for standard, 2.0 for Retina)
+    /// opacity is the current window opacity (0.0 to 1.0)
+    fn prepare(&mut self, _device: &wgpu::Device, _queue: &wgpu::Queue, _viewport: [f32; 2], _scale_factor: f32, _opacity: f32) {}
+
+    /// Called during rendering to render layers and text overlays
+    /// This is called after the main skin is rendered but before buttons
+    fn render_layers<'a>(
+        &'a mut self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _viewport: [f32; 2],
+        _render_pass: &mut wgpu::RenderPass<'a>,
+    ) {}
+}
+
+/// The app's widgets and the window's tooltip, for `ButtonRenderer`
+fn widgets<'a, A: GhostApp>(app: &'a A, window: &'a GhostWindow) -> crate::renderer::Widgets<'a> {
+    crate::renderer::Widgets {
+        buttons: app.buttons(),
+        toggles: app.toggles(),
+        sliders: app.sliders(),
+        progress_bars: app.progress_bars(),
+        text_inputs: app.text_inputs(),
+        tooltip: Some(&window.data.tooltip),
+        focused: window.data.focus.focused(),
+    }
+}
+
+/// Update the hover state of the app's widgets and drag the pressed
+/// slider, if any; `cursor` is None when it left the window
+fn hover_widgets<A: GhostApp>(app: &mut A, cursor: Option<(f32, f32)>, window_height: f32) {
+    let (cursor_x, cursor_y) = cursor.unwrap_or((-1.0, -1.0));
+    for button in app.buttons_mut() {
+        button.update_hover(cursor_x, cursor_y, window_height);
+    }
+    for toggle in app.toggles_mut() {
+        toggle.update_hover(cursor_x, cursor_y, window_height);
+    }
+    for input in app.text_inputs_mut() {
+        input.update_hover(cursor_x, cursor_y, window_height);
+    }
+    let mut changed = Vec::new();
+    for slider in app.sliders_mut() {
+        if cursor.is_some() && slider.drag(cursor_x, window_height) {
+            changed.push((slider.id(), slider.value()));
+        }
+        slider.update_hover(cursor_x, cursor_y, window_height);
+    }
+    for (id, value) in changed {
+        app.on_event(GhostEvent::SliderChanged(id, value));
+    }
+}
+
+/// Press the widget under the cursor; returns whether there was one
+fn press_widgets<A: GhostApp>(app: &mut A, cursor_x: f32, cursor_y: f32, window_height: f32) -> bool {
+    // Every input sees the press, so the others give up the keyboard
+    let mut pressed = false;
+    for input in app.text_inputs_mut() {
+        pressed |= input.handle_press(cursor_x, cursor_y, window_height);
+    }
+    let pressed = pressed
+        || app
+            .buttons_mut()
+            .into_iter()
+            .any(|button| button.handle_press(cursor_x, cursor_y, window_height))
+        || app
+            .toggles_mut()
+            .into_iter()
+            .any(|toggle| toggle.handle_press(cursor_x, cursor_y, window_height));
+    if pressed {
+        return true;
+    }
+
+    // A pressed slider jumps to the cursor
+    let slider = app.sliders_mut().into_iter().find_map(|slider| {
+        let before = slider.value();
+        slider
+            .handle_press(cursor_x, cursor_y, window_height)
+            .then(|| (slider.id(), slider.value(), slider.value() != before))
+    });
+    match slider {
+        Some((id, value, changed)) => {
+            if changed {
+                app.on_event(GhostEvent::SliderChanged(id, value));
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Release the pressed widget, reporting completed clicks and flipped
+/// toggles (sliders report changes while dragged)
+fn release_widgets<A: GhostApp>(app: &mut A, cursor_x: f32, cursor_y: f32, window_height: f32) {
+    for slider in app.sliders_mut() {
+        slider.handle_release(cursor_x, cursor_y, window_height);
+    }
+    for input in app.text_inputs_mut() {
+        input.handle_release(cursor_x, cursor_y, window_height);
+    }
+
+    // Collect first, the app is borrowed by its widgets
+    let clicked: Vec<_> = app
+        .buttons_mut()
+        .into_iter()
+        .filter_map(|button| button.handle_release(cursor_x, cursor_y, window_height).then(|| button.id()))
+        .collect();
+    let toggled: Vec<_> = app
+        .toggles_mut()
+        .into_iter()
+        .filter_map(|toggle| {
+            toggle
+                .handle_release(cursor_x, cursor_y, window_height)
+                .then(|| (toggle.id(), toggle.is_on()))
+        })
+        .collect();
+
+    for id in clicked {
+        app.on_event(GhostEvent::ButtonClicked(id));
+    }
+    for (id, on) in toggled {
+        app.on_event(GhostEvent::ToggleChanged(id, on));
+    }
+}
+
+/// Report right-clicks, double-clicks, scrolling and dropped files over
+/// the main window
+fn forward_pointer_input<A: GhostApp>(window: &mut GhostWindow, app: &mut A, event: &WindowEvent) {
+    match event {
+        WindowEvent::MouseInput { state: ElementState::Pressed, button, .. } => {
+            // The mouse takes over from the keyboard
+            if window.data.focus.clear() {
+                window.request_redraw();
+            }
+            let Some((x, y)) = window.logical_cursor_position() else { return };
+            match button {
+                MouseButton::Right => {
+                    app.on_event(GhostEvent::RightClicked(x, y));
+                    if let Some(menu) = app.context_menu().filter(|menu| !menu.is_empty()) {
+                        window.show_context_menu(&menu, Some((x, y)));
+                    }
+                }
+                MouseButton::Left if window.data.clicks.pressed([x, y], std::time::Instant::now()) => {
+                    app.on_event(GhostEvent::DoubleClicked(x, y));
+                }
+                _ => {}
+            }
+        }
+        WindowEvent::DroppedFile(path) => app.on_event(GhostEvent::FileDropped(path.clone())),
+        WindowEvent::MouseWheel { delta, .. } => {
+            let (x, y) = match delta {
+                MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+                MouseScrollDelta::PixelDelta(pixels) => {
+                    let scale = window.window().scale_factor() * SCROLL_LINE_PIXELS;
+                    ((pixels.x / scale) as f32, (pixels.y / scale) as f32)
+                }
+                _ => return,
+            };
+            app.on_event(GhostEvent::Scrolled(x, y));
+        }
+        _ => {}
+    }
+}
+
+/// Send keys and IME text to the focused text input, reporting edits and
+/// submissions; without one, keys move focus between buttons and press them
+fn forward_keyboard_input<A: GhostApp>(window: &mut GhostWindow, app: &mut A, event: &WindowEvent) {
+    let modifiers = window.data.modifiers;
+    let action = match event {
+        WindowEvent::ModifiersChanged(modifiers) => {
+            window.data.modifiers = *modifiers;
+            return;
+        }
+        WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+            if !app.text_inputs().iter().any(|input| input.is_focused()) {
+                match window.data.focus.handle_key(&event.logical_key, modifiers, &app.buttons()) {
+                    Some(FocusAction::Activated(id)) => app.on_event(GhostEvent::ButtonClicked(id)),
+                    Some(FocusAction::Moved) => window.request_redraw(),
+                    None => {}
+                }
+                return;
+            }
+            let Some(input) = app.text_inputs_mut().into_iter().find(|input| input.is_focused()) else { return };
+            input.handle_key(event, modifiers).map(|action| (input.id(), action, input.text().to_string()))
+        }
+        WindowEvent::ReceivedImeText(text) => {
+            let Some(input) = app.text_inputs_mut().into_iter().find(|input| input.is_focused()) else { return };
+            input
+                .insert(text)
+                .then(|| (input.id(), TextInputAction::Changed, input.text().to_string()))
+        }
+        _ => return,
+    };
+
+    match action {
+        Some((id, TextInputAction::Changed, text)) => app.on_event(GhostEvent::TextChanged(id, text)),
+        Some((id, TextInputAction::Submitted(text), _)) => app.on_event(GhostEvent::TextSubmitted(id, text)),
+        None => {}
+    }
+    window.request_redraw();
+}
+
+/// Do what a screen reader asked, reporting it like the same mouse or
+/// keyboard input would
+fn apply_access_action<A: GhostApp>(window: &mut GhostWindow, app: &mut A, action: AccessAction) {
+    match action {
+        AccessAction::Click(id) => app.on_event(GhostEvent::ButtonClicked(id)),
+        AccessAction::Focus(id) => window.data.focus.set_focused(Some(id)),
+        AccessAction::Toggle(id) => {
+            let Some(toggle) = app.toggles_mut().into_iter().find(|toggle| toggle.id() == id) else { return };
+            toggle.set_on(!toggle.is_on());
+            let on = toggle.is_on();
+            app.on_event(GhostEvent::ToggleChanged(id, on));
+        }
+        AccessAction::SetSlider(id, value) => {
+            let Some(slider) = app.sliders_mut().into_iter().find(|slider| slider.id() == id) else { return };
+            slider.set_value(value);
+            let value = slider.value();
+            app.on_event(GhostEvent::SliderChanged(id, value));
+        }
+        AccessAction::NudgeSlider(id, up) => {
+            let Some(slider) = app.sliders_mut().into_iter().find(|slider| slider.id() == id) else { return };
+            let nudge = accessibility::slider_nudge(slider.min(), slider.max(), slider.step());
+            slider.set_value(if up { slider.value() + nudge } else { slider.value() - nudge });
+            let value = slider.value();
+            app.on_event(GhostEvent::SliderChanged(id, value));
+        }
+        AccessAction::FocusInput(id) => {
+            for input in app.text_inputs_mut() {
+                input.set_focused(input.id() == id);
+            }
+        }
+        AccessAction::SetText(id, text) => {
+            let Some(input) = app.text_inputs_mut().into_iter().find(|input| input.id() == id) else { return };
+            input.set_text(text);
+            let text = input.text().to_string();
+            app.on_event(GhostEvent::TextChanged(id, text));
+        }
+    }
+    window.request_redraw();
+}
+
+/// Apply per-frame window state driven by the app: opacity, flip, cursor
+/// tracking, monitor changes and animated moves.
+///
+/// Returns the new window position if an animated move advanced.
+fn apply_app_state<A: GhostApp>(
+    window: &mut GhostWindow,
+    app: &mut A,
+    delta: f32,
+    last_screen: &mut Option<ScreenRect>,
+) -> Option<(i32, i32)> {
+    // App-driven opacity (e.g. time-of-day dimming)
+    let opacity_override = app.opacity_override();
+    if opacity_override != window.opacity_override() {
+        window.set_opacity_override(opacity_override);
+        window.request_redraw();
+    }
+
+    // Opacity levels changed by the app (e.g. a reloaded config)
+    if let Some((focused, unfocused)) = app.focus_opacity() {
+        let config = &window.data.config;
+        if (focused, unfocused) != (config.opacity_focused, config.opacity_unfocused) {
+            window.set_opacity_focused(focused);
+            window.set_opacity_unfocused(unfocused);
+            window.request_redraw();
         }
     }
 
-    /// Check if the window is draggable.
-    pub fn is_draggable(&self) -> bool {
-        self.data.config.draggable
+    // Mirror the skin when the app asks for it
+    let flipped = app.skin_flipped();
+    if flipped != window.is_flipped() {
+        window.set_flipped(flipped);
+        window.request_redraw();
     }
 
-    /// Check if the window is focused.
-    pub fn is_focused(&self) -> bool {
-        self.data.is_focused
+    // Scale the skin when the app asks for it
+    let skin_scale = app.skin_scale();
+    if skin_scale != window.skin_scale() {
+        window.set_skin_scale(skin_scale);
+        window.request_redraw();
     }
 
-    /// Start dragging the window.
-    pub fn drag(&self) {
-        let _ = self.data.window.drag_window();
+    // Recolor the skin when the app asks for it
+    let recolor = app.recolor().cloned().unwrap_or_default();
+    if &recolor != window.recolor() {
+        window.set_recolor(recolor);
+        window.request_redraw();
     }
 
-    /// Get the window's outer position (screen coordinates).
-    pub fn outer_position(&self) -> Option<(i32, i32)> {
-        self.data.window.outer_position().ok().map(|p| (p.x, p.y))
+    // Glow, dissolve, pixelate or gray out the skin when the app asks for it
+    let skin_effects = app.skin_effects();
+    if skin_effects != window.skin_effects() {
+        window.set_skin_effects(skin_effects);
+        window.request_redraw();
     }
 
-    /// Get the current aspect ratio.
-    pub fn aspect_ratio(&self) -> f32 {
-        self.data.aspect_ratio
+    // Pick up edits to the skin on disk
+    if window.poll_skin_changes(app.skin_path()) {
+        app.on_event(GhostEvent::SkinReloaded);
+        window.request_redraw();
     }
 
-    /// Set whether to maintain aspect ratio during resize.
-    pub fn set_maintain_aspect_ratio(&mut self, maintain: bool) {
-        self.data.config.maintain_aspect_ratio = maintain;
+    // And to the app's config
+    if window.poll_config_changes(app.config_path()) {
+        app.on_event(GhostEvent::ConfigReloaded);
+        window.request_redraw();
     }
 
-    /// Set the alpha threshold for hit testing.
-    pub fn set_alpha_threshold(&mut self, threshold: u8) {
-        self.data.config.alpha_threshold = threshold;
+    // Keep widgets in place as the window changes size
+    let size = window.window().inner_size();
+    if window.data.laid_out_size != Some((size.width, size.height)) {
+        window.data.laid_out_size = Some((size.width, size.height));
+        app.layout(size.width as f32, size.height as f32);
+        window.request_redraw();
     }
-}
 
-/// Events that can be emitted by the ghost window
-#[derive(Debug, Clone)]
-pub enum GhostEvent {
-    /// A button was clicked
-    ButtonClicked(crate::widget::ButtonId),
-    /// Window was focused or unfocused
-    FocusChanged(bool),
-    /// Window was resized
-    Resized(u32, u32),
-    /// Window was moved (x, y in screen coordinates)
-    Moved(i32, i32),
-    /// Frame update (for animations)
-    Update(f32), // delta time in seconds
-}
+    // Fade, slide or scale widgets in and out (progress bars and text
+    // inputs are updated by the app and below)
+    for button in app.buttons_mut() {
+        if button.update(delta) {
+            window.request_redraw();
+        }
+    }
+    for toggle in app.toggles_mut() {
+        if toggle.update(delta) {
+            window.request_redraw();
+        }
+    }
+    for slider in app.sliders_mut() {
+        if slider.update(delta) {
+            window.request_redraw();
+        }
+    }
 
-/// GPU resources for app initialization
-pub struct GpuResources<'a> {
-    pub device: &'a wgpu::Device,
-    pub queue: &'a wgpu::Queue,
-    pub format: wgpu::TextureFormat,
-}
+    // Show the hovered button's tooltip once the cursor rests on it
+    let cursor = window.data.cursor_position.map(|p| [p.x as f32, p.y as f32]);
+    let hovered = cursor.and_then(|_| {
+        app.buttons()
+            .into_iter()
+            .find(|button| button.is_visible() && button.state() == ButtonState::Hover)
+            .and_then(|button| button.tooltip())
+    });
+    if window.data.tooltip.update(hovered, cursor.unwrap_or_default(), delta) {
+        window.request_redraw();
+    }
 
-/// Application trait for handling ghost window events
-pub trait GhostApp {
-    /// Called once when GPU resources are available (for initializing renderers)
-    fn init_gpu(&mut self, _gpu: GpuResources<'_>) {}
+    // Carry out screen reader requests, then tell it what changed
+    if let Some(mut access) = window.data.accessibility.take() {
+        while let Some(action) = access.poll_action() {
+            apply_access_action(window, app, action);
+        }
+        access.update(&widgets(app, window), &app.accessible_text(), size.height as f32);
+        access.set_window_state(window.data.is_focused, window.outer_rect());
+        window.data.accessibility = Some(access);
+    }
 
-    /// Called each frame to update state (e.g., animations)
-    /// delta is the time since the last frame in seconds
-    fn update(&mut self, _delta: f32) {}
+    // Blink the caret and keep the IME candidate window next to it
+    let mut ime_position = None;
+    for input in app.text_inputs_mut() {
+        if input.update(delta) {
+            window.request_redraw();
+        }
+        ime_position = ime_position.or(input.ime_position(size.height as f32));
+    }
+    if ime_position != window.data.ime_position {
+        window.data.ime_position = ime_position;
+        if let Some((x, y)) = ime_position {
+            window.window().set_ime_position(PhysicalPosition::new(x as f64, y as f64));
+        }
+    }
 
-    /// Return true if the app wants to quit
-    fn should_quit(&self) -> bool {
-        false
+    // Report context menu choices
+    while let Some(id) = context_menu::poll_selection() {
+        app.on_event(GhostEvent::ContextMenuSelected(id));
+    }
+    while let Some(id) = context_menu::poll_tray_selection() {
+        app.on_event(GhostEvent::TrayMenuSelected(id));
     }
 
-    /// Called when an event occurs
-    fn on_event(&mut self, event: GhostEvent);
+    // Report global shortcuts
+    while let Some(id) = hotkeys::poll_pressed() {
+        app.on_event(GhostEvent::HotkeyPressed(id));
+    }
 
-    /// Called before rendering, return buttons to render
-    fn buttons(&self) -> Vec<&crate::widget::Button> {
-        Vec::new()
+    // Report the global cursor position
+    if app.tracks_cursor() {
+        if let Some((x, y)) = window.global_cursor_position() {
+            app.on_event(GhostEvent::CursorTracked(x, y));
+        }
     }
 
-    /// Called to update button states (for hover effects, etc.)
-    fn buttons_mut(&mut self) -> Vec<&mut crate::widget::Button> {
-        Vec::new()
+    // Report monitor changes
+    if let (Some(screen), Some(rect)) = (window.screen_rect(), window.outer_rect()) {
+        if *last_screen != Some(screen) {
+            *last_screen = Some(screen);
+            app.on_event(GhostEvent::ScreenChanged { screen, window: rect });
+        }
     }
 
-    /// Return the current skin to render (for animated skins)
-    /// If None, the window's static skin will be used
-    fn current_skin(&self) -> Option<&crate::Skin> {
-        None
+    if let Some(visible) = app.take_visibility_request() {
+        match app.visibility_transition() {
+            Some((transition, duration)) if visible => window.show_animated(transition, duration),
+            Some((transition, duration)) => window.hide_animated(transition, duration),
+            None => window.set_visible(visible),
+        }
     }
 
-    /// Return true if the app needs continuous frame updates (for animations)
-    /// When true, the event loop will use Poll instead of Wait
-    fn needs_continuous_update(&self) -> bool {
-        self.current_skin().is_some()
+    if app.take_focus_request() {
+        window.focus();
     }
 
-    /// Return the target frames per second for animations (default: 30)
-    fn target_fps(&self) -> f32 {
-        30.0
+    // Once a drag ends: throw the mascot or snap to an edge
+    let motion_enabled = app.motion_enabled();
+    if let Some(velocity) = window.advance_drag(delta) {
+        let scale = window.window().scale_factor() as f32;
+        match window.data.motion {
+            Some(ref mut motion) if motion_enabled => motion.throw(velocity, scale),
+            _ => {
+                if let Some(edge) = window.snap_to_edge() {
+                    app.on_event(GhostEvent::Docked(edge));
+                }
+            }
+        }
+    }
+    if let Some(state) = window.advance_motion(delta, motion_enabled) {
+        app.on_event(GhostEvent::MotionChanged(state));
     }
 
-    /// Called before rendering to prepare GPU resources (callouts, etc.)
-    /// scale_factor is the display's DPI scale (1.0 for standard, 2.0 for Retina)
-    /// opacity is the current window opacity (0.0 to 1.0)
-    fn prepare(&mut self, _device: &wgpu::Device, _queue: &wgpu::Queue, _viewport: [f32; 2], _scale_factor: f32, _opacity: f32) {}
+    // Animated moves
+    match app.take_move_request() {
+        Some(MoveRequest::To { x, y, duration, easing }) => window.move_to_animated(x, y, duration, easing),
+        Some(MoveRequest::Stop) => {
+            window.stop_move();
+        }
+        None => {}
+    }
+    // Show and hide animations
+    let slid = window.advance_transition(delta);
 
-    /// Called during rendering to render layers and text overlays
-    /// This is called after the main skin is rendered but before buttons
-    fn render_layers<'a>(
-        &'a mut self,
-        _device: &wgpu::Device,
-        _queue: &wgpu::Queue,
-        _viewport: [f32; 2],
-        _render_pass: &mut wgpu::RenderPass<'a>,
-    ) {}
+    let Some((position, arrived)) = window.advance_move(delta) else { return slid };
+    if arrived {
+        match window.data.docking.take() {
+            Some(edge) => app.on_event(GhostEvent::Docked(edge)),
+            None => app.on_event(GhostEvent::MoveFinished),
+        }
+    }
+    Some(position)
 }
 
 /// Run the ghost window event loop with custom event handling.
@@ -650,6 +2022,7 @@ pub fn run_with_app<A: GhostApp + 'static>(
     use std::time::Instant;
 
     let mut last_frame = Instant::now();
+    let mut last_screen: Option<ScreenRect> = None;
     let mut button_renderer: Option<crate::renderer::ButtonRenderer> = None;
     let mut gpu_initialized = false;
 
@@ -660,6 +2033,11 @@ pub fn run_with_app<A: GhostApp + 'static>(
         let window_size = ghost_window.window().inner_size();
         let window_height = window_size.height as f32;
 
+        if let Event::WindowEvent { ref event, .. } = event {
+            forward_pointer_input(&mut ghost_window, &mut app, event);
+            forward_keyboard_input(&mut ghost_window, &mut app, event);
+        }
+
         match event {
             Event::WindowEvent {
                 event: WindowEvent::Focused(focused),
@@ -675,13 +2053,14 @@ pub fn run_with_app<A: GhostApp + 'static>(
                 ..
             } => {
                 ghost_window.handle_cursor_moved(position);
+                if ghost_window.detect_rub() {
+                    app.on_event(GhostEvent::Rubbed);
+                }
 
-                // Update button hover states
+                // Update widget hover states
                 let cursor_x = position.x as f32;
                 let cursor_y = position.y as f32;
-                for button in app.buttons_mut() {
-                    button.update_hover(cursor_x, cursor_y, window_height);
-                }
+                hover_widgets(&mut app, Some((cursor_x, cursor_y)), window_height);
 
                 ghost_window.request_redraw();
             }
@@ -692,10 +2071,8 @@ pub fn run_with_app<A: GhostApp + 'static>(
             } => {
                 ghost_window.handle_cursor_left();
 
-                // Reset button states
-                for button in app.buttons_mut() {
-                    button.update_hover(-1.0, -1.0, window_height);
-                }
+                // Reset widget states
+                hover_widgets(&mut app, None, window_height);
             }
 
             Event::WindowEvent {
@@ -710,16 +2087,12 @@ pub fn run_with_app<A: GhostApp + 'static>(
                     let cursor_x = cursor_pos.x as f32;
                     let cursor_y = cursor_pos.y as f32;
 
-                    // Check if any button was pressed
-                    let mut button_pressed = false;
-                    for button in app.buttons_mut() {
-                        if button.handle_press(cursor_x, cursor_y, window_height) {
-                            button_pressed = true;
-                            break;
-                        }
-                    }
+                    let button_pressed = press_widgets(&mut app, cursor_x, cursor_y, window_height);
 
                     if !button_pressed && ghost_window.should_handle_click() && ghost_window.is_draggable() {
+                        if ghost_window.stop_move() {
+                            app.on_event(GhostEvent::MoveInterrupted);
+                        }
                         ghost_window.drag();
                     }
 
@@ -735,27 +2108,12 @@ pub fn run_with_app<A: GhostApp + 'static>(
                 },
                 ..
             } => {
+                ghost_window.handle_drag_released();
                 if let Some(cursor_pos) = ghost_window.cursor_position() {
                     let cursor_x = cursor_pos.x as f32;
                     let cursor_y = cursor_pos.y as f32;
 
-                    // Check if any button was released (clicked) - collect IDs first
-                    let clicked_ids: Vec<_> = app
-                        .buttons_mut()
-                        .iter_mut()
-                        .filter_map(|button| {
-                            if button.handle_release(cursor_x, cursor_y, window_height) {
-                                Some(button.id())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-
-                    // Then emit events
-                    for id in clicked_ids {
-                        app.on_event(GhostEvent::ButtonClicked(id));
-                    }
+                    release_widgets(&mut app, cursor_x, cursor_y, window_height);
 
                     ghost_window.request_redraw();
                 }
@@ -784,6 +2142,9 @@ pub fn run_with_app<A: GhostApp + 'static>(
                 app.update(delta);
                 app.on_event(GhostEvent::Update(delta));
 
+                // Opacity, flip, cursor tracking and animated moves
+                apply_app_state(&mut ghost_window, &mut app, delta, &mut last_screen);
+
                 // Check if app wants to quit
                 if app.should_quit() {
                     *control_flow = ControlFlow::Exit;
@@ -819,8 +2180,8 @@ pub fn run_with_app<A: GhostApp + 'static>(
                 if let (Some(ref mut btn_renderer), Some(ref renderer)) =
                     (&mut button_renderer, &ghost_window.renderer)
                 {
-                    let buttons: Vec<&crate::widget::Button> = app.buttons();
-                    btn_renderer.prepare(renderer.device(), renderer.queue(), &buttons, viewport);
+                    btn_renderer.layout_text_inputs(app.text_inputs_mut());
+                    btn_renderer.prepare(renderer.device(), renderer.queue(), &widgets(&app, &ghost_window), viewport);
                 }
 
                 // Let app prepare its rendering (callouts, etc.)
@@ -857,6 +2218,7 @@ pub fn run_with_app<A: GhostApp + 'static>(
                 *control_flow = ControlFlow::Exit;
             }
 
+            Event::LoopDestroyed => app.on_exit(),
             _ => (),
         }
     });
@@ -965,6 +2327,116 @@ pub trait CalloutApp {
 
     /// Called on update (for animations). Returns true if redraw is needed.
     fn update(&mut self, _delta: f32) -> bool { false }
+
+    /// Whether the bubble takes clicks; the rest of the window stays
+    /// click-through
+    fn interactive(&self) -> bool { false }
+
+    /// Whether a point (physical pixels in the callout window) is on the bubble
+    fn hit_test(&self, _x: f32, _y: f32) -> bool { false }
+
+    /// The bubble was clicked. Returns true if redraw is needed.
+    fn clicked(&mut self) -> bool { false }
+
+    /// Size and place the window should have to fit the bubble (None keeps
+    /// it as it is)
+    fn layout(&self) -> Option<CalloutLayout> { None }
+
+    /// Whether the window flips to the mascot's other side where it would
+    /// run off the screen
+    fn keep_on_screen(&self) -> bool { false }
+
+    /// The window was placed at `offset` (logical pixels) from the main
+    /// window, flipped as given to stay on screen
+    fn placed(&mut self, _offset: [f32; 2], _flip: CalloutFlip) {}
+}
+
+/// Move the callout window to `offset` from the main window at `main_pos`.
+/// If the app keeps it on screen, it is flipped to the main window's other
+/// side where it would run off the monitor; a main window kept in the work
+/// area keeps the callout in it too.
+fn place_callout_window<C: CalloutApp>(
+    callout_window: &GhostWindow,
+    main_window: &GhostWindow,
+    main_pos: (i32, i32),
+    offset: [i32; 2],
+    callout_app: &mut C,
+    scale_factor: f64,
+) {
+    let mut position = (main_pos.0 + offset[0], main_pos.1 + offset[1]);
+    let size = callout_window.data.window.outer_size();
+    let size = (size.width, size.height);
+    if callout_app.keep_on_screen() {
+        if let (Some(main), Some(screen)) = (main_window.outer_rect(), main_window.screen_rect()) {
+            let main = ScreenRect { x: main_pos.0, y: main_pos.1, ..main };
+            let (placed, flip) = place_callout(main, size, offset, screen);
+            position = placed;
+            callout_app.placed(
+                [
+                    ((placed.0 - main_pos.0) as f64 / scale_factor) as f32,
+                    ((placed.1 - main_pos.1) as f64 / scale_factor) as f32,
+                ],
+                flip,
+            );
+        }
+    }
+    if let Some(area) = main_window.linked_area() {
+        position = keep_inside(position, size, area);
+    }
+    callout_window.set_position(position.0, position.1);
+}
+
+/// Where the callout window goes to fit its bubble
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalloutLayout {
+    /// Window size in logical pixels
+    pub size: [f32; 2],
+    /// Window position relative to the callout offset, in logical pixels
+    pub origin: [f32; 2],
+}
+
+/// Resize the callout window to `layout`; returns its new offset from the
+/// main window in physical pixels
+fn fit_callout_window(
+    callout_window: &mut GhostWindow,
+    layout: CalloutLayout,
+    callout_offset: [i32; 2],
+    scale_factor: f64,
+) -> [i32; 2] {
+    let width = (layout.size[0] as f64 * scale_factor).ceil() as u32;
+    let height = (layout.size[1] as f64 * scale_factor).ceil() as u32;
+    if (width, height) != callout_window.data.last_size {
+        callout_window.data.window.set_inner_size(PhysicalSize::new(width, height));
+        callout_window.handle_resize(width, height);
+    }
+    [
+        ((callout_offset[0] as f32 + layout.origin[0]) as f64 * scale_factor).round() as i32,
+        ((callout_offset[1] as f32 + layout.origin[1]) as f64 * scale_factor).round() as i32,
+    ]
+}
+
+/// Make an interactive callout window take clicks only over the bubble
+fn update_callout_click_through<C: CalloutApp>(callout_window: &mut GhostWindow, callout_app: &C) {
+    if !callout_app.interactive() {
+        return;
+    }
+    let over_bubble = callout_window
+        .cursor_in_window()
+        .is_some_and(|[x, y]| callout_app.hit_test(x, y));
+    callout_window.set_ignore_cursor_events(!over_bubble);
+}
+
+/// Pass a click on the callout window to the app if it hit the bubble
+fn handle_callout_click<C: CalloutApp>(callout_window: &GhostWindow, callout_app: &mut C) {
+    if !callout_app.interactive() {
+        return;
+    }
+    let hit = callout_window
+        .cursor_in_window()
+        .is_some_and(|[x, y]| callout_app.hit_test(x, y));
+    if hit && callout_app.clicked() {
+        callout_window.request_redraw();
+    }
 }
 
 /// Run the ghost window with a linked callout window.
@@ -987,20 +2459,29 @@ pub fn run_with_app_and_callout<A, C>(
     let callout_window_id = callout_window.window().id();
 
     let mut last_frame = Instant::now();
+    let mut last_screen: Option<ScreenRect> = None;
     let mut button_renderer: Option<crate::renderer::ButtonRenderer> = None;
     let mut main_gpu_initialized = false;
     let mut callout_gpu_initialized = false;
 
     // Get scale factor for converting logical to physical offsets
     let scale_factor = main_window.window().scale_factor();
-    let scaled_callout_offset = [
+    let mut callout_layout: Option<CalloutLayout> = None;
+    let mut scaled_callout_offset = [
         (callout_offset[0] as f64 * scale_factor) as i32,
         (callout_offset[1] as f64 * scale_factor) as i32,
     ];
 
     // Position callout window initially
     if let Some((x, y)) = main_window.outer_position() {
-        callout_window.set_position(x + scaled_callout_offset[0], y + scaled_callout_offset[1]);
+        place_callout_window(
+            &callout_window,
+            &main_window,
+            (x, y),
+            scaled_callout_offset,
+            &mut callout_app,
+            scale_factor,
+        );
     }
 
     event_loop.run(move |event, _, control_flow| {
@@ -1010,6 +2491,8 @@ pub fn run_with_app_and_callout<A, C>(
             Event::WindowEvent { window_id, event, .. } if window_id == main_window_id => {
                 let window_size = main_window.window().inner_size();
                 let window_height = window_size.height as f32;
+                forward_pointer_input(&mut main_window, &mut app, &event);
+                forward_keyboard_input(&mut main_window, &mut app, &event);
 
                 match event {
                     WindowEvent::Focused(focused) => {
@@ -1020,19 +2503,18 @@ pub fn run_with_app_and_callout<A, C>(
 
                     WindowEvent::CursorMoved { position, .. } => {
                         main_window.handle_cursor_moved(position);
+                        if main_window.detect_rub() {
+                            app.on_event(GhostEvent::Rubbed);
+                        }
                         let cursor_x = position.x as f32;
                         let cursor_y = position.y as f32;
-                        for button in app.buttons_mut() {
-                            button.update_hover(cursor_x, cursor_y, window_height);
-                        }
+                        hover_widgets(&mut app, Some((cursor_x, cursor_y)), window_height);
                         main_window.request_redraw();
                     }
 
                     WindowEvent::CursorLeft { .. } => {
                         main_window.handle_cursor_left();
-                        for button in app.buttons_mut() {
-                            button.update_hover(-1.0, -1.0, window_height);
-                        }
+                        hover_widgets(&mut app, None, window_height);
                     }
 
                     WindowEvent::MouseInput {
@@ -1043,14 +2525,11 @@ pub fn run_with_app_and_callout<A, C>(
                         if let Some(cursor_pos) = main_window.cursor_position() {
                             let cursor_x = cursor_pos.x as f32;
                             let cursor_y = cursor_pos.y as f32;
-                            let mut button_pressed = false;
-                            for button in app.buttons_mut() {
-                                if button.handle_press(cursor_x, cursor_y, window_height) {
-                                    button_pressed = true;
-                                    break;
-                                }
-                            }
+                            let button_pressed = press_widgets(&mut app, cursor_x, cursor_y, window_height);
                             if !button_pressed && main_window.should_handle_click() && main_window.is_draggable() {
+                                if main_window.stop_move() {
+                                    app.on_event(GhostEvent::MoveInterrupted);
+                                }
                                 main_window.drag();
                             }
                             main_window.request_redraw();
@@ -1062,23 +2541,11 @@ pub fn run_with_app_and_callout<A, C>(
                         button: MouseButton::Left,
                         ..
                     } => {
+                        main_window.handle_drag_released();
                         if let Some(cursor_pos) = main_window.cursor_position() {
                             let cursor_x = cursor_pos.x as f32;
                             let cursor_y = cursor_pos.y as f32;
-                            let clicked_ids: Vec<_> = app
-                                .buttons_mut()
-                                .iter_mut()
-                                .filter_map(|button| {
-                                    if button.handle_release(cursor_x, cursor_y, window_height) {
-                                        Some(button.id())
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect();
-                            for id in clicked_ids {
-                                app.on_event(GhostEvent::ButtonClicked(id));
-                            }
+                            release_widgets(&mut app, cursor_x, cursor_y, window_height);
                             main_window.request_redraw();
                         }
                     }
@@ -1090,9 +2557,13 @@ pub fn run_with_app_and_callout<A, C>(
 
                     WindowEvent::Moved(position) => {
                         // Update callout window position to follow main window
-                        callout_window.set_position(
-                            position.x + scaled_callout_offset[0],
-                            position.y + scaled_callout_offset[1],
+                        place_callout_window(
+                            &callout_window,
+                            &main_window,
+                            (position.x, position.y),
+                            scaled_callout_offset,
+                            &mut callout_app,
+                            scale_factor,
                         );
                         app.on_event(GhostEvent::Moved(position.x, position.y));
                     }
@@ -1110,6 +2581,17 @@ pub fn run_with_app_and_callout<A, C>(
                     WindowEvent::CloseRequested => {
                         *control_flow = ControlFlow::Exit;
                     }
+                    WindowEvent::Resized(size) => {
+                        callout_window.handle_resize(size.width, size.height);
+                        callout_window.request_redraw();
+                    }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        handle_callout_click(&callout_window, &mut callout_app);
+                    }
                     _ => {}
                 }
             }
@@ -1122,6 +2604,20 @@ pub fn run_with_app_and_callout<A, C>(
                 app.update(delta);
                 app.on_event(GhostEvent::Update(delta));
                 callout_app.update(delta);
+                update_callout_click_through(&mut callout_window, &callout_app);
+
+                // Opacity, flip, cursor tracking and animated moves
+                if let Some((x, y)) = apply_app_state(&mut main_window, &mut app, delta, &mut last_screen) {
+                    // Keep the callout attached while gliding
+                    place_callout_window(
+                        &callout_window,
+                        &main_window,
+                        (x, y),
+                        scaled_callout_offset,
+                        &mut callout_app,
+                        scale_factor,
+                    );
+                }
 
                 // Check if app wants to quit
                 if app.should_quit() {
@@ -1157,8 +2653,8 @@ pub fn run_with_app_and_callout<A, C>(
                 if let (Some(ref mut btn_renderer), Some(ref renderer)) =
                     (&mut button_renderer, &main_window.renderer)
                 {
-                    let buttons: Vec<&crate::widget::Button> = app.buttons();
-                    btn_renderer.prepare(renderer.device(), renderer.queue(), &buttons, viewport);
+                    btn_renderer.layout_text_inputs(app.text_inputs_mut());
+                    btn_renderer.prepare(renderer.device(), renderer.queue(), &widgets(&app, &main_window), viewport);
                 }
 
                 if let Some(ref renderer) = main_window.renderer {
@@ -1194,8 +2690,27 @@ pub fn run_with_app_and_callout<A, C>(
 
                 // Render callout window
                 let _ = callout_window.render_callout(&callout_app);
+
+                // Fit the window to the bubble once it has been laid out
+                if let Some(layout) = callout_app.layout().filter(|l| callout_layout != Some(*l)) {
+                    callout_layout = Some(layout);
+                    scaled_callout_offset =
+                        fit_callout_window(&mut callout_window, layout, callout_offset, scale_factor);
+                    if let Some((x, y)) = main_window.outer_position() {
+                        place_callout_window(
+                            &callout_window,
+                            &main_window,
+                            (x, y),
+                            scaled_callout_offset,
+                            &mut callout_app,
+                            scale_factor,
+                        );
+                    }
+                    callout_window.request_redraw();
+                }
             }
 
+            Event::LoopDestroyed => app.on_exit(),
             _ => (),
         }
     });
@@ -1217,6 +2732,15 @@ pub trait ExtraWindow {
     fn is_visible(&self) -> bool;
     /// Set window position (for following main window)
     fn set_position(&self, x: i32, y: i32);
+    /// Outer size in physical pixels, to keep it inside the work area
+    fn outer_size(&self) -> Option<(u32, u32)> {
+        None
+    }
+    /// New offset (logical pixels) to follow the main window at, e.g. after
+    /// a resize (polled once per frame; ignored by free-floating windows)
+    fn take_offset_request(&mut self) -> Option<[i32; 2]> {
+        None
+    }
     /// Bring window to front (when main window is focused)
     fn bring_to_front(&self);
 }
@@ -1226,54 +2750,136 @@ pub trait ExtraWindow {
 /// The callout window follows the main window, positioned at the given offset.
 /// The extra window also follows the main window with its own offset.
 pub fn run_with_app_callout_and_extra<A, C, E>(
+    main_window: GhostWindow,
+    callout_window: GhostWindow,
+    callout_offset: [i32; 2],
+    extra_offset: [i32; 2],
+    event_loop: EventLoop<()>,
+    app: A,
+    callout_app: C,
+    extra_window: Option<E>,
+) where
+    A: GhostApp + 'static,
+    C: CalloutApp + 'static,
+    E: ExtraWindow + 'static,
+{
+    let extras = extra_window
+        .map(|extra| LinkedWindow::new(extra).with_offset(extra_offset))
+        .into_iter()
+        .collect();
+    run_with_app_callout_and_extras(
+        main_window,
+        callout_window,
+        callout_offset,
+        event_loop,
+        app,
+        callout_app,
+        extras,
+    );
+}
+
+/// An extra window managed by the event loop, optionally following the main window
+pub struct LinkedWindow {
+    pub window: Box<dyn ExtraWindow>,
+    /// Offset from the main window (logical pixels); None floats freely
+    pub offset: Option<[i32; 2]>,
+}
+
+impl LinkedWindow {
+    /// A free-floating extra window
+    pub fn new<E: ExtraWindow + 'static>(window: E) -> Self {
+        Self {
+            window: Box::new(window),
+            offset: None,
+        }
+    }
+
+    /// Follow the main window at the given offset
+    pub fn with_offset(mut self, offset: [i32; 2]) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Move next to the main window (if linked), inside `area` if given;
+    /// `only_visible` skips hidden windows
+    fn follow(&self, main_pos: (i32, i32), area: Option<ScreenRect>, only_visible: bool) {
+        if let Some(offset) = self.offset {
+            if !only_visible || self.window.is_visible() {
+                let mut position = (main_pos.0 + offset[0], main_pos.1 + offset[1]);
+                if let (Some(area), Some(size)) = (area, self.window.outer_size()) {
+                    position = keep_inside(position, size, area);
+                }
+                self.window.set_position(position.0, position.1);
+            }
+        }
+    }
+}
+
+/// Run the ghost window with a linked callout window and any number of extra
+/// windows (chat, debug console).
+///
+/// The callout window follows the main window, positioned at the given offset.
+/// Extra windows with an offset follow the main window too; the others float freely.
+pub fn run_with_app_callout_and_extras<A, C>(
     mut main_window: GhostWindow,
     mut callout_window: GhostWindow,
     callout_offset: [i32; 2],
-    extra_offset: [i32; 2],
     event_loop: EventLoop<()>,
     mut app: A,
     mut callout_app: C,
-    mut extra_window: Option<E>,
+    mut extras: Vec<LinkedWindow>,
 ) where
     A: GhostApp + 'static,
     C: CalloutApp + 'static,
-    E: ExtraWindow + 'static,
 {
     use std::time::Instant;
 
     let main_window_id = main_window.window().id();
     let callout_window_id = callout_window.window().id();
-    let extra_window_id = extra_window.as_ref().map(|e| e.window_id());
+    let extra_ids: Vec<_> = extras.iter().map(|e| e.window.window_id()).collect();
 
     let mut last_frame = Instant::now();
+    let mut last_screen: Option<ScreenRect> = None;
     let mut button_renderer: Option<crate::renderer::ButtonRenderer> = None;
     let mut main_gpu_initialized = false;
     let mut callout_gpu_initialized = false;
 
     // Track main window position for extra window positioning
     let mut main_pos: (i32, i32) = main_window.outer_position().unwrap_or((0, 0));
-    let mut extra_was_visible = false;
+    let mut extra_was_visible = vec![false; extras.len()];
 
     // Get scale factor for converting logical to physical offsets
     let scale_factor = main_window.window().scale_factor();
-    let scaled_callout_offset = [
+    let mut callout_layout: Option<CalloutLayout> = None;
+    let mut scaled_callout_offset = [
         (callout_offset[0] as f64 * scale_factor) as i32,
         (callout_offset[1] as f64 * scale_factor) as i32,
     ];
-    let scaled_extra_offset = [
-        (extra_offset[0] as f64 * scale_factor) as i32,
-        (extra_offset[1] as f64 * scale_factor) as i32,
-    ];
+    for extra in &mut extras {
+        extra.offset = extra.offset.map(|offset| {
+            [
+                (offset[0] as f64 * scale_factor) as i32,
+                (offset[1] as f64 * scale_factor) as i32,
+            ]
+        });
+    }
 
     log::debug!(
-        "Scale factor: {}, callout_offset: {:?} -> {:?}, extra_offset: {:?} -> {:?}",
-        scale_factor, callout_offset, scaled_callout_offset, extra_offset, scaled_extra_offset
+        "Scale factor: {}, callout_offset: {:?} -> {:?}",
+        scale_factor, callout_offset, scaled_callout_offset
     );
 
-    // Position callout and extra windows initially
-    callout_window.set_position(main_pos.0 + scaled_callout_offset[0], main_pos.1 + scaled_callout_offset[1]);
-    if let Some(ref extra) = extra_window {
-        extra.set_position(main_pos.0 + scaled_extra_offset[0], main_pos.1 + scaled_extra_offset[1]);
+    // Position callout and linked extra windows initially
+    place_callout_window(
+        &callout_window,
+        &main_window,
+        main_pos,
+        scaled_callout_offset,
+        &mut callout_app,
+        scale_factor,
+    );
+    for extra in &extras {
+        extra.follow(main_pos, main_window.linked_area(), false);
     }
 
     event_loop.run(move |event, _, control_flow| {
@@ -1283,6 +2889,8 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
             Event::WindowEvent { window_id, event, .. } if window_id == main_window_id => {
                 let window_size = main_window.window().inner_size();
                 let window_height = window_size.height as f32;
+                forward_pointer_input(&mut main_window, &mut app, &event);
+                forward_keyboard_input(&mut main_window, &mut app, &event);
 
                 match event {
                     WindowEvent::Focused(focused) => {
@@ -1290,29 +2898,29 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                         app.on_event(GhostEvent::FocusChanged(focused));
                         main_window.request_redraw();
 
-                        // Bring extra window to front when main window is focused
+                        // Bring linked extra windows to front when main window is focused
                         if focused {
-                            if let Some(ref extra) = extra_window {
-                                extra.bring_to_front();
+                            for extra in extras.iter().filter(|e| e.offset.is_some()) {
+                                extra.window.bring_to_front();
                             }
                         }
                     }
 
                     WindowEvent::CursorMoved { position, .. } => {
                         main_window.handle_cursor_moved(position);
+                        if main_window.detect_rub() {
+                            app.on_event(GhostEvent::Rubbed);
+                        }
                         let cursor_x = position.x as f32;
                         let cursor_y = position.y as f32;
-                        for button in app.buttons_mut() {
-                            button.update_hover(cursor_x, cursor_y, window_height);
-                        }
+                        hover_widgets(&mut app, Some((cursor_x, cursor_y)), window_height);
                         main_window.request_redraw();
                     }
 
                     WindowEvent::CursorLeft { .. } => {
                         main_window.handle_cursor_left();
-                        for button in app.buttons_mut() {
-                            button.update_hover(-1.0, -1.0, window_height);
-                        }
+                        hover_widgets(&mut app, None, window_height);
+                        main_window.request_redraw();
                     }
 
                     WindowEvent::MouseInput {
@@ -1323,14 +2931,11 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                         if let Some(cursor_pos) = main_window.cursor_position() {
                             let cursor_x = cursor_pos.x as f32;
                             let cursor_y = cursor_pos.y as f32;
-                            let mut button_pressed = false;
-                            for button in app.buttons_mut() {
-                                if button.handle_press(cursor_x, cursor_y, window_height) {
-                                    button_pressed = true;
-                                    break;
-                                }
-                            }
+                            let button_pressed = press_widgets(&mut app, cursor_x, cursor_y, window_height);
                             if !button_pressed && main_window.should_handle_click() && main_window.is_draggable() {
+                                if main_window.stop_move() {
+                                    app.on_event(GhostEvent::MoveInterrupted);
+                                }
                                 main_window.drag();
                             }
                             main_window.request_redraw();
@@ -1342,23 +2947,11 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                         button: MouseButton::Left,
                         ..
                     } => {
+                        main_window.handle_drag_released();
                         if let Some(cursor_pos) = main_window.cursor_position() {
                             let cursor_x = cursor_pos.x as f32;
                             let cursor_y = cursor_pos.y as f32;
-                            let clicked_ids: Vec<_> = app
-                                .buttons_mut()
-                                .iter_mut()
-                                .filter_map(|button| {
-                                    if button.handle_release(cursor_x, cursor_y, window_height) {
-                                        Some(button.id())
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect();
-                            for id in clicked_ids {
-                                app.on_event(GhostEvent::ButtonClicked(id));
-                            }
+                            release_widgets(&mut app, cursor_x, cursor_y, window_height);
                             main_window.request_redraw();
                         }
                     }
@@ -1366,6 +2959,7 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                     WindowEvent::Resized(size) => {
                         main_window.handle_resize(size.width, size.height);
                         app.on_event(GhostEvent::Resized(size.width, size.height));
+                        main_window.request_redraw();
                     }
 
                     WindowEvent::Moved(position) => {
@@ -1373,18 +2967,17 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                         main_pos = (position.x, position.y);
 
                         // Update callout window position to follow main window
-                        callout_window.set_position(
-                            position.x + scaled_callout_offset[0],
-                            position.y + scaled_callout_offset[1],
+                        place_callout_window(
+                            &callout_window,
+                            &main_window,
+                            (position.x, position.y),
+                            scaled_callout_offset,
+                            &mut callout_app,
+                            scale_factor,
                         );
-                        // Update extra window position to follow main window
-                        if let Some(ref extra) = extra_window {
-                            if extra.is_visible() {
-                                extra.set_position(
-                                    position.x + scaled_extra_offset[0],
-                                    position.y + scaled_extra_offset[1],
-                                );
-                            }
+                        // Update linked extra windows to follow main window
+                        for extra in &extras {
+                            extra.follow(main_pos, main_window.linked_area(), true);
                         }
                         app.on_event(GhostEvent::Moved(position.x, position.y));
                     }
@@ -1402,13 +2995,24 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                     WindowEvent::CloseRequested => {
                         *control_flow = ControlFlow::Exit;
                     }
+                    WindowEvent::Resized(size) => {
+                        callout_window.handle_resize(size.width, size.height);
+                        callout_window.request_redraw();
+                    }
+                    WindowEvent::MouseInput {
+                        state: ElementState::Pressed,
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        handle_callout_click(&callout_window, &mut callout_app);
+                    }
                     _ => {}
                 }
             }
 
-            Event::WindowEvent { window_id, event, .. } if Some(window_id) == extra_window_id => {
-                if let Some(ref mut extra) = extra_window {
-                    extra.handle_event(&event);
+            Event::WindowEvent { window_id, event, .. } if extra_ids.contains(&window_id) => {
+                if let Some(extra) = extras.iter_mut().find(|e| e.window.window_id() == window_id) {
+                    extra.window.handle_event(&event);
                 }
             }
 
@@ -1418,9 +3022,11 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
 
                 // Limit updates based on animation FPS (default 24fps = ~42ms)
                 // Use animation fps if available, otherwise 30fps for efficiency
-                let target_fps = app.current_skin()
-                    .map(|_| 24.0)  // If we have animated skin, use 30fps
-                    .unwrap_or(10.0);  // If no animation, 10fps is enough for interactions
+                let target_fps = if app.needs_continuous_update() || main_window.is_moving() || main_window.is_transitioning() {
+                    24.0  // Animated skin, gliding window or show/hide animation
+                } else {
+                    10.0  // If no animation, 10fps is enough for interactions
+                };
                 let min_frame_time = 1.0 / target_fps;
 
                 // if delta < min_frame_time {
@@ -1436,25 +3042,46 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                 app.update(delta);
                 app.on_event(GhostEvent::Update(delta));
                 let callout_changed = callout_app.update(delta);
+                update_callout_click_through(&mut callout_window, &callout_app);
+
+                // Opacity, flip, cursor tracking and animated moves
+                if let Some((x, y)) = apply_app_state(&mut main_window, &mut app, delta, &mut last_screen) {
+                    // Keep the satellite windows attached while gliding
+                    main_pos = (x, y);
+                    place_callout_window(
+                        &callout_window,
+                        &main_window,
+                        (x, y),
+                        scaled_callout_offset,
+                        &mut callout_app,
+                        scale_factor,
+                    );
+                    for extra in &extras {
+                        extra.follow(main_pos, main_window.linked_area(), true);
+                    }
+                }
 
                 // Process extra window updates
-                let mut extra_needs_redraw = false;
-                if let Some(ref mut extra) = extra_window {
-                    extra.process_updates();
+                let mut extra_needs_redraw = vec![false; extras.len()];
+                for (i, extra) in extras.iter_mut().enumerate() {
+                    extra.window.process_updates();
+                    if let Some(offset) = extra.window.take_offset_request() {
+                        if extra.offset.is_some() {
+                            extra.offset = Some(offset.map(|v| (v as f64 * scale_factor) as i32));
+                            extra.follow(main_pos, main_window.linked_area(), true);
+                        }
+                    }
 
                     // Check if extra window just became visible - reposition it
-                    let is_visible = extra.is_visible();
-                    if is_visible && !extra_was_visible {
+                    let is_visible = extra.window.is_visible();
+                    if is_visible && !extra_was_visible[i] {
                         if let Some((x, y)) = main_window.outer_position() {
                             main_pos = (x, y);
                         }
-                        extra.set_position(
-                            main_pos.0 + scaled_extra_offset[0],
-                            main_pos.1 + scaled_extra_offset[1],
-                        );
-                        extra_needs_redraw = true;
+                        extra.follow(main_pos, main_window.linked_area(), false);
+                        extra_needs_redraw[i] = true;
                     }
-                    extra_was_visible = is_visible;
+                    extra_was_visible[i] = is_visible;
                 }
 
                 // Check if app wants to quit
@@ -1463,9 +3090,8 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                     return;
                 }
 
-                // Always redraw main window if we have animated content
-                // The animation system handles frame timing internally
-                if app.current_skin().is_some() {
+                // Redraw the main window only when what it shows changed
+                if app.take_redraw_request() {
                     main_window.request_redraw();
                 }
 
@@ -1474,10 +3100,10 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                     callout_window.request_redraw();
                 }
 
-                // Redraw extra window if needed
-                if let Some(ref extra) = extra_window {
-                    if extra.is_visible() && extra_needs_redraw {
-                        extra.request_redraw();
+                // Redraw extra windows if needed
+                for (extra, needs_redraw) in extras.iter().zip(extra_needs_redraw) {
+                    if extra.window.is_visible() && needs_redraw {
+                        extra.window.request_redraw();
                     }
                 }
 
@@ -1511,8 +3137,8 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
                 if let (Some(ref mut btn_renderer), Some(ref renderer)) =
                     (&mut button_renderer, &main_window.renderer)
                 {
-                    let buttons: Vec<&crate::widget::Button> = app.buttons();
-                    btn_renderer.prepare(renderer.device(), renderer.queue(), &buttons, viewport);
+                    btn_renderer.layout_text_inputs(app.text_inputs_mut());
+                    btn_renderer.prepare(renderer.device(), renderer.queue(), &widgets(&app, &main_window), viewport);
                 }
 
                 if let Some(ref renderer) = main_window.renderer {
@@ -1548,14 +3174,31 @@ pub fn run_with_app_callout_and_extra<A, C, E>(
 
                 // Render callout window
                 let _ = callout_window.render_callout(&callout_app);
+
+                // Fit the window to the bubble once it has been laid out
+                if let Some(layout) = callout_app.layout().filter(|l| callout_layout != Some(*l)) {
+                    callout_layout = Some(layout);
+                    scaled_callout_offset =
+                        fit_callout_window(&mut callout_window, layout, callout_offset, scale_factor);
+                    place_callout_window(
+                        &callout_window,
+                        &main_window,
+                        main_pos,
+                        scaled_callout_offset,
+                        &mut callout_app,
+                        scale_factor,
+                    );
+                    callout_window.request_redraw();
+                }
             }
 
-            Event::RedrawRequested(window_id) if Some(window_id) == extra_window_id => {
-                if let Some(ref mut extra) = extra_window {
-                    extra.render();
+            Event::RedrawRequested(window_id) if extra_ids.contains(&window_id) => {
+                if let Some(extra) = extras.iter_mut().find(|e| e.window.window_id() == window_id) {
+                    extra.window.render();
                 }
             }
 
+            Event::LoopDestroyed => app.on_exit(),
             _ => (),
         }
     });
@@ -1598,6 +3241,14 @@ impl GhostWindowBuilder {
         self
     }
 
+    /// Stack the window above other windows, among them, or below them on
+    /// the desktop.
+    pub fn with_layer(mut self, layer: WindowLayer) -> Self {
+        self.config.always_on_top = layer == WindowLayer::Top;
+        self.config.always_on_bottom = layer == WindowLayer::Desktop;
+        self
+    }
+
     /// Set whether clicks should pass through the window entirely.
     pub fn with_click_through(mut self, click_through: bool) -> Self {
         self.config.click_through = click_through;
@@ -1663,6 +3314,50 @@ impl GhostWindowBuilder {
         self
     }
 
+    /// Recognize a rub gesture over the skin and emit `GhostEvent::Rubbed`.
+    pub fn with_rub_gesture(mut self, gesture: RubGesture) -> Self {
+        self.config.rub_gesture = Some(gesture);
+        self
+    }
+
+    /// Start on the given monitor (centered unless `with_position` is set).
+    pub fn with_monitor(mut self, monitor: MonitorSelection) -> Self {
+        self.config.monitor = Some(monitor);
+        self
+    }
+
+    /// Start at (x, y) logical pixels from the top-left corner of the
+    /// monitor chosen with `with_monitor` (the primary one if unset).
+    pub fn with_position(mut self, x: f32, y: f32) -> Self {
+        self.config.position = Some([x, y]);
+        self.config.monitor.get_or_insert(MonitorSelection::Primary);
+        self
+    }
+
+    /// Dock against a screen edge or corner when a drag ends within
+    /// `threshold_px` logical pixels of it; emits `GhostEvent::Docked`.
+    pub fn with_edge_snap(mut self, threshold_px: f32) -> Self {
+        self.config.edge_snap = Some(threshold_px);
+        self
+    }
+
+    /// Keep the window, its callout and linked windows inside the monitor's
+    /// work area, so moving, snapping and walking stay clear of the
+    /// taskbar, dock or menu bar.
+    pub fn with_keep_in_work_area(mut self, keep: bool) -> Self {
+        self.config.keep_in_work_area = keep;
+        self
+    }
+
+    /// Add the physics movement engine: falling to the bottom of the screen,
+    /// walking along it and being thrown. It runs while
+    /// `GhostApp::motion_enabled` returns true and reports
+    /// `GhostEvent::MotionChanged`. Edge snapping is skipped while it runs.
+    pub fn with_motion(mut self, config: MotionConfig) -> Self {
+        self.config.motion = Some(config);
+        self
+    }
+
     /// Set the skin from PNG bytes.
     pub fn with_skin_bytes(mut self, bytes: &[u8]) -> Self {
         self.skin_bytes = Some(bytes.to_vec());
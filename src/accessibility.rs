@@ -0,0 +1,75 @@
+//! Accessibility - the reduced-motion preference of the OS
+//!
+//! Asked once at startup when `[accessibility] reduced_motion` is not set:
+//! GNOME's `enable-animations`, macOS "Reduce motion" and the Windows
+//! "Animate controls and elements" setting (`MinAnimate`).
+
+use std::process::Command;
+
+use crate::config::AccessibilityConfig;
+
+/// Fill in `reduced_motion` from the OS preference when not configured
+pub fn resolve(config: &mut AccessibilityConfig) {
+    if config.reduced_motion.is_none() {
+        let detected = os_prefers_reduced_motion();
+        log::info!("OS reduced motion preference: {}", detected);
+        config.reduced_motion = Some(detected);
+    }
+}
+
+/// Whether the OS asks for reduced motion (false when unknown)
+fn os_prefers_reduced_motion() -> bool {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("defaults")
+            .args(["read", "com.apple.universalaccess", "reduceMotion"])
+            .output()
+    } else if cfg!(target_os = "windows") {
+        Command::new("reg")
+            .args(["query", r"HKCU\Control Panel\Desktop\WindowMetrics", "/v", "MinAnimate"])
+            .output()
+    } else {
+        Command::new("gsettings")
+            .args(["get", "org.gnome.desktop.interface", "enable-animations"])
+            .output()
+    };
+    let Ok(output) = output else { return false };
+    if !output.status.success() {
+        return false;
+    }
+    parse_preference(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Interpret the output of the platform's settings query
+fn parse_preference(output: &str) -> bool {
+    let value = output.split_whitespace().last().unwrap_or_default();
+    if cfg!(target_os = "macos") {
+        // reduceMotion = 1
+        value == "1"
+    } else if cfg!(target_os = "windows") {
+        // MinAnimate    REG_SZ    0
+        value == "0"
+    } else {
+        // enable-animations = false
+        value == "false"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_preference() {
+        if cfg!(target_os = "macos") {
+            assert!(parse_preference("1\n"));
+            assert!(!parse_preference("0\n"));
+        } else if cfg!(target_os = "windows") {
+            assert!(parse_preference("\r\nHKEY_CURRENT_USER\\Control Panel\\Desktop\\WindowMetrics\r\n    MinAnimate    REG_SZ    0\r\n"));
+            assert!(!parse_preference("    MinAnimate    REG_SZ    1\r\n"));
+        } else {
+            assert!(parse_preference("false\n"));
+            assert!(!parse_preference("true\n"));
+        }
+        assert!(!parse_preference(""));
+    }
+}
@@ -1,13 +1,58 @@
 //! Application state - combines UI and business logic
 
-use ghost_ui::{AnimatedSkin, AnimationState, Button, GhostApp, GhostEvent, GpuResources, Layer, LayerAnchor, LayerConfig, LayerRenderer, Skin, SpritePipeline, TextAlign, TextVAlign};
+use ghost_callout::parse_markup;
+use ghost_ui::{AnimatedSkin, AnimationState, AnimationStateMachine, Button, ContextMenu, Easing, Effect, EffectParams, EffectStack, GhostApp, GhostEvent, GpuResources, Layer, LayerAnchor, LayerConfig, LayerRenderer, MarqueeLabel, MotionState, MoveRequest, ParticleSystem, Recolor, Skin, SkinLoadEvent, SkinLoader, SpritePipeline, TextAlign, TextVAlign, Transition};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
 use wgpu::TextureFormat;
 
+use crate::accessibility;
+use crate::audio::{PersonaSounds, SoundPlayer};
+use crate::autostart;
+use crate::auto_life::AutoLife;
+use crate::lip_sync::LipSync;
+use crate::behavior::BehaviorSchedule;
+use crate::calendar::Calendar;
 use crate::callout_app::{CalloutCommand, CalloutSender};
-use crate::chat_window::{ChatSender, ChatWindowCommand};
-use crate::config::Config;
-use crate::tray::{self, MenuIds, TrayCommand};
+use crate::chat_backend::{ChatClient, ChatEvent};
+use crate::chat_history::ChatHistory;
+use crate::chat_window::{ChatMessage, ChatSender, ChatWindowCommand};
+use crate::clipboard;
+use crate::commands::{self, ChatAction, ChatCommand, PomodoroAction};
+use crate::config::{self, Config, EffectsConfig, GazeConfig, PersonaManifest, SkinConfig};
+use crate::context_menu;
+use crate::crash::{self, Snapshot};
+use crate::discord::DiscordPresence;
+use crate::hotkeys::{self, HotkeyAction};
+use crate::debug_window::{DebugAction, DebugInfo, DebugSender, DebugWindowCommand};
+use crate::ipc::IpcRequest;
+use crate::persona_gallery::{GalleryAction, GalleryCommand, GallerySender};
+use crate::look_at::LookAt;
+use crate::media::MediaWatcher;
+use crate::pomodoro::{Phase, Pomodoro};
+use crate::notification;
+use crate::notification_bridge::NotificationBridge;
+use crate::reminders::ReminderStore;
+use crate::scheduler::Scheduler;
+use crate::script::{self, Script, ScriptAction, ScriptEvent};
+use crate::session::{self, Session};
+use crate::speech;
+use crate::sysmon::{ReactionEvent, SystemMonitor};
+use crate::tray::{self, TrayCommand, TrayComponents, TrayState};
 use crate::ui;
+use crate::vars::{GhostState, Interaction};
+use crate::wander::Wander;
+use crate::weather::WeatherProvider;
+
+/// Recent chat messages kept for the tray menu
+const MAX_RECENT_CHATS: usize = 5;
+
+/// Seconds between crash snapshot refreshes
+const SNAPSHOT_INTERVAL: f32 = 1.0;
+
+/// Seconds between debug console refreshes
+const DEBUG_INTERVAL: f32 = 0.5;
 
 /// Main application state
 pub struct App {
@@ -16,17 +61,138 @@ pub struct App {
     callout_sender: CalloutSender,
     skin_size: (u32, u32),
     layers: Vec<Layer>,
+    /// Config ids of `layers` (same order)
+    layer_ids: Vec<Option<String>>,
+    /// Parts of a composite persona drawn over the body, below `layers`
+    parts: Vec<Layer>,
+    /// Manifest paths of `parts` (same order)
+    part_paths: Vec<String>,
+    /// Generated blinks and breathing of the persona
+    auto_life: Option<AutoLife>,
+    /// Mouth movement following speech
+    lip_sync: Option<LipSync>,
+    /// State blinking or lip-sync put the body in
+    body_override: Option<AnimationState>,
+    /// How the persona's eyes follow the cursor
+    gaze: GazeConfig,
+    /// Color variant of the persona (`[[recolor]]`)
+    recolor: Option<Recolor>,
+    /// Skin post effects (glow, dissolve, pixelate, grayscale)
+    effects: EffectStack,
     layer_renderer: LayerRenderer,
     layer_pipeline: Option<SpritePipeline>,
     texture_format: Option<TextureFormat>,
     /// Animated skin (if using frame sequences)
     animated_skin: Option<AnimatedSkin>,
-    /// Tray menu IDs for event handling
-    menu_ids: Option<MenuIds>,
+    /// Tray icon and menu (rebuilt when its state changes)
+    tray: Option<TrayComponents>,
+    /// Persona folders listed in the tray
+    personas: Vec<String>,
+    /// Persona to load on the next frame (needs the GPU)
+    pending_persona: Option<String>,
+    /// Persona being loaded in the background, with its name and path
+    persona_loader: Option<(String, PathBuf, SkinLoader)>,
+    /// The skin changed on disk; reload it on the next frame
+    skin_changed: bool,
+    /// A layer or the hearts changed; redraw the main window
+    layers_changed: bool,
+    /// Recent chat messages, newest first
+    recent_chats: Vec<String>,
+    /// Main window has focus
+    focused: bool,
+    /// Callouts and replies since the user last focused the ghost
+    unread: Vec<String>,
+    /// Main window position
+    position: Option<(i32, i32)>,
+    /// The ghost starts at login (checked once, then tracked)
+    autostart: bool,
+    /// Another ghost was started and asked this one to show itself
+    focus_receiver: Option<Receiver<()>>,
+    /// Seconds until the crash snapshot is refreshed
+    snapshot_timer: f32,
+    /// Debug console commands (if the console is enabled)
+    debug_sender: Option<DebugSender>,
+    /// Test triggers from the debug console
+    debug_actions: Option<Receiver<DebugAction>>,
+    /// Seconds until the debug console is refreshed
+    debug_timer: f32,
+    /// Persona gallery commands (if the gallery is enabled)
+    gallery_sender: Option<GallerySender>,
+    /// Persona switches and download progress from the gallery
+    gallery_actions: Option<Receiver<GalleryAction>>,
+    /// Text of the last callout, read out by screen readers
+    last_callout: Option<String>,
     /// Flag to signal quit
     should_quit: bool,
+    /// The ghost window was hidden from a menu
+    hidden: bool,
+    /// Pending show (true) or hide (false) of the ghost window
+    visibility_request: Option<bool>,
     /// Chat window sender (to send commands to chat window)
     chat_sender: ChatSender,
+    /// Scheduled actions from config
+    scheduler: Scheduler,
+    /// Time-of-day behaviors from config
+    behaviors: BehaviorSchedule,
+    /// Pomodoro timer
+    pomodoro: Pomodoro,
+    /// Messages typed into the chat window
+    chat_input: Receiver<String>,
+    /// Language model answering chat messages (if configured)
+    chat_client: Option<ChatClient>,
+    /// Saved conversation of the persona, also sent to the language model
+    chat_history: ChatHistory,
+    /// Reply being streamed in
+    chat_reply: String,
+    /// Persisted reminders and todo items
+    reminders: ReminderStore,
+    /// Seconds until reminders are checked again
+    reminder_check_timer: f32,
+    /// Commands received over local IPC
+    ipc_receiver: Option<Receiver<IpcRequest>>,
+    /// Template variables
+    vars: GhostState,
+    /// CPU/memory/battery monitor (if enabled)
+    monitor: Option<SystemMonitor>,
+    /// Weather provider (if enabled)
+    weather: Option<WeatherProvider>,
+    /// A forecast announcement is waiting for weather data
+    weather_announce_pending: bool,
+    /// Calendar feeds (if enabled)
+    calendar: Option<Calendar>,
+    /// Now-playing media watcher (if enabled)
+    media: Option<MediaWatcher>,
+    notifications: Option<NotificationBridge>,
+    /// Scrolling label for the current track
+    media_marquee: MarqueeLabel,
+    /// Scrolling system stats label
+    monitor_marquee: MarqueeLabel,
+    /// Discord Rich Presence (if enabled)
+    discord: Option<DiscordPresence>,
+    /// Cursor tracking
+    look_at: LookAt,
+    /// Transitions between animation states from the persona's config.toml
+    state_machine: Option<AnimationStateMachine>,
+    /// Sound output (if enabled and built with the `audio` feature)
+    sound: Option<SoundPlayer>,
+    /// Sounds of the active persona
+    sounds: PersonaSounds,
+    /// The persona's behavior script
+    script: Option<Script>,
+    /// Animation state the last state sound was played for
+    sound_state: Option<AnimationState>,
+    /// Walking along the bottom edge (if enabled)
+    wander: Option<Wander>,
+    /// Run the window's physics movement engine
+    motion_enabled: bool,
+    /// Latest state of the movement engine
+    motion_state: MotionState,
+    /// Window move waiting to be picked up by the event loop
+    move_request: Option<MoveRequest>,
+    /// Hearts shown while being petted
+    hearts: ParticleSystem,
+    /// Seconds before petting reacts again
+    petting_cooldown: f32,
 }
 
 impl App {
@@ -38,6 +204,7 @@ impl App {
         callout_sender: CalloutSender,
         animated_skin: Option<AnimatedSkin>,
         chat_sender: ChatSender,
+        chat_input: Receiver<String>,
     ) -> Self {
         let buttons = ui::create_buttons_from_config(&config.buttons);
 
@@ -62,7 +229,7 @@ impl App {
                 Ok(mut layer) => {
                     layer.calculate_position(skin_width, skin_height);
                     log::info!("Loaded layer: {} at position {:?}", layer_config.path, layer.position());
-                    layers.push(layer);
+                    layers.push((layer_config.id.clone(), layer));
                 }
                 Err(e) => {
                     log::error!("Failed to load layer '{}': {}", layer_config.path, e);
@@ -71,34 +238,345 @@ impl App {
         }
 
         // Sort layers by z_order
-        layers.sort_by_key(|l| l.config.z_order);
+        layers.sort_by_key(|(_, l)| l.config.z_order);
+        let (layer_ids, layers) = layers.into_iter().unzip();
+
+        let scheduler = Scheduler::from_config(&config.schedule);
+        let behaviors = BehaviorSchedule::from_config(&config.behavior);
+        let pomodoro = Pomodoro::new(config.pomodoro.clone());
+        let monitor = config.monitor.enabled.then(|| SystemMonitor::new(&config.monitor));
+        let chat_client = ChatClient::from_config(&config.llm);
+        let chat_history = ChatHistory::load(&config.skin.persona(), config.chat.history_limit);
+        show_chat_session(&chat_sender, &chat_history);
+        let weather = config.weather.enabled.then(|| WeatherProvider::new(config.weather.clone()));
+        let calendar = config.calendar.enabled.then(|| Calendar::new(config.calendar.clone()));
+        let media = config.media.enabled.then(|| MediaWatcher::start(config.media.poll_interval()));
+        let notifications = config
+            .notifications
+            .enabled
+            .then(|| NotificationBridge::start(config.notifications.poll_interval()));
+        let discord = DiscordPresence::start(&config.discord);
+        let look_at = LookAt::new(config.look_at.clone());
+        // Walking around is motion too; the physics engine does its own walking
+        let motion_enabled = config.motion.enabled && !config.accessibility.reduced_motion();
+        let wander = (config.wander.enabled && !config.accessibility.reduced_motion() && !motion_enabled)
+            .then(|| Wander::new(config.wander.clone()));
+        let manifest = PersonaManifest::load(&config.skin.path);
+        let state_machine = animated_skin.as_ref().and_then(|_| load_state_machine(&manifest));
+        let sound = SoundPlayer::start(&config.audio);
+        let sounds = PersonaSounds::load(Path::new(&config.skin.path), &manifest.sounds);
+        let hearts = match config.petting.particle {
+            Some(ref path) => ParticleSystem::from_path(path, config.petting.particle_size).unwrap_or_else(|e| {
+                log::error!("Failed to load particle image '{}': {}", path, e);
+                ParticleSystem::hearts(config.petting.particle_size)
+            }),
+            None => ParticleSystem::hearts(config.petting.particle_size),
+        };
+        // Personas are animation folders, so switching only works for animated skins
+        let personas = if config.skin.animated {
+            tray::scan_personas(&config.skin.persona_dir())
+        } else {
+            Vec::new()
+        };
+        let media_marquee = MarqueeLabel::new("")
+            .with_width(config.media.marquee_width)
+            .with_speed(config.media.marquee_speed)
+            .with_gap("   ♪   ")
+            .with_reduced_motion(config.accessibility.reduced_motion());
+        let monitor_marquee = MarqueeLabel::new("")
+            .with_width(config.monitor.marquee_width)
+            .with_speed(config.monitor.marquee_speed)
+            .with_reduced_motion(config.accessibility.reduced_motion());
+        let effects = load_effects(&config.effects);
 
-        Self {
+        let mut app = Self {
             config,
             buttons,
             callout_sender,
             skin_size: (skin_width, skin_height),
             layers,
+            layer_ids,
+            parts: Vec::new(),
+            part_paths: Vec::new(),
+            auto_life: None,
+            lip_sync: None,
+            body_override: None,
+            gaze: GazeConfig::default(),
+            recolor: None,
+            effects,
             layer_renderer: LayerRenderer::new(),
             layer_pipeline: None,
             texture_format: None,
             animated_skin,
-            menu_ids: None,
+            tray: None,
+            personas,
+            pending_persona: None,
+            persona_loader: None,
+            skin_changed: false,
+            layers_changed: false,
+            recent_chats: Vec::new(),
+            focused: false,
+            unread: Vec::new(),
+            position: None,
+            autostart: autostart::is_enabled(),
+            focus_receiver: None,
+            snapshot_timer: 0.0,
+            debug_sender: None,
+            debug_actions: None,
+            debug_timer: 0.0,
+            gallery_sender: None,
+            gallery_actions: None,
+            last_callout: None,
             should_quit: false,
+            hidden: false,
+            visibility_request: None,
             chat_sender,
-        }
+            scheduler,
+            behaviors,
+            pomodoro,
+            chat_input,
+            chat_client,
+            chat_history,
+            chat_reply: String::new(),
+            reminders: ReminderStore::load(),
+            reminder_check_timer: 0.0,
+            ipc_receiver: None,
+            vars: GhostState::load(),
+            monitor,
+            weather,
+            weather_announce_pending: false,
+            calendar,
+            media,
+            notifications,
+            media_marquee,
+            monitor_marquee,
+            discord,
+            look_at,
+            state_machine,
+            sound,
+            sounds,
+            sound_state: None,
+            wander,
+            motion_enabled,
+            motion_state: MotionState::Standing,
+            move_request: None,
+            hearts,
+            petting_cooldown: 0.0,
+            script: None,
+        };
+        app.load_parts(&manifest);
+        app.load_persona_motion(&manifest);
+        app.load_recolor();
+        app.load_script(&manifest);
+        app.run_script(ScriptEvent::Start);
+        app
     }
 
-    /// Send a callout command
-    fn send_callout(&self, cmd: CalloutCommand) {
+    /// Send a callout command, expanding `{name}` templates in its text
+    fn send_callout(&mut self, cmd: CalloutCommand) {
+        let cmd = cmd.map_text(|text| self.vars.expand(&text) + self.mood_suffix());
+        if let Some(text) = cmd.text() {
+            self.note_unread(text.to_string());
+            self.last_callout = Some(text.to_string());
+        }
+        if let Some(kind) = cmd.kind() {
+            self.play_sound(self.sounds.for_callout(kind));
+        }
+        // Spoken callouts move the mouth along with the voice
+        if let (CalloutCommand::Say(text), Some(lip_sync)) = (&cmd, &mut self.lip_sync) {
+            if self.config.speech.enabled {
+                lip_sync.speak(&parse_markup(text).0, speech::speech_duration(text, self.config.speech.rate));
+            }
+        }
         if let Err(e) = self.callout_sender.send(cmd) {
             log::error!("Failed to send callout command: {}", e);
         }
     }
 
-    /// Set tray menu IDs for event handling
-    pub fn set_menu_ids(&mut self, menu_ids: MenuIds) {
-        self.menu_ids = Some(menu_ids);
+    fn play_sound(&self, path: Option<&Path>) {
+        if let (Some(sound), Some(path)) = (&self.sound, path) {
+            sound.play(path);
+        }
+    }
+
+    /// Play the sound of an animation state when it starts
+    fn update_state_sound(&mut self) {
+        let Some(ref animated_skin) = self.animated_skin else { return };
+        let state = animated_skin.current_state();
+        if self.sound_state.replace(state).is_some_and(|last| last != state) {
+            self.play_sound(self.sounds.for_state(state));
+        }
+    }
+
+    /// Keep a message the user may have missed (shown as a tray and dock badge)
+    fn note_unread(&mut self, text: String) {
+        if !self.focused {
+            self.unread.push(text);
+        }
+    }
+
+    /// Receive focus requests from ghosts started while this one runs
+    pub fn set_focus_receiver(&mut self, receiver: Receiver<()>) {
+        self.focus_receiver = Some(receiver);
+    }
+
+    /// Restore the session saved by a crash
+    pub fn recover(&mut self, snapshot: Snapshot) {
+        if let Some((x, y)) = snapshot.position {
+            self.move_request = Some(MoveRequest::To { x, y, duration: 0.0, easing: Easing::Linear });
+        }
+        if let Some(persona) = snapshot.persona {
+            if persona != self.config.skin.persona() && self.personas.contains(&persona) {
+                self.pending_persona = Some(persona);
+            }
+        }
+        self.recent_chats = snapshot.recent_chats;
+        for text in snapshot.unread {
+            self.reply_in_chat(text);
+        }
+        self.send_callout(CalloutCommand::Say("Oops, I crashed... but I'm back!".to_string()));
+    }
+
+    /// Restore the mute, volume and chat window state of the last session
+    /// (the position, persona and chat size are applied at startup)
+    pub fn restore_session(&mut self, session: &Session) {
+        if let Some(ref mut sound) = self.sound {
+            sound.set_muted(session.muted);
+            if let Some(volume) = session.volume {
+                sound.set_volume(volume);
+            }
+        }
+        if session.chat_open {
+            if let Err(e) = self.chat_sender.send(ChatWindowCommand::Show) {
+                log::error!("Failed to send chat window command: {}", e);
+            }
+        }
+    }
+
+    /// Session state for crash recovery
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            position: self.position,
+            persona: Some(self.config.skin.persona()),
+            recent_chats: self.recent_chats.clone(),
+            unread: self.unread.clone(),
+        }
+    }
+
+    /// Refresh the crash snapshot now and then
+    fn update_snapshot(&mut self, delta: f32) {
+        self.snapshot_timer -= delta;
+        if self.snapshot_timer <= 0.0 {
+            self.snapshot_timer = SNAPSHOT_INTERVAL;
+            crash::update(self.snapshot());
+        }
+    }
+
+    /// Connect the debug console
+    pub fn set_debug_window(&mut self, sender: DebugSender, actions: Receiver<DebugAction>) {
+        self.debug_sender = Some(sender);
+        self.debug_actions = Some(actions);
+    }
+
+    fn send_debug(&self, cmd: DebugWindowCommand) {
+        if let Some(ref sender) = self.debug_sender {
+            if let Err(e) = sender.send(cmd) {
+                log::error!("Failed to send debug window command: {}", e);
+            }
+        }
+    }
+
+    /// Live state for the debug console
+    fn debug_info(&self) -> DebugInfo {
+        let state = match self.animated_skin {
+            Some(ref skin) => format!("{:?}", skin.current_state()),
+            None => "static skin".to_string(),
+        };
+        DebugInfo {
+            state,
+            mood: self.vars.mood(),
+            vars: self.vars.vars(),
+        }
+    }
+
+    /// Refresh the debug console and run its test triggers
+    fn update_debug(&mut self, delta: f32) {
+        let actions: Vec<DebugAction> = match self.debug_actions {
+            Some(ref receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for action in actions {
+            match action {
+                DebugAction::Callout { kind, text } => {
+                    let cmd = if kind == "alert" {
+                        CalloutCommand::Alert(text)
+                    } else {
+                        CalloutCommand::from_kind(&kind, text)
+                    };
+                    self.send_callout(cmd);
+                }
+                DebugAction::SetState(state) => self.set_animation_state(&state),
+            }
+        }
+
+        self.debug_timer -= delta;
+        if self.debug_timer <= 0.0 {
+            self.debug_timer = DEBUG_INTERVAL;
+            self.send_debug(DebugWindowCommand::Update(self.debug_info()));
+        }
+    }
+
+    /// Connect the persona gallery
+    pub fn set_gallery_window(&mut self, sender: GallerySender, actions: Receiver<GalleryAction>) {
+        self.gallery_sender = Some(sender);
+        self.gallery_actions = Some(actions);
+    }
+
+    /// Switch personas and announce downloads requested from the gallery
+    fn update_gallery(&mut self) {
+        let actions: Vec<GalleryAction> = match self.gallery_actions {
+            Some(ref receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for action in actions {
+            match action {
+                GalleryAction::SetPersona(name) => self.pending_persona = Some(name),
+                GalleryAction::Callout(text) => self.send_callout(CalloutCommand::Say(text)),
+                GalleryAction::Installed(name) => {
+                    self.personas = tray::scan_personas(&self.config.skin.persona_dir());
+                    self.send_callout(CalloutCommand::Say(format!("{} is installed!", name)));
+                    self.pending_persona = Some(name);
+                }
+            }
+        }
+    }
+
+    /// Set the tray icon for event handling and menu updates
+    pub fn set_tray(&mut self, tray: TrayComponents) {
+        self.tray = Some(tray);
+    }
+
+    /// Current state for the tray menu
+    pub fn tray_state(&self) -> TrayState {
+        TrayState {
+            pomodoro_running: self.pomodoro.is_running(),
+            look_at: self.look_at.is_enabled(),
+            personas: self.personas.clone(),
+            active_persona: Some(self.config.skin.persona()),
+            recent_chats: self.recent_chats.clone(),
+            debug: self.debug_sender.is_some(),
+            gallery: self.gallery_sender.is_some(),
+            volume: self.sound.as_ref().map(SoundPlayer::volume),
+            muted: self.sound.as_ref().is_some_and(SoundPlayer::is_muted),
+            hidden: self.hidden,
+            has_reply: self.last_reply().is_some(),
+            autostart: self.autostart,
+        }
+    }
+
+    /// Set the IPC request receiver
+    pub fn set_ipc_receiver(&mut self, receiver: Receiver<IpcRequest>) {
+        self.ipc_receiver = Some(receiver);
     }
 
     /// Set animation state by name
@@ -114,6 +592,74 @@ impl App {
         }
     }
 
+    /// Idle state for the current mood (falls back to "idle")
+    fn idle_state(&self) -> &str {
+        if !self.config.mood.enabled {
+            return "idle";
+        }
+        let mood = self.vars.mood().kind();
+        self.config
+            .mood
+            .idle_states
+            .get(mood.name())
+            .filter(|state| {
+                let state = AnimationState::from_str(state);
+                self.animated_skin.as_ref().is_some_and(|s| s.has_state(state))
+            })
+            .map_or("idle", String::as_str)
+    }
+
+    /// Go back to the idle state for the current mood
+    fn restore_idle(&mut self) {
+        let state = self.idle_state().to_string();
+        self.set_animation_state(&state);
+    }
+
+    /// Whether the skin is showing an idle state
+    fn is_idle(&self) -> bool {
+        let Some(ref animated_skin) = self.animated_skin else { return true };
+        let current = animated_skin.current_state();
+        current == AnimationState::Idle || current == AnimationState::from_str(self.idle_state())
+    }
+
+    /// Text appended to callouts for the current mood
+    fn mood_suffix(&self) -> &str {
+        if !self.config.mood.enabled {
+            return "";
+        }
+        let mood = self.vars.mood().kind();
+        self.config.mood.suffixes.get(mood.name()).map_or("", String::as_str)
+    }
+
+    /// Feed an interaction into the mood; welcomes the user back if the ghost was lonely
+    fn interact(&mut self, interaction: Interaction) {
+        if !self.config.mood.enabled {
+            return;
+        }
+        let idle = self.is_idle();
+        let was_lonely = self.vars.interact(interaction, &self.config.mood);
+        if was_lonely && !self.config.mood.welcome_back.is_empty() {
+            self.send_callout(CalloutCommand::Say(self.config.mood.welcome_back.clone()));
+        }
+        if idle {
+            self.restore_idle();
+        }
+    }
+
+    /// Let the mood drift and switch to the matching idle state
+    fn update_mood(&mut self, delta: f32) {
+        if !self.config.mood.enabled {
+            return;
+        }
+        let idle = self.is_idle();
+        if let Some(mood) = self.vars.update_mood(delta, &self.config.mood) {
+            log::info!("Mood changed to {}", mood.name());
+            if idle {
+                self.restore_idle();
+            }
+        }
+    }
+
     /// Open the chat window
     fn open_chat_window(&self) {
         if let Err(e) = self.chat_sender.send(ChatWindowCommand::Show) {
@@ -123,160 +669,1615 @@ impl App {
         }
     }
 
-    /// Poll and handle tray menu events
-    fn poll_tray_events(&mut self) {
-        let Some(ref menu_ids) = self.menu_ids else { return };
+    /// Set the text of the layer with the given config id
+    fn set_layer_text(&mut self, id: &str, text: Option<String>) {
+        let index = self.layer_ids.iter().position(|l| l.as_deref() == Some(id));
+        match index {
+            Some(i) => {
+                self.layers[i].set_text(text);
+                self.layers_changed = true;
+            }
+            None => log::warn!("No layer with id '{}'", id),
+        }
+    }
+
+    /// Restore the text a layer was configured with
+    fn restore_layer_text(&mut self, id: &str) {
+        let original = self.config.layers.iter()
+            .find(|l| l.id.as_deref() == Some(id))
+            .and_then(|l| l.text.clone());
+        self.set_layer_text(id, original);
+    }
+
+    /// Post an assistant reply in the chat window
+    fn reply_in_chat(&mut self, content: String) {
+        let message = ChatMessage {
+            role: "assistant".to_string(),
+            content,
+        };
+        self.note_unread(message.content.clone());
+        self.chat_history.push(&message.role, message.content.clone());
+        if let Err(e) = self.chat_sender.send(ChatWindowCommand::AddMessage(message)) {
+            log::error!("Failed to send chat message: {}", e);
+        }
+    }
 
-        if let Some(cmd) = tray::poll_menu_event(menu_ids) {
-            match cmd {
-                TrayCommand::OpenChat => {
-                    self.open_chat_window();
+    /// Execute a chat/IPC command and return the reply text
+    fn execute_command(&mut self, cmd: ChatCommand) -> String {
+        match cmd {
+            ChatCommand::Pomodoro(PomodoroAction::Start) => {
+                self.start_pomodoro();
+                "Pomodoro started.".to_string()
+            }
+            ChatCommand::Pomodoro(PomodoroAction::Stop) => {
+                self.stop_pomodoro();
+                "Pomodoro stopped.".to_string()
+            }
+            ChatCommand::Pomodoro(PomodoroAction::Status) => {
+                if self.pomodoro.is_running() {
+                    format!("{:?}: {} left", self.pomodoro.phase(), self.pomodoro.remaining_label())
+                } else {
+                    "Pomodoro is not running.".to_string()
+                }
+            }
+            ChatCommand::Remind { due, text } => {
+                let id = self.reminders.add_reminder(text, due);
+                format!("Okay! I'll remind you at {} (#{}).", due.format("%a %H:%M"), id)
+            }
+            ChatCommand::TodoAdd(text) => {
+                let id = self.reminders.add_todo(text);
+                format!("Added todo #{}.", id)
+            }
+            ChatCommand::TodoDone(id) => {
+                if self.reminders.complete(id) {
+                    format!("Marked #{} as done.", id)
+                } else {
+                    format!("No item #{}.", id)
+                }
+            }
+            ChatCommand::TodoRemove(id) => {
+                if self.reminders.remove(id) {
+                    format!("Removed #{}.", id)
+                } else {
+                    format!("No item #{}.", id)
+                }
+            }
+            ChatCommand::ListReminders => self.reminder_list(),
+            ChatCommand::Invalid(reason) => reason,
+            ChatCommand::Chat(_) if self.chat_client.as_ref().is_some_and(ChatClient::is_busy) => {
+                "Let me finish my answer first.".to_string()
+            }
+            ChatCommand::Chat(ChatAction::Clear) => {
+                self.chat_history.clear();
+                if let Err(e) = self.chat_sender.send(ChatWindowCommand::Clear) {
+                    log::error!("Failed to clear chat window: {}", e);
+                }
+                "Chat cleared.".to_string()
+            }
+            ChatCommand::Chat(ChatAction::Export(name)) => {
+                match self.chat_history.export(name.as_deref()) {
+                    Ok(path) => format!("Chat saved to {}", path.display()),
+                    Err(e) => format!("Couldn't export the chat: {}", e),
+                }
+            }
+            ChatCommand::Chat(ChatAction::Session(name)) => {
+                self.chat_history.switch(&name);
+                show_chat_session(&self.chat_sender, &self.chat_history);
+                format!("Switched to chat session '{}'.", self.chat_history.session())
+            }
+            ChatCommand::Chat(ChatAction::ListSessions) => {
+                let current = self.chat_history.session().to_string();
+                let sessions: Vec<String> = self
+                    .chat_history
+                    .sessions()
+                    .into_iter()
+                    .map(|name| if name == current { format!("{} (current)", name) } else { name })
+                    .collect();
+                format!("Chat sessions: {}", sessions.join(", "))
+            }
+            ChatCommand::LookAt(enabled) => {
+                let enabled = enabled.unwrap_or(!self.look_at.is_enabled());
+                self.set_look_at(enabled);
+                if enabled {
+                    "Following the cursor.".to_string()
+                } else {
+                    "Stopped following the cursor.".to_string()
                 }
-                TrayCommand::SetState(state) => {
-                    self.set_animation_state(&state);
+            }
+            ChatCommand::Effect(Some(name)) => match Effect::from_name(&name) {
+                Some(effect) => {
+                    self.effects.toggle(effect, self.effect_fade());
+                    let on = if self.effects.is_on(effect) { "on" } else { "off" };
+                    format!("Effect {} {}.", effect.name(), on)
                 }
-                TrayCommand::Quit => {
-                    log::info!("Quit requested from tray");
-                    self.should_quit = true;
+                None => format!("Unknown effect '{}' (try {}).", name, effect_names(&Effect::ALL)),
+            },
+            ChatCommand::Effect(None) => {
+                let on: Vec<Effect> = Effect::ALL.into_iter().filter(|&e| self.effects.is_on(e)).collect();
+                if on.is_empty() {
+                    format!("No effects on (try {}).", effect_names(&Effect::ALL))
+                } else {
+                    format!("Effects on: {}.", effect_names(&on))
                 }
             }
         }
     }
-}
-
-impl GhostApp for App {
-    fn init_gpu(&mut self, gpu: GpuResources<'_>) {
-        log::info!("Main window GPU initialized");
 
-        // Initialize animated skin GPU resources
-        if let Some(ref mut animated_skin) = self.animated_skin {
-            animated_skin.init_gpu(gpu.device, gpu.queue);
-            log::info!("Animated skin initialized with states: {:?}", animated_skin.available_states());
+    /// Pending reminders and todos, one per line
+    fn reminder_list(&self) -> String {
+        let lines: Vec<String> = self.reminders.pending().map(|r| r.describe()).collect();
+        if lines.is_empty() {
+            "Nothing to do!".to_string()
+        } else {
+            lines.join("\n")
         }
+    }
 
-        // Initialize layer GPU resources
-        for layer in &mut self.layers {
-            layer.init_gpu(gpu.device, gpu.queue);
+    /// Handle messages typed into the chat window
+    fn poll_chat_input(&mut self) {
+        while let Ok(input) = self.chat_input.try_recv() {
+            self.handle_chat_input(input);
         }
+    }
 
-        // Create sprite pipeline for layers
-        self.layer_pipeline = Some(SpritePipeline::new(gpu.device, gpu.format));
-        self.texture_format = Some(gpu.format);
+    /// Reply to a chat message (command or free text)
+    fn handle_chat_input(&mut self, input: String) {
+        self.note_interaction();
+        self.interact(Interaction::Chatted);
 
-        // Initialize layer text renderer
-        self.layer_renderer.init_gpu(gpu.device, gpu.queue, gpu.format);
+        self.recent_chats.retain(|message| *message != input);
+        self.recent_chats.insert(0, input.clone());
+        self.recent_chats.truncate(MAX_RECENT_CHATS);
+
+        self.chat_history.push("user", input.clone());
+        let reply = match commands::parse(&input) {
+            Some(cmd) => self.execute_command(cmd),
+            None if self.chat_client.is_some() => return self.ask_llm(),
+            None => format!("You said: \"{}\" (set a provider in [llm] to chat with an AI)", input),
+        };
+        self.reply_in_chat(reply);
     }
 
-    fn update(&mut self, delta: f32) {
-        // Poll tray menu events
-        self.poll_tray_events();
+    /// Send a message to the language model; the reply streams in through
+    /// `poll_chat_backend`
+    fn ask_llm(&mut self) {
+        let Some(ref mut client) = self.chat_client else { return };
+        if client.is_busy() {
+            self.reply_in_chat("Hold on, I'm still answering your last message.".to_string());
+            return;
+        }
+        client.send(self.chat_history.recent(self.config.llm.max_history.max(1)));
 
-        // Update animated skin
-        if let Some(ref mut animated_skin) = self.animated_skin {
-            animated_skin.update(delta);
+        // Empty reply bubble, filled as tokens arrive
+        self.chat_reply.clear();
+        let message = ChatMessage {
+            role: "assistant".to_string(),
+            content: String::new(),
+        };
+        if let Err(e) = self.chat_sender.send(ChatWindowCommand::AddMessage(message)) {
+            log::error!("Failed to send chat message: {}", e);
+        }
+        if self.config.llm.callout {
+            self.send_callout(CalloutCommand::Stream);
+        }
+        if self.is_idle() {
+            self.set_animation_state("thinking");
         }
     }
 
-    fn current_skin(&self) -> Option<&Skin> {
-        // Return current frame of animated skin if available
-        self.animated_skin.as_ref().and_then(|a| a.current_skin())
+    /// Stream the language model's reply into the chat window and callout
+    fn poll_chat_backend(&mut self) {
+        let Some(ref mut client) = self.chat_client else { return };
+        for event in client.poll() {
+            match event {
+                ChatEvent::Token(token) => {
+                    self.chat_reply.push_str(&token);
+                    if let Err(e) = self.chat_sender.send(ChatWindowCommand::AppendToLast(token.clone())) {
+                        log::error!("Failed to send chat message: {}", e);
+                    }
+                    if self.config.llm.callout {
+                        self.send_callout(CalloutCommand::Append(token));
+                    }
+                }
+                ChatEvent::Done => {
+                    let reply = std::mem::take(&mut self.chat_reply);
+                    self.note_unread(reply.clone());
+                    self.chat_history.push("assistant", reply);
+                    self.finish_llm_reply();
+                }
+                ChatEvent::Error(e) => {
+                    log::warn!("Chat backend failed: {}", e);
+                    let text = format!("Sorry, I couldn't reach the AI: {}", e);
+                    if let Err(e) = self.chat_sender.send(ChatWindowCommand::AppendToLast(text.clone())) {
+                        log::error!("Failed to send chat message: {}", e);
+                    }
+                    let reply = std::mem::take(&mut self.chat_reply) + text.as_str();
+                    self.chat_history.push("assistant", reply);
+                    self.finish_llm_reply();
+                }
+            }
+        }
     }
 
-    fn target_fps(&self) -> f32 {
-        // Use fps from config, default to 30 if not animated
-        if self.animated_skin.is_some() {
-            self.config.skin.fps
-        } else {
-            30.0
+    fn finish_llm_reply(&mut self) {
+        if self.config.llm.callout {
+            self.send_callout(CalloutCommand::EndStream);
+        }
+        let thinking = self
+            .animated_skin
+            .as_ref()
+            .is_some_and(|s| s.current_state() == AnimationState::Thinking);
+        if thinking {
+            self.restore_idle();
         }
     }
 
-    fn should_quit(&self) -> bool {
-        self.should_quit
+    /// Start loading another persona in the background, showing its
+    /// progress in a callout; the current one stays until it is ready
+    fn load_persona(&mut self, name: &str) {
+        let path = self.config.skin.persona_dir().join(name);
+        let fps = self.config.skin.fps;
+        let trust = self.config.skin.trust();
+        let loader_path = path.clone();
+        let loader =
+            SkinLoader::spawn(move |progress| SkinConfig::load_animated_from(&loader_path, fps, &trust, progress));
+        self.persona_loader = Some((name.to_string(), path, loader));
+        self.send_callout(CalloutCommand::Say(format!("Loading {}...", name)));
+        self.send_callout(CalloutCommand::Progress(Some(0.0)));
     }
 
-    fn on_event(&mut self, event: GhostEvent) {
-        match event {
-            GhostEvent::ButtonClicked(id) => {
-                // Find which button was clicked by ID
-                for btn_config in &self.config.buttons {
-                    if ui::get_button_id(&btn_config.id) == id {
-                        // Send command to callout window based on button ID
-                        match btn_config.id.as_str() {
-                            "greet" => {
-                                self.send_callout(CalloutCommand::Say("Hi, how are you today?".to_string()));
-                                log::info!("Action: Greeting");
-                            }
-                            "think" => {
-                                self.send_callout(CalloutCommand::Think("Hmm, let me think about that...".to_string()));
-                                log::info!("Action: Thinking");
-                            }
-                            "scream" => {
-                                self.send_callout(CalloutCommand::Scream("WATCH OUT!".to_string()));
-                                log::info!("Action: Screaming");
-                            }
-                            _ => {
-                                self.send_callout(CalloutCommand::Say(format!("Button '{}' clicked!", btn_config.id)));
-                                log::info!("Action: Unknown button '{}'", btn_config.id);
-                            }
-                        }
-                        break;
-                    }
+    /// Follow the background persona load and switch once it is done
+    fn poll_persona_loader(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Some((_, _, ref mut loader)) = self.persona_loader else { return };
+        let mut loaded = None;
+        let mut fraction = None;
+        for event in loader.poll() {
+            match event {
+                SkinLoadEvent::Progress(progress) => {
+                    log::debug!("Loaded state '{}' ({} frames)", progress.state, progress.frames);
+                    fraction = Some(progress.fraction());
                 }
+                SkinLoadEvent::Loaded(result) => loaded = Some(result),
             }
-            GhostEvent::Resized(_width, _height) => {
-                // Note: Don't update skin_size on resize. The skin dimensions are fixed,
-                // and layers should always be positioned relative to the original skin size.
-                // The resize event may give different values on HiDPI displays.
+        }
+        let Some(result) = loaded else {
+            if fraction.is_some() {
+                self.send_callout(CalloutCommand::Progress(fraction));
             }
-            GhostEvent::Moved(_x, _y) => {
-                // Main window moved - callout window position is updated by the event loop
+            return;
+        };
+
+        let Some((name, path, _)) = self.persona_loader.take() else { return };
+        self.send_callout(CalloutCommand::Progress(None));
+        match result {
+            Ok(mut animated_skin) => {
+                animated_skin.set_reduced_motion(self.config.accessibility.reduced_motion());
+                animated_skin.set_gpu_budget(self.config.window.gpu_budget());
+                animated_skin.init_gpu(device, queue);
+                self.animated_skin = Some(animated_skin);
+                self.config.skin.path = path.to_string_lossy().into_owned();
+                let manifest = PersonaManifest::load(&path);
+                self.state_machine = load_state_machine(&manifest);
+                self.sounds = PersonaSounds::load(&path, &manifest.sounds);
+                self.load_parts(&manifest);
+                self.load_persona_motion(&manifest);
+                self.load_recolor();
+                self.load_script(&manifest);
+                self.run_script(ScriptEvent::Start);
+                self.chat_history = ChatHistory::load(&self.config.skin.persona(), self.config.chat.history_limit);
+                show_chat_session(&self.chat_sender, &self.chat_history);
+                self.restore_idle();
+                self.send_callout(CalloutCommand::Hide);
+                log::info!("Switched persona to {}", name);
+            }
+            Err(e) => {
+                log::error!("Failed to load persona '{}': {}", path.display(), e);
+                self.send_callout(CalloutCommand::Say(format!("Couldn't load {}: {}", name, e)));
             }
-            _ => {}
         }
     }
 
-    fn buttons(&self) -> Vec<&Button> {
-        self.buttons.iter().collect()
-    }
+    /// Read ui.toml again after it changed and reapply what was built from
+    /// it at startup: buttons, the callout bubble and the chat window size.
+    /// The window opacity and settings read as they are used follow on
+    /// their own; a broken file keeps the current config.
+    fn reload_config(&mut self) {
+        let mut config = match Config::load(config::CONFIG_FILE) {
+            Ok(config) => config,
+            Err(e) => {
+                log::error!("Failed to reload {}: {}", config::CONFIG_FILE, e);
+                return;
+            }
+        };
+        accessibility::resolve(&mut config.accessibility);
+        // Switching personas is up to the tray and gallery
+        config.skin.path = self.config.skin.path.clone();
 
-    fn buttons_mut(&mut self) -> Vec<&mut Button> {
-        self.buttons.iter_mut().collect()
+        self.buttons = ui::create_buttons_from_config(&config.buttons);
+        if let Err(e) = self.callout_sender.send(CalloutCommand::Restyle(Box::new(config.clone()))) {
+            log::error!("Failed to send callout command: {}", e);
+        }
+        let (width, height) = self.skin_size;
+        let offset = config.chat.calculate_offset(width, height);
+        if config.chat.size != self.config.chat.size || offset != self.config.chat.calculate_offset(width, height) {
+            let cmd = ChatWindowCommand::Resize { size: config.chat.size, offset };
+            if let Err(e) = self.chat_sender.send(cmd) {
+                log::error!("Failed to send chat window command: {}", e);
+            }
+        }
+        self.config = config;
+        // Redraw with the new buttons
+        self.layers_changed = true;
+        log::info!("Reloaded {}", config::CONFIG_FILE);
     }
 
-    fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, viewport: [f32; 2], scale_factor: f32, opacity: f32) {
-        // Prepare layer bind groups with window opacity
-        if let Some(pipeline) = &self.layer_pipeline {
-            for layer in &mut self.layers {
-                layer.prepare_with_opacity(pipeline, device, queue, viewport, scale_factor, opacity);
+    /// Load the animated skin again after its files changed, staying in
+    /// the current state; the old skin is kept if the new one is broken
+    fn reload_skin(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        match self.config.skin.load_animated() {
+            Ok(mut animated_skin) => {
+                animated_skin.set_reduced_motion(self.config.accessibility.reduced_motion());
+                animated_skin.set_gpu_budget(self.config.window.gpu_budget());
+                animated_skin.init_gpu(device, queue);
+                if let Some(state) = self.animated_skin.as_ref().map(AnimatedSkin::current_state) {
+                    if animated_skin.has_state(state) {
+                        animated_skin.set_state(state);
+                    }
+                }
+                self.animated_skin = Some(animated_skin);
+                let path = Path::new(&self.config.skin.path);
+                let manifest = PersonaManifest::load(path);
+                self.state_machine = load_state_machine(&manifest);
+                self.sounds = PersonaSounds::load(path, &manifest.sounds);
+                self.load_parts(&manifest);
+                self.load_persona_motion(&manifest);
+                self.load_recolor();
+                // An edited script picks up where the old one was, without greeting again
+                let previous = self.script.take();
+                self.load_script(&manifest);
+                if let (Some(script), Some(previous)) = (&mut self.script, previous) {
+                    script.carry_over(previous);
+                }
+                log::info!("Reloaded persona {}", self.config.skin.persona());
             }
+            Err(e) => log::error!("Failed to reload skin '{}': {}", self.config.skin.path, e),
         }
+    }
 
-        // Prepare layer text rendering
-        for layer in &self.layers {
-            if layer.text().is_some() {
-                self.layer_renderer.prepare_text(device, queue, layer, viewport, scale_factor);
+    /// Handle commands received over IPC
+    fn poll_ipc(&mut self) {
+        let Some(ref receiver) = self.ipc_receiver else { return };
+        let requests: Vec<IpcRequest> = receiver.try_iter().collect();
+
+        for request in requests {
+            let reply = match commands::parse(&request.line) {
+                // Anyone on the machine can connect, so they don't get to name files
+                Some(ChatCommand::Chat(ChatAction::Export(Some(_)))) => {
+                    "error: export file names can only be given in the chat window".to_string()
+                }
+                Some(cmd) => self.execute_command(cmd),
+                None => format!("error: unknown command '{}'", request.line),
+            };
+            let _ = request.reply.send(reply);
+        }
+    }
+
+    /// Announce reminders that became due
+    fn poll_reminders(&mut self, delta: f32) {
+        self.reminder_check_timer -= delta;
+        if self.reminder_check_timer > 0.0 {
+            return;
+        }
+        self.reminder_check_timer = 1.0;
+
+        for reminder in self.reminders.take_due() {
+            log::info!("Reminder due: {}", reminder.text);
+            self.send_callout(CalloutCommand::Alert(reminder.text.clone()));
+            if self.config.reminders.notify {
+                notification::notify_desktop("Reminder", &reminder.text);
             }
         }
     }
 
-    fn render_layers<'a>(
-        &'a mut self,
-        _device: &wgpu::Device,
-        _queue: &wgpu::Queue,
-        _viewport: [f32; 2],
-        render_pass: &mut wgpu::RenderPass<'a>,
-    ) {
-        // Render layer images
-        if let Some(pipeline) = &self.layer_pipeline {
-            for layer in &self.layers {
-                if let Some(bind_group) = layer.bind_group() {
-                    pipeline.render_bind_group(render_pass, bind_group);
+    /// Start a pomodoro cycle
+    fn start_pomodoro(&mut self) {
+        self.pomodoro.start();
+        self.announce_pomodoro_phase();
+    }
+
+    /// Stop the pomodoro and restore the label layer
+    fn stop_pomodoro(&mut self) {
+        self.pomodoro.stop();
+        if let Some(id) = self.pomodoro.label_layer().map(str::to_string) {
+            self.restore_layer_text(&id);
+        }
+        self.restore_idle();
+    }
+
+    /// Announce the current pomodoro phase via callout and animation state
+    fn announce_pomodoro_phase(&mut self) {
+        let text = self.pomodoro.phase_text().to_string();
+        let state = self.pomodoro.phase_state().to_string();
+        self.send_callout(CalloutCommand::Say(text));
+        self.set_animation_state(&state);
+        log::info!("Pomodoro phase: {:?}", self.pomodoro.phase());
+    }
+
+    /// Advance the pomodoro and refresh its label
+    fn update_pomodoro(&mut self, delta: f32) {
+        if !self.pomodoro.is_running() {
+            return;
+        }
+
+        if self.pomodoro.update(delta).is_some() {
+            self.announce_pomodoro_phase();
+        }
+
+        if let Some(id) = self.pomodoro.label_layer().map(str::to_string) {
+            let label = self.pomodoro.remaining_label();
+            self.set_layer_text(&id, Some(label));
+        }
+    }
+
+    /// Refresh system metrics and run threshold reactions
+    fn update_monitor(&mut self, delta: f32) {
+        let Some(ref mut monitor) = self.monitor else { return };
+        let label_layer = self.config.monitor.label_layer.clone();
+        let Some(events) = monitor.update(delta) else {
+            if let Some(ref id) = label_layer {
+                if self.monitor_marquee.update(delta) {
+                    self.set_layer_text(id, Some(self.monitor_marquee.visible_text()));
                 }
             }
+            return;
+        };
+
+        let metrics = *monitor.metrics();
+        self.vars.set("cpu", format!("{:.0}", metrics.cpu));
+        self.vars.set("memory", format!("{:.0}", metrics.memory));
+        if let Some(battery) = metrics.battery {
+            self.vars.set("battery", format!("{:.0}", battery));
         }
+        self.vars.set("download", format!("{:.0}", metrics.download));
+        self.vars.set("upload", format!("{:.0}", metrics.upload));
 
-        // Render layer text
-        self.layer_renderer.render_text(render_pass);
+        if let Some(ref id) = label_layer {
+            self.monitor_marquee.set_text(self.vars.expand(&self.config.monitor.label));
+            self.set_layer_text(id, Some(self.monitor_marquee.visible_text()));
+        }
+
+        for event in events {
+            match event {
+                ReactionEvent::Triggered(reaction) => {
+                    log::info!("Monitor reaction: {} ({:?})", reaction.metric, metrics);
+                    if let Some(ref state) = reaction.state {
+                        self.set_animation_state(state);
+                    }
+                    if let Some(text) = reaction.text {
+                        self.send_callout(CalloutCommand::from_kind(&reaction.callout, text));
+                    }
+                }
+                ReactionEvent::Cleared(reaction) => {
+                    if reaction.state.is_some() {
+                        self.restore_idle();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Announce today's forecast, fetching fresh data first
+    fn announce_weather(&mut self) {
+        let Some(ref mut weather) = self.weather else {
+            log::warn!("Weather announcement requested but [weather] is not enabled");
+            return;
+        };
+        weather.refresh();
+        self.weather_announce_pending = true;
+    }
+
+    /// Pick up weather results and publish `{weather}` / `{forecast}`
+    fn update_weather(&mut self, delta: f32) {
+        let Some(ref mut provider) = self.weather else { return };
+        let Some(weather) = provider.update(delta) else { return };
+
+        let (summary, forecast) = (weather.summary(), weather.forecast());
+        self.vars.set("weather", summary);
+        self.vars.set("forecast", forecast.clone());
+
+        if self.weather_announce_pending {
+            self.weather_announce_pending = false;
+            self.send_callout(CalloutCommand::Say(forecast));
+        }
+    }
+
+    /// Announce calendar events that are about to start
+    fn update_calendar(&mut self, delta: f32) {
+        let Some(ref mut calendar) = self.calendar else { return };
+        let lead = calendar.lead_minutes();
+        let events = calendar.update(delta);
+
+        for event in events {
+            let minutes = ((event.start - chrono::Local::now()).num_seconds() as f32 / 60.0)
+                .ceil()
+                .clamp(0.0, lead);
+            let text = format!("\"{}\" starts in {:.0} min ({})", event.summary, minutes, event.start.format("%H:%M"));
+            log::info!("Calendar: {}", text);
+            self.send_callout(CalloutCommand::Alert(text.clone()));
+            if self.config.calendar.notify {
+                notification::notify_desktop("Upcoming event", &text);
+            }
+        }
+    }
+
+    /// React to track changes and scroll the now-playing label
+    fn update_media(&mut self, delta: f32) {
+        let Some(ref media) = self.media else { return };
+        let label_layer = self.config.media.label_layer.clone();
+
+        if let Some(change) = media.poll() {
+            match change {
+                Some(track) => {
+                    let display = track.display();
+                    log::info!("Now playing: {}", display);
+                    self.vars.set("track", display.clone());
+                    self.media_marquee.set_text(display);
+
+                    if !self.config.media.text.is_empty() {
+                        self.send_callout(CalloutCommand::Say(self.config.media.text.clone()));
+                    }
+                    if let Some(state) = self.config.media.state.clone() {
+                        self.set_animation_state(&state);
+                    }
+                    if let Some(ref id) = label_layer {
+                        self.set_layer_text(id, Some(self.media_marquee.visible_text()));
+                    }
+                }
+                None => {
+                    self.vars.set("track", "");
+                    self.media_marquee.set_text("");
+                    if let Some(ref id) = label_layer {
+                        self.restore_layer_text(id);
+                    }
+                }
+            }
+        }
+
+        if let Some(ref id) = label_layer {
+            if self.media_marquee.update(delta) {
+                self.set_layer_text(id, Some(self.media_marquee.visible_text()));
+            }
+        }
+    }
+
+    /// Show accepted desktop notifications as callouts
+    fn poll_notifications(&mut self) {
+        let Some(ref bridge) = self.notifications else { return };
+        let config = &self.config.notifications;
+        let texts: Vec<String> = bridge
+            .poll()
+            .into_iter()
+            .filter(|notification| notification.accepted_by(config))
+            .map(|notification| notification.format(&config.text))
+            .filter(|text| !text.is_empty())
+            .collect();
+        let Some(text) = texts.into_iter().last() else { return };
+        log::info!("Notification: {}", text);
+        self.send_callout(CalloutCommand::from_kind(&self.config.notifications.callout, text));
+        if let Some(state) = self.config.notifications.state.clone() {
+            self.set_animation_state(&state);
+        }
+    }
+
+    /// Run any scheduled actions that are due
+    fn poll_schedule(&mut self) {
+        if self.scheduler.is_empty() {
+            return;
+        }
+
+        for action in self.scheduler.poll() {
+            if let Some(cmd) = action.callout.clone() {
+                self.send_callout(cmd);
+            }
+            if let Some(ref state) = action.state {
+                self.set_animation_state(state);
+            }
+            if action.weather {
+                self.announce_weather();
+            }
+            action.spawn_script();
+        }
+    }
+
+    /// Turn cursor tracking on or off
+    fn set_look_at(&mut self, enabled: bool) {
+        self.look_at.set_enabled(enabled);
+        if !enabled {
+            self.apply_look_offset();
+        }
+        log::info!("Cursor tracking {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Move the look-at layer to the current offset
+    fn apply_look_offset(&mut self) {
+        let Some(id) = self.look_at.layer() else { return };
+        let index = self.layer_ids.iter().position(|l| l.as_deref() == Some(id));
+        match index {
+            Some(i) => {
+                self.layers[i].set_dynamic_offset(self.look_at.offset());
+                self.layers_changed = true;
+            }
+            None => log::warn!("No layer with id '{}'", id),
+        }
+    }
+
+    /// Ease the look-at layer toward the cursor
+    fn update_look_at(&mut self, delta: f32) {
+        if self.look_at.is_enabled() && self.look_at.update(delta) {
+            // The persona's own eyes follow in `update_parts`
+            self.layers_changed |= self.gaze.layer.is_some();
+            self.apply_look_offset();
+        }
+    }
+
+    /// Point the eyes look from: the middle of the persona's eyes part, or
+    /// of the skin
+    fn gaze_center(&self) -> [f32; 2] {
+        let eyes = self.gaze.layer.as_deref().and_then(|layer| {
+            let index = self.part_paths.iter().position(|path| path == layer)?;
+            Some(&self.parts[index])
+        });
+        match eyes {
+            Some(part) => {
+                let (width, height) = part.dimensions();
+                let [x, y] = part.position();
+                [x + width as f32 / 2.0, y + height as f32 / 2.0]
+            }
+            None => [self.skin_size.0 as f32 / 2.0, self.skin_size.1 as f32 / 2.0],
+        }
+    }
+
+    /// Load the parts of the current persona's composite skin, sorted by
+    /// z_order
+    fn load_parts(&mut self, manifest: &PersonaManifest) {
+        self.parts.clear();
+        self.part_paths.clear();
+        self.layers_changed = true;
+        let persona_dir = Path::new(&self.config.skin.path);
+        if !manifest.layers.is_empty() && !persona_dir.is_dir() {
+            log::warn!("Persona layers are only loaded from persona folders, not {}", persona_dir.display());
+            return;
+        }
+        let mut parts = Vec::new();
+        for part in &manifest.layers {
+            let path = persona_dir.join(&part.path);
+            match AnimatedSkin::from_directory(&path, part.fps.unwrap_or(self.config.skin.fps)) {
+                Ok(mut skin) => {
+                    skin.set_reduced_motion(self.config.accessibility.reduced_motion());
+                    let config = LayerConfig {
+                        anchor: LayerAnchor::from_str(&part.anchor),
+                        offset: part.offset,
+                        size: part.size,
+                        z_order: part.z_order,
+                        ..LayerConfig::default()
+                    };
+                    let mut layer = Layer::animated(skin, config);
+                    layer.calculate_position(self.skin_size.0, self.skin_size.1);
+                    log::info!("Loaded persona layer: {} at position {:?}", part.path, layer.position());
+                    parts.push((part.path.clone(), layer));
+                }
+                Err(e) => log::error!("Failed to load persona layer '{}': {}", path.display(), e),
+            }
+        }
+        parts.sort_by_key(|(_, part)| part.config.z_order);
+        (self.part_paths, self.parts) = parts.into_iter().unzip();
+    }
+
+    /// Start the persona's generated blinks and breathing (unless motion
+    /// is reduced), its lip-sync and its gaze
+    fn load_persona_motion(&mut self, manifest: &PersonaManifest) {
+        self.auto_life = manifest
+            .auto_life
+            .clone()
+            .filter(|_| !self.config.accessibility.reduced_motion())
+            .map(AutoLife::new);
+        self.lip_sync = manifest.lip_sync.clone().map(LipSync::new);
+        self.gaze = manifest.gaze.clone().unwrap_or_default();
+        self.look_at.set_max_offset(self.gaze.max_offset.unwrap_or(self.config.look_at.max_offset));
+    }
+
+    /// Pick the persona's color variant from `[[recolor]]`
+    fn load_recolor(&mut self) {
+        self.recolor = self.config.recolor(&self.config.skin.persona());
+    }
+
+    /// Play each part's animation for the body's state, blinking,
+    /// lip-syncing and breathing along with the body
+    fn update_parts(&mut self, delta: f32) {
+        // States forced by the gaze, blinks and speech (later ones win), on
+        // a part or the body (None), with the level picking the frame
+        let mut overrides: Vec<(Option<String>, AnimationState, Option<f32>)> = Vec::new();
+        let look = self.look_at.direction().filter(|_| self.look_at.is_enabled());
+        if let Some(state) = look.and_then(|direction| self.gaze.state(direction)) {
+            overrides.push((self.gaze.layer.clone(), AnimationState::from_str(state), None));
+        }
+        let mut scale = 1.0;
+        if let Some(ref mut auto_life) = self.auto_life {
+            auto_life.update(delta);
+            if auto_life.eyes_closed() {
+                let state = AnimationState::from_str(auto_life.blink_state());
+                overrides.push((auto_life.blink_layer().map(str::to_string), state, None));
+            }
+            scale = auto_life.breathing_scale();
+        }
+        if let Some(ref mut lip_sync) = self.lip_sync {
+            let audio = self.sound.as_ref().map_or(0.0, SoundPlayer::level);
+            if let Some(level) = lip_sync.update(delta, audio) {
+                let state = AnimationState::from_str(lip_sync.state());
+                overrides.push((lip_sync.layer().map(str::to_string), state, Some(level)));
+            }
+        }
+
+        // The body takes over from idle and returns to it afterwards
+        if let Some(ref mut skin) = self.animated_skin {
+            let current = skin.current_state();
+            let free = current == AnimationState::Idle || self.body_override == Some(current);
+            match overrides.iter().rev().find(|(layer, _, _)| layer.is_none()) {
+                Some(&(_, state, level)) if free && skin.has_state(state) => {
+                    skin.set_state(state);
+                    skin.set_level(level);
+                    self.body_override = Some(state);
+                }
+                _ => {
+                    if let Some(state) = self.body_override.take() {
+                        skin.set_level(None);
+                        if skin.current_state() == state {
+                            skin.set_state(AnimationState::Idle);
+                        }
+                    }
+                }
+            }
+        }
+
+        let state = self.animated_skin.as_ref().map(AnimatedSkin::current_state);
+        let body_height = self.skin_size.1 as f32;
+        let pupils = self.gaze.layer.as_deref().filter(|_| !self.gaze.has_states());
+        for (part, path) in self.parts.iter_mut().zip(&self.part_paths) {
+            match overrides.iter().rev().find(|(layer, _, _)| layer.as_deref() == Some(path.as_str())) {
+                Some(&(_, state, level)) => {
+                    part.follow_state(state);
+                    part.set_level(level);
+                }
+                None => {
+                    part.set_level(None);
+                    if let Some(state) = state {
+                        part.follow_state(state);
+                    }
+                }
+            }
+            self.layers_changed |= part.update(delta);
+            // Parts ride along as the body shrinks towards its bottom edge,
+            // the pupils also shift toward the cursor
+            part.set_dynamic_offset([0.0, 0.0]);
+            let mut offset = [0.0, (body_height - part.position()[1]) * (1.0 - scale)];
+            if pupils == Some(path.as_str()) {
+                let look = self.look_at.offset();
+                offset = [offset[0] + look[0], offset[1] + look[1]];
+            }
+            part.set_dynamic_offset(offset);
+        }
+    }
+
+    /// Load the current persona's behavior script
+    fn load_script(&mut self, manifest: &PersonaManifest) {
+        self.script = Script::load(Path::new(&self.config.skin.path), manifest);
+    }
+
+    /// Run the script handlers for `event`
+    fn run_script(&mut self, event: ScriptEvent) {
+        let actions = self.script.as_mut().map(|script| script.on_event(event)).unwrap_or_default();
+        self.run_script_actions(actions);
+    }
+
+    /// Fire due idle and time handlers
+    fn update_script(&mut self, delta: f32) {
+        let Some(ref mut script) = self.script else { return };
+        let actions = script.update(delta, chrono::Local::now().naive_local());
+        self.run_script_actions(actions);
+    }
+
+    fn run_script_actions(&mut self, actions: Vec<ScriptAction>) {
+        for action in actions {
+            match action {
+                ScriptAction::Callout { kind, text } => self.send_callout(CalloutCommand::from_kind(&kind, text)),
+                ScriptAction::State(state) => self.set_animation_state(&state),
+                ScriptAction::Move { dx, dy, easing } => {
+                    if let Some((x, y)) = self.position {
+                        let duration = if self.config.accessibility.reduced_motion() { 0.0 } else { script::MOVE_DURATION };
+                        let (x, y) = (x.saturating_add(dx), y.saturating_add(dy));
+                        self.move_request = Some(MoveRequest::To { x, y, duration, easing });
+                    }
+                }
+            }
+        }
+    }
+
+    /// The user interacted with the ghost: stop wandering for a while
+    fn note_interaction(&mut self) {
+        if let Some(ref mut script) = self.script {
+            script.note_activity();
+        }
+        let Some(ref mut wander) = self.wander else { return };
+        if wander.interrupt() {
+            self.move_request = Some(MoveRequest::Stop);
+            self.restore_idle();
+        }
+    }
+
+    /// React to being petted: happy animation, hearts and a callout
+    fn on_petted(&mut self) {
+        if self.petting_cooldown > 0.0 {
+            return;
+        }
+        self.petting_cooldown = self.config.petting.cooldown;
+        log::info!("Petted");
+        self.run_script(ScriptEvent::Pet);
+
+        self.note_interaction();
+        self.interact(Interaction::Petted);
+        // The persona's state machine may pick its own animation
+        if !self.trigger_animation("petted") {
+            if let Some(ref mut animated_skin) = self.animated_skin {
+                let state = AnimationState::from_str(&self.config.petting.state);
+                if animated_skin.has_state(state) {
+                    animated_skin.play_once(state);
+                }
+            }
+        }
+
+        if !self.config.accessibility.reduced_motion() {
+            let origin = [self.skin_size.0 as f32 / 2.0, self.skin_size.1 as f32 / 4.0];
+            self.hearts.burst(origin, self.config.petting.hearts);
+        }
+
+        if !self.config.petting.text.is_empty() {
+            let petting = &self.config.petting;
+            let cmd = CalloutCommand::from_kind(&petting.callout, petting.text.clone());
+            self.send_callout(cmd);
+        }
+    }
+
+    /// Fire a state machine event; returns true if it changed the animation
+    fn trigger_animation(&mut self, event: &str) -> bool {
+        match (&mut self.state_machine, &mut self.animated_skin) {
+            (Some(state_machine), Some(animated_skin)) => state_machine.trigger(animated_skin, event),
+            _ => false,
+        }
+    }
+
+    /// Play the animation matching the movement engine's state
+    fn on_motion_changed(&mut self, state: MotionState) {
+        log::debug!("Motion: {:?}", state);
+        self.motion_state = state;
+        if state == MotionState::Dragged {
+            self.note_interaction();
+        }
+        let motion = &self.config.motion;
+        let animation = match state {
+            MotionState::Standing => None,
+            MotionState::Walking { .. } => Some(motion.walk_state.clone()),
+            MotionState::Falling => Some(motion.fall_state.clone()),
+            MotionState::Dragged => Some(motion.drag_state.clone()),
+        };
+        match animation {
+            Some(animation) => self.set_animation_state(&animation),
+            None => self.restore_idle(),
+        }
+    }
+
+    /// Seconds effects take to fade (instant with reduced motion)
+    fn effect_fade(&self) -> f32 {
+        if self.config.accessibility.reduced_motion() {
+            0.0
+        } else {
+            self.config.effects.fade.max(0.0)
+        }
+    }
+
+    /// Start a walk when it's time (only while idle)
+    fn update_wander(&mut self, delta: f32) {
+        let idle = self.is_idle();
+        let Some(ref mut wander) = self.wander else { return };
+        if !idle && wander.walking_left().is_none() {
+            return;
+        }
+
+        if let Some(request) = wander.update(delta) {
+            let state = wander.walk_state().to_string();
+            self.move_request = Some(request);
+            self.set_animation_state(&state);
+        }
+    }
+
+    /// Switch time-of-day behaviors (state, effects and greeting; opacity is
+    /// applied by the window)
+    fn update_behaviors(&mut self, delta: f32) {
+        let Some(change) = self.behaviors.update(delta) else { return };
+        let fade = self.effect_fade();
+
+        if let Some(left) = change.left {
+            log::info!("Behavior ended: {}", left.name);
+            let restores_state = change.entered.as_ref().is_none_or(|b| b.state.is_none());
+            if left.state.is_some() && restores_state {
+                self.restore_idle();
+            }
+            for &effect in &left.effects {
+                self.effects.set(effect, false, fade);
+            }
+        }
+
+        if let Some(entered) = change.entered {
+            log::info!("Behavior started: {}", entered.name);
+            if let Some(ref state) = entered.state {
+                self.set_animation_state(state);
+            }
+            for &effect in &entered.effects {
+                self.effects.set(effect, true, fade);
+            }
+            if let Some(greeting) = entered.greeting {
+                self.send_callout(greeting);
+            }
+        }
+    }
+
+    /// Set `{persona}` and `{activity}` and pass them on to Discord
+    fn update_activity(&mut self, delta: f32) {
+        let activity = if self.pomodoro.is_running() {
+            match self.pomodoro.phase() {
+                Phase::Work => "In a pomodoro".to_string(),
+                Phase::ShortBreak | Phase::LongBreak => "On a break".to_string(),
+            }
+        } else {
+            match self.vars.get("track") {
+                Some(track) if !track.is_empty() => format!("Listening to {}", track),
+                _ => format!("Feeling {}", self.vars.mood().kind().name()),
+            }
+        };
+        if self.vars.get("activity") != Some(activity.as_str()) {
+            self.vars.set("activity", activity);
+        }
+        let persona = self.config.skin.persona();
+        if self.vars.get("persona") != Some(persona.as_str()) {
+            self.vars.set("persona", persona);
+        }
+
+        if let Some(ref mut discord) = self.discord {
+            discord.update(delta, &self.vars);
+        }
+    }
+
+    /// Rebuild the tray menu when checkmarks or lists changed, and animate the icon
+    fn refresh_tray(&mut self, delta: f32) {
+        let state = self.tray_state();
+        let thinking = self
+            .animated_skin
+            .as_ref()
+            .is_some_and(|s| s.current_state() == AnimationState::Thinking);
+        if let Some(ref mut tray) = self.tray {
+            tray.rebuild(&state);
+            tray.update_icon(delta, thinking, self.unread.len() as u32);
+        }
+    }
+
+    /// Carry out a tray or context menu choice
+    fn run_tray_command(&mut self, cmd: TrayCommand) {
+        match cmd {
+            TrayCommand::OpenChat => {
+                self.open_chat_window();
+            }
+            TrayCommand::SetState(state) => {
+                self.set_animation_state(&state);
+            }
+            TrayCommand::ShowReminders => {
+                self.open_chat_window();
+                self.reply_in_chat(self.reminder_list());
+            }
+            TrayCommand::TogglePomodoro => {
+                if self.pomodoro.is_running() {
+                    self.stop_pomodoro();
+                } else {
+                    self.start_pomodoro();
+                }
+            }
+            TrayCommand::ToggleLookAt => {
+                self.set_look_at(!self.look_at.is_enabled());
+            }
+            TrayCommand::SetPersona(name) => {
+                self.pending_persona = Some(name);
+            }
+            TrayCommand::ResendChat(message) => {
+                self.open_chat_window();
+                let message_echo = ChatMessage {
+                    role: "user".to_string(),
+                    content: message.clone(),
+                };
+                if let Err(e) = self.chat_sender.send(ChatWindowCommand::AddMessage(message_echo)) {
+                    log::error!("Failed to send chat message: {}", e);
+                }
+                self.handle_chat_input(message);
+            }
+            TrayCommand::ShowDebug => {
+                self.send_debug(DebugWindowCommand::Show);
+            }
+            TrayCommand::ToggleMute => {
+                if let Some(ref mut sound) = self.sound {
+                    sound.set_muted(!sound.is_muted());
+                }
+            }
+            TrayCommand::SetVolume(volume) => {
+                if let Some(ref mut sound) = self.sound {
+                    sound.set_volume(volume);
+                }
+            }
+            TrayCommand::ToggleAutostart => {
+                let result = if self.autostart { autostart::disable() } else { autostart::enable() };
+                match result {
+                    Ok(()) => self.autostart = !self.autostart,
+                    Err(e) => log::error!("Failed to change autostart: {}", e),
+                }
+            }
+            TrayCommand::ShowGallery => {
+                if let Some(ref sender) = self.gallery_sender {
+                    let active = self.config.skin.persona();
+                    if let Err(e) = sender.send(GalleryCommand::Show { active }) {
+                        log::error!("Failed to send gallery command: {}", e);
+                    }
+                }
+            }
+            TrayCommand::SetVisible(visible) => {
+                self.hidden = !visible;
+                if !visible {
+                    self.send_callout(CalloutCommand::Hide);
+                }
+                // The window dissolves the body only; dissolve the persona's
+                // parts along with it
+                if let Some((Transition::Dissolve, duration)) = self.visibility_transition() {
+                    self.effects.set(Effect::Dissolve, !visible, duration.as_secs_f32());
+                }
+                self.visibility_request = Some(visible);
+            }
+            TrayCommand::RunAction(id) => {
+                self.run_button_action(&id);
+            }
+            TrayCommand::ExplainClipboard => self.explain_clipboard(),
+            TrayCommand::CopyReply => self.copy_last_reply(),
+            TrayCommand::Quit => {
+                log::info!("Quit requested from tray");
+                self.should_quit = true;
+            }
+        }
+    }
+
+    /// Carry out the action of a global shortcut
+    fn run_hotkey_action(&mut self, action: HotkeyAction) {
+        match action {
+            HotkeyAction::ToggleChat => {
+                if let Err(e) = self.chat_sender.send(ChatWindowCommand::Toggle) {
+                    log::error!("Failed to send chat window toggle command: {}", e);
+                }
+            }
+            HotkeyAction::ToggleVisibility => self.run_tray_command(TrayCommand::SetVisible(self.hidden)),
+            HotkeyAction::NextPersona => {
+                let active = self.config.skin.persona();
+                if let Some(name) = hotkeys::next_persona(&self.personas, &active) {
+                    self.pending_persona = Some(name.clone());
+                }
+            }
+            HotkeyAction::Persona(name) => self.run_tray_command(TrayCommand::SetPersona(name)),
+            HotkeyAction::Say(text) => self.send_callout(CalloutCommand::Say(text)),
+        }
+    }
+
+    /// Send the clipboard text to the chat backend
+    fn explain_clipboard(&mut self) {
+        let text = match clipboard::read() {
+            Ok(text) => text,
+            Err(e) => {
+                log::warn!("Failed to read the clipboard: {}", e);
+                self.send_callout(CalloutCommand::Say("I can't read the clipboard.".to_string()));
+                return;
+            }
+        };
+        match clipboard::explain_prompt(&text) {
+            Some(prompt) => self.run_tray_command(TrayCommand::ResendChat(prompt)),
+            None => self.send_callout(CalloutCommand::Say("The clipboard is empty.".to_string())),
+        }
+    }
+
+    /// The last assistant message in the chat history
+    fn last_reply(&self) -> Option<&str> {
+        self.chat_history
+            .messages()
+            .iter()
+            .rev()
+            .find(|message| message.role == "assistant" && !message.content.is_empty())
+            .map(|message| message.content.as_str())
+    }
+
+    /// Put the last assistant reply on the clipboard
+    fn copy_last_reply(&mut self) {
+        let Some(reply) = self.last_reply() else {
+            self.send_callout(CalloutCommand::Say("There's no reply to copy yet.".to_string()));
+            return;
+        };
+        match clipboard::write(reply) {
+            Ok(()) => self.send_callout(CalloutCommand::Whisper("Copied!".to_string())),
+            Err(e) => {
+                log::warn!("Failed to write the clipboard: {}", e);
+                self.send_callout(CalloutCommand::Say("I couldn't copy that.".to_string()));
+            }
+        }
+    }
+
+    /// Run the action of a button or custom menu entry
+    fn run_button_action(&mut self, id: &str) {
+        match id {
+            "explain_clipboard" => self.explain_clipboard(),
+            "copy_reply" => self.copy_last_reply(),
+            "greet" => {
+                self.send_callout(CalloutCommand::Say("Hi, how are you today?".to_string()));
+                log::info!("Action: Greeting");
+            }
+            "think" => {
+                self.send_callout(CalloutCommand::Think("Hmm, let me think about that...".to_string()));
+                log::info!("Action: Thinking");
+            }
+            "scream" => {
+                self.send_callout(CalloutCommand::Scream("WATCH OUT!".to_string()));
+                log::info!("Action: Screaming");
+            }
+            _ => {
+                self.send_callout(CalloutCommand::Say(format!("Button '{}' clicked!", id)));
+                log::info!("Action: Unknown button '{}'", id);
+            }
+        }
+    }
+}
+
+impl GhostApp for App {
+    fn init_gpu(&mut self, gpu: GpuResources<'_>) {
+        log::info!("Main window GPU initialized");
+
+        // Initialize animated skin GPU resources
+        if let Some(ref mut animated_skin) = self.animated_skin {
+            animated_skin.init_gpu(gpu.device, gpu.queue);
+            log::info!("Animated skin initialized with states: {:?}", animated_skin.available_states());
+        }
+
+        // Initialize layer GPU resources
+        for layer in &mut self.layers {
+            layer.init_gpu(gpu.device, gpu.queue);
+        }
+
+        // Create sprite pipeline for layers
+        self.layer_pipeline = Some(SpritePipeline::new(gpu.device, gpu.format));
+        self.texture_format = Some(gpu.format);
+
+        self.hearts.init_gpu(gpu.device, gpu.queue);
+
+        // Initialize layer text renderer
+        self.layer_renderer.init_gpu(gpu.device, gpu.queue, gpu.format);
+    }
+
+    fn update(&mut self, delta: f32) {
+        // Handle chat input (commands and messages) and IPC
+        self.poll_chat_input();
+        self.poll_chat_backend();
+        self.poll_ipc();
+
+        // Announce due reminders
+        self.poll_reminders(delta);
+
+        // Run scheduled actions
+        self.poll_schedule();
+
+        // Apply time-of-day behaviors
+        self.update_behaviors(delta);
+
+        // Run the persona's idle and time handlers
+        self.update_script(delta);
+
+        // Let the mood drift
+        self.update_mood(delta);
+
+        // Advance the pomodoro timer
+        self.update_pomodoro(delta);
+
+        // Poll system metrics
+        self.update_monitor(delta);
+
+        // Refresh weather
+        self.update_weather(delta);
+
+        // Announce upcoming calendar events
+        self.update_calendar(delta);
+
+        // Track now-playing media
+        self.update_media(delta);
+
+        // Repeat other apps' notifications
+        self.poll_notifications();
+
+        // Follow the cursor
+        self.update_look_at(delta);
+
+        // Walk around
+        self.update_wander(delta);
+
+        // Float hearts (one more redraw clears the last of them)
+        self.layers_changed |= self.hearts.is_active();
+        self.hearts.update(delta);
+        self.petting_cooldown = (self.petting_cooldown - delta).max(0.0);
+
+        // Update animated skin
+        if let Some(ref mut animated_skin) = self.animated_skin {
+            animated_skin.update(delta);
+            if let Some(ref mut state_machine) = self.state_machine {
+                state_machine.update(animated_skin, delta);
+            }
+        }
+        self.update_state_sound();
+
+        // Animate the parts of a composite persona (e.g. lip-sync)
+        self.update_parts(delta);
+
+        // Fade glow, dissolve and other skin effects
+        self.effects.update(delta);
+
+        // Publish what the ghost is doing
+        self.update_activity(delta);
+
+        // Keep tray checkmarks and lists current
+        self.refresh_tray(delta);
+
+        // Keep the crash snapshot current
+        self.update_snapshot(delta);
+
+        // Feed the debug console
+        self.update_debug(delta);
+
+        // Persona switches and downloads from the gallery
+        self.update_gallery();
+    }
+
+    fn current_skin(&self) -> Option<&Skin> {
+        // Return current frame of animated skin if available
+        self.animated_skin.as_ref().and_then(|a| a.current_skin())
+    }
+
+    fn skin_scale(&self) -> f32 {
+        self.auto_life.as_ref().map_or(1.0, AutoLife::breathing_scale)
+    }
+
+    fn recolor(&self) -> Option<&Recolor> {
+        self.recolor.as_ref()
+    }
+
+    fn skin_effects(&self) -> EffectParams {
+        self.effects.params()
+    }
+
+    fn opacity_override(&self) -> Option<f32> {
+        self.behaviors.current().and_then(|b| b.opacity)
+    }
+
+    fn needs_continuous_update(&self) -> bool {
+        self.animated_skin.as_ref().is_some_and(AnimatedSkin::is_animating)
+            || self.parts.iter().any(Layer::is_animating)
+            || self.auto_life.as_ref().is_some_and(AutoLife::is_breathing)
+            || self.lip_sync.as_ref().is_some_and(LipSync::is_speaking)
+            || self.look_at.is_enabled()
+            || self.hearts.is_active()
+            || self.effects.is_animating()
+            || self.motion_enabled
+            || self.chat_client.as_ref().is_some_and(ChatClient::is_busy)
+            || self.persona_loader.is_some()
+    }
+
+    fn take_redraw_request(&mut self) -> bool {
+        let skin_changed = self.animated_skin.as_mut().is_some_and(AnimatedSkin::take_changed);
+        // Persona switches and reloads are picked up while preparing a frame
+        let skin_pending = self.pending_persona.is_some() || self.skin_changed || self.persona_loader.is_some();
+        std::mem::take(&mut self.layers_changed) || skin_changed || skin_pending
+    }
+
+    fn skin_path(&self) -> Option<&Path> {
+        self.config.skin.hot_reload.then(|| Path::new(&self.config.skin.path))
+    }
+
+    fn config_path(&self) -> Option<&Path> {
+        self.config.hot_reload.then(|| Path::new(config::CONFIG_FILE))
+    }
+
+    fn focus_opacity(&self) -> Option<(f32, f32)> {
+        Some(self.config.window.opacity())
+    }
+
+    fn skin_flipped(&self) -> bool {
+        // Face the walking direction, otherwise the cursor
+        let motion_left = match self.motion_state {
+            MotionState::Walking { left } => Some(left),
+            _ => None,
+        };
+        let facing_left = self
+            .wander
+            .as_ref()
+            .and_then(|w| w.walking_left())
+            .or(motion_left)
+            .or(self.look_at.facing_left());
+        let faces_left = self.config.skin.faces.eq_ignore_ascii_case("left");
+        facing_left.is_some_and(|left| left != faces_left)
+    }
+
+    fn take_move_request(&mut self) -> Option<MoveRequest> {
+        self.move_request.take()
+    }
+
+    fn take_focus_request(&mut self) -> bool {
+        self.focus_receiver.as_ref().is_some_and(|r| r.try_iter().count() > 0)
+    }
+
+    fn motion_enabled(&self) -> bool {
+        self.motion_enabled
+    }
+
+    fn tracks_cursor(&self) -> bool {
+        self.look_at.is_enabled()
+    }
+
+    fn target_fps(&self) -> f32 {
+        // Use fps from config, default to 30 if not animated
+        if self.animated_skin.is_some() {
+            self.config.skin.fps
+        } else {
+            30.0
+        }
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    fn on_exit(&mut self) {
+        if !self.config.instance.restore_session {
+            return;
+        }
+        let persona = self.animated_skin.is_some().then(|| self.config.skin.persona());
+        let sound = self.sound.as_ref();
+        session::update(|s| {
+            s.position = self.position.or(s.position);
+            s.persona = persona.or(s.persona.take());
+            s.muted = sound.map_or(s.muted, SoundPlayer::is_muted);
+            s.volume = sound.map(SoundPlayer::volume).or(s.volume);
+        });
+        session::save();
+    }
+
+    fn on_event(&mut self, event: GhostEvent) {
+        match event {
+            GhostEvent::ButtonClicked(id) => {
+                self.note_interaction();
+                self.interact(Interaction::Chatted);
+
+                // Find which button was clicked by ID
+                let clicked = self
+                    .config
+                    .buttons
+                    .iter()
+                    .find(|btn_config| ui::get_button_id(&btn_config.id) == id)
+                    .map(|btn_config| btn_config.id.clone());
+
+                if let Some(button_id) = clicked {
+                    self.run_button_action(&button_id);
+                }
+            }
+            GhostEvent::Resized(_width, _height) => {
+                // Note: Don't update skin_size on resize. The skin dimensions are fixed,
+                // and layers should always be positioned relative to the original skin size.
+                // The resize event may give different values on HiDPI displays.
+            }
+            GhostEvent::Moved(x, y) => {
+                self.position = Some((x, y));
+                // Main window moved - callout window position is updated by the event loop
+                if let Some(ref mut wander) = self.wander {
+                    wander.set_position(x, y);
+                }
+            }
+            GhostEvent::ScreenChanged { screen, window } => {
+                if let Some(ref mut wander) = self.wander {
+                    wander.set_screen(screen, window);
+                }
+            }
+            GhostEvent::MoveFinished => {
+                if let Some(ref mut wander) = self.wander {
+                    wander.finish();
+                    self.restore_idle();
+                }
+            }
+            GhostEvent::MoveInterrupted => {
+                self.note_interaction();
+            }
+            GhostEvent::MotionChanged(state) => {
+                self.on_motion_changed(state);
+            }
+            GhostEvent::Docked(edge) => {
+                log::debug!("Docked against {:?}", edge);
+            }
+            GhostEvent::FocusChanged(focused) => {
+                self.focused = focused;
+                if focused {
+                    self.unread.clear();
+                    self.note_interaction();
+                    self.trigger_animation("focused");
+                }
+            }
+            GhostEvent::Rubbed => {
+                self.on_petted();
+            }
+            GhostEvent::DoubleClicked(_, _) => {
+                self.note_interaction();
+                self.run_script(ScriptEvent::DoubleClick);
+            }
+            GhostEvent::FileDropped(path) => {
+                self.note_interaction();
+                self.run_script(ScriptEvent::Drop(&path));
+            }
+            GhostEvent::SkinReloaded => {
+                // A static skin was already reloaded by the window
+                self.skin_changed = self.animated_skin.is_some();
+            }
+            GhostEvent::ConfigReloaded => self.reload_config(),
+            GhostEvent::CursorTracked(x, y) => {
+                self.look_at.set_cursor((x, y), self.gaze_center());
+            }
+            GhostEvent::ContextMenuSelected(id) => match context_menu::command(&id) {
+                Some(cmd) => self.run_tray_command(cmd),
+                None => log::warn!("Unknown context menu item '{}'", id),
+            },
+            GhostEvent::TrayMenuSelected(id) => match self.tray.as_ref().and_then(|tray| tray.command(&id)) {
+                Some(cmd) => self.run_tray_command(cmd),
+                None => log::warn!("Unknown tray menu item '{}'", id),
+            },
+            GhostEvent::HotkeyPressed(action) => match HotkeyAction::parse(&action) {
+                Ok(action) => self.run_hotkey_action(action),
+                Err(e) => log::warn!("Hotkey: {}", e),
+            },
+            _ => {}
+        }
+    }
+
+    fn buttons(&self) -> Vec<&Button> {
+        self.buttons.iter().collect()
+    }
+
+    fn buttons_mut(&mut self) -> Vec<&mut Button> {
+        self.buttons.iter_mut().collect()
+    }
+
+    fn accessible_text(&self) -> Vec<String> {
+        // The latest callout, without markup, so screen readers announce it
+        self.last_callout.as_ref().map(|text| parse_markup(text).0).into_iter().collect()
+    }
+
+    fn context_menu(&self) -> Option<ContextMenu> {
+        let config = &self.config.context_menu;
+        config.enabled.then(|| context_menu::build(config, &self.tray_state()))
+    }
+
+    fn take_visibility_request(&mut self) -> Option<bool> {
+        self.visibility_request.take()
+    }
+
+    fn visibility_transition(&self) -> Option<(Transition, Duration)> {
+        self.config.window.transition().filter(|_| !self.config.accessibility.reduced_motion())
+    }
+
+    fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, viewport: [f32; 2], scale_factor: f32, opacity: f32) {
+        if let Some(name) = self.pending_persona.take() {
+            self.load_persona(&name);
+        } else if std::mem::take(&mut self.skin_changed) {
+            self.reload_skin(device, queue);
+        }
+        self.poll_persona_loader(device, queue);
+        if let Some(ref mut animated_skin) = self.animated_skin {
+            animated_skin.prepare_gpu(device, queue);
+        }
+
+        // Prepare layer bind groups with window opacity; persona parts
+        // take the persona's colors and effects (but no glow of their own),
+        // ui layers keep their own
+        if let Some(pipeline) = &mut self.layer_pipeline {
+            pipeline.set_recolor(self.recolor.clone().unwrap_or_default());
+            pipeline.set_effects(self.effects.params().without_glow());
+            for part in &mut self.parts {
+                part.init_gpu(device, queue);
+                part.prepare_with_opacity(pipeline, device, queue, viewport, scale_factor, opacity);
+            }
+            pipeline.set_recolor(Recolor::default());
+            pipeline.set_effects(EffectParams::default());
+            for layer in &mut self.layers {
+                layer.prepare_with_opacity(pipeline, device, queue, viewport, scale_factor, opacity);
+            }
+            self.hearts.prepare(pipeline, device, queue, viewport, scale_factor, opacity);
+        }
+
+        // Prepare layer text rendering
+        self.layer_renderer.prepare_text(device, queue, &self.layers, viewport, scale_factor);
+    }
+
+    fn render_layers<'a>(
+        &'a mut self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _viewport: [f32; 2],
+        render_pass: &mut wgpu::RenderPass<'a>,
+    ) {
+        // Render persona parts, then layer images
+        if let Some(pipeline) = &self.layer_pipeline {
+            for layer in self.parts.iter().chain(&self.layers) {
+                if let Some(bind_group) = layer.bind_group() {
+                    pipeline.render_bind_group(render_pass, bind_group);
+                }
+            }
+        }
+
+        // Render layer text
+        self.layer_renderer.render_text(render_pass);
+
+        // Hearts float above everything
+        if let Some(pipeline) = &self.layer_pipeline {
+            self.hearts.render(pipeline, render_pass);
+        }
+    }
+}
+
+/// Effect stack with the effects `[effects]` switches on from the start
+fn load_effects(config: &EffectsConfig) -> EffectStack {
+    let mut effects = EffectStack::new()
+        .with_glow(config.glow_color, config.glow_radius)
+        .with_pixel_size(config.pixel_size);
+    for name in &config.enabled {
+        match Effect::from_name(name) {
+            Some(effect) => effects.set(effect, true, 0.0),
+            None => log::warn!("Unknown effect '{}' in [effects]", name),
+        }
+    }
+    effects
+}
+
+/// Effect names for messages, e.g. "glow, dissolve"
+fn effect_names(effects: &[Effect]) -> String {
+    effects.iter().map(|e| e.name()).collect::<Vec<_>>().join(", ")
+}
+
+/// State machine from a persona's config.toml, if it declares one
+fn load_state_machine(manifest: &PersonaManifest) -> Option<AnimationStateMachine> {
+    let config = manifest.state_machine.clone()?;
+    log::info!(
+        "Animation state machine: {} transitions, {} idle variations",
+        config.transitions.len(),
+        config.idle_variations.len()
+    );
+    Some(AnimationStateMachine::new(config))
+}
+
+/// Show the session's conversation in the chat window
+fn show_chat_session(chat_sender: &ChatSender, history: &ChatHistory) {
+    let command = ChatWindowCommand::LoadSession {
+        name: history.session().to_string(),
+        messages: history.messages().to_vec(),
+    };
+    if let Err(e) = chat_sender.send(command) {
+        log::error!("Failed to send chat session: {}", e);
     }
 }
@@ -0,0 +1,252 @@
+//! Sound playback - persona sounds for animation states and callouts
+//!
+//! Personas list WAV/OGG files in the `[sounds]` section of their
+//! `config.toml`; the files ship in the persona folder (or `.persona.zip`).
+//! Playing needs the `audio` feature; the output device lives on its own
+//! thread, so decoding never stalls the ghost.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use ghost_ui::AnimationState;
+use serde::Deserialize;
+
+use crate::config::AudioConfig;
+use crate::lip_sync::AudioLevel;
+
+/// Volume steps offered in the tray menu
+pub const VOLUME_STEPS: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+
+/// The `[sounds]` section of a persona's `config.toml`, paths relative to
+/// the persona folder
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SoundsConfig {
+    /// Played when an animation state starts, by state name
+    #[serde(default)]
+    pub states: HashMap<String, String>,
+    /// Played when a callout appears: "talk", "think", "scream", "whisper"
+    /// or "alert"
+    #[serde(default)]
+    pub callouts: HashMap<String, String>,
+}
+
+/// Sound files of the active persona
+#[derive(Debug, Default)]
+pub struct PersonaSounds {
+    states: HashMap<AnimationState, PathBuf>,
+    callouts: HashMap<String, PathBuf>,
+}
+
+impl PersonaSounds {
+    /// Resolve the configured files, skipping missing ones
+    pub fn load(persona_dir: &Path, config: &SoundsConfig) -> Self {
+        let resolve = |file: &String| {
+            let path = persona_dir.join(file);
+            if path.is_file() {
+                Some(path)
+            } else {
+                log::warn!("Sound file not found: {}", path.display());
+                None
+            }
+        };
+        Self {
+            states: config
+                .states
+                .iter()
+                .filter_map(|(state, file)| Some((AnimationState::from_str(state), resolve(file)?)))
+                .collect(),
+            callouts: config
+                .callouts
+                .iter()
+                .filter_map(|(kind, file)| Some((kind.to_lowercase(), resolve(file)?)))
+                .collect(),
+        }
+    }
+
+    pub fn for_state(&self, state: AnimationState) -> Option<&Path> {
+        self.states.get(&state).map(PathBuf::as_path)
+    }
+
+    pub fn for_callout(&self, kind: &str) -> Option<&Path> {
+        self.callouts.get(kind).map(PathBuf::as_path)
+    }
+}
+
+/// A sound to play at a volume (0.0 - 1.0)
+#[cfg_attr(not(feature = "audio"), allow(dead_code))]
+struct Playback {
+    path: PathBuf,
+    volume: f32,
+}
+
+/// Sends sounds to the audio thread
+pub struct SoundPlayer {
+    sender: Sender<Playback>,
+    /// Loudness of the sounds playing, for lip-sync
+    level: AudioLevel,
+    volume: f32,
+    muted: bool,
+}
+
+impl SoundPlayer {
+    /// Open the audio output in the background; None when disabled or built
+    /// without the `audio` feature
+    pub fn start(config: &AudioConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let level = AudioLevel::default();
+        let sender = spawn_output(level.clone())?;
+        Some(Self {
+            sender,
+            level,
+            volume: config.volume.clamp(0.0, 1.0),
+            muted: config.muted,
+        })
+    }
+
+    pub fn play(&self, path: &Path) {
+        if self.muted || self.volume <= 0.0 {
+            return;
+        }
+        let playback = Playback {
+            path: path.to_path_buf(),
+            volume: self.volume,
+        };
+        if let Err(e) = self.sender.send(playback) {
+            log::error!("Failed to send sound: {}", e);
+        }
+    }
+
+    /// Loudness (0-1) of what is playing right now
+    pub fn level(&self) -> f32 {
+        self.level.get()
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+}
+
+#[cfg(feature = "audio")]
+fn spawn_output(level: AudioLevel) -> Option<Sender<Playback>> {
+    use rodio::{Decoder, OutputStream, Sink};
+    use std::io::BufReader;
+    use std::sync::mpsc::channel;
+
+    let (sender, receiver) = channel::<Playback>();
+    std::thread::spawn(move || {
+        // The output stream must stay on the thread that opened it
+        let (_stream, handle) = match OutputStream::try_default() {
+            Ok(output) => output,
+            Err(e) => {
+                log::error!("No audio output: {}", e);
+                return;
+            }
+        };
+        log::info!("Audio output opened");
+        for playback in receiver {
+            let file = match std::fs::File::open(&playback.path) {
+                Ok(file) => file,
+                Err(e) => {
+                    log::error!("{}: {}", playback.path.display(), e);
+                    continue;
+                }
+            };
+            let source = match Decoder::new(BufReader::new(file)) {
+                Ok(source) => source,
+                Err(e) => {
+                    log::error!("Can't decode {}: {}", playback.path.display(), e);
+                    continue;
+                }
+            };
+            match Sink::try_new(&handle) {
+                Ok(sink) => {
+                    sink.set_volume(playback.volume);
+                    sink.append(Envelope::new(source, level.clone()));
+                    sink.detach();
+                }
+                Err(e) => log::error!("Failed to play sound: {}", e),
+            }
+        }
+    });
+    Some(sender)
+}
+
+/// Passes a sound through, reporting its loudness (RMS over 20 ms windows)
+#[cfg(feature = "audio")]
+struct Envelope<S> {
+    source: S,
+    level: AudioLevel,
+    /// Samples per window
+    window: usize,
+    count: usize,
+    sum: f32,
+}
+
+#[cfg(feature = "audio")]
+impl<S: rodio::Source<Item = i16>> Envelope<S> {
+    fn new(source: S, level: AudioLevel) -> Self {
+        let window = (source.sample_rate() as usize * source.channels() as usize / 50).max(1);
+        Self { source, level, window, count: 0, sum: 0.0 }
+    }
+}
+
+#[cfg(feature = "audio")]
+impl<S: rodio::Source<Item = i16>> Iterator for Envelope<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let Some(sample) = self.source.next() else {
+            self.level.set(0.0);
+            return None;
+        };
+        let value = sample as f32 / i16::MAX as f32;
+        self.sum += value * value;
+        self.count += 1;
+        if self.count >= self.window {
+            self.level.set((self.sum / self.count as f32).sqrt());
+            self.sum = 0.0;
+            self.count = 0;
+        }
+        Some(sample)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl<S: rodio::Source<Item = i16>> rodio::Source for Envelope<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<std::time::Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+fn spawn_output(_level: AudioLevel) -> Option<Sender<Playback>> {
+    log::info!("No sound: ghost was built without the `audio` feature");
+    None
+}
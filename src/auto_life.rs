@@ -0,0 +1,160 @@
+//! Auto-life - random blinks and a breathing motion on top of the persona's
+//! own animations (see `[auto_life]` in the persona's config.toml)
+//!
+//! Blinks switch the body or an eyes part to its blink state for a moment;
+//! breathing slowly shrinks and grows the body, anchored at its bottom edge.
+
+use std::f32::consts::TAU;
+
+use ghost_ui::Rng;
+
+use crate::config::AutoLifeConfig;
+
+/// Seconds between the two blinks of a double blink
+const DOUBLE_BLINK_GAP: f32 = 0.12;
+
+/// Blink and breathing timers
+pub struct AutoLife {
+    config: AutoLifeConfig,
+    /// Seconds until the next blink
+    next_blink: f32,
+    /// Seconds the eyes stay closed, 0 while open
+    closed: f32,
+    /// The blink in progress is the first of a double blink
+    double: bool,
+    /// The next blink is the second of a double blink
+    second: bool,
+    /// Seconds into the current breath
+    breath: f32,
+    rng: Rng,
+}
+
+impl AutoLife {
+    pub fn new(config: AutoLifeConfig) -> Self {
+        Self::with_seed(config, Rng::new().next_u64())
+    }
+
+    fn with_seed(config: AutoLifeConfig, seed: u64) -> Self {
+        let mut auto_life = Self {
+            config,
+            next_blink: 0.0,
+            closed: 0.0,
+            double: false,
+            second: false,
+            breath: 0.0,
+            rng: Rng::with_seed(seed),
+        };
+        auto_life.next_blink = auto_life.blink_interval();
+        auto_life
+    }
+
+    /// State played while the eyes are closed
+    pub fn blink_state(&self) -> &str {
+        &self.config.blink_state
+    }
+
+    /// Path of the part that blinks (None = the body)
+    pub fn blink_layer(&self) -> Option<&str> {
+        self.config.blink_layer.as_deref()
+    }
+
+    /// Advance the timers
+    pub fn update(&mut self, delta: f32) {
+        let period = self.config.breathing_period.max(0.5);
+        self.breath = (self.breath + delta) % period;
+
+        if self.closed > 0.0 {
+            self.closed = (self.closed - delta).max(0.0);
+            if self.closed == 0.0 {
+                self.next_blink = if self.double { DOUBLE_BLINK_GAP } else { self.blink_interval() };
+                self.second = self.double;
+                self.double = false;
+            }
+            return;
+        }
+        self.next_blink -= delta;
+        if self.next_blink <= 0.0 {
+            // A blink always lasts at least one frame
+            self.closed = self.config.blink_duration.max(0.01);
+            self.double = !self.second && self.rng.range(0.0, 1.0) < self.config.double_blink;
+            self.second = false;
+        }
+    }
+
+    /// Whether the eyes are closed right now
+    pub fn eyes_closed(&self) -> bool {
+        self.closed > 0.0
+    }
+
+    /// Vertical scale of the body, between `1 - breathing` and 1
+    pub fn breathing_scale(&self) -> f32 {
+        let period = self.config.breathing_period.max(0.5);
+        let amplitude = self.config.breathing.clamp(0.0, 0.2);
+        1.0 - amplitude * (1.0 - (TAU * self.breath / period).cos()) / 2.0
+    }
+
+    /// Whether breathing moves the body continuously
+    pub fn is_breathing(&self) -> bool {
+        self.config.breathing > 0.0
+    }
+
+    fn blink_interval(&mut self) -> f32 {
+        let [min, max] = self.config.blink_interval;
+        let min = min.max(0.5);
+        self.rng.range(min, max.max(min))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AutoLifeConfig {
+        AutoLifeConfig {
+            blink_interval: [2.0, 2.0],
+            blink_duration: 0.2,
+            double_blink: 0.0,
+            breathing: 0.02,
+            breathing_period: 4.0,
+            ..AutoLifeConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_blink() {
+        let mut auto_life = AutoLife::with_seed(config(), 7);
+        auto_life.update(1.9);
+        assert!(!auto_life.eyes_closed());
+        auto_life.update(0.2);
+        assert!(auto_life.eyes_closed());
+        auto_life.update(0.2);
+        assert!(!auto_life.eyes_closed());
+        auto_life.update(1.5);
+        assert!(!auto_life.eyes_closed());
+    }
+
+    #[test]
+    fn test_double_blink() {
+        let config = AutoLifeConfig { double_blink: 1.0, ..config() };
+        let mut auto_life = AutoLife::with_seed(config, 7);
+        auto_life.update(2.0);
+        auto_life.update(0.2);
+        assert!(!auto_life.eyes_closed());
+        // The second blink follows right away and is never doubled again
+        auto_life.update(DOUBLE_BLINK_GAP);
+        assert!(auto_life.eyes_closed());
+        auto_life.update(0.2);
+        auto_life.update(DOUBLE_BLINK_GAP);
+        assert!(!auto_life.eyes_closed());
+    }
+
+    #[test]
+    fn test_breathing_scale() {
+        let mut auto_life = AutoLife::with_seed(config(), 7);
+        assert_eq!(auto_life.breathing_scale(), 1.0);
+        auto_life.update(2.0);
+        assert!((auto_life.breathing_scale() - 0.98).abs() < 1e-4);
+        auto_life.update(2.0);
+        assert!((auto_life.breathing_scale() - 1.0).abs() < 1e-4);
+    }
+}
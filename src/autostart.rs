@@ -0,0 +1,158 @@
+//! Autostart - starting the ghost when the user logs in
+//!
+//! Installs a LaunchAgent on macOS, a `Run` registry value on Windows and an
+//! XDG autostart entry elsewhere. Each starts this executable with `--dir`
+//! set to the current directory, where ui.toml and the assets are.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// LaunchAgent label and file name (macOS)
+const LAUNCH_AGENT: &str = "com.ghost.mascot";
+
+/// Registry key of the programs started at login (Windows)
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// Value name under `RUN_KEY` and desktop entry name
+const NAME: &str = "Ghost";
+
+/// Start the ghost at login from now on
+pub fn enable() -> io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let dir = std::env::current_dir()?;
+    if cfg!(target_os = "windows") {
+        let command = format!("\"{}\" --dir \"{}\"", exe.display(), dir.display());
+        reg(&["add", RUN_KEY, "/v", NAME, "/t", "REG_SZ", "/d", &command, "/f"])?;
+    } else {
+        let path = entry_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = if cfg!(target_os = "macos") {
+            launch_agent(&exe, &dir)
+        } else {
+            desktop_entry(&exe, &dir)
+        };
+        std::fs::write(&path, content)?;
+    }
+    log::info!("Autostart enabled");
+    Ok(())
+}
+
+/// Stop starting the ghost at login
+pub fn disable() -> io::Result<()> {
+    if cfg!(target_os = "windows") {
+        reg(&["delete", RUN_KEY, "/v", NAME, "/f"])?;
+    } else {
+        match std::fs::remove_file(entry_path()?) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    log::info!("Autostart disabled");
+    Ok(())
+}
+
+/// Whether the ghost starts at login
+pub fn is_enabled() -> bool {
+    if cfg!(target_os = "windows") {
+        return reg(&["query", RUN_KEY, "/v", NAME]).is_ok();
+    }
+    entry_path().is_ok_and(|path| path.exists())
+}
+
+/// File that makes the ghost start at login (macOS and XDG desktops)
+fn entry_path() -> io::Result<PathBuf> {
+    let not_found = || io::Error::new(io::ErrorKind::NotFound, "no home directory");
+    if cfg!(target_os = "macos") {
+        let home = dirs::home_dir().ok_or_else(not_found)?;
+        Ok(home.join("Library/LaunchAgents").join(format!("{}.plist", LAUNCH_AGENT)))
+    } else {
+        let config = dirs::config_dir().ok_or_else(not_found)?;
+        Ok(config.join("autostart").join("ghost.desktop"))
+    }
+}
+
+/// Run `reg` with `args`, failing when it does
+fn reg(args: &[&str]) -> io::Result<()> {
+    let output = Command::new("reg").args(args).output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(io::Error::other(message))
+    }
+}
+
+/// LaunchAgent property list starting `exe` in `dir`
+fn launch_agent(exe: &Path, dir: &Path) -> String {
+    let arguments = [exe.to_string_lossy(), "--dir".into(), dir.to_string_lossy()]
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+        .collect::<String>();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+{}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT, arguments
+    )
+}
+
+/// XDG autostart entry starting `exe` in `dir`
+fn desktop_entry(exe: &Path, dir: &Path) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec={} --dir {}\nX-GNOME-Autostart-enabled=true\n",
+        NAME,
+        exec_quote(&exe.to_string_lossy()),
+        exec_quote(&dir.to_string_lossy()),
+    )
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Quote an `Exec` argument as the desktop entry spec asks
+fn exec_quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_quotes_paths() {
+        let entry = desktop_entry(Path::new("/opt/my ghost/ghost"), Path::new("/home/me/$ghost"));
+        assert!(entry.contains("Exec=\"/opt/my ghost/ghost\" --dir \"/home/me/\\$ghost\"\n"));
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+    }
+
+    #[test]
+    fn test_launch_agent_arguments() {
+        let plist = launch_agent(Path::new("/Applications/Ghost & Co/ghost"), Path::new("/Users/me"));
+        assert!(plist.contains("<string>/Applications/Ghost &amp; Co/ghost</string>"));
+        assert!(plist.contains("<string>--dir</string>\n        <string>/Users/me</string>"));
+        assert!(plist.contains("<key>RunAtLoad</key>"));
+    }
+}
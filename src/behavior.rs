@@ -0,0 +1,210 @@
+//! Time-of-day behaviors - states, opacity, effects and greetings from `[[behavior]]`
+//!
+//! The active behavior is re-evaluated against the wall clock every few
+//! seconds; entering one can show a greeting, leaving it restores defaults.
+
+use chrono::{Datelike, Duration, Local, NaiveDateTime, NaiveTime, Weekday};
+use ghost_ui::Effect;
+
+use crate::callout_app::CalloutCommand;
+use crate::config::BehaviorConfig;
+
+/// Seconds between two evaluations
+const CHECK_INTERVAL: f32 = 5.0;
+
+/// A parsed `[[behavior]]` entry
+#[derive(Debug, Clone)]
+pub struct Behavior {
+    pub name: String,
+    from: NaiveTime,
+    to: NaiveTime,
+    /// Empty = every day
+    days: Vec<Weekday>,
+    pub state: Option<String>,
+    pub opacity: Option<f32>,
+    pub effects: Vec<Effect>,
+    pub greeting: Option<CalloutCommand>,
+}
+
+impl Behavior {
+    fn from_config(config: &BehaviorConfig, name: String) -> Result<Self, String> {
+        let parse_time = |s: &str| {
+            NaiveTime::parse_from_str(s.trim(), "%H:%M").map_err(|_| format!("invalid time '{}'", s))
+        };
+        let days = config
+            .days
+            .iter()
+            .map(|d| d.parse::<Weekday>().map_err(|_| format!("invalid day '{}'", d)))
+            .collect::<Result<_, _>>()?;
+        let effects = config
+            .effects
+            .iter()
+            .map(|e| Effect::from_name(e).ok_or_else(|| format!("unknown effect '{}'", e)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name,
+            from: parse_time(&config.from)?,
+            to: parse_time(&config.to)?,
+            days,
+            state: config.state.clone(),
+            opacity: config.opacity.map(|o| o.clamp(0.0, 1.0)),
+            effects,
+            greeting: config
+                .greeting
+                .as_ref()
+                .map(|text| CalloutCommand::from_kind(&config.callout, text.clone())),
+        })
+    }
+
+    /// Whether the behavior is active at `t`. For ranges that wrap past
+    /// midnight, the early-morning part belongs to the previous day.
+    fn matches(&self, t: &NaiveDateTime) -> bool {
+        let time = t.time();
+        let (in_range, started_yesterday) = if self.from <= self.to {
+            (time >= self.from && time < self.to, false)
+        } else if time >= self.from {
+            (true, false)
+        } else {
+            (time < self.to, true)
+        };
+
+        if !in_range || self.days.is_empty() {
+            return in_range;
+        }
+
+        let day = if started_yesterday { *t - Duration::days(1) } else { *t };
+        self.days.contains(&day.weekday())
+    }
+}
+
+/// A switch between behaviors
+pub struct BehaviorChange {
+    pub left: Option<Behavior>,
+    pub entered: Option<Behavior>,
+}
+
+/// Tracks which behavior is active
+pub struct BehaviorSchedule {
+    behaviors: Vec<Behavior>,
+    active: Option<usize>,
+    check_timer: f32,
+}
+
+impl BehaviorSchedule {
+    /// Parse behaviors from config, skipping invalid entries
+    pub fn from_config(entries: &[BehaviorConfig]) -> Self {
+        let mut behaviors = Vec::new();
+
+        for (i, entry) in entries.iter().enumerate() {
+            let name = entry.name.clone().unwrap_or_else(|| format!("behavior #{}", i + 1));
+            match Behavior::from_config(entry, name.clone()) {
+                Ok(behavior) => behaviors.push(behavior),
+                Err(e) => log::error!("Invalid {}: {}", name, e),
+            }
+        }
+
+        if !behaviors.is_empty() {
+            log::info!("Loaded {} time-of-day behaviors", behaviors.len());
+        }
+
+        Self {
+            behaviors,
+            active: None,
+            check_timer: 0.0,
+        }
+    }
+
+    /// The currently active behavior
+    pub fn current(&self) -> Option<&Behavior> {
+        self.active.map(|i| &self.behaviors[i])
+    }
+
+    /// Index of the behavior active at `t`: day-specific entries win over
+    /// generic ones, otherwise the last matching entry wins
+    fn active_at(&self, t: &NaiveDateTime) -> Option<usize> {
+        let matching: Vec<usize> = (0..self.behaviors.len())
+            .filter(|&i| self.behaviors[i].matches(t))
+            .collect();
+        matching
+            .iter()
+            .rev()
+            .find(|&&i| !self.behaviors[i].days.is_empty())
+            .or_else(|| matching.last())
+            .copied()
+    }
+
+    /// Re-evaluate periodically; returns the change when the active behavior switched
+    pub fn update(&mut self, delta: f32) -> Option<BehaviorChange> {
+        if self.behaviors.is_empty() {
+            return None;
+        }
+
+        self.check_timer -= delta;
+        if self.check_timer > 0.0 {
+            return None;
+        }
+        self.check_timer = CHECK_INTERVAL;
+
+        let active = self.active_at(&Local::now().naive_local());
+        if active == self.active {
+            return None;
+        }
+
+        let left = self.current().cloned();
+        self.active = active;
+        let entered = self.current().cloned();
+        Some(BehaviorChange { left, entered })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn entry(from: &str, to: &str, days: &[&str]) -> BehaviorConfig {
+        BehaviorConfig {
+            name: None,
+            from: from.to_string(),
+            to: to.to_string(),
+            days: days.iter().map(|d| d.to_string()).collect(),
+            state: None,
+            opacity: None,
+            effects: Vec::new(),
+            greeting: None,
+            callout: "say".to_string(),
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-05-10 is a Friday
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_wrapping_range() {
+        let schedule = BehaviorSchedule::from_config(&[entry("23:00", "07:00", &[])]);
+        assert_eq!(schedule.active_at(&at(10, 23, 30)), Some(0));
+        assert_eq!(schedule.active_at(&at(11, 6, 59)), Some(0));
+        assert_eq!(schedule.active_at(&at(11, 7, 0)), None);
+        assert_eq!(schedule.active_at(&at(10, 12, 0)), None);
+    }
+
+    #[test]
+    fn test_day_override() {
+        let schedule = BehaviorSchedule::from_config(&[
+            entry("07:00", "10:00", &[]),
+            entry("09:00", "12:00", &[]),
+            entry("23:00", "09:00", &["fri", "sat"]),
+        ]);
+        // Thursday: last generic match wins
+        assert_eq!(schedule.active_at(&at(9, 9, 30)), Some(1));
+        // Saturday morning: Friday night's override still runs
+        assert_eq!(schedule.active_at(&at(11, 8, 0)), Some(2));
+        // Sunday morning belongs to Saturday night
+        assert_eq!(schedule.active_at(&at(12, 8, 0)), Some(2));
+        // Monday morning: Sunday night is not overridden
+        assert_eq!(schedule.active_at(&at(13, 8, 0)), Some(0));
+    }
+}
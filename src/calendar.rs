@@ -0,0 +1,254 @@
+//! Calendar integration - reads ICS feeds and announces upcoming events
+//!
+//! Feeds may be `http(s)://` URLs or local file paths. Only single events are
+//! supported; recurrence rules (RRULE) are ignored and all-day events are skipped.
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::config::CalendarConfig;
+
+/// Longest announcement lead: a week
+const MAX_LEAD_MINUTES: f32 = 7.0 * 24.0 * 60.0;
+
+/// A timed calendar event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    pub start: DateTime<Local>,
+}
+
+/// Event fields collected while inside a VEVENT
+#[derive(Default)]
+struct PartialEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    start: Option<DateTime<Local>>,
+}
+
+/// Parse VEVENTs from ICS text
+pub fn parse_ics(content: &str) -> Vec<Event> {
+    let mut events = Vec::new();
+    let mut current: Option<PartialEvent> = None;
+
+    for line in unfold_lines(content) {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        // Property name without parameters, e.g. "DTSTART;TZID=Europe/Berlin"
+        let (name, params) = key.split_once(';').unwrap_or((key, ""));
+
+        match (name, value) {
+            ("BEGIN", "VEVENT") => current = Some(PartialEvent::default()),
+            ("END", "VEVENT") => {
+                if let Some(PartialEvent { uid, summary: Some(summary), start: Some(start) }) = current.take() {
+                    let uid = uid.unwrap_or_else(|| format!("{}@{}", summary, start));
+                    events.push(Event { uid, summary, start });
+                }
+            }
+            _ => {
+                let Some(ref mut event) = current else { continue };
+                match name {
+                    "UID" => event.uid = Some(value.to_string()),
+                    "SUMMARY" => event.summary = Some(unescape(value)),
+                    "DTSTART" => event.start = parse_datetime(value, params),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    events
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// Parse a DTSTART value. UTC ("...Z") is converted; TZID and floating times
+/// are treated as local time. All-day dates return None.
+fn parse_datetime(value: &str, params: &str) -> Option<DateTime<Local>> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+
+    // A bare date without VALUE=DATE is still all-day
+    if NaiveDate::parse_from_str(value, "%Y%m%d").is_ok() {
+        return None;
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Load a feed from a URL or local path
+fn fetch_feed(feed: &str) -> Result<String, String> {
+    if feed.starts_with("http://") || feed.starts_with("https://") {
+        ureq::get(feed)
+            .call()
+            .map_err(|e| e.to_string())?
+            .into_string()
+            .map_err(|e| e.to_string())
+    } else {
+        std::fs::read_to_string(feed).map_err(|e| e.to_string())
+    }
+}
+
+/// Periodically reloads feeds and reports events entering the lead window
+pub struct Calendar {
+    config: CalendarConfig,
+    sender: Sender<Vec<Event>>,
+    receiver: Receiver<Vec<Event>>,
+    /// Seconds until the next feed refresh
+    refresh_timer: f32,
+    /// Seconds until upcoming events are checked again
+    check_timer: f32,
+    events: Vec<Event>,
+    /// Events already announced (uid + start)
+    announced: HashSet<(String, DateTime<Local>)>,
+}
+
+impl Calendar {
+    pub fn new(config: CalendarConfig) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            config,
+            sender,
+            receiver,
+            refresh_timer: 0.0,
+            check_timer: 0.0,
+            events: Vec::new(),
+            announced: HashSet::new(),
+        }
+    }
+
+    /// Lead time before an event in minutes
+    pub fn lead_minutes(&self) -> f32 {
+        self.config.lead_minutes
+    }
+
+    /// Reload all feeds on a background thread
+    fn refresh(&mut self) {
+        self.refresh_timer = self.config.refresh_minutes.max(1.0) * 60.0;
+
+        let feeds = self.config.feeds.clone();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let mut events = Vec::new();
+            for feed in &feeds {
+                match fetch_feed(feed) {
+                    Ok(content) => events.extend(parse_ics(&content)),
+                    Err(e) => log::warn!("Failed to load calendar feed '{}': {}", feed, e),
+                }
+            }
+            let _ = sender.send(events);
+        });
+    }
+
+    /// Advance timers; returns events that should be announced now
+    pub fn update(&mut self, delta: f32) -> Vec<Event> {
+        self.refresh_timer -= delta;
+        if self.refresh_timer <= 0.0 {
+            self.refresh();
+        }
+
+        if let Ok(events) = self.receiver.try_recv() {
+            log::info!("Calendar loaded {} events", events.len());
+            self.events = events;
+        }
+
+        self.check_timer -= delta;
+        if self.check_timer > 0.0 {
+            return Vec::new();
+        }
+        self.check_timer = 15.0;
+
+        let now = Local::now();
+        let lead = lead_time(self.config.lead_minutes);
+        let mut due = Vec::new();
+
+        for event in &self.events {
+            let announce = event.start.checked_sub_signed(lead);
+            if event.start > now && announce.is_some_and(|announce| announce <= now) {
+                let key = (event.uid.clone(), event.start);
+                if self.announced.insert(key) {
+                    due.push(event.clone());
+                }
+            }
+        }
+
+        // Forget announcements for events that are long over
+        self.announced.retain(|(_, start)| *start > now - Duration::days(1));
+
+        due
+    }
+}
+
+/// How long before an event it is announced, at most a week
+fn lead_time(minutes: f32) -> Duration {
+    Duration::try_seconds((minutes.clamp(0.0, MAX_LEAD_MINUTES) * 60.0) as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lead_time() {
+        assert_eq!(lead_time(10.0), Duration::minutes(10));
+        assert_eq!(lead_time(-5.0), Duration::zero());
+        assert_eq!(lead_time(1e30), Duration::weeks(1));
+        assert_eq!(lead_time(f32::NAN), Duration::zero());
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:abc-1\r\n\
+            SUMMARY:Team sync\\, weekly\r\n\
+            DTSTART:20240510T090000Z\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:abc-2\r\n\
+            SUMMARY:Holiday\r\n\
+            DTSTART;VALUE=DATE:20240511\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            SUMMARY:Long meeting\r\n \
+            name\r\n\
+            DTSTART;TZID=Europe/Berlin:20240512T140000\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let events = parse_ics(ics);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].uid, "abc-1");
+        assert_eq!(events[0].summary, "Team sync, weekly");
+        assert_eq!(events[0].start, Utc.with_ymd_and_hms(2024, 5, 10, 9, 0, 0).unwrap());
+        assert_eq!(events[1].summary, "Long meetingname");
+    }
+}
@@ -1,12 +1,14 @@
 //! Callout window application - renders the callout bubble in a separate window
 
-use ghost_callout::{Callout, CalloutStyle, TextAnimation};
-use ghost_ui::CalloutApp;
+use ghost_callout::{parse_markup, ArrowTarget, Callout, CalloutStyle, CalloutType, TextAnimation, TextStream};
+use ghost_ui::{CalloutApp, CalloutFlip, CalloutLayout};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::Duration;
 use wgpu::{Device, Queue, RenderPass, TextureFormat};
 
-use crate::config::{Anchor, Config};
+use crate::config::{Anchor, Config, NineSliceConfig};
+use crate::debug_window::{DebugSender, DebugWindowCommand};
+use crate::speech::Speaker;
 
 /// Commands that can be sent to the callout window
 #[derive(Debug, Clone)]
@@ -14,7 +16,76 @@ pub enum CalloutCommand {
     Say(String),
     Think(String),
     Scream(String),
+    /// Quiet aside in a faint, dashed bubble
+    Whisper(String),
+    /// High-priority callout (e.g. due reminders) - not replaced by normal callouts
+    Alert(String),
+    /// Open an empty bubble filled by `Append`, e.g. a chat reply as it arrives
+    Stream,
+    Append(String),
+    /// The streamed text is complete
+    EndStream,
+    /// Show a progress bar (0-1) under the current callout, or remove it
+    Progress(Option<f32>),
     Hide,
+    /// Rebuild the bubble from a reloaded config (style, font, animation)
+    Restyle(Box<Config>),
+}
+
+impl CalloutCommand {
+    /// Build a callout from a config type name: "say", "think", "scream"
+    /// or "whisper"
+    pub fn from_kind(kind: &str, text: String) -> Self {
+        match kind {
+            "think" => Self::Think(text),
+            "scream" => Self::Scream(text),
+            "whisper" => Self::Whisper(text),
+            _ => Self::Say(text),
+        }
+    }
+
+    /// Sound name of the callout: "talk", "think", "scream", "whisper" or
+    /// "alert"
+    pub fn kind(&self) -> Option<&'static str> {
+        match self {
+            Self::Say(_) | Self::Stream => Some("talk"),
+            Self::Think(_) => Some("think"),
+            Self::Scream(_) => Some("scream"),
+            Self::Whisper(_) => Some("whisper"),
+            Self::Alert(_) => Some("alert"),
+            Self::Append(_) | Self::EndStream | Self::Progress(_) | Self::Hide | Self::Restyle(_) => None,
+        }
+    }
+
+    /// "kind: text" for the debug console's callout queue
+    fn describe(&self) -> Option<String> {
+        let kind = self.kind()?;
+        Some(format!("{}: {}", kind, self.text().unwrap_or("(streaming)")))
+    }
+
+    /// The callout text (None for `Hide`)
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Say(text) | Self::Think(text) | Self::Scream(text) | Self::Whisper(text) | Self::Alert(text) => {
+                Some(text)
+            }
+            Self::Stream | Self::Append(_) | Self::EndStream | Self::Progress(_) | Self::Hide | Self::Restyle(_) => {
+                None
+            }
+        }
+    }
+
+    /// Apply `f` to the callout text (if any)
+    pub fn map_text(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            Self::Say(text) => Self::Say(f(text)),
+            Self::Think(text) => Self::Think(f(text)),
+            Self::Scream(text) => Self::Scream(f(text)),
+            Self::Whisper(text) => Self::Whisper(f(text)),
+            Self::Alert(text) => Self::Alert(f(text)),
+            cmd => cmd,
+        }
+    }
 }
 
 /// Sender for callout commands - used by main app
@@ -25,33 +96,231 @@ pub struct CalloutWindowApp {
     callout: Callout,
     receiver: Receiver<CalloutCommand>,
     initialized: bool,
+    /// Format of the window's surface, to set up a rebuilt bubble
+    format: Option<TextureFormat>,
+    /// An alert is currently showing
+    alert_active: bool,
+    /// Latest normal callout waiting for the alert to finish
+    pending: Option<CalloutCommand>,
+    /// Speaks "say" callouts when speech is enabled
+    speaker: Option<Speaker>,
+    /// Feeds the bubble opened by `CalloutCommand::Stream`
+    stream: Option<TextStream>,
+    /// Clicks on the bubble skip or dismiss it
+    interactive: bool,
+    /// Point on the skin the tail aims at
+    arrow_target: Option<[f32; 2]>,
+    /// Configured offset of the window from the main window
+    window_offset: [i32; 2],
+    /// Window fitted around the current bubble
+    layout: Option<CalloutLayout>,
+    /// Where the tail's tip stays, relative to the window offset
+    anchor: Option<[f32; 2]>,
+    /// Flip the window to the mascot's other side near screen edges
+    keep_on_screen: bool,
+    /// Where the window actually is relative to the main window
+    window_position: [f32; 2],
+    /// Tail of the bubble before flipping
+    arrow: ArrowTarget,
+    flip: CalloutFlip,
+    /// Last callout put in the bubble, as "kind: text"
+    showing: Option<String>,
+    /// Debug console, told whenever the callout queue changes
+    debug_sender: Option<DebugSender>,
+    /// Queue last sent to the debug console
+    reported_queue: Vec<String>,
 }
 
 impl CalloutWindowApp {
     pub fn new(config: &Config, receiver: Receiver<CalloutCommand>) -> Self {
         let callout = create_callout_from_config(config);
+        let arrow = callout.arrow();
         Self {
             callout,
             receiver,
             initialized: false,
+            format: None,
+            alert_active: false,
+            pending: None,
+            speaker: Speaker::start(&config.speech),
+            stream: None,
+            interactive: config.callout.interactive,
+            arrow_target: config.callout.arrow_target,
+            window_offset: [0, 0],
+            layout: None,
+            anchor: None,
+            keep_on_screen: config.callout.keep_on_screen,
+            window_position: [0.0, 0.0],
+            arrow,
+            flip: CalloutFlip::default(),
+            showing: None,
+            debug_sender: None,
+            reported_queue: Vec::new(),
+        }
+    }
+
+    /// Report the callout queue to the debug console
+    pub fn set_debug_sender(&mut self, sender: DebugSender) {
+        self.debug_sender = Some(sender);
+    }
+
+    /// The callout showing, then the one held back behind an alert
+    fn queue(&self) -> Vec<String> {
+        let showing = self.showing.clone().filter(|_| self.callout.is_visible());
+        showing.into_iter().chain(self.pending.as_ref().and_then(CalloutCommand::describe)).collect()
+    }
+
+    /// Send the queue to the debug console if it changed
+    fn report_queue(&mut self) {
+        let Some(ref sender) = self.debug_sender else { return };
+        let queue = self.queue();
+        if queue != self.reported_queue {
+            if let Err(e) = sender.send(DebugWindowCommand::CalloutQueue(queue.clone())) {
+                log::error!("Failed to send debug window command: {}", e);
+            }
+            self.reported_queue = queue;
+        }
+    }
+
+    /// The callout window sits at `offset` from the main window; re-aims the
+    /// tail at the skin's anchor point from there
+    pub fn set_window_offset(&mut self, offset: [i32; 2]) {
+        self.window_offset = offset;
+        self.window_position = offset.map(|v| v as f32);
+        self.aim_arrow();
+    }
+
+    /// Aim the tail at the skin's anchor point from where the window is
+    fn aim_arrow(&mut self) {
+        if let Some([x, y]) = self.arrow_target {
+            let [left, top] = self.window_position;
+            self.callout.set_arrow(ArrowTarget::Point([x - left, y - top]));
+        }
+    }
+
+    /// Fit the window around the bubble, moving it so the tail's tip stays
+    /// where the first bubble put it
+    fn update_layout(&mut self) {
+        // Draw the bubble from the window's top-left corner
+        let Some([x, y, ..]) = self.callout.extent() else { return };
+        if x != 0.0 || y != 0.0 {
+            let [left, top] = self.callout.position();
+            self.callout.set_position(left - x, top - y);
+        }
+        let Some([_, _, width, height]) = self.callout.extent() else { return };
+        let size = [width.ceil(), height.ceil()];
+        if self.layout.is_some_and(|layout| layout.size == size) {
+            return;
         }
+
+        let origin = self.layout.map_or([0.0, 0.0], |layout| layout.origin);
+        // The tip as it would be without flipping
+        let tip = self.callout.arrow_tip().map(|[x, y]| {
+            [
+                if self.flip.horizontal { size[0] - x } else { x },
+                if self.flip.vertical { size[1] - y } else { y },
+            ]
+        });
+        let origin = match tip {
+            Some(tip) => {
+                let [anchor_x, anchor_y] = *self.anchor.get_or_insert([origin[0] + tip[0], origin[1] + tip[1]]);
+                [anchor_x - tip[0], anchor_y - tip[1]]
+            }
+            None => origin,
+        };
+        self.layout = Some(CalloutLayout { size, origin });
+        self.window_position = [
+            self.window_offset[0] as f32 + origin[0],
+            self.window_offset[1] as f32 + origin[1],
+        ];
+        self.aim_arrow();
     }
 
-    fn process_commands(&mut self) {
-        // Process all pending commands
+    /// Replace the bubble with one built from `config`; whatever it was
+    /// showing is dropped, and the window refits around the next one
+    fn restyle(&mut self, config: &Config) {
+        self.callout = create_callout_from_config(config);
+        self.arrow = self.callout.arrow();
+        self.initialized = false;
+        self.alert_active = false;
+        self.pending = None;
+        self.stream = None;
+        self.interactive = config.callout.interactive;
+        self.arrow_target = config.callout.arrow_target;
+        self.keep_on_screen = config.callout.keep_on_screen;
+        self.layout = None;
+        self.anchor = None;
+        self.flip = CalloutFlip::default();
+        self.aim_arrow();
+    }
+
+    /// Process all pending commands, returns true if there were any
+    fn process_commands(&mut self) -> bool {
+        let mut had_commands = false;
         while let Ok(cmd) = self.receiver.try_recv() {
-            match cmd {
-                CalloutCommand::Say(text) => self.callout.say(text),
-                CalloutCommand::Think(text) => self.callout.think(text),
-                CalloutCommand::Scream(text) => self.callout.scream(text),
-                CalloutCommand::Hide => self.callout.hide(),
+            had_commands = true;
+            self.apply(cmd);
+        }
+        had_commands
+    }
+
+    fn apply(&mut self, cmd: CalloutCommand) {
+        // Everything but an alert waits (or is dropped) while one shows
+        if !self.alert_active || matches!(cmd, CalloutCommand::Alert(_)) {
+            if let Some(label) = cmd.describe() {
+                self.showing = Some(label);
             }
         }
+        match cmd {
+            CalloutCommand::Alert(text) => {
+                self.alert_active = true;
+                self.callout.scream(text);
+            }
+            CalloutCommand::Hide => {
+                self.alert_active = false;
+                self.pending = None;
+                if let Some(ref speaker) = self.speaker {
+                    speaker.stop();
+                }
+                self.stream = None;
+                self.callout.hide();
+            }
+            CalloutCommand::Append(text) => {
+                if let Some(ref stream) = self.stream {
+                    let _ = stream.send(text);
+                }
+            }
+            CalloutCommand::EndStream => self.stream = None,
+            CalloutCommand::Restyle(config) => self.restyle(&config),
+            // Progress belongs to the callout the alert replaced
+            CalloutCommand::Progress(_) if self.alert_active => {}
+            CalloutCommand::Progress(progress) => self.callout.set_progress(progress),
+            // A stream can't wait for the alert without losing its text
+            CalloutCommand::Stream if self.alert_active => {}
+            cmd if self.alert_active => {
+                // Hold normal callouts back until the alert is done
+                self.pending = Some(cmd);
+            }
+            CalloutCommand::Say(text) => match self.speaker {
+                Some(ref speaker) => {
+                    // Type along with the voice
+                    speaker.speak(&parse_markup(&text).0);
+                    let duration = speaker.estimate(&text);
+                    self.callout.say_paced(text, duration);
+                }
+                None => self.callout.say(text),
+            },
+            CalloutCommand::Think(text) => self.callout.think(text),
+            CalloutCommand::Scream(text) => self.callout.scream(text),
+            CalloutCommand::Whisper(text) => self.callout.whisper(text),
+            CalloutCommand::Stream => self.stream = Some(self.callout.stream(CalloutType::Talk)),
+        }
     }
 }
 
 impl CalloutApp for CalloutWindowApp {
     fn init_gpu(&mut self, device: &Device, queue: &Queue, format: TextureFormat) {
+        self.format = Some(format);
         if !self.initialized {
             self.callout.init(device, queue, format);
             self.initialized = true;
@@ -60,8 +329,14 @@ impl CalloutApp for CalloutWindowApp {
     }
 
     fn prepare(&mut self, device: &Device, queue: &Queue, viewport: [f32; 2], scale_factor: f32, _opacity: f32) {
+        // A restyled bubble needs its renderers
+        if let (false, Some(format)) = (self.initialized, self.format) {
+            self.callout.init(device, queue, format);
+            self.initialized = true;
+        }
         if self.callout.is_visible() {
             self.callout.prepare(device, queue, viewport, scale_factor);
+            self.update_layout();
         }
     }
 
@@ -73,10 +348,15 @@ impl CalloutApp for CalloutWindowApp {
 
     fn update(&mut self, delta: f32) -> bool {
         // Process any pending commands
-        let had_commands = self.receiver.try_recv().is_ok();
-        if had_commands {
-            // Re-process including the one we just peeked
-            self.process_commands();
+        let mut had_commands = self.process_commands();
+
+        // Once an alert has faded out, show the next held-back callout
+        if self.alert_active && !self.callout.is_visible() {
+            self.alert_active = false;
+            if let Some(cmd) = self.pending.take() {
+                self.apply(cmd);
+                had_commands = true;
+            }
         }
 
         // Update callout animation - returns true if animation is active
@@ -84,21 +364,66 @@ impl CalloutApp for CalloutWindowApp {
         self.callout.update(delta);
         let is_visible = self.callout.is_visible();
 
+        self.report_queue();
+
         // Need redraw if: had commands, visibility changed, or animation is running
         had_commands || (was_visible != is_visible) || (is_visible && self.callout.is_animating())
     }
+
+    fn interactive(&self) -> bool {
+        self.interactive
+    }
+
+    fn hit_test(&self, x: f32, y: f32) -> bool {
+        self.callout.contains(x, y)
+    }
+
+    fn clicked(&mut self) -> bool {
+        // First click shows all text, the next one dismisses the bubble
+        if !self.callout.is_text_complete() {
+            self.callout.skip_animation();
+        } else {
+            self.apply(CalloutCommand::Hide);
+        }
+        true
+    }
+
+    fn layout(&self) -> Option<CalloutLayout> {
+        self.layout
+    }
+
+    fn keep_on_screen(&self) -> bool {
+        self.keep_on_screen
+    }
+
+    fn placed(&mut self, offset: [f32; 2], flip: CalloutFlip) {
+        self.window_position = offset;
+        if flip != self.flip {
+            self.flip = flip;
+            self.callout.set_arrow(self.arrow.mirrored(flip.horizontal, flip.vertical));
+        }
+        self.aim_arrow();
+    }
 }
 
 fn create_callout_from_config(config: &Config) -> Callout {
     // Create style with configured font size
-    let style = CalloutStyle {
+    let mut style = CalloutStyle {
         background: config.callout.style.background,
         text_color: config.callout.style.text_color,
         font_size: config.callout.font_size,
         padding: config.callout.style.padding,
         border_radius: config.callout.style.border_radius,
-        ..Default::default()
+        border_color: config.callout.style.border_color,
+        border_width: config.callout.style.border_width,
+        shadow_color: config.callout.style.shadow_color,
+        shadow_offset: config.callout.style.shadow_offset,
+        shadow_blur: config.callout.style.shadow_blur,
     };
+    let reduced_motion = config.accessibility.reduced_motion();
+    if reduced_motion {
+        style = style.with_min_contrast(config.accessibility.min_contrast);
+    }
 
     // Parse animation
     let animation = match config.callout.animation.as_str() {
@@ -120,11 +445,16 @@ fn create_callout_from_config(config: &Config) -> Callout {
         .with_position(0.0, 0.0)
         .with_max_width(config.callout.max_width)
         .with_text_animation(animation)
+        .with_reduced_motion(reduced_motion)
+        .with_auto_size(config.callout.auto_size)
         .with_style(style);
 
     if config.callout.duration > 0.0 {
         callout = callout.with_duration(Duration::from_secs_f32(config.callout.duration));
     }
+    if let Some(skin) = config.callout.style.skin.as_ref().and_then(NineSliceConfig::load) {
+        callout = callout.with_skin(skin);
+    }
 
     callout
 }
@@ -0,0 +1,290 @@
+//! Chat backends - AI replies for the chat window
+//!
+//! A `ChatBackend` sends the conversation to a language model and streams the
+//! reply back piece by piece. Built in are OpenAI-compatible HTTP APIs (OpenAI,
+//! LM Studio, llama.cpp server, ...) and Ollama. Requests run on a background
+//! thread; `ChatClient::poll` picks up the reply as it arrives.
+
+use std::io::{BufRead, BufReader};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::chat_window::ChatMessage;
+use crate::config::LlmConfig;
+
+/// A language model that answers a conversation
+pub trait ChatBackend: Send + Sync {
+    /// Send the conversation and call `on_token` with each piece of the reply
+    /// as it arrives; blocks until the reply is complete
+    fn stream(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str)) -> Result<(), String>;
+}
+
+/// Progress of a reply
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatEvent {
+    /// The next piece of the reply
+    Token(String),
+    /// The reply is complete
+    Done,
+    Error(String),
+}
+
+/// Runs a backend in the background, one request at a time
+pub struct ChatClient {
+    backend: Arc<dyn ChatBackend>,
+    system_prompt: String,
+    sender: Sender<ChatEvent>,
+    receiver: Receiver<ChatEvent>,
+    busy: bool,
+}
+
+impl ChatClient {
+    pub fn new(backend: Arc<dyn ChatBackend>, system_prompt: String) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            backend,
+            system_prompt,
+            sender,
+            receiver,
+            busy: false,
+        }
+    }
+
+    /// Client for the backend in `[llm]`, None when no provider is set
+    pub fn from_config(config: &LlmConfig) -> Option<Self> {
+        let backend: Arc<dyn ChatBackend> = match config.provider.to_lowercase().as_str() {
+            "" | "none" => return None,
+            "openai" => Arc::new(OpenAiBackend::new(config)),
+            "ollama" => Arc::new(OllamaBackend::new(config)),
+            other => {
+                log::warn!("Unknown chat provider '{}' (expected \"openai\" or \"ollama\")", other);
+                return None;
+            }
+        };
+        log::info!("Chat backend: {} ({})", config.provider, config.model);
+        Some(Self::new(backend, config.system_prompt.clone()))
+    }
+
+    /// Whether a reply is still coming
+    pub fn is_busy(&self) -> bool {
+        self.busy
+    }
+
+    /// Ask for a reply to the conversation (ignored while busy)
+    pub fn send(&mut self, history: &[ChatMessage]) {
+        if self.busy {
+            return;
+        }
+        self.busy = true;
+
+        let mut messages = Vec::with_capacity(history.len() + 1);
+        if !self.system_prompt.is_empty() {
+            messages.push(ChatMessage {
+                role: "system".to_string(),
+                content: self.system_prompt.clone(),
+            });
+        }
+        messages.extend_from_slice(history);
+
+        let backend = self.backend.clone();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let result = backend.stream(&messages, &mut |token| {
+                let _ = sender.send(ChatEvent::Token(token.to_string()));
+            });
+            let _ = sender.send(match result {
+                Ok(()) => ChatEvent::Done,
+                Err(e) => ChatEvent::Error(e),
+            });
+        });
+    }
+
+    /// Events received since the last call
+    pub fn poll(&mut self) -> Vec<ChatEvent> {
+        let events: Vec<ChatEvent> = self.receiver.try_iter().collect();
+        if events.iter().any(|e| !matches!(e, ChatEvent::Token(_))) {
+            self.busy = false;
+        }
+        events
+    }
+}
+
+/// OpenAI-compatible `/chat/completions` API
+pub struct OpenAiBackend {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiBackend {
+    pub fn new(config: &LlmConfig) -> Self {
+        let url = config.url.as_deref().unwrap_or("https://api.openai.com/v1");
+        Self {
+            url: format!("{}/chat/completions", url.trim_end_matches('/')),
+            model: config.model.clone(),
+            api_key: config.api_key(),
+        }
+    }
+}
+
+impl ChatBackend for OpenAiBackend {
+    fn stream(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str)) -> Result<(), String> {
+        let mut request = ureq::post(&self.url);
+        if let Some(ref key) = self.api_key {
+            request = request.set("Authorization", &format!("Bearer {}", key));
+        }
+        let response = request
+            .send_json(json!({
+                "model": self.model,
+                "messages": messages,
+                "stream": true,
+            }))
+            .map_err(describe_error)?;
+
+        // Server-sent events, one `data: {...}` line per chunk
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else { continue };
+            if data == "[DONE]" {
+                break;
+            }
+            if let Some(token) = openai_token(data) {
+                on_token(&token);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Ollama `/api/chat` API
+pub struct OllamaBackend {
+    url: String,
+    model: String,
+}
+
+impl OllamaBackend {
+    pub fn new(config: &LlmConfig) -> Self {
+        let url = config.url.as_deref().unwrap_or("http://localhost:11434");
+        Self {
+            url: format!("{}/api/chat", url.trim_end_matches('/')),
+            model: config.model.clone(),
+        }
+    }
+}
+
+impl ChatBackend for OllamaBackend {
+    fn stream(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str)) -> Result<(), String> {
+        let response = ureq::post(&self.url)
+            .send_json(json!({
+                "model": self.model,
+                "messages": messages,
+                "stream": true,
+            }))
+            .map_err(describe_error)?;
+
+        // One JSON object per line
+        for line in BufReader::new(response.into_reader()).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let chunk: OllamaChunk = serde_json::from_str(&line).map_err(|e| e.to_string())?;
+            if let Some(error) = chunk.error {
+                return Err(error);
+            }
+            if let Some(message) = chunk.message.filter(|m| !m.content.is_empty()) {
+                on_token(&message.content);
+            }
+            if chunk.done {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenAiChunk {
+    #[serde(default)]
+    choices: Vec<OpenAiChoice>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoice {
+    #[serde(default)]
+    delta: OpenAiDelta,
+}
+
+#[derive(Deserialize, Default)]
+struct OpenAiDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaChunk {
+    #[serde(default)]
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    content: String,
+}
+
+/// Reply text in an OpenAI stream chunk
+fn openai_token(data: &str) -> Option<String> {
+    let chunk: OpenAiChunk = serde_json::from_str(data).ok()?;
+    chunk
+        .choices
+        .into_iter()
+        .next()?
+        .delta
+        .content
+        .filter(|c| !c.is_empty())
+}
+
+/// Include the server's explanation for HTTP errors
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            format!("HTTP {}: {}", code, body.trim())
+        }
+        e => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_token() {
+        let data = r#"{"choices":[{"index":0,"delta":{"content":"Hel"}}]}"#;
+        assert_eq!(openai_token(data), Some("Hel".to_string()));
+        // Role-only and final chunks carry no text
+        assert_eq!(openai_token(r#"{"choices":[{"delta":{"role":"assistant"}}]}"#), None);
+        assert_eq!(openai_token(r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#), None);
+        assert_eq!(openai_token("not json"), None);
+    }
+
+    #[test]
+    fn test_ollama_chunk() {
+        let chunk: OllamaChunk =
+            serde_json::from_str(r#"{"model":"llama3","message":{"role":"assistant","content":"Hi"},"done":false}"#)
+                .unwrap();
+        assert_eq!(chunk.message.unwrap().content, "Hi");
+        assert!(!chunk.done);
+        let last: OllamaChunk = serde_json::from_str(r#"{"model":"llama3","done":true}"#).unwrap();
+        assert!(last.done && last.message.is_none());
+    }
+}
@@ -0,0 +1,236 @@
+//! Chat history - conversations saved per persona in named sessions
+//!
+//! Each session is a JSON file at `chat/<persona>/<session>.json` in the data
+//! directory; `chat/<persona>/current.json` remembers the session to restore
+//! on startup. Exports are written as Markdown to `exports/` in the data
+//! directory.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chat_window::ChatMessage;
+use crate::storage;
+
+/// Session used when none was picked
+pub const DEFAULT_SESSION: &str = "default";
+
+/// Directory in the data directory that exports are written to
+const EXPORT_DIR: &str = "exports";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CurrentSession {
+    session: String,
+}
+
+/// The conversation of the active persona's current session
+pub struct ChatHistory {
+    persona: String,
+    session: String,
+    messages: Vec<ChatMessage>,
+    /// Most messages kept on disk (0 = don't save)
+    limit: usize,
+}
+
+impl ChatHistory {
+    /// Restore the session last used with the persona
+    pub fn load(persona: &str, limit: usize) -> Self {
+        let session = storage::load_json::<CurrentSession>(&current_file(persona))
+            .map(|current| current.session)
+            .filter(|session| !session.is_empty())
+            .unwrap_or_else(|| DEFAULT_SESSION.to_string());
+        Self::open(persona, &session, limit)
+    }
+
+    /// Open a session of the persona (empty if new)
+    pub fn open(persona: &str, session: &str, limit: usize) -> Self {
+        let session = session_name(session);
+        let messages = if limit > 0 {
+            storage::load_json(&session_file(persona, &session)).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        Self {
+            persona: persona.to_string(),
+            session,
+            messages,
+            limit,
+        }
+    }
+
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// The last `count` messages
+    pub fn recent(&self, count: usize) -> &[ChatMessage] {
+        &self.messages[self.messages.len().saturating_sub(count)..]
+    }
+
+    /// Add a message and save the session
+    pub fn push(&mut self, role: &str, content: String) {
+        self.messages.push(ChatMessage {
+            role: role.to_string(),
+            content,
+        });
+        let excess = self.messages.len().saturating_sub(self.limit.max(1));
+        self.messages.drain(..excess);
+        self.save();
+    }
+
+    /// Forget all messages of the session
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.save();
+    }
+
+    /// Switch to another session of the same persona
+    pub fn switch(&mut self, session: &str) {
+        *self = Self::open(&self.persona, session, self.limit);
+        self.save();
+    }
+
+    /// Saved sessions of the persona, sorted by name
+    pub fn sessions(&self) -> Vec<String> {
+        let dir = storage::data_path(&persona_dir(&self.persona));
+        let mut sessions: Vec<String> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+            .filter(|name| name != "current")
+            .collect();
+        if !sessions.contains(&self.session) {
+            sessions.push(self.session.clone());
+        }
+        sessions.sort();
+        sessions
+    }
+
+    /// Write the session as Markdown into the exports directory, under
+    /// `name` (a file name, never a path) or one made from the persona and
+    /// session; returns the file written
+    pub fn export(&self, name: Option<&str>) -> std::io::Result<PathBuf> {
+        let dir = storage::data_path(EXPORT_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(export_file_name(&self.persona, &self.session, name));
+        std::fs::write(&path, to_markdown(&self.persona, &self.messages))?;
+        Ok(path)
+    }
+
+    fn save(&self) {
+        if self.limit == 0 {
+            return;
+        }
+        storage::save_json(&session_file(&self.persona, &self.session), &self.messages);
+        let current = CurrentSession {
+            session: self.session.clone(),
+        };
+        storage::save_json(&current_file(&self.persona), &current);
+    }
+}
+
+fn persona_dir(persona: &str) -> String {
+    format!("chat/{}", session_name(persona))
+}
+
+fn session_file(persona: &str, session: &str) -> String {
+    format!("{}/{}.json", persona_dir(persona), session)
+}
+
+fn current_file(persona: &str) -> String {
+    format!("{}/current.json", persona_dir(persona))
+}
+
+/// Session name usable as a file name
+fn session_name(name: &str) -> String {
+    match safe_name(name).as_str() {
+        "" | "current" => DEFAULT_SESSION.to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// Markdown file name for an export; anything that could leave the
+/// exports directory is replaced
+fn export_file_name(persona: &str, session: &str, name: Option<&str>) -> String {
+    let name = name.map(|name| {
+        let name = name.trim();
+        // The last three bytes may split a character
+        let stem = name
+            .len()
+            .checked_sub(3)
+            .filter(|&end| name.get(end..).is_some_and(|ext| ext.eq_ignore_ascii_case(".md")));
+        safe_name(stem.map_or(name, |end| &name[..end]))
+    });
+    match name.filter(|name| !name.is_empty()) {
+        Some(name) => format!("{}.md", name),
+        None => format!("ghost-chat-{}-{}.md", safe_name(persona), session),
+    }
+}
+
+/// `name` with everything but letters, digits, '-' and '_' replaced by '-'
+fn safe_name(name: &str) -> String {
+    let name: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    name.trim_matches('-').to_string()
+}
+
+/// The conversation as a Markdown transcript
+fn to_markdown(persona: &str, messages: &[ChatMessage]) -> String {
+    let mut out = format!("# Chat with {}\n", persona);
+    for message in messages {
+        let speaker = if message.role == "user" { "You" } else { persona };
+        out.push_str(&format!("\n**{}:** {}\n", speaker, message.content));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_name() {
+        assert_eq!(session_name("work"), "work");
+        assert_eq!(session_name(" My Notes "), "My-Notes");
+        assert_eq!(session_name("../etc"), "etc");
+        assert_eq!(session_name("current"), DEFAULT_SESSION);
+        assert_eq!(session_name(""), DEFAULT_SESSION);
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(export_file_name("casper", "work", None), "ghost-chat-casper-work.md");
+        assert_eq!(export_file_name("casper", "work", Some("notes.md")), "notes.md");
+        assert_eq!(export_file_name("casper", "work", Some("../../.bashrc")), "bashrc.md");
+        assert_eq!(export_file_name("casper", "work", Some("/etc/passwd")), "etc-passwd.md");
+        assert_eq!(export_file_name("casper", "work", Some("C:\\x.MD")), "C--x.md");
+        assert_eq!(export_file_name("casper", "work", Some("..")), "ghost-chat-casper-work.md");
+        assert_eq!(export_file_name("casper", "work", Some("éé")), "éé.md");
+        assert_eq!(export_file_name("casper", "work", Some("café.md")), "café.md");
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let messages = vec![
+            ChatMessage {
+                role: "user".to_string(),
+                content: "Hi".to_string(),
+            },
+            ChatMessage {
+                role: "assistant".to_string(),
+                content: "Boo!".to_string(),
+            },
+        ];
+        assert_eq!(to_markdown("casper", &messages), "# Chat with casper\n\n**You:** Hi\n\n**casper:** Boo!\n");
+    }
+}
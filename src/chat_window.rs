@@ -4,18 +4,18 @@
 //! tao event loop instead of spawning a separate thread.
 
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::time::Instant;
 
-use egui_wgpu::ScreenDescriptor;
-use ghost_ui::ExtraWindow;
-use tao::dpi::LogicalSize;
+use ghost_ui::{ExtraWindow, NineSliceSkin};
+use serde::{Deserialize, Serialize};
 use tao::event::WindowEvent;
 use tao::event_loop::EventLoop;
-use tao::window::{Window, WindowBuilder, WindowId};
-use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
+use tao::window::WindowId;
+
+use crate::egui_window::EguiWindow;
+use crate::session;
 
 /// Message in the chat
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String, // "user" or "assistant"
     pub content: String,
@@ -28,6 +28,14 @@ pub enum ChatWindowCommand {
     Hide,
     Toggle,
     AddMessage(ChatMessage),
+    /// Add text to the last message (a reply being streamed)
+    AppendToLast(String),
+    /// Remove all messages
+    Clear,
+    /// Show a saved session in place of the current conversation
+    LoadSession { name: String, messages: Vec<ChatMessage> },
+    /// Change the window size (logical pixels) and its offset from the ghost
+    Resize { size: [u32; 2], offset: [i32; 2] },
 }
 
 /// Channel for sending commands to the chat window
@@ -41,20 +49,19 @@ pub fn create_chat_channel() -> (ChatSender, ChatReceiver) {
 
 /// Chat window state and rendering
 pub struct ChatWindow {
-    window: Window,
-    surface: Surface<'static>,
-    device: Device,
-    queue: Queue,
-    config: SurfaceConfiguration,
-    egui_ctx: egui::Context,
-    egui_renderer: egui_wgpu::Renderer,
+    egui: EguiWindow,
     messages: Vec<ChatMessage>,
+    /// Name of the session shown
+    session: String,
     input_text: String,
     receiver: ChatReceiver,
     on_send: Option<Sender<String>>,
     visible: bool,
     needs_repaint: bool,
-    start_time: Instant,
+    /// Offset from the ghost to follow at after a resize
+    offset_request: Option<[i32; 2]>,
+    /// Background image and its texture
+    skin: Option<(NineSliceSkin, egui::TextureHandle)>,
 }
 
 impl ChatWindow {
@@ -64,102 +71,32 @@ impl ChatWindow {
         receiver: ChatReceiver,
         on_send: Option<Sender<String>>,
         size: [u32; 2],
+        skin: Option<NineSliceSkin>,
     ) -> Self {
-        // Create the window (hidden initially, no decorations for precise positioning)
-        let window = WindowBuilder::new()
-            .with_inner_size(LogicalSize::new(size[0], size[1]))
-            .with_min_inner_size(LogicalSize::new(300, 400))
-            .with_title("Ghost Chat")
-            .with_visible(false)
-            .with_decorations(false) // No title bar for precise alignment
-            .build(event_loop)
-            .expect("Failed to create chat window");
-
-        // Create wgpu instance and surface
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
+        // No decorations (title bar) for precise alignment next to the ghost
+        let egui = EguiWindow::new(event_loop, "Ghost Chat", size, [300, 400], false);
+        let skin = skin.and_then(|skin| {
+            let texture = load_texture(egui.context(), &skin)?;
+            Some((skin, texture))
         });
 
-        // SAFETY: The window lives as long as the surface
-        let surface = unsafe {
-            let window_ptr = &window as *const Window;
-            instance
-                .create_surface(&*window_ptr)
-                .expect("Failed to create surface")
-        };
-
-        // Request adapter
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::LowPower,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))
-        .expect("Failed to find suitable adapter");
-
-        // Create device and queue
-        let (device, queue) = pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label: Some("Chat Window Device"),
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
-            },
-            None,
-        ))
-        .expect("Failed to create device");
-
-        // Configure surface
-        let size = window.inner_size();
-        let surface_caps = surface.get_capabilities(&adapter);
-        let format = surface_caps
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_caps.formats[0]);
-
-        let config = SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: size.width.max(1),
-            height: size.height.max(1),
-            present_mode: wgpu::PresentMode::AutoVsync,
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
-            view_formats: vec![],
-            desired_maximum_frame_latency: 2,
-        };
-        surface.configure(&device, &config);
-
-        // Create egui context
-        let egui_ctx = egui::Context::default();
-
-        // Create egui-wgpu renderer
-        let egui_renderer = egui_wgpu::Renderer::new(&device, format, None, 1);
-
         Self {
-            window,
-            surface,
-            device,
-            queue,
-            config,
-            egui_ctx,
-            egui_renderer,
-            messages: vec![ChatMessage {
-                role: "assistant".to_string(),
-                content: "Hello! How can I help you today?".to_string(),
-            }],
+            egui,
+            messages: vec![greeting()],
+            session: String::new(),
             input_text: String::new(),
             receiver,
             on_send,
             visible: false,
             needs_repaint: true,
-            start_time: Instant::now(),
+            offset_request: None,
+            skin,
         }
     }
 
     /// Get the window ID for event routing
     pub fn window_id(&self) -> WindowId {
-        self.window.id()
+        self.egui.window().id()
     }
 
     /// Check if the window is visible
@@ -170,15 +107,17 @@ impl ChatWindow {
     /// Show the window
     pub fn show(&mut self) {
         self.visible = true;
-        self.window.set_visible(true);
-        self.window.set_focus();
+        self.egui.window().set_visible(true);
+        self.egui.window().set_focus();
         self.needs_repaint = true;
+        session::update(|s| s.chat_open = true);
     }
 
     /// Hide the window
     pub fn hide(&mut self) {
         self.visible = false;
-        self.window.set_visible(false);
+        self.egui.window().set_visible(false);
+        session::update(|s| s.chat_open = false);
     }
 
     /// Toggle window visibility
@@ -196,9 +135,35 @@ impl ChatWindow {
         self.needs_repaint = true;
     }
 
+    /// Show a session's messages (the greeting if there are none)
+    pub fn load_session(&mut self, name: String, messages: Vec<ChatMessage>) {
+        self.session = name;
+        self.messages = if messages.is_empty() { vec![greeting()] } else { messages };
+        self.needs_repaint = true;
+    }
+
+    /// Add text to the last message
+    pub fn append_to_last(&mut self, text: &str) {
+        if let Some(message) = self.messages.last_mut() {
+            message.content.push_str(text);
+            self.needs_repaint = true;
+        }
+    }
+
+    /// Change the window size (logical pixels), following the ghost at
+    /// `offset` from then on
+    pub fn resize(&mut self, size: [u32; 2], offset: [i32; 2]) {
+        self.egui
+            .window()
+            .set_inner_size(tao::dpi::LogicalSize::new(size[0], size[1]));
+        self.offset_request = Some(offset);
+        self.needs_repaint = true;
+    }
+
     /// Set the window position (in physical pixels)
     pub fn set_position(&self, x: i32, y: i32) {
-        self.window
+        self.egui
+            .window()
             .set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
     }
 
@@ -209,7 +174,7 @@ impl ChatWindow {
             {
                 use tao::platform::macos::WindowExtMacOS;
                 // Get the NSWindow and call orderFront to bring to front without stealing focus
-                let ns_window = self.window.ns_window();
+                let ns_window = self.egui.window().ns_window();
                 unsafe {
                     use objc::{msg_send, sel, sel_impl};
                     let _: () = msg_send![ns_window as cocoa::base::id, orderFront: cocoa::base::nil];
@@ -218,7 +183,7 @@ impl ChatWindow {
             #[cfg(not(target_os = "macos"))]
             {
                 // On other platforms, set focus
-                self.window.set_focus();
+                self.egui.window().set_focus();
             }
         }
     }
@@ -231,134 +196,36 @@ impl ChatWindow {
                 ChatWindowCommand::Hide => self.hide(),
                 ChatWindowCommand::Toggle => self.toggle(),
                 ChatWindowCommand::AddMessage(msg) => self.add_message(msg),
+                ChatWindowCommand::AppendToLast(text) => self.append_to_last(&text),
+                ChatWindowCommand::Clear => self.load_session(self.session.clone(), Vec::new()),
+                ChatWindowCommand::LoadSession { name, messages } => self.load_session(name, messages),
+                ChatWindowCommand::Resize { size, offset } => self.resize(size, offset),
             }
         }
     }
 
     /// Handle window events
     pub fn handle_event(&mut self, event: &WindowEvent) {
-        // Convert tao event to egui input
-        match event {
-            WindowEvent::Resized(size) => {
-                if size.width > 0 && size.height > 0 {
-                    self.config.width = size.width;
-                    self.config.height = size.height;
-                    self.surface.configure(&self.device, &self.config);
-                    self.needs_repaint = true;
-                }
-            }
-            WindowEvent::CloseRequested => {
-                self.hide();
-            }
-            WindowEvent::KeyboardInput { event, .. } => {
-                let pressed = event.state == tao::event::ElementState::Pressed;
-
-                // Convert tao KeyCode to egui key
-                use tao::keyboard::KeyCode;
-                let egui_key = match event.physical_key {
-                    KeyCode::Escape => Some(egui::Key::Escape),
-                    KeyCode::Tab => Some(egui::Key::Tab),
-                    KeyCode::Backspace => Some(egui::Key::Backspace),
-                    KeyCode::Enter | KeyCode::NumpadEnter => Some(egui::Key::Enter),
-                    KeyCode::Space => Some(egui::Key::Space),
-                    KeyCode::Delete => Some(egui::Key::Delete),
-                    KeyCode::ArrowDown => Some(egui::Key::ArrowDown),
-                    KeyCode::ArrowLeft => Some(egui::Key::ArrowLeft),
-                    KeyCode::ArrowRight => Some(egui::Key::ArrowRight),
-                    KeyCode::ArrowUp => Some(egui::Key::ArrowUp),
-                    KeyCode::Home => Some(egui::Key::Home),
-                    KeyCode::End => Some(egui::Key::End),
-                    KeyCode::PageUp => Some(egui::Key::PageUp),
-                    KeyCode::PageDown => Some(egui::Key::PageDown),
-                    KeyCode::KeyA => Some(egui::Key::A),
-                    KeyCode::KeyC => Some(egui::Key::C),
-                    KeyCode::KeyV => Some(egui::Key::V),
-                    KeyCode::KeyX => Some(egui::Key::X),
-                    KeyCode::KeyZ => Some(egui::Key::Z),
-                    _ => None,
-                };
-
-                self.egui_ctx.input_mut(|i| {
-                    // Send key event
-                    if let Some(key) = egui_key {
-                        i.events.push(egui::Event::Key {
-                            key,
-                            physical_key: None,
-                            pressed,
-                            repeat: event.repeat,
-                            modifiers: i.modifiers,
-                        });
-                    }
-
-                    // Send text event for printable characters (only on press)
-                    if pressed {
-                        if let Some(text) = event.text {
-                            // Don't send text for control characters
-                            if !text.chars().all(|c| c.is_control()) {
-                                i.events.push(egui::Event::Text(text.to_string()));
-                            }
-                        }
-                    }
-                });
-                self.needs_repaint = true;
-            }
-            WindowEvent::ModifiersChanged(modifiers) => {
-                self.egui_ctx.input_mut(|i| {
-                    i.modifiers.alt = modifiers.alt_key();
-                    i.modifiers.ctrl = modifiers.control_key();
-                    i.modifiers.shift = modifiers.shift_key();
-                    i.modifiers.mac_cmd = modifiers.super_key();
-                    i.modifiers.command = if cfg!(target_os = "macos") {
-                        modifiers.super_key()
-                    } else {
-                        modifiers.control_key()
-                    };
-                });
-            }
-            WindowEvent::CursorMoved { position, .. } => {
-                // Convert physical pixels to logical pixels
-                let scale_factor = self.window.scale_factor() as f32;
-                let pos = egui::pos2(
-                    position.x as f32 / scale_factor,
-                    position.y as f32 / scale_factor,
-                );
-                self.egui_ctx.input_mut(|i| {
-                    i.events.push(egui::Event::PointerMoved(pos));
-                });
-                self.needs_repaint = true;
-            }
-            WindowEvent::MouseInput { state, button, .. } => {
-                let pressed = *state == tao::event::ElementState::Pressed;
-                let egui_button = match button {
-                    tao::event::MouseButton::Left => egui::PointerButton::Primary,
-                    tao::event::MouseButton::Right => egui::PointerButton::Secondary,
-                    tao::event::MouseButton::Middle => egui::PointerButton::Middle,
-                    _ => return,
-                };
-                self.egui_ctx.input_mut(|i| {
-                    i.events.push(egui::Event::PointerButton {
-                        pos: i.pointer.latest_pos().unwrap_or_default(),
-                        button: egui_button,
-                        pressed,
-                        modifiers: i.modifiers,
-                    });
-                });
-                self.needs_repaint = true;
-            }
-            WindowEvent::Focused(focused) => {
-                self.egui_ctx.input_mut(|i| {
-                    i.focused = *focused;
-                });
-                self.needs_repaint = true;
+        if let WindowEvent::CloseRequested = event {
+            self.hide();
+            return;
+        }
+        // Minimizing reports a zero size, which isn't worth keeping
+        if let WindowEvent::Resized(size) = event {
+            if size.width > 0 && size.height > 0 {
+                let size = size.to_logical::<u32>(self.egui.window().scale_factor());
+                session::update(|s| s.chat_size = Some([size.width, size.height]));
             }
-            _ => {}
+        }
+        if self.egui.handle_event(event) {
+            self.needs_repaint = true;
         }
     }
 
     /// Request a redraw
     pub fn request_redraw(&self) {
         if self.visible {
-            self.window.request_redraw();
+            self.egui.window().request_redraw();
         }
     }
 
@@ -375,57 +242,29 @@ impl ChatWindow {
 
         self.needs_repaint = false;
 
-        let output = self.surface.get_current_texture();
-        let output = match output {
-            Ok(output) => output,
-            Err(wgpu::SurfaceError::Lost) => {
-                self.surface.configure(&self.device, &self.config);
-                return;
-            }
-            Err(wgpu::SurfaceError::OutOfMemory) => {
-                log::error!("Chat window: Out of memory");
-                return;
-            }
-            Err(e) => {
-                log::error!("Chat window surface error: {:?}", e);
-                return;
-            }
-        };
-
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Begin egui frame with time info for cursor blinking
-        // IMPORTANT: screen_rect must be in LOGICAL pixels (physical / scale_factor)
-        let scale_factor = self.window.scale_factor() as f32;
-        let logical_width = self.config.width as f32 / scale_factor;
-        let logical_height = self.config.height as f32 / scale_factor;
-
-        // Set pixels_per_point on the context
-        self.egui_ctx.set_pixels_per_point(scale_factor);
-
-        let raw_input = egui::RawInput {
-            screen_rect: Some(egui::Rect::from_min_size(
-                egui::Pos2::ZERO,
-                egui::vec2(logical_width, logical_height),
-            )),
-            time: Some(self.start_time.elapsed().as_secs_f64()),
-            predicted_dt: 1.0 / 60.0,
-            ..Default::default()
-        };
-
         // Clone data needed for UI
         let messages = self.messages.clone();
+        let session = self.session.clone();
         let mut input_text = std::mem::take(&mut self.input_text);
         let on_send = self.on_send.clone();
+        let skin = &self.skin;
 
         // New messages to add after the frame
         let mut new_messages: Vec<ChatMessage> = Vec::new();
 
-        let full_output = self.egui_ctx.run(raw_input, |ctx| {
+        let has_shapes = self.egui.render(|ctx| {
+            // With a skin the panels are see-through over it
+            let (mut input_frame, mut content_frame) =
+                (egui::Frame::side_top_panel(&ctx.style()), egui::Frame::central_panel(&ctx.style()));
+            if let Some((skin, texture)) = skin {
+                paint_skin(ctx, skin, texture);
+                input_frame.fill = egui::Color32::TRANSPARENT;
+                content_frame.fill = egui::Color32::TRANSPARENT;
+            }
+
             // Use TopBottomPanel for fixed input at bottom (like WhatsApp)
             egui::TopBottomPanel::bottom("input_panel")
+                .frame(input_frame)
                 .resizable(false)
                 .min_height(50.0)
                 .show(ctx, |ui| {
@@ -452,20 +291,20 @@ impl ChatWindow {
                                 content: user_msg.clone(),
                             });
 
-                            // Send to callback if available
+                            // Send to callback if available, it replies via AddMessage
                             if let Some(ref sender) = on_send {
                                 let _ = sender.send(user_msg.clone());
+                            } else {
+                                // No handler - echo back a placeholder response
+                                new_messages.push(ChatMessage {
+                                    role: "assistant".to_string(),
+                                    content: format!(
+                                        "You said: \"{}\" (AI integration coming soon!)",
+                                        user_msg
+                                    ),
+                                });
                             }
 
-                            // For now, echo back a placeholder response
-                            new_messages.push(ChatMessage {
-                                role: "assistant".to_string(),
-                                content: format!(
-                                    "You said: \"{}\" (AI integration coming soon!)",
-                                    user_msg
-                                ),
-                            });
-
                             input_text.clear();
                         }
                     });
@@ -473,10 +312,13 @@ impl ChatWindow {
                 });
 
             // Main content area with messages
-            egui::CentralPanel::default().show(ctx, |ui| {
+            egui::CentralPanel::default().frame(content_frame).show(ctx, |ui| {
                 // Chat title
                 ui.horizontal(|ui| {
                     ui.heading("Chat");
+                    if !session.is_empty() {
+                        ui.label(egui::RichText::new(&session).weak());
+                    }
                 });
                 ui.separator();
 
@@ -525,74 +367,52 @@ impl ChatWindow {
         self.input_text = input_text;
 
         // Handle repaint requests - check if there are pending animations
-        if !full_output.shapes.is_empty() {
+        if has_shapes {
             self.needs_repaint = true;
         }
+    }
+}
 
-        // Process egui output
-        let clipped_primitives = self.egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
-
-        // Update textures
-        for (id, delta) in &full_output.textures_delta.set {
-            self.egui_renderer.update_texture(&self.device, &self.queue, *id, delta);
-        }
-
-        let screen_descriptor = ScreenDescriptor {
-            size_in_pixels: [self.config.width, self.config.height],
-            pixels_per_point: self.window.scale_factor() as f32,
-        };
-
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Chat Encoder"),
-        });
+/// Upload a skin image to egui
+fn load_texture(ctx: &egui::Context, skin: &NineSliceSkin) -> Option<egui::TextureHandle> {
+    let image = image::load_from_memory(skin.data().bytes())
+        .map_err(|e| log::error!("Failed to decode chat skin: {}", e))
+        .ok()?
+        .to_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    let image = egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw());
+    Some(ctx.load_texture("chat-skin", image, egui::TextureOptions::LINEAR))
+}
 
-        self.egui_renderer.update_buffers(
-            &self.device,
-            &self.queue,
-            &mut encoder,
-            &clipped_primitives,
-            &screen_descriptor,
+/// Stretch the skin over the whole window, behind everything else
+fn paint_skin(ctx: &egui::Context, skin: &NineSliceSkin, texture: &egui::TextureHandle) {
+    let screen = ctx.screen_rect();
+    let rect = [screen.min.x, screen.min.y, screen.width(), screen.height()];
+    let mut mesh = egui::Mesh::with_texture(texture.id());
+    for patch in skin.patches(rect, 1.0) {
+        let [x, y, w, h] = patch.rect;
+        let [u0, v0, u1, v1] = patch.uv;
+        mesh.add_rect_with_uv(
+            egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(w, h)),
+            egui::Rect::from_min_max(egui::pos2(u0, v0), egui::pos2(u1, v1)),
+            egui::Color32::WHITE,
         );
-
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Chat Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.1,
-                            b: 0.1,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-
-            self.egui_renderer.render(&mut render_pass, &clipped_primitives, &screen_descriptor);
-        }
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
-
-        // Free textures
-        for id in &full_output.textures_delta.free {
-            self.egui_renderer.free_texture(id);
-        }
     }
+    ctx.layer_painter(egui::LayerId::background()).add(mesh);
+}
 
+/// First message of an empty conversation
+fn greeting() -> ChatMessage {
+    ChatMessage {
+        role: "assistant".to_string(),
+        content: "Hello! How can I help you today?".to_string(),
+    }
 }
 
 /// Implement ExtraWindow trait for integration with ghost-ui event loop
 impl ExtraWindow for ChatWindow {
     fn window_id(&self) -> WindowId {
-        self.window.id()
+        self.egui.window().id()
     }
 
     fn handle_event(&mut self, event: &WindowEvent) {
@@ -619,6 +439,15 @@ impl ExtraWindow for ChatWindow {
         ChatWindow::set_position(self, x, y);
     }
 
+    fn outer_size(&self) -> Option<(u32, u32)> {
+        let size = self.egui.window().outer_size();
+        Some((size.width, size.height))
+    }
+
+    fn take_offset_request(&mut self) -> Option<[i32; 2]> {
+        self.offset_request.take()
+    }
+
     fn bring_to_front(&self) {
         ChatWindow::bring_to_front(self);
     }
@@ -0,0 +1,109 @@
+//! System clipboard access
+//!
+//! Uses the platform's own tools (`pbpaste`/`pbcopy` on macOS, PowerShell
+//! on Windows, `wl-paste`/`wl-copy` on Wayland and `xclip` elsewhere).
+
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Longest clipboard text sent to the chat backend, in characters
+const MAX_EXPLAIN_CHARS: usize = 4000;
+
+/// Text on the clipboard
+pub fn read() -> io::Result<String> {
+    let output = paste_command().stdin(Stdio::null()).stderr(Stdio::null()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!("reading the clipboard failed ({})", output.status)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Put `text` on the clipboard
+pub fn write(text: &str) -> io::Result<()> {
+    let mut child = copy_command()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("writing the clipboard failed ({})", status)));
+    }
+    Ok(())
+}
+
+/// Chat message asking to explain the clipboard text, None when it is blank
+pub fn explain_prompt(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let mut excerpt: String = text.chars().take(MAX_EXPLAIN_CHARS).collect();
+    if excerpt.len() < text.len() {
+        excerpt.push_str("\n[…]");
+    }
+    Some(format!("Please explain this:\n\n{}", excerpt))
+}
+
+fn wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+fn paste_command() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("pbpaste")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", "Get-Clipboard -Raw"]);
+        command
+    } else if wayland() {
+        let mut command = Command::new("wl-paste");
+        command.arg("--no-newline");
+        command
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard", "-out"]);
+        command
+    }
+}
+
+/// Command reading the new clipboard text from stdin
+fn copy_command() -> Command {
+    if cfg!(target_os = "macos") {
+        Command::new("pbcopy")
+    } else if cfg!(target_os = "windows") {
+        let mut command = Command::new("powershell");
+        command.args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+        ]);
+        command
+    } else if wayland() {
+        Command::new("wl-copy")
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard", "-in"]);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_prompt() {
+        assert_eq!(explain_prompt("  \n"), None);
+        assert_eq!(explain_prompt(" fn main() {}\n").unwrap(), "Please explain this:\n\nfn main() {}");
+
+        let long = "z".repeat(MAX_EXPLAIN_CHARS + 10);
+        let prompt = explain_prompt(&long).unwrap();
+        assert!(prompt.ends_with("\n[…]"));
+        assert_eq!(prompt.matches('z').count(), MAX_EXPLAIN_CHARS);
+    }
+}
@@ -0,0 +1,239 @@
+//! Chat commands - text typed into the chat window (or sent over IPC) that controls the ghost
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+
+/// A command recognized in chat input
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatCommand {
+    Pomodoro(PomodoroAction),
+    /// "remind me at 3pm to stretch"
+    Remind { due: DateTime<Local>, text: String },
+    /// "/todo add buy milk"
+    TodoAdd(String),
+    /// "/todo done 3"
+    TodoDone(u32),
+    /// "/todo remove 3"
+    TodoRemove(u32),
+    /// "/todo" or "/reminders"
+    ListReminders,
+    /// "/look on", "/look off" or "/look" (toggle) - follow the cursor
+    LookAt(Option<bool>),
+    /// "/effect glow" (toggle) or "/effect" (list) - skin effects
+    Effect(Option<String>),
+    /// "/chat clear", "/chat export [name]", "/chat session work", "/chat sessions"
+    Chat(ChatAction),
+    /// A command that was recognized but can't be carried out, with why
+    Invalid(String),
+}
+
+/// Pomodoro sub-commands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PomodoroAction {
+    Start,
+    Stop,
+    Status,
+}
+
+/// Chat history sub-commands
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChatAction {
+    Clear,
+    /// Save the session as Markdown in the exports directory (under the
+    /// given file name, if any)
+    Export(Option<String>),
+    /// Switch to (or start) a named session
+    Session(String),
+    ListSessions,
+}
+
+/// Parse chat input into a command
+pub fn parse(input: &str) -> Option<ChatCommand> {
+    parse_at(input, Local::now())
+}
+
+/// Parse chat input, resolving relative times against `now`
+pub fn parse_at(input: &str, now: DateTime<Local>) -> Option<ChatCommand> {
+    let input = input.trim();
+
+    // Natural-language reminder, with or without a leading slash
+    for prefix in ["remind me ", "/remind "] {
+        if input.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix)) {
+            return parse_reminder(&input[prefix.len()..], now);
+        }
+    }
+
+    // Everything else is a slash command
+    let input = input.strip_prefix('/')?;
+    let (name, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let args = args.trim();
+    let (sub, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    let rest = rest.trim();
+
+    match (name.to_lowercase().as_str(), sub.to_lowercase().as_str()) {
+        ("pomodoro", "start") => Some(ChatCommand::Pomodoro(PomodoroAction::Start)),
+        ("pomodoro", "stop") => Some(ChatCommand::Pomodoro(PomodoroAction::Stop)),
+        ("pomodoro", "" | "status") => Some(ChatCommand::Pomodoro(PomodoroAction::Status)),
+        ("todo", "add") if !rest.is_empty() => Some(ChatCommand::TodoAdd(rest.to_string())),
+        ("todo", "done") => rest.parse().ok().map(ChatCommand::TodoDone),
+        ("todo", "remove" | "rm") => rest.parse().ok().map(ChatCommand::TodoRemove),
+        ("todo" | "reminders", "" | "list") => Some(ChatCommand::ListReminders),
+        ("look", "") => Some(ChatCommand::LookAt(None)),
+        ("look", "on") => Some(ChatCommand::LookAt(Some(true))),
+        ("look", "off") => Some(ChatCommand::LookAt(Some(false))),
+        ("effect" | "effects", "") => Some(ChatCommand::Effect(None)),
+        ("effect", name) => Some(ChatCommand::Effect(Some(name.to_string()))),
+        ("chat", "clear") => Some(ChatCommand::Chat(ChatAction::Clear)),
+        ("chat", "export") => Some(ChatCommand::Chat(ChatAction::Export(
+            (!rest.is_empty()).then(|| rest.to_string()),
+        ))),
+        ("chat", "session" | "switch") if !rest.is_empty() => {
+            Some(ChatCommand::Chat(ChatAction::Session(rest.to_string())))
+        }
+        ("chat", "" | "sessions") => Some(ChatCommand::Chat(ChatAction::ListSessions)),
+        _ => None,
+    }
+}
+
+/// Parse "at 3pm to stretch" or "in 10 minutes to stretch"
+fn parse_reminder(rest: &str, now: DateTime<Local>) -> Option<ChatCommand> {
+    let mut words = rest.split_whitespace();
+
+    let due = match words.next()?.to_lowercase().as_str() {
+        "at" => {
+            let time = parse_time_of_day(words.next()?)?;
+            let today = now.date_naive().and_time(time);
+            let mut due = Local.from_local_datetime(&today).earliest()?;
+            // Times already passed today mean tomorrow
+            if due <= now {
+                due += Duration::days(1);
+            }
+            due
+        }
+        "in" => {
+            let amount: i64 = words.next()?.parse().ok()?;
+            let unit = match words.next()?.to_lowercase().trim_end_matches('s') {
+                "sec" | "second" => Duration::try_seconds(amount),
+                "min" | "minute" | "m" => Duration::try_minutes(amount),
+                "hour" | "hr" | "h" => Duration::try_hours(amount),
+                "day" | "d" => Duration::try_days(amount),
+                _ => return None,
+            };
+            match unit.and_then(|unit| now.checked_add_signed(unit)) {
+                Some(due) => due,
+                None => return Some(ChatCommand::Invalid("That's too far away to remind you of.".to_string())),
+            }
+        }
+        _ => return None,
+    };
+
+    let mut text: Vec<&str> = words.collect();
+    if text.first().is_some_and(|w| w.eq_ignore_ascii_case("to")) {
+        text.remove(0);
+    }
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(ChatCommand::Remind {
+        due,
+        text: text.join(" "),
+    })
+}
+
+/// Parse "3pm", "3:30pm", "15:30" or "9"
+fn parse_time_of_day(s: &str) -> Option<NaiveTime> {
+    let s = s.to_lowercase();
+    let (clock, offset) = if let Some(c) = s.strip_suffix("am") {
+        (c, Some(0))
+    } else if let Some(c) = s.strip_suffix("pm") {
+        (c, Some(12))
+    } else {
+        (s.as_str(), None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 10, 14, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_time_of_day() {
+        assert_eq!(parse_time_of_day("3pm"), NaiveTime::from_hms_opt(15, 0, 0));
+        assert_eq!(parse_time_of_day("12am"), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(parse_time_of_day("9:45am"), NaiveTime::from_hms_opt(9, 45, 0));
+        assert_eq!(parse_time_of_day("17:30"), NaiveTime::from_hms_opt(17, 30, 0));
+        assert_eq!(parse_time_of_day("13pm"), None);
+    }
+
+    #[test]
+    fn test_remind_at() {
+        let cmd = parse_at("remind me at 3pm to Call Mom", now());
+        assert_eq!(
+            cmd,
+            Some(ChatCommand::Remind {
+                due: Local.with_ymd_and_hms(2024, 5, 10, 15, 0, 0).unwrap(),
+                text: "Call Mom".to_string(),
+            })
+        );
+
+        // Already passed today -> tomorrow
+        let Some(ChatCommand::Remind { due, .. }) = parse_at("remind me at 9am stretch", now()) else {
+            panic!("expected reminder");
+        };
+        assert_eq!(due, Local.with_ymd_and_hms(2024, 5, 11, 9, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_remind_in() {
+        let Some(ChatCommand::Remind { due, text }) = parse_at("remind me in 20 minutes to stretch", now()) else {
+            panic!("expected reminder");
+        };
+        assert_eq!(due, now() + Duration::minutes(20));
+        assert_eq!(text, "stretch");
+
+        assert!(matches!(
+            parse_at("/remind in 99999999999999 days to wait", now()),
+            Some(ChatCommand::Invalid(_))
+        ));
+        assert!(matches!(
+            parse_at("/remind in -9223372036854775808 seconds to wait", now()),
+            Some(ChatCommand::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_slash_commands() {
+        assert_eq!(parse_at("/todo add buy milk", now()), Some(ChatCommand::TodoAdd("buy milk".to_string())));
+        assert_eq!(parse_at("/todo done 3", now()), Some(ChatCommand::TodoDone(3)));
+        assert_eq!(parse_at("/todo", now()), Some(ChatCommand::ListReminders));
+        assert_eq!(parse_at("/pomodoro start", now()), Some(ChatCommand::Pomodoro(PomodoroAction::Start)));
+        assert_eq!(parse_at("/look off", now()), Some(ChatCommand::LookAt(Some(false))));
+        assert_eq!(parse_at("/effect Glow", now()), Some(ChatCommand::Effect(Some("glow".to_string()))));
+        assert_eq!(parse_at("/effects", now()), Some(ChatCommand::Effect(None)));
+        assert_eq!(parse_at("/chat clear", now()), Some(ChatCommand::Chat(ChatAction::Clear)));
+        assert_eq!(parse_at("/chat export", now()), Some(ChatCommand::Chat(ChatAction::Export(None))));
+        assert_eq!(
+            parse_at("/chat session work", now()),
+            Some(ChatCommand::Chat(ChatAction::Session("work".to_string())))
+        );
+        assert_eq!(parse_at("/chat session", now()), None);
+        assert_eq!(parse_at("hello there", now()), None);
+    }
+}
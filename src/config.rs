@@ -1,19 +1,181 @@
 //! Configuration loading from ui.toml
 
+use ghost_ui::{
+    AnimatedSkin, Animation, ButtonImage, LoadProgress, NineSliceSkin, PersonaTrust, Recolor, SkinData, SkinError,
+    StateMachineConfig, StateSettings, Transition, WindowLayer,
+};
 use serde::Deserialize;
+
+use crate::audio::SoundsConfig;
+use crate::config_check;
+use crate::look_at::GazeDirection;
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
+
+/// Default configuration file, in the current directory
+pub const CONFIG_FILE: &str = "ui.toml";
+
+/// Longest show and hide animation of the window, in seconds
+pub const MAX_TRANSITION_DURATION: f32 = 10.0;
 
 /// Root configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub skin: SkinConfig,
+    #[serde(default)]
+    pub window: WindowConfig,
     pub callout: CalloutConfig,
     #[serde(default)]
     pub chat: ChatConfig,
     #[serde(default)]
+    pub llm: LlmConfig,
+    #[serde(default)]
     pub buttons: Vec<ButtonConfig>,
     #[serde(default)]
     pub layers: Vec<LayerConfig>,
+    #[serde(default)]
+    pub schedule: Vec<ScheduleConfig>,
+    #[serde(default)]
+    pub behavior: Vec<BehaviorConfig>,
+    #[serde(default)]
+    pub pomodoro: PomodoroConfig,
+    #[serde(default)]
+    pub reminders: RemindersConfig,
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    #[serde(default)]
+    pub weather: WeatherConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub media: MediaConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub look_at: LookAtConfig,
+    #[serde(default)]
+    pub wander: WanderConfig,
+    #[serde(default)]
+    pub motion: MotionConfig,
+    #[serde(default)]
+    pub petting: PettingConfig,
+    #[serde(default)]
+    pub context_menu: ContextMenuConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeysConfig,
+    #[serde(default)]
+    pub mood: MoodConfig,
+    #[serde(default)]
+    pub instance: InstanceConfig,
+    #[serde(default)]
+    pub debug: DebugConfig,
+    #[serde(default)]
+    pub gallery: GalleryConfig,
+    #[serde(default)]
+    pub discord: DiscordConfig,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    #[serde(default)]
+    pub audio: AudioConfig,
+    #[serde(default)]
+    pub speech: SpeechConfig,
+    #[serde(default)]
+    pub recolor: Vec<RecolorConfig>,
+    #[serde(default)]
+    pub effects: EffectsConfig,
+    /// Reapply edits to this file while the ghost runs
+    #[serde(default = "default_true")]
+    pub hot_reload: bool,
+}
+
+/// Where the ghost window starts
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WindowConfig {
+    /// Monitor to start on: "primary", "cursor", an index ("0", "1", ...) or
+    /// part of the monitor name. Unset: let the OS decide.
+    #[serde(default)]
+    pub monitor: Option<String>,
+    /// Start position in logical pixels from the monitor's top-left corner
+    /// (default: centered)
+    #[serde(default)]
+    pub position: Option<[f32; 2]>,
+    /// Dock against a screen edge or corner when a drag ends this close to
+    /// it, in pixels (unset: no snapping)
+    #[serde(default)]
+    pub edge_snap: Option<f32>,
+    /// Keep the ghost, its callout and chat inside the usable part of the
+    /// screen, clear of the taskbar, dock or menu bar
+    #[serde(default)]
+    pub keep_in_work_area: bool,
+    /// Video memory animation frames may take, in MB. Only the frames about
+    /// to play are uploaded and the least recently used are dropped (unset:
+    /// upload every frame)
+    #[serde(default)]
+    pub gpu_memory_mb: Option<u32>,
+    /// Animation when the ghost is hidden or shown: "fade", "pop", "slide"
+    /// or "dissolve" (unset: instant)
+    #[serde(default)]
+    pub transition: Option<String>,
+    /// Seconds the show and hide animation takes (default: 0.4, at most
+    /// `MAX_TRANSITION_DURATION`)
+    #[serde(default)]
+    pub transition_duration: Option<f32>,
+    /// Where the ghost stacks among other windows: "top" (default),
+    /// "normal" or "desktop" (below other windows, like a desktop widget)
+    #[serde(default)]
+    pub layer: Option<String>,
+    /// Opacity while the ghost has focus, 0.0 - 1.0 (default: 1.0)
+    #[serde(default)]
+    pub opacity_focused: Option<f32>,
+    /// Opacity while another window has focus (default: 0.7)
+    #[serde(default)]
+    pub opacity_unfocused: Option<f32>,
+}
+
+impl WindowConfig {
+    /// The animation frame budget in bytes
+    pub fn gpu_budget(&self) -> Option<usize> {
+        self.gpu_memory_mb.map(|mb| mb as usize * 1024 * 1024)
+    }
+
+    /// The show and hide animation and its length, if any
+    pub fn transition(&self) -> Option<(Transition, Duration)> {
+        let name = self.transition.as_deref()?;
+        let Some(transition) = Transition::from_name(name) else {
+            if name != "none" {
+                log::warn!("Unknown window transition '{}'", name);
+            }
+            return None;
+        };
+        let seconds = self.transition_duration.unwrap_or(0.4).clamp(0.0, MAX_TRANSITION_DURATION);
+        let Ok(duration) = Duration::try_from_secs_f32(seconds) else {
+            log::warn!("Invalid window transition duration {}", seconds);
+            return None;
+        };
+        Some((transition, duration))
+    }
+
+    /// Where the ghost and its callout stack among other windows
+    pub fn layer(&self) -> WindowLayer {
+        let Some(name) = self.layer.as_deref() else {
+            return WindowLayer::Top;
+        };
+        WindowLayer::from_name(name).unwrap_or_else(|| {
+            log::warn!("Unknown window layer '{}', keeping the ghost on top", name);
+            WindowLayer::Top
+        })
+    }
+
+    /// Opacity with and without focus
+    pub fn opacity(&self) -> (f32, f32) {
+        (
+            self.opacity_focused.unwrap_or(1.0).clamp(0.0, 1.0),
+            self.opacity_unfocused.unwrap_or(0.7).clamp(0.0, 1.0),
+        )
+    }
 }
 
 /// Chat window configuration
@@ -32,6 +194,12 @@ pub struct ChatConfig {
     /// Chat window size [width, height]
     #[serde(default = "default_chat_size")]
     pub size: [u32; 2],
+    /// Messages kept per saved session (0 = don't save chats)
+    #[serde(default = "default_chat_history_limit")]
+    pub history_limit: usize,
+    /// Image drawn behind the chat
+    #[serde(default)]
+    pub skin: Option<NineSliceConfig>,
 }
 
 impl Default for ChatConfig {
@@ -41,10 +209,81 @@ impl Default for ChatConfig {
             offset: [0, 0],
             align: default_chat_align(),
             size: default_chat_size(),
+            history_limit: default_chat_history_limit(),
+            skin: None,
+        }
+    }
+}
+
+/// Language model answering in the chat window
+#[derive(Debug, Clone, Deserialize)]
+pub struct LlmConfig {
+    /// "openai" (any OpenAI-compatible API), "ollama" or "none"
+    #[serde(default = "default_llm_provider")]
+    pub provider: String,
+    /// API base URL (default: the provider's public/local address)
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub model: String,
+    /// API key; prefer `api_key_env` to keep it out of the config file
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Environment variable holding the API key
+    #[serde(default = "default_llm_api_key_env")]
+    pub api_key_env: String,
+    /// Instructions sent ahead of the conversation
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Most recent messages sent along as context
+    #[serde(default = "default_llm_max_history")]
+    pub max_history: usize,
+    /// Also show replies in the callout as they arrive
+    #[serde(default = "default_true")]
+    pub callout: bool,
+}
+
+impl LlmConfig {
+    /// The configured key, or the one in `api_key_env`
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var(&self.api_key_env).ok())
+            .filter(|key| !key.is_empty())
+    }
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            provider: default_llm_provider(),
+            url: None,
+            model: String::new(),
+            api_key: None,
+            api_key_env: default_llm_api_key_env(),
+            system_prompt: String::new(),
+            max_history: default_llm_max_history(),
+            callout: true,
         }
     }
 }
 
+fn default_llm_provider() -> String {
+    "none".to_string()
+}
+
+fn default_llm_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+fn default_llm_max_history() -> usize {
+    20
+}
+
+fn default_chat_history_limit() -> usize {
+    500
+}
+
 fn default_chat_anchor() -> String {
     "right".to_string()
 }
@@ -129,12 +368,88 @@ pub struct SkinConfig {
     /// Frames per second for animated skins
     #[serde(default = "default_skin_fps")]
     pub fps: f32,
+    /// Direction the skin image faces: "left" or "right" (used when flipping)
+    #[serde(default = "default_skin_faces")]
+    pub faces: String,
+    /// Directory of persona folders listed in the tray (default: parent of `path`)
+    #[serde(default)]
+    pub persona_dir: Option<String>,
+    /// Reload the skin when its files change on disk
+    #[serde(default = "default_true")]
+    pub hot_reload: bool,
+    /// Fingerprints or public keys of persona signers (`gassetsmaker sign`);
+    /// when set, only personas signed by one of them load
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+}
+
+impl SkinConfig {
+    /// Load the animated skin: a folder of states, a packed persona (.zip)
+    /// or a single GIF/APNG/WebP
+    pub fn load_animated(&self) -> Result<AnimatedSkin, SkinError> {
+        Self::load_animated_from(Path::new(&self.path), self.fps, &self.trust(), &mut |_| {})
+    }
+
+    /// Load an animated skin from `path` like `load_animated`, reporting
+    /// each state as it is loaded
+    pub fn load_animated_from(
+        path: &Path,
+        fps: f32,
+        trust: &PersonaTrust,
+        progress: &mut dyn FnMut(LoadProgress),
+    ) -> Result<AnimatedSkin, SkinError> {
+        if Animation::is_animation_file(path) {
+            // A lone animation file can't carry a signature
+            if trust.requires_signature() {
+                return Err(SkinError::Untrusted(format!("{}: not signed", path.display())));
+            }
+            AnimatedSkin::from_file(path)
+        } else if is_zip(path) {
+            AnimatedSkin::from_zip_with_progress(path, fps, trust, progress)
+        } else {
+            AnimatedSkin::from_directory_with_progress(path, fps, trust, progress)
+        }
+    }
+
+    /// Persona signers to accept
+    pub fn trust(&self) -> PersonaTrust {
+        PersonaTrust::new(self.trusted_keys.clone())
+    }
+
+    /// Directory scanned for personas
+    pub fn persona_dir(&self) -> std::path::PathBuf {
+        match self.persona_dir {
+            Some(ref dir) => dir.into(),
+            None => Path::new(&self.path).parent().map(Path::to_path_buf).unwrap_or_default(),
+        }
+    }
+
+    /// Name of the active persona (the skin's folder or file name, without
+    /// a `.persona.zip` extension)
+    pub fn persona(&self) -> String {
+        let name = Path::new(&self.path)
+            .file_stem()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        match name.strip_suffix(".persona") {
+            Some(name) => name.to_string(),
+            None => name,
+        }
+    }
+}
+
+fn is_zip(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
 }
 
 fn default_skin_fps() -> f32 {
     24.0
 }
 
+fn default_skin_faces() -> String {
+    "right".to_string()
+}
+
 /// Callout configuration
 #[derive(Debug, Clone, Deserialize)]
 pub struct CalloutConfig {
@@ -156,6 +471,20 @@ pub struct CalloutConfig {
     /// Duration in seconds (0 = permanent)
     #[serde(default = "default_duration")]
     pub duration: f32,
+    /// Fit the bubble to the text and grow it as text appears
+    #[serde(default)]
+    pub auto_size: bool,
+    /// Clicking the bubble skips the typing, a second click dismisses it
+    #[serde(default)]
+    pub interactive: bool,
+    /// Point on the skin [x, y] (e.g. the mascot's mouth) for the bubble's
+    /// tail to aim at; unset keeps the tail at the bottom
+    #[serde(default)]
+    pub arrow_target: Option<[f32; 2]>,
+    /// Flip the bubble (and its tail) to the mascot's other side where it
+    /// would run off the screen
+    #[serde(default = "default_true")]
+    pub keep_on_screen: bool,
     /// Style options
     #[serde(default)]
     pub style: CalloutStyleConfig,
@@ -185,6 +514,24 @@ pub struct CalloutStyleConfig {
     pub padding: f32,
     #[serde(default = "default_border_radius")]
     pub border_radius: f32,
+    /// Outline color, RGBA
+    #[serde(default = "default_border_color")]
+    pub border_color: [f32; 4],
+    /// Outline width in pixels (0 = no outline)
+    #[serde(default = "default_border_width")]
+    pub border_width: f32,
+    /// Drop shadow color, RGBA (transparent = no shadow)
+    #[serde(default = "default_shadow_color")]
+    pub shadow_color: [f32; 4],
+    /// Drop shadow offset [x, y] in pixels
+    #[serde(default = "default_shadow_offset")]
+    pub shadow_offset: [f32; 2],
+    /// How far the shadow fades out, in pixels
+    #[serde(default = "default_shadow_blur")]
+    pub shadow_blur: f32,
+    /// Image drawn as the bubble instead of the shape
+    #[serde(default)]
+    pub skin: Option<NineSliceConfig>,
 }
 
 impl Default for CalloutStyleConfig {
@@ -194,6 +541,12 @@ impl Default for CalloutStyleConfig {
             text_color: default_text_color(),
             padding: default_padding(),
             border_radius: default_border_radius(),
+            border_color: default_border_color(),
+            border_width: default_border_width(),
+            shadow_color: default_shadow_color(),
+            shadow_offset: default_shadow_offset(),
+            shadow_blur: default_shadow_blur(),
+            skin: None,
         }
     }
 }
@@ -210,6 +563,40 @@ fn default_padding() -> f32 {
 fn default_border_radius() -> f32 {
     10.0
 }
+fn default_border_color() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 0.2]
+}
+fn default_border_width() -> f32 {
+    1.0
+}
+fn default_shadow_color() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 0.2]
+}
+fn default_shadow_offset() -> [f32; 2] {
+    [2.0, 2.0]
+}
+fn default_shadow_blur() -> f32 {
+    4.0
+}
+
+/// Background image stretched to fit, keeping its borders (nine-slice)
+#[derive(Debug, Clone, Deserialize)]
+pub struct NineSliceConfig {
+    /// PNG image
+    pub path: String,
+    /// Borders [left, top, right, bottom] in image pixels that don't stretch
+    #[serde(default)]
+    pub insets: [f32; 4],
+}
+
+impl NineSliceConfig {
+    /// Load the image, logging failures
+    pub fn load(&self) -> Option<NineSliceSkin> {
+        NineSliceSkin::from_path(&self.path, self.insets)
+            .map_err(|e| log::error!("Failed to load skin '{}': {}", self.path, e))
+            .ok()
+    }
+}
 
 /// Button configuration
 #[derive(Debug, Clone, Deserialize)]
@@ -221,11 +608,39 @@ pub struct ButtonConfig {
     pub size: [f32; 2],
     #[serde(default = "default_button_style")]
     pub style: String,
+    /// Sprite sheet drawn instead of the style, frames side by side in the
+    /// order normal, hover, pressed, disabled
+    pub image: Option<String>,
+    /// Number of frames in `image`
+    #[serde(default = "default_button_frames")]
+    pub frames: u32,
+    /// Separate images per state in the same order, instead of `image`
+    #[serde(default)]
+    pub images: Vec<String>,
+}
+
+impl ButtonConfig {
+    /// Load the button's image, if it has one (None and logged if it fails)
+    pub fn load_image(&self) -> Option<ButtonImage> {
+        let image = match self.image {
+            Some(ref path) => SkinData::from_path(path).and_then(|sheet| ButtonImage::from_sheet(sheet, self.frames)),
+            None if self.images.is_empty() => return None,
+            None => self
+                .images
+                .iter()
+                .map(SkinData::from_path)
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|frames| ButtonImage::from_frames(&frames)),
+        };
+        image.map_err(|e| log::error!("Failed to load the image of button '{}': {}", self.id, e)).ok()
+    }
 }
 
 /// Layer configuration for overlay images
 #[derive(Debug, Clone, Deserialize)]
 pub struct LayerConfig {
+    /// Optional identifier so other features can update the layer text
+    pub id: Option<String>,
     /// Path to the layer image
     pub path: String,
     /// Anchor point: "top-left", "bottom-center", etc.
@@ -285,86 +700,1440 @@ fn default_layer_text_padding() -> [f32; 4] {
     [8.0, 8.0, 8.0, 8.0]
 }
 
-fn default_button_size() -> [f32; 2] {
-    [60.0, 28.0]
+/// Scheduled action configuration
+///
+/// Exactly one of `cron` or `every` must be set.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleConfig {
+    /// Optional name used in logs
+    pub name: Option<String>,
+    /// Cron expression "minute hour day month weekday" (local time)
+    pub cron: Option<String>,
+    /// Interval: "90s", "15m", "2h", "1d"
+    pub every: Option<String>,
+    /// Callout text to show
+    pub text: Option<String>,
+    /// Callout type: "say", "think", "scream", "whisper"
+    #[serde(default = "default_schedule_callout")]
+    pub callout: String,
+    /// Animation state to switch to
+    pub state: Option<String>,
+    /// Shell command to run
+    pub script: Option<String>,
+    /// Announce today's weather forecast
+    #[serde(default)]
+    pub weather: bool,
+    /// Run once on wake if the job was missed (e.g. during sleep)
+    #[serde(default = "default_true")]
+    pub catch_up: bool,
 }
-fn default_button_style() -> String {
-    "default".to_string()
+
+fn default_schedule_callout() -> String {
+    "say".to_string()
 }
 
-/// Anchor position enum
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum Anchor {
-    TopLeft,
-    TopCenter,
-    TopRight,
-    CenterLeft,
-    CenterCenter,
-    CenterRight,
-    BottomLeft,
-    BottomCenter,
-    BottomRight,
+/// Time-of-day behavior (`[[behavior]]`), active between `from` and `to`
+///
+/// Ranges may wrap past midnight ("23:00" to "07:00"). Entries with `days`
+/// take priority over entries without, so they act as per-day overrides.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BehaviorConfig {
+    /// Optional name used in logs
+    pub name: Option<String>,
+    /// Start time "HH:MM" (local time)
+    pub from: String,
+    /// End time "HH:MM" (exclusive)
+    pub to: String,
+    /// Restrict to weekdays, e.g. ["sat", "sun"] (empty = every day)
+    #[serde(default)]
+    pub days: Vec<String>,
+    /// Animation state while active
+    pub state: Option<String>,
+    /// Window opacity while active (0.0 - 1.0)
+    pub opacity: Option<f32>,
+    /// Skin effects while active, e.g. ["grayscale"]
+    #[serde(default)]
+    pub effects: Vec<String>,
+    /// Callout shown when the behavior starts
+    pub greeting: Option<String>,
+    /// Callout type: "say", "think", "scream", "whisper"
+    #[serde(default = "default_schedule_callout")]
+    pub callout: String,
 }
 
-impl Anchor {
-    pub fn from_str(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "top-left" | "topleft" => Self::TopLeft,
-            "top-center" | "topcenter" | "top" => Self::TopCenter,
-            "top-right" | "topright" => Self::TopRight,
-            "center-left" | "centerleft" | "left" => Self::CenterLeft,
-            "center-center" | "centercenter" | "center" => Self::CenterCenter,
-            "center-right" | "centerright" | "right" => Self::CenterRight,
-            "bottom-left" | "bottomleft" => Self::BottomLeft,
-            "bottom-center" | "bottomcenter" | "bottom" => Self::BottomCenter,
-            "bottom-right" | "bottomright" => Self::BottomRight,
-            _ => {
-                log::warn!("Unknown anchor '{}', defaulting to top-right", s);
-                Self::TopRight
-            }
+/// Pomodoro timer configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct PomodoroConfig {
+    /// Focus phase length in minutes
+    #[serde(default = "default_pomodoro_work")]
+    pub work_minutes: f32,
+    /// Short break length in minutes
+    #[serde(default = "default_pomodoro_short_break")]
+    pub short_break_minutes: f32,
+    /// Long break length in minutes
+    #[serde(default = "default_pomodoro_long_break")]
+    pub long_break_minutes: f32,
+    /// Take a long break after this many focus phases
+    #[serde(default = "default_pomodoro_long_break_every")]
+    pub long_break_every: u32,
+    /// Animation state during focus phases
+    #[serde(default = "default_pomodoro_work_state")]
+    pub work_state: String,
+    /// Animation state during breaks
+    #[serde(default = "default_pomodoro_break_state")]
+    pub break_state: String,
+    /// Callout text when a focus phase starts
+    #[serde(default = "default_pomodoro_work_text")]
+    pub work_text: String,
+    /// Callout text when a short break starts
+    #[serde(default = "default_pomodoro_short_break_text")]
+    pub short_break_text: String,
+    /// Callout text when a long break starts
+    #[serde(default = "default_pomodoro_long_break_text")]
+    pub long_break_text: String,
+    /// Id of the layer that shows the remaining time
+    pub label_layer: Option<String>,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: default_pomodoro_work(),
+            short_break_minutes: default_pomodoro_short_break(),
+            long_break_minutes: default_pomodoro_long_break(),
+            long_break_every: default_pomodoro_long_break_every(),
+            work_state: default_pomodoro_work_state(),
+            break_state: default_pomodoro_break_state(),
+            work_text: default_pomodoro_work_text(),
+            short_break_text: default_pomodoro_short_break_text(),
+            long_break_text: default_pomodoro_long_break_text(),
+            label_layer: None,
         }
     }
+}
 
-    /// Get the anchor position as a fraction of skin size (0.0 to 1.0)
-    pub fn as_fraction(&self) -> (f32, f32) {
-        match self {
-            Self::TopLeft => (0.0, 0.0),
-            Self::TopCenter => (0.5, 0.0),
-            Self::TopRight => (1.0, 0.0),
-            Self::CenterLeft => (0.0, 0.5),
-            Self::CenterCenter => (0.5, 0.5),
-            Self::CenterRight => (1.0, 0.5),
-            Self::BottomLeft => (0.0, 1.0),
-            Self::BottomCenter => (0.5, 1.0),
-            Self::BottomRight => (1.0, 1.0),
+fn default_pomodoro_work() -> f32 {
+    25.0
+}
+fn default_pomodoro_short_break() -> f32 {
+    5.0
+}
+fn default_pomodoro_long_break() -> f32 {
+    15.0
+}
+fn default_pomodoro_long_break_every() -> u32 {
+    4
+}
+fn default_pomodoro_work_state() -> String {
+    "thinking".to_string()
+}
+fn default_pomodoro_break_state() -> String {
+    "happy".to_string()
+}
+fn default_pomodoro_work_text() -> String {
+    "Focus time! Let's get to work.".to_string()
+}
+fn default_pomodoro_short_break_text() -> String {
+    "Break time~ Stretch a little!".to_string()
+}
+fn default_pomodoro_long_break_text() -> String {
+    "Long break! You earned it.".to_string()
+}
+
+/// Reminders configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemindersConfig {
+    /// Also show a native desktop notification for due reminders
+    #[serde(default = "default_true")]
+    pub notify: bool,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self { notify: true }
+    }
+}
+
+/// Local IPC server configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct IpcConfig {
+    /// Accept commands on localhost; off by default since any program on
+    /// the machine can connect
+    #[serde(default)]
+    pub enabled: bool,
+    /// TCP port on 127.0.0.1
+    #[serde(default = "default_ipc_port")]
+    pub port: u16,
+}
+
+impl Default for IpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_ipc_port(),
         }
     }
 }
 
-/// Window layout information including skin offset
-#[derive(Debug, Clone)]
-pub struct WindowLayout {
-    /// Total window width
-    pub window_width: u32,
-    /// Total window height
-    pub window_height: u32,
-    /// Skin offset within window [x, y]
-    pub skin_offset: [f32; 2],
+/// System monitor configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonitorConfig {
+    /// Poll CPU/memory/battery metrics
+    #[serde(default)]
+    pub enabled: bool,
+    /// Poll interval in seconds
+    #[serde(default = "default_monitor_interval")]
+    pub interval: f32,
+    /// Reactions to metric thresholds
+    #[serde(default)]
+    pub reactions: Vec<ReactionConfig>,
+    /// Id of the layer that shows `label`
+    pub label_layer: Option<String>,
+    /// Stats label text (supports templates like {cpu} and {download})
+    #[serde(default = "default_monitor_label")]
+    pub label: String,
+    /// Visible width of the label in characters
+    #[serde(default = "default_media_marquee_width")]
+    pub marquee_width: usize,
+    /// Label scroll speed in characters per second
+    #[serde(default = "default_media_marquee_speed")]
+    pub marquee_speed: f32,
 }
 
-impl Config {
-    /// Load configuration from a TOML file
-    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
-        let content = std::fs::read_to_string(path.as_ref())
-            .map_err(|e| ConfigError::Io(e.to_string()))?;
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: default_monitor_interval(),
+            reactions: Vec::new(),
+            label_layer: None,
+            label: default_monitor_label(),
+            marquee_width: default_media_marquee_width(),
+            marquee_speed: default_media_marquee_speed(),
+        }
+    }
+}
+
+fn default_monitor_label() -> String {
+    "CPU {cpu}% RAM {memory}%".to_string()
+}
+
+/// Reaction to a system metric crossing a threshold
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReactionConfig {
+    /// Metric: "cpu", "memory", "battery" (in percent), "download",
+    /// "upload" or "network" (in KB/s)
+    pub metric: String,
+    /// Trigger when the metric drops below this value
+    pub below: Option<f32>,
+    /// Trigger when the metric rises above this value
+    pub above: Option<f32>,
+    /// Animation state while the condition holds
+    pub state: Option<String>,
+    /// Callout text when triggered (supports templates like {battery})
+    pub text: Option<String>,
+    /// Callout type: "say", "think", "scream", "whisper"
+    #[serde(default = "default_schedule_callout")]
+    pub callout: String,
+    /// Minimum seconds between two triggers
+    #[serde(default = "default_reaction_cooldown")]
+    pub cooldown: f32,
+}
+
+/// Weather provider configuration (Open-Meteo)
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeatherConfig {
+    /// Fetch weather periodically
+    #[serde(default)]
+    pub enabled: bool,
+    /// Location latitude
+    #[serde(default)]
+    pub latitude: f32,
+    /// Location longitude
+    #[serde(default)]
+    pub longitude: f32,
+    /// Temperature units: "celsius" or "fahrenheit"
+    #[serde(default = "default_weather_units")]
+    pub units: String,
+    /// Minutes between refreshes
+    #[serde(default = "default_weather_refresh")]
+    pub refresh_minutes: f32,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            latitude: 0.0,
+            longitude: 0.0,
+            units: default_weather_units(),
+            refresh_minutes: default_weather_refresh(),
+        }
+    }
+}
+
+/// Calendar (ICS feed) configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct CalendarConfig {
+    /// Announce upcoming events
+    #[serde(default)]
+    pub enabled: bool,
+    /// ICS feed URLs or local file paths
+    #[serde(default)]
+    pub feeds: Vec<String>,
+    /// Announce events this many minutes before they start
+    #[serde(default = "default_calendar_lead")]
+    pub lead_minutes: f32,
+    /// Minutes between feed reloads
+    #[serde(default = "default_calendar_refresh")]
+    pub refresh_minutes: f32,
+    /// Also show a native desktop notification
+    #[serde(default = "default_true")]
+    pub notify: bool,
+}
 
-        toml::from_str(&content)
-            .map_err(|e| ConfigError::Parse(e.to_string()))
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            feeds: Vec::new(),
+            lead_minutes: default_calendar_lead(),
+            refresh_minutes: default_calendar_refresh(),
+            notify: true,
+        }
     }
+}
 
-    /// Load from default path (ui.toml in current directory)
-    pub fn load_default() -> Result<Self, ConfigError> {
-        Self::load("ui.toml")
+/// Now-playing media configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct MediaConfig {
+    /// Watch the system media session
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds between media session polls
+    #[serde(default = "default_media_poll")]
+    pub poll_interval: f32,
+    /// Id of the layer that shows the scrolling track name
+    pub label_layer: Option<String>,
+    /// Visible width of the track label in characters
+    #[serde(default = "default_media_marquee_width")]
+    pub marquee_width: usize,
+    /// Track label scroll speed in characters per second
+    #[serde(default = "default_media_marquee_speed")]
+    pub marquee_speed: f32,
+    /// Callout when the song changes ({track} = "Artist - Title", empty = no callout)
+    #[serde(default = "default_media_text")]
+    pub text: String,
+    /// Animation state to play when the song changes
+    pub state: Option<String>,
+}
+
+impl MediaConfig {
+    /// Time between media session polls
+    pub fn poll_interval(&self) -> Duration {
+        poll_duration(self.poll_interval)
+    }
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval: default_media_poll(),
+            label_layer: None,
+            marquee_width: default_media_marquee_width(),
+            marquee_speed: default_media_marquee_speed(),
+            text: default_media_text(),
+            state: None,
+        }
+    }
+}
+
+/// Other apps' desktop notifications repeated as callouts
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Only repeat these apps (case-insensitive, empty = all)
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// Never repeat these apps
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Callout text ({app}, {summary} and {body} are filled in)
+    #[serde(default = "default_notifications_text")]
+    pub text: String,
+    /// Callout type: "say", "think", "scream" or "whisper"
+    #[serde(default = "default_notifications_callout")]
+    pub callout: String,
+    /// Animation state to play for a notification
+    pub state: Option<String>,
+    /// Seconds between notification checks on Windows
+    #[serde(default = "default_media_poll")]
+    pub poll_interval: f32,
+}
+
+impl NotificationsConfig {
+    /// Time between notification checks
+    pub fn poll_interval(&self) -> Duration {
+        poll_duration(self.poll_interval)
+    }
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            apps: Vec::new(),
+            ignore: Vec::new(),
+            text: default_notifications_text(),
+            callout: default_notifications_callout(),
+            state: None,
+            poll_interval: default_media_poll(),
+        }
+    }
+}
+
+fn default_notifications_text() -> String {
+    "{app}: {summary}".to_string()
+}
+
+fn default_notifications_callout() -> String {
+    "say".to_string()
+}
+
+/// Cursor tracking configuration
+#[derive(Debug, Clone, Deserialize)]
+pub struct LookAtConfig {
+    /// Follow the cursor at startup (can be toggled at runtime)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Id of the layer to shift toward the cursor (e.g. the eyes)
+    pub layer: Option<String>,
+    /// Flip the skin to face the cursor
+    #[serde(default)]
+    pub flip: bool,
+    /// Maximum layer shift in pixels
+    #[serde(default = "default_look_at_max_offset")]
+    pub max_offset: f32,
+    /// Radius around the skin center where the cursor is ignored, in pixels
+    #[serde(default = "default_look_at_dead_zone")]
+    pub dead_zone: f32,
+    /// How quickly the layer catches up (higher = snappier)
+    #[serde(default = "default_look_at_smoothing")]
+    pub smoothing: f32,
+}
+
+impl Default for LookAtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            layer: None,
+            flip: false,
+            max_offset: default_look_at_max_offset(),
+            dead_zone: default_look_at_dead_zone(),
+            smoothing: default_look_at_smoothing(),
+        }
+    }
+}
+
+/// Wandering along the bottom edge of the screen
+#[derive(Debug, Clone, Deserialize)]
+pub struct WanderConfig {
+    /// Walk around on its own
+    #[serde(default)]
+    pub enabled: bool,
+    /// Minimum seconds between walks
+    #[serde(default = "default_wander_min_interval")]
+    pub min_interval: f32,
+    /// Maximum seconds between walks
+    #[serde(default = "default_wander_max_interval")]
+    pub max_interval: f32,
+    /// Walking speed in pixels per second
+    #[serde(default = "default_wander_speed")]
+    pub speed: f32,
+    /// Longest single walk in pixels
+    #[serde(default = "default_wander_max_distance")]
+    pub max_distance: f32,
+    /// Animation state while walking
+    #[serde(default = "default_wander_walk_state")]
+    pub walk_state: String,
+    /// Seconds to stand still after the user interacts
+    #[serde(default = "default_wander_pause")]
+    pub pause_after_interaction: f32,
+    /// Distance kept from the bottom edge (e.g. for the taskbar) in pixels
+    #[serde(default = "default_wander_bottom_margin")]
+    pub bottom_margin: i32,
+}
+
+impl Default for WanderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_interval: default_wander_min_interval(),
+            max_interval: default_wander_max_interval(),
+            speed: default_wander_speed(),
+            max_distance: default_wander_max_distance(),
+            walk_state: default_wander_walk_state(),
+            pause_after_interaction: default_wander_pause(),
+            bottom_margin: default_wander_bottom_margin(),
+        }
+    }
+}
+
+/// Single-instance lock, crash recovery and session restore
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstanceConfig {
+    /// Allow only one ghost; starting another focuses the running one
+    #[serde(default = "default_true")]
+    pub single: bool,
+    /// Start a new ghost after a crash
+    #[serde(default)]
+    pub relaunch_on_crash: bool,
+    /// Start where the last session left off (position, persona, chat
+    /// window, mute) instead of the configured defaults
+    #[serde(default = "default_true")]
+    pub restore_session: bool,
+}
+
+impl Default for InstanceConfig {
+    fn default() -> Self {
+        Self {
+            single: true,
+            relaunch_on_crash: false,
+            restore_session: true,
+        }
+    }
+}
+
+/// Debug console window
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DebugConfig {
+    /// Offer the debug console in the tray menu
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Persona gallery window
+#[derive(Debug, Clone, Deserialize)]
+pub struct GalleryConfig {
+    /// Offer the gallery in the tray's persona menu
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// URL of a JSON index of downloadable personas
+    /// (`{"personas": [{"name", "url", "description", "thumbnail", "size"}]}`)
+    #[serde(default)]
+    pub index_url: Option<String>,
+}
+
+impl Default for GalleryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            index_url: None,
+        }
+    }
+}
+
+/// Discord Rich Presence (needs the `discord` build feature)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Application id from the Discord developer portal
+    #[serde(default)]
+    pub client_id: String,
+    /// Show how long the current activity has been going on
+    #[serde(default = "default_true")]
+    pub show_elapsed: bool,
+    /// Asset key of the large image uploaded to the application
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            client_id: String::new(),
+            show_elapsed: true,
+            image: None,
+        }
+    }
+}
+
+/// Persona sounds (needs the `audio` build feature)
+#[derive(Debug, Clone, Deserialize)]
+pub struct AudioConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Volume (0.0 - 1.0)
+    #[serde(default = "default_audio_volume")]
+    pub volume: f32,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: default_audio_volume(),
+            muted: false,
+        }
+    }
+}
+
+fn default_audio_volume() -> f32 {
+    0.75
+}
+
+/// Speaking "say" callouts aloud
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpeechConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Voice name of the speech engine (engine default when unset)
+    #[serde(default)]
+    pub voice: Option<String>,
+    /// Words per minute
+    #[serde(default = "default_speech_rate")]
+    pub rate: f32,
+    /// External engine reading the text from stdin, e.g. ["festival", "--tts"]
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            voice: None,
+            rate: default_speech_rate(),
+            command: Vec::new(),
+        }
+    }
+}
+
+fn default_speech_rate() -> f32 {
+    180.0
+}
+
+/// A color variant of a persona, applied while drawing it (`[[recolor]]`).
+/// Colors are RGB from 0.0 to 1.0.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RecolorConfig {
+    /// Persona the variant is for (None = every persona)
+    pub persona: Option<String>,
+    /// Hue rotation in degrees
+    pub hue_shift: f32,
+    /// Saturation multiplier (0.0 = grayscale)
+    pub saturation: f32,
+    /// Brightness multiplier
+    pub brightness: f32,
+    /// Color to tint the persona with
+    pub tint: Option<[f32; 3]>,
+    /// How much of the tint shows (0.0 - 1.0)
+    pub tint_strength: f32,
+    /// Colors replaced by others, keeping the shading (at most 8)
+    pub palette: Vec<PaletteSwapConfig>,
+    /// How far a pixel may be from a palette color and still be replaced
+    pub tolerance: f32,
+}
+
+impl Default for RecolorConfig {
+    fn default() -> Self {
+        Self {
+            persona: None,
+            hue_shift: 0.0,
+            saturation: 1.0,
+            brightness: 1.0,
+            tint: None,
+            tint_strength: 0.5,
+            palette: Vec::new(),
+            tolerance: 0.1,
+        }
+    }
+}
+
+impl RecolorConfig {
+    /// Whether the variant applies to `persona`
+    pub fn matches(&self, persona: &str) -> bool {
+        self.persona.as_deref().is_none_or(|name| name == persona)
+    }
+
+    pub fn to_recolor(&self) -> Recolor {
+        Recolor {
+            hue_shift: self.hue_shift,
+            saturation: self.saturation,
+            brightness: self.brightness,
+            tint: self.tint.unwrap_or([1.0, 1.0, 1.0]),
+            tint_strength: if self.tint.is_some() { self.tint_strength.clamp(0.0, 1.0) } else { 0.0 },
+            palette: self.palette.iter().map(|swap| (swap.from, swap.to)).collect(),
+            tolerance: self.tolerance,
+        }
+    }
+}
+
+/// One color of a palette swap
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaletteSwapConfig {
+    pub from: [f32; 3],
+    pub to: [f32; 3],
+}
+
+/// Skin post effects (`[effects]`): "glow", "dissolve", "pixelate" and
+/// "grayscale", switched on here, by behaviors or with "/effect"
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EffectsConfig {
+    /// Effects on from the start, e.g. ["glow"]
+    pub enabled: Vec<String>,
+    /// Seconds an effect takes to fade in or out
+    pub fade: f32,
+    /// Glow color (RGBA 0.0 - 1.0)
+    pub glow_color: [f32; 4],
+    /// Glow reach in pixels
+    pub glow_radius: f32,
+    /// Block size of "pixelate" in pixels
+    pub pixel_size: f32,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Vec::new(),
+            fade: 0.5,
+            glow_color: [0.7, 0.85, 1.0, 0.8],
+            glow_radius: 6.0,
+            pixel_size: 6.0,
+        }
+    }
+}
+
+/// Accessibility options
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccessibilityConfig {
+    /// No typing, fading, particles, wandering or frame animation.
+    /// Unset: follow the OS preference.
+    #[serde(default)]
+    pub reduced_motion: Option<bool>,
+    /// Minimum contrast ratio (WCAG, 1-21) of callout text with reduced motion
+    #[serde(default = "default_min_contrast")]
+    pub min_contrast: f32,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            reduced_motion: None,
+            min_contrast: default_min_contrast(),
+        }
+    }
+}
+
+impl AccessibilityConfig {
+    /// Whether reduced motion is on (after `accessibility::resolve`)
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion.unwrap_or(false)
+    }
+}
+
+fn default_min_contrast() -> f32 {
+    7.0
+}
+
+/// Mood - affection and energy shaped by interactions
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoodConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Affection gained per petting
+    #[serde(default = "default_mood_pet_affection")]
+    pub pet_affection: f32,
+    /// Affection gained per chat message or button click
+    #[serde(default = "default_mood_chat_affection")]
+    pub chat_affection: f32,
+    /// Energy spent per interaction
+    #[serde(default = "default_mood_activity_cost")]
+    pub activity_cost: f32,
+    /// Seconds without interaction before the ghost feels ignored
+    #[serde(default = "default_mood_ignored_after")]
+    pub ignored_after: f32,
+    /// Affection lost per minute while ignored
+    #[serde(default = "default_mood_lonely_rate")]
+    pub lonely_rate: f32,
+    /// Energy recovered per minute while left alone
+    #[serde(default = "default_mood_rest_rate")]
+    pub rest_rate: f32,
+    /// Idle animation state per mood ("happy", "content", "lonely", "tired")
+    #[serde(default)]
+    pub idle_states: HashMap<String, String>,
+    /// Text appended to callouts per mood
+    #[serde(default)]
+    pub suffixes: HashMap<String, String>,
+    /// Greeting when the user comes back to a lonely ghost (empty = none)
+    #[serde(default = "default_mood_welcome_back")]
+    pub welcome_back: String,
+}
+
+impl Default for MoodConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pet_affection: default_mood_pet_affection(),
+            chat_affection: default_mood_chat_affection(),
+            activity_cost: default_mood_activity_cost(),
+            ignored_after: default_mood_ignored_after(),
+            lonely_rate: default_mood_lonely_rate(),
+            rest_rate: default_mood_rest_rate(),
+            idle_states: HashMap::new(),
+            suffixes: HashMap::new(),
+            welcome_back: default_mood_welcome_back(),
+        }
+    }
+}
+
+fn default_mood_pet_affection() -> f32 {
+    5.0
+}
+
+fn default_mood_chat_affection() -> f32 {
+    2.0
+}
+
+fn default_mood_activity_cost() -> f32 {
+    1.0
+}
+
+fn default_mood_ignored_after() -> f32 {
+    600.0
+}
+
+fn default_mood_lonely_rate() -> f32 {
+    0.5
+}
+
+fn default_mood_rest_rate() -> f32 {
+    1.0
+}
+
+fn default_mood_welcome_back() -> String {
+    "You're back! I missed you~".to_string()
+}
+
+/// Petting - rubbing the cursor back and forth over the skin
+#[derive(Debug, Clone, Deserialize)]
+pub struct PettingConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Direction changes needed to count as petting
+    #[serde(default = "default_petting_min_reversals")]
+    pub min_reversals: u32,
+    /// Minimum length of each stroke in pixels
+    #[serde(default = "default_petting_min_stroke")]
+    pub min_stroke: f32,
+    /// Seconds the strokes must happen within
+    #[serde(default = "default_petting_window")]
+    pub window: f32,
+    /// Animation state to play
+    #[serde(default = "default_petting_state")]
+    pub state: String,
+    /// Callout text (empty = no callout)
+    #[serde(default = "default_petting_text")]
+    pub text: String,
+    /// Callout type: "say", "think", "scream", "whisper" or "alert"
+    #[serde(default = "default_schedule_callout")]
+    pub callout: String,
+    /// Number of hearts per burst
+    #[serde(default = "default_petting_hearts")]
+    pub hearts: usize,
+    /// Particle image (default: built-in heart)
+    #[serde(default)]
+    pub particle: Option<String>,
+    /// Particle size in pixels
+    #[serde(default = "default_petting_particle_size")]
+    pub particle_size: f32,
+    /// Seconds before petting reacts again
+    #[serde(default = "default_petting_cooldown")]
+    pub cooldown: f32,
+}
+
+impl Default for PettingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_reversals: default_petting_min_reversals(),
+            min_stroke: default_petting_min_stroke(),
+            window: default_petting_window(),
+            state: default_petting_state(),
+            text: default_petting_text(),
+            callout: default_schedule_callout(),
+            hearts: default_petting_hearts(),
+            particle: None,
+            particle_size: default_petting_particle_size(),
+            cooldown: default_petting_cooldown(),
+        }
+    }
+}
+
+fn default_petting_min_reversals() -> u32 {
+    4
+}
+
+fn default_petting_min_stroke() -> f32 {
+    12.0
+}
+
+fn default_petting_window() -> f32 {
+    1.2
+}
+
+fn default_petting_state() -> String {
+    "happy".to_string()
+}
+
+fn default_petting_text() -> String {
+    "Hehe~ that tickles!".to_string()
+}
+
+fn default_petting_hearts() -> usize {
+    6
+}
+
+fn default_petting_particle_size() -> f32 {
+    24.0
+}
+
+fn default_petting_cooldown() -> f32 {
+    3.0
+}
+
+/// Menu opened by right-clicking the ghost
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContextMenuConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Entries from top to bottom: "chat", "personas", "look_at",
+    /// "pomodoro", "explain_clipboard", "copy_reply", "hide", "autostart",
+    /// "quit", "separator" or the id of an action
+    #[serde(default = "default_context_menu_items")]
+    pub items: Vec<String>,
+    /// Custom entries, handled like clicking the button with the same id
+    #[serde(default)]
+    pub actions: Vec<ContextMenuAction>,
+}
+
+impl Default for ContextMenuConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            items: default_context_menu_items(),
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// A custom context menu entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContextMenuAction {
+    pub id: String,
+    pub label: String,
+}
+
+fn default_context_menu_items() -> Vec<String> {
+    ["chat", "personas", "separator", "hide", "quit"].map(String::from).to_vec()
+}
+
+/// System-wide keyboard shortcuts, working while another app has focus
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HotkeysConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub bindings: Vec<HotkeyBinding>,
+}
+
+/// A shortcut and what it does
+#[derive(Debug, Clone, Deserialize)]
+pub struct HotkeyBinding {
+    /// Key combination like "Ctrl+Shift+G"
+    pub keys: String,
+    /// "toggle_chat", "toggle_visibility", "next_persona",
+    /// "persona:<name>" or "say:<text>"
+    pub action: String,
+}
+
+/// Physics movement - falling, walking and being thrown
+#[derive(Debug, Clone, Deserialize)]
+pub struct MotionConfig {
+    /// Fall to the bottom of the screen and walk around (replaces wander)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Downward acceleration in pixels per second²
+    #[serde(default = "default_motion_gravity")]
+    pub gravity: f32,
+    /// Walking speed in pixels per second
+    #[serde(default = "default_wander_speed")]
+    pub walk_speed: f32,
+    /// Longest single walk in pixels
+    #[serde(default = "default_motion_max_distance")]
+    pub max_distance: f32,
+    /// Minimum seconds standing between walks
+    #[serde(default = "default_motion_min_interval")]
+    pub min_interval: f32,
+    /// Maximum seconds standing between walks
+    #[serde(default = "default_motion_max_interval")]
+    pub max_interval: f32,
+    /// Velocity kept when bouncing (0 = no bounce, 1 = forever)
+    #[serde(default = "default_motion_bounce")]
+    pub bounce: f32,
+    /// Distance kept from the bottom edge (e.g. for the taskbar) in pixels
+    #[serde(default = "default_wander_bottom_margin")]
+    pub bottom_margin: i32,
+    /// Animation state while walking
+    #[serde(default = "default_wander_walk_state")]
+    pub walk_state: String,
+    /// Animation state while falling or thrown
+    #[serde(default = "default_motion_fall_state")]
+    pub fall_state: String,
+    /// Animation state while held by the user
+    #[serde(default = "default_motion_drag_state")]
+    pub drag_state: String,
+}
+
+impl Default for MotionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gravity: default_motion_gravity(),
+            walk_speed: default_wander_speed(),
+            max_distance: default_motion_max_distance(),
+            min_interval: default_motion_min_interval(),
+            max_interval: default_motion_max_interval(),
+            bounce: default_motion_bounce(),
+            bottom_margin: default_wander_bottom_margin(),
+            walk_state: default_wander_walk_state(),
+            fall_state: default_motion_fall_state(),
+            drag_state: default_motion_drag_state(),
+        }
+    }
+}
+
+fn default_motion_gravity() -> f32 {
+    2000.0
+}
+
+fn default_motion_max_distance() -> f32 {
+    300.0
+}
+
+fn default_motion_min_interval() -> f32 {
+    5.0
+}
+
+fn default_motion_max_interval() -> f32 {
+    20.0
+}
+
+fn default_motion_bounce() -> f32 {
+    0.3
+}
+
+fn default_motion_fall_state() -> String {
+    "fall".to_string()
+}
+
+fn default_motion_drag_state() -> String {
+    "drag".to_string()
+}
+
+fn default_wander_min_interval() -> f32 {
+    60.0
+}
+
+fn default_wander_max_interval() -> f32 {
+    180.0
+}
+
+fn default_wander_speed() -> f32 {
+    60.0
+}
+
+fn default_wander_max_distance() -> f32 {
+    400.0
+}
+
+fn default_wander_walk_state() -> String {
+    "walk".to_string()
+}
+
+fn default_wander_pause() -> f32 {
+    30.0
+}
+
+fn default_wander_bottom_margin() -> i32 {
+    48
+}
+
+fn default_look_at_max_offset() -> f32 {
+    4.0
+}
+
+fn default_look_at_dead_zone() -> f32 {
+    24.0
+}
+
+fn default_look_at_smoothing() -> f32 {
+    10.0
+}
+
+fn default_media_poll() -> f32 {
+    2.0
+}
+
+/// `seconds` between polls, at least half a second; the default when it's
+/// not a usable number
+fn poll_duration(seconds: f32) -> Duration {
+    Duration::try_from_secs_f32(seconds.max(0.5)).unwrap_or_else(|_| {
+        log::warn!("Invalid poll interval {}, using {} seconds", seconds, default_media_poll());
+        Duration::from_secs_f32(default_media_poll())
+    })
+}
+
+fn default_media_marquee_width() -> usize {
+    24
+}
+
+fn default_media_marquee_speed() -> f32 {
+    6.0
+}
+
+fn default_media_text() -> String {
+    "Now playing: {track}".to_string()
+}
+
+fn default_calendar_lead() -> f32 {
+    10.0
+}
+
+fn default_calendar_refresh() -> f32 {
+    15.0
+}
+
+fn default_weather_units() -> String {
+    "celsius".to_string()
+}
+
+fn default_weather_refresh() -> f32 {
+    30.0
+}
+
+fn default_monitor_interval() -> f32 {
+    5.0
+}
+
+fn default_reaction_cooldown() -> f32 {
+    300.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_ipc_port() -> u16 {
+    47474
+}
+
+fn default_button_size() -> [f32; 2] {
+    [60.0, 28.0]
+}
+fn default_button_style() -> String {
+    "default".to_string()
+}
+fn default_button_frames() -> u32 {
+    1
+}
+
+/// Anchor position enum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    CenterCenter,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "top-left" | "topleft" => Self::TopLeft,
+            "top-center" | "topcenter" | "top" => Self::TopCenter,
+            "top-right" | "topright" => Self::TopRight,
+            "center-left" | "centerleft" | "left" => Self::CenterLeft,
+            "center-center" | "centercenter" | "center" => Self::CenterCenter,
+            "center-right" | "centerright" | "right" => Self::CenterRight,
+            "bottom-left" | "bottomleft" => Self::BottomLeft,
+            "bottom-center" | "bottomcenter" | "bottom" => Self::BottomCenter,
+            "bottom-right" | "bottomright" => Self::BottomRight,
+            _ => {
+                log::warn!("Unknown anchor '{}', defaulting to top-right", s);
+                Self::TopRight
+            }
+        }
+    }
+
+    /// Get the anchor position as a fraction of skin size (0.0 to 1.0)
+    pub fn as_fraction(&self) -> (f32, f32) {
+        match self {
+            Self::TopLeft => (0.0, 0.0),
+            Self::TopCenter => (0.5, 0.0),
+            Self::TopRight => (1.0, 0.0),
+            Self::CenterLeft => (0.0, 0.5),
+            Self::CenterCenter => (0.5, 0.5),
+            Self::CenterRight => (1.0, 0.5),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::BottomCenter => (0.5, 1.0),
+            Self::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// Window layout information including skin offset
+#[derive(Debug, Clone)]
+pub struct WindowLayout {
+    /// Total window width
+    pub window_width: u32,
+    /// Total window height
+    pub window_height: u32,
+    /// Skin offset within window [x, y]
+    pub skin_offset: [f32; 2],
+}
+
+/// The parts of a persona's `config.toml` read by the app
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PersonaManifest {
+    /// Animation transitions and idle variations
+    #[serde(default)]
+    pub state_machine: Option<StateMachineConfig>,
+    /// Sounds for animation states and callouts
+    #[serde(default)]
+    pub sounds: SoundsConfig,
+    /// Per-state fps, play mode, sound and follow-up state (the playback
+    /// settings are applied by the skin loader)
+    #[serde(default)]
+    pub states: HashMap<String, StateSettings>,
+    /// Rhai behavior script inside the persona (default "behavior.rhai")
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Parts drawn over the body, each with its own animations
+    #[serde(default)]
+    pub layers: Vec<PersonaLayerConfig>,
+    /// Random blinks and breathing on top of the animations
+    #[serde(default)]
+    pub auto_life: Option<AutoLifeConfig>,
+    /// Mouth moving with speech and sounds
+    #[serde(default)]
+    pub lip_sync: Option<LipSyncConfig>,
+    /// Eyes following the cursor
+    #[serde(default)]
+    pub gaze: Option<GazeConfig>,
+}
+
+/// Micro-animations generated for a persona (`[auto_life]` in its
+/// config.toml)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AutoLifeConfig {
+    /// State with the eyes closed, played for each blink
+    pub blink_state: String,
+    /// Part (path of a `[[layers]]` entry) that blinks; without one the
+    /// body blinks, and only while idle
+    pub blink_layer: Option<String>,
+    /// Seconds between blinks [min, max]
+    pub blink_interval: [f32; 2],
+    /// Seconds the eyes stay closed
+    pub blink_duration: f32,
+    /// Chance (0-1) a blink is followed by a second one
+    pub double_blink: f32,
+    /// How much the body shrinks while breathing out (0.01 = 1%, 0 = off)
+    pub breathing: f32,
+    /// Seconds per breath
+    pub breathing_period: f32,
+}
+
+impl Default for AutoLifeConfig {
+    fn default() -> Self {
+        Self {
+            blink_state: "blink".to_string(),
+            blink_layer: None,
+            blink_interval: [2.0, 6.0],
+            blink_duration: 0.15,
+            double_blink: 0.2,
+            breathing: 0.01,
+            breathing_period: 4.0,
+        }
+    }
+}
+
+/// Lip-sync of a persona (`[lip_sync]` in its config.toml): the frames of
+/// the mouth state, from closed to open, follow the loudness of speech
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LipSyncConfig {
+    /// Part (path of a `[[layers]]` entry) holding the mouth; without one
+    /// the body's own frames move
+    pub layer: Option<String>,
+    /// State whose frames go from a closed to an open mouth
+    pub state: String,
+    /// Multiplier of the sound loudness (quiet files need more)
+    pub gain: f32,
+    /// Seconds the mouth takes to follow the loudness
+    pub smoothing: f32,
+}
+
+impl Default for LipSyncConfig {
+    fn default() -> Self {
+        Self {
+            layer: None,
+            state: "talk".to_string(),
+            gain: 4.0,
+            smoothing: 0.06,
+        }
+    }
+}
+
+/// Eyes of a persona following the cursor (`[gaze]` in its config.toml)
+/// while cursor tracking is on (`[look_at]` in ui.toml): pupils shifted
+/// toward the cursor, or states looking each way when any is given
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct GazeConfig {
+    /// Part (path of a `[[layers]]` entry) with the pupils, or showing the
+    /// look states; without one the body shows them
+    pub layer: Option<String>,
+    /// Largest pupil shift in pixels (default: `max_offset` of `[look_at]`)
+    pub max_offset: Option<f32>,
+    /// States looking left, right, up and down
+    pub left: Option<String>,
+    pub right: Option<String>,
+    pub up: Option<String>,
+    pub down: Option<String>,
+}
+
+impl GazeConfig {
+    /// State looking in `direction`, if the persona has one
+    pub fn state(&self, direction: GazeDirection) -> Option<&str> {
+        let state = match direction {
+            GazeDirection::Left => &self.left,
+            GazeDirection::Right => &self.right,
+            GazeDirection::Up => &self.up,
+            GazeDirection::Down => &self.down,
+        };
+        state.as_deref()
+    }
+
+    /// Whether the eyes look through states rather than shifted pupils
+    pub fn has_states(&self) -> bool {
+        self.left.is_some() || self.right.is_some() || self.up.is_some() || self.down.is_some()
+    }
+}
+
+/// A part of a composite persona (eyes, mouth, accessory), loaded from a
+/// folder of state animations laid out like the persona itself. Each part
+/// plays its animation for the body's current state, or its idle one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PersonaLayerConfig {
+    /// Folder inside the persona, e.g. "layers/mouth" (folders under
+    /// `layers/` are not loaded as states of the body)
+    pub path: String,
+    /// Anchor point on the body: "top-left", "center", etc.
+    #[serde(default = "default_layer_anchor")]
+    pub anchor: String,
+    /// Offset from anchor [x, y] in pixels
+    #[serde(default)]
+    pub offset: [f32; 2],
+    /// Optional size override [width, height] in pixels
+    pub size: Option<[f32; 2]>,
+    /// Drawing order among the parts (higher = on top)
+    #[serde(default)]
+    pub z_order: i32,
+    /// Frames per second (default: the skin's fps)
+    pub fps: Option<f32>,
+}
+
+impl PersonaManifest {
+    /// Load `config.toml` from a persona directory or packed persona (empty
+    /// when missing or invalid)
+    pub fn load(persona_dir: impl AsRef<Path>) -> Self {
+        let persona_dir = persona_dir.as_ref();
+        let path = persona_dir.join("config.toml");
+        let Some(content) = Self::read_file(persona_dir, "config.toml") else {
+            return Self::default();
+        };
+        let mut manifest: Self = toml::from_str(&content).unwrap_or_else(|e| {
+            log::error!("Invalid persona manifest {}: {}", path.display(), e);
+            Self::default()
+        });
+        // A state's own sound counts unless [sounds.states] names one
+        for (state, settings) in &manifest.states {
+            if let Some(ref sound) = settings.sound {
+                manifest.sounds.states.entry(state.clone()).or_insert_with(|| sound.clone());
+            }
+        }
+        manifest
+    }
+
+    /// Read a text file from a persona directory or packed persona
+    pub fn read_file(persona_dir: &Path, name: &str) -> Option<String> {
+        if is_zip(persona_dir) {
+            read_zip_text(persona_dir, name)
+        } else {
+            std::fs::read_to_string(persona_dir.join(name)).ok()
+        }
+    }
+}
+
+/// The `config.toml` of a packed persona, at the top or in its single folder
+fn read_zip_text(path: &Path, file_name: &str) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let name = archive
+        .file_names()
+        .filter(|name| *name == file_name || name.split_once('/').is_some_and(|(_, rest)| rest == file_name))
+        .min_by_key(|name| name.len())?
+        .to_string();
+    let mut content = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name(&name).ok()?, &mut content).ok()?;
+    Some(content)
+}
+
+impl Config {
+    /// Load configuration from a TOML file, logging unknown keys, values out
+    /// of range and missing assets
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let (config, problems) = config_check::load(path)?;
+        for problem in problems {
+            log::warn!("{}: {}", path.display(), problem);
+        }
+        Ok(config)
+    }
+
+    /// Load from default path (ui.toml in current directory)
+    pub fn load_default() -> Result<Self, ConfigError> {
+        Self::load(CONFIG_FILE)
+    }
+
+    /// Colors of `persona`: the first `[[recolor]]` entry for it, if any
+    pub fn recolor(&self, persona: &str) -> Option<Recolor> {
+        self.recolor.iter().find(|recolor| recolor.matches(persona)).map(RecolorConfig::to_recolor)
     }
 
     /// Calculate the window layout to accommodate both skin and callout
@@ -0,0 +1,285 @@
+//! Checking ui.toml - keys the ghost doesn't know, values out of range and
+//! assets that don't exist, each with the line it is on
+//!
+//! Problems are warnings: the ghost starts anyway, and `ghost --check-config`
+//! lists them without starting.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::{Config, ConfigError, NineSliceConfig, CONFIG_FILE, MAX_TRANSITION_DURATION};
+
+/// Something wrong in the config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Line in the file (1-based), if the key could be found
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Read and check a config file
+pub fn load(path: &Path) -> Result<(Config, Vec<Problem>), ConfigError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::Io(format!("{}: {}", path.display(), e)))?;
+    parse(&content)
+}
+
+/// Parse and check the text of a config file
+pub fn parse(content: &str) -> Result<(Config, Vec<Problem>), ConfigError> {
+    let mut unknown = Vec::new();
+    let deserializer = toml::Deserializer::new(content);
+    let config: Config = serde_ignored::deserialize(deserializer, |path| unknown.push(segments(&path)))
+        .map_err(|e| ConfigError::Parse(e.to_string()))?;
+
+    let mut problems: Vec<Problem> = unknown
+        .into_iter()
+        .map(|key| Problem {
+            line: line_of(content, &key),
+            message: format!("unknown key `{}`", key.join(".")),
+        })
+        .collect();
+    problems.extend(check(&config).into_iter().map(|(key, message)| {
+        let key: Vec<String> = key.split('.').map(str::to_string).collect();
+        Problem { line: line_of(content, &key), message }
+    }));
+    Ok((config, problems))
+}
+
+/// Check `ui.toml` for `ghost --check-config`, printing what is wrong;
+/// returns the process exit code
+pub fn run() -> i32 {
+    match load(Path::new(CONFIG_FILE)) {
+        Ok((_, problems)) if problems.is_empty() => {
+            println!("{}: OK", CONFIG_FILE);
+            0
+        }
+        Ok((_, problems)) => {
+            for problem in &problems {
+                println!("{}: {}", CONFIG_FILE, problem);
+            }
+            println!("{} problem(s) found", problems.len());
+            1
+        }
+        Err(e) => {
+            println!("{}: {}", CONFIG_FILE, e);
+            1
+        }
+    }
+}
+
+/// Values out of range and missing assets, as (dotted key, message)
+fn check(config: &Config) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+    let mut range = |key: String, value: f32, min: f32, max: f32| {
+        if !(min..=max).contains(&value) {
+            let message = if max == f32::INFINITY {
+                format!("`{}` is {}, must be at least {}", key, value, min)
+            } else {
+                format!("`{}` is {}, must be between {} and {}", key, value, min, max)
+            };
+            problems.push((key, message));
+        }
+    };
+
+    range("skin.fps".into(), config.skin.fps, 0.1, f32::INFINITY);
+    if let Some(opacity) = config.window.opacity_focused {
+        range("window.opacity_focused".into(), opacity, 0.0, 1.0);
+    }
+    if let Some(opacity) = config.window.opacity_unfocused {
+        range("window.opacity_unfocused".into(), opacity, 0.0, 1.0);
+    }
+    if let Some(seconds) = config.window.transition_duration {
+        range("window.transition_duration".into(), seconds, 0.0, MAX_TRANSITION_DURATION);
+    }
+    range("callout.max_width".into(), config.callout.max_width, 1.0, f32::INFINITY);
+    range("callout.font_size".into(), config.callout.font_size, 1.0, f32::INFINITY);
+    range("callout.duration".into(), config.callout.duration, 0.0, f32::INFINITY);
+    range("callout.style.padding".into(), config.callout.style.padding, 0.0, f32::INFINITY);
+    for (i, value) in config.chat.size.iter().enumerate() {
+        range(format!("chat.size.{}", i), *value as f32, 1.0, f32::INFINITY);
+    }
+    for (i, button) in config.buttons.iter().enumerate() {
+        for (j, value) in button.size.iter().enumerate() {
+            range(format!("buttons.{}.size.{}", i, j), *value, 1.0, f32::INFINITY);
+        }
+    }
+    for (i, layer) in config.layers.iter().enumerate() {
+        range(format!("layers.{}.font_size", i), layer.font_size, 1.0, f32::INFINITY);
+        for (j, value) in layer.size.iter().flatten().enumerate() {
+            range(format!("layers.{}.size.{}", i, j), *value, 1.0, f32::INFINITY);
+        }
+    }
+    for (i, behavior) in config.behavior.iter().enumerate() {
+        if let Some(opacity) = behavior.opacity {
+            range(format!("behavior.{}.opacity", i), opacity, 0.0, 1.0);
+        }
+    }
+
+    let mut missing = |key: String, path: &str| {
+        if !Path::new(path).exists() {
+            problems.push((key, format!("`{}` not found", path)));
+        }
+    };
+    missing("skin.path".into(), &config.skin.path);
+    if let Some(ref dir) = config.skin.persona_dir {
+        missing("skin.persona_dir".into(), dir);
+    }
+    for (i, layer) in config.layers.iter().enumerate() {
+        missing(format!("layers.{}.path", i), &layer.path);
+    }
+    let skins = [("chat.skin.path", &config.chat.skin), ("callout.style.skin.path", &config.callout.style.skin)];
+    for (key, skin) in skins {
+        if let Some(NineSliceConfig { ref path, .. }) = skin {
+            missing(key.into(), path);
+        }
+    }
+    problems
+}
+
+/// Keys and array indices of an ignored value
+fn segments(path: &serde_ignored::Path) -> Vec<String> {
+    use serde_ignored::Path as P;
+    match path {
+        P::Root => Vec::new(),
+        P::Seq { parent, index } => {
+            let mut segments = segments(parent);
+            segments.push(index.to_string());
+            segments
+        }
+        P::Map { parent, key } => {
+            let mut segments = segments(parent);
+            segments.push(key.clone());
+            segments
+        }
+        P::Some { parent } | P::NewtypeStruct { parent } | P::NewtypeVariant { parent } => segments(parent),
+    }
+}
+
+/// Line (1-based) of the key at `path`, or of the closest enclosing key or
+/// table if it sits inside an inline table or array
+fn line_of(content: &str, path: &[String]) -> Option<usize> {
+    // How many entries each array of tables has so far
+    let mut entries: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut table: Vec<String> = Vec::new();
+    let mut best: Option<(usize, usize)> = None;
+    let mut consider = |key: &[String], line: usize| {
+        if path.starts_with(key) && best.is_none_or(|(len, _)| key.len() > len) {
+            best = Some((key.len(), line));
+        }
+    };
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix("[[").and_then(|l| l.split("]]").next()) {
+            let mut header = split_key(header);
+            let name = header.pop().unwrap_or_default();
+            table = resolve(&entries, header);
+            table.push(name);
+            let count = entries.entry(table.clone()).or_insert(0);
+            table.push(count.to_string());
+            *count += 1;
+            consider(&table[..table.len() - 1], i + 1);
+            consider(&table, i + 1);
+        } else if let Some(header) = line.strip_prefix('[').and_then(|l| l.split(']').next()) {
+            table = resolve(&entries, split_key(header));
+            consider(&table, i + 1);
+        } else if let Some((key, _)) = line.split_once('=').filter(|_| !line.starts_with('#')) {
+            let mut key_path = table.clone();
+            key_path.extend(split_key(key));
+            consider(&key_path, i + 1);
+        }
+    }
+    best.map(|(_, line)| line)
+}
+
+/// Insert the current entry of each array of tables a header goes through
+fn resolve(entries: &HashMap<Vec<String>, usize>, header: Vec<String>) -> Vec<String> {
+    let mut path = Vec::new();
+    for segment in header {
+        path.push(segment);
+        if let Some(count) = entries.get(&path) {
+            path.push(count.saturating_sub(1).to_string());
+        }
+    }
+    path
+}
+
+/// Parts of a dotted key, without quotes
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\'').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+hot_reload = true
+
+[skin]
+path = "Cargo.toml"
+
+[window]
+opactiy = 0.5
+opacity_unfocused = 1.5
+
+[callout]
+anchor = "top-center"
+offset = [0.0, 0.0]
+max_width = 200.0
+style = { paddng = 4.0 }
+
+[[buttons]]
+id = "a"
+label = "A"
+position = [0.0, 0.0]
+
+[[buttons]]
+id = "b"
+label = "B"
+position = [0.0, 0.0]
+colour = "red"
+"#;
+
+    fn problem(problems: &[Problem], text: &str) -> Option<usize> {
+        problems.iter().find(|p| p.message.contains(text)).and_then(|p| p.line)
+    }
+
+    #[test]
+    fn test_unknown_keys_with_lines() {
+        let (_, problems) = parse(CONFIG).unwrap();
+        assert_eq!(problem(&problems, "`window.opactiy`"), Some(8));
+        assert_eq!(problem(&problems, "`callout.style.paddng`"), Some(15));
+        assert_eq!(problem(&problems, "`buttons.1.colour`"), Some(26));
+    }
+
+    #[test]
+    fn test_out_of_range_and_missing_assets() {
+        let (_, problems) = parse(CONFIG).unwrap();
+        assert_eq!(problem(&problems, "`window.opacity_unfocused` is 1.5"), Some(9));
+        assert!(problem(&problems, "`Cargo.toml` not found").is_none());
+
+        let missing = CONFIG.replace("Cargo.toml", "no/such/skin");
+        let (_, problems) = parse(&missing).unwrap();
+        assert_eq!(problem(&problems, "`no/such/skin` not found"), Some(5));
+    }
+
+    #[test]
+    fn test_parse_error_has_line() {
+        let broken = CONFIG.replace("max_width = 200.0", "max_width = ");
+        let Err(ConfigError::Parse(message)) = parse(&broken) else { panic!("expected a parse error") };
+        assert!(message.contains("line 14"), "{}", message);
+    }
+}
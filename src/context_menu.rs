@@ -0,0 +1,127 @@
+//! Menu opened by right-clicking the ghost
+
+use ghost_ui::ContextMenu;
+
+use crate::config::ContextMenuConfig;
+use crate::tray::{TrayCommand, TrayState};
+
+/// Item id prefix of the persona entries
+const PERSONA_PREFIX: &str = "persona:";
+/// Item id prefix of the custom actions
+const ACTION_PREFIX: &str = "action:";
+
+/// Build the menu from the configured entries
+pub fn build(config: &ContextMenuConfig, state: &TrayState) -> ContextMenu {
+    let mut menu = ContextMenu::new();
+    for entry in &config.items {
+        menu = match entry.as_str() {
+            "chat" => menu.item("chat", "Open Chat"),
+            "personas" if state.personas.is_empty() => menu,
+            "personas" => {
+                let personas = state.personas.iter().fold(ContextMenu::new(), |personas, name| {
+                    let active = state.active_persona.as_ref() == Some(name);
+                    personas.check_item(format!("{}{}", PERSONA_PREFIX, name), name, active)
+                });
+                menu.submenu("Switch Persona", personas)
+            }
+            "explain_clipboard" => menu.item("explain_clipboard", "Explain Clipboard"),
+            "copy_reply" if state.has_reply => menu.item("copy_reply", "Copy Last Reply"),
+            "copy_reply" => menu.disabled_item("copy_reply", "Copy Last Reply"),
+            "look_at" => menu.check_item("look_at", "Follow Cursor", state.look_at),
+            "pomodoro" => menu.check_item("pomodoro", "Pomodoro", state.pomodoro_running),
+            "hide" => menu.item("hide", "Hide"),
+            "autostart" => menu.check_item("autostart", "Start at Login", state.autostart),
+            "quit" => menu.item("quit", "Quit"),
+            "separator" => menu.separator(),
+            id => match config.actions.iter().find(|action| action.id == id) {
+                Some(action) => menu.item(format!("{}{}", ACTION_PREFIX, id), &action.label),
+                None => {
+                    log::warn!("Unknown context menu entry '{}'", id);
+                    menu
+                }
+            },
+        };
+    }
+    menu
+}
+
+/// What choosing the item with `id` does
+pub fn command(id: &str) -> Option<TrayCommand> {
+    if let Some(name) = id.strip_prefix(PERSONA_PREFIX) {
+        return Some(TrayCommand::SetPersona(name.to_string()));
+    }
+    if let Some(action) = id.strip_prefix(ACTION_PREFIX) {
+        return Some(TrayCommand::RunAction(action.to_string()));
+    }
+    match id {
+        "chat" => Some(TrayCommand::OpenChat),
+        "explain_clipboard" => Some(TrayCommand::ExplainClipboard),
+        "copy_reply" => Some(TrayCommand::CopyReply),
+        "look_at" => Some(TrayCommand::ToggleLookAt),
+        "pomodoro" => Some(TrayCommand::TogglePomodoro),
+        "hide" => Some(TrayCommand::SetVisible(false)),
+        "autostart" => Some(TrayCommand::ToggleAutostart),
+        "quit" => Some(TrayCommand::Quit),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ContextMenuAction;
+    use ghost_ui::ContextMenuItem;
+
+    fn config(items: &[&str]) -> ContextMenuConfig {
+        ContextMenuConfig {
+            enabled: true,
+            items: items.iter().map(|s| s.to_string()).collect(),
+            actions: vec![ContextMenuAction { id: "greet".to_string(), label: "Say hello".to_string() }],
+        }
+    }
+
+    fn ids(menu: &ContextMenu) -> Vec<String> {
+        menu.items()
+            .iter()
+            .map(|item| match item {
+                ContextMenuItem::Action { id, .. } | ContextMenuItem::Check { id, .. } => id.clone(),
+                ContextMenuItem::Separator => "-".to_string(),
+                ContextMenuItem::Submenu { label, .. } => label.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build_entries() {
+        let state = TrayState {
+            personas: vec!["cat".to_string(), "ghost".to_string()],
+            active_persona: Some("ghost".to_string()),
+            ..Default::default()
+        };
+        let menu = build(&config(&["chat", "personas", "separator", "greet", "bogus", "quit"]), &state);
+        assert_eq!(ids(&menu), ["chat", "Switch Persona", "-", "action:greet", "quit"]);
+
+        let Some(ContextMenuItem::Submenu { menu: personas, .. }) = menu.items().get(1) else {
+            panic!("no persona submenu");
+        };
+        assert_eq!(
+            personas.items()[1],
+            ContextMenuItem::Check { id: "persona:ghost".to_string(), label: "ghost".to_string(), checked: true }
+        );
+    }
+
+    #[test]
+    fn test_build_skips_empty_personas() {
+        let menu = build(&config(&["personas", "hide"]), &TrayState::default());
+        assert_eq!(ids(&menu), ["hide"]);
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(command("persona:cat"), Some(TrayCommand::SetPersona("cat".to_string())));
+        assert_eq!(command("action:greet"), Some(TrayCommand::RunAction("greet".to_string())));
+        assert_eq!(command("hide"), Some(TrayCommand::SetVisible(false)));
+        assert_eq!(command("copy_reply"), Some(TrayCommand::CopyReply));
+        assert_eq!(command("unknown"), None);
+    }
+}
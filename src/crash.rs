@@ -0,0 +1,107 @@
+//! Crash recovery - a panic hook that saves a snapshot of the session and
+//! can relaunch the ghost
+//!
+//! The app refreshes the snapshot periodically; after a crash the next start
+//! picks it up with `take_recovered` and restores what it can.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::storage;
+
+/// File the snapshot is written to on a crash
+const CRASH_FILE: &str = "crash.json";
+
+/// Environment variable counting relaunches in a row
+const RELAUNCH_ENV: &str = "GHOST_RELAUNCHES";
+
+/// Give up relaunching after this many crashes in a row
+const MAX_RELAUNCHES: u32 = 3;
+
+/// A ghost that ran this long before crashing starts a fresh relaunch count
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Latest session state, written out by the panic hook
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+/// Session state worth keeping across a crash
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Main window position
+    pub position: Option<(i32, i32)>,
+    /// Active persona folder
+    pub persona: Option<String>,
+    /// Recent chat messages, newest first
+    pub recent_chats: Vec<String>,
+    /// Callouts and replies the user hadn't seen yet
+    pub unread: Vec<String>,
+}
+
+/// Install the panic hook, which saves the snapshot when the main thread
+/// panics. With `relaunch`, such a crash starts a new ghost (up to a few
+/// times in a row).
+pub fn install(relaunch: bool) {
+    let started = Instant::now();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        // Background threads panicking (e.g. a skin loader) don't take the
+        // ghost down, so there is nothing to recover from
+        if std::thread::current().name() != Some("main") {
+            return;
+        }
+        let snapshot = SNAPSHOT.lock().ok().and_then(|s| s.clone());
+        if let Some(ref snapshot) = snapshot {
+            storage::save_json(CRASH_FILE, snapshot);
+        }
+        if relaunch {
+            relaunch_self(started.elapsed() >= STABLE_AFTER);
+        }
+    }));
+}
+
+/// Replace the snapshot saved on a crash
+pub fn update(snapshot: Snapshot) {
+    if let Ok(mut current) = SNAPSHOT.lock() {
+        *current = Some(snapshot);
+    }
+}
+
+/// Whether this process was started by the crash handler
+pub fn is_relaunch() -> bool {
+    std::env::var_os(RELAUNCH_ENV).is_some()
+}
+
+/// Snapshot left behind by a crash, removed once read
+pub fn take_recovered() -> Option<Snapshot> {
+    let snapshot = storage::load_json(CRASH_FILE)?;
+    if let Err(e) = std::fs::remove_file(storage::data_path(CRASH_FILE)) {
+        log::warn!("Failed to remove crash snapshot: {}", e);
+    }
+    Some(snapshot)
+}
+
+fn relaunch_self(was_stable: bool) {
+    let relaunches = std::env::var(RELAUNCH_ENV)
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|_| !was_stable)
+        .unwrap_or(0);
+    if relaunches >= MAX_RELAUNCHES {
+        log::error!("Crashed {} times in a row, not relaunching", relaunches + 1);
+        return;
+    }
+
+    let result = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .env(RELAUNCH_ENV, (relaunches + 1).to_string())
+            .spawn()
+    });
+    match result {
+        Ok(_) => log::info!("Relaunched after crash"),
+        Err(e) => log::error!("Failed to relaunch: {}", e),
+    }
+}
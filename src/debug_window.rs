@@ -0,0 +1,369 @@
+//! Debug console window - recent log lines, live app state, the callout
+//! queue and test triggers
+//!
+//! Log lines are captured by a wrapper around env_logger (see `init_logging`)
+//! once the console is enabled. The app pushes a `DebugInfo` snapshot now and
+//! then, the callout window its queue; buttons in the window send
+//! `DebugAction`s back.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+use ghost_ui::ExtraWindow;
+use tao::event::WindowEvent;
+use tao::event_loop::EventLoop;
+use tao::window::WindowId;
+
+use crate::egui_window::EguiWindow;
+use crate::vars::Mood;
+
+/// Log lines kept for the console
+const MAX_LOG_LINES: usize = 500;
+
+/// Built-in animation states offered as buttons
+const TEST_STATES: [&str; 5] = ["idle", "talking", "thinking", "happy", "sad"];
+
+/// Callout kinds offered as buttons
+const TEST_CALLOUTS: [&str; 5] = ["say", "think", "scream", "whisper", "alert"];
+
+/// Recent log lines, oldest first
+static LOG_LINES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Whether log lines are kept for the console (see `enable_capture`)
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// env_logger that also keeps recent lines for the console once capturing.
+///
+/// Ghost's own info messages are captured even when RUST_LOG hides them.
+/// Until then it is plain env_logger: the logger has to be installed before
+/// ui.toml says whether the console is enabled.
+struct CaptureLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata) || is_captured(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.inner.log(record);
+        if (self.inner.matches(record) || is_captured(record.metadata())) && CAPTURING.load(Ordering::Relaxed) {
+            let line = format!(
+                "{} {:<5} {}: {}",
+                chrono::Local::now().format("%H:%M:%S"),
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            if let Ok(mut lines) = LOG_LINES.lock() {
+                if lines.len() >= MAX_LOG_LINES {
+                    lines.pop_front();
+                }
+                lines.push_back(line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Info and above from the ghost crates, while capturing
+fn is_captured(metadata: &log::Metadata) -> bool {
+    CAPTURING.load(Ordering::Relaxed)
+        && metadata.level() <= log::Level::Info
+        && metadata.target().starts_with("ghost")
+}
+
+/// Initialize logging (replaces `env_logger::init`)
+pub fn init_logging() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(CaptureLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Keep log lines for the console from now on (when `[debug] enabled`)
+pub fn enable_capture() {
+    CAPTURING.store(true, Ordering::Relaxed);
+    log::set_max_level(log::max_level().max(log::LevelFilter::Info));
+}
+
+/// Live app state shown in the console
+#[derive(Debug, Clone, Default)]
+pub struct DebugInfo {
+    /// Current animation state
+    pub state: String,
+    pub mood: Mood,
+    /// Template variables, sorted by name
+    pub vars: Vec<(String, String)>,
+}
+
+/// Commands to control the debug window
+#[derive(Debug)]
+pub enum DebugWindowCommand {
+    Show,
+    Update(DebugInfo),
+    /// Callouts in the callout window: the one showing, then those held
+    /// back, as "kind: text"
+    CalloutQueue(Vec<String>),
+}
+
+/// Test triggers sent from the debug window to the app
+#[derive(Debug, Clone)]
+pub enum DebugAction {
+    /// Show a test callout of the given kind ("say", "think", "scream", "whisper", "alert")
+    Callout { kind: String, text: String },
+    /// Switch the animation state
+    SetState(String),
+}
+
+/// Channel for sending commands to the debug window
+pub type DebugSender = Sender<DebugWindowCommand>;
+
+/// Debug console state and rendering
+pub struct DebugWindow {
+    egui: EguiWindow,
+    receiver: Receiver<DebugWindowCommand>,
+    actions: Sender<DebugAction>,
+    info: DebugInfo,
+    callout_queue: Vec<String>,
+    callout_text: String,
+    state_text: String,
+    visible: bool,
+    needs_repaint: bool,
+}
+
+impl DebugWindow {
+    /// Create the debug window (starts hidden) with its command channel and
+    /// the receiver for its test triggers
+    pub fn new(event_loop: &EventLoop<()>) -> (Self, DebugSender, Receiver<DebugAction>) {
+        let (sender, receiver) = channel();
+        let (actions, action_receiver) = channel();
+        let window = Self {
+            egui: EguiWindow::new(event_loop, "Ghost Debug", [520, 600], [360, 300], true),
+            receiver,
+            actions,
+            info: DebugInfo::default(),
+            callout_queue: Vec::new(),
+            callout_text: "Testing, testing!".to_string(),
+            state_text: String::new(),
+            visible: false,
+            needs_repaint: true,
+        };
+        (window, sender, action_receiver)
+    }
+
+    /// Show the window
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.egui.window().set_visible(true);
+        self.egui.window().set_focus();
+        self.needs_repaint = true;
+    }
+
+    /// Hide the window
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.egui.window().set_visible(false);
+    }
+
+    /// Process incoming commands
+    pub fn process_commands(&mut self) {
+        while let Ok(cmd) = self.receiver.try_recv() {
+            match cmd {
+                DebugWindowCommand::Show => self.show(),
+                DebugWindowCommand::Update(info) => {
+                    self.info = info;
+                    self.needs_repaint = true;
+                }
+                DebugWindowCommand::CalloutQueue(queue) => {
+                    self.callout_queue = queue;
+                    self.needs_repaint = true;
+                }
+            }
+        }
+    }
+
+    fn send(&self, action: DebugAction) {
+        if let Err(e) = self.actions.send(action) {
+            log::error!("Failed to send debug action: {}", e);
+        }
+    }
+
+    /// Render the debug window
+    pub fn render(&mut self) {
+        if !self.visible {
+            return;
+        }
+        self.needs_repaint = false;
+
+        let info = self.info.clone();
+        let callout_queue = self.callout_queue.clone();
+        let lines: Vec<String> = LOG_LINES
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default();
+        let mut callout_text = std::mem::take(&mut self.callout_text);
+        let mut state_text = std::mem::take(&mut self.state_text);
+        let mut actions = Vec::new();
+
+        let has_shapes = self.egui.render(|ctx| {
+            egui::TopBottomPanel::top("state_panel").show(ctx, |ui| {
+                ui.add_space(4.0);
+                egui::Grid::new("state_grid").num_columns(2).show(ui, |ui| {
+                    ui.label("Animation");
+                    ui.monospace(&info.state);
+                    ui.end_row();
+                    ui.label("Mood");
+                    ui.monospace(format!(
+                        "{} (affection {:.0}, energy {:.0})",
+                        info.mood.kind().name(),
+                        info.mood.affection,
+                        info.mood.energy
+                    ));
+                    ui.end_row();
+                });
+
+                egui::CollapsingHeader::new("Variables").show(ui, |ui| {
+                    egui::Grid::new("vars_grid").num_columns(2).striped(true).show(ui, |ui| {
+                        for (name, value) in &info.vars {
+                            ui.monospace(name);
+                            ui.monospace(value);
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                egui::CollapsingHeader::new("Callout queue").default_open(true).show(ui, |ui| {
+                    if callout_queue.is_empty() {
+                        ui.weak("Empty");
+                    }
+                    for (i, callout) in callout_queue.iter().enumerate() {
+                        let place = if i == 0 { "showing" } else { "waiting" };
+                        ui.horizontal(|ui| {
+                            ui.weak(place);
+                            ui.label(callout);
+                        });
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut callout_text).desired_width(180.0));
+                    for kind in TEST_CALLOUTS {
+                        if ui.button(kind).clicked() {
+                            actions.push(DebugAction::Callout {
+                                kind: kind.to_string(),
+                                text: callout_text.clone(),
+                            });
+                        }
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for state in TEST_STATES {
+                        if ui.button(state).clicked() {
+                            actions.push(DebugAction::SetState(state.to_string()));
+                        }
+                    }
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut state_text)
+                            .hint_text("custom state")
+                            .desired_width(100.0),
+                    );
+                    let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if (enter || ui.button("Play").clicked()) && !state_text.trim().is_empty() {
+                        actions.push(DebugAction::SetState(state_text.trim().to_string()));
+                    }
+                });
+                ui.add_space(4.0);
+            });
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Log");
+                    if ui.button("Clear").clicked() {
+                        if let Ok(mut lines) = LOG_LINES.lock() {
+                            lines.clear();
+                        }
+                    }
+                });
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &lines {
+                            ui.monospace(line);
+                        }
+                    });
+            });
+        });
+
+        self.callout_text = callout_text;
+        self.state_text = state_text;
+        for action in actions {
+            self.send(action);
+        }
+        if has_shapes {
+            self.needs_repaint = true;
+        }
+    }
+}
+
+impl ExtraWindow for DebugWindow {
+    fn window_id(&self) -> WindowId {
+        self.egui.window().id()
+    }
+
+    fn handle_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::CloseRequested = event {
+            self.hide();
+        } else if self.egui.handle_event(event) {
+            self.needs_repaint = true;
+            self.request_redraw();
+        }
+    }
+
+    fn process_updates(&mut self) {
+        self.process_commands();
+        if self.needs_repaint {
+            self.request_redraw();
+        }
+    }
+
+    fn render(&mut self) {
+        DebugWindow::render(self);
+    }
+
+    fn request_redraw(&self) {
+        if self.visible {
+            self.egui.window().request_redraw();
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.egui
+            .window()
+            .set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
+    }
+
+    fn outer_size(&self) -> Option<(u32, u32)> {
+        let size = self.egui.window().outer_size();
+        Some((size.width, size.height))
+    }
+
+    fn bring_to_front(&self) {
+        if self.visible {
+            self.egui.window().set_focus();
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Discord Rich Presence - shows the persona and what the ghost is up to on
+//! the user's Discord profile
+//!
+//! The presence is built from the `{persona}` and `{activity}` variables of
+//! `GhostState`. Talking to Discord needs the `discord` feature; the IPC
+//! client lives on its own thread, so a closed or restarting Discord never
+//! stalls the ghost.
+
+use std::sync::mpsc::Sender;
+
+use crate::config::DiscordConfig;
+use crate::vars::GhostState;
+
+/// Seconds between checks for a changed presence
+const UPDATE_INTERVAL: f32 = 2.0;
+
+/// Seconds between connection attempts while Discord is not running
+#[cfg(feature = "discord")]
+const RECONNECT_INTERVAL: u64 = 30;
+
+/// What Discord shows under the application name
+#[derive(Debug, Clone, PartialEq)]
+pub struct Presence {
+    /// First line: the persona
+    pub details: String,
+    /// Second line: the current activity
+    pub state: String,
+    /// Unix time the activity started, for the elapsed timer
+    pub start: Option<i64>,
+    /// Asset key of the large image
+    pub image: Option<String>,
+}
+
+/// Publishes presence changes to the Discord client thread
+pub struct DiscordPresence {
+    sender: Sender<Presence>,
+    config: DiscordConfig,
+    /// Last sent presence
+    current: Option<Presence>,
+    /// Seconds until the next check
+    timer: f32,
+}
+
+impl DiscordPresence {
+    /// Connect to Discord in the background; None when disabled, without a
+    /// client id or built without the `discord` feature
+    pub fn start(config: &DiscordConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        if config.client_id.is_empty() {
+            log::warn!("Discord presence enabled but no client_id set");
+            return None;
+        }
+        let sender = spawn_client(config.client_id.clone())?;
+        log::info!("Discord presence started");
+        Some(Self {
+            sender,
+            config: config.clone(),
+            current: None,
+            timer: 0.0,
+        })
+    }
+
+    /// Send the presence when the persona or activity changed
+    pub fn update(&mut self, delta: f32, vars: &GhostState) {
+        self.timer -= delta;
+        if self.timer > 0.0 {
+            return;
+        }
+        self.timer = UPDATE_INTERVAL;
+
+        let now = chrono::Utc::now().timestamp();
+        let presence = next_presence(self.current.as_ref(), vars, &self.config, now);
+        if self.current.as_ref() == Some(&presence) {
+            return;
+        }
+        if let Err(e) = self.sender.send(presence.clone()) {
+            log::error!("Failed to send Discord presence: {}", e);
+        }
+        self.current = Some(presence);
+    }
+}
+
+/// Presence for the current state; the elapsed timer restarts whenever the
+/// activity changes
+fn next_presence(current: Option<&Presence>, vars: &GhostState, config: &DiscordConfig, now: i64) -> Presence {
+    let details = vars.get("persona").unwrap_or("Ghost").to_string();
+    let state = vars.get("activity").unwrap_or_default().to_string();
+    let start = match current {
+        _ if !config.show_elapsed => None,
+        Some(current) if current.state == state => current.start,
+        _ => Some(now),
+    };
+    Presence {
+        details,
+        state,
+        start,
+        image: config.image.clone(),
+    }
+}
+
+#[cfg(feature = "discord")]
+fn spawn_client(client_id: String) -> Option<Sender<Presence>> {
+    use discord_rich_presence::activity::{Activity, Assets, Timestamps};
+    use discord_rich_presence::{DiscordIpc, DiscordIpcClient};
+    use std::sync::mpsc::{channel, RecvTimeoutError};
+    use std::time::Duration;
+
+    let (sender, receiver) = channel::<Presence>();
+    std::thread::spawn(move || {
+        let mut client = DiscordIpcClient::new(&client_id);
+        let mut connected = false;
+        // Latest presence, resent after (re)connecting
+        let mut latest: Option<Presence> = None;
+        loop {
+            match receiver.recv_timeout(Duration::from_secs(RECONNECT_INTERVAL)) {
+                Ok(presence) => latest = Some(presence),
+                Err(RecvTimeoutError::Timeout) if connected => continue,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            let Some(ref presence) = latest else { continue };
+
+            if !connected {
+                if let Err(e) = client.connect() {
+                    log::debug!("Discord not available: {}", e);
+                    continue;
+                }
+                log::info!("Connected to Discord");
+                connected = true;
+            }
+
+            let mut activity = Activity::new().details(&presence.details);
+            if !presence.state.is_empty() {
+                activity = activity.state(&presence.state);
+            }
+            if let Some(start) = presence.start {
+                activity = activity.timestamps(Timestamps::new().start(start));
+            }
+            if let Some(ref image) = presence.image {
+                activity = activity.assets(Assets::new().large_image(image).large_text(&presence.details));
+            }
+            if let Err(e) = client.set_activity(activity) {
+                log::warn!("Discord presence update failed: {}", e);
+                let _ = client.close();
+                connected = false;
+            }
+        }
+        if connected {
+            let _ = client.close();
+        }
+    });
+    Some(sender)
+}
+
+#[cfg(not(feature = "discord"))]
+fn spawn_client(_client_id: String) -> Option<Sender<Presence>> {
+    log::warn!("Discord presence enabled but ghost was built without the `discord` feature");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(show_elapsed: bool) -> DiscordConfig {
+        DiscordConfig {
+            enabled: true,
+            client_id: "1".to_string(),
+            show_elapsed,
+            image: None,
+        }
+    }
+
+    #[test]
+    fn test_elapsed_restarts_on_activity_change() {
+        let mut vars = GhostState::new();
+        vars.set("persona", "rin");
+        vars.set("activity", "In a pomodoro");
+
+        let first = next_presence(None, &vars, &config(true), 100);
+        assert_eq!(first.details, "rin");
+        assert_eq!(first.start, Some(100));
+
+        // Same activity keeps counting
+        let same = next_presence(Some(&first), &vars, &config(true), 160);
+        assert_eq!(same.start, Some(100));
+
+        vars.set("activity", "On a break");
+        let changed = next_presence(Some(&same), &vars, &config(true), 200);
+        assert_eq!(changed.start, Some(200));
+    }
+
+    #[test]
+    fn test_no_elapsed() {
+        let vars = GhostState::new();
+        let presence = next_presence(None, &vars, &config(false), 100);
+        assert_eq!(presence.details, "Ghost");
+        assert_eq!(presence.start, None);
+    }
+}
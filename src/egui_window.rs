@@ -0,0 +1,357 @@
+//! Shared egui/wgpu plumbing for tool windows (chat, debug console)
+//!
+//! Owns a tao window with its own wgpu device and egui context, converts
+//! window events into egui input and renders an egui frame on request.
+
+use std::time::Instant;
+
+use egui_wgpu::ScreenDescriptor;
+use tao::dpi::LogicalSize;
+use tao::event::WindowEvent;
+use tao::event_loop::EventLoop;
+use tao::window::{Window, WindowBuilder};
+use wgpu::{Device, Queue, Surface, SurfaceConfiguration};
+
+/// A tao window rendered with egui
+pub struct EguiWindow {
+    window: Window,
+    surface: Surface<'static>,
+    device: Device,
+    queue: Queue,
+    config: SurfaceConfiguration,
+    egui_ctx: egui::Context,
+    egui_renderer: egui_wgpu::Renderer,
+    start_time: Instant,
+    label: &'static str,
+}
+
+impl EguiWindow {
+    /// Create the window (hidden) and its GPU resources
+    pub fn new(
+        event_loop: &EventLoop<()>,
+        title: &'static str,
+        size: [u32; 2],
+        min_size: [u32; 2],
+        decorations: bool,
+    ) -> Self {
+        let window = WindowBuilder::new()
+            .with_inner_size(LogicalSize::new(size[0], size[1]))
+            .with_min_inner_size(LogicalSize::new(min_size[0], min_size[1]))
+            .with_title(title)
+            .with_visible(false)
+            .with_decorations(decorations)
+            .build(event_loop)
+            .expect("Failed to create egui window");
+
+        // Create wgpu instance and surface
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::all(),
+            ..Default::default()
+        });
+
+        // SAFETY: The window lives as long as the surface
+        let surface = unsafe {
+            let window_ptr = &window as *const Window;
+            instance
+                .create_surface(&*window_ptr)
+                .expect("Failed to create surface")
+        };
+
+        // Request adapter
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: Some(&surface),
+            force_fallback_adapter: false,
+        }))
+        .expect("Failed to find suitable adapter");
+
+        // Create device and queue
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some(title),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::default(),
+            },
+            None,
+        ))
+        .expect("Failed to create device");
+
+        // Configure surface
+        let size = window.inner_size();
+        let surface_caps = surface.get_capabilities(&adapter);
+        let format = surface_caps
+            .formats
+            .iter()
+            .find(|f| f.is_srgb())
+            .copied()
+            .unwrap_or(surface_caps.formats[0]);
+
+        let config = SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+        surface.configure(&device, &config);
+
+        // Create egui context and renderer
+        let egui_ctx = egui::Context::default();
+        let egui_renderer = egui_wgpu::Renderer::new(&device, format, None, 1);
+
+        Self {
+            window,
+            surface,
+            device,
+            queue,
+            config,
+            egui_ctx,
+            egui_renderer,
+            start_time: Instant::now(),
+            label: title,
+        }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn context(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// Feed a window event to egui; returns true if a repaint is needed
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::Resized(size) => {
+                if size.width == 0 || size.height == 0 {
+                    return false;
+                }
+                self.config.width = size.width;
+                self.config.height = size.height;
+                self.surface.configure(&self.device, &self.config);
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let pressed = event.state == tao::event::ElementState::Pressed;
+
+                // Convert tao KeyCode to egui key
+                use tao::keyboard::KeyCode;
+                let egui_key = match event.physical_key {
+                    KeyCode::Escape => Some(egui::Key::Escape),
+                    KeyCode::Tab => Some(egui::Key::Tab),
+                    KeyCode::Backspace => Some(egui::Key::Backspace),
+                    KeyCode::Enter | KeyCode::NumpadEnter => Some(egui::Key::Enter),
+                    KeyCode::Space => Some(egui::Key::Space),
+                    KeyCode::Delete => Some(egui::Key::Delete),
+                    KeyCode::ArrowDown => Some(egui::Key::ArrowDown),
+                    KeyCode::ArrowLeft => Some(egui::Key::ArrowLeft),
+                    KeyCode::ArrowRight => Some(egui::Key::ArrowRight),
+                    KeyCode::ArrowUp => Some(egui::Key::ArrowUp),
+                    KeyCode::Home => Some(egui::Key::Home),
+                    KeyCode::End => Some(egui::Key::End),
+                    KeyCode::PageUp => Some(egui::Key::PageUp),
+                    KeyCode::PageDown => Some(egui::Key::PageDown),
+                    KeyCode::KeyA => Some(egui::Key::A),
+                    KeyCode::KeyC => Some(egui::Key::C),
+                    KeyCode::KeyV => Some(egui::Key::V),
+                    KeyCode::KeyX => Some(egui::Key::X),
+                    KeyCode::KeyZ => Some(egui::Key::Z),
+                    _ => None,
+                };
+
+                self.egui_ctx.input_mut(|i| {
+                    // Send key event
+                    if let Some(key) = egui_key {
+                        i.events.push(egui::Event::Key {
+                            key,
+                            physical_key: None,
+                            pressed,
+                            repeat: event.repeat,
+                            modifiers: i.modifiers,
+                        });
+                    }
+
+                    // Send text event for printable characters (only on press)
+                    if pressed {
+                        if let Some(text) = event.text {
+                            // Don't send text for control characters
+                            if !text.chars().all(|c| c.is_control()) {
+                                i.events.push(egui::Event::Text(text.to_string()));
+                            }
+                        }
+                    }
+                });
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.egui_ctx.input_mut(|i| {
+                    i.modifiers.alt = modifiers.alt_key();
+                    i.modifiers.ctrl = modifiers.control_key();
+                    i.modifiers.shift = modifiers.shift_key();
+                    i.modifiers.mac_cmd = modifiers.super_key();
+                    i.modifiers.command = if cfg!(target_os = "macos") {
+                        modifiers.super_key()
+                    } else {
+                        modifiers.control_key()
+                    };
+                });
+                return false;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                // Convert physical pixels to logical pixels
+                let scale_factor = self.window.scale_factor() as f32;
+                let pos = egui::pos2(
+                    position.x as f32 / scale_factor,
+                    position.y as f32 / scale_factor,
+                );
+                self.egui_ctx.input_mut(|i| {
+                    i.events.push(egui::Event::PointerMoved(pos));
+                });
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let pressed = *state == tao::event::ElementState::Pressed;
+                let egui_button = match button {
+                    tao::event::MouseButton::Left => egui::PointerButton::Primary,
+                    tao::event::MouseButton::Right => egui::PointerButton::Secondary,
+                    tao::event::MouseButton::Middle => egui::PointerButton::Middle,
+                    _ => return false,
+                };
+                self.egui_ctx.input_mut(|i| {
+                    i.events.push(egui::Event::PointerButton {
+                        pos: i.pointer.latest_pos().unwrap_or_default(),
+                        button: egui_button,
+                        pressed,
+                        modifiers: i.modifiers,
+                    });
+                });
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    tao::event::MouseScrollDelta::LineDelta(x, y) => egui::vec2(*x, *y) * 24.0,
+                    tao::event::MouseScrollDelta::PixelDelta(p) => {
+                        let scale_factor = self.window.scale_factor();
+                        egui::vec2((p.x / scale_factor) as f32, (p.y / scale_factor) as f32)
+                    }
+                    _ => return false,
+                };
+                self.egui_ctx.input_mut(|i| {
+                    i.events.push(egui::Event::Scroll(delta));
+                });
+            }
+            WindowEvent::Focused(focused) => {
+                self.egui_ctx.input_mut(|i| {
+                    i.focused = *focused;
+                });
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Run one egui frame and draw it; returns true if egui produced shapes
+    /// (the caller should keep repainting, e.g. for cursor blinking)
+    pub fn render(&mut self, run_ui: impl FnOnce(&egui::Context)) -> bool {
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost) => {
+                self.surface.configure(&self.device, &self.config);
+                return false;
+            }
+            Err(wgpu::SurfaceError::OutOfMemory) => {
+                log::error!("{}: Out of memory", self.label);
+                return false;
+            }
+            Err(e) => {
+                log::error!("{} surface error: {:?}", self.label, e);
+                return false;
+            }
+        };
+
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Begin egui frame with time info for cursor blinking
+        // IMPORTANT: screen_rect must be in LOGICAL pixels (physical / scale_factor)
+        let scale_factor = self.window.scale_factor() as f32;
+        let logical_width = self.config.width as f32 / scale_factor;
+        let logical_height = self.config.height as f32 / scale_factor;
+
+        // Set pixels_per_point on the context
+        self.egui_ctx.set_pixels_per_point(scale_factor);
+
+        let raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(logical_width, logical_height),
+            )),
+            time: Some(self.start_time.elapsed().as_secs_f64()),
+            predicted_dt: 1.0 / 60.0,
+            ..Default::default()
+        };
+
+        let full_output = self.egui_ctx.run(raw_input, run_ui);
+        let has_shapes = !full_output.shapes.is_empty();
+
+        // Process egui output
+        let clipped_primitives = self.egui_ctx.tessellate(full_output.shapes, full_output.pixels_per_point);
+
+        // Update textures
+        for (id, delta) in &full_output.textures_delta.set {
+            self.egui_renderer.update_texture(&self.device, &self.queue, *id, delta);
+        }
+
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [self.config.width, self.config.height],
+            pixels_per_point: scale_factor,
+        };
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some(self.label),
+        });
+
+        self.egui_renderer.update_buffers(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &clipped_primitives,
+            &screen_descriptor,
+        );
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(self.label),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.1,
+                            b: 0.1,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            self.egui_renderer.render(&mut render_pass, &clipped_primitives, &screen_descriptor);
+        }
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        output.present();
+
+        // Free textures
+        for id in &full_output.textures_delta.free {
+            self.egui_renderer.free_texture(id);
+        }
+
+        has_shapes
+    }
+}
@@ -0,0 +1,98 @@
+//! Actions bound to global shortcuts (see [hotkeys] in ui.toml)
+
+use ghost_ui::Hotkey;
+
+use crate::config::HotkeysConfig;
+
+/// What pressing a shortcut does
+#[derive(Debug, Clone, PartialEq)]
+pub enum HotkeyAction {
+    /// Show or hide the chat window
+    ToggleChat,
+    /// Show or hide the ghost
+    ToggleVisibility,
+    /// Switch to the persona after the active one
+    NextPersona,
+    /// Switch to the named persona
+    Persona(String),
+    /// Show a callout with the text
+    Say(String),
+}
+
+impl HotkeyAction {
+    /// Parse an action like `"toggle_chat"` or `"persona:cat"`
+    pub fn parse(action: &str) -> Result<Self, String> {
+        if let Some(name) = action.strip_prefix("persona:") {
+            return Ok(Self::Persona(name.trim().to_string()));
+        }
+        if let Some(text) = action.strip_prefix("say:") {
+            return Ok(Self::Say(text.trim().to_string()));
+        }
+        match action {
+            "toggle_chat" => Ok(Self::ToggleChat),
+            "toggle_visibility" => Ok(Self::ToggleVisibility),
+            "next_persona" => Ok(Self::NextPersona),
+            _ => Err(format!("unknown action '{}'", action)),
+        }
+    }
+}
+
+/// Register the configured shortcuts; each is reported with its action as id
+pub fn register(config: &HotkeysConfig) {
+    let mut bindings = Vec::new();
+    for binding in &config.bindings {
+        if let Err(e) = HotkeyAction::parse(&binding.action) {
+            log::warn!("Ignoring hotkey '{}': {}", binding.keys, e);
+            continue;
+        }
+        match binding.keys.parse::<Hotkey>() {
+            Ok(hotkey) => bindings.push((binding.action.clone(), hotkey)),
+            Err(e) => log::warn!("Ignoring hotkey '{}': {}", binding.keys, e),
+        }
+    }
+    if bindings.is_empty() {
+        return;
+    }
+    if let Err(e) = ghost_ui::register_hotkeys(bindings) {
+        log::warn!("Failed to register hotkeys: {}", e);
+    }
+}
+
+/// The persona after `active`, wrapping around
+pub fn next_persona<'a>(personas: &'a [String], active: &str) -> Option<&'a String> {
+    let index = personas.iter().position(|p| p == active).map_or(0, |i| i + 1);
+    personas.get(index % personas.len().max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(HotkeyAction::parse("toggle_chat"), Ok(HotkeyAction::ToggleChat));
+        assert_eq!(HotkeyAction::parse("persona: cat"), Ok(HotkeyAction::Persona("cat".to_string())));
+        assert_eq!(HotkeyAction::parse("say:Boo!"), Ok(HotkeyAction::Say("Boo!".to_string())));
+        assert!(HotkeyAction::parse("dance").is_err());
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        let hotkey: Hotkey = "ctrl+Shift+g".parse().unwrap();
+        assert!(hotkey.ctrl && hotkey.shift && !hotkey.alt);
+        assert_eq!(hotkey.key, ghost_ui::HotkeyKey::Letter('G'));
+        assert_eq!("F12".parse::<Hotkey>().map(|h| h.key), Ok(ghost_ui::HotkeyKey::Function(12)));
+        assert!("Shift+G".parse::<Hotkey>().is_err());
+        assert!("Ctrl+Shift".parse::<Hotkey>().is_err());
+        assert!("Ctrl+Foo".parse::<Hotkey>().is_err());
+    }
+
+    #[test]
+    fn test_next_persona() {
+        let personas = vec!["cat".to_string(), "ghost".to_string()];
+        assert_eq!(next_persona(&personas, "cat").map(String::as_str), Some("ghost"));
+        assert_eq!(next_persona(&personas, "ghost").map(String::as_str), Some("cat"));
+        assert_eq!(next_persona(&personas, "gone").map(String::as_str), Some("cat"));
+        assert_eq!(next_persona(&[], "cat"), None);
+    }
+}
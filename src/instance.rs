@@ -0,0 +1,130 @@
+//! Single-instance lock - a lock file in the user's data directory that only
+//! one ghost can hold
+//!
+//! A second ghost of the same user finds the file locked, asks the running
+//! one to come to the front, and exits. The running ghost listens for that
+//! on a localhost port it writes next to the lock. The lock is released by
+//! the OS even after a crash.
+
+use std::fs::{File, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::storage;
+
+/// Message asking the running instance to show itself
+const FOCUS_MESSAGE: &str = "focus";
+
+/// Lock file held by the running ghost
+const LOCK_FILE: &str = "instance.lock";
+
+/// Port the running ghost takes focus requests on
+const PORT_FILE: &str = "instance.port";
+
+/// Try to become the user's only running ghost.
+///
+/// Returns a receiver that gets a message whenever another instance starts,
+/// or None if a ghost is already running (it has been asked to focus).
+/// `wait` keeps retrying the lock, e.g. while a crashed ghost is exiting.
+pub fn acquire(wait: Duration) -> Option<Receiver<()>> {
+    acquire_in(&storage::data_dir(), wait)
+}
+
+fn acquire_in(dir: &Path, wait: Duration) -> Option<Receiver<()>> {
+    let path = dir.join(LOCK_FILE);
+    let lock = std::fs::create_dir_all(dir)
+        .and_then(|()| File::options().create(true).truncate(false).write(true).open(&path));
+    let lock = match lock {
+        Ok(lock) => lock,
+        Err(e) => {
+            log::warn!("Cannot open the instance lock {}: {}", path.display(), e);
+            return Some(mpsc::channel().1);
+        }
+    };
+
+    let deadline = Instant::now() + wait;
+    loop {
+        match lock.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(200));
+            }
+            Err(TryLockError::WouldBlock) => {
+                log::info!("{} is locked, focusing the running ghost", path.display());
+                notify_running(dir);
+                return None;
+            }
+            Err(TryLockError::Error(e)) => {
+                log::warn!("Cannot lock {}: {}", path.display(), e);
+                return Some(mpsc::channel().1);
+            }
+        }
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).and_then(|listener| {
+        let port = listener.local_addr()?.port();
+        std::fs::write(dir.join(PORT_FILE), port.to_string())?;
+        Ok(listener)
+    });
+    let (sender, receiver) = mpsc::channel();
+    match listener {
+        Ok(listener) => {
+            std::thread::spawn(move || {
+                // Held for as long as the ghost runs
+                let _lock = lock;
+                for stream in listener.incoming().flatten() {
+                    let mut line = String::new();
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+                    if BufReader::new(stream).read_line(&mut line).is_ok() && line.trim() == FOCUS_MESSAGE {
+                        log::info!("Another ghost was started, focusing");
+                        if sender.send(()).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+        Err(e) => {
+            log::warn!("Cannot listen for focus requests: {}", e);
+            std::mem::forget(lock);
+        }
+    }
+
+    Some(receiver)
+}
+
+/// Ask the running instance to come to the front
+fn notify_running(dir: &Path) {
+    let port = std::fs::read_to_string(dir.join(PORT_FILE))
+        .ok()
+        .and_then(|port| port.trim().parse::<u16>().ok());
+    let Some(port) = port else {
+        log::warn!("Could not find the running ghost's port");
+        return;
+    };
+    let result = TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+        .and_then(|mut stream| writeln!(stream, "{}", FOCUS_MESSAGE));
+    if let Err(e) = result {
+        log::warn!("Could not reach the running ghost: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_instance_focuses_the_first() {
+        let dir = std::env::temp_dir().join(format!("ghost-instance-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let first = acquire_in(&dir, Duration::ZERO).expect("first ghost gets the lock");
+        assert!(acquire_in(&dir, Duration::ZERO).is_none());
+        assert_eq!(first.recv_timeout(Duration::from_secs(5)), Ok(()));
+        // The lock is held, not just a port: another user's ghost uses its own data directory
+        assert!(acquire_in(&dir.join("other-user"), Duration::ZERO).is_some());
+    }
+}
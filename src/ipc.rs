@@ -0,0 +1,78 @@
+//! Local IPC - line-based command server on localhost
+//!
+//! Each line received is handled like chat input (e.g. `/todo add milk` or
+//! `remind me in 5 minutes to stretch`) and answered with a single reply line:
+//!
+//! ```text
+//! echo "/todo" | nc 127.0.0.1 47474
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// How long a connection waits for the app to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A command received over IPC, with a channel for the reply
+pub struct IpcRequest {
+    pub line: String,
+    pub reply: Sender<String>,
+}
+
+/// Start the IPC server on a background thread
+pub fn start_server(port: u16) -> Option<Receiver<IpcRequest>> {
+    let listener = match TcpListener::bind(("127.0.0.1", port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to start IPC server on port {}: {}", port, e);
+            return None;
+        }
+    };
+    log::info!("IPC server listening on 127.0.0.1:{}", port);
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    std::thread::spawn(move || handle_connection(stream, sender));
+                }
+                Err(e) => log::warn!("IPC connection failed: {}", e),
+            }
+        }
+    });
+
+    Some(receiver)
+}
+
+fn handle_connection(stream: TcpStream, sender: Sender<IpcRequest>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (reply_sender, reply_receiver) = mpsc::channel();
+        let request = IpcRequest {
+            line,
+            reply: reply_sender,
+        };
+        if sender.send(request).is_err() {
+            // App is shutting down
+            break;
+        }
+
+        let reply = reply_receiver
+            .recv_timeout(REPLY_TIMEOUT)
+            .unwrap_or_else(|_| "error: no reply".to_string());
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
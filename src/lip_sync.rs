@@ -0,0 +1,146 @@
+//! Lip-sync - the mouth follows the loudness of speech (see `[lip_sync]`
+//! in the persona's config.toml)
+//!
+//! Sound files report their loudness from the audio thread. Text-to-speech
+//! runs in an external engine, so its loudness is estimated from the text
+//! over the time speaking takes: vowels open the mouth, other letters half
+//! way, and spaces and punctuation close it.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::LipSyncConfig;
+
+/// Levels below this count as silence
+const SILENCE: f32 = 0.02;
+
+/// Loudness (0-1) of the sounds playing, shared with the audio thread
+#[derive(Debug, Clone, Default)]
+pub struct AudioLevel(Arc<AtomicU32>);
+
+impl AudioLevel {
+    #[cfg_attr(not(feature = "audio"), allow(dead_code))]
+    pub fn set(&self, level: f32) {
+        self.0.store(level.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Mouth level of one character of spoken text
+fn letter_level(c: char) -> f32 {
+    match c.to_ascii_lowercase() {
+        'a' | 'o' => 1.0,
+        'e' | 'i' | 'u' | 'y' => 0.8,
+        c if c.is_alphanumeric() => 0.4,
+        _ => 0.0,
+    }
+}
+
+/// Estimated loudness of speaking `text`, one level per character
+pub fn text_envelope(text: &str) -> Vec<f32> {
+    text.chars().map(letter_level).collect()
+}
+
+/// Turns speech and sound loudness into a smoothed mouth level
+pub struct LipSync {
+    config: LipSyncConfig,
+    /// Estimated loudness of the speech in progress
+    speech: Vec<f32>,
+    /// Seconds per entry of `speech`
+    step: f32,
+    /// Seconds since the speech started
+    elapsed: f32,
+    /// Mouth level shown
+    level: f32,
+}
+
+impl LipSync {
+    pub fn new(config: LipSyncConfig) -> Self {
+        Self {
+            config,
+            speech: Vec::new(),
+            step: 0.0,
+            elapsed: 0.0,
+            level: 0.0,
+        }
+    }
+
+    /// Path of the mouth part (None = the body)
+    pub fn layer(&self) -> Option<&str> {
+        self.config.layer.as_deref()
+    }
+
+    /// State with the mouth frames
+    pub fn state(&self) -> &str {
+        &self.config.state
+    }
+
+    /// Text-to-speech started speaking `text`, taking about `duration`
+    pub fn speak(&mut self, text: &str, duration: Duration) {
+        self.speech = text_envelope(text);
+        self.step = duration.as_secs_f32() / self.speech.len().max(1) as f32;
+        self.elapsed = 0.0;
+    }
+
+    /// Whether the mouth is moving
+    pub fn is_speaking(&self) -> bool {
+        !self.speech.is_empty() || self.level > SILENCE
+    }
+
+    /// Advance by `delta` with the loudness of the sounds playing; the
+    /// mouth level to show, None while silent
+    pub fn update(&mut self, delta: f32, audio: f32) -> Option<f32> {
+        self.elapsed += delta;
+        let index = if self.step > 0.0 { (self.elapsed / self.step) as usize } else { usize::MAX };
+        let speech = self.speech.get(index).copied();
+        if speech.is_none() {
+            self.speech.clear();
+        }
+
+        let target = speech.unwrap_or(0.0).max(audio * self.config.gain).clamp(0.0, 1.0);
+        let follow = if self.config.smoothing > 0.0 { (delta / self.config.smoothing).min(1.0) } else { 1.0 };
+        self.level = self.level * (1.0 - follow) + target * follow;
+
+        let speaking = speech.is_some() || target > SILENCE || self.level > SILENCE;
+        speaking.then_some(self.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lip_sync() -> LipSync {
+        LipSync::new(LipSyncConfig { smoothing: 0.0, gain: 2.0, ..LipSyncConfig::default() })
+    }
+
+    #[test]
+    fn test_text_envelope() {
+        assert_eq!(text_envelope("Hi, ok"), [0.4, 0.8, 0.0, 0.0, 1.0, 0.4]);
+    }
+
+    #[test]
+    fn test_speech() {
+        let mut lip_sync = lip_sync();
+        assert_eq!(lip_sync.update(0.1, 0.0), None);
+        lip_sync.speak("ah", Duration::from_secs(1));
+        assert_eq!(lip_sync.update(0.1, 0.0), Some(1.0));
+        assert_eq!(lip_sync.update(0.5, 0.0), Some(0.4));
+        // Done speaking: the mouth closes
+        assert_eq!(lip_sync.update(0.5, 0.0), None);
+    }
+
+    #[test]
+    fn test_audio_level() {
+        let level = AudioLevel::default();
+        level.set(0.25);
+        let mut lip_sync = lip_sync();
+        assert_eq!(lip_sync.update(0.1, level.get()), Some(0.5));
+        level.set(0.9);
+        assert_eq!(lip_sync.update(0.1, level.get()), Some(1.0));
+    }
+}
@@ -0,0 +1,173 @@
+//! Cursor tracking - shifts an "eyes" layer toward the cursor and/or flips
+//! the skin to face it
+
+use crate::config::LookAtConfig;
+
+/// Way the eyes point, for personas with a state per direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GazeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Smoothed look-at state, fed with the cursor position every frame
+pub struct LookAt {
+    config: LookAtConfig,
+    enabled: bool,
+    /// Offset the layer is moving toward
+    target: [f32; 2],
+    /// Current (smoothed) layer offset
+    offset: [f32; 2],
+    /// Cursor is on the left side of the skin
+    cursor_left: bool,
+}
+
+impl LookAt {
+    pub fn new(config: LookAtConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            config,
+            target: [0.0, 0.0],
+            offset: [0.0, 0.0],
+            cursor_left: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn tracking on or off; turning it off recenters immediately
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.target = [0.0, 0.0];
+            self.offset = [0.0, 0.0];
+            self.cursor_left = false;
+        }
+    }
+
+    /// Id of the layer to shift
+    pub fn layer(&self) -> Option<&str> {
+        self.config.layer.as_deref()
+    }
+
+    /// Point toward `cursor`, relative to `center` (both in window pixels)
+    pub fn set_cursor(&mut self, cursor: (f32, f32), center: [f32; 2]) {
+        let dx = cursor.0 - center[0];
+        let dy = cursor.1 - center[1];
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        // Inside the dead zone: keep the last facing, look straight ahead
+        if distance <= self.config.dead_zone {
+            self.target = [0.0, 0.0];
+            return;
+        }
+
+        let scale = self.config.max_offset / distance;
+        self.target = [dx * scale, dy * scale];
+        if dx.abs() > self.config.dead_zone {
+            self.cursor_left = dx < 0.0;
+        }
+    }
+
+    /// Ease the offset toward the target; returns true if it moved visibly
+    pub fn update(&mut self, delta: f32) -> bool {
+        let t = 1.0 - (-self.config.smoothing.max(0.0) * delta).exp();
+        let before = self.offset;
+        for i in 0..2 {
+            self.offset[i] += (self.target[i] - self.offset[i]) * t;
+        }
+        (self.offset[0] - before[0]).abs() > 0.01 || (self.offset[1] - before[1]).abs() > 0.01
+    }
+
+    /// Current layer offset in pixels
+    pub fn offset(&self) -> [f32; 2] {
+        self.offset
+    }
+
+    /// Change the largest layer shift (a persona's own eyes may need more
+    /// or less room)
+    pub fn set_max_offset(&mut self, max_offset: f32) {
+        self.config.max_offset = max_offset.max(0.0);
+    }
+
+    /// Where the eyes point once the offset reaches half its maximum,
+    /// along the axis it leans on most
+    pub fn direction(&self) -> Option<GazeDirection> {
+        let [x, y] = self.offset;
+        if self.config.max_offset <= 0.0 || x.hypot(y) < self.config.max_offset / 2.0 {
+            return None;
+        }
+        let direction = if x.abs() >= y.abs() {
+            if x < 0.0 { GazeDirection::Left } else { GazeDirection::Right }
+        } else if y < 0.0 {
+            GazeDirection::Up
+        } else {
+            GazeDirection::Down
+        };
+        Some(direction)
+    }
+
+    /// Whether the cursor is to the left, if the skin should turn to face it
+    pub fn facing_left(&self) -> Option<bool> {
+        (self.enabled && self.config.flip).then_some(self.cursor_left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn look_at() -> LookAt {
+        LookAt::new(LookAtConfig {
+            enabled: true,
+            flip: true,
+            max_offset: 4.0,
+            dead_zone: 10.0,
+            smoothing: 1000.0,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_offset_and_dead_zone() {
+        let mut look = look_at();
+        look.set_cursor((100.0, 50.0), [50.0, 50.0]);
+        look.update(1.0);
+        assert!((look.offset()[0] - 4.0).abs() < 0.001);
+        assert!(look.offset()[1].abs() < 0.001);
+
+        look.set_cursor((55.0, 50.0), [50.0, 50.0]);
+        look.update(1.0);
+        assert!(look.offset()[0].abs() < 0.001);
+    }
+
+    #[test]
+    fn test_direction() {
+        let mut look = look_at();
+        assert_eq!(look.direction(), None);
+        look.set_cursor((50.0, 0.0), [50.0, 50.0]);
+        look.update(1.0);
+        assert_eq!(look.direction(), Some(GazeDirection::Up));
+        look.set_cursor((0.0, 60.0), [50.0, 50.0]);
+        look.update(1.0);
+        assert_eq!(look.direction(), Some(GazeDirection::Left));
+        look.set_max_offset(0.0);
+        assert_eq!(look.direction(), None);
+    }
+
+    #[test]
+    fn test_facing_follows_cursor() {
+        let mut look = look_at();
+        look.set_cursor((0.0, 50.0), [50.0, 50.0]);
+        assert_eq!(look.facing_left(), Some(true));
+        // Dead zone keeps the last facing
+        look.set_cursor((52.0, 50.0), [50.0, 50.0]);
+        assert_eq!(look.facing_left(), Some(true));
+        look.set_cursor((100.0, 50.0), [50.0, 50.0]);
+        assert_eq!(look.facing_left(), Some(false));
+    }
+}
@@ -1,61 +1,165 @@
 //! Ghost - Desktop mascot with callout bubbles
 
+mod accessibility;
 mod actions;
 mod app;
+mod auto_life;
+mod audio;
+mod autostart;
+mod behavior;
+mod calendar;
 mod callout_app;
+mod chat_backend;
+mod chat_history;
 mod chat_window;
+mod clipboard;
+mod commands;
 mod config;
+mod config_check;
+mod context_menu;
+mod crash;
+mod debug_window;
+mod discord;
+mod egui_window;
+mod hotkeys;
+mod instance;
+mod ipc;
+mod lip_sync;
+mod look_at;
+mod media;
+mod notification;
+mod notification_bridge;
+mod persona_gallery;
+mod pomodoro;
+mod reminders;
+mod scheduler;
+mod script;
+mod session;
+mod speech;
+mod storage;
+mod sysmon;
 mod tray;
 mod ui;
+mod vars;
+mod wander;
+mod weather;
 
-use ghost_ui::{skin, AnimatedSkin, EventLoop, GhostWindowBuilder};
+use ghost_ui::{
+    skin, EventLoop, GhostWindowBuilder, LinkedWindow, MonitorSelection, MotionConfig,
+    RubGesture,
+};
+use std::time::Duration;
+
+/// How long a relaunched ghost waits for the crashed one to release the lock
+const RELAUNCH_LOCK_WAIT: Duration = Duration::from_secs(5);
 
 fn main() {
-    // Initialize logging
-    env_logger::init();
+    // Initialize logging (the debug console starts capturing once enabled)
+    debug_window::init_logging();
+
+    // Started at login (see autostart): ui.toml is in the given directory
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(dir) = args.windows(2).find(|pair| pair[0] == "--dir").map(|pair| &pair[1]) {
+        if let Err(e) = std::env::set_current_dir(dir) {
+            log::error!("Cannot change to directory '{}': {}", dir, e);
+        }
+    }
 
     // --- 1. LOAD CONFIGURATION ---
-    let config = config::Config::load_default().unwrap_or_else(|e| {
-        log::error!("Failed to load ui.toml: {}", e);
-        log::info!("Using default configuration");
-        panic!("Please create ui.toml configuration file");
+    if args.iter().any(|arg| arg == "--check-config") {
+        std::process::exit(config_check::run());
+    }
+    let mut config = config::Config::load_default().unwrap_or_else(|e| {
+        log::error!("Failed to load {}: {}", config::CONFIG_FILE, e);
+        log::error!("Create it next to the ghost (see the ui.toml in the repository), then check it with --check-config");
+        std::process::exit(1);
     });
 
+    if config.debug.enabled {
+        debug_window::enable_capture();
+    }
     log::info!("Loaded configuration from ui.toml");
     log::info!("Skin: {}", config.skin.path);
     log::info!("Animated: {}", config.skin.animated);
     log::info!("Callout anchor: {}", config.callout.anchor);
     log::info!("Buttons: {}", config.buttons.len());
+    accessibility::resolve(&mut config.accessibility);
+    log::info!("Reduced motion: {}", config.accessibility.reduced_motion());
+
+    // --- SINGLE INSTANCE AND CRASH RECOVERY ---
+    crash::install(config.instance.relaunch_on_crash);
+    let focus_receiver = if config.instance.single {
+        let wait = if crash::is_relaunch() { RELAUNCH_LOCK_WAIT } else { Duration::ZERO };
+        match instance::acquire(wait) {
+            Some(receiver) => Some(receiver),
+            None => {
+                log::info!("Ghost is already running");
+                return;
+            }
+        }
+    } else {
+        None
+    };
+
+    // --- RESTORE THE LAST SESSION ---
+    let last_session = config.instance.restore_session.then(session::load);
+    if let Some(ref last) = last_session {
+        if let Some(ref persona) = last.persona {
+            let persona_dir = config.skin.persona_dir();
+            if config.skin.animated && tray::scan_personas(&persona_dir).contains(persona) {
+                config.skin.path = persona_dir.join(persona).to_string_lossy().into_owned();
+            }
+        }
+    }
 
     // --- CREATE EVENT LOOP FIRST (required for all windows) ---
     let event_loop = EventLoop::new();
 
     // --- CREATE CHAT CHANNEL AND WINDOW ---
+    let chat_config = config::ChatConfig {
+        size: last_session.as_ref().and_then(|last| last.chat_size).unwrap_or(config.chat.size),
+        ..config.chat.clone()
+    };
     let (chat_sender, chat_receiver) = chat_window::create_chat_channel();
+    let (chat_input_sender, chat_input_receiver) = std::sync::mpsc::channel();
     let chat_win = chat_window::ChatWindow::new(
         &event_loop,
         chat_receiver,
-        None,
-        config.chat.size,
+        Some(chat_input_sender),
+        chat_config.size,
+        config.chat.skin.as_ref().and_then(config::NineSliceConfig::load),
     );
-    log::info!("Chat window created (hidden) with size {:?}", config.chat.size);
+    log::info!("Chat window created (hidden) with size {:?}", chat_config.size);
+
+    // --- CREATE DEBUG CONSOLE (if enabled) ---
+    let debug_win = config.debug.enabled.then(|| debug_window::DebugWindow::new(&event_loop));
+
+    // --- CREATE PERSONA GALLERY (animated skins only) ---
+    let gallery_win = (config.gallery.enabled && config.skin.animated).then(|| {
+        persona_gallery::PersonaGallery::new(
+            &event_loop,
+            config.skin.persona_dir(),
+            config.gallery.index_url.clone(),
+        )
+    });
 
     // --- 2. SETUP ICONS (tray + dock) ---
     // let mut app_icon = icon_bytes(include_bytes!("../assets/icon.png"));
     // if let Err(e) = app_icon.setup_all() {
     //     log::error!("Failed to setup icons: {}", e);
     // }
-    let tray_components = tray::setup_tray("assets/icon.png");
+    // The tray icon is created with the app below, so its menu reflects the app state
 
     // --- 3. LOAD SKIN FROM CONFIG ---
     // Load either animated skin (directory of frames) or static skin (single image)
     let (skin_width, skin_height, animated_skin) = if config.skin.animated {
-        // Load animated skin from directory
-        let animated = AnimatedSkin::from_directory(&config.skin.path, config.skin.fps)
-            .unwrap_or_else(|e| {
-                log::error!("Failed to load animated skin '{}': {}", config.skin.path, e);
-                panic!("Could not load animated skin");
-            });
+        // Load animated skin from a directory of states or a single GIF/APNG/WebP
+        let mut animated = config.skin.load_animated().unwrap_or_else(|e| {
+            log::error!("Failed to load animated skin '{}': {}", config.skin.path, e);
+            panic!("Could not load animated skin");
+        });
+        animated.set_reduced_motion(config.accessibility.reduced_motion());
+        animated.set_gpu_budget(config.window.gpu_budget());
         let dims = animated.dimensions().unwrap_or((200, 200));
         log::info!("Loaded animated skin: {}x{} at {}fps", dims.0, dims.1, config.skin.fps);
         (dims.0, dims.1, Some(animated))
@@ -80,14 +184,48 @@ fn main() {
     // --- 6. CREATE MAIN GHOST WINDOW ---
     let mut window_builder = GhostWindowBuilder::new()
         .with_size(skin_width, skin_height)
-        .with_always_on_top(true)
+        .with_layer(config.window.layer())
         .with_draggable(true)
         .with_click_through(false)
         .with_alpha_hit_test(true)
-        .with_opacity_focused(1.0)
-        .with_opacity_unfocused(0.7)
+        .with_opacity_focused(config.window.opacity().0)
+        .with_opacity_unfocused(config.window.opacity().1)
         .with_title("Ghost");
 
+    if let Some(ref monitor) = config.window.monitor {
+        window_builder = window_builder.with_monitor(MonitorSelection::parse(monitor));
+    }
+    if let Some([x, y]) = config.window.position {
+        window_builder = window_builder.with_position(x, y);
+    }
+    if let Some(threshold) = config.window.edge_snap {
+        window_builder = window_builder.with_edge_snap(threshold);
+    }
+    if config.window.keep_in_work_area {
+        window_builder = window_builder.with_keep_in_work_area(true);
+    }
+
+    if config.motion.enabled {
+        let motion = &config.motion;
+        window_builder = window_builder.with_motion(MotionConfig {
+            gravity: motion.gravity,
+            walk_speed: motion.walk_speed,
+            max_walk_distance: motion.max_distance,
+            min_walk_interval: motion.min_interval,
+            max_walk_interval: motion.max_interval,
+            bounce: motion.bounce,
+            bottom_margin: motion.bottom_margin as f32,
+        });
+    }
+
+    if config.petting.enabled {
+        window_builder = window_builder.with_rub_gesture(RubGesture {
+            min_reversals: config.petting.min_reversals,
+            min_stroke: config.petting.min_stroke,
+            window: config.petting.window,
+        });
+    }
+
     // Only set static skin if not using animated skin
     if !config.skin.animated {
         let skin_data = skin(&config.skin.path).unwrap();
@@ -97,11 +235,20 @@ fn main() {
     let main_window = window_builder
         .build(&event_loop)
         .expect("Failed to create main window");
+    let last_position = last_session.as_ref().and_then(|last| {
+        last.position_on(event_loop.available_monitors().map(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            (position.x, position.y, size.width, size.height)
+        }))
+    });
+    if let Some((x, y)) = last_position {
+        main_window.set_position(x, y);
+    }
 
     // --- 7. CREATE CALLOUT WINDOW ---
     let callout_window = GhostWindowBuilder::new()
         .with_size(callout_size.0, callout_size.1)
-        .with_always_on_top(true)
+        .with_layer(config.window.layer())
         .with_draggable(false) // Callout follows main window
         .with_click_through(true) // Clicks pass through
         .with_alpha_hit_test(false)
@@ -119,25 +266,60 @@ fn main() {
         callout_sender,
         animated_skin,
         chat_sender,
+        chat_input_receiver,
     );
-    main_app.set_menu_ids(tray_components.menu_ids);
-    let callout_window_app = callout_app::CalloutWindowApp::new(&config, callout_receiver);
+    let mut extras = Vec::new();
+    let mut callout_debug_sender = None;
+    if let Some((debug_win, debug_sender, debug_actions)) = debug_win {
+        callout_debug_sender = Some(debug_sender.clone());
+        main_app.set_debug_window(debug_sender, debug_actions);
+        extras.push(LinkedWindow::new(debug_win));
+    }
+    if let Some((gallery_win, gallery_sender, gallery_actions)) = gallery_win {
+        main_app.set_gallery_window(gallery_sender, gallery_actions);
+        extras.push(LinkedWindow::new(gallery_win));
+    }
+    let tray_components = tray::setup_tray("assets/icon.png", &main_app.tray_state());
+    main_app.set_tray(tray_components);
+    if let Some(focus_receiver) = focus_receiver {
+        main_app.set_focus_receiver(focus_receiver);
+    }
+    if let Some(ref last) = last_session {
+        main_app.restore_session(last);
+    }
+    if let Some(snapshot) = crash::take_recovered() {
+        log::info!("Recovering session after a crash");
+        main_app.recover(snapshot);
+    }
+    if config.hotkeys.enabled {
+        hotkeys::register(&config.hotkeys);
+    }
+    if config.ipc.enabled {
+        if let Some(ipc_receiver) = ipc::start_server(config.ipc.port) {
+            main_app.set_ipc_receiver(ipc_receiver);
+        }
+    }
+    let mut callout_window_app = callout_app::CalloutWindowApp::new(&config, callout_receiver);
+    callout_window_app.set_window_offset(callout_offset);
+    if let Some(sender) = callout_debug_sender {
+        callout_window_app.set_debug_sender(sender);
+    }
 
     log::info!("Ghost app started with linked callout window and chat");
 
     // Calculate chat window offset from config
-    let chat_offset = config.chat.calculate_offset(skin_width, skin_height);
+    let chat_offset = chat_config.calculate_offset(skin_width, skin_height);
     log::info!("Chat window offset: {:?}", chat_offset);
 
-    // Run with linked callout window and chat window
-    ghost_ui::run_with_app_callout_and_extra(
+    // Run with linked callout window, chat window, debug console and gallery
+    extras.insert(0, LinkedWindow::new(chat_win).with_offset(chat_offset));
+    ghost_ui::run_with_app_callout_and_extras(
         main_window,
         callout_window,
         callout_offset,
-        chat_offset,
         event_loop,
         main_app,
         callout_window_app,
-        Some(chat_win),
+        extras,
     );
 }
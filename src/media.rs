@@ -0,0 +1,140 @@
+//! Now-playing media - reads the current track from the platform media session
+//!
+//! - Linux: MPRIS over D-Bus
+//! - Windows: System Media Transport Controls (SMTC)
+//! - macOS: MediaRemote is a private framework, so Music and Spotify are
+//!   queried through AppleScript instead
+//!
+//! The session is polled on a background thread; track changes arrive via `poll()`.
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Currently playing track
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    pub title: String,
+    pub artist: String,
+}
+
+impl Track {
+    /// "Artist - Title", or just the title without an artist
+    pub fn display(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.artist, self.title)
+        }
+    }
+}
+
+/// Watches the media session for track changes
+pub struct MediaWatcher {
+    receiver: Receiver<Option<Track>>,
+}
+
+impl MediaWatcher {
+    /// Start polling the media session every `interval`
+    pub fn start(interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let mut last: Option<Track> = None;
+            loop {
+                let track = current_track();
+                if track != last {
+                    if sender.send(track.clone()).is_err() {
+                        break;
+                    }
+                    last = track;
+                }
+                std::thread::sleep(interval);
+            }
+        });
+
+        Self { receiver }
+    }
+
+    /// Latest track change since the last poll: `Some(None)` means playback stopped
+    pub fn poll(&self) -> Option<Option<Track>> {
+        self.receiver.try_iter().last()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn current_track() -> Option<Track> {
+    use mpris::{PlaybackStatus, PlayerFinder};
+
+    let player = PlayerFinder::new().ok()?.find_active().ok()?;
+    if player.get_playback_status().ok()? != PlaybackStatus::Playing {
+        return None;
+    }
+
+    let metadata = player.get_metadata().ok()?;
+    Some(Track {
+        title: metadata.title()?.to_string(),
+        artist: metadata.artists().map(|a| a.join(", ")).unwrap_or_default(),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn current_track() -> Option<Track> {
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus as Status,
+    };
+
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .ok()?
+        .get()
+        .ok()?;
+    let session = manager.GetCurrentSession().ok()?;
+    let status = session.GetPlaybackInfo().ok()?.PlaybackStatus().ok()?;
+    if status != Status::Playing {
+        return None;
+    }
+
+    let properties = session.TryGetMediaPropertiesAsync().ok()?.get().ok()?;
+    Some(Track {
+        title: properties.Title().ok()?.to_string_lossy(),
+        artist: properties.Artist().map(|a| a.to_string_lossy()).unwrap_or_default(),
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn current_track() -> Option<Track> {
+    ["Music", "Spotify"].into_iter().find_map(apple_script_track)
+}
+
+/// Query a player app through AppleScript (only if it's already running,
+/// so the script doesn't launch it)
+#[cfg(target_os = "macos")]
+fn apple_script_track(app: &str) -> Option<Track> {
+    let run = |script: String| -> Option<String> {
+        let output = std::process::Command::new("osascript")
+            .args(["-e", &script])
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    if run(format!("application \"{}\" is running", app))? != "true" {
+        return None;
+    }
+
+    let text = run(format!(
+        "tell application \"{}\" to if player state is playing then \
+         return (artist of current track) & tab & (name of current track)",
+        app
+    ))?;
+    let (artist, title) = text.split_once('\t')?;
+    Some(Track {
+        title: title.to_string(),
+        artist: artist.to_string(),
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn current_track() -> Option<Track> {
+    None
+}
@@ -0,0 +1,14 @@
+//! Native desktop notifications
+
+/// Show a native desktop notification
+pub fn notify_desktop(summary: &str, body: &str) {
+    let result = notify_rust::Notification::new()
+        .appname("Ghost")
+        .summary(summary)
+        .body(body)
+        .show();
+
+    if let Err(e) = result {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
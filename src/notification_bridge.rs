@@ -0,0 +1,199 @@
+//! Desktop notification bridge - repeats other apps' notifications as callouts
+//!
+//! - Linux: watches `Notify` calls on the D-Bus session bus
+//! - Windows: polls the user notification listener (needs the user's
+//!   permission, asked for once)
+//! - macOS: there is no public API for reading other apps' notifications,
+//!   so nothing is reported
+//!
+//! Notifications arrive on a background thread and are read with `poll()`.
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use crate::config::NotificationsConfig;
+
+/// App name of our own notifications, never repeated
+const OWN_APP_NAME: &str = "Ghost";
+
+/// A notification shown by another app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopNotification {
+    pub app: String,
+    pub summary: String,
+    pub body: String,
+}
+
+impl DesktopNotification {
+    /// Whether `[notifications]` lets this one through
+    pub fn accepted_by(&self, config: &NotificationsConfig) -> bool {
+        let matches = |name: &String| name.eq_ignore_ascii_case(&self.app);
+        !self.app.eq_ignore_ascii_case(OWN_APP_NAME)
+            && (config.apps.is_empty() || config.apps.iter().any(matches))
+            && !config.ignore.iter().any(matches)
+    }
+
+    /// Fill `{app}`, `{summary}` and `{body}` in a callout template
+    pub fn format(&self, template: &str) -> String {
+        template
+            .replace("{app}", &self.app)
+            .replace("{summary}", &self.summary)
+            .replace("{body}", &self.body)
+            .trim()
+            .to_string()
+    }
+}
+
+/// Listens for desktop notifications in the background
+pub struct NotificationBridge {
+    receiver: Receiver<DesktopNotification>,
+}
+
+impl NotificationBridge {
+    pub fn start(poll_interval: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || listen(sender, poll_interval));
+        Self { receiver }
+    }
+
+    /// Notifications received since the last poll
+    pub fn poll(&self) -> Vec<DesktopNotification> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn listen(sender: mpsc::Sender<DesktopNotification>, _poll_interval: Duration) {
+    use dbus::blocking::Connection;
+    use dbus::channel::MatchingReceiver;
+    use dbus::message::MatchRule;
+
+    let conn = match Connection::new_session() {
+        Ok(conn) => conn,
+        Err(e) => {
+            log::warn!("Notification bridge: no D-Bus session: {}", e);
+            return;
+        }
+    };
+    let rule = MatchRule::new_method_call()
+        .with_interface("org.freedesktop.Notifications")
+        .with_member("Notify");
+    let proxy = conn.with_proxy("org.freedesktop.DBus", "/org/freedesktop/DBus", Duration::from_secs(5));
+    let result: Result<(), dbus::Error> =
+        proxy.method_call("org.freedesktop.DBus.Monitoring", "BecomeMonitor", (vec![rule.match_str()], 0u32));
+    if let Err(e) = result {
+        log::warn!("Notification bridge: can't monitor D-Bus: {}", e);
+        return;
+    }
+    conn.start_receive(
+        rule,
+        Box::new(move |message, _| {
+            // Notify(app_name, replaces_id, app_icon, summary, body, ...)
+            match message.read5::<String, u32, String, String, String>() {
+                Ok((app, _, _, summary, body)) => sender.send(DesktopNotification { app, summary, body }).is_ok(),
+                Err(_) => true,
+            }
+        }),
+    );
+    log::info!("Listening for desktop notifications");
+    while conn.process(Duration::from_secs(1)).is_ok() {}
+}
+
+#[cfg(target_os = "windows")]
+fn listen(sender: mpsc::Sender<DesktopNotification>, poll_interval: Duration) {
+    use std::collections::HashSet;
+    use windows::UI::Notifications::Management::{UserNotificationListener, UserNotificationListenerAccessStatus};
+    use windows::UI::Notifications::NotificationKinds;
+
+    let Ok(listener) = UserNotificationListener::Current() else { return };
+    let access = listener.RequestAccessAsync().and_then(|op| op.get());
+    if access != Ok(UserNotificationListenerAccessStatus::Allowed) {
+        log::warn!("Notification bridge: access to notifications was not granted");
+        return;
+    }
+    log::info!("Listening for desktop notifications");
+
+    // Notifications already showing at start are not repeated
+    let mut seen: Option<HashSet<u32>> = None;
+    loop {
+        if let Ok(list) = listener.GetNotificationsAsync(NotificationKinds::Toast).and_then(|op| op.get()) {
+            let size = list.Size().unwrap_or(0);
+            let mut current = HashSet::new();
+            for index in 0..size {
+                let Ok(notification) = list.GetAt(index) else { continue };
+                let Ok(id) = notification.Id() else { continue };
+                current.insert(id);
+                let Some(ref seen) = seen else { continue };
+                if seen.contains(&id) {
+                    continue;
+                }
+                if let Some(notification) = windows_notification(&notification) {
+                    if sender.send(notification).is_err() {
+                        return;
+                    }
+                }
+            }
+            seen = Some(current);
+        }
+        std::thread::sleep(poll_interval);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn windows_notification(notification: &windows::UI::Notifications::UserNotification) -> Option<DesktopNotification> {
+    use windows::UI::Notifications::KnownNotificationBindings;
+
+    let app = notification.AppInfo().ok()?.DisplayInfo().ok()?.DisplayName().ok()?.to_string_lossy();
+    let binding = notification
+        .Notification()
+        .ok()?
+        .Visual()
+        .ok()?
+        .GetBinding(&KnownNotificationBindings::ToastGeneric().ok()?)
+        .ok()?;
+    let texts = binding.GetTextElements().ok()?;
+    let text = |index| texts.GetAt(index).and_then(|t| t.Text()).map(|t| t.to_string_lossy()).unwrap_or_default();
+    let body = (1..texts.Size().unwrap_or(0)).map(text).collect::<Vec<_>>().join("\n");
+    Some(DesktopNotification { app, summary: text(0), body })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn listen(_sender: mpsc::Sender<DesktopNotification>, _poll_interval: Duration) {
+    log::warn!("Notification bridge is not supported on this platform");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(app: &str) -> DesktopNotification {
+        DesktopNotification {
+            app: app.to_string(),
+            summary: "New message".to_string(),
+            body: "Hi there".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut config = NotificationsConfig::default();
+        assert!(notification("Slack").accepted_by(&config));
+        assert!(!notification("ghost").accepted_by(&config));
+
+        config.apps = vec!["slack".to_string(), "Thunderbird".to_string()];
+        assert!(notification("Slack").accepted_by(&config));
+        assert!(!notification("Firefox").accepted_by(&config));
+
+        config.apps.clear();
+        config.ignore = vec!["Firefox".to_string()];
+        assert!(!notification("firefox").accepted_by(&config));
+        assert!(notification("Slack").accepted_by(&config));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(notification("Slack").format("{app}: {summary} {body}"), "Slack: New message Hi there");
+        let empty = DesktopNotification { body: String::new(), ..notification("Slack") };
+        assert_eq!(empty.format("{summary} {body}"), "New message");
+    }
+}
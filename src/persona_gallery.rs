@@ -0,0 +1,736 @@
+//! Persona gallery window - installed personas with thumbnails, plus a
+//! remote index of downloadable ones
+//!
+//! Scanning, fetching and downloading run on worker threads; results come
+//! back as `GalleryEvent`s. Clicks and download progress go to the app as
+//! `GalleryAction`s.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use ghost_ui::ExtraWindow;
+use serde::Deserialize;
+use tao::event::WindowEvent;
+use tao::event_loop::EventLoop;
+use tao::window::WindowId;
+
+use crate::egui_window::EguiWindow;
+use crate::tray;
+
+/// Longest side of thumbnails in the gallery
+const THUMBNAIL_SIZE: u32 = 96;
+
+/// Files tried, in order, as a persona's thumbnail
+const THUMBNAIL_CANDIDATES: [&str; 3] = ["thumbnail.png", "still.png", "idle/frame_0001.png"];
+
+/// Download progress is announced in steps of this many percent
+const PROGRESS_STEP: u32 = 25;
+
+/// Largest persona download, in bytes
+const MAX_DOWNLOAD_SIZE: usize = 256 * 1024 * 1024;
+
+/// Largest thumbnail download, in bytes
+const MAX_THUMBNAIL_DOWNLOAD_SIZE: usize = 2 * 1024 * 1024;
+
+/// Widest and tallest image decoded as a thumbnail
+const MAX_THUMBNAIL_SOURCE_SIZE: u32 = 4096;
+
+/// Most memory decoding a thumbnail may take, in bytes
+const MAX_THUMBNAIL_ALLOC: u64 = 64 * 1024 * 1024;
+
+/// Most bytes a downloaded persona may unpack to
+const MAX_EXTRACTED_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Entry of the remote persona index
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemotePersona {
+    /// Folder name once installed
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// URL of the `.persona.zip`
+    pub url: String,
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Download size in bytes
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+/// Remote index file: `{ "personas": [ ... ] }`
+#[derive(Debug, Deserialize)]
+struct RemoteIndex {
+    personas: Vec<RemotePersona>,
+}
+
+/// Commands to control the gallery window
+#[derive(Debug)]
+pub enum GalleryCommand {
+    /// Show the window, marking the active persona
+    Show { active: String },
+}
+
+/// Requests from the gallery to the app
+#[derive(Debug, Clone)]
+pub enum GalleryAction {
+    /// Switch to an installed persona
+    SetPersona(String),
+    /// Announce download progress or errors in a callout
+    Callout(String),
+    /// A downloaded persona was installed
+    Installed(String),
+}
+
+/// Channel for sending commands to the gallery window
+pub type GallerySender = Sender<GalleryCommand>;
+
+/// Results from the worker threads
+enum GalleryEvent {
+    Local(Vec<(String, Option<egui::ColorImage>)>),
+    Index(Result<Vec<RemotePersona>, String>),
+    RemoteThumbnail(String, egui::ColorImage),
+    Progress(String, f32),
+    Installed(String),
+    Failed(String, String),
+}
+
+/// Persona gallery state and rendering
+pub struct PersonaGallery {
+    egui: EguiWindow,
+    receiver: Receiver<GalleryCommand>,
+    actions: Sender<GalleryAction>,
+    events: Receiver<GalleryEvent>,
+    event_sender: Sender<GalleryEvent>,
+    persona_dir: PathBuf,
+    index_url: Option<String>,
+    active: String,
+    /// Installed persona names, sorted
+    installed: Vec<String>,
+    remote: Vec<RemotePersona>,
+    /// Index is being fetched
+    loading: bool,
+    index_error: Option<String>,
+    /// Download progress (0-1) by persona name
+    downloads: HashMap<String, f32>,
+    /// Thumbnails waiting for a texture upload, by `local:`/`remote:` key
+    pending_images: Vec<(String, egui::ColorImage)>,
+    textures: HashMap<String, egui::TextureHandle>,
+    visible: bool,
+    needs_repaint: bool,
+}
+
+impl PersonaGallery {
+    /// Create the gallery window (starts hidden) with its command channel
+    /// and the receiver for its actions
+    pub fn new(
+        event_loop: &EventLoop<()>,
+        persona_dir: PathBuf,
+        index_url: Option<String>,
+    ) -> (Self, GallerySender, Receiver<GalleryAction>) {
+        let (sender, receiver) = channel();
+        let (actions, action_receiver) = channel();
+        let (event_sender, events) = channel();
+        let window = Self {
+            egui: EguiWindow::new(event_loop, "Personas", [560, 520], [360, 300], true),
+            receiver,
+            actions,
+            events,
+            event_sender,
+            persona_dir,
+            index_url,
+            active: String::new(),
+            installed: Vec::new(),
+            remote: Vec::new(),
+            loading: false,
+            index_error: None,
+            downloads: HashMap::new(),
+            pending_images: Vec::new(),
+            textures: HashMap::new(),
+            visible: false,
+            needs_repaint: true,
+        };
+        (window, sender, action_receiver)
+    }
+
+    /// Show the window and rescan installed personas
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.egui.window().set_visible(true);
+        self.egui.window().set_focus();
+        self.needs_repaint = true;
+        self.scan_local();
+        if self.remote.is_empty() && !self.loading {
+            self.fetch_index();
+        }
+    }
+
+    /// Hide the window
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.egui.window().set_visible(false);
+    }
+
+    /// Process incoming commands and worker results
+    pub fn process_commands(&mut self) {
+        while let Ok(cmd) = self.receiver.try_recv() {
+            match cmd {
+                GalleryCommand::Show { active } => {
+                    self.active = active;
+                    self.show();
+                }
+            }
+        }
+
+        while let Ok(event) = self.events.try_recv() {
+            self.needs_repaint = true;
+            match event {
+                GalleryEvent::Local(personas) => {
+                    self.installed.clear();
+                    for (name, thumbnail) in personas {
+                        if let Some(image) = thumbnail {
+                            self.pending_images.push((format!("local:{}", name), image));
+                        }
+                        self.installed.push(name);
+                    }
+                }
+                GalleryEvent::Index(result) => {
+                    self.loading = false;
+                    match result {
+                        Ok(remote) => {
+                            self.index_error = None;
+                            self.remote = remote;
+                        }
+                        Err(e) => {
+                            log::warn!("Persona index fetch failed: {}", e);
+                            self.index_error = Some(e);
+                        }
+                    }
+                }
+                GalleryEvent::RemoteThumbnail(name, image) => {
+                    self.pending_images.push((format!("remote:{}", name), image));
+                }
+                GalleryEvent::Progress(name, fraction) => {
+                    let previous = self.downloads.insert(name.clone(), fraction).unwrap_or(0.0);
+                    let step = |f: f32| (f * 100.0) as u32 / PROGRESS_STEP;
+                    if step(fraction) > step(previous) && fraction < 1.0 {
+                        self.send(GalleryAction::Callout(format!(
+                            "Downloading {}... {}%",
+                            name,
+                            step(fraction) * PROGRESS_STEP
+                        )));
+                    }
+                }
+                GalleryEvent::Installed(name) => {
+                    self.downloads.remove(&name);
+                    log::info!("Installed persona {}", name);
+                    self.send(GalleryAction::Installed(name.clone()));
+                    self.active = name;
+                    self.scan_local();
+                }
+                GalleryEvent::Failed(name, e) => {
+                    self.downloads.remove(&name);
+                    log::error!("Failed to install persona {}: {}", name, e);
+                    self.send(GalleryAction::Callout(format!("Couldn't install {}: {}", name, e)));
+                }
+            }
+        }
+    }
+
+    fn send(&self, action: GalleryAction) {
+        if let Err(e) = self.actions.send(action) {
+            log::error!("Failed to send gallery action: {}", e);
+        }
+    }
+
+    /// Rescan installed personas and their thumbnails in the background
+    fn scan_local(&self) {
+        let dir = self.persona_dir.clone();
+        let events = self.event_sender.clone();
+        std::thread::spawn(move || {
+            let personas = tray::scan_personas(&dir)
+                .into_iter()
+                .map(|name| {
+                    let thumbnail = local_thumbnail(&dir.join(&name));
+                    (name, thumbnail)
+                })
+                .collect();
+            let _ = events.send(GalleryEvent::Local(personas));
+        });
+    }
+
+    /// Fetch the remote index and its thumbnails in the background
+    fn fetch_index(&mut self) {
+        let Some(url) = self.index_url.clone() else { return };
+        self.loading = true;
+        self.index_error = None;
+        let events = self.event_sender.clone();
+        std::thread::spawn(move || {
+            let result = fetch_index(&url);
+            let thumbnails: Vec<(String, String)> = match result {
+                Ok(ref remote) => remote
+                    .iter()
+                    .filter_map(|p| p.thumbnail.clone().map(|t| (p.name.clone(), t)))
+                    .collect(),
+                Err(_) => Vec::new(),
+            };
+            let _ = events.send(GalleryEvent::Index(result));
+
+            for (name, url) in thumbnails {
+                match download(&url, MAX_THUMBNAIL_DOWNLOAD_SIZE, |_| {}).and_then(|bytes| decode_thumbnail(&bytes)) {
+                    Ok(image) => {
+                        let _ = events.send(GalleryEvent::RemoteThumbnail(name, image));
+                    }
+                    Err(e) => log::warn!("Thumbnail for {} failed: {}", name, e),
+                }
+            }
+        });
+    }
+
+    /// Download and unpack a persona in the background
+    fn install(&mut self, persona: RemotePersona) {
+        let name = safe_name(&persona.name);
+        if name.is_empty() || self.downloads.contains_key(&name) {
+            return;
+        }
+        let dest = self.persona_dir.join(&name);
+        if dest.exists() {
+            self.send(GalleryAction::Callout(format!("There already is a persona called {}.", name)));
+            return;
+        }
+        self.downloads.insert(name.clone(), 0.0);
+        self.send(GalleryAction::Callout(format!("Downloading {}...", name)));
+
+        let events = self.event_sender.clone();
+        std::thread::spawn(move || {
+            let progress = |fraction| {
+                let _ = events.send(GalleryEvent::Progress(name.clone(), fraction));
+            };
+            let result = download(&persona.url, MAX_DOWNLOAD_SIZE, progress).and_then(|bytes| extract(&bytes, &dest));
+            let _ = events.send(match result {
+                Ok(()) => GalleryEvent::Installed(name),
+                Err(e) => GalleryEvent::Failed(name, e),
+            });
+        });
+    }
+
+    /// Render the gallery window
+    pub fn render(&mut self) {
+        if !self.visible {
+            return;
+        }
+        self.needs_repaint = false;
+
+        let pending_images = std::mem::take(&mut self.pending_images);
+        let mut textures = std::mem::take(&mut self.textures);
+        let mut switch_to = None;
+        let mut install = None;
+        let mut refresh = false;
+
+        let has_shapes = self.egui.render(|ctx| {
+            for (key, image) in pending_images {
+                let texture = ctx.load_texture(&key, image, egui::TextureOptions::LINEAR);
+                textures.insert(key, texture);
+            }
+            let thumbnail = |ui: &mut egui::Ui, key: &str| -> egui::Response {
+                let size = egui::vec2(THUMBNAIL_SIZE as f32, THUMBNAIL_SIZE as f32);
+                match textures.get(key) {
+                    Some(texture) => {
+                        let image = egui::Image::new(texture).max_size(size).fit_to_exact_size(size);
+                        ui.add(egui::ImageButton::new(image))
+                    }
+                    None => ui.add_sized(size, egui::Button::new("?")),
+                }
+            };
+
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    ui.heading("Installed");
+                    if self.installed.is_empty() {
+                        ui.weak(format!("No personas in {}", self.persona_dir.display()));
+                    }
+                    ui.horizontal_wrapped(|ui| {
+                        for name in &self.installed {
+                            ui.vertical(|ui| {
+                                let response = thumbnail(ui, &format!("local:{}", name));
+                                if response.on_hover_text("Switch to this persona").clicked() {
+                                    switch_to = Some(name.clone());
+                                }
+                                if *name == self.active {
+                                    ui.strong(format!("✔ {}", name));
+                                } else {
+                                    ui.label(name);
+                                }
+                            });
+                        }
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.heading("Available");
+                        if self.index_url.is_some() && !self.loading && ui.button("Refresh").clicked() {
+                            refresh = true;
+                        }
+                        if self.loading {
+                            ui.spinner();
+                        }
+                    });
+                    if self.index_url.is_none() {
+                        ui.weak("Set gallery.index_url in ui.toml to browse downloadable personas.");
+                    }
+                    if let Some(ref e) = self.index_error {
+                        ui.colored_label(ui.visuals().error_fg_color, e);
+                    }
+
+                    for persona in &self.remote {
+                        let name = safe_name(&persona.name);
+                        ui.horizontal(|ui| {
+                            thumbnail(ui, &format!("remote:{}", persona.name));
+                            ui.vertical(|ui| {
+                                ui.strong(&persona.name);
+                                if !persona.description.is_empty() {
+                                    ui.label(&persona.description);
+                                }
+                                if let Some(size) = persona.size {
+                                    ui.weak(format!("{:.1} MB", size as f64 / 1_000_000.0));
+                                }
+                                if let Some(&fraction) = self.downloads.get(&name) {
+                                    ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                                } else if self.installed.contains(&name) {
+                                    ui.weak("Installed");
+                                } else if ui.button("Download").clicked() {
+                                    install = Some(persona.clone());
+                                }
+                            });
+                        });
+                    }
+                });
+            });
+        });
+
+        self.textures = textures;
+        if let Some(name) = switch_to {
+            self.active = name.clone();
+            self.send(GalleryAction::SetPersona(name));
+            self.needs_repaint = true;
+        }
+        if let Some(persona) = install {
+            self.install(persona);
+            self.needs_repaint = true;
+        }
+        if refresh {
+            self.fetch_index();
+        }
+        if has_shapes || !self.downloads.is_empty() || self.loading {
+            self.needs_repaint = true;
+        }
+    }
+}
+
+impl ExtraWindow for PersonaGallery {
+    fn window_id(&self) -> WindowId {
+        self.egui.window().id()
+    }
+
+    fn handle_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::CloseRequested = event {
+            self.hide();
+        } else if self.egui.handle_event(event) {
+            self.needs_repaint = true;
+            self.request_redraw();
+        }
+    }
+
+    fn process_updates(&mut self) {
+        self.process_commands();
+        if self.needs_repaint {
+            self.request_redraw();
+        }
+    }
+
+    fn render(&mut self) {
+        PersonaGallery::render(self);
+    }
+
+    fn request_redraw(&self) {
+        if self.visible {
+            self.egui.window().request_redraw();
+        }
+    }
+
+    fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    fn set_position(&self, x: i32, y: i32) {
+        self.egui
+            .window()
+            .set_outer_position(tao::dpi::PhysicalPosition::new(x, y));
+    }
+
+    fn outer_size(&self) -> Option<(u32, u32)> {
+        let size = self.egui.window().outer_size();
+        Some((size.width, size.height))
+    }
+
+    fn bring_to_front(&self) {
+        if self.visible {
+            self.egui.window().set_focus();
+        }
+    }
+}
+
+fn fetch_index(url: &str) -> Result<Vec<RemotePersona>, String> {
+    let index: RemoteIndex = ureq::get(url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+    Ok(index.personas)
+}
+
+/// GET a URL into memory, reporting progress (0-1) when the size is known;
+/// fails past `limit` bytes
+fn download(url: &str, limit: usize, mut progress: impl FnMut(f32)) -> Result<Vec<u8>, String> {
+    let too_large = || format!("larger than {} MB", limit / (1024 * 1024));
+    let response = ureq::get(url).call().map_err(|e| e.to_string())?;
+    let total: Option<usize> = response.header("Content-Length").and_then(|l| l.parse().ok());
+    if total.is_some_and(|total| total > limit) {
+        return Err(too_large());
+    }
+
+    let mut reader = response.into_reader();
+    let mut bytes = Vec::with_capacity(total.unwrap_or(0));
+    let mut chunk = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut chunk).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        if bytes.len() + read > limit {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        if let Some(total) = total {
+            progress((bytes.len() as f32 / total.max(1) as f32).min(1.0));
+        }
+    }
+    Ok(bytes)
+}
+
+/// Unpack a `.persona.zip` into `dest`, dropping a common top-level folder;
+/// never replaces an existing persona
+fn extract(bytes: &[u8], dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        return Err(format!("{} already exists", dest.display()));
+    }
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    let root = common_root(&names);
+
+    // Unpack into a hidden folder next to the destination first so a failed
+    // download leaves no half-installed persona behind
+    let name = dest.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let staging = dest.with_file_name(format!(".{}.download", name));
+    let _ = std::fs::remove_dir_all(&staging);
+    let result = unpack(&mut archive, root.as_deref(), &staging)
+        .and_then(|()| std::fs::rename(&staging, dest).map_err(|e| format!("{}: {}", dest.display(), e)));
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+/// Write the entries of `archive` under `root` (all if None) into `dir`,
+/// failing past `MAX_EXTRACTED_SIZE`
+fn unpack(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, root: Option<&str>, dir: &Path) -> Result<(), String> {
+    let mut remaining = MAX_EXTRACTED_SIZE;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let Some(path) = entry.enclosed_name().map(Path::to_path_buf) else {
+            continue;
+        };
+        let path = match root {
+            Some(root) => match path.strip_prefix(root) {
+                Ok(path) => path.to_path_buf(),
+                Err(_) => continue,
+            },
+            None => path,
+        };
+        let target = dir.join(path);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut file = std::fs::File::create(&target).map_err(|e| format!("{}: {}", target.display(), e))?;
+        let written = std::io::copy(&mut entry.by_ref().take(remaining + 1), &mut file).map_err(|e| e.to_string())?;
+        remaining = remaining
+            .checked_sub(written)
+            .ok_or_else(|| format!("unpacks to more than {} MB", MAX_EXTRACTED_SIZE / (1024 * 1024)))?;
+    }
+    Ok(())
+}
+
+/// Folder wrapping every entry of an archive, unless it holds the manifest
+/// at the top level
+fn common_root(names: &[String]) -> Option<String> {
+    if names.iter().any(|n| n == "config.toml") {
+        return None;
+    }
+    let (root, _) = names.first()?.split_once('/')?;
+    let prefix = format!("{}/", root);
+    names.iter().all(|n| n.starts_with(&prefix)).then(|| root.to_string())
+}
+
+/// Folder name from an index entry: letters, digits, `-` and `_` only
+fn safe_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+/// First thumbnail candidate, then any top-level PNG
+fn local_thumbnail(dir: &Path) -> Option<egui::ColorImage> {
+    let candidates = THUMBNAIL_CANDIDATES.iter().map(|name| dir.join(name));
+    let top_level = glob::glob(&format!("{}/*.png", dir.display()))
+        .into_iter()
+        .flatten()
+        .flatten();
+    candidates
+        .chain(top_level)
+        .filter(|path| path.is_file())
+        .find_map(|path| std::fs::read(path).ok().and_then(|bytes| decode_thumbnail(&bytes).ok()))
+}
+
+/// Decode an image shrunk to thumbnail size; images too large to be a
+/// thumbnail are refused before they are decoded
+fn decode_thumbnail(bytes: &[u8]) -> Result<egui::ColorImage, String> {
+    let mut reader = image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?;
+    let mut limits = image::io::Limits::default();
+    limits.max_image_width = Some(MAX_THUMBNAIL_SOURCE_SIZE);
+    limits.max_image_height = Some(MAX_THUMBNAIL_SOURCE_SIZE);
+    limits.max_alloc = Some(MAX_THUMBNAIL_ALLOC);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| e.to_string())?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .into_rgba8();
+    let size = [image.width() as usize, image.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_common_root() {
+        assert_eq!(
+            common_root(&names(&["rin/config.toml", "rin/idle/frame_0001.png"])),
+            Some("rin".to_string())
+        );
+        assert_eq!(common_root(&names(&["config.toml", "idle/frame_0001.png"])), None);
+        assert_eq!(common_root(&names(&["a/x.png", "b/y.png"])), None);
+    }
+
+    #[test]
+    fn test_safe_name() {
+        assert_eq!(safe_name("rin-v2"), "rin-v2");
+        assert_eq!(safe_name("../../etc"), "etc");
+        assert_eq!(safe_name("Xiao Mei"), "XiaoMei");
+    }
+
+    #[test]
+    fn test_extract_keeps_existing_persona() {
+        let dest = std::env::temp_dir().join(format!("ghost-gallery-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("config.toml"), "mine").unwrap();
+
+        assert!(extract(b"not even a zip", &dest).is_err());
+        assert_eq!(std::fs::read_to_string(dest.join("config.toml")).unwrap(), "mine");
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+
+    /// A zip of `(name, contents)` entries; contents of None make a folder
+    fn zip(entries: &[(&str, Option<&str>)]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            match contents {
+                Some(contents) => {
+                    writer.start_file(*name, Default::default()).unwrap();
+                    writer.write_all(contents.as_bytes()).unwrap();
+                }
+                None => writer.add_directory(*name, Default::default()).unwrap(),
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// A persona folder `name` in a fresh temp directory, and its staging
+    /// folder
+    fn destination(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("ghost-gallery-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        (dir.join("rin"), dir.join(".rin.download"))
+    }
+
+    #[test]
+    fn test_extract() {
+        let (dest, staging) = destination("extract");
+        let bytes = zip(&[
+            ("rin/", None),
+            ("rin/config.toml", Some("name = \"Rin\"")),
+            ("rin/idle/frame_0001.png", Some("png")),
+        ]);
+
+        extract(&bytes, &dest).unwrap();
+        assert_eq!(std::fs::read_to_string(dest.join("config.toml")).unwrap(), "name = \"Rin\"");
+        assert_eq!(std::fs::read_to_string(dest.join("idle/frame_0001.png")).unwrap(), "png");
+        assert!(!staging.exists());
+        std::fs::remove_dir_all(dest.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_failed_extract_leaves_nothing() {
+        let (dest, staging) = destination("failed");
+        // "idle" is a file, so the frame inside it can't be written
+        let bytes = zip(&[
+            ("config.toml", Some("name = \"Rin\"")),
+            ("idle", Some("")),
+            ("idle/frame_0001.png", Some("png")),
+        ]);
+
+        assert!(extract(&bytes, &dest).is_err());
+        assert!(!dest.exists());
+        assert!(!staging.exists());
+        std::fs::remove_dir_all(dest.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_decode_thumbnail_limits() {
+        let png = |width, height| {
+            let mut bytes = Vec::new();
+            image::DynamicImage::new_rgba8(width, height)
+                .write_to(&mut Cursor::new(&mut bytes), image::ImageOutputFormat::Png)
+                .unwrap();
+            bytes
+        };
+        let thumbnail = decode_thumbnail(&png(192, 96)).unwrap();
+        assert_eq!(thumbnail.size, [96, 48]);
+        assert!(decode_thumbnail(&png(MAX_THUMBNAIL_SOURCE_SIZE + 1, 1)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Pomodoro timer - alternating focus and break phases
+
+use crate::config::PomodoroConfig;
+
+/// Shortest phase in seconds, so a length of zero or less can't switch
+/// phases every frame
+const MIN_PHASE_SECONDS: f32 = 1.0;
+
+/// Current pomodoro phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Pomodoro timer state, advanced from the app update loop
+pub struct Pomodoro {
+    config: PomodoroConfig,
+    phase: Phase,
+    /// Seconds left in the current phase
+    remaining: f32,
+    /// Completed work sessions since start
+    completed: u32,
+    running: bool,
+}
+
+impl Pomodoro {
+    pub fn new(config: PomodoroConfig) -> Self {
+        let remaining = phase_seconds(config.work_minutes);
+        Self {
+            config,
+            phase: Phase::Work,
+            remaining,
+            completed: 0,
+            running: false,
+        }
+    }
+
+    /// Start a fresh cycle with a work phase
+    pub fn start(&mut self) {
+        self.running = true;
+        self.completed = 0;
+        self.enter(Phase::Work);
+    }
+
+    /// Stop the timer
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Advance the timer, returning the new phase if it changed
+    pub fn update(&mut self, delta: f32) -> Option<Phase> {
+        if !self.running {
+            return None;
+        }
+
+        self.remaining -= delta;
+        if self.remaining > 0.0 {
+            return None;
+        }
+
+        let next = match self.phase {
+            Phase::Work => {
+                self.completed += 1;
+                let every = self.config.long_break_every.max(1);
+                if self.completed.is_multiple_of(every) {
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+        self.enter(next);
+        Some(next)
+    }
+
+    fn enter(&mut self, phase: Phase) {
+        self.phase = phase;
+        let minutes = match phase {
+            Phase::Work => self.config.work_minutes,
+            Phase::ShortBreak => self.config.short_break_minutes,
+            Phase::LongBreak => self.config.long_break_minutes,
+        };
+        self.remaining = phase_seconds(minutes);
+    }
+
+    /// Remaining time as "MM:SS"
+    pub fn remaining_label(&self) -> String {
+        let secs = self.remaining.max(0.0).ceil() as u32;
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+
+    /// Callout text announcing the current phase
+    pub fn phase_text(&self) -> &str {
+        match self.phase {
+            Phase::Work => &self.config.work_text,
+            Phase::ShortBreak => &self.config.short_break_text,
+            Phase::LongBreak => &self.config.long_break_text,
+        }
+    }
+
+    /// Animation state for the current phase
+    pub fn phase_state(&self) -> &str {
+        match self.phase {
+            Phase::Work => &self.config.work_state,
+            Phase::ShortBreak | Phase::LongBreak => &self.config.break_state,
+        }
+    }
+
+    /// Layer id that shows the remaining time
+    pub fn label_layer(&self) -> Option<&str> {
+        self.config.label_layer.as_deref()
+    }
+}
+
+/// Length of a phase of `minutes` in seconds
+fn phase_seconds(minutes: f32) -> f32 {
+    (minutes * 60.0).max(MIN_PHASE_SECONDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_length_phase() {
+        let config = PomodoroConfig {
+            work_minutes: 0.0,
+            short_break_minutes: -5.0,
+            ..Default::default()
+        };
+        let mut pomodoro = Pomodoro::new(config);
+        pomodoro.start();
+        // Not a new phase every frame
+        assert_eq!(pomodoro.update(1.0 / 60.0), None);
+        assert_eq!(pomodoro.update(1.0), Some(Phase::ShortBreak));
+        assert_eq!(pomodoro.update(1.0 / 60.0), None);
+    }
+}
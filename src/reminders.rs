@@ -0,0 +1,125 @@
+//! Reminders and todo list - persisted to `reminders.json` in the data directory
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const REMINDERS_FILE: &str = "reminders.json";
+
+/// A reminder (with a due time) or a plain todo item (without one)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: u32,
+    pub text: String,
+    /// When to announce it (None = todo item)
+    pub due: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub done: bool,
+    /// Already announced
+    #[serde(default)]
+    pub notified: bool,
+}
+
+impl Reminder {
+    /// One-line description for lists
+    pub fn describe(&self) -> String {
+        let check = if self.done { "x" } else { " " };
+        match self.due {
+            Some(due) => format!("[{}] #{} {} ({})", check, self.id, self.text, due.format("%a %H:%M")),
+            None => format!("[{}] #{} {}", check, self.id, self.text),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredReminders {
+    next_id: u32,
+    items: Vec<Reminder>,
+}
+
+/// Reminder/todo store, saved after every change
+pub struct ReminderStore {
+    data: StoredReminders,
+}
+
+impl ReminderStore {
+    /// Load the store from disk (empty if missing)
+    pub fn load() -> Self {
+        let data = storage::load_json(REMINDERS_FILE).unwrap_or_default();
+        Self { data }
+    }
+
+    fn save(&self) {
+        storage::save_json(REMINDERS_FILE, &self.data);
+    }
+
+    fn push(&mut self, text: String, due: Option<DateTime<Local>>) -> u32 {
+        self.data.next_id += 1;
+        let id = self.data.next_id;
+        self.data.items.push(Reminder {
+            id,
+            text,
+            due,
+            done: false,
+            notified: false,
+        });
+        self.save();
+        id
+    }
+
+    /// Add a reminder due at the given time
+    pub fn add_reminder(&mut self, text: String, due: DateTime<Local>) -> u32 {
+        self.push(text, Some(due))
+    }
+
+    /// Add a todo item without a due time
+    pub fn add_todo(&mut self, text: String) -> u32 {
+        self.push(text, None)
+    }
+
+    /// Mark an item as done, returns false if not found
+    pub fn complete(&mut self, id: u32) -> bool {
+        let Some(item) = self.data.items.iter_mut().find(|r| r.id == id) else {
+            return false;
+        };
+        item.done = true;
+        self.save();
+        true
+    }
+
+    /// Remove an item, returns false if not found
+    pub fn remove(&mut self, id: u32) -> bool {
+        let len = self.data.items.len();
+        self.data.items.retain(|r| r.id != id);
+        if self.data.items.len() == len {
+            return false;
+        }
+        self.save();
+        true
+    }
+
+    /// Items that are not done yet
+    pub fn pending(&self) -> impl Iterator<Item = &Reminder> {
+        self.data.items.iter().filter(|r| !r.done)
+    }
+
+    /// Return reminders that became due and mark them as announced
+    pub fn take_due(&mut self) -> Vec<Reminder> {
+        let now = Local::now();
+        let mut due = Vec::new();
+
+        for item in &mut self.data.items {
+            let is_due = item.due.is_some_and(|t| t <= now);
+            if is_due && !item.done && !item.notified {
+                item.notified = true;
+                due.push(item.clone());
+            }
+        }
+
+        if !due.is_empty() {
+            self.save();
+        }
+        due
+    }
+}
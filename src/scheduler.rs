@@ -0,0 +1,442 @@
+//! Scheduled actions - cron-like and interval triggers from `[[schedule]]`
+//!
+//! Jobs are evaluated against the wall clock (not a monotonic timer) so that
+//! runs missed while the machine was asleep can be caught up on wake.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike};
+
+use crate::callout_app::CalloutCommand;
+use crate::config::ScheduleConfig;
+
+/// Runs older than this are considered "missed" rather than just late
+const MISSED_GRACE_SECS: i64 = 60;
+
+/// Upper bound when searching for the next cron match (a bit over 4 years, for Feb 29)
+const CRON_SEARCH_DAYS: i64 = 366 * 4 + 1;
+
+/// Longest stretch of local time skipped by a DST change
+const DST_GAP_MINUTES: i64 = 24 * 60;
+
+/// Parsed 5-field cron expression: minute hour day-of-month month day-of-week
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// Day-of-month field was `*`
+    any_day: bool,
+    /// Day-of-week field was `*`
+    any_weekday: bool,
+}
+
+impl CronExpr {
+    /// Parse a cron expression like `"*/15 9-17 * * 1-5"`
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        }
+
+        // Sunday may be written as 0 or 7
+        let mut weekdays = parse_field(fields[4], 0, 7)? as u8;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & 0x7f;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)? as u32,
+            days: parse_field(fields[2], 1, 31)? as u32,
+            months: parse_field(fields[3], 1, 12)? as u16,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    fn matches_date(&self, t: &NaiveDateTime) -> bool {
+        if self.months & (1 << t.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+
+        // Classic cron: when both day fields are restricted, either may match
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            (true, false) => weekday,
+            (false, true) => day,
+            (true, true) => true,
+        }
+    }
+
+    /// Find the first matching minute strictly after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after + Duration::days(CRON_SEARCH_DAYS);
+
+        while t <= limit {
+            if !self.matches_date(&t) {
+                // Skip to the start of the next day
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                // Skip to the start of the next hour
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+}
+
+/// Parse one cron field into a bitmask (bit N set = value N allowed)
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step '{}'", step))?;
+                if step == 0 {
+                    return Err("step must be greater than zero".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, min, max)?, parse_value(b, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // "5/10" means "from 5 to max, every 10"
+            if step > 1 {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+
+        if start > end {
+            return Err(format!("invalid range '{}'", range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(s: &str, min: u32, max: u32) -> Result<u32, String> {
+    let value: u32 = s.parse().map_err(|_| format!("invalid value '{}'", s))?;
+    if value < min || value > max {
+        return Err(format!("value {} out of range {}-{}", value, min, max));
+    }
+    Ok(value)
+}
+
+/// Parse an interval like `"90s"`, `"15m"`, `"2h"` or `"1d"` (bare numbers are seconds)
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let value: i64 = number
+        .parse()
+        .map_err(|_| format!("invalid interval '{}'", s))?;
+
+    let duration = match unit.trim() {
+        "" | "s" | "sec" | "secs" => Duration::try_seconds(value),
+        "m" | "min" | "mins" => Duration::try_minutes(value),
+        "h" | "hr" | "hrs" => Duration::try_hours(value),
+        "d" | "day" | "days" => Duration::try_days(value),
+        other => return Err(format!("unknown interval unit '{}'", other)),
+    }
+    .ok_or_else(|| format!("interval '{}' is too long", s))?;
+
+    if duration <= Duration::zero() {
+        return Err("interval must be greater than zero".to_string());
+    }
+    Ok(duration)
+}
+
+/// When a job fires
+#[derive(Debug, Clone)]
+enum Trigger {
+    Cron(CronExpr),
+    Interval(Duration),
+}
+
+/// What a job does when it fires
+#[derive(Debug, Clone, Default)]
+pub struct ScheduledAction {
+    /// Callout to show
+    pub callout: Option<CalloutCommand>,
+    /// Animation state to switch to
+    pub state: Option<String>,
+    /// Shell command to run
+    pub script: Option<String>,
+    /// Announce the weather forecast
+    pub weather: bool,
+}
+
+impl ScheduledAction {
+    fn from_config(config: &ScheduleConfig) -> Self {
+        let callout = config
+            .text
+            .as_ref()
+            .map(|text| CalloutCommand::from_kind(&config.callout, text.clone()));
+
+        Self {
+            callout,
+            state: config.state.clone(),
+            script: config.script.clone(),
+            weather: config.weather,
+        }
+    }
+
+    /// Run the script (if any) without waiting for it to finish
+    pub fn spawn_script(&self) {
+        let Some(ref script) = self.script else { return };
+
+        #[cfg(target_os = "windows")]
+        let result = std::process::Command::new("cmd").args(["/C", script]).spawn();
+        #[cfg(not(target_os = "windows"))]
+        let result = std::process::Command::new("sh").args(["-c", script]).spawn();
+
+        match result {
+            Ok(_) => log::info!("Scheduled script started: {}", script),
+            Err(e) => log::error!("Failed to run scheduled script '{}': {}", script, e),
+        }
+    }
+}
+
+/// A single scheduled job
+struct Job {
+    name: String,
+    trigger: Trigger,
+    action: ScheduledAction,
+    catch_up: bool,
+    next: Option<DateTime<Local>>,
+}
+
+impl Job {
+    fn next_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        match &self.trigger {
+            Trigger::Interval(interval) => after.clone().checked_add_signed(*interval),
+            Trigger::Cron(cron) => {
+                let t = cron.next_after(after.naive_local())?;
+                let timezone = after.timezone();
+                // A time skipped by a DST change runs at the first minute that exists
+                (0..=DST_GAP_MINUTES).find_map(|minutes| {
+                    let time = timezone.from_local_datetime(&(t + Duration::minutes(minutes)));
+                    // In an hour repeated by a DST change, the time may have
+                    // passed the first time round already
+                    [time.clone().earliest(), time.latest()].into_iter().flatten().find(|time| *time > after)
+                })
+            }
+        }
+    }
+}
+
+/// Evaluates scheduled jobs against the wall clock
+pub struct Scheduler {
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    /// Build the scheduler from config entries, skipping invalid ones
+    pub fn from_config(entries: &[ScheduleConfig]) -> Self {
+        let now = Local::now();
+        let mut jobs = Vec::new();
+
+        for (i, entry) in entries.iter().enumerate() {
+            let name = entry.name.clone().unwrap_or_else(|| format!("schedule #{}", i + 1));
+
+            let trigger = match (&entry.cron, &entry.every) {
+                (Some(cron), None) => CronExpr::parse(cron).map(Trigger::Cron),
+                (None, Some(every)) => parse_interval(every).map(Trigger::Interval),
+                (Some(_), Some(_)) => Err("set either 'cron' or 'every', not both".to_string()),
+                (None, None) => Err("missing 'cron' or 'every'".to_string()),
+            };
+
+            let trigger = match trigger {
+                Ok(trigger) => trigger,
+                Err(e) => {
+                    log::error!("Invalid {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let mut job = Job {
+                name,
+                trigger,
+                action: ScheduledAction::from_config(entry),
+                catch_up: entry.catch_up,
+                next: None,
+            };
+            job.next = job.next_after(now);
+            log::info!("Scheduled '{}', next run: {:?}", job.name, job.next);
+            jobs.push(job);
+        }
+
+        Self { jobs }
+    }
+
+    /// Check whether any jobs are due and return their actions.
+    ///
+    /// A job that missed several runs (e.g. while the system was asleep)
+    /// fires at most once, and only if `catch_up` is enabled for it.
+    pub fn poll(&mut self) -> Vec<ScheduledAction> {
+        let now = Local::now();
+        let mut due = Vec::new();
+
+        for job in &mut self.jobs {
+            let Some(next) = job.next else { continue };
+            if now < next {
+                continue;
+            }
+
+            let missed = (now - next).num_seconds() > MISSED_GRACE_SECS;
+            if !missed || job.catch_up {
+                if missed {
+                    log::info!("Catching up missed run of '{}' (was due {})", job.name, next);
+                } else {
+                    log::debug!("Running scheduled '{}'", job.name);
+                }
+                due.push(job.action.clone());
+            } else {
+                log::info!("Skipping missed run of '{}' (was due {})", job.name, next);
+            }
+
+            // Reschedule from now so a long sleep doesn't replay every missed run
+            job.next = job.next_after(now);
+        }
+
+        due
+    }
+
+    /// Whether there are no jobs configured
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDate};
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let cron = CronExpr::parse("*/15 9-17 * * 1-5").unwrap();
+
+        // Friday 17:50 -> Monday 09:00
+        assert_eq!(cron.next_after(at(2024, 3, 1, 17, 50)), Some(at(2024, 3, 4, 9, 0)));
+        // Monday 09:00 -> 09:15
+        assert_eq!(cron.next_after(at(2024, 3, 4, 9, 0)), Some(at(2024, 3, 4, 9, 15)));
+    }
+
+    #[test]
+    fn test_cron_sunday_as_seven() {
+        let cron = CronExpr::parse("0 12 * * 7").unwrap();
+        // Saturday -> Sunday noon
+        assert_eq!(cron.next_after(at(2024, 3, 2, 8, 0)), Some(at(2024, 3, 3, 12, 0)));
+    }
+
+    #[test]
+    fn test_cron_rejects_invalid() {
+        assert!(CronExpr::parse("* * * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+    }
+
+    /// Central European time around the end of DST on 2024-10-27, when
+    /// the clock goes back from 03:00 to 02:00
+    #[derive(Debug, Clone, Copy)]
+    struct FallBack;
+
+    impl FallBack {
+        const SUMMER: i32 = 2 * 3600;
+        const WINTER: i32 = 3600;
+
+        fn offset(seconds: i32) -> FixedOffset {
+            FixedOffset::east_opt(seconds).unwrap()
+        }
+    }
+
+    impl TimeZone for FallBack {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            FallBack
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            if *local < at(2024, 10, 27, 2, 0) {
+                LocalResult::Single(Self::offset(Self::SUMMER))
+            } else if *local < at(2024, 10, 27, 3, 0) {
+                LocalResult::Ambiguous(Self::offset(Self::SUMMER), Self::offset(Self::WINTER))
+            } else {
+                LocalResult::Single(Self::offset(Self::WINTER))
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if *utc < at(2024, 10, 27, 1, 0) {
+                Self::offset(Self::SUMMER)
+            } else {
+                Self::offset(Self::WINTER)
+            }
+        }
+    }
+
+    #[test]
+    fn test_cron_after_dst_fall_back() {
+        let job = Job {
+            name: "quarter".to_string(),
+            trigger: Trigger::Cron(CronExpr::parse("*/15 * * * *").unwrap()),
+            action: ScheduledAction::default(),
+            catch_up: false,
+            next: None,
+        };
+        // 02:30 the first time round: 02:45 the first time
+        let first = FallBack.from_utc_datetime(&at(2024, 10, 27, 0, 30));
+        assert_eq!(job.next_after(first), Some(FallBack.from_utc_datetime(&at(2024, 10, 27, 0, 45))));
+        // 02:30 the second time: the first 02:45 has passed
+        let second = FallBack.from_utc_datetime(&at(2024, 10, 27, 1, 30));
+        assert_eq!(job.next_after(second), Some(FallBack.from_utc_datetime(&at(2024, 10, 27, 1, 45))));
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("90s").unwrap(), Duration::seconds(90));
+        assert_eq!(parse_interval("15m").unwrap(), Duration::minutes(15));
+        assert_eq!(parse_interval("2h").unwrap(), Duration::hours(2));
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("5 weeks").is_err());
+        assert!(parse_interval("99999999999999d").is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! Persona behavior scripts - Rhai scripts shipped with a persona
+//! (`behavior.rhai`, or the file named by `script` in its config.toml)
+//!
+//! The ghost calls each of these functions the script defines when the
+//! event happens:
+//!
+//! ```text
+//! fn on_start() {
+//!     say("Hello again!");
+//! }
+//! fn on_double_click() {
+//!     // `this` is a map kept between events
+//!     this.clicks = (this.clicks ?? 0) + 1;
+//!     if this.clicks > 3 {
+//!         scream("Hey, that tickles!");
+//!     } else {
+//!         state("happy");
+//!     }
+//! }
+//! fn on_idle(seconds) {
+//!     if seconds == 300 {
+//!         think("Anyone there?");
+//!     }
+//! }
+//! fn on_time(hour, minute) {
+//!     if hour == 12 && minute == 30 {
+//!         say("Lunch time!");
+//!     }
+//! }
+//! fn on_drop(file, extension) {
+//!     if extension == "png" || extension == "jpg" {
+//!         say(`Nice picture: ${file}`);
+//!     }
+//! }
+//! ```
+//!
+//! Events: `on_start()` when the persona is loaded, `on_double_click()`,
+//! `on_pet()`, `on_idle(seconds)` every second nobody interacts with the
+//! ghost (seconds missed while the computer slept follow a few at a time),
+//! `on_time(hour, minute)` when the clock reaches a new minute and
+//! `on_drop(file, extension)` with the dropped file's name and lowercase
+//! extension. Functions: `say`, `think`, `scream` and `whisper` with a
+//! text, `state(name)` and `move_by(dx, dy)` or `move_by(dx, dy, easing)`
+//! in pixels, easing one of `linear`, `ease-in`, `ease-out`, `ease-in-out`
+//! (default), `back`, `bounce` or `elastic`; `print` goes to the log.
+//! Scripts can't touch files or the network, and a handler that runs too
+//! long, recurses too deep or builds huge strings, arrays or maps is
+//! stopped.
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use chrono::{NaiveDateTime, Timelike};
+use ghost_ui::Easing;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, FuncArgs, Map, Scope, AST};
+
+use crate::config::PersonaManifest;
+
+/// Script file used when the manifest names none
+const DEFAULT_SCRIPT: &str = "behavior.rhai";
+
+/// Seconds a scripted move takes
+pub const MOVE_DURATION: f32 = 0.5;
+
+/// Most operations one event may take, so a runaway loop can't hang the ghost
+const MAX_OPERATIONS: u64 = 500_000;
+
+/// Longest string a script may build, in bytes
+const MAX_STRING_SIZE: usize = 64 * 1024;
+
+/// Most items in a script's arrays and maps (including `this`)
+const MAX_COLLECTION_SIZE: usize = 10_000;
+
+/// Deepest nesting of function calls
+const MAX_CALL_LEVELS: usize = 32;
+
+/// Deepest nesting of expressions, at the top level and inside functions
+const MAX_EXPR_DEPTHS: (usize, usize) = (64, 32);
+
+/// Most `on_idle` calls per update; seconds beyond that (e.g. after the
+/// computer slept) follow in the next updates
+const MAX_IDLE_CALLS: i64 = 10;
+
+/// Something a script asks the ghost to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    /// Callout of a config type ("say", "think", "scream" or "whisper")
+    Callout { kind: String, text: String },
+    /// Switch the animation state
+    State(String),
+    /// Move the window by this many pixels
+    Move { dx: i32, dy: i32, easing: Easing },
+}
+
+/// Events passed to a script
+#[derive(Debug, Clone, Copy)]
+pub enum ScriptEvent<'a> {
+    Start,
+    DoubleClick,
+    Pet,
+    Drop(&'a Path),
+}
+
+/// Actions the running handler asked for
+type Queue = Rc<RefCell<Vec<ScriptAction>>>;
+
+/// A compiled behavior script and its timers
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    queue: Queue,
+    /// `this` of the handlers
+    memory: Dynamic,
+    /// Seconds since the last interaction
+    idle: f32,
+    /// Last second passed to `on_idle`
+    idle_called: i64,
+    /// Hour and minute when time was last checked
+    last_minute: Option<(u32, u32)>,
+}
+
+impl Script {
+    /// Compile a script; errors name the line
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let queue = Queue::default();
+        let engine = engine(&queue);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        Ok(Self {
+            engine,
+            ast,
+            queue,
+            memory: Dynamic::from_map(Map::new()),
+            idle: 0.0,
+            idle_called: 0,
+            last_minute: None,
+        })
+    }
+
+    /// Load the script of the persona at `persona_dir`, if it has one
+    pub fn load(persona_dir: &Path, manifest: &PersonaManifest) -> Option<Self> {
+        let name = manifest.script.as_deref().unwrap_or(DEFAULT_SCRIPT);
+        let source = PersonaManifest::read_file(persona_dir, name)?;
+        match Self::parse(&source) {
+            Ok(script) => {
+                log::info!("Loaded behavior script with {} handler(s)", script.ast.iter_functions().count());
+                Some(script)
+            }
+            Err(e) => {
+                log::error!("Invalid behavior script {}: {}", persona_dir.join(name).display(), e);
+                None
+            }
+        }
+    }
+
+    /// Keep the memory and timers of the script this one replaces, e.g.
+    /// after it was edited
+    pub fn carry_over(&mut self, previous: Self) {
+        self.memory = previous.memory;
+        self.idle = previous.idle;
+        self.idle_called = previous.idle_called;
+        self.last_minute = previous.last_minute;
+    }
+
+    /// Actions the script's handler for `event` asked for
+    pub fn on_event(&mut self, event: ScriptEvent) -> Vec<ScriptAction> {
+        match event {
+            ScriptEvent::Start => self.call("on_start", ()),
+            ScriptEvent::DoubleClick => self.call("on_double_click", ()),
+            ScriptEvent::Pet => self.call("on_pet", ()),
+            ScriptEvent::Drop(path) => {
+                let file = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+                let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+                self.call("on_drop", (file, extension))
+            }
+        }
+    }
+
+    /// The user interacted with the ghost: restart the idle timer
+    pub fn note_activity(&mut self) {
+        self.idle = 0.0;
+        self.idle_called = 0;
+    }
+
+    /// Advance the timers; actions of the idle and time handlers that came
+    /// due
+    pub fn update(&mut self, delta: f32, now: NaiveDateTime) -> Vec<ScriptAction> {
+        self.idle += delta;
+        let seconds = self.idle as i64;
+        let mut actions = Vec::new();
+        // Every second once, even when an update crosses several
+        let last = seconds.min(self.idle_called + MAX_IDLE_CALLS);
+        for second in self.idle_called + 1..=last {
+            actions.extend(self.call("on_idle", (second,)));
+        }
+        self.idle_called = self.idle_called.max(last);
+
+        let minute = (now.hour(), now.minute());
+        if self.last_minute.replace(minute).is_some_and(|last| last != minute) {
+            actions.extend(self.call("on_time", (minute.0 as i64, minute.1 as i64)));
+        }
+        actions
+    }
+
+    /// Run the handler `name` if the script defines it with as many
+    /// parameters as `args` has; a failing handler is logged, keeping the
+    /// actions it asked for before it failed
+    fn call(&mut self, name: &str, args: impl FuncArgs) -> Vec<ScriptAction> {
+        let mut values = Vec::new();
+        args.parse(&mut values);
+        let defined = self.ast.iter_functions().any(|f| f.name == name && f.params.len() == values.len());
+        if !defined {
+            return Vec::new();
+        }
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut self.memory);
+        if let Err(e) = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, values) {
+            log::error!("Behavior script {} failed: {}", name, e);
+        }
+        self.queue.borrow_mut().drain(..).collect()
+    }
+}
+
+/// A sandboxed engine whose ghost functions queue actions in `queue`
+fn engine(queue: &Queue) -> Engine {
+    let mut engine = Engine::new();
+    // No `import` of other script files
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_expr_depths(MAX_EXPR_DEPTHS.0, MAX_EXPR_DEPTHS.1);
+    engine.on_print(|text| log::info!("Behavior script: {}", text));
+    engine.on_debug(|text, _, position| log::debug!("Behavior script {}: {}", position, text));
+
+    for kind in ["say", "think", "scream", "whisper"] {
+        let queue = queue.clone();
+        engine.register_fn(kind, move |text: &str| {
+            queue.borrow_mut().push(ScriptAction::Callout { kind: kind.to_string(), text: text.to_string() });
+        });
+    }
+    let state_queue = queue.clone();
+    engine.register_fn("state", move |name: &str| state_queue.borrow_mut().push(ScriptAction::State(name.to_string())));
+    let move_queue = queue.clone();
+    engine.register_fn("move_by", move |dx: i64, dy: i64| {
+        move_queue.borrow_mut().push(ScriptAction::Move { dx: pixels(dx), dy: pixels(dy), easing: Easing::EaseInOut });
+    });
+    let move_queue = queue.clone();
+    engine.register_fn("move_by", move |dx: i64, dy: i64, easing: &str| -> Result<(), Box<EvalAltResult>> {
+        let easing = Easing::from_name(easing).ok_or_else(|| format!("unknown easing '{}'", easing))?;
+        move_queue.borrow_mut().push(ScriptAction::Move { dx: pixels(dx), dy: pixels(dy), easing });
+        Ok(())
+    });
+    engine
+}
+
+fn pixels(value: i64) -> i32 {
+    value.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn say(text: &str) -> ScriptAction {
+        ScriptAction::Callout { kind: "say".to_string(), text: text.to_string() }
+    }
+
+    fn at(hour: u32, minute: u32, second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn test_handlers() {
+        let mut script = Script::parse(
+            "// greeting\nfn on_start() {\n    say(\"Hi!\");\n    state(\"happy\");\n}\n\
+             fn on_double_click() {\n  move_by(-10, 5);\n  move_by(0, -40, \"bounce\");\n}\n",
+        )
+        .unwrap();
+        assert_eq!(script.on_event(ScriptEvent::Start), [say("Hi!"), ScriptAction::State("happy".to_string())]);
+        assert_eq!(
+            script.on_event(ScriptEvent::DoubleClick),
+            [
+                ScriptAction::Move { dx: -10, dy: 5, easing: Easing::EaseInOut },
+                ScriptAction::Move { dx: 0, dy: -40, easing: Easing::Bounce },
+            ]
+        );
+        // Events without a handler do nothing
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let Err(e) = Script::parse("fn on_start() { say(\"hi\") ") else { panic!("expected a syntax error") };
+        assert!(e.contains("line 1"), "{}", e);
+
+        // A failing handler keeps what it did before failing
+        let mut script = Script::parse("fn on_start() { say(\"hi\"); move_by(1, 2, \"wobble\"); say(\"never\"); }").unwrap();
+        assert_eq!(script.on_event(ScriptEvent::Start), [say("hi")]);
+
+        // Runaway loops are stopped
+        let mut script = Script::parse("fn on_pet() { loop { } }").unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+    }
+
+    #[test]
+    fn test_limits() {
+        // A string doubling itself is stopped long before the operation cap
+        let mut script = Script::parse("fn on_pet() { let s = \"x\"; loop { s += s; } }").unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+
+        let mut script = Script::parse("fn on_pet() { this.items = []; loop { this.items.push(1); } }").unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+
+        let mut script = Script::parse("fn deeper(n) { deeper(n + 1) } fn on_pet() { deeper(0); }").unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+
+        // Other script files can't be imported
+        let path = std::env::temp_dir().join("ghost_script_import.rhai");
+        std::fs::write(&path, "export const GREETING = \"hi\";").unwrap();
+        let source = format!("fn on_pet() {{ import {:?} as m; say(m::GREETING); }}", path.with_extension(""));
+        let mut script = Script::parse(&source).unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut script = Script::parse(
+            "fn on_pet() { this.pets = (this.pets ?? 0) + 1; if this.pets == 2 { say(`pet ${this.pets} times`); } }",
+        )
+        .unwrap();
+        assert!(script.on_event(ScriptEvent::Pet).is_empty());
+        assert_eq!(script.on_event(ScriptEvent::Pet), [say("pet 2 times")]);
+    }
+
+    #[test]
+    fn test_carry_over() {
+        let mut script = Script::parse("fn on_pet() { this.pets = (this.pets ?? 0) + 1; }").unwrap();
+        script.on_event(ScriptEvent::Pet);
+        let mut edited = Script::parse("fn on_pet() { say(`${this.pets}`); }").unwrap();
+        edited.carry_over(script);
+        assert_eq!(edited.on_event(ScriptEvent::Pet), [say("1")]);
+    }
+
+    #[test]
+    fn test_drop() {
+        let mut script = Script::parse(
+            "fn on_drop(file, extension) { if extension == \"png\" { say(`Nice ${file}`); } state(\"happy\"); }",
+        )
+        .unwrap();
+        let actions = script.on_event(ScriptEvent::Drop(Path::new("/tmp/cat.PNG")));
+        assert_eq!(actions, [say("Nice cat.PNG"), ScriptAction::State("happy".to_string())]);
+        let actions = script.on_event(ScriptEvent::Drop(Path::new("notes.txt")));
+        assert_eq!(actions, [ScriptAction::State("happy".to_string())]);
+    }
+
+    #[test]
+    fn test_idle() {
+        let mut script = Script::parse("fn on_idle(seconds) { if seconds == 10 { say(\"bored\"); } }").unwrap();
+        assert!(script.update(6.0, at(9, 0, 0)).is_empty());
+        assert_eq!(script.update(4.0, at(9, 0, 4)), [say("bored")]);
+        assert!(script.update(6.0, at(9, 0, 10)).is_empty());
+        script.note_activity();
+        assert!(script.update(6.0, at(9, 0, 16)).is_empty());
+        assert_eq!(script.update(4.0, at(9, 0, 20)), [say("bored")]);
+    }
+
+    #[test]
+    fn test_idle_catches_up() {
+        let mut script = Script::parse("fn on_idle(seconds) { if seconds == 300 { say(\"bored\"); } }").unwrap();
+        // Slept through the whole stretch
+        let mut actions = script.update(3600.0, at(10, 0, 0));
+        assert!(actions.is_empty());
+        for _ in 0..40 {
+            actions.extend(script.update(0.0, at(10, 0, 0)));
+        }
+        assert_eq!(actions, [say("bored")]);
+    }
+
+    #[test]
+    fn test_time() {
+        let mut script =
+            Script::parse("fn on_time(hour, minute) { if hour == 12 && minute == 30 { say(\"lunch\"); } }").unwrap();
+        assert!(script.update(0.0, at(12, 29, 59)).is_empty());
+        assert_eq!(script.update(0.0, at(12, 30, 0)), [say("lunch")]);
+        assert!(script.update(0.0, at(12, 30, 1)).is_empty());
+        assert!(script.update(0.0, at(12, 31, 0)).is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+//! Session state - where the ghost was and how it was set up when it last
+//! exited, restored on the next launch
+//!
+//! The app and the chat window record their parts as they change; the app
+//! writes the file when the event loop ends.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::storage;
+
+/// File the session is saved to on exit
+const SESSION_FILE: &str = "session.json";
+
+/// Session being recorded, starting from the one loaded at launch
+static CURRENT: Mutex<Option<Session>> = Mutex::new(None);
+
+/// State kept from one launch to the next
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Main window position (physical pixels)
+    pub position: Option<(i32, i32)>,
+    /// Active persona folder
+    pub persona: Option<String>,
+    /// Whether the chat window was open
+    pub chat_open: bool,
+    /// Chat window size (logical pixels)
+    pub chat_size: Option<[u32; 2]>,
+    /// Sound muted from the tray
+    pub muted: bool,
+    /// Sound volume set from the tray
+    pub volume: Option<f32>,
+}
+
+impl Session {
+    /// The position, if it lies on one of the `screens` (x, y, width,
+    /// height); a monitor that was unplugged since would leave the ghost
+    /// out of sight
+    pub fn position_on(&self, mut screens: impl Iterator<Item = (i32, i32, u32, u32)>) -> Option<(i32, i32)> {
+        let (x, y) = self.position?;
+        screens
+            .any(|(sx, sy, width, height)| {
+                x >= sx && y >= sy && x < sx + width as i32 && y < sy + height as i32
+            })
+            .then_some((x, y))
+    }
+}
+
+/// Load the session saved by the last exit, which later updates build on
+pub fn load() -> Session {
+    let session: Session = storage::load_json(SESSION_FILE).unwrap_or_default();
+    if let Ok(mut current) = CURRENT.lock() {
+        *current = Some(session.clone());
+    }
+    session
+}
+
+/// Change the session being recorded
+pub fn update(change: impl FnOnce(&mut Session)) {
+    if let Ok(mut current) = CURRENT.lock() {
+        change(current.get_or_insert_with(Session::default));
+    }
+}
+
+/// Write the session being recorded for the next launch
+pub fn save() {
+    let session = CURRENT.lock().ok().and_then(|s| s.clone());
+    if let Some(ref session) = session {
+        storage::save_json(SESSION_FILE, session);
+        log::info!("Saved session");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_on_screens() {
+        let screens = [(0, 0, 1920, 1080), (1920, 0, 1280, 1024)];
+        let at = |position| Session { position: Some(position), ..Default::default() };
+
+        assert_eq!(at((100, 200)).position_on(screens.into_iter()), Some((100, 200)));
+        assert_eq!(at((2000, 500)).position_on(screens.into_iter()), Some((2000, 500)));
+        // Below the shorter second monitor, or on one that is gone
+        assert_eq!(at((2000, 1050)).position_on(screens.into_iter()), None);
+        assert_eq!(at((-800, 0)).position_on(screens.into_iter()), None);
+        assert_eq!(Session::default().position_on(screens.into_iter()), None);
+    }
+
+    #[test]
+    fn test_missing_fields_default() {
+        let session: Session = serde_json::from_str(r#"{"muted": true}"#).unwrap();
+        assert!(session.muted);
+        assert_eq!(session.position, None);
+        assert!(!session.chat_open);
+    }
+}
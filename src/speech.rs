@@ -0,0 +1,138 @@
+//! Text-to-speech - speaks "say" callouts aloud
+//!
+//! Uses the platform's own voice (`say` on macOS, System.Speech through
+//! PowerShell on Windows, `espeak-ng` elsewhere) or any engine given as
+//! `[speech] command`, which gets the text on stdin. A new callout cuts off
+//! the one being spoken.
+
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+use crate::config::SpeechConfig;
+
+/// Speaks text on a background thread
+pub struct Speaker {
+    sender: Sender<Option<String>>,
+    /// Speaking rate in words per minute
+    rate: f32,
+}
+
+impl Speaker {
+    /// None when speech is disabled
+    pub fn start(config: &SpeechConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let (sender, receiver) = channel::<Option<String>>();
+        let rate = config.rate.max(1.0);
+        let config = config.clone();
+        std::thread::spawn(move || {
+            let mut speaking: Option<Child> = None;
+            for text in receiver {
+                if let Some(mut child) = speaking.take() {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                }
+                let Some(text) = text else { continue };
+                match spawn_engine(&config, &text) {
+                    Ok(child) => speaking = Some(child),
+                    Err(e) => log::error!("Text-to-speech failed: {}", e),
+                }
+            }
+        });
+        log::info!("Text-to-speech enabled");
+        Some(Self { sender, rate })
+    }
+
+    /// Speak the text, cutting off anything still being spoken
+    pub fn speak(&self, text: &str) {
+        if let Err(e) = self.sender.send(Some(text.to_string())) {
+            log::error!("Failed to send text to speak: {}", e);
+        }
+    }
+
+    /// Stop speaking
+    pub fn stop(&self) {
+        let _ = self.sender.send(None);
+    }
+
+    /// Roughly how long speaking the text takes
+    pub fn estimate(&self, text: &str) -> Duration {
+        speech_duration(text, self.rate)
+    }
+}
+
+/// Speaking time at `rate` words per minute
+pub fn speech_duration(text: &str, rate: f32) -> Duration {
+    let words = text.split_whitespace().count();
+    Duration::from_secs_f32(words as f32 * 60.0 / rate.max(1.0))
+}
+
+/// Start the speech engine with the text on stdin
+fn spawn_engine(config: &SpeechConfig, text: &str) -> std::io::Result<Child> {
+    let mut command = match config.command.split_first() {
+        Some((program, args)) => {
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        None => native_command(config),
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child)
+}
+
+/// The platform's speech command, reading the text from stdin
+fn native_command(config: &SpeechConfig) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        command.args(["-r", &config.rate.round().to_string()]);
+        if let Some(ref voice) = config.voice {
+            command.args(["-v", voice]);
+        }
+        command
+    } else if cfg!(target_os = "windows") {
+        // SAPI rate is -10..10, 0 being about 180 words per minute
+        let rate = ((config.rate - 180.0) / 20.0).round().clamp(-10.0, 10.0);
+        let voice = match config.voice {
+            Some(ref voice) => format!("$s.SelectVoice('{}');", voice.replace('\'', "''")),
+            None => String::new(),
+        };
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; {} $s.Rate = {}; \
+             $s.Speak([Console]::In.ReadToEnd())",
+            voice, rate
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    } else {
+        let mut command = Command::new("espeak-ng");
+        command.args(["--stdin", "-s", &config.rate.round().to_string()]);
+        if let Some(ref voice) = config.voice {
+            command.args(["-v", voice]);
+        }
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speech_duration() {
+        assert_eq!(speech_duration("one two three", 180.0), Duration::from_secs(1));
+        assert_eq!(speech_duration("", 180.0), Duration::ZERO);
+    }
+}
@@ -0,0 +1,51 @@
+//! Persistent storage - JSON files in the per-user data directory
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Directory for persisted ghost data (e.g. `~/.local/share/ghost`)
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("ghost")
+}
+
+/// Path of a file inside the data directory (may include subdirectories)
+pub fn data_path(file_name: &str) -> PathBuf {
+    data_dir().join(file_name)
+}
+
+/// Load a JSON file, returning `None` if it is missing or invalid
+pub fn load_json<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = data_path(file_name);
+    let content = std::fs::read_to_string(&path).ok()?;
+
+    match serde_json::from_str(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::error!("Failed to parse {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Save a value as pretty JSON, writing to a temp file first so a crash can't truncate it
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) {
+    let path = data_path(file_name);
+    let dir = path.parent().map_or_else(data_dir, Path::to_path_buf);
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| {
+            serde_json::to_string_pretty(value)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+        })
+        .and_then(|json| {
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(&tmp, &path)
+        });
+
+    if let Err(e) = result {
+        log::error!("Failed to save {}: {}", path.display(), e);
+    }
+}
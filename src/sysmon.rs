@@ -0,0 +1,240 @@
+//! System monitor - CPU, memory, battery and network metrics with configurable
+//! reactions
+
+use std::time::{Duration, Instant};
+
+use sysinfo::{Networks, System};
+
+use crate::config::{MonitorConfig, ReactionConfig};
+
+/// Latest system metrics
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    /// Global CPU usage in percent
+    pub cpu: f32,
+    /// Memory usage in percent
+    pub memory: f32,
+    /// Battery charge in percent (None without a battery)
+    pub battery: Option<f32>,
+    /// Whether the battery is charging
+    pub charging: bool,
+    /// Received KB per second over all interfaces
+    pub download: f32,
+    /// Sent KB per second over all interfaces
+    pub upload: f32,
+}
+
+impl Metrics {
+    /// Value of a metric by config name
+    fn get(&self, metric: &str) -> Option<f32> {
+        match metric {
+            "cpu" => Some(self.cpu),
+            "memory" | "ram" => Some(self.memory),
+            "battery" => self.battery,
+            "download" => Some(self.download),
+            "upload" => Some(self.upload),
+            "network" => Some(self.download + self.upload),
+            _ => None,
+        }
+    }
+}
+
+/// Config names of the metrics
+const METRICS: [&str; 7] = ["cpu", "memory", "ram", "battery", "download", "upload", "network"];
+
+/// KB per second for `bytes` transferred over `seconds`
+fn kilobytes_per_second(bytes: u64, seconds: f32) -> f32 {
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    bytes as f32 / 1024.0 / seconds
+}
+
+/// What to do when a reaction starts or stops
+#[derive(Debug, Clone)]
+pub enum ReactionEvent {
+    /// Condition became true
+    Triggered(ReactionConfig),
+    /// Condition cleared - restore this state if one was set
+    Cleared(ReactionConfig),
+}
+
+struct Reaction {
+    config: ReactionConfig,
+    /// Minimum time between two triggers
+    cooldown: Duration,
+    /// Condition held on the last poll
+    active: bool,
+    /// Triggered for the current activation (not suppressed by cooldown)
+    fired: bool,
+    last_fired: Option<Instant>,
+}
+
+impl Reaction {
+    /// Whether the reaction condition holds for the given metrics
+    fn condition(&self, metrics: &Metrics) -> bool {
+        // A low battery isn't worrying while it's charging
+        if self.config.metric == "battery" && metrics.charging {
+            return false;
+        }
+
+        let Some(value) = metrics.get(&self.config.metric) else { return false };
+        self.config.below.is_some_and(|limit| value < limit)
+            || self.config.above.is_some_and(|limit| value > limit)
+    }
+}
+
+/// Polls system metrics at a fixed interval
+pub struct SystemMonitor {
+    system: System,
+    battery: Option<starship_battery::Manager>,
+    networks: Networks,
+    /// When network counters were last read
+    last_refresh: Option<Instant>,
+    interval: f32,
+    timer: f32,
+    metrics: Metrics,
+    reactions: Vec<Reaction>,
+}
+
+impl SystemMonitor {
+    pub fn new(config: &MonitorConfig) -> Self {
+        let battery = starship_battery::Manager::new()
+            .map_err(|e| log::warn!("Battery info unavailable: {}", e))
+            .ok();
+
+        let reactions = config
+            .reactions
+            .iter()
+            .filter(|r| {
+                let valid = METRICS.contains(&r.metric.as_str());
+                if !valid {
+                    log::error!("Unknown monitor metric '{}'", r.metric);
+                }
+                valid
+            })
+            .filter_map(|r| {
+                let Ok(cooldown) = Duration::try_from_secs_f32(r.cooldown.max(0.0)) else {
+                    log::error!("Invalid cooldown {} for the '{}' reaction", r.cooldown, r.metric);
+                    return None;
+                };
+                Some(Reaction {
+                    config: r.clone(),
+                    cooldown,
+                    active: false,
+                    fired: false,
+                    last_fired: None,
+                })
+            })
+            .collect();
+
+        Self {
+            system: System::new(),
+            battery,
+            networks: Networks::new_with_refreshed_list(),
+            last_refresh: None,
+            interval: config.interval.max(1.0),
+            // Poll on the first update
+            timer: 0.0,
+            metrics: Metrics::default(),
+            reactions,
+        }
+    }
+
+    /// Advance the poll timer; returns reaction events when metrics were refreshed
+    pub fn update(&mut self, delta: f32) -> Option<Vec<ReactionEvent>> {
+        self.timer -= delta;
+        if self.timer > 0.0 {
+            return None;
+        }
+        self.timer = self.interval;
+
+        self.refresh();
+        Some(self.evaluate())
+    }
+
+    /// Latest metrics
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    fn refresh(&mut self) {
+        self.system.refresh_cpu();
+        self.system.refresh_memory();
+
+        self.metrics.cpu = self.system.global_cpu_info().cpu_usage();
+        let total = self.system.total_memory();
+        self.metrics.memory = if total > 0 {
+            self.system.used_memory() as f32 / total as f32 * 100.0
+        } else {
+            0.0
+        };
+
+        // Counters hold the bytes since the previous refresh
+        self.networks.refresh();
+        let now = Instant::now();
+        let elapsed = self.last_refresh.replace(now).map_or(0.0, |last| (now - last).as_secs_f32());
+        let (received, sent) = self
+            .networks
+            .iter()
+            .fold((0, 0), |(received, sent), (_, data)| (received + data.received(), sent + data.transmitted()));
+        self.metrics.download = kilobytes_per_second(received, elapsed);
+        self.metrics.upload = kilobytes_per_second(sent, elapsed);
+
+        self.metrics.battery = None;
+        self.metrics.charging = false;
+        if let Some(ref manager) = self.battery {
+            // Use the first battery found
+            if let Some(Ok(battery)) = manager.batteries().ok().and_then(|mut b| b.next()) {
+                self.metrics.battery = Some(battery.state_of_charge().value * 100.0);
+                self.metrics.charging = battery.state() == starship_battery::State::Charging;
+            }
+        }
+    }
+
+    fn evaluate(&mut self) -> Vec<ReactionEvent> {
+        let mut events = Vec::new();
+        let now = Instant::now();
+
+        for reaction in &mut self.reactions {
+            let condition = reaction.condition(&self.metrics);
+
+            if condition && !reaction.active {
+                let cooled_down = reaction.last_fired.is_none_or(|t| now - t >= reaction.cooldown);
+                reaction.active = true;
+                reaction.fired = cooled_down;
+                if cooled_down {
+                    reaction.last_fired = Some(now);
+                    events.push(ReactionEvent::Triggered(reaction.config.clone()));
+                }
+            } else if !condition && reaction.active {
+                reaction.active = false;
+                if reaction.fired {
+                    events.push(ReactionEvent::Cleared(reaction.config.clone()));
+                }
+            }
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_names() {
+        let metrics = Metrics { cpu: 50.0, download: 30.0, upload: 10.0, ..Default::default() };
+        assert_eq!(metrics.get("cpu"), Some(50.0));
+        assert_eq!(metrics.get("network"), Some(40.0));
+        assert_eq!(metrics.get("battery"), None);
+        assert!(METRICS.iter().filter(|m| **m != "battery").all(|m| metrics.get(m).is_some()));
+    }
+
+    #[test]
+    fn test_kilobytes_per_second() {
+        assert_eq!(kilobytes_per_second(10 * 1024, 2.0), 5.0);
+        assert_eq!(kilobytes_per_second(4096, 0.0), 0.0);
+    }
+}
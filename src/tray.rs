@@ -1,101 +1,313 @@
+use ghost_ui::{AppIcon, TrayMenuBuilder, TrayMenuHandle};
 use std::path::Path;
-use tray_icon::menu::{MenuEvent, MenuId};
-use tray_icon::{
-    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    TrayIcon, TrayIconBuilder,
-};
-
-/// Menu item IDs for handling events
-pub struct MenuIds {
-    pub open_chat: MenuId,
-    pub talk: MenuId,
-    pub idle: MenuId,
-    pub quit: MenuId,
+use tray_icon::TrayIconBuilder;
+
+use crate::audio::VOLUME_STEPS;
+
+/// Frames per second of the busy pulse
+const PULSE_FPS: f32 = 8.0;
+
+/// Longest recent chat label shown in the menu (characters)
+const RECENT_CHAT_LABEL_LEN: usize = 32;
+
+/// Menu id prefix of persona items, followed by the persona name
+const PERSONA_PREFIX: &str = "persona:";
+
+/// Menu id prefix of volume items, followed by the index in `VOLUME_STEPS`
+const VOLUME_PREFIX: &str = "volume:";
+
+/// Menu id prefix of recent chat items, followed by the index in
+/// `TrayState::recent_chats`
+const RECENT_PREFIX: &str = "recent:";
+
+/// State reflected by the tray menu (checkmarks and dynamic lists)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayState {
+    pub pomodoro_running: bool,
+    pub look_at: bool,
+    /// Available persona names
+    pub personas: Vec<String>,
+    pub active_persona: Option<String>,
+    /// Recent chat messages, newest first
+    pub recent_chats: Vec<String>,
+    /// Offer the debug console
+    pub debug: bool,
+    /// Offer the persona gallery
+    pub gallery: bool,
+    /// Sound volume (0.0 - 1.0), None without sound
+    pub volume: Option<f32>,
+    pub muted: bool,
+    /// The ghost window is hidden
+    pub hidden: bool,
+    /// There is an assistant reply to copy
+    pub has_reply: bool,
+    /// The ghost starts at login
+    pub autostart: bool,
+}
+
+impl TrayState {
+    /// Whether the menu for `other` has the same items as for this state,
+    /// so only labels, checkmarks and greyed out items differ
+    fn same_items(&self, other: &TrayState) -> bool {
+        self.personas == other.personas
+            && self.recent_chats == other.recent_chats
+            && self.debug == other.debug
+            && self.gallery == other.gallery
+            && self.volume.is_some() == other.volume.is_some()
+    }
 }
 
 pub struct TrayComponents {
-    /// Keep tray icon alive (dropping it removes the icon)
-    #[allow(dead_code)]
-    pub tray_icon: TrayIcon,
-    pub menu_ids: MenuIds,
+    /// Keeps the tray icon alive (dropping it removes the icon) and animates it
+    icon: AppIcon,
+    menu: TrayMenuHandle,
+    /// State the current menu shows
+    state: TrayState,
+}
+
+impl TrayComponents {
+    /// Bring the menu up to date with the state: rebuilt when its items
+    /// changed, otherwise updated in place
+    pub fn rebuild(&mut self, state: &TrayState) {
+        if *state == self.state {
+            return;
+        }
+
+        if state.same_items(&self.state) {
+            update_menu(&self.menu, state);
+        } else {
+            self.menu = build_menu(state).build();
+            self.icon.set_menu(&self.menu);
+            log::debug!("Tray menu rebuilt");
+        }
+        self.state = state.clone();
+    }
+
+    /// Command of the menu item with `id` (from `GhostEvent::TrayMenuSelected`)
+    pub fn command(&self, id: &str) -> Option<TrayCommand> {
+        command(&self.state, id)
+    }
+
+    /// Pulse the icon while busy, and badge it and the dock icon with the
+    /// unread count
+    pub fn update_icon(&mut self, delta: f32, busy: bool, unread: u32) {
+        if busy {
+            self.icon.start_pulse(PULSE_FPS);
+        } else {
+            self.icon.stop_animation();
+        }
+        self.icon.set_badge(unread);
+        self.icon.update(delta);
+    }
 }
 
 /// Commands that can be sent from tray menu
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TrayCommand {
     OpenChat,
     SetState(String), // "idle", "talk", etc.
+    TogglePomodoro,
+    ShowReminders,
+    ToggleLookAt,
+    SetPersona(String),
+    ResendChat(String),
+    ShowDebug,
+    ShowGallery,
+    ToggleMute,
+    SetVolume(f32),
+    /// Start or stop starting the ghost at login
+    ToggleAutostart,
+    /// Show (true) or hide (false) the ghost window
+    SetVisible(bool),
+    /// Run the action of the button with this id
+    RunAction(String),
+    /// Ask the chat backend about the clipboard text
+    ExplainClipboard,
+    /// Copy the last assistant reply to the clipboard
+    CopyReply,
     Quit,
 }
 
-pub fn setup_tray(icon_path: &str) -> TrayComponents {
-    let tray_menu = Menu::new();
-
-    // 1. Create a Submenu for animation states
-    let state_submenu = Submenu::new("State", true);
-    let talk_item = MenuItem::new("Talk", true, None);
-    let idle_item = MenuItem::new("Idle", true, None);
+pub fn setup_tray(icon_path: &str, state: &TrayState) -> TrayComponents {
+    let menu = build_menu(state).build();
 
-    // Store IDs before moving items
-    let talk_id = talk_item.id().clone();
-    let idle_id = idle_item.id().clone();
-
-    state_submenu
-        .append_items(&[&talk_item, &idle_item])
-        .unwrap();
-
-    // 2. Main Menu Items
-    let open_chat_item = MenuItem::new("Open Chat Window", true, None);
-    let quit_item = MenuItem::new("Quit", true, None);
-
-    let open_chat_id = open_chat_item.id().clone();
-    let quit_id = quit_item.id().clone();
-
-    // 3. Assemble everything into the main menu
-    tray_menu
-        .append_items(&[
-            &open_chat_item,
-            &PredefinedMenuItem::separator(),
-            &state_submenu,
-            &PredefinedMenuItem::separator(),
-            &quit_item,
-        ])
-        .unwrap();
-
-    // 4. Build the Icon
+    // Build the Icon
     let icon = load_icon(Path::new(icon_path));
 
     let tray_icon = TrayIconBuilder::new()
-        .with_menu(Box::new(tray_menu))
+        .with_menu(Box::new(menu.menu().clone()))
         .with_tooltip("Ghost")
         .with_icon(icon)
         .build()
         .unwrap();
 
-    let menu_ids = MenuIds {
-        open_chat: open_chat_id,
-        talk: talk_id,
-        idle: idle_id,
-        quit: quit_id,
-    };
+    let mut icon = AppIcon::from_path(icon_path).expect("Failed to open icon path");
+    icon.set_tray(tray_icon);
+    // The dock icon (macOS) carries the same unread badge
+    if let Err(e) = icon.setup_dock() {
+        log::warn!("Could not set dock icon: {}", e);
+    }
+
+    TrayComponents {
+        icon,
+        menu,
+        state: state.clone(),
+    }
+}
+
+/// Tray menu for the given state
+fn build_menu(state: &TrayState) -> TrayMenuBuilder {
+    // Animation states
+    let state_menu = TrayMenuBuilder::new().item("talk", "Talk").item("idle", "Idle");
+
+    // Personas, the active one checked
+    let mut persona_menu = TrayMenuBuilder::new();
+    for name in &state.personas {
+        let checked = state.active_persona.as_ref() == Some(name);
+        persona_menu = persona_menu.check_item(format!("{}{}", PERSONA_PREFIX, name), name, checked);
+    }
+    if state.gallery {
+        if !state.personas.is_empty() {
+            persona_menu = persona_menu.separator();
+        }
+        persona_menu = persona_menu.item("gallery", "Browse Personas…");
+    }
+    let has_personas = !persona_menu.is_empty();
+
+    // Recent chats (clicking resends the message)
+    let mut recent_menu = TrayMenuBuilder::new();
+    for (i, message) in state.recent_chats.iter().enumerate() {
+        recent_menu = recent_menu.item(format!("{}{}", RECENT_PREFIX, i), chat_label(message));
+    }
+    let has_recent = !recent_menu.is_empty();
+
+    let mut menu = TrayMenuBuilder::new()
+        .item("chat", "Open Chat Window")
+        .submenu("recent_chats", "Recent Chats", recent_menu)
+        .enabled(has_recent)
+        .item("explain_clipboard", "Explain Clipboard")
+        .item("copy_reply", "Copy Last Reply")
+        .enabled(state.has_reply)
+        .check_item("pomodoro", "Pomodoro", state.pomodoro_running)
+        .item("reminders", "Reminders")
+        .check_item("look_at", "Follow Cursor", state.look_at)
+        .item("visibility", visibility_label(state.hidden))
+        .separator()
+        .submenu("states", "State", state_menu)
+        .submenu("personas", "Persona", persona_menu)
+        .enabled(has_personas)
+        .separator();
+
+    // Sound: mute and volume steps
+    if let Some(volume) = state.volume {
+        let mut sound_menu = TrayMenuBuilder::new().check_item("mute", "Mute", state.muted).separator();
+        for (i, step) in VOLUME_STEPS.into_iter().enumerate() {
+            let label = format!("{}%", (step * 100.0).round());
+            sound_menu = sound_menu
+                .check_item(format!("{}{}", VOLUME_PREFIX, i), label, is_volume(step, volume))
+                .enabled(!state.muted);
+        }
+        menu = menu.submenu("sound", "Sound", sound_menu);
+    }
 
-    TrayComponents { tray_icon, menu_ids }
+    if state.debug {
+        menu = menu.item("debug", "Debug Console");
+    }
+    menu.check_item("autostart", "Start at Login", state.autostart).item("quit", "Quit")
 }
 
-/// Check for menu events and return command if any
-pub fn poll_menu_event(menu_ids: &MenuIds) -> Option<TrayCommand> {
-    if let Ok(event) = MenuEvent::receiver().try_recv() {
-        if event.id == menu_ids.open_chat {
-            return Some(TrayCommand::OpenChat);
-        } else if event.id == menu_ids.talk {
-            return Some(TrayCommand::SetState("talk".to_string()));
-        } else if event.id == menu_ids.idle {
-            return Some(TrayCommand::SetState("idle".to_string()));
-        } else if event.id == menu_ids.quit {
-            return Some(TrayCommand::Quit);
+/// Update labels, checkmarks and greyed out items of a menu built for a
+/// state with the same items
+fn update_menu(menu: &TrayMenuHandle, state: &TrayState) {
+    for name in &state.personas {
+        let checked = state.active_persona.as_ref() == Some(name);
+        menu.set_checked(&format!("{}{}", PERSONA_PREFIX, name), checked);
+    }
+    if let Some(volume) = state.volume {
+        menu.set_checked("mute", state.muted);
+        for (i, step) in VOLUME_STEPS.into_iter().enumerate() {
+            let id = format!("{}{}", VOLUME_PREFIX, i);
+            menu.set_checked(&id, is_volume(step, volume));
+            menu.set_enabled(&id, !state.muted);
         }
     }
-    None
+    menu.set_enabled("copy_reply", state.has_reply);
+    menu.set_checked("pomodoro", state.pomodoro_running);
+    menu.set_checked("look_at", state.look_at);
+    menu.set_label("visibility", visibility_label(state.hidden));
+    menu.set_checked("autostart", state.autostart);
+}
+
+/// Command of the menu item with `id` in the menu built for `state`
+fn command(state: &TrayState, id: &str) -> Option<TrayCommand> {
+    if let Some(name) = id.strip_prefix(PERSONA_PREFIX) {
+        return Some(TrayCommand::SetPersona(name.to_string()));
+    }
+    if let Some(i) = id.strip_prefix(VOLUME_PREFIX) {
+        let step = VOLUME_STEPS.get(i.parse::<usize>().ok()?)?;
+        return Some(TrayCommand::SetVolume(*step));
+    }
+    if let Some(i) = id.strip_prefix(RECENT_PREFIX) {
+        let message = state.recent_chats.get(i.parse::<usize>().ok()?)?;
+        return Some(TrayCommand::ResendChat(message.clone()));
+    }
+    match id {
+        "chat" => Some(TrayCommand::OpenChat),
+        "talk" => Some(TrayCommand::SetState("talk".to_string())),
+        "idle" => Some(TrayCommand::SetState("idle".to_string())),
+        "pomodoro" => Some(TrayCommand::TogglePomodoro),
+        "reminders" => Some(TrayCommand::ShowReminders),
+        "look_at" => Some(TrayCommand::ToggleLookAt),
+        "explain_clipboard" => Some(TrayCommand::ExplainClipboard),
+        "copy_reply" => Some(TrayCommand::CopyReply),
+        "visibility" => Some(TrayCommand::SetVisible(state.hidden)),
+        "autostart" => Some(TrayCommand::ToggleAutostart),
+        "debug" => Some(TrayCommand::ShowDebug),
+        "gallery" => Some(TrayCommand::ShowGallery),
+        "mute" => Some(TrayCommand::ToggleMute),
+        "quit" => Some(TrayCommand::Quit),
+        _ => None,
+    }
+}
+
+fn visibility_label(hidden: bool) -> &'static str {
+    if hidden {
+        "Show Ghost"
+    } else {
+        "Hide Ghost"
+    }
+}
+
+/// Whether the volume is (close enough to) a volume step
+fn is_volume(step: f32, volume: f32) -> bool {
+    (step - volume).abs() < 0.01
+}
+
+/// Persona folders inside `dir`, sorted by name
+pub fn scan_personas(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        log::warn!("Persona directory not found: {}", dir.display());
+        return Vec::new();
+    };
+
+    let mut personas: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    personas.sort_by_key(|name| name.to_lowercase());
+    personas
+}
+
+/// Single-line, shortened menu label for a chat message
+fn chat_label(message: &str) -> String {
+    let line = message.lines().next().unwrap_or_default();
+    if line.chars().count() > RECENT_CHAT_LABEL_LEN {
+        let short: String = line.chars().take(RECENT_CHAT_LABEL_LEN - 1).collect();
+        format!("{}…", short)
+    } else {
+        line.to_string()
+    }
 }
 
 fn load_icon(path: &Path) -> tray_icon::Icon {
@@ -104,6 +316,26 @@ fn load_icon(path: &Path) -> tray_icon::Icon {
         .into_rgba8();
     let (width, height) = image.dimensions();
     let rgba = image.into_raw();
-    
+
     tray_icon::Icon::from_rgba(rgba, width, height).expect("Failed to create icon")
-} // <-- Ensure this brace is here to close the function!
\ No newline at end of file
+} // <-- Ensure this brace is here to close the function!
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_of_dynamic_items() {
+        let state = TrayState {
+            recent_chats: vec!["hello".to_string(), "how are you?".to_string()],
+            hidden: true,
+            ..Default::default()
+        };
+        assert_eq!(command(&state, "persona:cat"), Some(TrayCommand::SetPersona("cat".to_string())));
+        assert_eq!(command(&state, "volume:0"), Some(TrayCommand::SetVolume(VOLUME_STEPS[0])));
+        assert_eq!(command(&state, "recent:1"), Some(TrayCommand::ResendChat("how are you?".to_string())));
+        assert_eq!(command(&state, "visibility"), Some(TrayCommand::SetVisible(true)));
+        assert_eq!(command(&state, "recent:2"), None);
+        assert_eq!(command(&state, "volume:x"), None);
+    }
+}
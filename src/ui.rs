@@ -23,11 +23,15 @@ pub fn create_buttons_from_config(configs: &[ButtonConfig]) -> Vec<Button> {
                 _ => ButtonStyle::default(),
             };
 
-            Button::new(id, &cfg.label)
+            let button = Button::new(id, &cfg.label)
                 .with_position(cfg.position[0], cfg.position[1])
                 .with_size(cfg.size[0], cfg.size[1])
                 .with_style(style)
-                .with_origin(Origin::BottomLeft)
+                .with_origin(Origin::BottomLeft);
+            match cfg.load_image() {
+                Some(image) => button.with_image(image),
+                None => button,
+            }
         })
         .collect()
 }
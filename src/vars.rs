@@ -0,0 +1,231 @@
+//! Ghost state variables - values exposed to text templates as `{name}`,
+//! plus the persisted mood (`{affection}`, `{energy}`, `{mood}`)
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::MoodConfig;
+use crate::storage;
+
+/// File the mood is persisted to
+const MOOD_FILE: &str = "mood.json";
+
+/// Seconds between saves while the mood drifts
+const MOOD_SAVE_INTERVAL: f32 = 60.0;
+
+/// Affection and energy, both 0-100
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Mood {
+    pub affection: f32,
+    pub energy: f32,
+}
+
+impl Default for Mood {
+    fn default() -> Self {
+        Self {
+            affection: 50.0,
+            energy: 80.0,
+        }
+    }
+}
+
+impl Mood {
+    /// Overall mood derived from affection and energy
+    pub fn kind(&self) -> MoodKind {
+        if self.energy < 25.0 {
+            MoodKind::Tired
+        } else if self.affection >= 70.0 {
+            MoodKind::Happy
+        } else if self.affection < 30.0 {
+            MoodKind::Lonely
+        } else {
+            MoodKind::Content
+        }
+    }
+
+    fn adjust(&mut self, affection: f32, energy: f32) {
+        self.affection = (self.affection + affection).clamp(0.0, 100.0);
+        self.energy = (self.energy + energy).clamp(0.0, 100.0);
+    }
+}
+
+/// Named mood, used to pick idle states and callout phrasing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoodKind {
+    Happy,
+    Content,
+    Lonely,
+    Tired,
+}
+
+impl MoodKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Happy => "happy",
+            Self::Content => "content",
+            Self::Lonely => "lonely",
+            Self::Tired => "tired",
+        }
+    }
+}
+
+/// Ways the user can interact with the ghost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interaction {
+    Petted,
+    Chatted,
+}
+
+/// Shared variables that features publish and callout texts can reference
+#[derive(Debug, Default)]
+pub struct GhostState {
+    vars: HashMap<String, String>,
+    mood: Mood,
+    /// Seconds since the last interaction
+    ignored_for: f32,
+    /// Seconds until the drifting mood is saved
+    save_timer: f32,
+}
+
+impl GhostState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create the state with the mood saved by the last run
+    pub fn load() -> Self {
+        let mut state = Self {
+            mood: storage::load_json(MOOD_FILE).unwrap_or_default(),
+            ..Self::new()
+        };
+        state.publish_mood();
+        state
+    }
+
+    pub fn mood(&self) -> Mood {
+        self.mood
+    }
+
+    /// Record an interaction; returns true if the ghost was feeling lonely
+    pub fn interact(&mut self, interaction: Interaction, config: &MoodConfig) -> bool {
+        let was_lonely = self.mood.kind() == MoodKind::Lonely;
+        let affection = match interaction {
+            Interaction::Petted => config.pet_affection,
+            Interaction::Chatted => config.chat_affection,
+        };
+        self.mood.adjust(affection, -config.activity_cost);
+        self.ignored_for = 0.0;
+        self.save_mood();
+        was_lonely
+    }
+
+    /// Drift the mood over time: rest restores energy, being ignored costs
+    /// affection. Returns the new mood when it changed.
+    pub fn update_mood(&mut self, delta: f32, config: &MoodConfig) -> Option<MoodKind> {
+        let before = self.mood.kind();
+        self.ignored_for += delta;
+
+        let minutes = delta / 60.0;
+        let lonely = if self.ignored_for > config.ignored_after { config.lonely_rate } else { 0.0 };
+        self.mood.adjust(-lonely * minutes, config.rest_rate * minutes);
+
+        let after = self.mood.kind();
+        self.save_timer -= delta;
+        if self.save_timer <= 0.0 || after != before {
+            self.save_timer = MOOD_SAVE_INTERVAL;
+            self.save_mood();
+        }
+
+        (after != before).then_some(after)
+    }
+
+    /// Persist the mood and refresh its template variables
+    fn save_mood(&mut self) {
+        storage::save_json(MOOD_FILE, &self.mood);
+        self.publish_mood();
+    }
+
+    fn publish_mood(&mut self) {
+        self.set("affection", format!("{:.0}", self.mood.affection));
+        self.set("energy", format!("{:.0}", self.mood.energy));
+        self.set("mood", self.mood.kind().name());
+    }
+
+    /// Set a template variable
+    pub fn set(&mut self, name: &str, value: impl Into<String>) {
+        self.vars.insert(name.to_string(), value.into());
+    }
+
+    /// Value of a template variable
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
+
+    /// All template variables, sorted by name
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<_> = self.vars.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        vars.sort();
+        vars
+    }
+
+    /// Replace `{name}` placeholders with their values.
+    /// Unknown placeholders are left as-is.
+    pub fn expand(&self, template: &str) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+
+            match after.find('}') {
+                Some(end) => {
+                    let name = &after[..end];
+                    match self.vars.get(name) {
+                        Some(value) => result.push_str(value),
+                        None => {
+                            result.push('{');
+                            result.push_str(name);
+                            result.push('}');
+                        }
+                    }
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    result.push_str(&rest[start..]);
+                    rest = "";
+                }
+            }
+        }
+
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let mut state = GhostState::new();
+        state.set("battery", "15");
+
+        assert_eq!(state.expand("Battery at {battery}%!"), "Battery at 15%!");
+        assert_eq!(state.expand("{unknown} stays"), "{unknown} stays");
+        assert_eq!(state.expand("open { brace"), "open { brace");
+    }
+
+    #[test]
+    fn test_mood_kind() {
+        assert_eq!(Mood { affection: 50.0, energy: 80.0 }.kind(), MoodKind::Content);
+        assert_eq!(Mood { affection: 90.0, energy: 80.0 }.kind(), MoodKind::Happy);
+        assert_eq!(Mood { affection: 10.0, energy: 80.0 }.kind(), MoodKind::Lonely);
+        assert_eq!(Mood { affection: 90.0, energy: 10.0 }.kind(), MoodKind::Tired);
+
+        let mut mood = Mood::default();
+        mood.adjust(80.0, -100.0);
+        assert_eq!(mood, Mood { affection: 100.0, energy: 0.0 });
+    }
+}
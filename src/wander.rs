@@ -0,0 +1,158 @@
+//! Wandering - the ghost occasionally walks along the bottom edge of the screen
+//!
+//! Targets are chosen here; the window glides there through the animated
+//! move API (`MoveRequest`), and reports back with `MoveFinished`.
+
+use ghost_ui::{Easing, MoveRequest, Rng, ScreenRect};
+
+use crate::config::WanderConfig;
+
+/// Walks shorter than this are not worth starting (pixels)
+const MIN_WALK: i32 = 20;
+
+/// Picks walk targets and tracks whether a walk is in progress
+pub struct Wander {
+    config: WanderConfig,
+    /// Monitor bounds
+    screen: Option<ScreenRect>,
+    /// Window outer rect (kept up to date with `set_position`)
+    window: Option<ScreenRect>,
+    /// Seconds until the next walk
+    timer: f32,
+    /// Walking direction while a walk is in progress (true = left)
+    walking_left: Option<bool>,
+    rng: Rng,
+}
+
+impl Wander {
+    pub fn new(config: WanderConfig) -> Self {
+        let mut wander = Self {
+            config,
+            screen: None,
+            window: None,
+            timer: 0.0,
+            walking_left: None,
+            rng: Rng::new(),
+        };
+        wander.timer = wander.next_interval();
+        wander
+    }
+
+    pub fn walk_state(&self) -> &str {
+        &self.config.walk_state
+    }
+
+    /// Update the monitor and window bounds
+    pub fn set_screen(&mut self, screen: ScreenRect, window: ScreenRect) {
+        self.screen = Some(screen);
+        self.window = Some(window);
+    }
+
+    /// Track the window position (from `GhostEvent::Moved`)
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        if let Some(ref mut window) = self.window {
+            window.x = x;
+            window.y = y;
+        }
+    }
+
+    /// Direction of the current walk (true = left), None when standing
+    pub fn walking_left(&self) -> Option<bool> {
+        self.walking_left
+    }
+
+    /// The user interacted: stop walking and wait before the next walk.
+    /// Returns true if a walk was interrupted.
+    pub fn interrupt(&mut self) -> bool {
+        self.timer = self.timer.max(self.config.pause_after_interaction);
+        self.walking_left.take().is_some()
+    }
+
+    /// The current walk reached its target
+    pub fn finish(&mut self) {
+        self.walking_left = None;
+        self.timer = self.next_interval();
+    }
+
+    /// Count down to the next walk; returns the move to start
+    pub fn update(&mut self, delta: f32) -> Option<MoveRequest> {
+        if self.walking_left.is_some() {
+            return None;
+        }
+
+        self.timer -= delta;
+        if self.timer > 0.0 {
+            return None;
+        }
+        self.timer = self.next_interval();
+
+        let (screen, window) = (self.screen?, self.window?);
+        let distance = self.rng.range(self.config.max_distance / 4.0, self.config.max_distance) as i32;
+        let left = self.rng.next_u64().is_multiple_of(2);
+        let (x, y) = walk_target(screen, window, distance, left, self.config.bottom_margin)?;
+
+        let dx = (x - window.x) as f32;
+        let dy = (y - window.y) as f32;
+        let duration = (dx * dx + dy * dy).sqrt() / self.config.speed.max(1.0);
+
+        self.walking_left = Some(x < window.x);
+        Some(MoveRequest::To { x, y, duration, easing: Easing::Linear })
+    }
+
+    fn next_interval(&mut self) -> f32 {
+        let min = self.config.min_interval.max(1.0);
+        let max = self.config.max_interval.max(min);
+        self.rng.range(min, max)
+    }
+}
+
+/// Target position on the bottom edge, `distance` pixels to the left or
+/// right, turning around at the screen edges. None if there is no room.
+fn walk_target(screen: ScreenRect, window: ScreenRect, distance: i32, left: bool, margin: i32) -> Option<(i32, i32)> {
+    let min_x = screen.x;
+    let max_x = screen.x + screen.width as i32 - window.width as i32;
+    if max_x <= min_x {
+        return None;
+    }
+
+    let y = screen.y + screen.height as i32 - window.height as i32 - margin;
+    let current = window.x.clamp(min_x, max_x);
+    let step = |left: bool| {
+        let x = if left { current - distance } else { current + distance };
+        x.clamp(min_x, max_x)
+    };
+
+    // Turn around when there's no room in the chosen direction
+    let mut x = step(left);
+    if (x - current).abs() < MIN_WALK {
+        x = step(!left);
+    }
+
+    let moved = (x - window.x).abs() >= MIN_WALK || (y - window.y).abs() >= MIN_WALK;
+    moved.then_some((x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: ScreenRect = ScreenRect { x: 0, y: 0, width: 1000, height: 800 };
+
+    fn window_at(x: i32, y: i32) -> ScreenRect {
+        ScreenRect { x, y, width: 100, height: 200 }
+    }
+
+    #[test]
+    fn test_walk_target_bottom_edge() {
+        assert_eq!(walk_target(SCREEN, window_at(500, 600), 200, true, 0), Some((300, 600)));
+        assert_eq!(walk_target(SCREEN, window_at(500, 600), 200, false, 40), Some((700, 560)));
+    }
+
+    #[test]
+    fn test_walk_target_turns_at_edge() {
+        // Already at the left edge: walk right instead
+        assert_eq!(walk_target(SCREEN, window_at(0, 600), 200, true, 0), Some((200, 600)));
+        // Clamped to the right edge
+        assert_eq!(walk_target(SCREEN, window_at(850, 600), 200, false, 0), Some((900, 600)));
+    }
+}
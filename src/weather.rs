@@ -0,0 +1,182 @@
+//! Weather provider - current conditions and today's forecast from Open-Meteo
+//!
+//! Requests run on a background thread; results are picked up in `update()`.
+
+use serde::Deserialize;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::config::WeatherConfig;
+
+const API_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// Current weather and today's forecast
+#[derive(Debug, Clone)]
+pub struct Weather {
+    pub temperature: f32,
+    pub code: u32,
+    pub today_max: f32,
+    pub today_min: f32,
+    /// Max precipitation probability today in percent
+    pub rain_chance: f32,
+    /// "°C" or "°F"
+    pub unit: &'static str,
+}
+
+impl Weather {
+    /// Short summary for `{weather}`, e.g. "Partly cloudy, 18°C"
+    pub fn summary(&self) -> String {
+        format!("{}, {:.0}{}", describe_code(self.code), self.temperature, self.unit)
+    }
+
+    /// Forecast sentence for announcements
+    pub fn forecast(&self) -> String {
+        format!(
+            "Today: {}, {:.0}-{:.0}{} with a {:.0}% chance of rain.",
+            describe_code(self.code).to_lowercase(),
+            self.today_min,
+            self.today_max,
+            self.unit,
+            self.rain_chance
+        )
+    }
+}
+
+/// Describe a WMO weather code
+pub fn describe_code(code: u32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Foggy",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 | 73 | 75 | 77 => "Snow",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown weather",
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    current: ApiCurrent,
+    daily: ApiDaily,
+}
+
+#[derive(Deserialize)]
+struct ApiCurrent {
+    temperature_2m: f32,
+    weather_code: u32,
+}
+
+#[derive(Deserialize)]
+struct ApiDaily {
+    temperature_2m_max: Vec<f32>,
+    temperature_2m_min: Vec<f32>,
+    precipitation_probability_max: Vec<Option<f32>>,
+}
+
+/// Polls Open-Meteo for the configured location
+pub struct WeatherProvider {
+    config: WeatherConfig,
+    sender: Sender<Result<Weather, String>>,
+    receiver: Receiver<Result<Weather, String>>,
+    /// Seconds until the next refresh
+    refresh_timer: f32,
+    in_flight: bool,
+    current: Option<Weather>,
+}
+
+impl WeatherProvider {
+    pub fn new(config: WeatherConfig) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            config,
+            sender,
+            receiver,
+            // Fetch on the first update
+            refresh_timer: 0.0,
+            in_flight: false,
+            current: None,
+        }
+    }
+
+    /// Start a fetch now (ignored if one is already running)
+    pub fn refresh(&mut self) {
+        if self.in_flight {
+            return;
+        }
+        self.in_flight = true;
+        self.refresh_timer = self.config.refresh_minutes.max(1.0) * 60.0;
+
+        let config = self.config.clone();
+        let sender = self.sender.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(fetch(&config));
+        });
+    }
+
+    /// Advance the refresh timer; returns new weather when a fetch completed
+    pub fn update(&mut self, delta: f32) -> Option<&Weather> {
+        self.refresh_timer -= delta;
+        if self.refresh_timer <= 0.0 {
+            self.refresh();
+        }
+
+        match self.receiver.try_recv() {
+            Ok(Ok(weather)) => {
+                self.in_flight = false;
+                log::info!("Weather updated: {}", weather.summary());
+                self.current = Some(weather);
+                self.current.as_ref()
+            }
+            Ok(Err(e)) => {
+                self.in_flight = false;
+                log::warn!("Weather fetch failed: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+fn fetch(config: &WeatherConfig) -> Result<Weather, String> {
+    let fahrenheit = config.units.eq_ignore_ascii_case("fahrenheit");
+
+    let response: ApiResponse = ureq::get(API_URL)
+        .query("latitude", &config.latitude.to_string())
+        .query("longitude", &config.longitude.to_string())
+        .query("current", "temperature_2m,weather_code")
+        .query(
+            "daily",
+            "temperature_2m_max,temperature_2m_min,precipitation_probability_max",
+        )
+        .query("temperature_unit", if fahrenheit { "fahrenheit" } else { "celsius" })
+        .query("timezone", "auto")
+        .query("forecast_days", "1")
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_json()
+        .map_err(|e| e.to_string())?;
+
+    let daily = response.daily;
+    Ok(Weather {
+        temperature: response.current.temperature_2m,
+        code: response.current.weather_code,
+        today_max: daily.temperature_2m_max.first().copied().unwrap_or_default(),
+        today_min: daily.temperature_2m_min.first().copied().unwrap_or_default(),
+        rain_chance: daily
+            .precipitation_probability_max
+            .first()
+            .copied()
+            .flatten()
+            .unwrap_or_default(),
+        unit: if fahrenheit { "°F" } else { "°C" },
+    })
+}